@@ -49,10 +49,18 @@ use quiche_apps::common::*;
 
 use quiche_apps::sendto::*;
 
+use pcapng_writer::capture_datagram;
+use pcapng_writer::keylog_writer;
+use pcapng_writer::make_pcap_writer;
+use pcapng_writer::Direction;
+
 const MAX_BUF_SIZE: usize = 65507;
 
 const MAX_DATAGRAM_SIZE: usize = 1350;
 
+const PCAP_FLUSH_INTERVAL: std::time::Duration =
+    std::time::Duration::from_secs(1);
+
 fn main() {
     let mut buf = [0; MAX_BUF_SIZE];
     let mut out = [0; MAX_BUF_SIZE];
@@ -140,7 +148,19 @@ fn main() {
             .unwrap();
 
         keylog = Some(file);
+    }
+
+    // Only bother with pcap if the user specified it. All connections are
+    // captured in a single file, as they share the same socket.
+    let pcap = std::env::var_os("PCAPDIR").map(|dir| {
+        let name = format!("server-{}.pcapng", std::process::id());
+        make_pcap_writer(dir, &name, "quiche-server")
+            .unwrap_or_else(|e| panic!("{e}"))
+    });
+
+    let should_log_keys = keylog.is_some() || pcap.is_some();
 
+    if should_log_keys {
         config.log_keys();
     }
 
@@ -176,6 +196,8 @@ fn main() {
 
     let mut continue_write = false;
 
+    let mut pcap_flushed = std::time::Instant::now();
+
     let local_addr = socket.local_addr().unwrap();
 
     loop {
@@ -242,6 +264,19 @@ fn main() {
 
             pkt_count += 1;
 
+            let recv_info = quiche::RecvInfo {
+                to: local_addr,
+                from,
+            };
+
+            capture_datagram(
+                pcap.as_ref(),
+                Direction::Inbound,
+                recv_info.from,
+                recv_info.to,
+                pkt_buf,
+            );
+
             // Parse the QUIC packet's header.
             let hdr = match quiche::Header::from_slice(
                 pkt_buf,
@@ -293,6 +328,15 @@ fn main() {
 
                         panic!("send() failed: {e:?}");
                     }
+
+                    capture_datagram(
+                        pcap.as_ref(),
+                        Direction::Outbound,
+                        recv_info.to,
+                        recv_info.from,
+                        out,
+                    );
+
                     continue 'read;
                 }
 
@@ -332,6 +376,15 @@ fn main() {
 
                             panic!("send() failed: {e:?}");
                         }
+
+                        capture_datagram(
+                            pcap.as_ref(),
+                            Direction::Outbound,
+                            recv_info.to,
+                            recv_info.from,
+                            out,
+                        );
+
                         continue 'read;
                     }
 
@@ -368,10 +421,10 @@ fn main() {
                 )
                 .unwrap();
 
-                if let Some(keylog) = &mut keylog {
-                    if let Ok(keylog) = keylog.try_clone() {
-                        conn.set_keylog(Box::new(keylog));
-                    }
+                if let Some(keylog) =
+                    keylog_writer(pcap.as_ref(), keylog.as_ref())
+                {
+                    conn.set_keylog(keylog);
                 }
 
                 // Only bother with qlog if the user specified it.
@@ -419,11 +472,6 @@ fn main() {
                 clients.get_mut(cid).unwrap()
             };
 
-            let recv_info = quiche::RecvInfo {
-                to: local_addr,
-                from,
-            };
-
             // Process potentially coalesced packets.
             let read = match client.conn.recv(pkt_buf, recv_info) {
                 Ok(v) => v,
@@ -609,6 +657,18 @@ fn main() {
                 panic!("send_to() failed: {e:?}");
             }
 
+            // Packets might have been coalesced into a single GSO buffer, so
+            // capture each datagram separately.
+            for datagram in out[..total_write].chunks(client.max_datagram_size) {
+                capture_datagram(
+                    pcap.as_ref(),
+                    Direction::Outbound,
+                    dst_info.unwrap().from,
+                    dst_info.unwrap().to,
+                    datagram,
+                );
+            }
+
             trace!(
                 "{} written {total_write} bytes with {dst_info:?}",
                 client.conn.trace_id()
@@ -621,6 +681,8 @@ fn main() {
             }
         }
 
+        let mut collected = false;
+
         // Garbage collect closed connections.
         clients.retain(|_, ref mut c| {
             trace!("Collecting garbage");
@@ -637,10 +699,21 @@ fn main() {
                     let id_owned = id.clone().into_owned();
                     clients_ids.remove(&id_owned);
                 }
+
+                collected = true;
             }
 
             !c.conn.is_closed()
         });
+
+        // The server only stops when killed, so flush the capture when a
+        // connection ends and periodically, rather than on every wakeup.
+        if let Some(pcap) = &pcap {
+            if collected || pcap_flushed.elapsed() >= PCAP_FLUSH_INTERVAL {
+                pcap.lock().unwrap().flush().ok();
+                pcap_flushed = std::time::Instant::now();
+            }
+        }
     }
 }
 
@@ -35,6 +35,11 @@ use std::cell::RefCell;
 
 use ring::rand::*;
 
+use pcapng_writer::capture_datagram;
+use pcapng_writer::keylog_writer;
+use pcapng_writer::make_pcap_writer;
+use pcapng_writer::Direction;
+
 const MAX_DATAGRAM_SIZE: usize = 1350;
 
 #[derive(Debug)]
@@ -137,7 +142,13 @@ pub fn connect(
             .unwrap();
 
         keylog = Some(file);
+    }
+
+    let pcap_dir = std::env::var_os("PCAPDIR");
 
+    let should_log_keys = keylog.is_some() || pcap_dir.is_some();
+
+    if should_log_keys {
         config.log_keys();
     }
 
@@ -192,10 +203,17 @@ pub fn connect(
     )
     .unwrap();
 
-    if let Some(keylog) = &mut keylog {
-        if let Ok(keylog) = keylog.try_clone() {
-            conn.set_keylog(Box::new(keylog));
-        }
+    // Only bother with pcap if the user specified it.
+    let pcap = pcap_dir.map(|dir| {
+        let id: String =
+            scid.as_ref().iter().map(|b| format!("{b:02x}")).collect();
+        let name = format!("client-{id}.pcapng");
+        make_pcap_writer(dir, &name, "quiche-client")
+            .unwrap_or_else(|e| panic!("{e}"))
+    });
+
+    if let Some(keylog) = keylog_writer(pcap.as_ref(), keylog.as_ref()) {
+        conn.set_keylog(keylog);
     }
 
     // Only bother with qlog if the user specified it.
@@ -241,6 +259,14 @@ pub fn connect(
         return Err(ClientError::Other(format!("send() failed: {e:?}")));
     }
 
+    capture_datagram(
+        pcap.as_ref(),
+        Direction::Outbound,
+        send_info.from,
+        send_info.to,
+        &out[..write],
+    );
+
     trace!("written {write}");
 
     let app_data_start = std::time::Instant::now();
@@ -313,6 +339,14 @@ pub fn connect(
                     from,
                 };
 
+                capture_datagram(
+                    pcap.as_ref(),
+                    Direction::Inbound,
+                    recv_info.from,
+                    recv_info.to,
+                    &buf[..len],
+                );
+
                 // Process potentially coalesced packets.
                 let read = match conn.recv(&mut buf[..len], recv_info) {
                     Ok(v) => v,
@@ -535,6 +569,14 @@ pub fn connect(
                         )));
                     }
 
+                    capture_datagram(
+                        pcap.as_ref(),
+                        Direction::Outbound,
+                        send_info.from,
+                        send_info.to,
+                        &out[..write],
+                    );
+
                     trace!(
                         "written {write} bytes from {local_addr} to {}",
                         send_info.to
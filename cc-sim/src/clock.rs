@@ -0,0 +1,103 @@
+// Copyright (C) 2026, Cloudflare, Inc.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are
+// met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//
+//     * Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in the
+//       documentation and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS
+// IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO,
+// THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR
+// PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Simulated time.
+
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+
+/// A clock that only advances when the simulation tells it to.
+///
+/// Simulation time is expressed as an offset from the start of the
+/// simulation. The clock is shared with the simulated connections through
+/// [`quiche::Config::set_clock()`], which map it to an [`Instant`] relative
+/// to a fixed base.
+#[derive(Debug)]
+pub struct SimClock {
+    base: Instant,
+    elapsed_ns: AtomicU64,
+}
+
+impl SimClock {
+    pub fn new() -> Self {
+        SimClock {
+            base: Instant::now(),
+            elapsed_ns: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the current simulation time.
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_nanos(self.elapsed_ns.load(Ordering::Relaxed))
+    }
+
+    /// Moves the clock to `t`. Time never goes backwards.
+    pub fn advance_to(&self, t: Duration) {
+        self.elapsed_ns
+            .fetch_max(t.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    /// Converts an [`Instant`] returned by a connection into simulation time.
+    pub fn to_sim_time(&self, instant: Instant) -> Duration {
+        instant.saturating_duration_since(self.base)
+    }
+}
+
+impl Default for SimClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl quiche::Clock for SimClock {
+    fn now(&self) -> Instant {
+        self.base + self.elapsed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use quiche::Clock;
+
+    #[test]
+    fn advance() {
+        let clock = SimClock::new();
+        let start = clock.now();
+
+        clock.advance_to(Duration::from_millis(10));
+        assert_eq!(clock.now() - start, Duration::from_millis(10));
+
+        // Time never goes backwards.
+        clock.advance_to(Duration::from_millis(5));
+        assert_eq!(clock.elapsed(), Duration::from_millis(10));
+
+        assert_eq!(clock.to_sim_time(clock.now()), Duration::from_millis(10));
+    }
+}
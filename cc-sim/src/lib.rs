@@ -0,0 +1,118 @@
+// Copyright (C) 2026, Cloudflare, Inc.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are
+// met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//
+//     * Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in the
+//       documentation and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS
+// IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO,
+// THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR
+// PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! A deterministic network simulator for quiche congestion control.
+//!
+//! The simulator drives a pair of [`quiche::Connection`]s over a modeled
+//! bottleneck link, advancing a simulated clock from one event to the next
+//! instead of waiting for real time to pass. This makes it possible to run
+//! congestion controllers against networks with a given bandwidth, delay,
+//! queue discipline, loss and jitter, and compare results across changes.
+//!
+//! Scenarios are described by [`Scenario`] files, and each run produces a
+//! [`Summary`] and, optionally, qlog files for both endpoints.
+
+use std::fmt;
+
+pub mod clock;
+pub mod link;
+pub mod metrics;
+pub mod queue;
+pub mod rng;
+pub mod scenario;
+pub mod sim;
+
+pub use crate::metrics::Summary;
+pub use crate::scenario::Scenario;
+pub use crate::sim::run;
+pub use crate::sim::SimOptions;
+
+/// A specialized [`Result`] type for simulator operations.
+///
+/// [`Result`]: https://doc.rust-lang.org/std/result/enum.Result.html
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A simulator error.
+#[derive(Debug)]
+pub enum Error {
+    /// An I/O error, e.g. while reading a scenario file.
+    Io(std::io::Error),
+
+    /// A scenario file could not be parsed.
+    Json(serde_json::Error),
+
+    /// The scenario is not valid.
+    InvalidScenario(String),
+
+    /// A connection could not be configured or created.
+    Quiche(quiche::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "I/O error: {e}"),
+
+            Error::Json(e) => write!(f, "invalid scenario file: {e}"),
+
+            Error::InvalidScenario(e) => write!(f, "invalid scenario: {e}"),
+
+            Error::Quiche(e) => write!(f, "quiche error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+
+            Error::Json(e) => Some(e),
+
+            Error::InvalidScenario(_) => None,
+
+            Error::Quiche(e) => Some(e),
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
+
+impl From<quiche::Error> for Error {
+    fn from(err: quiche::Error) -> Self {
+        Error::Quiche(err)
+    }
+}
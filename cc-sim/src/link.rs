@@ -0,0 +1,423 @@
+// Copyright (C) 2026, Cloudflare, Inc.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are
+// met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//
+//     * Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in the
+//       documentation and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS
+// IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO,
+// THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR
+// PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! One direction of a bottleneck link.
+
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::metrics::percentile_ms;
+use crate::metrics::LinkSummary;
+use crate::queue::Packet;
+use crate::queue::Queue;
+use crate::queue::QueueConfig;
+use crate::rng::SimRng;
+
+/// The parameters of one direction of a link.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct LinkConfig {
+    /// The bottleneck bandwidth.
+    pub bandwidth_mbps: f64,
+
+    /// The one-way propagation delay.
+    #[serde(default)]
+    pub delay_ms: f64,
+
+    /// The maximum extra delay added to each packet. Jitter never reorders
+    /// packets.
+    #[serde(default)]
+    pub jitter_ms: f64,
+
+    #[serde(default)]
+    pub loss: LossConfig,
+
+    #[serde(default)]
+    pub queue: QueueConfig,
+
+    /// Changes to the link parameters over time, e.g. to model a drop in
+    /// bandwidth.
+    #[serde(default)]
+    pub changes: Vec<LinkChange>,
+}
+
+/// Random packet loss, applied before packets enter the queue.
+///
+/// Losses follow a two-state Gilbert model: all packets are lost while the
+/// link is in the "bad" state, and none otherwise. The transition
+/// probabilities are derived from the average loss rate and the mean burst
+/// length, so a mean burst of 1 packet amounts to independent losses.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct LossConfig {
+    /// The average fraction of packets lost.
+    #[serde(default)]
+    pub rate: f64,
+
+    /// The mean length of loss bursts, in packets.
+    #[serde(default = "default_mean_burst")]
+    pub mean_burst: f64,
+}
+
+fn default_mean_burst() -> f64 {
+    1.0
+}
+
+impl Default for LossConfig {
+    fn default() -> Self {
+        LossConfig {
+            rate: 0.0,
+            mean_burst: default_mean_burst(),
+        }
+    }
+}
+
+/// A change to the link parameters at a given time.
+///
+/// Parameters that are not set keep their current value.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct LinkChange {
+    pub at_ms: f64,
+
+    #[serde(default)]
+    pub bandwidth_mbps: Option<f64>,
+
+    #[serde(default)]
+    pub delay_ms: Option<f64>,
+
+    #[serde(default)]
+    pub jitter_ms: Option<f64>,
+
+    #[serde(default)]
+    pub loss: Option<LossConfig>,
+}
+
+#[derive(Debug, Default)]
+struct LinkStats {
+    packets_sent: u64,
+    bytes_sent: u64,
+    packets_delivered: u64,
+    bytes_delivered: u64,
+    random_losses: u64,
+    max_queue_bytes: usize,
+    queue_delays: Vec<Duration>,
+}
+
+/// A serializing link with a queue in front of it.
+///
+/// Packets are transmitted one at a time at the link bandwidth, and are
+/// delivered after the propagation delay plus jitter.
+#[derive(Debug)]
+pub struct Link {
+    bandwidth_bps: f64,
+    delay: Duration,
+    jitter: Duration,
+    loss: LossConfig,
+    in_loss_burst: bool,
+
+    queue: Queue,
+
+    /// The packet currently being serialized.
+    transmitting: Option<Packet>,
+
+    last_arrival: Duration,
+
+    rng: SimRng,
+
+    stats: LinkStats,
+}
+
+impl Link {
+    pub fn new(config: &LinkConfig, rng: SimRng) -> Self {
+        Link {
+            bandwidth_bps: config.bandwidth_mbps * 1_000_000.0,
+            delay: duration_from_ms(config.delay_ms),
+            jitter: duration_from_ms(config.jitter_ms),
+            loss: config.loss.clone(),
+            in_loss_burst: false,
+
+            queue: Queue::new(&config.queue),
+
+            transmitting: None,
+
+            last_arrival: Duration::ZERO,
+
+            rng,
+
+            stats: LinkStats::default(),
+        }
+    }
+
+    /// Applies a change to the link parameters.
+    ///
+    /// A packet already being serialized is not affected.
+    pub fn apply(&mut self, change: &LinkChange) {
+        if let Some(v) = change.bandwidth_mbps {
+            self.bandwidth_bps = v * 1_000_000.0;
+        }
+
+        if let Some(v) = change.delay_ms {
+            self.delay = duration_from_ms(v);
+        }
+
+        if let Some(v) = change.jitter_ms {
+            self.jitter = duration_from_ms(v);
+        }
+
+        if let Some(v) = &change.loss {
+            self.loss = v.clone();
+            self.in_loss_burst = false;
+        }
+    }
+
+    /// Offers a packet to the link at time `now`.
+    ///
+    /// Returns the time at which the packet finishes being serialized if the
+    /// link was idle, in which case [`on_tx_complete()`] must be called at
+    /// that time.
+    ///
+    /// [`on_tx_complete()`]: Link::on_tx_complete
+    pub fn send(&mut self, data: Vec<u8>, now: Duration) -> Option<Duration> {
+        self.stats.packets_sent += 1;
+        self.stats.bytes_sent += data.len() as u64;
+
+        if self.lose() {
+            self.stats.random_losses += 1;
+            return None;
+        }
+
+        let pkt = Packet {
+            data,
+            enqueued: now,
+        };
+
+        if !self.queue.enqueue(pkt, &mut self.rng) {
+            return None;
+        }
+
+        self.stats.max_queue_bytes =
+            self.stats.max_queue_bytes.max(self.queue.bytes());
+
+        if self.transmitting.is_some() {
+            return None;
+        }
+
+        self.start_tx(now)
+    }
+
+    /// Completes the transmission of the current packet.
+    ///
+    /// Returns the packet along with its arrival time at the receiver, if
+    /// any, and the time at which the transmission of the next queued packet
+    /// completes, if any.
+    pub fn on_tx_complete(
+        &mut self, now: Duration,
+    ) -> (Option<(Duration, Vec<u8>)>, Option<Duration>) {
+        let delivery = self.transmitting.take().map(|pkt| {
+            let jitter = self.jitter.mul_f64(self.rng.next_f64());
+
+            let arrival = (now + self.delay + jitter).max(self.last_arrival);
+
+            self.last_arrival = arrival;
+
+            self.stats.packets_delivered += 1;
+            self.stats.bytes_delivered += pkt.data.len() as u64;
+
+            (arrival, pkt.data)
+        });
+
+        (delivery, self.start_tx(now))
+    }
+
+    pub fn summary(&self) -> LinkSummary {
+        let mut delays = self.stats.queue_delays.clone();
+        delays.sort_unstable();
+
+        LinkSummary {
+            packets_sent: self.stats.packets_sent,
+            bytes_sent: self.stats.bytes_sent,
+            packets_delivered: self.stats.packets_delivered,
+            bytes_delivered: self.stats.bytes_delivered,
+            random_losses: self.stats.random_losses,
+            queue_drops: self.queue.drops,
+            max_queue_bytes: self.stats.max_queue_bytes,
+            queue_delay_p50_ms: percentile_ms(&delays, 50.0),
+            queue_delay_p95_ms: percentile_ms(&delays, 95.0),
+            queue_delay_p99_ms: percentile_ms(&delays, 99.0),
+            queue_delay_max_ms: percentile_ms(&delays, 100.0),
+        }
+    }
+
+    fn start_tx(&mut self, now: Duration) -> Option<Duration> {
+        let pkt = self.queue.dequeue(now)?;
+
+        self.stats
+            .queue_delays
+            .push(now.saturating_sub(pkt.enqueued));
+
+        let tx_time = Duration::from_secs_f64(
+            pkt.data.len() as f64 * 8.0 / self.bandwidth_bps,
+        );
+
+        self.transmitting = Some(pkt);
+
+        Some(now + tx_time)
+    }
+
+    fn lose(&mut self) -> bool {
+        let rate = self.loss.rate;
+
+        if rate <= 0.0 {
+            return false;
+        }
+
+        if self.loss.mean_burst <= 1.0 {
+            return self.rng.chance(rate);
+        }
+
+        let p_exit = 1.0 / self.loss.mean_burst;
+        let p_enter = rate * p_exit / (1.0 - rate);
+
+        self.in_loss_burst = if self.in_loss_burst {
+            !self.rng.chance(p_exit)
+        } else {
+            self.rng.chance(p_enter)
+        };
+
+        self.in_loss_burst
+    }
+}
+
+pub(crate) fn duration_from_ms(ms: f64) -> Duration {
+    Duration::from_secs_f64(ms.max(0.0) / 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(bandwidth_mbps: f64, delay_ms: f64) -> LinkConfig {
+        LinkConfig {
+            bandwidth_mbps,
+            delay_ms,
+            jitter_ms: 0.0,
+            loss: LossConfig::default(),
+            queue: QueueConfig::default(),
+            changes: vec![],
+        }
+    }
+
+    #[test]
+    fn serialization_and_delay() {
+        let mut link = Link::new(&config(12.0, 10.0), SimRng::new(0));
+
+        let ms = Duration::from_millis;
+
+        // 1500 bytes at 12Mbps take 1ms to serialize.
+        assert_eq!(link.send(vec![0; 1500], ms(0)), Some(ms(1)));
+
+        // The link is busy, the packet is queued.
+        assert_eq!(link.send(vec![1; 1500], ms(0)), None);
+
+        let (delivery, next) = link.on_tx_complete(ms(1));
+        assert_eq!(delivery, Some((ms(11), vec![0; 1500])));
+        assert_eq!(next, Some(ms(2)));
+
+        let (delivery, next) = link.on_tx_complete(ms(2));
+        assert_eq!(delivery, Some((ms(12), vec![1; 1500])));
+        assert_eq!(next, None);
+
+        let summary = link.summary();
+        assert_eq!(summary.packets_delivered, 2);
+        assert_eq!(summary.queue_delay_max_ms, 1.0);
+    }
+
+    #[test]
+    fn change_bandwidth() {
+        let mut link = Link::new(&config(12.0, 0.0), SimRng::new(0));
+
+        link.apply(&LinkChange {
+            at_ms: 0.0,
+            bandwidth_mbps: Some(6.0),
+            delay_ms: None,
+            jitter_ms: None,
+            loss: None,
+        });
+
+        assert_eq!(
+            link.send(vec![0; 1500], Duration::ZERO),
+            Some(Duration::from_millis(2))
+        );
+    }
+
+    #[test]
+    fn jitter_does_not_reorder() {
+        let mut cfg = config(1000.0, 10.0);
+        cfg.jitter_ms = 5.0;
+
+        let mut link = Link::new(&cfg, SimRng::new(7));
+
+        let mut now = Duration::ZERO;
+        let mut last = Duration::ZERO;
+
+        for _ in 0..1000 {
+            let done = link.send(vec![0; 100], now).unwrap();
+            let (delivery, _) = link.on_tx_complete(done);
+            let (arrival, _) = delivery.unwrap();
+
+            assert!(arrival >= last);
+            assert!(arrival <= done + Duration::from_millis(15));
+
+            last = arrival;
+            now = done;
+        }
+    }
+
+    #[test]
+    fn loss_rate() {
+        for mean_burst in [1.0, 4.0] {
+            let mut cfg = config(1000.0, 0.0);
+            cfg.loss = LossConfig {
+                rate: 0.1,
+                mean_burst,
+            };
+
+            let mut link = Link::new(&cfg, SimRng::new(3));
+
+            for _ in 0..20_000 {
+                if let Some(done) = link.send(vec![0; 100], Duration::ZERO) {
+                    link.on_tx_complete(done);
+                }
+            }
+
+            let lost = link.summary().random_losses;
+            assert!((1_600..2_400).contains(&lost), "{mean_burst} {lost}");
+        }
+    }
+}
@@ -0,0 +1,191 @@
+// Copyright (C) 2026, Cloudflare, Inc.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are
+// met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//
+//     * Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in the
+//       documentation and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS
+// IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO,
+// THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR
+// PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::exit;
+
+use clap::Arg;
+use clap::ArgAction;
+use clap::Command;
+use log::error;
+use log::info;
+
+use cc_sim::Scenario;
+use cc_sim::SimOptions;
+
+fn main() {
+    env_logger::builder().init();
+
+    exit(run());
+}
+
+fn command() -> Command {
+    Command::new("cc-sim")
+        .about("Runs quiche congestion control algorithms over simulated networks")
+        .arg(
+            Arg::new("SCENARIOS")
+                .help("The scenario files to run")
+                .required(true)
+                .num_args(1..)
+                .index(1),
+        )
+        .arg(
+            Arg::new("cc")
+                .long("cc")
+                .help("Runs the scenarios with this congestion control algorithm instead of the ones they list")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("qlog-dir")
+                .long("qlog-dir")
+                .help("Writes the qlog of each endpoint to this directory"),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .help("Writes the JSON summaries to this file instead of stdout"),
+        )
+        .arg(
+            Arg::new("check")
+                .long("check")
+                .help("Exits with an error if a run does not meet the scenario's expectations")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("cert")
+                .long("cert")
+                .help("The TLS certificate of the simulated server"),
+        )
+        .arg(
+            Arg::new("key")
+                .long("key")
+                .help("The TLS private key of the simulated server"),
+        )
+}
+
+fn run() -> i32 {
+    let mut matches = command().get_matches();
+
+    let files: Vec<String> = matches
+        .remove_many::<String>("SCENARIOS")
+        .unwrap()
+        .collect();
+    let cc_override: Option<Vec<String>> =
+        matches.remove_many::<String>("cc").map(|v| v.collect());
+    let output = matches.remove_one::<String>("output");
+    let check = matches.get_flag("check");
+
+    let mut options = SimOptions {
+        qlog_dir: matches.remove_one::<String>("qlog-dir").map(PathBuf::from),
+        ..Default::default()
+    };
+
+    if let Some(cert) = matches.remove_one::<String>("cert") {
+        options.cert_file = cert;
+    }
+
+    if let Some(key) = matches.remove_one::<String>("key") {
+        options.key_file = key;
+    }
+
+    if let Some(dir) = &options.qlog_dir {
+        if let Err(e) = std::fs::create_dir_all(dir) {
+            error!("failed to create {}: {e}", dir.display());
+            return 1;
+        }
+    }
+
+    let mut summaries = vec![];
+    let mut failed = false;
+
+    for file in &files {
+        let scenario = match Scenario::from_file(Path::new(file)) {
+            Ok(v) => v,
+
+            Err(e) => {
+                error!("{file}: {e}");
+                return 1;
+            },
+        };
+
+        let algorithms = cc_override.as_ref().unwrap_or(&scenario.cc_algorithms);
+
+        for cc in algorithms {
+            let summary = match cc_sim::run(&scenario, cc, &options) {
+                Ok(v) => v,
+
+                Err(e) => {
+                    error!("{} with {cc}: {e}", scenario.name);
+                    return 1;
+                },
+            };
+
+            info!(
+                "{} with {cc}: completed={} duration={:.1}ms",
+                scenario.name, summary.completed, summary.duration_ms
+            );
+
+            if check {
+                let failures = scenario
+                    .expect
+                    .get(cc)
+                    .map(|expect| expect.check(&summary))
+                    .unwrap_or_default();
+
+                for failure in &failures {
+                    error!("{} with {cc}: {failure}", scenario.name);
+                }
+
+                failed |= !failures.is_empty();
+            }
+
+            summaries.push(summary);
+        }
+    }
+
+    let json = serde_json::to_string_pretty(&summaries).unwrap();
+
+    let written = match output {
+        Some(path) => File::create(&path).and_then(|mut f| writeln!(f, "{json}")),
+
+        None => writeln!(std::io::stdout(), "{json}"),
+    };
+
+    if let Err(e) = written {
+        error!("failed to write summaries: {e}");
+        return 1;
+    }
+
+    if failed {
+        return 1;
+    }
+
+    0
+}
@@ -0,0 +1,179 @@
+// Copyright (C) 2026, Cloudflare, Inc.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are
+// met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//
+//     * Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in the
+//       documentation and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS
+// IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO,
+// THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR
+// PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Summary metrics of a simulation run.
+
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+/// The outcome of a simulation run.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Summary {
+    pub scenario: String,
+
+    pub cc_algorithm: String,
+
+    /// Whether all transfers completed before the duration limit.
+    pub completed: bool,
+
+    /// The simulation time at the end of the run.
+    pub duration_ms: f64,
+
+    pub handshake_ms: Option<f64>,
+
+    pub upload: TransferSummary,
+
+    pub download: TransferSummary,
+
+    pub client: EndpointSummary,
+
+    pub server: EndpointSummary,
+
+    /// The link carrying packets from the client to the server.
+    pub uplink: LinkSummary,
+
+    /// The link carrying packets from the server to the client.
+    pub downlink: LinkSummary,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct TransferSummary {
+    pub bytes: u64,
+
+    pub bytes_received: u64,
+
+    /// The time from the start of the simulation to the reception of the
+    /// last byte.
+    pub completion_ms: Option<f64>,
+
+    /// The goodput from the end of the handshake to the reception of the
+    /// last byte.
+    pub goodput_mbps: Option<f64>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct EndpointSummary {
+    pub packets_sent: usize,
+
+    pub packets_lost: usize,
+
+    pub packets_spurious_lost: usize,
+
+    pub packets_retransmitted: usize,
+
+    pub bytes_sent: u64,
+
+    pub bytes_lost: u64,
+
+    pub srtt_ms: f64,
+
+    pub min_rtt_ms: Option<f64>,
+
+    pub cwnd: usize,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct LinkSummary {
+    pub packets_sent: u64,
+
+    pub bytes_sent: u64,
+
+    pub packets_delivered: u64,
+
+    pub bytes_delivered: u64,
+
+    pub random_losses: u64,
+
+    pub queue_drops: u64,
+
+    pub max_queue_bytes: usize,
+
+    pub queue_delay_p50_ms: f64,
+
+    pub queue_delay_p95_ms: f64,
+
+    pub queue_delay_p99_ms: f64,
+
+    pub queue_delay_max_ms: f64,
+}
+
+impl EndpointSummary {
+    pub fn new(conn: &quiche::Connection) -> Self {
+        let stats = conn.stats();
+
+        let mut summary = EndpointSummary {
+            packets_sent: stats.sent,
+            packets_lost: stats.lost,
+            packets_spurious_lost: stats.spurious_lost,
+            packets_retransmitted: stats.retrans,
+            bytes_sent: stats.sent_bytes,
+            bytes_lost: stats.lost_bytes,
+            ..Default::default()
+        };
+
+        if let Some(path) = conn.path_stats().find(|p| p.active) {
+            summary.srtt_ms = as_ms(path.rtt);
+            summary.min_rtt_ms = path.min_rtt.map(as_ms);
+            summary.cwnd = path.cwnd;
+        }
+
+        summary
+    }
+}
+
+pub fn as_ms(d: Duration) -> f64 {
+    d.as_nanos() as f64 / 1_000_000.0
+}
+
+/// Returns the `p`-th percentile of sorted `values`, in milliseconds.
+pub fn percentile_ms(values: &[Duration], p: f64) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+
+    let rank = (p / 100.0 * values.len() as f64).ceil() as usize;
+
+    as_ms(values[rank.clamp(1, values.len()) - 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles() {
+        let values: Vec<_> = (1..=100).map(Duration::from_millis).collect();
+
+        assert_eq!(percentile_ms(&values, 0.0), 1.0);
+        assert_eq!(percentile_ms(&values, 50.0), 50.0);
+        assert_eq!(percentile_ms(&values, 95.0), 95.0);
+        assert_eq!(percentile_ms(&values, 100.0), 100.0);
+
+        assert_eq!(percentile_ms(&[], 50.0), 0.0);
+    }
+}
@@ -0,0 +1,456 @@
+// Copyright (C) 2026, Cloudflare, Inc.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are
+// met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//
+//     * Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in the
+//       documentation and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS
+// IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO,
+// THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR
+// PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Bottleneck queue disciplines.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::rng::SimRng;
+
+/// Packets smaller than this never make CoDel enter the dropping state.
+const CODEL_MAX_PACKET: usize = 1500;
+
+/// A packet waiting in, or travelling through, a link.
+#[derive(Clone, Debug)]
+pub struct Packet {
+    pub data: Vec<u8>,
+
+    /// The time at which the packet entered the queue.
+    pub enqueued: Duration,
+}
+
+/// The queue discipline of a link.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum QueueConfig {
+    /// Drops packets arriving when the queue is full.
+    DropTail { limit_bytes: usize },
+
+    /// Random Early Detection, dropping arriving packets with a probability
+    /// that grows with the average queue size.
+    Red {
+        limit_bytes: usize,
+        min_threshold_bytes: usize,
+        max_threshold_bytes: usize,
+        max_p: f64,
+
+        /// The weight of the moving average of the queue size.
+        #[serde(default = "default_red_weight")]
+        weight: f64,
+    },
+
+    /// Controlled Delay (RFC 8289), dropping packets at dequeue when their
+    /// sojourn time stays above `target_ms` for at least `interval_ms`.
+    Codel {
+        limit_bytes: usize,
+
+        #[serde(default = "default_codel_target_ms")]
+        target_ms: f64,
+
+        #[serde(default = "default_codel_interval_ms")]
+        interval_ms: f64,
+    },
+}
+
+fn default_red_weight() -> f64 {
+    0.002
+}
+
+fn default_codel_target_ms() -> f64 {
+    5.0
+}
+
+fn default_codel_interval_ms() -> f64 {
+    100.0
+}
+
+impl Default for QueueConfig {
+    fn default() -> Self {
+        QueueConfig::DropTail {
+            limit_bytes: 150_000,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct RedState {
+    min_threshold: f64,
+    max_threshold: f64,
+    max_p: f64,
+    weight: f64,
+    avg: f64,
+}
+
+#[derive(Debug)]
+struct CodelState {
+    target: Duration,
+    interval: Duration,
+    first_above_time: Option<Duration>,
+    drop_next: Duration,
+    count: u32,
+    last_count: u32,
+    dropping: bool,
+}
+
+impl CodelState {
+    fn control_law(&self, t: Duration) -> Duration {
+        t + self.interval.div_f64((self.count as f64).sqrt())
+    }
+}
+
+#[derive(Debug)]
+enum Aqm {
+    None,
+    Red(RedState),
+    Codel(CodelState),
+}
+
+/// A FIFO queue with an optional active queue management algorithm.
+#[derive(Debug)]
+pub struct Queue {
+    packets: VecDeque<Packet>,
+    bytes: usize,
+    limit_bytes: usize,
+    aqm: Aqm,
+
+    /// The number of packets dropped by the queue so far.
+    pub drops: u64,
+}
+
+impl Queue {
+    pub fn new(config: &QueueConfig) -> Self {
+        let (limit_bytes, aqm) = match *config {
+            QueueConfig::DropTail { limit_bytes } => (limit_bytes, Aqm::None),
+
+            QueueConfig::Red {
+                limit_bytes,
+                min_threshold_bytes,
+                max_threshold_bytes,
+                max_p,
+                weight,
+            } => (
+                limit_bytes,
+                Aqm::Red(RedState {
+                    min_threshold: min_threshold_bytes as f64,
+                    max_threshold: max_threshold_bytes as f64,
+                    max_p,
+                    weight,
+                    avg: 0.0,
+                }),
+            ),
+
+            QueueConfig::Codel {
+                limit_bytes,
+                target_ms,
+                interval_ms,
+            } => (
+                limit_bytes,
+                Aqm::Codel(CodelState {
+                    target: Duration::from_secs_f64(target_ms / 1000.0),
+                    interval: Duration::from_secs_f64(interval_ms / 1000.0),
+                    first_above_time: None,
+                    drop_next: Duration::ZERO,
+                    count: 0,
+                    last_count: 0,
+                    dropping: false,
+                }),
+            ),
+        };
+
+        Queue {
+            packets: VecDeque::new(),
+            bytes: 0,
+            limit_bytes,
+            aqm,
+            drops: 0,
+        }
+    }
+
+    /// Returns the number of bytes currently queued.
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.packets.is_empty()
+    }
+
+    /// Adds a packet to the tail of the queue.
+    ///
+    /// Returns `false` if the packet was dropped.
+    pub fn enqueue(&mut self, pkt: Packet, rng: &mut SimRng) -> bool {
+        if self.bytes + pkt.data.len() > self.limit_bytes {
+            self.drops += 1;
+            return false;
+        }
+
+        if let Aqm::Red(red) = &mut self.aqm {
+            red.avg =
+                (1.0 - red.weight) * red.avg + red.weight * self.bytes as f64;
+
+            let drop = if red.avg < red.min_threshold {
+                false
+            } else if red.avg >= red.max_threshold {
+                true
+            } else {
+                let p = red.max_p * (red.avg - red.min_threshold) /
+                    (red.max_threshold - red.min_threshold);
+
+                rng.chance(p)
+            };
+
+            if drop {
+                self.drops += 1;
+                return false;
+            }
+        }
+
+        self.bytes += pkt.data.len();
+        self.packets.push_back(pkt);
+
+        true
+    }
+
+    /// Removes the packet at the head of the queue, if any.
+    pub fn dequeue(&mut self, now: Duration) -> Option<Packet> {
+        if !matches!(self.aqm, Aqm::Codel(_)) {
+            return self.pop();
+        }
+
+        // CoDel dequeue routine, see RFC 8289 Section 5.
+        let (mut pkt, mut ok_to_drop) = self.codel_do_dequeue(now);
+
+        if pkt.is_none() {
+            self.codel().dropping = false;
+            return None;
+        }
+
+        if self.codel().dropping {
+            if !ok_to_drop {
+                self.codel().dropping = false;
+            }
+
+            while pkt.is_some() &&
+                self.codel().dropping &&
+                now >= self.codel().drop_next
+            {
+                self.drops += 1;
+                self.codel().count += 1;
+
+                (pkt, ok_to_drop) = self.codel_do_dequeue(now);
+
+                let codel = self.codel();
+
+                if !ok_to_drop {
+                    codel.dropping = false;
+                } else {
+                    codel.drop_next = codel.control_law(codel.drop_next);
+                }
+            }
+        } else if ok_to_drop {
+            self.drops += 1;
+
+            (pkt, _) = self.codel_do_dequeue(now);
+
+            let codel = self.codel();
+
+            codel.dropping = true;
+
+            let delta = codel.count.saturating_sub(codel.last_count);
+
+            codel.count = 1;
+
+            if delta > 1 &&
+                now.saturating_sub(codel.drop_next) < codel.interval * 16
+            {
+                codel.count = delta;
+            }
+
+            codel.drop_next = codel.control_law(now);
+            codel.last_count = codel.count;
+        }
+
+        pkt
+    }
+
+    fn pop(&mut self) -> Option<Packet> {
+        let pkt = self.packets.pop_front()?;
+
+        self.bytes -= pkt.data.len();
+
+        Some(pkt)
+    }
+
+    fn codel(&mut self) -> &mut CodelState {
+        match &mut self.aqm {
+            Aqm::Codel(codel) => codel,
+
+            _ => unreachable!(),
+        }
+    }
+
+    fn codel_do_dequeue(&mut self, now: Duration) -> (Option<Packet>, bool) {
+        let Some(pkt) = self.pop() else {
+            self.codel().first_above_time = None;
+            return (None, false);
+        };
+
+        let sojourn = now.saturating_sub(pkt.enqueued);
+        let bytes = self.bytes;
+
+        let codel = self.codel();
+
+        let mut ok_to_drop = false;
+
+        if sojourn < codel.target || bytes <= CODEL_MAX_PACKET {
+            codel.first_above_time = None;
+        } else {
+            match codel.first_above_time {
+                None => codel.first_above_time = Some(now + codel.interval),
+
+                Some(t) => ok_to_drop = now >= t,
+            }
+        }
+
+        (Some(pkt), ok_to_drop)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pkt(len: usize, enqueued: Duration) -> Packet {
+        Packet {
+            data: vec![0; len],
+            enqueued,
+        }
+    }
+
+    #[test]
+    fn drop_tail() {
+        let mut rng = SimRng::new(0);
+        let mut q = Queue::new(&QueueConfig::DropTail { limit_bytes: 3000 });
+
+        assert!(q.enqueue(pkt(1200, Duration::ZERO), &mut rng));
+        assert!(q.enqueue(pkt(1200, Duration::ZERO), &mut rng));
+        assert!(!q.enqueue(pkt(1200, Duration::ZERO), &mut rng));
+        assert_eq!(q.bytes(), 2400);
+        assert_eq!(q.drops, 1);
+
+        assert!(q.dequeue(Duration::ZERO).is_some());
+        assert!(q.enqueue(pkt(600, Duration::ZERO), &mut rng));
+        assert_eq!(q.bytes(), 1800);
+    }
+
+    #[test]
+    fn red_drops_early() {
+        let mut rng = SimRng::new(0);
+        let mut q = Queue::new(&QueueConfig::Red {
+            limit_bytes: 1_000_000,
+            min_threshold_bytes: 10_000,
+            max_threshold_bytes: 30_000,
+            max_p: 0.1,
+            weight: 0.2,
+        });
+
+        // Nothing is dropped while the average stays below the minimum
+        // threshold.
+        for _ in 0..8 {
+            assert!(q.enqueue(pkt(1000, Duration::ZERO), &mut rng));
+        }
+
+        // Everything is dropped once the average exceeds the maximum
+        // threshold, even though the queue limit is far away.
+        for _ in 0..200 {
+            q.enqueue(pkt(1000, Duration::ZERO), &mut rng);
+        }
+
+        assert!(q.drops > 0);
+        assert!(q.bytes() < 40_000);
+    }
+
+    #[test]
+    fn codel_drops_standing_queue() {
+        let mut rng = SimRng::new(0);
+        let mut q = Queue::new(&QueueConfig::Codel {
+            limit_bytes: 1_000_000,
+            target_ms: 5.0,
+            interval_ms: 100.0,
+        });
+
+        for _ in 0..500 {
+            assert!(q.enqueue(pkt(1200, Duration::ZERO), &mut rng));
+        }
+
+        // Drain one packet every 2ms, so the sojourn time is always above
+        // target.
+        let mut now = Duration::from_millis(10);
+        let mut delivered = 0;
+
+        while !q.is_empty() {
+            if q.dequeue(now).is_some() {
+                delivered += 1;
+            }
+
+            now += Duration::from_millis(2);
+        }
+
+        // No drops during the first interval, then drops at an increasing
+        // rate.
+        assert!(q.drops > 0);
+        assert!(delivered > 50);
+        assert_eq!(delivered + q.drops, 500);
+    }
+
+    #[test]
+    fn codel_no_drops_below_target() {
+        let mut rng = SimRng::new(0);
+        let mut q = Queue::new(&QueueConfig::Codel {
+            limit_bytes: 1_000_000,
+            target_ms: 5.0,
+            interval_ms: 100.0,
+        });
+
+        let mut now = Duration::ZERO;
+
+        for _ in 0..1000 {
+            q.enqueue(pkt(1200, now), &mut rng);
+            q.enqueue(pkt(1200, now), &mut rng);
+
+            now += Duration::from_millis(1);
+
+            assert!(q.dequeue(now).is_some());
+            assert!(q.dequeue(now).is_some());
+        }
+
+        assert_eq!(q.drops, 0);
+    }
+}
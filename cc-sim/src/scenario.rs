@@ -0,0 +1,334 @@
+// Copyright (C) 2026, Cloudflare, Inc.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are
+// met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//
+//     * Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in the
+//       documentation and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS
+// IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO,
+// THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR
+// PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Scenario files.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::link::LinkConfig;
+use crate::metrics::Summary;
+use crate::Error;
+use crate::Result;
+
+/// A simulated network and workload.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    /// The scenario name. Defaults to the file name.
+    #[serde(default)]
+    pub name: String,
+
+    /// The congestion control algorithms to run the scenario with, as
+    /// accepted by [`quiche::Config::set_cc_algorithm_name()`].
+    #[serde(default = "default_cc_algorithms")]
+    pub cc_algorithms: Vec<String>,
+
+    /// The seed of the link models' random number generator.
+    #[serde(default)]
+    pub seed: u64,
+
+    /// The simulation stops at this time even if transfers are incomplete.
+    #[serde(default = "default_duration_limit_ms")]
+    pub duration_limit_ms: f64,
+
+    /// The number of bytes sent by the client after the handshake.
+    #[serde(default)]
+    pub upload_bytes: u64,
+
+    /// The number of bytes sent by the server after the handshake.
+    #[serde(default)]
+    pub download_bytes: u64,
+
+    #[serde(default = "default_pacing")]
+    pub pacing: bool,
+
+    /// The link carrying packets from the client to the server.
+    pub uplink: LinkConfig,
+
+    /// The link carrying packets from the server to the client.
+    pub downlink: LinkConfig,
+
+    /// Expected results, per congestion control algorithm.
+    #[serde(default)]
+    pub expect: BTreeMap<String, Expectations>,
+}
+
+fn default_cc_algorithms() -> Vec<String> {
+    vec!["cubic".to_string()]
+}
+
+fn default_duration_limit_ms() -> f64 {
+    60_000.0
+}
+
+fn default_pacing() -> bool {
+    true
+}
+
+/// Bounds on the results of a run, used to catch regressions.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Expectations {
+    #[serde(default)]
+    pub max_completion_ms: Option<f64>,
+
+    #[serde(default)]
+    pub min_upload_goodput_mbps: Option<f64>,
+
+    #[serde(default)]
+    pub min_download_goodput_mbps: Option<f64>,
+
+    /// The maximum number of packets lost by either endpoint.
+    #[serde(default)]
+    pub max_packets_lost: Option<usize>,
+
+    /// The maximum 95th percentile of queueing delay on either link.
+    #[serde(default)]
+    pub max_queue_delay_p95_ms: Option<f64>,
+}
+
+impl Expectations {
+    /// Returns a description of each expectation `summary` does not meet.
+    pub fn check(&self, summary: &Summary) -> Vec<String> {
+        let mut failures = vec![];
+
+        if !summary.completed {
+            failures.push(format!(
+                "transfers incomplete after {:.0}ms",
+                summary.duration_ms
+            ));
+        }
+
+        let completion_ms =
+            [summary.upload.completion_ms, summary.download.completion_ms]
+                .into_iter()
+                .flatten()
+                .fold(0.0, f64::max);
+
+        if let Some(max) = self.max_completion_ms {
+            if completion_ms > max {
+                failures.push(format!(
+                    "completion time {completion_ms:.1}ms above {max}ms"
+                ));
+            }
+        }
+
+        let goodputs = [
+            (
+                "upload",
+                self.min_upload_goodput_mbps,
+                summary.upload.goodput_mbps,
+            ),
+            (
+                "download",
+                self.min_download_goodput_mbps,
+                summary.download.goodput_mbps,
+            ),
+        ];
+
+        for (name, min, goodput) in goodputs {
+            let (Some(min), goodput) = (min, goodput.unwrap_or(0.0)) else {
+                continue;
+            };
+
+            if goodput < min {
+                failures.push(format!(
+                    "{name} goodput {goodput:.2}Mbps below {min}Mbps"
+                ));
+            }
+        }
+
+        if let Some(max) = self.max_packets_lost {
+            let lost =
+                summary.client.packets_lost.max(summary.server.packets_lost);
+
+            if lost > max {
+                failures.push(format!("{lost} packets lost, above {max}"));
+            }
+        }
+
+        if let Some(max) = self.max_queue_delay_p95_ms {
+            let delay = summary
+                .uplink
+                .queue_delay_p95_ms
+                .max(summary.downlink.queue_delay_p95_ms);
+
+            if delay > max {
+                failures.push(format!(
+                    "p95 queueing delay {delay:.1}ms above {max}ms"
+                ));
+            }
+        }
+
+        failures
+    }
+}
+
+impl Scenario {
+    /// Reads a scenario from a JSON file.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let file = File::open(path)?;
+
+        let mut scenario: Scenario =
+            serde_json::from_reader(BufReader::new(file))?;
+
+        if scenario.name.is_empty() {
+            scenario.name = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default();
+        }
+
+        scenario.validate()?;
+
+        Ok(scenario)
+    }
+
+    /// Checks that the scenario describes a workload that can be simulated.
+    pub fn validate(&self) -> Result<()> {
+        if self.upload_bytes == 0 && self.download_bytes == 0 {
+            return Err(Error::InvalidScenario(
+                "no upload_bytes or download_bytes".to_string(),
+            ));
+        }
+
+        if self.cc_algorithms.is_empty() {
+            return Err(Error::InvalidScenario("no cc_algorithms".to_string()));
+        }
+
+        for (name, link) in
+            [("uplink", &self.uplink), ("downlink", &self.downlink)]
+        {
+            let loss_rates = std::iter::once(link.loss.rate).chain(
+                link.changes
+                    .iter()
+                    .filter_map(|c| c.loss.as_ref().map(|l| l.rate)),
+            );
+
+            let bandwidths = std::iter::once(link.bandwidth_mbps)
+                .chain(link.changes.iter().filter_map(|c| c.bandwidth_mbps));
+
+            for rate in loss_rates {
+                if !(0.0..1.0).contains(&rate) {
+                    return Err(Error::InvalidScenario(format!(
+                        "{name} loss rate {rate} not in [0, 1)"
+                    )));
+                }
+            }
+
+            for bw in bandwidths {
+                if bw.is_nan() || bw <= 0.0 {
+                    return Err(Error::InvalidScenario(format!(
+                        "{name} bandwidth {bw} must be positive"
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::queue::QueueConfig;
+
+    #[test]
+    fn parse() {
+        let scenario: Scenario = serde_json::from_str(
+            r#"{
+                "cc_algorithms": ["reno", "bbr2"],
+                "download_bytes": 1000000,
+                "uplink": { "bandwidth_mbps": 10, "delay_ms": 20 },
+                "downlink": {
+                    "bandwidth_mbps": 10,
+                    "delay_ms": 20,
+                    "loss": { "rate": 0.01 },
+                    "queue": { "type": "codel", "limit_bytes": 100000 },
+                    "changes": [{ "at_ms": 1000, "bandwidth_mbps": 5 }]
+                },
+                "expect": { "reno": { "max_completion_ms": 5000 } }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(scenario.cc_algorithms, ["reno", "bbr2"]);
+        assert_eq!(scenario.duration_limit_ms, 60_000.0);
+        assert!(scenario.pacing);
+        assert_eq!(scenario.uplink.queue, QueueConfig::default());
+        assert_eq!(scenario.downlink.loss.mean_burst, 1.0);
+        assert_eq!(scenario.downlink.queue, QueueConfig::Codel {
+            limit_bytes: 100_000,
+            target_ms: 5.0,
+            interval_ms: 100.0,
+        });
+        assert_eq!(scenario.downlink.changes[0].bandwidth_mbps, Some(5.0));
+        assert_eq!(scenario.expect["reno"].max_completion_ms, Some(5000.0));
+
+        assert!(scenario.validate().is_ok());
+    }
+
+    #[test]
+    fn invalid() {
+        let mut scenario: Scenario = serde_json::from_str(
+            r#"{
+                "uplink": { "bandwidth_mbps": 10 },
+                "downlink": { "bandwidth_mbps": 0 }
+            }"#,
+        )
+        .unwrap();
+
+        assert!(scenario.validate().is_err());
+
+        scenario.download_bytes = 1000;
+        assert!(scenario.validate().is_err());
+
+        scenario.downlink.bandwidth_mbps = 1.0;
+        assert!(scenario.validate().is_ok());
+
+        scenario.uplink.loss.rate = 1.0;
+        assert!(scenario.validate().is_err());
+    }
+
+    #[test]
+    fn unknown_fields() {
+        assert!(serde_json::from_str::<Scenario>(
+            r#"{
+                "uplink": { "bandwidth_mbps": 10, "bandwith_mbps": 5 },
+                "downlink": { "bandwidth_mbps": 10 }
+            }"#,
+        )
+        .is_err());
+    }
+}
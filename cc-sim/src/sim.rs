@@ -0,0 +1,697 @@
+// Copyright (C) 2026, Cloudflare, Inc.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are
+// met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//
+//     * Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in the
+//       documentation and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS
+// IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO,
+// THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR
+// PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! The discrete-event simulation loop.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::BufWriter;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::debug;
+use log::error;
+
+use crate::clock::SimClock;
+use crate::link::duration_from_ms;
+use crate::link::Link;
+use crate::metrics::as_ms;
+use crate::metrics::EndpointSummary;
+use crate::metrics::Summary;
+use crate::metrics::TransferSummary;
+use crate::rng::ConnRng;
+use crate::rng::SimRng;
+use crate::scenario::Scenario;
+use crate::Result;
+
+const MAX_DATAGRAM_SIZE: usize = 1350;
+
+/// The client-initiated bidirectional stream carrying the upload.
+const UPLOAD_STREAM_ID: u64 = 0;
+
+/// The server-initiated bidirectional stream carrying the download.
+const DOWNLOAD_STREAM_ID: u64 = 1;
+
+/// The flow control windows are large enough to never limit transfers.
+const MAX_DATA: u64 = 1 << 30;
+
+static STREAM_DATA: [u8; 16384] = [0; 16384];
+
+/// Options of a simulation run that are not part of the scenario.
+#[derive(Clone, Debug)]
+pub struct SimOptions {
+    /// The directory qlog files are written to, if any.
+    pub qlog_dir: Option<PathBuf>,
+
+    pub cert_file: String,
+
+    pub key_file: String,
+}
+
+impl Default for SimOptions {
+    fn default() -> Self {
+        SimOptions {
+            qlog_dir: None,
+            cert_file: concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/../quiche/examples/cert.crt"
+            )
+            .to_string(),
+            key_file: concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/../quiche/examples/cert.key"
+            )
+            .to_string(),
+        }
+    }
+}
+
+/// Runs `scenario` with the given congestion control algorithm.
+pub fn run(
+    scenario: &Scenario, cc_algorithm: &str, options: &SimOptions,
+) -> Result<Summary> {
+    scenario.validate()?;
+
+    let mut sim = Simulation::new(scenario, cc_algorithm, options)?;
+
+    sim.run();
+
+    Ok(sim.summary())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Direction {
+    /// From the client to the server.
+    Uplink,
+
+    /// From the server to the client.
+    Downlink,
+}
+
+#[derive(Debug)]
+enum Event {
+    /// A packet is released by its sender, after pacing.
+    Send { dir: Direction, data: Vec<u8> },
+
+    /// A link finished serializing a packet.
+    TxComplete { dir: Direction },
+
+    /// A packet arrives at its receiver.
+    Arrive { dir: Direction, data: Vec<u8> },
+
+    /// The link parameters change, see [`crate::link::LinkChange`].
+    LinkChange { dir: Direction, index: usize },
+}
+
+/// An event in the simulation queue.
+///
+/// Events are ordered by time, and events scheduled for the same time are
+/// processed in the order they were scheduled, so runs are reproducible.
+#[derive(Debug)]
+struct Scheduled {
+    time: Duration,
+    seq: u64,
+    event: Event,
+}
+
+impl Ord for Scheduled {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // `BinaryHeap` is a max-heap, so reverse the order to pop the
+        // earliest event first.
+        (other.time, other.seq).cmp(&(self.time, self.seq))
+    }
+}
+
+impl PartialOrd for Scheduled {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Scheduled {
+    fn eq(&self, other: &Self) -> bool {
+        (self.time, self.seq) == (other.time, other.seq)
+    }
+}
+
+impl Eq for Scheduled {}
+
+/// A bulk transfer on a single stream.
+#[derive(Debug)]
+struct Transfer {
+    stream_id: u64,
+    total: u64,
+    sent: u64,
+    received: u64,
+    completed_at: Option<Duration>,
+}
+
+impl Transfer {
+    fn new(stream_id: u64, total: u64) -> Self {
+        Transfer {
+            stream_id,
+            total,
+            sent: 0,
+            received: 0,
+            completed_at: None,
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.total == 0 || self.completed_at.is_some()
+    }
+
+    /// Writes as much of the remaining data as flow and congestion control
+    /// allow.
+    fn send(&mut self, conn: &mut quiche::Connection) {
+        if !conn.is_established() {
+            return;
+        }
+
+        while self.sent < self.total {
+            let len = (self.total - self.sent).min(STREAM_DATA.len() as u64);
+            let fin = self.sent + len == self.total;
+
+            match conn.stream_send(
+                self.stream_id,
+                &STREAM_DATA[..len as usize],
+                fin,
+            ) {
+                Ok(0) | Err(quiche::Error::Done) => break,
+
+                Ok(written) => self.sent += written as u64,
+
+                Err(e) => {
+                    error!("stream {} send failed: {e:?}", self.stream_id);
+                    break;
+                },
+            }
+        }
+    }
+
+    /// Reads all readable data, recording the time the transfer completes.
+    fn recv(&mut self, conn: &mut quiche::Connection, now: Duration) {
+        let mut buf = [0; 65535];
+
+        for stream_id in conn.readable() {
+            while let Ok((read, fin)) = conn.stream_recv(stream_id, &mut buf) {
+                if stream_id != self.stream_id {
+                    continue;
+                }
+
+                self.received += read as u64;
+
+                if fin {
+                    self.completed_at = Some(now);
+                }
+            }
+        }
+    }
+
+    fn summary(&self, start: Option<Duration>) -> TransferSummary {
+        let goodput_mbps = match (start, self.completed_at) {
+            (Some(start), Some(end)) if end > start =>
+                Some(self.total as f64 * 8.0 / (end - start).as_secs_f64() / 1e6),
+
+            _ => None,
+        };
+
+        TransferSummary {
+            bytes: self.total,
+            bytes_received: self.received,
+            completion_ms: self.completed_at.map(as_ms),
+            goodput_mbps,
+        }
+    }
+}
+
+struct Endpoint {
+    conn: quiche::Connection,
+    local: SocketAddr,
+    peer: SocketAddr,
+}
+
+struct Simulation<'a> {
+    scenario: &'a Scenario,
+    cc_algorithm: String,
+
+    clock: Arc<SimClock>,
+
+    events: BinaryHeap<Scheduled>,
+    next_seq: u64,
+
+    client: Endpoint,
+    server: Endpoint,
+
+    uplink: Link,
+    downlink: Link,
+
+    upload: Transfer,
+    download: Transfer,
+
+    handshake_completed_at: Option<Duration>,
+}
+
+impl<'a> Simulation<'a> {
+    fn new(
+        scenario: &'a Scenario, cc_algorithm: &str, options: &SimOptions,
+    ) -> Result<Self> {
+        let clock = Arc::new(SimClock::new());
+
+        let mut rng = SimRng::new(scenario.seed);
+
+        let client_addr: SocketAddr = "10.0.0.1:4433".parse().unwrap();
+        let server_addr: SocketAddr = "10.0.0.2:443".parse().unwrap();
+
+        let mut client_scid = [0; quiche::MAX_CONN_ID_LEN];
+        rng.fill(&mut client_scid);
+
+        let mut server_scid = [0; quiche::MAX_CONN_ID_LEN];
+        rng.fill(&mut server_scid);
+
+        let uplink_rng = rng.fork();
+        let downlink_rng = rng.fork();
+
+        let mut client_config =
+            make_config(scenario, cc_algorithm, &clock, options, false)?;
+        client_config.set_rng(Arc::new(ConnRng::new(rng.fork())));
+
+        let mut server_config =
+            make_config(scenario, cc_algorithm, &clock, options, true)?;
+        server_config.set_rng(Arc::new(ConnRng::new(rng.fork())));
+
+        let mut client = quiche::connect(
+            Some("cc-sim"),
+            &quiche::ConnectionId::from_ref(&client_scid),
+            client_addr,
+            server_addr,
+            &mut client_config,
+        )?;
+
+        let mut server = quiche::accept(
+            &quiche::ConnectionId::from_ref(&server_scid),
+            None,
+            server_addr,
+            client_addr,
+            &mut server_config,
+        )?;
+
+        if let Some(dir) = &options.qlog_dir {
+            for (conn, role) in [(&mut client, "client"), (&mut server, "server")]
+            {
+                let name = format!("{}-{cc_algorithm}-{role}", scenario.name);
+                let file = File::create(dir.join(format!("{name}.sqlog")))?;
+
+                conn.set_qlog(
+                    Box::new(BufWriter::new(file)),
+                    name,
+                    format!("cc-sim {} {role}", scenario.name),
+                );
+            }
+        }
+
+        let mut sim = Simulation {
+            scenario,
+            cc_algorithm: cc_algorithm.to_string(),
+
+            clock,
+
+            events: BinaryHeap::new(),
+            next_seq: 0,
+
+            client: Endpoint {
+                conn: client,
+                local: client_addr,
+                peer: server_addr,
+            },
+
+            server: Endpoint {
+                conn: server,
+                local: server_addr,
+                peer: client_addr,
+            },
+
+            uplink: Link::new(&scenario.uplink, uplink_rng),
+            downlink: Link::new(&scenario.downlink, downlink_rng),
+
+            upload: Transfer::new(UPLOAD_STREAM_ID, scenario.upload_bytes),
+            download: Transfer::new(DOWNLOAD_STREAM_ID, scenario.download_bytes),
+
+            handshake_completed_at: None,
+        };
+
+        for dir in [Direction::Uplink, Direction::Downlink] {
+            for (index, change) in sim.link_config(dir).changes.iter().enumerate()
+            {
+                sim.schedule(duration_from_ms(change.at_ms), Event::LinkChange {
+                    dir,
+                    index,
+                });
+            }
+        }
+
+        Ok(sim)
+    }
+
+    fn run(&mut self) {
+        let limit = duration_from_ms(self.scenario.duration_limit_ms);
+
+        loop {
+            self.service_transfers();
+
+            self.flush(Direction::Uplink);
+            self.flush(Direction::Downlink);
+
+            if self.is_complete() ||
+                self.client.conn.is_closed() ||
+                self.server.conn.is_closed()
+            {
+                break;
+            }
+
+            let Some(next) = self.next_event_time() else {
+                break;
+            };
+
+            if next > limit {
+                self.clock.advance_to(limit);
+                break;
+            }
+
+            self.clock.advance_to(next);
+
+            let now = self.clock.elapsed();
+
+            while self.events.peek().is_some_and(|e| e.time <= now) {
+                let scheduled = self.events.pop().unwrap();
+
+                self.handle(scheduled.event, now);
+            }
+
+            for conn in [&mut self.client.conn, &mut self.server.conn] {
+                let expired = conn
+                    .timeout_instant()
+                    .is_some_and(|t| self.clock.to_sim_time(t) <= now);
+
+                if expired {
+                    conn.on_timeout();
+                }
+            }
+        }
+    }
+
+    fn summary(&self) -> Summary {
+        Summary {
+            scenario: self.scenario.name.clone(),
+            cc_algorithm: self.cc_algorithm.clone(),
+            completed: self.is_complete(),
+            duration_ms: as_ms(self.clock.elapsed()),
+            handshake_ms: self.handshake_completed_at.map(as_ms),
+            upload: self.upload.summary(self.handshake_completed_at),
+            download: self.download.summary(self.handshake_completed_at),
+            client: EndpointSummary::new(&self.client.conn),
+            server: EndpointSummary::new(&self.server.conn),
+            uplink: self.uplink.summary(),
+            downlink: self.downlink.summary(),
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.upload.is_complete() && self.download.is_complete()
+    }
+
+    fn schedule(&mut self, time: Duration, event: Event) {
+        self.events.push(Scheduled {
+            time,
+            seq: self.next_seq,
+            event,
+        });
+
+        self.next_seq += 1;
+    }
+
+    /// Returns the time of the next event or connection timeout.
+    fn next_event_time(&self) -> Option<Duration> {
+        let timeouts = [&self.client.conn, &self.server.conn]
+            .into_iter()
+            .filter_map(|conn| conn.timeout_instant())
+            .map(|t| self.clock.to_sim_time(t));
+
+        self.events
+            .peek()
+            .map(|e| e.time)
+            .into_iter()
+            .chain(timeouts)
+            .min()
+    }
+
+    fn service_transfers(&mut self) {
+        let now = self.clock.elapsed();
+
+        if self.handshake_completed_at.is_none() &&
+            self.client.conn.is_established()
+        {
+            self.handshake_completed_at = Some(now);
+        }
+
+        self.upload.send(&mut self.client.conn);
+        self.download.send(&mut self.server.conn);
+
+        self.upload.recv(&mut self.server.conn, now);
+        self.download.recv(&mut self.client.conn, now);
+    }
+
+    /// Hands all packets the sender of `dir` has to send to the link, at the
+    /// time given by their pacing hints.
+    fn flush(&mut self, dir: Direction) {
+        let now = self.clock.elapsed();
+
+        let mut out = [0; MAX_DATAGRAM_SIZE];
+
+        loop {
+            let conn = &mut self.sender(dir).conn;
+
+            let (len, info) = match conn.send(&mut out) {
+                Ok(v) => v,
+
+                Err(quiche::Error::Done) => break,
+
+                Err(e) => {
+                    error!("{dir:?} send failed: {e:?}");
+                    break;
+                },
+            };
+
+            let at = self.clock.to_sim_time(info.at).max(now);
+
+            self.schedule(at, Event::Send {
+                dir,
+                data: out[..len].to_vec(),
+            });
+        }
+    }
+
+    fn handle(&mut self, event: Event, now: Duration) {
+        match event {
+            Event::Send { dir, data } =>
+                if let Some(done) = self.link(dir).send(data, now) {
+                    self.schedule(done, Event::TxComplete { dir });
+                },
+
+            Event::TxComplete { dir } => {
+                let (delivery, next) = self.link(dir).on_tx_complete(now);
+
+                if let Some((arrival, data)) = delivery {
+                    self.schedule(arrival, Event::Arrive { dir, data });
+                }
+
+                if let Some(next) = next {
+                    self.schedule(next, Event::TxComplete { dir });
+                }
+            },
+
+            Event::Arrive { dir, mut data } => {
+                let receiver = self.receiver(dir);
+
+                let recv_info = quiche::RecvInfo {
+                    from: receiver.peer,
+                    to: receiver.local,
+                };
+
+                if let Err(e) = receiver.conn.recv(&mut data, recv_info) {
+                    debug!("{dir:?} recv failed: {e:?}");
+                }
+            },
+
+            Event::LinkChange { dir, index } => {
+                let change = self.link_config(dir).changes[index].clone();
+
+                self.link(dir).apply(&change);
+            },
+        }
+    }
+
+    fn link_config(&self, dir: Direction) -> &'a crate::link::LinkConfig {
+        match dir {
+            Direction::Uplink => &self.scenario.uplink,
+
+            Direction::Downlink => &self.scenario.downlink,
+        }
+    }
+
+    fn link(&mut self, dir: Direction) -> &mut Link {
+        match dir {
+            Direction::Uplink => &mut self.uplink,
+
+            Direction::Downlink => &mut self.downlink,
+        }
+    }
+
+    fn sender(&mut self, dir: Direction) -> &mut Endpoint {
+        match dir {
+            Direction::Uplink => &mut self.client,
+
+            Direction::Downlink => &mut self.server,
+        }
+    }
+
+    fn receiver(&mut self, dir: Direction) -> &mut Endpoint {
+        match dir {
+            Direction::Uplink => &mut self.server,
+
+            Direction::Downlink => &mut self.client,
+        }
+    }
+}
+
+fn make_config(
+    scenario: &Scenario, cc_algorithm: &str, clock: &Arc<SimClock>,
+    options: &SimOptions, is_server: bool,
+) -> Result<quiche::Config> {
+    let mut config = quiche::Config::new(quiche::PROTOCOL_VERSION)?;
+
+    if is_server {
+        config.load_cert_chain_from_pem_file(&options.cert_file)?;
+        config.load_priv_key_from_pem_file(&options.key_file)?;
+    }
+
+    config.verify_peer(false);
+    config.set_application_protos(&[b"cc-sim"])?;
+    config.set_max_idle_timeout(30_000);
+    config.set_max_recv_udp_payload_size(MAX_DATAGRAM_SIZE);
+    config.set_max_send_udp_payload_size(MAX_DATAGRAM_SIZE);
+    config.set_initial_max_data(MAX_DATA);
+    config.set_initial_max_stream_data_bidi_local(MAX_DATA);
+    config.set_initial_max_stream_data_bidi_remote(MAX_DATA);
+    config.set_initial_max_streams_bidi(2);
+    config.set_cc_algorithm_name(cc_algorithm)?;
+    config.enable_pacing(scenario.pacing);
+    config.set_clock(clock.clone());
+
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::link::LinkConfig;
+    use crate::link::LossConfig;
+    use crate::queue::QueueConfig;
+
+    fn scenario(download_bytes: u64) -> Scenario {
+        let link = LinkConfig {
+            bandwidth_mbps: 10.0,
+            delay_ms: 20.0,
+            jitter_ms: 0.0,
+            loss: LossConfig::default(),
+            queue: QueueConfig::default(),
+            changes: vec![],
+        };
+
+        Scenario {
+            name: "test".to_string(),
+            cc_algorithms: vec!["cubic".to_string()],
+            seed: 0,
+            duration_limit_ms: 10_000.0,
+            upload_bytes: 0,
+            download_bytes,
+            pacing: true,
+            uplink: link.clone(),
+            downlink: link,
+            expect: Default::default(),
+        }
+    }
+
+    #[test]
+    fn download() {
+        let summary =
+            run(&scenario(500_000), "cubic", &SimOptions::default()).unwrap();
+
+        assert!(summary.completed);
+        assert_eq!(summary.download.bytes_received, 500_000);
+
+        // The handshake takes at least one round trip.
+        assert!(summary.handshake_ms.unwrap() >= 40.0);
+
+        // The transfer can't be faster than the link.
+        let goodput = summary.download.goodput_mbps.unwrap();
+        assert!(goodput > 1.0 && goodput < 10.0, "{goodput}");
+
+        let min_rtt = summary.client.min_rtt_ms.unwrap();
+        assert!((40.0..45.0).contains(&min_rtt), "{min_rtt}");
+    }
+
+    #[test]
+    fn duration_limit() {
+        let mut scenario = scenario(100_000_000);
+        scenario.duration_limit_ms = 1_000.0;
+
+        let summary = run(&scenario, "reno", &SimOptions::default()).unwrap();
+
+        assert!(!summary.completed);
+        assert_eq!(summary.duration_ms, 1_000.0);
+        assert!(summary.download.bytes_received > 0);
+    }
+
+    #[test]
+    fn bidirectional_transfers_with_loss() {
+        let mut scenario = scenario(300_000);
+        scenario.upload_bytes = 200_000;
+        scenario.downlink.loss.rate = 0.02;
+
+        let summary = run(&scenario, "bbr2", &SimOptions::default()).unwrap();
+
+        assert!(summary.completed);
+        assert_eq!(summary.upload.bytes_received, 200_000);
+        assert_eq!(summary.download.bytes_received, 300_000);
+        assert!(summary.downlink.random_losses > 0);
+        assert!(summary.server.packets_lost > 0);
+    }
+}
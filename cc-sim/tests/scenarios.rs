@@ -0,0 +1,82 @@
+// Copyright (C) 2026, Cloudflare, Inc.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are
+// met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//
+//     * Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in the
+//       documentation and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS
+// IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO,
+// THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR
+// PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Runs the scenarios shipped in `scenarios/` and checks their expectations,
+//! so congestion control regressions are caught on CI.
+
+use std::path::Path;
+
+use cc_sim::Scenario;
+use cc_sim::SimOptions;
+
+#[test]
+fn scenarios() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios");
+
+    let mut paths: Vec<_> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+
+    paths.sort();
+
+    assert!(!paths.is_empty());
+
+    let mut failures = vec![];
+
+    for path in paths {
+        let scenario = Scenario::from_file(&path).unwrap();
+
+        for cc in &scenario.cc_algorithms {
+            let summary =
+                cc_sim::run(&scenario, cc, &SimOptions::default()).unwrap();
+
+            let expect = scenario.expect.get(cc).cloned().unwrap_or_default();
+
+            for failure in expect.check(&summary) {
+                failures.push(format!("{} with {cc}: {failure}", scenario.name));
+            }
+        }
+    }
+
+    assert!(failures.is_empty(), "{failures:#?}");
+}
+
+#[test]
+fn deterministic_runs() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("scenarios")
+        .join("lossy-jitter.json");
+
+    let scenario = Scenario::from_file(&path).unwrap();
+
+    let a = cc_sim::run(&scenario, "cubic", &SimOptions::default()).unwrap();
+    let b = cc_sim::run(&scenario, "cubic", &SimOptions::default()).unwrap();
+
+    assert!(a.completed && b.completed);
+    assert_eq!(a, b);
+}
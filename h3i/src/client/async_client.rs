@@ -54,7 +54,9 @@ use tokio_quiche::QuicResult;
 use crate::actions::h3::Action;
 use crate::actions::h3::WaitType;
 use crate::actions::h3::WaitingFor;
+use crate::client::early_actions_to_replay;
 use crate::client::execute_action;
+use crate::client::execute_early_actions;
 use crate::client::parse_args;
 use crate::client::parse_streams;
 use crate::client::ClientError;
@@ -69,6 +71,8 @@ use crate::quiche;
 
 use super::Client;
 use super::ConnectionCloseDetails;
+use super::EarlyActionRecord;
+use super::EarlyDataSummary;
 use super::StreamParserMap;
 
 /// Connect to the socket.
@@ -76,6 +80,31 @@ pub async fn connect(
     args: &H3iConfig, frame_actions: Vec<Action>,
     close_trigger_frames: Option<CloseTriggerFrames>,
 ) -> std::result::Result<BuildingConnectionSummary, ClientError> {
+    connect_with_early_data(args, None, frame_actions, close_trigger_frames).await
+}
+
+/// Connect to the socket and execute provided early_actions and
+/// frame_actions.
+///
+/// If `args` contains a session to resume and the connection obtains 0-RTT
+/// keys, `early_actions` are executed before the handshake completes and sent
+/// in 0-RTT packets. Whether each early action was sent this way is recorded
+/// in [`ConnectionSummary::early_data`].
+///
+/// Returns [`ClientError::Other`] if `args` asks for a pcap capture, which
+/// only the sync client supports.
+pub async fn connect_with_early_data(
+    args: &H3iConfig, early_actions: Option<Vec<Action>>,
+    frame_actions: Vec<Action>, close_trigger_frames: Option<CloseTriggerFrames>,
+) -> std::result::Result<BuildingConnectionSummary, ClientError> {
+    // The socket is owned by tokio-quiche, so there is no place to capture
+    // datagrams from.
+    if args.pcap_dir.is_some() {
+        return Err(ClientError::Other(
+            "pcap capture is only supported by the sync client".to_string(),
+        ));
+    }
+
     let quic_settings = create_config(args);
     let mut connection_params =
         ConnectionParams::new_client(quic_settings, None, Hooks::default());
@@ -97,8 +126,21 @@ pub async fn connect(
         socket.local_addr().unwrap()
     );
 
-    let (h3i, conn_summary_fut) =
+    // Without a session to resume there is no early data, so the early
+    // actions are executed once the handshake completes.
+    let (early_actions, frame_actions) = if args.session.is_some() {
+        (Some(early_actions.unwrap_or_default()), frame_actions)
+    } else {
+        let mut actions = early_actions.unwrap_or_default();
+        actions.extend(frame_actions);
+        (None, actions)
+    };
+
+    let (mut h3i, conn_summary_fut) =
         H3iDriver::new(frame_actions, close_trigger_frames);
+
+    h3i.early_actions = early_actions;
+
     match tokio_quiche::quic::connect_with_config(
         Socket::try_from(socket).unwrap(),
         connect_url,
@@ -112,6 +154,44 @@ pub async fn connect(
     }
 }
 
+/// Run a two-phase session resumption scenario.
+///
+/// The first connection executes `priming_actions` in order to obtain a
+/// session ticket from the server. The second connection resumes that session,
+/// sends `early_actions` in 0-RTT and then executes `frame_actions` as usual.
+///
+/// `close_trigger_frames` only apply to the second connection. Returns the
+/// [`ConnectionSummary`] of both connections, in order, or
+/// [`ClientError::Other`] if the first connection didn't yield a session.
+pub async fn connect_with_resumption(
+    args: &H3iConfig, priming_actions: Vec<Action>, early_actions: Vec<Action>,
+    frame_actions: Vec<Action>, close_trigger_frames: Option<CloseTriggerFrames>,
+) -> std::result::Result<(ConnectionSummary, ConnectionSummary), ClientError> {
+    let mut priming_args = args.clone();
+    priming_args.session = None;
+
+    let priming = connect(&priming_args, priming_actions, None).await?.await;
+
+    let Some(session) = priming.conn_close_details.session.clone() else {
+        return Err(ClientError::Other(
+            "no session ticket received on priming connection".to_string(),
+        ));
+    };
+
+    let resumed_args = args.clone().with_session(session).enable_early_data(true);
+
+    let resumed = connect_with_early_data(
+        &resumed_args,
+        Some(early_actions),
+        frame_actions,
+        close_trigger_frames,
+    )
+    .await?
+    .await;
+
+    Ok((priming, resumed))
+}
+
 fn create_config(args: &H3iConfig) -> QuicSettings {
     let mut quic_settings = QuicSettings::default();
 
@@ -139,6 +219,8 @@ fn create_config(args: &H3iConfig) -> QuicSettings {
     quic_settings.keylog_file = std::env::var_os("SSLKEYLOGFILE")
         .and_then(|os_str| os_str.into_string().ok());
 
+    quic_settings.enable_early_data = args.enable_early_data;
+
     quic_settings.enable_dgram = args.enable_dgram;
     quic_settings.dgram_recv_max_queue_len = args.dgram_recv_queue_len;
     quic_settings.dgram_send_max_queue_len = args.dgram_send_queue_len;
@@ -206,6 +288,7 @@ impl Future for BuildingConnectionSummary {
                 ConnectionRecord::ConnectionStats(s) => summary.stats = Some(s),
                 ConnectionRecord::PathStats(ps) => summary.path_stats = ps,
                 ConnectionRecord::Close(d) => summary.conn_close_details = d,
+                ConnectionRecord::EarlyData(e) => summary.early_data = Some(e),
             };
         }
 
@@ -221,6 +304,13 @@ impl Future for BuildingConnectionSummary {
 }
 
 pub struct H3iDriver {
+    /// Actions to send in 0-RTT. `None` if the connection isn't attempting
+    /// resumption.
+    early_actions: Option<Vec<Action>>,
+    early_actions_executed: usize,
+    /// Indices of the early actions queued again once the handshake
+    /// completed. `None` until then.
+    early_actions_replayed: Option<Vec<usize>>,
     actions: Vec<Action>,
     actions_executed: usize,
     next_fire_time: Instant,
@@ -244,6 +334,9 @@ impl H3iDriver {
 
         (
             Self {
+                early_actions: None,
+                early_actions_executed: 0,
+                early_actions_replayed: None,
                 actions,
                 actions_executed: 0,
                 next_fire_time: Instant::now(),
@@ -261,6 +354,66 @@ impl H3iDriver {
         Instant::now() >= self.next_fire_time
     }
 
+    /// Execute early actions while the connection is in early data, stopping
+    /// at the first wait.
+    fn execute_early_actions(&mut self, qconn: &mut QuicheConnection) {
+        let Some(early_actions) = self.early_actions.take() else {
+            return;
+        };
+
+        if self.early_actions_executed == 0 {
+            self.early_actions_executed = execute_early_actions(
+                &early_actions,
+                qconn,
+                self.stream_parsers_mut(),
+            );
+        }
+
+        self.early_actions = Some(early_actions);
+    }
+
+    /// Once the handshake completes, queue the early actions that still need
+    /// to be executed ahead of the remaining actions.
+    ///
+    /// Returns false while the handshake is in progress, so that no regular
+    /// action is executed before the early actions it may depend on.
+    fn replay_early_actions(&mut self, qconn: &QuicheConnection) -> bool {
+        let Some(early_actions) = &self.early_actions else {
+            return true;
+        };
+
+        if self.early_actions_replayed.is_some() {
+            return true;
+        }
+
+        if !qconn.is_established() {
+            return false;
+        }
+
+        let accepted =
+            qconn.early_data_reason() == EarlyDataSummary::EARLY_DATA_ACCEPTED;
+        let replayed = early_actions_to_replay(
+            early_actions,
+            self.early_actions_executed,
+            accepted,
+        );
+
+        if !replayed.is_empty() {
+            log::info!(
+                "h3i: executing {} early action(s) after the handshake",
+                replayed.len()
+            );
+        }
+
+        let at = self.actions_executed;
+        self.actions
+            .splice(at..at, replayed.iter().map(|&i| early_actions[i].clone()));
+
+        self.early_actions_replayed = Some(replayed);
+
+        true
+    }
+
     /// Insert all waits into the waiting set.
     fn register_waits(&mut self) {
         while self.actions_executed < self.actions.len() {
@@ -312,9 +465,18 @@ impl Client for H3iDriver {
 
 impl ApplicationOverQuic for H3iDriver {
     fn on_conn_established(
-        &mut self, _qconn: &mut QuicheConnection, _handshake_info: &HandshakeInfo,
+        &mut self, qconn: &mut QuicheConnection, _handshake_info: &HandshakeInfo,
     ) -> QuicResult<()> {
-        log::info!("h3i: HTTP/3 connection established");
+        // tokio-quiche also calls this once 0-RTT keys are available, before
+        // the handshake is complete.
+        if qconn.is_in_early_data() {
+            log::info!("h3i: connection in early data");
+        } else {
+            log::info!("h3i: HTTP/3 connection established");
+        }
+
+        self.execute_early_actions(qconn);
+
         Ok(())
     }
 
@@ -330,7 +492,9 @@ impl ApplicationOverQuic for H3iDriver {
         // This is executed in process_reads so that work_loop can clear any waits
         // on the current event loop iteration - if it was in process_writes, we
         // could potentially miss waits and hang the client.
-        self.register_waits();
+        if self.replay_early_actions(qconn) {
+            self.register_waits();
+        }
 
         let stream_events = parse_streams(qconn, self);
         for event in stream_events {
@@ -345,6 +509,10 @@ impl ApplicationOverQuic for H3iDriver {
     fn process_writes(&mut self, qconn: &mut QuicheConnection) -> QuicResult<()> {
         log::trace!("h3i: process_writes");
 
+        if !self.replay_early_actions(qconn) {
+            return Ok(());
+        }
+
         if !self.waiting_for_responses.is_empty() {
             log::debug!(
                 "awaiting responses on streams {:?}, skipping further action",
@@ -440,6 +608,18 @@ impl ApplicationOverQuic for H3iDriver {
         let _ = self
             .record_tx
             .send(ConnectionRecord::PathStats(conn_path_stats));
+
+        if let Some(early_actions) = &self.early_actions {
+            let records = EarlyActionRecord::from_executed(
+                early_actions.len(),
+                self.early_actions_executed,
+                self.early_actions_replayed.as_deref().unwrap_or_default(),
+            );
+
+            let _ = self.record_tx.send(ConnectionRecord::EarlyData(
+                EarlyDataSummary::new(qconn, records),
+            ));
+        }
     }
 }
 
@@ -448,4 +628,5 @@ pub enum ConnectionRecord {
     Close(ConnectionCloseDetails),
     PathStats(Vec<PathStats>),
     ConnectionStats(Stats),
+    EarlyData(EarlyDataSummary),
 }
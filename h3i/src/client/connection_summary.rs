@@ -63,6 +63,8 @@ pub struct ConnectionSummary {
     pub path_stats: Vec<PathStats>,
     /// Details about why the connection closed.
     pub conn_close_details: ConnectionCloseDetails,
+    /// The 0-RTT outcome, if the connection attempted to resume a session.
+    pub early_data: Option<EarlyDataSummary>,
 }
 
 impl Serialize for ConnectionSummary {
@@ -70,7 +72,7 @@ impl Serialize for ConnectionSummary {
     where
         S: Serializer,
     {
-        let mut state = s.serialize_struct("path_stats", 4)?;
+        let mut state = s.serialize_struct("path_stats", 6)?;
         state.serialize_field("stream_map", &self.stream_map)?;
         state.serialize_field(
             "stats",
@@ -84,6 +86,7 @@ impl Serialize for ConnectionSummary {
             "missed_close_trigger_frames",
             &self.stream_map.missing_close_trigger_frames(),
         )?;
+        state.serialize_field("early_data", &self.early_data)?;
         state.end()
     }
 }
@@ -166,6 +169,30 @@ impl StreamMap {
             .unwrap_or_default()
     }
 
+    /// The IDs of all streams that frames were received on, in ascending
+    /// order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use h3i::client::connection_summary::StreamMap;
+    /// use h3i::frame::H3iFrame;
+    /// use quiche::h3::Header;
+    ///
+    /// let h = Header::new(b"hello", b"world");
+    /// let headers = H3iFrame::Headers(vec![h].into());
+    ///
+    /// let stream_map: StreamMap =
+    ///     [(4, vec![headers.clone()]), (0, vec![headers])].into();
+    /// assert_eq!(stream_map.stream_ids(), vec![0, 4]);
+    /// ```
+    pub fn stream_ids(&self) -> Vec<u64> {
+        let mut ids: Vec<u64> = self.stream_frame_map.keys().copied().collect();
+        ids.sort_unstable();
+
+        ids
+    }
+
     /// Check if a provided [`H3iFrame`] was received, regardless of what stream
     /// it was received on.
     ///
@@ -460,6 +487,73 @@ impl Serialize for ConnectionCloseDetails {
     }
 }
 
+/// Describes how a connection that attempted resumption used 0-RTT.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct EarlyDataSummary {
+    /// If the connection resumed a previous session.
+    pub resumed: bool,
+    /// The reason why early data was accepted or rejected, as reported by
+    /// [`quiche::Connection::early_data_reason()`].
+    pub early_data_reason: u32,
+    /// One record per early action, in the order they were provided.
+    pub actions: Vec<EarlyActionRecord>,
+}
+
+impl EarlyDataSummary {
+    /// The value of [`Self::early_data_reason`] when the server accepted
+    /// early data.
+    ///
+    /// See BoringSSL's `ssl_early_data_accepted`.
+    pub const EARLY_DATA_ACCEPTED: u32 = 1;
+
+    pub(crate) fn new<F: quiche::BufFactory>(
+        qconn: &quiche::Connection<F>, actions: Vec<EarlyActionRecord>,
+    ) -> Self {
+        Self {
+            resumed: qconn.is_resumed(),
+            early_data_reason: qconn.early_data_reason(),
+            actions,
+        }
+    }
+
+    /// If the server accepted early data.
+    pub fn accepted(&self) -> bool {
+        self.early_data_reason == Self::EARLY_DATA_ACCEPTED
+    }
+}
+
+/// Whether an individual early action was sent in 0-RTT.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct EarlyActionRecord {
+    /// The position of the action in the list of early actions.
+    pub index: usize,
+    /// If the action was executed while the connection was in early data.
+    /// Actions are not executed at all if 0-RTT keys were unavailable, or if
+    /// they come after a [`crate::actions::h3::Action::Wait`].
+    pub sent_in_early_data: bool,
+    /// If the action was executed again once the handshake completed, because
+    /// it was not sent in early data or was lost when the server rejected
+    /// 0-RTT.
+    pub replayed: bool,
+}
+
+impl EarlyActionRecord {
+    /// Builds a record for each of `total` early actions, marking the first
+    /// `executed` of them as sent in early data, and the ones at the
+    /// `replayed` indices as executed again after the handshake.
+    pub(crate) fn from_executed(
+        total: usize, executed: usize, replayed: &[usize],
+    ) -> Vec<Self> {
+        (0..total)
+            .map(|index| EarlyActionRecord {
+                index,
+                sent_in_early_data: index < executed,
+                replayed: replayed.contains(&index),
+            })
+            .collect()
+    }
+}
+
 /// A wrapper to help serialize [quiche::PathStats]
 pub struct SerializablePathStats<'a>(&'a quiche::PathStats);
 
@@ -613,6 +707,19 @@ mod tests {
         vec![headers, data]
     }
 
+    #[test]
+    fn early_action_records() {
+        let records = EarlyActionRecord::from_executed(3, 2, &[2]);
+
+        assert_eq!(records.len(), 3);
+        assert!(records[0].sent_in_early_data);
+        assert!(records[1].sent_in_early_data);
+        assert!(!records[2].sent_in_early_data);
+        assert_eq!(records[2].index, 2);
+        assert!(!records[1].replayed);
+        assert!(records[2].replayed);
+    }
+
     #[test]
     fn test_stream_map_trigger_frames_with_none() {
         let stream_map: StreamMap = vec![(0, stream_map_data())].into();
@@ -0,0 +1,701 @@
+// Copyright (C) 2026, Cloudflare, Inc.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are
+// met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//
+//     * Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in the
+//       documentation and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS
+// IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO,
+// THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR
+// PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Compares the outcome of running the same actions against two servers.
+//!
+//! Behavioural differences between two deployments of an HTTP/3 stack, such as
+//! staging and production, or between two implementations, usually show up as
+//! different frames on a stream or a different close error. [`run`] executes
+//! one action list against a left and a right target, and [`diff_summaries`]
+//! lists every [`Divergence`] between the resulting [`ConnectionSummary`]s.
+
+use std::fmt;
+use std::thread;
+
+use serde::Serialize;
+use serde_json::json;
+use serde_json::Value;
+
+use crate::actions::h3::Action;
+use crate::client::sync_client;
+use crate::client::ClientError;
+use crate::client::CloseTriggerFrames;
+use crate::client::ConnectionSummary;
+use crate::config::Config;
+use crate::frame::H3iFrame;
+use crate::quiche;
+use quiche::h3::frame::Frame as QFrame;
+use quiche::h3::NameValue;
+
+/// Names of the [`SerializableStats`] fields counting packets and bytes.
+///
+/// These depend on packetization, pacing and loss, so they differ between
+/// two runs even when both servers behave the same.
+///
+/// [`SerializableStats`]: crate::client::connection_summary::SerializableStats
+pub const TRAFFIC_STATS: &[&str] = &[
+    "recv",
+    "sent",
+    "lost",
+    "retrans",
+    "sent_bytes",
+    "recv_bytes",
+    "lost_bytes",
+    "stream_retrans_bytes",
+];
+
+/// Controls which parts of two [`ConnectionSummary`]s are compared.
+///
+/// Some values are expected to differ between servers, so by default the
+/// `date` header is masked, frames of unknown type, such as GREASE, are
+/// skipped, and the [`TRAFFIC_STATS`] are not compared.
+#[derive(Clone, Debug)]
+pub struct DiffConfig {
+    /// Names of headers, matched case-insensitively, whose values are not
+    /// compared. The headers are still expected to be present on both sides.
+    pub ignored_headers: Vec<String>,
+    /// Names of [`SerializableStats`] fields that are not compared.
+    ///
+    /// [`SerializableStats`]: crate::client::connection_summary::SerializableStats
+    pub ignored_stats: Vec<String>,
+    /// Whether connection statistics are compared at all.
+    pub compare_stats: bool,
+    /// Whether the [`TRAFFIC_STATS`] are compared.
+    pub compare_traffic_stats: bool,
+    /// Whether frames of unknown type are compared.
+    pub compare_unknown_frames: bool,
+}
+
+impl Default for DiffConfig {
+    fn default() -> Self {
+        Self {
+            ignored_headers: vec!["date".to_string()],
+            ignored_stats: vec![],
+            compare_stats: true,
+            compare_traffic_stats: false,
+            compare_unknown_frames: false,
+        }
+    }
+}
+
+impl DiffConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_ignored_header(mut self, name: &str) -> Self {
+        self.ignored_headers.push(name.to_ascii_lowercase());
+        self
+    }
+
+    pub fn with_ignored_stat(mut self, name: &str) -> Self {
+        self.ignored_stats.push(name.to_string());
+        self
+    }
+
+    pub fn compare_stats(mut self, compare_stats: bool) -> Self {
+        self.compare_stats = compare_stats;
+        self
+    }
+
+    pub fn compare_traffic_stats(mut self, compare_traffic_stats: bool) -> Self {
+        self.compare_traffic_stats = compare_traffic_stats;
+        self
+    }
+
+    pub fn compare_unknown_frames(
+        mut self, compare_unknown_frames: bool,
+    ) -> Self {
+        self.compare_unknown_frames = compare_unknown_frames;
+        self
+    }
+
+    fn is_ignored_header(&self, name: &[u8]) -> bool {
+        self.ignored_headers
+            .iter()
+            .any(|h| h.as_bytes().eq_ignore_ascii_case(name))
+    }
+
+    fn is_ignored_stat(&self, name: &str) -> bool {
+        self.ignored_stats.iter().any(|s| s == name) ||
+            (!self.compare_traffic_stats && TRAFFIC_STATS.contains(&name))
+    }
+}
+
+/// A single difference between the left and right run.
+///
+/// Values are taken from the serialized [`ConnectionSummary`], and `None`
+/// means the value was absent on that side.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Divergence {
+    /// One or both connections failed to produce a summary, for different
+    /// reasons.
+    Connection {
+        left: Option<String>,
+        right: Option<String>,
+    },
+    /// The frame at `index` on a stream differs, or was only received on one
+    /// side.
+    Frame {
+        stream_id: u64,
+        index: usize,
+        left: Option<Value>,
+        right: Option<Value>,
+    },
+    /// The close error of the connection differs. `origin` is either `peer`
+    /// or `local`.
+    CloseError {
+        origin: String,
+        left: Option<Value>,
+        right: Option<Value>,
+    },
+    /// Only one of the connections timed out.
+    TimedOut { left: bool, right: bool },
+    /// A connection statistic differs.
+    Stat {
+        name: String,
+        left: Option<Value>,
+        right: Option<Value>,
+    },
+}
+
+struct Side<'a>(&'a Option<Value>);
+
+impl fmt::Display for Side<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(v) => write!(f, "{v}"),
+            None => write!(f, "<none>"),
+        }
+    }
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Divergence::Connection { left, right } => write!(
+                f,
+                "connection failed\n    left:  {}\n    right: {}",
+                left.as_deref().unwrap_or("<ok>"),
+                right.as_deref().unwrap_or("<ok>"),
+            ),
+
+            Divergence::Frame {
+                stream_id,
+                index,
+                left,
+                right,
+            } => write!(
+                f,
+                "stream {stream_id} frame {index}\n    left:  {}\n    right: {}",
+                Side(left),
+                Side(right),
+            ),
+
+            Divergence::CloseError {
+                origin,
+                left,
+                right,
+            } => write!(
+                f,
+                "{origin} error\n    left:  {}\n    right: {}",
+                Side(left),
+                Side(right),
+            ),
+
+            Divergence::TimedOut { left, right } =>
+                write!(f, "timed out\n    left:  {left}\n    right: {right}"),
+
+            Divergence::Stat { name, left, right } => write!(
+                f,
+                "stats.{name}\n    left:  {}\n    right: {}",
+                Side(left),
+                Side(right),
+            ),
+        }
+    }
+}
+
+/// The outcome of running the same actions against two targets.
+///
+/// [`DiffReport`] implements [Serialize] for machine consumption and
+/// [Display](fmt::Display) for a readable report of the divergences.
+#[derive(Debug, Serialize)]
+pub struct DiffReport {
+    /// The host and port of the left target.
+    pub left_target: String,
+    /// The host and port of the right target.
+    pub right_target: String,
+    /// The left connection's summary, or the error that prevented one.
+    pub left: Result<ConnectionSummary, ClientError>,
+    /// The right connection's summary, or the error that prevented one.
+    pub right: Result<ConnectionSummary, ClientError>,
+    /// Every difference found between the two runs.
+    pub divergences: Vec<Divergence>,
+}
+
+impl DiffReport {
+    /// Builds a report from the results of the left and right connection.
+    pub fn new(
+        left_target: String, left: Result<ConnectionSummary, ClientError>,
+        right_target: String, right: Result<ConnectionSummary, ClientError>,
+        config: &DiffConfig,
+    ) -> Self {
+        let divergences = match (&left, &right) {
+            (Ok(l), Ok(r)) => diff_summaries(l, r, config),
+
+            (l, r) => {
+                let l = l.as_ref().err().map(|e| format!("{e:?}"));
+                let r = r.as_ref().err().map(|e| format!("{e:?}"));
+
+                if l == r {
+                    vec![]
+                } else {
+                    vec![Divergence::Connection { left: l, right: r }]
+                }
+            },
+        };
+
+        Self {
+            left_target,
+            right_target,
+            left,
+            right,
+            divergences,
+        }
+    }
+
+    /// Returns true if no divergences were found.
+    pub fn is_equivalent(&self) -> bool {
+        self.divergences.is_empty()
+    }
+}
+
+impl fmt::Display for DiffReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "left: {}, right: {}: ",
+            self.left_target, self.right_target
+        )?;
+
+        if self.divergences.is_empty() {
+            return write!(f, "no divergences");
+        }
+
+        write!(f, "{} divergence(s)", self.divergences.len())?;
+
+        for d in &self.divergences {
+            write!(f, "\n  - {d}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Lists the differences between two connection summaries.
+///
+/// Frames are compared per stream, in the order they were received. Close
+/// errors and [`SerializableStats`] are compared using their serialized
+/// representation, skipping the [`TRAFFIC_STATS`] unless
+/// [`DiffConfig::compare_traffic_stats`] is set.
+///
+/// [`SerializableStats`]: crate::client::connection_summary::SerializableStats
+pub fn diff_summaries(
+    left: &ConnectionSummary, right: &ConnectionSummary, config: &DiffConfig,
+) -> Vec<Divergence> {
+    let mut divergences = vec![];
+
+    diff_streams(left, right, config, &mut divergences);
+
+    let left_value = serde_json::to_value(left).unwrap_or_default();
+    let right_value = serde_json::to_value(right).unwrap_or_default();
+
+    diff_close(
+        &left_value["error"],
+        &right_value["error"],
+        &mut divergences,
+    );
+
+    if config.compare_stats {
+        diff_stats(
+            &left_value["stats"],
+            &right_value["stats"],
+            config,
+            &mut divergences,
+        );
+    }
+
+    divergences
+}
+
+fn diff_streams(
+    left: &ConnectionSummary, right: &ConnectionSummary, config: &DiffConfig,
+    divergences: &mut Vec<Divergence>,
+) {
+    let mut stream_ids = left.stream_map.stream_ids();
+    stream_ids.extend(right.stream_map.stream_ids());
+    stream_ids.sort_unstable();
+    stream_ids.dedup();
+
+    for stream_id in stream_ids {
+        let normalize = |summary: &ConnectionSummary| -> Vec<Value> {
+            summary
+                .stream_map
+                .stream(stream_id)
+                .iter()
+                .filter_map(|f| normalize_frame(f, config))
+                .collect()
+        };
+
+        let l = normalize(left);
+        let r = normalize(right);
+
+        for index in 0..l.len().max(r.len()) {
+            let (l, r) = (l.get(index), r.get(index));
+
+            if l != r {
+                divergences.push(Divergence::Frame {
+                    stream_id,
+                    index,
+                    left: l.cloned(),
+                    right: r.cloned(),
+                });
+            }
+        }
+    }
+}
+
+/// Converts a frame into the value that is compared, or `None` if the frame
+/// should be skipped.
+fn normalize_frame(frame: &H3iFrame, config: &DiffConfig) -> Option<Value> {
+    match frame {
+        // The header block length depends on the peer's QPACK encoder, so only
+        // the decoded headers are compared.
+        H3iFrame::Headers(enriched) => {
+            let headers: Vec<Value> = enriched
+                .headers()
+                .iter()
+                .map(|h| {
+                    let value = if config.is_ignored_header(h.name()) {
+                        "<ignored>".into()
+                    } else {
+                        String::from_utf8_lossy(h.value())
+                    };
+
+                    json!({
+                        "name": String::from_utf8_lossy(h.name()),
+                        "value": value,
+                    })
+                })
+                .collect();
+
+            Some(json!({ "headers": headers }))
+        },
+
+        H3iFrame::QuicheH3(QFrame::Unknown { .. })
+            if !config.compare_unknown_frames =>
+            None,
+
+        f => serde_json::to_value(f).ok(),
+    }
+}
+
+fn diff_close(left: &Value, right: &Value, divergences: &mut Vec<Divergence>) {
+    for origin in ["peer", "local"] {
+        let key = format!("{origin}_error");
+        let (l, r) = (left.get(&key), right.get(&key));
+
+        if l != r {
+            divergences.push(Divergence::CloseError {
+                origin: origin.to_string(),
+                left: l.cloned(),
+                right: r.cloned(),
+            });
+        }
+    }
+
+    let timed_out = |v: &Value| v["timed_out"].as_bool().unwrap_or_default();
+
+    if timed_out(left) != timed_out(right) {
+        divergences.push(Divergence::TimedOut {
+            left: timed_out(left),
+            right: timed_out(right),
+        });
+    }
+}
+
+fn diff_stats(
+    left: &Value, right: &Value, config: &DiffConfig,
+    divergences: &mut Vec<Divergence>,
+) {
+    let (Some(l), Some(r)) = (left.as_object(), right.as_object()) else {
+        if left != right {
+            divergences.push(Divergence::Stat {
+                name: "*".to_string(),
+                left: Some(left.clone()).filter(|v| !v.is_null()),
+                right: Some(right.clone()).filter(|v| !v.is_null()),
+            });
+        }
+
+        return;
+    };
+
+    let mut names: Vec<&String> = l.keys().chain(r.keys()).collect();
+    names.sort_unstable();
+    names.dedup();
+
+    for name in names {
+        if config.is_ignored_stat(name) {
+            continue;
+        }
+
+        let (lv, rv) = (l.get(name), r.get(name));
+
+        if lv != rv {
+            divergences.push(Divergence::Stat {
+                name: name.clone(),
+                left: lv.cloned(),
+                right: rv.cloned(),
+            });
+        }
+    }
+}
+
+/// Run the same actions against a left and a right target and compare the
+/// outcomes.
+///
+/// Both connections are made concurrently with the
+/// [sync client](crate::client::sync_client), using their respective
+/// configuration. Blocks until both connections have finished.
+pub fn run(
+    left: &Config, right: &Config, actions: &[Action],
+    close_trigger_frames: Option<CloseTriggerFrames>, config: &DiffConfig,
+) -> DiffReport {
+    let connect = |args: &Config| {
+        let args = args.clone();
+        let actions = actions.to_vec();
+        let close_trigger_frames = close_trigger_frames.clone();
+
+        move || sync_client::connect(args, actions, close_trigger_frames)
+    };
+
+    let (left_summary, right_summary) = thread::scope(|scope| {
+        let l = scope.spawn(connect(left));
+        let r = scope.spawn(connect(right));
+
+        let join = |h: thread::ScopedJoinHandle<_>| {
+            h.join().unwrap_or_else(|_| {
+                Err(ClientError::Other("connection thread panicked".into()))
+            })
+        };
+
+        (join(l), join(r))
+    });
+
+    DiffReport::new(
+        left.host_port.clone(),
+        left_summary,
+        right.host_port.clone(),
+        right_summary,
+        config,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::connection_summary::StreamMap;
+    use quiche::h3::Header;
+
+    fn summary(frames: Vec<(u64, Vec<H3iFrame>)>) -> ConnectionSummary {
+        ConnectionSummary {
+            stream_map: StreamMap::from(frames),
+            ..Default::default()
+        }
+    }
+
+    fn response(status: &[u8], date: &[u8]) -> H3iFrame {
+        vec![Header::new(b":status", status), Header::new(b"date", date)].into()
+    }
+
+    #[test]
+    fn identical_streams_ignore_date() {
+        let left = summary(vec![(0, vec![response(b"200", b"Mon")])]);
+        let right = summary(vec![(0, vec![response(b"200", b"Tue")])]);
+
+        let config = DiffConfig::new();
+        assert!(diff_summaries(&left, &right, &config).is_empty());
+    }
+
+    #[test]
+    fn frame_divergences() {
+        let data = H3iFrame::QuicheH3(QFrame::Data {
+            payload: b"hi".to_vec(),
+        });
+        let grease = H3iFrame::QuicheH3(QFrame::Unknown {
+            raw_type: 0x21,
+            payload: vec![],
+        });
+
+        let left = summary(vec![
+            (0, vec![response(b"200", b""), data.clone()]),
+            (3, vec![grease]),
+        ]);
+        let right =
+            summary(vec![(0, vec![response(b"404", b"")]), (4, vec![data])]);
+
+        let d = diff_summaries(&left, &right, &DiffConfig::new());
+        assert_eq!(d.len(), 3);
+
+        assert!(matches!(d[0], Divergence::Frame {
+            stream_id: 0,
+            index: 0,
+            ..
+        }));
+        assert!(matches!(d[1], Divergence::Frame {
+            stream_id: 0,
+            index: 1,
+            right: None,
+            ..
+        }));
+        assert!(matches!(d[2], Divergence::Frame {
+            stream_id: 4,
+            index: 0,
+            left: None,
+            ..
+        }));
+
+        let config = DiffConfig::new().compare_unknown_frames(true);
+        assert_eq!(diff_summaries(&left, &right, &config).len(), 4);
+    }
+
+    #[test]
+    fn close_and_stats_divergences() {
+        let mut d = vec![];
+
+        let left = json!({
+            "peer_error": { "is_app": true, "error_code": 256, "reason": "" },
+            "timed_out": false,
+        });
+        let right = json!({ "timed_out": true });
+        diff_close(&left, &right, &mut d);
+
+        assert_eq!(d.len(), 2);
+        assert!(matches!(&d[0], Divergence::CloseError { right: None, .. }));
+        assert_eq!(d[0].to_string().lines().next(), Some("peer error"));
+        assert_eq!(d[1], Divergence::TimedOut {
+            left: false,
+            right: true,
+        });
+
+        let left = json!({ "recv": 10, "paths_count": 1, "lost": 0 });
+        let right = json!({ "recv": 12, "paths_count": 2, "lost": 1 });
+
+        let mut d = vec![];
+        diff_stats(&left, &right, &DiffConfig::new(), &mut d);
+
+        assert_eq!(d, vec![Divergence::Stat {
+            name: "paths_count".to_string(),
+            left: Some(json!(1)),
+            right: Some(json!(2)),
+        }]);
+
+        let mut d = vec![];
+        let config = DiffConfig::new()
+            .compare_traffic_stats(true)
+            .with_ignored_stat("lost")
+            .with_ignored_stat("paths_count");
+        diff_stats(&left, &right, &config, &mut d);
+
+        assert_eq!(d, vec![Divergence::Stat {
+            name: "recv".to_string(),
+            left: Some(json!(10)),
+            right: Some(json!(12)),
+        }]);
+    }
+
+    #[test]
+    fn different_packet_counts_are_equivalent() {
+        let stats = |sent, sent_bytes| {
+            let mut stats = quiche::Stats::default();
+            stats.sent = sent;
+            stats.sent_bytes = sent_bytes;
+            stats.recv = sent + 1;
+            stats.paths_count = 1;
+            stats
+        };
+
+        let frames = vec![(0, vec![response(b"200", b"Mon")])];
+        let left = ConnectionSummary {
+            stats: Some(stats(10, 12_000)),
+            ..summary(frames.clone())
+        };
+        let right = ConnectionSummary {
+            stats: Some(stats(14, 15_000)),
+            ..summary(frames)
+        };
+
+        let report = DiffReport::new(
+            "a".into(),
+            Ok(left),
+            "b".into(),
+            Ok(right),
+            &DiffConfig::new(),
+        );
+        assert!(report.is_equivalent());
+
+        let left = report.left.unwrap();
+        let right = report.right.unwrap();
+        let config = DiffConfig::new().compare_traffic_stats(true);
+        assert_eq!(diff_summaries(&left, &right, &config).len(), 3);
+    }
+
+    #[test]
+    fn failed_connections() {
+        let config = DiffConfig::new();
+
+        let report = DiffReport::new(
+            "a".into(),
+            Err(ClientError::HandshakeFail),
+            "b".into(),
+            Err(ClientError::HandshakeFail),
+            &config,
+        );
+        assert!(report.is_equivalent());
+
+        let report = DiffReport::new(
+            "a".into(),
+            Err(ClientError::HandshakeFail),
+            "b".into(),
+            Ok(ConnectionSummary::default()),
+            &config,
+        );
+        assert!(!report.is_equivalent());
+        assert!(report.to_string().contains("1 divergence(s)"));
+    }
+}
@@ -0,0 +1,605 @@
+// Copyright (C) 2026, Cloudflare, Inc.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are
+// met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//
+//     * Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in the
+//       documentation and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS
+// IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO,
+// THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR
+// PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Drives many concurrent h3i connections against the same server.
+//!
+//! Some server behaviour only shows up when many clients misbehave at once.
+//! [`run`] starts [`LoadConfig::connections`] instances of the
+//! [sync client](crate::client::sync_client), each on its own thread, spread
+//! over the [`LoadConfig::ramp_up`] period. At most
+//! [`LoadConfig::max_concurrency`] connections run at the same time. Every
+//! connection can be given its own action list, and the outcome of all of them
+//! is collected into a single [`LoadSummary`].
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+use serde::Serialize;
+use serde_with::serde_as;
+
+use crate::actions::h3::Action;
+use crate::client::sync_client;
+use crate::client::ClientError;
+use crate::client::CloseTriggerFrames;
+use crate::client::ConnectionSummary;
+use crate::config::Config;
+
+/// The default maximum number of connections running at the same time.
+pub const DEFAULT_MAX_CONCURRENCY: usize = 256;
+
+/// How to pick the local UDP port for each connection.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum SourcePorts {
+    /// Use the port from the base [`Config`] for every connection. A value of
+    /// 0 lets the OS choose an ephemeral port.
+    #[default]
+    FromConfig,
+    /// Connection `i` binds to `base + i`.
+    Sequential(u32),
+    /// Connection `i` binds to the `i`th entry of the list. Connections
+    /// beyond the end of the list use an ephemeral port.
+    List(Vec<u32>),
+}
+
+impl SourcePorts {
+    /// Checks that every connection of a run of `connections` connections
+    /// gets a valid UDP port.
+    pub fn validate(&self, connections: usize) -> Result<(), String> {
+        let max = u64::from(u16::MAX);
+
+        match self {
+            SourcePorts::FromConfig => Ok(()),
+
+            SourcePorts::Sequential(base) => {
+                let last =
+                    u64::from(*base) + connections.saturating_sub(1) as u64;
+
+                if last > max {
+                    return Err(format!(
+                        "{connections} connections from port {base} exceed port {max}"
+                    ));
+                }
+
+                Ok(())
+            },
+
+            SourcePorts::List(ports) =>
+                match ports.iter().find(|p| u64::from(**p) > max) {
+                    Some(port) => Err(format!("port {port} exceeds {max}")),
+
+                    None => Ok(()),
+                },
+        }
+    }
+
+    fn port_for(&self, index: usize, default: u32) -> u32 {
+        match self {
+            SourcePorts::FromConfig => default,
+            SourcePorts::Sequential(base) => base + index as u32,
+            SourcePorts::List(ports) => ports.get(index).copied().unwrap_or(0),
+        }
+    }
+}
+
+/// Parameters for a multi-connection run.
+#[derive(Clone, Debug)]
+pub struct LoadConfig {
+    /// Total number of connections to open.
+    pub connections: usize,
+    /// The period over which connection starts are evenly spread. A zero
+    /// duration starts all connections at once.
+    pub ramp_up: Duration,
+    /// How each connection picks its source port.
+    pub source_ports: SourcePorts,
+    /// The maximum number of connections running at the same time. A
+    /// connection that is due while the limit is reached starts as soon as
+    /// another one finishes.
+    pub max_concurrency: usize,
+}
+
+impl LoadConfig {
+    /// Construct a new config object with default values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_connections(mut self, connections: usize) -> Self {
+        self.connections = connections;
+        self
+    }
+
+    pub fn with_ramp_up(mut self, ramp_up: Duration) -> Self {
+        self.ramp_up = ramp_up;
+        self
+    }
+
+    pub fn with_source_ports(mut self, source_ports: SourcePorts) -> Self {
+        self.source_ports = source_ports;
+        self
+    }
+
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency;
+        self
+    }
+
+    /// The delay, relative to the start of the run, after which connection
+    /// `index` is started.
+    fn start_offset(&self, index: usize) -> Duration {
+        if self.connections <= 1 {
+            return Duration::ZERO;
+        }
+
+        self.ramp_up.mul_f64(index as f64 / self.connections as f64)
+    }
+}
+
+impl Default for LoadConfig {
+    fn default() -> Self {
+        Self {
+            connections: 1,
+            ramp_up: Duration::ZERO,
+            source_ports: SourcePorts::default(),
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+        }
+    }
+}
+
+/// The outcome of a single connection in a multi-connection run.
+#[serde_as]
+#[derive(Debug, Serialize)]
+pub struct ConnectionResult {
+    /// The index of the connection, in start order.
+    pub index: usize,
+    /// The source port the connection was asked to bind to.
+    pub source_port: u32,
+    /// When the connection was started, relative to the start of the run.
+    #[serde_as(as = "serde_with::DurationMilliSecondsWithFrac<f64>")]
+    pub start_offset: Duration,
+    /// The time from starting the connection until it was closed.
+    #[serde_as(as = "serde_with::DurationMilliSecondsWithFrac<f64>")]
+    pub latency: Duration,
+    /// The connection's summary, or the error that prevented one.
+    pub summary: Result<ConnectionSummary, ClientError>,
+    /// Whether the connection's thread panicked. The summary is then an
+    /// error, and the latency is zero.
+    pub panicked: bool,
+}
+
+impl ConnectionResult {
+    /// A result for a connection that never got to run.
+    fn failed(
+        index: usize, source_port: u32, start_offset: Duration, error: String,
+        panicked: bool,
+    ) -> Self {
+        ConnectionResult {
+            index,
+            source_port,
+            start_offset,
+            latency: Duration::ZERO,
+            summary: Err(ClientError::Other(error)),
+            panicked,
+        }
+    }
+}
+
+/// Latency distribution across the connections of a run.
+#[serde_as]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct LatencyPercentiles {
+    #[serde_as(as = "serde_with::DurationMilliSecondsWithFrac<f64>")]
+    pub min: Duration,
+    #[serde_as(as = "serde_with::DurationMilliSecondsWithFrac<f64>")]
+    pub p50: Duration,
+    #[serde_as(as = "serde_with::DurationMilliSecondsWithFrac<f64>")]
+    pub p90: Duration,
+    #[serde_as(as = "serde_with::DurationMilliSecondsWithFrac<f64>")]
+    pub p99: Duration,
+    #[serde_as(as = "serde_with::DurationMilliSecondsWithFrac<f64>")]
+    pub max: Duration,
+}
+
+impl LatencyPercentiles {
+    /// Computes nearest-rank percentiles over `latencies`. Returns `None` if
+    /// `latencies` is empty.
+    pub fn from_latencies(latencies: &[Duration]) -> Option<Self> {
+        if latencies.is_empty() {
+            return None;
+        }
+
+        let mut sorted = latencies.to_vec();
+        sorted.sort_unstable();
+
+        let rank = |p: f64| {
+            let idx = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+            sorted[idx.saturating_sub(1).min(sorted.len() - 1)]
+        };
+
+        Some(Self {
+            min: sorted[0],
+            p50: rank(50.0),
+            p90: rank(90.0),
+            p99: rank(99.0),
+            max: sorted[sorted.len() - 1],
+        })
+    }
+}
+
+/// The aggregated outcome of a multi-connection run.
+///
+/// [`LoadSummary`] implements [Serialize], with every per-connection
+/// [`ConnectionSummary`] embedded in [`LoadSummary::connections`].
+#[derive(Debug, Default, Serialize)]
+pub struct LoadSummary {
+    /// Number of connections that produced a [`ConnectionSummary`].
+    pub completed: usize,
+    /// Number of connections that failed the handshake.
+    pub handshake_failures: usize,
+    /// Number of connections that failed for any other reason.
+    pub other_failures: usize,
+    /// Number of connections whose thread panicked. These are also counted
+    /// in [`LoadSummary::other_failures`].
+    pub panicked: usize,
+    /// Number of connections that closed due to the idle timeout.
+    pub timed_out: usize,
+    /// Count of CONNECTION_CLOSE error codes received from the peer.
+    pub peer_error_codes: BTreeMap<u64, usize>,
+    /// Count of CONNECTION_CLOSE error codes sent by h3i.
+    pub local_error_codes: BTreeMap<u64, usize>,
+    /// Latency percentiles over the completed connections.
+    pub latency: Option<LatencyPercentiles>,
+    /// Per-connection results, ordered by connection index.
+    pub connections: Vec<ConnectionResult>,
+}
+
+impl LoadSummary {
+    fn new(mut connections: Vec<ConnectionResult>) -> Self {
+        connections.sort_by_key(|c| c.index);
+
+        let mut summary = LoadSummary::default();
+        let mut latencies = Vec::with_capacity(connections.len());
+
+        for conn in &connections {
+            match &conn.summary {
+                Ok(s) => {
+                    summary.completed += 1;
+                    latencies.push(conn.latency);
+
+                    let details = &s.conn_close_details;
+                    if details.timed_out {
+                        summary.timed_out += 1;
+                    }
+
+                    if let Some(e) = details.peer_error() {
+                        *summary
+                            .peer_error_codes
+                            .entry(e.error_code)
+                            .or_default() += 1;
+                    }
+
+                    if let Some(e) = details.local_error() {
+                        *summary
+                            .local_error_codes
+                            .entry(e.error_code)
+                            .or_default() += 1;
+                    }
+                },
+
+                Err(ClientError::HandshakeFail) =>
+                    summary.handshake_failures += 1,
+
+                Err(_) => summary.other_failures += 1,
+            }
+
+            if conn.panicked {
+                summary.panicked += 1;
+            }
+        }
+
+        summary.latency = LatencyPercentiles::from_latencies(&latencies);
+        summary.connections = connections;
+
+        summary
+    }
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> &str {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg
+    } else {
+        "unknown panic"
+    }
+}
+
+/// Notifies the driver of a run when a connection thread exits, including
+/// when it panics.
+struct ExitNotifier {
+    index: usize,
+    tx: mpsc::Sender<usize>,
+}
+
+impl Drop for ExitNotifier {
+    fn drop(&mut self) {
+        let _ = self.tx.send(self.index);
+    }
+}
+
+/// A connection whose thread is running.
+struct Running<'scope> {
+    source_port: u32,
+    start_offset: Duration,
+    handle: thread::ScopedJoinHandle<'scope, ConnectionResult>,
+}
+
+impl Running<'_> {
+    fn join(self, index: usize) -> ConnectionResult {
+        self.handle.join().unwrap_or_else(|payload| {
+            let msg = panic_message(payload.as_ref());
+            log::error!("load: connection {index} panicked: {msg}");
+
+            ConnectionResult::failed(
+                index,
+                self.source_port,
+                self.start_offset,
+                format!("connection thread panicked: {msg}"),
+                true,
+            )
+        })
+    }
+}
+
+/// Connect to a server many times concurrently.
+///
+/// `actions_for` is called with the index of each connection and returns the
+/// actions that connection executes, which allows either the same or a
+/// parameterized action list per connection. Each connection otherwise uses
+/// `args`, with the source port overridden according to
+/// [`LoadConfig::source_ports`].
+///
+/// Each connection thread is spawned once its start offset is due and fewer
+/// than [`LoadConfig::max_concurrency`] connections are running. A connection
+/// whose thread can't be spawned is counted as failed.
+///
+/// Blocks until every connection has finished.
+pub fn run<F>(
+    args: &Config, load: &LoadConfig, actions_for: F,
+    close_trigger_frames: Option<CloseTriggerFrames>,
+) -> LoadSummary
+where
+    F: Fn(usize) -> Vec<Action> + Sync,
+{
+    let start = Instant::now();
+    let max_concurrency = load.max_concurrency.max(1);
+
+    let results = thread::scope(|scope| {
+        let (exit_tx, exit_rx) = mpsc::channel();
+
+        let mut running: HashMap<usize, Running> = HashMap::new();
+        let mut results = Vec::with_capacity(load.connections);
+
+        for index in 0..load.connections {
+            // Wait for a connection to exit before starting another one.
+            while running.len() >= max_concurrency {
+                let exited = exit_rx.recv().expect("exit sender is alive");
+
+                if let Some(conn) = running.remove(&exited) {
+                    results.push(conn.join(exited));
+                }
+            }
+
+            let start_offset = load.start_offset(index);
+            thread::sleep(start_offset.saturating_sub(start.elapsed()));
+
+            let source_port = load.source_ports.port_for(index, args.source_port);
+            let config = args.clone().with_source_port(source_port);
+            let close_trigger_frames = close_trigger_frames.clone();
+            let actions_for = &actions_for;
+            let notifier = ExitNotifier {
+                index,
+                tx: exit_tx.clone(),
+            };
+
+            let spawned = thread::Builder::new()
+                .name(format!("h3i-load-{index}"))
+                .spawn_scoped(scope, move || {
+                    let _notifier = notifier;
+
+                    log::info!(
+                        "load: starting connection {index} from port {source_port}"
+                    );
+
+                    let actions = actions_for(index);
+                    let conn_start = Instant::now();
+                    let summary = sync_client::connect(
+                        config,
+                        actions,
+                        close_trigger_frames,
+                    );
+
+                    ConnectionResult {
+                        index,
+                        source_port,
+                        start_offset,
+                        latency: conn_start.elapsed(),
+                        summary,
+                        panicked: false,
+                    }
+                });
+
+            match spawned {
+                Ok(handle) => {
+                    running.insert(index, Running {
+                        source_port,
+                        start_offset,
+                        handle,
+                    });
+                },
+
+                Err(e) => {
+                    log::error!(
+                        "load: failed to spawn thread for connection {index}: {e}"
+                    );
+
+                    results.push(ConnectionResult::failed(
+                        index,
+                        source_port,
+                        start_offset,
+                        format!("failed to spawn connection thread: {e}"),
+                        false,
+                    ));
+                },
+            }
+        }
+
+        results.extend(running.into_iter().map(|(index, conn)| conn.join(index)));
+
+        results
+    });
+
+    LoadSummary::new(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles() {
+        let latencies: Vec<Duration> =
+            (1..=100).rev().map(Duration::from_millis).collect();
+
+        let p = LatencyPercentiles::from_latencies(&latencies).unwrap();
+        assert_eq!(p.min, Duration::from_millis(1));
+        assert_eq!(p.p50, Duration::from_millis(50));
+        assert_eq!(p.p90, Duration::from_millis(90));
+        assert_eq!(p.p99, Duration::from_millis(99));
+        assert_eq!(p.max, Duration::from_millis(100));
+
+        let single = LatencyPercentiles::from_latencies(&[Duration::ZERO]);
+        assert_eq!(single.unwrap().p99, Duration::ZERO);
+
+        assert!(LatencyPercentiles::from_latencies(&[]).is_none());
+    }
+
+    #[test]
+    fn ramp_up_and_source_ports() {
+        let load = LoadConfig::new()
+            .with_connections(4)
+            .with_ramp_up(Duration::from_millis(400))
+            .with_source_ports(SourcePorts::Sequential(5000));
+
+        assert_eq!(load.start_offset(0), Duration::ZERO);
+        assert_eq!(load.start_offset(3), Duration::from_millis(300));
+        assert_eq!(load.source_ports.port_for(3, 0), 5003);
+
+        let list = SourcePorts::List(vec![1234]);
+        assert_eq!(list.port_for(0, 0), 1234);
+        assert_eq!(list.port_for(1, 0), 0);
+        assert_eq!(SourcePorts::FromConfig.port_for(1, 42), 42);
+    }
+
+    #[test]
+    fn source_port_ranges() {
+        assert!(SourcePorts::Sequential(65535).validate(1).is_ok());
+        assert!(SourcePorts::Sequential(65535).validate(0).is_ok());
+        assert!(SourcePorts::Sequential(65530).validate(6).is_ok());
+        assert!(SourcePorts::Sequential(65530).validate(7).is_err());
+        assert!(SourcePorts::Sequential(70000).validate(1).is_err());
+
+        assert!(SourcePorts::List(vec![1, 65535]).validate(2).is_ok());
+        assert!(SourcePorts::List(vec![1, 65536]).validate(2).is_err());
+        assert!(SourcePorts::FromConfig.validate(100_000).is_ok());
+    }
+
+    #[test]
+    fn panicked_connections_are_failures() {
+        let args = Config::new();
+        let load = LoadConfig::new().with_connections(2);
+
+        // Panic before connecting.
+        let summary = run(
+            &args,
+            &load,
+            |index| panic!("no actions for connection {index}"),
+            None,
+        );
+
+        assert_eq!(summary.connections.len(), 2);
+        assert_eq!(summary.completed, 0);
+        assert_eq!(summary.panicked, 2);
+        assert_eq!(summary.other_failures, 2);
+
+        let conn = &summary.connections[1];
+        assert_eq!(conn.index, 1);
+        assert!(conn.panicked);
+        assert!(matches!(
+            &conn.summary,
+            Err(ClientError::Other(msg))
+                if msg.contains("no actions for connection 1")
+        ));
+    }
+
+    #[test]
+    fn concurrency_is_capped() {
+        use std::sync::atomic::AtomicUsize;
+        use std::sync::atomic::Ordering;
+
+        let args = Config::new();
+        let load = LoadConfig::new()
+            .with_connections(6)
+            .with_max_concurrency(2);
+
+        let active = AtomicUsize::new(0);
+        let max_active = AtomicUsize::new(0);
+
+        // Panic before connecting, once the connection has been active for a
+        // while.
+        let summary = run(
+            &args,
+            &load,
+            |index| {
+                let now_active = active.fetch_add(1, Ordering::SeqCst) + 1;
+                max_active.fetch_max(now_active, Ordering::SeqCst);
+
+                thread::sleep(Duration::from_millis(20));
+
+                active.fetch_sub(1, Ordering::SeqCst);
+                panic!("no actions for connection {index}");
+            },
+            None,
+        );
+
+        assert_eq!(summary.connections.len(), 6);
+        assert_eq!(summary.panicked, 6);
+        assert_eq!(max_active.load(Ordering::SeqCst), 2);
+    }
+}
@@ -33,6 +33,8 @@
 #[cfg(feature = "async")]
 pub mod async_client;
 pub mod connection_summary;
+pub mod diff;
+pub mod load;
 pub mod sync_client;
 
 use connection_summary::*;
@@ -148,6 +150,73 @@ fn validate_stream_send_result(
     }
 }
 
+/// Splits `actions` into the actions to send in 0-RTT and the actions to
+/// execute once the handshake completes.
+///
+/// Early data can only carry actions up to the first [`Action::Wait`], since
+/// the responses they wait for only arrive after the handshake. The returned
+/// early actions are the ones before it, and the regular actions start with
+/// the wait itself.
+pub fn split_early_actions(
+    mut actions: Vec<Action>,
+) -> (Vec<Action>, Vec<Action>) {
+    let first_wait = actions
+        .iter()
+        .position(|a| matches!(a, Action::Wait { .. }))
+        .unwrap_or(actions.len());
+
+    let actions_after_wait = actions.split_off(first_wait);
+
+    (actions, actions_after_wait)
+}
+
+/// Executes `early_actions` while the connection is in early data, stopping at
+/// the first [`Action::Wait`]. Returns the number of actions executed.
+pub(crate) fn execute_early_actions<F: quiche::BufFactory>(
+    early_actions: &[Action], conn: &mut quiche::Connection<F>,
+    stream_parsers: &mut StreamParserMap,
+) -> usize {
+    if !conn.is_in_early_data() {
+        log::info!("0-RTT keys unavailable, not sending early actions");
+        return 0;
+    }
+
+    let mut executed = 0;
+
+    for action in early_actions {
+        match action {
+            Action::Wait { .. } => break,
+            Action::FlushPackets => {},
+            action => execute_action(action, conn, stream_parsers),
+        }
+
+        executed += 1;
+    }
+
+    executed
+}
+
+/// Returns the indices of the early actions that need to be executed again
+/// once the handshake completes.
+///
+/// These are the actions that were not executed in early data, and the
+/// datagrams sent in 0-RTT packets the server rejected. Stream data and
+/// control frames from rejected 0-RTT packets are retransmitted by quiche, so
+/// executing those actions again would send them twice.
+pub(crate) fn early_actions_to_replay(
+    early_actions: &[Action], executed: usize, accepted: bool,
+) -> Vec<usize> {
+    early_actions
+        .iter()
+        .enumerate()
+        .filter(|(i, action)| {
+            *i >= executed ||
+                (!accepted && matches!(action, Action::SendDatagram { .. }))
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
 pub(crate) fn execute_action<F: quiche::BufFactory>(
     action: &Action, conn: &mut quiche::Connection<F>,
     stream_parsers: &mut StreamParserMap,
@@ -596,3 +665,60 @@ fn resolve_socket_addrs(args: &Config) -> (SocketAddr, SocketAddr) {
         bind_addr.parse().expect("unable to parse bind address"),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::h3::send_headers_frame;
+    use crate::actions::h3::WaitType;
+    use quiche::h3::Header;
+    use std::time::Duration;
+
+    fn request(stream_id: u64) -> Action {
+        send_headers_frame(stream_id, true, vec![Header::new(b":method", b"GET")])
+    }
+
+    fn wait() -> Action {
+        Action::Wait {
+            wait_type: WaitType::WaitDuration(Duration::from_millis(10)),
+        }
+    }
+
+    #[test]
+    fn split_early_actions_at_first_wait() {
+        let actions = vec![request(0), request(4), wait(), request(8), wait()];
+
+        let (early, rest) = split_early_actions(actions);
+
+        assert_eq!(early, vec![request(0), request(4)]);
+        assert_eq!(rest, vec![wait(), request(8), wait()]);
+    }
+
+    #[test]
+    fn split_early_actions_without_wait() {
+        let (early, rest) = split_early_actions(vec![request(0)]);
+        assert_eq!(early, vec![request(0)]);
+        assert!(rest.is_empty());
+
+        let (early, rest) = split_early_actions(vec![wait(), request(0)]);
+        assert!(early.is_empty());
+        assert_eq!(rest, vec![wait(), request(0)]);
+    }
+
+    #[test]
+    fn replay_early_actions() {
+        let datagram = Action::SendDatagram {
+            payload: vec![1, 2, 3],
+        };
+        let early = vec![request(0), datagram, wait(), request(4)];
+
+        // Accepted, everything before the wait was sent in 0-RTT.
+        assert_eq!(early_actions_to_replay(&early, 2, true), vec![2, 3]);
+
+        // Rejected, the datagram is lost but quiche resends the request.
+        assert_eq!(early_actions_to_replay(&early, 2, false), vec![1, 2, 3]);
+
+        // No 0-RTT keys, nothing was sent.
+        assert_eq!(early_actions_to_replay(&early, 0, false), vec![0, 1, 2, 3]);
+    }
+}
@@ -26,10 +26,13 @@
 
 //! Responsible for creating a [quiche::Connection] and managing I/O.
 
-use std::slice::Iter;
 use std::time::Duration;
 use std::time::Instant;
 
+use pcapng_writer::capture_datagram;
+use pcapng_writer::keylog_writer;
+use pcapng_writer::make_pcap_writer;
+use pcapng_writer::Direction;
 use ring::rand::*;
 
 use crate::client::QUIC_VERSION;
@@ -40,7 +43,9 @@ use crate::actions::h3::Action;
 use crate::actions::h3::StreamEventType;
 use crate::actions::h3::WaitType;
 use crate::actions::h3::WaitingFor;
+use crate::client::early_actions_to_replay;
 use crate::client::execute_action;
+use crate::client::execute_early_actions;
 use crate::client::parse_streams;
 use crate::client::ClientError;
 use crate::client::ConnectionCloseDetails;
@@ -51,6 +56,8 @@ use super::parse_args;
 use super::Client;
 use super::CloseTriggerFrames;
 use super::ConnectionSummary;
+use super::EarlyActionRecord;
+use super::EarlyDataSummary;
 use super::ParsedArgs;
 use super::StreamMap;
 use super::StreamParserMap;
@@ -143,6 +150,11 @@ pub fn connect(
 
 /// Connect to a server and execute provided early_action and actions.
 ///
+/// If `args` contains a session to resume and the connection obtains 0-RTT
+/// keys, `early_actions` are executed before the handshake completes and sent
+/// in 0-RTT packets. Whether each early action was sent this way is recorded
+/// in [`ConnectionSummary::early_data`].
+///
 /// See `connect` for additional documentation.
 pub fn connect_with_early_data(
     args: Config, early_actions: Option<Vec<Action>>, actions: Vec<Action>,
@@ -179,7 +191,9 @@ pub fn connect_with_early_data(
         keylog = Some(file);
     }
 
-    let mut config = create_config(&args, keylog.is_some());
+    // Secrets are embedded in the capture, so they need to be logged too.
+    let mut config =
+        create_config(&args, keylog.is_some() || args.pcap_dir.is_some());
 
     // Generate a random source connection ID for the connection.
     let mut scid = [0; quiche::MAX_CONN_ID_LEN];
@@ -203,10 +217,17 @@ pub fn connect_with_early_data(
             .map_err(|error| ClientError::Other(error.to_string()))?;
     }
 
-    if let Some(keylog) = &mut keylog {
-        if let Ok(keylog) = keylog.try_clone() {
-            conn.set_keylog(Box::new(keylog));
-        }
+    let pcap = match &args.pcap_dir {
+        Some(dir) => Some(
+            make_pcap_writer(dir, &format!("h3i-{scid:?}.pcapng"), "h3i")
+                .map_err(|e| ClientError::Other(e.to_string()))?,
+        ),
+
+        None => None,
+    };
+
+    if let Some(keylog) = keylog_writer(pcap.as_ref(), keylog.as_ref()) {
+        conn.set_keylog(keylog);
     }
 
     log::info!(
@@ -219,24 +240,20 @@ pub fn connect_with_early_data(
     let (write, send_info) = conn.send(&mut out).expect("initial send failed");
 
     let mut client = SyncClient::new(close_trigger_frames);
+    let mut early_actions_executed = 0;
+
+    // Indices of the early actions executed again once the handshake
+    // completed. `None` until then.
+    let mut early_actions_replayed: Option<Vec<usize>> = None;
+
     // Send early data if connection is_in_early_data (resumption with 0-RTT was
     // successful) and if we have early_actions.
-    if conn.is_in_early_data() {
-        if let Some(early_actions) = early_actions {
-            let mut early_action_iter = early_actions.iter();
-            let mut wait_duration = None;
-            let mut wait_instant = None;
-            let mut waiting_for = WaitingFor::default();
-
-            check_duration_and_do_actions(
-                &mut wait_duration,
-                &mut wait_instant,
-                &mut early_action_iter,
-                &mut conn,
-                &mut waiting_for,
-                client.stream_parsers_mut(),
-            );
-        }
+    if let Some(early_actions) = &early_actions {
+        early_actions_executed = execute_early_actions(
+            early_actions,
+            &mut conn,
+            client.stream_parsers_mut(),
+        );
     }
 
     while let Err(e) = socket.send_to(&out[..write], send_info.to) {
@@ -252,9 +269,50 @@ pub fn connect_with_early_data(
         return Err(ClientError::Other(format!("send() failed: {e:?}")));
     }
 
+    capture_datagram(
+        pcap.as_ref(),
+        Direction::Outbound,
+        send_info.from,
+        send_info.to,
+        &out[..write],
+    );
+
+    // Flush the 0-RTT packets carrying early actions straight away. Waiting
+    // for the server's first flight would likely complete the handshake,
+    // causing the early actions to be sent in 1-RTT packets instead.
+    if early_actions_executed > 0 {
+        loop {
+            let (write, send_info) = match conn.send(&mut out) {
+                Ok(v) => v,
+
+                Err(quiche::Error::Done) => break,
+
+                Err(e) =>
+                    return Err(ClientError::Other(format!("send failed: {e:?}"))),
+            };
+
+            if let Err(e) = socket.send_to(&out[..write], send_info.to) {
+                if e.kind() == std::io::ErrorKind::WouldBlock {
+                    break;
+                }
+
+                return Err(ClientError::Other(format!("send() failed: {e:?}")));
+            }
+
+            capture_datagram(
+                pcap.as_ref(),
+                Direction::Outbound,
+                send_info.from,
+                send_info.to,
+                &out[..write],
+            );
+        }
+    }
+
     let app_data_start = std::time::Instant::now();
 
-    let mut action_iter = actions.iter();
+    let mut actions = actions;
+    let mut next_action = 0;
     let mut wait_duration = None;
     let mut wait_instant = None;
 
@@ -320,6 +378,14 @@ pub fn connect_with_early_data(
                     },
                 };
 
+                capture_datagram(
+                    pcap.as_ref(),
+                    Direction::Inbound,
+                    from,
+                    local_addr,
+                    &buf[..len],
+                );
+
                 let recv_info = quiche::RecvInfo {
                     to: local_addr,
                     from,
@@ -368,11 +434,47 @@ pub fn connect_with_early_data(
             app_proto_selected = true;
         }
 
-        if app_proto_selected {
+        // Once the handshake completes, queue the early actions that still
+        // need to be executed ahead of the remaining actions. Until then, no
+        // regular action is executed, as it may depend on the early actions.
+        let mut actions_ready = true;
+
+        if let Some(early_actions) = &early_actions {
+            if early_actions_replayed.is_none() {
+                actions_ready = conn.is_established();
+            }
+
+            if actions_ready && early_actions_replayed.is_none() {
+                let accepted = conn.early_data_reason() ==
+                    EarlyDataSummary::EARLY_DATA_ACCEPTED;
+                let replayed = early_actions_to_replay(
+                    early_actions,
+                    early_actions_executed,
+                    accepted,
+                );
+
+                if !replayed.is_empty() {
+                    log::info!(
+                        "executing {} early action(s) after the handshake",
+                        replayed.len()
+                    );
+                }
+
+                actions.splice(
+                    next_action..next_action,
+                    replayed.iter().map(|&i| early_actions[i].clone()),
+                );
+
+                early_actions_replayed = Some(replayed);
+            }
+        }
+
+        if app_proto_selected && actions_ready {
             check_duration_and_do_actions(
                 &mut wait_duration,
                 &mut wait_instant,
-                &mut action_iter,
+                &actions,
+                &mut next_action,
                 &mut conn,
                 &mut waiting_for,
                 client.stream_parsers_mut(),
@@ -406,7 +508,8 @@ pub fn connect_with_early_data(
                 check_duration_and_do_actions(
                     &mut wait_duration,
                     &mut wait_instant,
-                    &mut action_iter,
+                    &actions,
+                    &mut next_action,
                     &mut conn,
                     &mut waiting_for,
                     client.stream_parsers_mut(),
@@ -468,6 +571,14 @@ pub fn connect_with_early_data(
                             local_addr, send_info.to, e
                         )));
                     }
+
+                    capture_datagram(
+                        pcap.as_ref(),
+                        Direction::Outbound,
+                        send_info.from,
+                        send_info.to,
+                        &out[..write],
+                    );
                 }
             }
         }
@@ -497,14 +608,62 @@ pub fn connect_with_early_data(
         stats: Some(conn.stats()),
         path_stats: conn.path_stats().collect(),
         conn_close_details: ConnectionCloseDetails::new(&conn),
+        early_data: args.session.is_some().then(|| {
+            let records = EarlyActionRecord::from_executed(
+                early_actions.as_ref().map_or(0, Vec::len),
+                early_actions_executed,
+                early_actions_replayed.as_deref().unwrap_or_default(),
+            );
+
+            EarlyDataSummary::new(&conn, records)
+        }),
     })
 }
 
+/// Run a two-phase session resumption scenario.
+///
+/// The first connection executes `priming_actions` in order to obtain a
+/// session ticket from the server. The second connection resumes that session,
+/// sends `early_actions` in 0-RTT and then executes `actions` as usual.
+///
+/// `close_trigger_frames` only apply to the second connection. Returns the
+/// [ConnectionSummary] of both connections, in order, or
+/// [ClientError::Other] if the first connection didn't yield a session.
+pub fn connect_with_resumption(
+    args: Config, priming_actions: Vec<Action>, early_actions: Vec<Action>,
+    actions: Vec<Action>, close_trigger_frames: Option<CloseTriggerFrames>,
+) -> std::result::Result<(ConnectionSummary, ConnectionSummary), ClientError> {
+    let mut priming_args = args.clone();
+    priming_args.session = None;
+
+    let priming = connect(priming_args, priming_actions, None)?;
+
+    let Some(session) = priming.conn_close_details.session.clone() else {
+        return Err(ClientError::Other(
+            "no session ticket received on priming connection".to_string(),
+        ));
+    };
+
+    let resumed_args = args.with_session(session).enable_early_data(true);
+
+    let resumed = connect_with_early_data(
+        resumed_args,
+        Some(early_actions),
+        actions,
+        close_trigger_frames,
+    )?;
+
+    Ok((priming, resumed))
+}
+
 fn check_duration_and_do_actions(
     wait_duration: &mut Option<Duration>, wait_instant: &mut Option<Instant>,
-    action_iter: &mut Iter<Action>, conn: &mut quiche::Connection,
+    actions: &[Action], next_action: &mut usize, conn: &mut quiche::Connection,
     waiting_for: &mut WaitingFor, stream_parsers: &mut StreamParserMap,
 ) {
+    let mut action_iter = actions[*next_action..].iter();
+    let action_iter = &mut action_iter;
+
     match wait_duration.as_ref() {
         None => {
             if let Some(idle_wait) =
@@ -543,6 +702,8 @@ fn check_duration_and_do_actions(
             }
         },
     }
+
+    *next_action = actions.len() - action_iter.len();
 }
 
 /// Generate a new pair of Source Connection ID and reset token.
@@ -71,6 +71,10 @@ pub struct Config {
     pub dgram_recv_queue_len: usize,
     /// Datagram send queue length.
     pub dgram_send_queue_len: usize,
+    /// Directory to write a pcapng capture of each connection to, including
+    /// its TLS secrets. Only supported by the sync client; the async client
+    /// rejects it.
+    pub pcap_dir: Option<String>,
 }
 
 impl Config {
@@ -153,6 +157,16 @@ impl Config {
         self
     }
 
+    pub fn with_session(mut self, session: Vec<u8>) -> Self {
+        self.session = Some(session);
+        self
+    }
+
+    pub fn enable_early_data(mut self, enable_early_data: bool) -> Self {
+        self.enable_early_data = enable_early_data;
+        self
+    }
+
     pub fn enable_dgram(mut self, enable_dgram: bool) -> Self {
         self.enable_dgram = enable_dgram;
         self
@@ -172,6 +186,11 @@ impl Config {
         self
     }
 
+    pub fn with_pcap_dir(mut self, pcap_dir: String) -> Self {
+        self.pcap_dir = Some(pcap_dir);
+        self
+    }
+
     pub fn build(self) -> Result<Self, io::Error> {
         if self.host_port.is_empty() {
             return Err(io::Error::new(
@@ -195,11 +214,12 @@ impl Config {
             max_streams_uni: self.max_streams_uni,
             max_window: self.max_window,
             max_stream_window: self.max_stream_window,
-            session: None,
+            session: self.session,
             enable_early_data: self.enable_early_data,
             enable_dgram: self.enable_dgram,
             dgram_recv_queue_len: self.dgram_recv_queue_len,
             dgram_send_queue_len: self.dgram_send_queue_len,
+            pcap_dir: self.pcap_dir,
         })
     }
 }
@@ -227,6 +247,7 @@ impl Default for Config {
             enable_dgram: true,
             dgram_recv_queue_len: 65536,
             dgram_send_queue_len: 65536,
+            pcap_dir: None,
         }
     }
 }
@@ -277,6 +277,27 @@ fn fake_packet_sent(frames: Option<Vec<QuicFrame>>) -> EventData {
     })
 }
 
+/// Encodes bytes as a lowercase hex string, as used for qlog raw data.
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Decodes a hex string, ignoring any whitespace. Returns `None` if the input
+/// contains non-hex characters or an odd number of digits.
+fn hex_decode(hex: &str) -> Option<Vec<u8>> {
+    let digits: Vec<u8> = hex
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_digit(16).map(|d| d as u8))
+        .collect::<Option<_>>()?;
+
+    if !digits.len().is_multiple_of(2) {
+        return None;
+    }
+
+    Some(digits.chunks(2).map(|p| (p[0] << 4) | p[1]).collect())
+}
+
 pub mod actions;
 pub mod client;
 pub mod config;
@@ -284,3 +305,18 @@ pub mod frame;
 pub mod frame_parser;
 pub mod prompts;
 pub mod recordreplay;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_round_trip() {
+        assert_eq!(hex_encode(&[0x00, 0x1f, 0xab]), "001fab");
+        assert_eq!(hex_decode("001fab"), Some(vec![0x00, 0x1f, 0xab]));
+        assert_eq!(hex_decode("00 1F\tAB"), Some(vec![0x00, 0x1f, 0xab]));
+        assert_eq!(hex_decode(""), Some(vec![]));
+        assert_eq!(hex_decode("abc"), None);
+        assert_eq!(hex_decode("zz"), None);
+    }
+}
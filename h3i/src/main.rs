@@ -28,16 +28,17 @@ use std::env;
 use std::io::BufReader;
 use std::result::Result;
 use std::time;
-use std::time::Instant;
 
 use h3i::actions::h3::Action;
 use h3i::client::connection_summary::ConnectionSummary;
+use h3i::client::diff::DiffConfig;
+use h3i::client::load::LoadConfig;
+use h3i::client::load::SourcePorts;
+use h3i::client::load::DEFAULT_MAX_CONCURRENCY;
+use h3i::client::split_early_actions;
 use h3i::client::ClientError;
 use h3i::prompts::h3::Prompter;
-use h3i::recordreplay::qlog::QlogEvent;
 use h3i::recordreplay::qlog::*;
-use qlog::events::HTTP3_URI;
-use qlog::events::QUIC_URI;
 use qlog::reader::QlogSeqReader;
 
 use clap::App;
@@ -67,6 +68,53 @@ fn main() -> Result<(), ClientError> {
         None => prompt_frames(&config),
     };
 
+    if config.load.connections > 1 {
+        let summary = h3i::client::load::run(
+            &config.library_config,
+            &config.load,
+            |_| actions.clone(),
+            None,
+        );
+
+        log::info!(
+            "load summary: {}",
+            serde_json::to_string_pretty(&summary)
+                .unwrap_or_else(|e| e.to_string())
+        );
+
+        if summary.completed == 0 {
+            return Err(ClientError::Other("all connections failed".into()));
+        }
+
+        return Ok(());
+    }
+
+    if let Some(diff_target) = &config.diff_target {
+        let report = h3i::client::diff::run(
+            &config.library_config,
+            diff_target,
+            &actions,
+            None,
+            &config.diff_config,
+        );
+
+        log::debug!(
+            "diff report: {}",
+            serde_json::to_string_pretty(&report)
+                .unwrap_or_else(|e| e.to_string())
+        );
+
+        log::info!("{report}");
+
+        if !report.is_equivalent() {
+            return Err(ClientError::Other("servers diverged".into()));
+        }
+
+        return Ok(());
+    }
+
+    let session_file = config.session_file.clone();
+
     #[cfg(not(feature = "async"))]
     let summary = sync_client(config, actions);
     #[cfg(feature = "async")]
@@ -79,6 +127,25 @@ fn main() -> Result<(), ClientError> {
                 serde_json::to_string_pretty(&s)
                     .unwrap_or_else(|e| e.to_string())
             );
+
+            if let Some(early_data) = &s.early_data {
+                log::info!(
+                    "early data: resumed={} accepted={} actions={}",
+                    early_data.resumed,
+                    early_data.accepted(),
+                    serde_json::to_string(&early_data.actions)
+                        .unwrap_or_else(|e| e.to_string())
+                );
+            }
+
+            if let (Some(path), Some(session)) =
+                (&session_file, &s.conn_close_details.session)
+            {
+                match std::fs::write(path, session) {
+                    Ok(_) => log::info!("session saved to {path}"),
+                    Err(e) => log::error!("failed to save session: {e}"),
+                }
+            }
         },
         Err(e) => {
             log::error!("{e:?}");
@@ -93,9 +160,16 @@ struct Config {
     pub qlog_input: Option<String>,
     pub qlog_actions_output: bool,
     pub host_override: Option<String>,
+    pub load: LoadConfig,
+    pub session_file: Option<String>,
+    pub early_data: bool,
+    pub diff_target: Option<h3i::config::Config>,
+    pub diff_config: DiffConfig,
 }
 
 fn config_from_clap() -> std::result::Result<Config, String> {
+    let default_max_concurrency = DEFAULT_MAX_CONCURRENCY.to_string();
+
     let matches = App::new("h3i")
         .version("v0.1.0")
         .about("Interactive HTTP/3 console debugger")
@@ -224,6 +298,86 @@ fn config_from_clap() -> std::result::Result<Config, String> {
                 .default_value("65536")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("pcap-dir")
+                .long("pcap-dir")
+                .help("Directory to write a pcapng capture of each connection to, with its TLS secrets embedded.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("session-file")
+                .long("session-file")
+                .help("File to resume a TLS session from, if it exists. The session from the connection is saved to it on exit.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("early-data")
+                .long("early-data")
+                .help("Send the actions up to the first wait as early data on a resumed connection, and the rest after the handshake. If no session is available, a first connection executes the same actions to obtain one.")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("connections")
+                .long("connections")
+                .help("Number of concurrent connections to run the actions on.")
+                .default_value("1")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("ramp-up")
+                .long("ramp-up")
+                .help("Period in milliseconds over which connection starts are spread.")
+                .default_value("0")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("max-concurrency")
+                .long("max-concurrency")
+                .help("Maximum number of connections running at the same time. Connections that are due while the limit is reached start as others finish.")
+                .default_value(&default_max_concurrency)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("source-port")
+                .long("source-port")
+                .help("First source port to bind to. Each additional connection uses the next port. Defaults to an ephemeral port.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("diff-against")
+                .long("diff-against")
+                .help("Hostname and port of a second HTTP/3 server. The actions are run against both servers and the differences are reported.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("diff-connect-to")
+                .long("diff-connect-to")
+                .help("Set a specific IP address to connect to for the --diff-against server, rather than use DNS resolution")
+                .requires("diff-against")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("diff-ignore-header")
+                .long("diff-ignore-header")
+                .help("Header whose value is not compared between the two servers. Can be given multiple times.")
+                .requires("diff-against")
+                .multiple_occurrences(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("diff-ignore-stat")
+                .long("diff-ignore-stat")
+                .help("Connection statistic that is not compared between the two servers, e.g. paths_count. Can be given multiple times.")
+                .requires("diff-against")
+                .multiple_occurrences(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("diff-traffic-stats")
+                .long("diff-traffic-stats")
+                .help("Also compare packet and byte counters between the two servers. These usually differ even when both servers behave the same.")
+                .requires("diff-against"),
+        )
         .get_matches();
 
     let host_port = matches.value_of("host:port").unwrap().to_string();
@@ -299,6 +453,96 @@ fn config_from_clap() -> std::result::Result<Config, String> {
         .parse::<usize>()
         .map_err(|e| format!("dgram-send-queue-len input error {e}"))?;
 
+    let connections = matches
+        .value_of("connections")
+        .unwrap()
+        .parse::<usize>()
+        .map_err(|e| format!("connections input error {e}"))?;
+
+    let ramp_up = matches
+        .value_of("ramp-up")
+        .unwrap()
+        .parse::<u64>()
+        .map_err(|e| format!("ramp-up input error {e}"))?;
+
+    let max_concurrency = matches
+        .value_of("max-concurrency")
+        .unwrap()
+        .parse::<usize>()
+        .map_err(|e| format!("max-concurrency input error {e}"))?;
+
+    if max_concurrency == 0 {
+        return Err("max-concurrency input error must be at least 1".into());
+    }
+
+    let source_port = matches
+        .value_of("source-port")
+        .map(|v| v.parse::<u16>().map(u32::from))
+        .transpose()
+        .map_err(|e| format!("source-port input error {e}"))?;
+
+    let source_ports = match source_port {
+        Some(base) if base != 0 => SourcePorts::Sequential(base),
+        _ => SourcePorts::FromConfig,
+    };
+
+    source_ports
+        .validate(connections)
+        .map_err(|e| format!("source-port input error {e}"))?;
+
+    let load = LoadConfig::new()
+        .with_connections(connections)
+        .with_ramp_up(time::Duration::from_millis(ramp_up))
+        .with_source_ports(source_ports)
+        .with_max_concurrency(max_concurrency);
+
+    let session_file = matches.value_of("session-file").map(|s| s.to_string());
+
+    let session = match &session_file {
+        Some(path) if std::path::Path::new(path).exists() => Some(
+            std::fs::read(path)
+                .map_err(|e| format!("session-file input error {e}"))?,
+        ),
+
+        _ => None,
+    };
+
+    let early_data = matches.is_present("early-data");
+
+    let pcap_dir = matches.value_of("pcap-dir").map(|s| s.to_string());
+
+    // Load mode runs the actions on fresh connections only.
+    if connections > 1 {
+        for arg in ["diff-against", "session-file", "early-data"] {
+            if matches.is_present(arg) {
+                return Err(format!(
+                    "connections input error {arg} requires a single connection"
+                ));
+            }
+        }
+    }
+
+    if cfg!(feature = "async") && pcap_dir.is_some() {
+        return Err("pcap-dir is only supported by the sync client".into());
+    }
+
+    let diff_against = matches.value_of("diff-against").map(|s| s.to_string());
+    let diff_connect_to =
+        matches.value_of("diff-connect-to").map(|s| s.to_string());
+
+    let diff_config = matches
+        .values_of("diff-ignore-header")
+        .into_iter()
+        .flatten()
+        .fold(DiffConfig::new(), |c, name| c.with_ignored_header(name));
+
+    let diff_config = matches
+        .values_of("diff-ignore-stat")
+        .into_iter()
+        .flatten()
+        .fold(diff_config, |c, name| c.with_ignored_stat(name))
+        .compare_traffic_stats(matches.is_present("diff-traffic-stats"));
+
     let qlog_actions_output = !matches.is_present("no-qlog-actions-output");
     let qlog_input = matches.value_of("qlog-input").and_then(|q| {
         std::path::Path::new(q)
@@ -316,7 +560,7 @@ fn config_from_clap() -> std::result::Result<Config, String> {
         host_port,
         omit_sni,
         connect_to,
-        source_port: 0,
+        source_port: source_port.unwrap_or(0),
         verify_peer,
         idle_timeout,
         max_data,
@@ -327,18 +571,30 @@ fn config_from_clap() -> std::result::Result<Config, String> {
         max_streams_uni,
         max_window,
         max_stream_window,
-        session: None,
-        enable_early_data: false,
+        session,
+        enable_early_data: early_data,
         enable_dgram,
         dgram_recv_queue_len,
         dgram_send_queue_len,
+        pcap_dir,
     };
 
+    let diff_target = diff_against.map(|host_port| h3i::config::Config {
+        host_port,
+        connect_to: diff_connect_to,
+        ..library_config.clone()
+    });
+
     Ok(Config {
         qlog_input,
         qlog_actions_output,
         library_config,
         host_override,
+        load,
+        session_file,
+        early_data,
+        diff_target,
+        diff_config,
     })
 }
 
@@ -351,18 +607,58 @@ fn async_client(
         .build()
         .map_err(Box::new)?;
 
-    let fut = async {
-        h3i::client::async_client::connect(
-            &config.library_config,
+    rt.block_on(async_connect(config, frame_actions))
+}
+
+#[cfg(feature = "async")]
+async fn async_connect(
+    config: Config, frame_actions: Vec<Action>,
+) -> Result<ConnectionSummary, BoxError> {
+    use h3i::client::async_client;
+
+    let args = &config.library_config;
+
+    if !config.early_data {
+        let summary = async_client::connect(args, frame_actions, None)
+            .await
+            .map_err(|e| format!("{e:?}"))?
+            .await;
+
+        return Ok(summary);
+    }
+
+    // The actions up to the first wait are sent as early data, and the rest
+    // once the handshake completes.
+    let (early_actions, actions) = split_early_actions(frame_actions.clone());
+
+    let summary = if args.session.is_none() {
+        // Without a stored session, the first connection is only used to
+        // obtain a session ticket. The actions are then replayed with early
+        // data.
+        let (_, resumed) = async_client::connect_with_resumption(
+            args,
             frame_actions,
+            early_actions,
+            actions,
             None,
         )
         .await
-        .unwrap()
+        .map_err(|e| format!("{e:?}"))?;
+
+        resumed
+    } else {
+        async_client::connect_with_early_data(
+            args,
+            Some(early_actions),
+            actions,
+            None,
+        )
+        .await
+        .map_err(|e| format!("{e:?}"))?
         .await
     };
 
-    Ok(rt.block_on(fut))
+    Ok(summary)
 }
 
 #[cfg(not(feature = "async"))]
@@ -370,7 +666,38 @@ fn sync_client(
     config: Config, actions: Vec<Action>,
 ) -> Result<ConnectionSummary, ClientError> {
     // TODO: CLI/qlog don't support passing close trigger frames at the moment
-    h3i::client::sync_client::connect(config.library_config, actions, None)
+    if !config.early_data {
+        return h3i::client::sync_client::connect(
+            config.library_config,
+            actions,
+            None,
+        );
+    }
+
+    // The actions up to the first wait are sent as early data, and the rest
+    // once the handshake completes.
+    let (early_actions, regular_actions) = split_early_actions(actions.clone());
+
+    // Without a stored session, the first connection is only used to obtain
+    // a session ticket. The actions are then replayed with early data.
+    if config.library_config.session.is_none() {
+        let (_, resumed) = h3i::client::sync_client::connect_with_resumption(
+            config.library_config,
+            actions,
+            early_actions,
+            regular_actions,
+            None,
+        )?;
+
+        return Ok(resumed);
+    }
+
+    h3i::client::sync_client::connect_with_early_data(
+        config.library_config,
+        Some(early_actions),
+        regular_actions,
+        None,
+    )
 }
 
 fn read_qlog(filename: &str, host_override: Option<&str>) -> Vec<Action> {
@@ -404,26 +731,7 @@ fn prompt_frames(config: &Config) -> Vec<Action> {
     if !actions.is_empty() && config.qlog_actions_output {
         let writer = make_qlog_writer();
         let mut streamer = make_streamer(std::boxed::Box::new(writer));
-
-        for action in &actions {
-            let events: QlogEvents = action.into();
-            for event in events {
-                match event {
-                    QlogEvent::Event { data, ex_data } => {
-                        streamer.add_event_data_ex_now(*data, ex_data).ok();
-                    },
-
-                    QlogEvent::JsonEvent(mut ev) => {
-                        // need to rewrite the event time
-                        ev.time = Instant::now()
-                            .duration_since(streamer.start_time())
-                            .as_secs_f64() *
-                            1000.0;
-                        streamer.add_event(ev).ok();
-                    },
-                }
-            }
-        }
+        write_actions(&mut streamer, &actions).ok();
     }
 
     actions
@@ -448,35 +756,3 @@ pub fn make_qlog_writer() -> std::io::BufWriter<std::fs::File> {
             panic!("Error creating qlog file attempted path was {path:?}: {e}"),
     }
 }
-
-pub fn make_streamer(
-    writer: Box<dyn std::io::Write + Send + Sync>,
-) -> qlog::streamer::QlogStreamer {
-    let vp = qlog::VantagePointType::Client;
-
-    let trace = qlog::TraceSeq::new(
-        Some("h3i".into()),
-        Some("h3i".into()),
-        None,
-        Some(qlog::VantagePoint {
-            name: None,
-            ty: vp,
-            flow: None,
-        }),
-        vec![QUIC_URI.to_string(), HTTP3_URI.to_string()],
-    );
-
-    let mut streamer = qlog::streamer::QlogStreamer::new(
-        Some("h3i".into()),
-        Some("h3i".into()),
-        time::Instant::now(),
-        trace,
-        qlog::events::EventImportance::Extra,
-        qlog::streamer::EventTimePrecision::NanoSeconds,
-        writer,
-    );
-
-    streamer.start_log().ok();
-
-    streamer
-}
@@ -33,6 +33,8 @@ use inquire::Text;
 use qlog::events::quic::ErrorSpace;
 
 use crate::prompts::h3;
+use crate::quiche::h3::WireErrorCode as H3WireErrorCode;
+use crate::quiche::WireErrorCode;
 
 use super::SuggestionResult;
 
@@ -97,23 +99,26 @@ pub fn prompt_transport_or_app_error() -> InquireResult<(ErrorSpace, u64)> {
             .prompt()?;
 
         match error_code.as_str() {
-            NO_ERROR => 0x0,
-            INTERNAL_ERROR => 0x1,
-            CONNECTION_REFUSED => 0x2,
-            FLOW_CONTROL_ERROR => 0x3,
-            STREAM_LIMIT_ERROR => 0x4,
-            STREAM_STATE_ERROR => 0x5,
-            FINAL_SIZE_ERROR => 0x6,
-            FRAME_ENCODING_ERROR => 0x7,
-            TRANSPORT_PARAMETER_ERROR => 0x8,
-            CONNECTION_ID_LIMIT_ERROR => 0x9,
-            PROTOCOL_VIOLATION => 0x0a,
-            INVALID_TOKEN => 0x0b,
-            APPLICATION_ERROR => 0x0c,
-            CRYPTO_BUFFER_EXCEEDED => 0x0d,
-            KEY_UPDATE_ERROR => 0x0e,
-            AEAD_LIMIT_REACHED => 0x0f,
-            NO_VIABLE_PATH => 0x10,
+            NO_ERROR => WireErrorCode::NoError as u64,
+            INTERNAL_ERROR => WireErrorCode::InternalError as u64,
+            CONNECTION_REFUSED => WireErrorCode::ConnectionRefused as u64,
+            FLOW_CONTROL_ERROR => WireErrorCode::FlowControlError as u64,
+            STREAM_LIMIT_ERROR => WireErrorCode::StreamLimitError as u64,
+            STREAM_STATE_ERROR => WireErrorCode::StreamStateError as u64,
+            FINAL_SIZE_ERROR => WireErrorCode::FinalSizeError as u64,
+            FRAME_ENCODING_ERROR => WireErrorCode::FrameEncodingError as u64,
+            TRANSPORT_PARAMETER_ERROR =>
+                WireErrorCode::TransportParameterError as u64,
+            CONNECTION_ID_LIMIT_ERROR =>
+                WireErrorCode::ConnectionIdLimitError as u64,
+            PROTOCOL_VIOLATION => WireErrorCode::ProtocolViolation as u64,
+            INVALID_TOKEN => WireErrorCode::InvalidToken as u64,
+            APPLICATION_ERROR => WireErrorCode::ApplicationError as u64,
+            CRYPTO_BUFFER_EXCEEDED => WireErrorCode::CryptoBufferExceeded as u64,
+            KEY_UPDATE_ERROR => WireErrorCode::KeyUpdateError as u64,
+            AEAD_LIMIT_REACHED => WireErrorCode::AeadLimitReached as u64,
+            NO_VIABLE_PATH => WireErrorCode::NoViablePath as u64,
+            // Not yet part of `WireErrorCode`, see RFC 9368.
             VERSION_NEGOTIATION_ERROR => 0x11,
 
             v => v.parse::<u64>().unwrap(),
@@ -126,24 +131,29 @@ pub fn prompt_transport_or_app_error() -> InquireResult<(ErrorSpace, u64)> {
             .prompt()?;
 
         match error_code.as_str() {
+            // RFC 9297, not part of `h3::WireErrorCode`.
             H3_DATAGRAM_ERROR => 0x33,
-            H3_NO_ERROR => 0x100,
-            H3_GENERAL_PROTOCOL_ERROR => 0x101,
-            H3_INTERNAL_ERROR => 0x102,
-            H3_STREAM_CREATION_ERROR => 0x103,
-            H3_CLOSED_CRITICAL_STREAM => 0x104,
-            H3_FRAME_UNEXPECTED => 0x105,
-            H3_FRAME_ERROR => 0x106,
-            H3_EXCESSIVE_LOAD => 0x107,
-            H3_ID_ERROR => 0x108,
-            H3_SETTINGS_ERROR => 0x109,
-            H3_MISSING_SETTINGS => 0x10a,
-            H3_REQUEST_REJECTED => 0x10b,
-            H3_REQUEST_CANCELLED => 0x10c,
-            H3_REQUEST_INCOMPLETE => 0x10d,
-            H3_MESSAGE_ERROR => 0x10e,
-            H3_CONNECT_ERROR => 0x10f,
-            H3_VERSION_FALLBACK => 0x110,
+            H3_NO_ERROR => H3WireErrorCode::NoError as u64,
+            H3_GENERAL_PROTOCOL_ERROR =>
+                H3WireErrorCode::GeneralProtocolError as u64,
+            H3_INTERNAL_ERROR => H3WireErrorCode::InternalError as u64,
+            H3_STREAM_CREATION_ERROR =>
+                H3WireErrorCode::StreamCreationError as u64,
+            H3_CLOSED_CRITICAL_STREAM =>
+                H3WireErrorCode::ClosedCriticalStream as u64,
+            H3_FRAME_UNEXPECTED => H3WireErrorCode::FrameUnexpected as u64,
+            H3_FRAME_ERROR => H3WireErrorCode::FrameError as u64,
+            H3_EXCESSIVE_LOAD => H3WireErrorCode::ExcessiveLoad as u64,
+            H3_ID_ERROR => H3WireErrorCode::IdError as u64,
+            H3_SETTINGS_ERROR => H3WireErrorCode::SettingsError as u64,
+            H3_MISSING_SETTINGS => H3WireErrorCode::MissingSettings as u64,
+            H3_REQUEST_REJECTED => H3WireErrorCode::RequestRejected as u64,
+            H3_REQUEST_CANCELLED => H3WireErrorCode::RequestCancelled as u64,
+            H3_REQUEST_INCOMPLETE => H3WireErrorCode::RequestIncomplete as u64,
+            H3_MESSAGE_ERROR => H3WireErrorCode::MessageError as u64,
+            H3_CONNECT_ERROR => H3WireErrorCode::ConnectError as u64,
+            H3_VERSION_FALLBACK => H3WireErrorCode::VersionFallback as u64,
+            // RFC 9204, not part of `h3::WireErrorCode`.
             QPACK_DECOMPRESSION_FAILED => 0x200,
             QPACK_ENCODER_STREAM_ERROR => 0x201,
             QPACK_DECODER_STREAM_ERROR => 0x202,
@@ -38,11 +38,16 @@ use quiche::ConnectionError;
 
 use crate::actions::h3::Action;
 use crate::config::Config;
+use crate::hex_decode;
 use crate::prompts::h3;
 use crate::prompts::h3::headers::prompt_push_promise;
+use crate::recordreplay::qlog::make_streamer;
+use crate::recordreplay::qlog::write_actions;
 use crate::StreamIdAllocator;
 
 use std::cell::RefCell;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 
 use crate::quiche;
 
@@ -91,6 +96,7 @@ const MAX_PUSH_ID: &str = "max_push_id";
 const PRIORITY_UPDATE: &str = "priority_update";
 const GREASE: &str = "grease";
 const EXTENSION: &str = "extension_frame";
+const UNKNOWN_FRAME_HEX: &str = "unknown_frame_hex";
 const OPEN_UNI_STREAM: &str = "open_uni_stream";
 const RESET_STREAM: &str = "reset_stream";
 const STOP_SENDING: &str = "stop_sending";
@@ -98,11 +104,15 @@ const CONNECTION_CLOSE: &str = "connection_close";
 const STREAM_BYTES: &str = "stream_bytes";
 const DATAGRAM_QUARTER_STREAM_ID: &str = "datagram_quarter_stream_id";
 const DATAGRAM_RAW_PAYLOAD: &str = "datagram_raw_payload";
+const DATAGRAM_HEX_PAYLOAD: &str = "datagram_hex_payload";
 
 const COMMIT: &str = "commit";
 const FLUSH_PACKETS: &str = "flush_packets";
 const WAIT: &str = "wait";
 const QUIT: &str = "quit";
+const UNDO: &str = "undo";
+const HISTORY: &str = "history";
+const EXPORT: &str = "export";
 
 const YES: &str = "Yes";
 const NO: &str = "No";
@@ -118,6 +128,9 @@ enum PromptOutcome {
     Repeat,
     Commit,
     Clear,
+    Undo,
+    History,
+    Export,
 }
 
 /// The main prompter interface and state management.
@@ -165,6 +178,7 @@ impl Prompter {
             STOP_SENDING => stream::prompt_stop_sending(),
             GREASE => prompt_grease(),
             EXTENSION => prompt_extension(),
+            UNKNOWN_FRAME_HEX => prompt_unknown_frame_hex(),
             GOAWAY => prompt_goaway(),
             MAX_PUSH_ID => prompt_max_push_id(),
             CANCEL_PUSH => prompt_cancel_push(),
@@ -174,11 +188,15 @@ impl Prompter {
             STREAM_BYTES => prompt_stream_bytes(),
             DATAGRAM_QUARTER_STREAM_ID | DATAGRAM_RAW_PAYLOAD =>
                 prompt_send_datagram(action == DATAGRAM_QUARTER_STREAM_ID),
+            DATAGRAM_HEX_PAYLOAD => prompt_send_datagram_hex(),
             FLUSH_PACKETS =>
                 return PromptOutcome::Action(Box::new(Action::FlushPackets)),
             COMMIT => return PromptOutcome::Commit,
             WAIT => prompt_wait(),
             QUIT => return PromptOutcome::Clear,
+            UNDO => return PromptOutcome::Undo,
+            HISTORY => return PromptOutcome::History,
+            EXPORT => return PromptOutcome::Export,
 
             _ => {
                 println!("error: unknown action {action}");
@@ -225,11 +243,61 @@ impl Prompter {
                 PromptOutcome::Repeat => continue,
                 PromptOutcome::Commit => return actions,
                 PromptOutcome::Clear => return vec![],
+                PromptOutcome::Undo => match actions.pop() {
+                    Some(action) => println!("removed {action:?}"),
+                    None => println!("nothing to undo"),
+                },
+                PromptOutcome::History => print_history(&actions),
+                PromptOutcome::Export =>
+                    if let Err(e) = prompt_export(&actions) {
+                        println!("error: export failed: {e}");
+                    },
             }
         }
     }
 }
 
+fn print_history(actions: &[Action]) {
+    if actions.is_empty() {
+        println!("no actions queued");
+    }
+
+    for (i, action) in actions.iter().enumerate() {
+        println!("{i}: {action:?}");
+    }
+}
+
+/// Writes the queued actions to a qlog file that can be replayed with
+/// `--qlog-input`.
+fn prompt_export(actions: &[Action]) -> std::result::Result<(), String> {
+    let default_path = format!(
+        "{}-qlog.sqlog",
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default()
+    );
+
+    let path = match Text::new("export to file:")
+        .with_default(&default_path)
+        .with_help_message(ESC_TO_RET)
+        .prompt()
+    {
+        Ok(path) => path,
+        Err(_) => return Ok(()),
+    };
+
+    let file = std::fs::File::create(&path).map_err(|e| e.to_string())?;
+    let mut streamer = make_streamer(Box::new(std::io::BufWriter::new(file)));
+
+    write_actions(&mut streamer, actions).map_err(|e| e.to_string())?;
+    streamer.finish_log().map_err(|e| e.to_string())?;
+
+    println!("exported {} actions to {path}", actions.len());
+
+    Ok(())
+}
+
 fn handle_action_loop_error(err: InquireError) -> bool {
     match err {
         inquire::InquireError::OperationCanceled |
@@ -269,6 +337,7 @@ fn action_suggester(val: &str) -> SuggestionResult<Vec<String>> {
         MAX_PUSH_ID,
         GREASE,
         EXTENSION,
+        UNKNOWN_FRAME_HEX,
         OPEN_UNI_STREAM,
         RESET_STREAM,
         STOP_SENDING,
@@ -276,9 +345,13 @@ fn action_suggester(val: &str) -> SuggestionResult<Vec<String>> {
         STREAM_BYTES,
         DATAGRAM_QUARTER_STREAM_ID,
         DATAGRAM_RAW_PAYLOAD,
+        DATAGRAM_HEX_PAYLOAD,
         FLUSH_PACKETS,
         COMMIT,
         WAIT,
+        UNDO,
+        HISTORY,
+        EXPORT,
         QUIT,
     ];
 
@@ -314,6 +387,27 @@ fn validate_varint(id: &str) -> SuggestionResult<Validation> {
     Ok(Validation::Valid)
 }
 
+fn validate_hex(hex: &str) -> SuggestionResult<Validation> {
+    if hex_decode(hex).is_none() {
+        return Ok(Validation::Invalid(ErrorMessage::Custom(
+            "expected an even number of hex digits".to_string(),
+        )));
+    }
+
+    Ok(Validation::Valid)
+}
+
+fn prompt_hex_payload() -> InquireResult<Vec<u8>> {
+    let payload = Text::new("payload hex:")
+        .with_validator(h3::validate_hex)
+        .with_placeholder("e.g. 00ff 1a2b")
+        .with_help_message(ESC_TO_RET)
+        .prompt()?;
+
+    // payload is already validated so unwrap always succeeds
+    Ok(hex_decode(&payload).unwrap())
+}
+
 fn prompt_stream_id() -> InquireResult<u64> {
     prompt_varint(STREAM_ID_PROMPT)
 }
@@ -366,7 +460,7 @@ fn prompt_data() -> InquireResult<Action> {
 }
 
 fn prompt_max_push_id() -> InquireResult<Action> {
-    let stream_id = h3::prompt_stream_id()?;
+    let stream_id = h3::prompt_control_stream_id()?;
     let push_id = h3::prompt_varint(PUSH_ID_PROMPT)?;
 
     let fin_stream = prompt_fin_stream()?;
@@ -398,7 +492,7 @@ fn prompt_cancel_push() -> InquireResult<Action> {
 }
 
 fn prompt_goaway() -> InquireResult<Action> {
-    let stream_id = h3::prompt_stream_id()?;
+    let stream_id = h3::prompt_control_stream_id()?;
     let id = h3::prompt_varint("ID:")?;
 
     let fin_stream = prompt_fin_stream()?;
@@ -458,6 +552,23 @@ fn prompt_extension() -> InquireResult<Action> {
     Ok(action)
 }
 
+fn prompt_unknown_frame_hex() -> InquireResult<Action> {
+    let stream_id = h3::prompt_stream_id()?;
+    let raw_type = h3::prompt_varint("frame type:")?;
+    let payload = prompt_hex_payload()?;
+
+    let fin_stream = prompt_fin_stream()?;
+
+    let action = Action::SendFrame {
+        stream_id,
+        fin_stream,
+        frame: quiche::h3::frame::Frame::Unknown { raw_type, payload },
+        expected_result: Default::default(),
+    };
+
+    Ok(action)
+}
+
 pub fn prompt_connection_close() -> InquireResult<Action> {
     let (error_space, error_code) = errors::prompt_transport_or_app_error()?;
     let reason = Text::new("reason phrase:")
@@ -509,6 +620,12 @@ pub fn prompt_send_datagram(with_quarter_stream: bool) -> InquireResult<Action>
     }
 }
 
+pub fn prompt_send_datagram_hex() -> InquireResult<Action> {
+    let payload = prompt_hex_payload()?;
+
+    Ok(Action::SendDatagram { payload })
+}
+
 fn validate_wait_period(period: &str) -> SuggestionResult<Validation> {
     let x = period.parse::<u64>();
 
@@ -541,3 +658,22 @@ mod priority;
 mod settings;
 mod stream;
 mod wait;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_validation() {
+        assert_eq!(validate_hex("00ff").unwrap(), Validation::Valid);
+        assert_eq!(validate_hex("").unwrap(), Validation::Valid);
+        assert!(matches!(
+            validate_hex("0ff").unwrap(),
+            Validation::Invalid(_)
+        ));
+        assert!(matches!(
+            validate_hex("xx").unwrap(),
+            Validation::Invalid(_)
+        ));
+    }
+}
@@ -25,6 +25,7 @@
 // SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use std::collections::BTreeMap;
+use std::time::Instant;
 
 use crate::quiche;
 use qlog::events::http3::FrameCreated;
@@ -40,6 +41,9 @@ use qlog::events::EventData;
 use qlog::events::ExData;
 use qlog::events::JsonEvent;
 use qlog::events::RawInfo;
+use qlog::events::HTTP3_URI;
+use qlog::events::QUIC_URI;
+use qlog::streamer::QlogStreamer;
 use quiche::h3::frame::Frame;
 use quiche::h3::NameValue;
 
@@ -50,6 +54,8 @@ use crate::actions::h3::WaitType;
 use crate::encode_header_block;
 use crate::encode_header_block_literal;
 use crate::fake_packet_sent;
+use crate::hex_decode;
+use crate::hex_encode;
 use crate::HTTP3_CONTROL_STREAM_TYPE_ID;
 use crate::HTTP3_PUSH_STREAM_TYPE_ID;
 use crate::QPACK_DECODER_STREAM_TYPE_ID;
@@ -86,9 +92,21 @@ impl From<&Action> for QlogEvents {
                 frame,
                 ..
             } => {
+                let mut qlog_frame = frame.to_qlog();
+
+                // quiche doesn't log the payload of unknown frames, but it is
+                // needed to replay them.
+                if let (
+                    Frame::Unknown { payload, .. },
+                    Http3Frame::Unknown { raw: Some(raw), .. },
+                ) = (frame, &mut qlog_frame)
+                {
+                    raw.data = Some(Box::new(hex_encode(payload)));
+                }
+
                 let frame_ev = EventData::Http3FrameCreated(FrameCreated {
                     stream_id: *stream_id,
-                    frame: frame.to_qlog(),
+                    frame: qlog_frame,
                     ..Default::default()
                 });
 
@@ -599,6 +617,70 @@ impl From<H3FrameCreatedEx> for Action {
                 expected_result: Default::default(),
             },
 
+            Http3Frame::CancelPush { push_id, .. } => Action::SendFrame {
+                stream_id,
+                fin_stream,
+                frame: Frame::CancelPush { push_id: *push_id },
+                expected_result: Default::default(),
+            },
+
+            Http3Frame::MaxPushId { push_id, .. } => Action::SendFrame {
+                stream_id,
+                fin_stream,
+                frame: Frame::MaxPushId { push_id: *push_id },
+                expected_result: Default::default(),
+            },
+
+            Http3Frame::PriorityUpdate {
+                stream_id: prioritized_stream_id,
+                push_id,
+                priority_field_value,
+                ..
+            } => {
+                let priority_field_value =
+                    priority_field_value.as_bytes().to_vec();
+
+                let frame = match (prioritized_stream_id, push_id) {
+                    (_, Some(push_id)) => Frame::PriorityUpdatePush {
+                        prioritized_element_id: *push_id,
+                        priority_field_value,
+                    },
+
+                    (id, None) => Frame::PriorityUpdateRequest {
+                        prioritized_element_id: id.unwrap_or_default(),
+                        priority_field_value,
+                    },
+                };
+
+                Action::SendFrame {
+                    stream_id,
+                    fin_stream,
+                    frame,
+                    expected_result: Default::default(),
+                }
+            },
+
+            Http3Frame::Unknown {
+                frame_type_bytes,
+                raw,
+            } => {
+                let payload = raw
+                    .as_ref()
+                    .and_then(|r| r.data.as_deref())
+                    .and_then(|d| hex_decode(d))
+                    .unwrap_or_default();
+
+                Action::SendFrame {
+                    stream_id,
+                    fin_stream,
+                    frame: Frame::Unknown {
+                        raw_type: *frame_type_bytes,
+                        payload,
+                    },
+                    expected_result: Default::default(),
+                }
+            },
+
             _ => unimplemented!(),
         };
 
@@ -606,6 +688,70 @@ impl From<H3FrameCreatedEx> for Action {
     }
 }
 
+/// Makes a [QlogStreamer] suitable for recording h3i actions.
+///
+/// The log header has already been written when this returns.
+pub fn make_streamer(
+    writer: Box<dyn std::io::Write + Send + Sync>,
+) -> QlogStreamer {
+    let vp = qlog::VantagePointType::Client;
+
+    let trace = qlog::TraceSeq::new(
+        Some("h3i".into()),
+        Some("h3i".into()),
+        None,
+        Some(qlog::VantagePoint {
+            name: None,
+            ty: vp,
+            flow: None,
+        }),
+        vec![QUIC_URI.to_string(), HTTP3_URI.to_string()],
+    );
+
+    let mut streamer = QlogStreamer::new(
+        Some("h3i".into()),
+        Some("h3i".into()),
+        Instant::now(),
+        trace,
+        qlog::events::EventImportance::Extra,
+        qlog::streamer::EventTimePrecision::NanoSeconds,
+        writer,
+    );
+
+    streamer.start_log().ok();
+
+    streamer
+}
+
+/// Writes `actions` to `streamer` as qlog events.
+///
+/// The resulting qlog can be replayed with h3i's `--qlog-input` option.
+pub fn write_actions(
+    streamer: &mut QlogStreamer, actions: &[Action],
+) -> qlog::Result<()> {
+    for action in actions {
+        let events: QlogEvents = action.into();
+        for event in events {
+            match event {
+                QlogEvent::Event { data, ex_data } => {
+                    streamer.add_event_data_ex_now(*data, ex_data)?;
+                },
+
+                QlogEvent::JsonEvent(mut ev) => {
+                    // need to rewrite the event time
+                    ev.time = Instant::now()
+                        .duration_since(streamer.start_time())
+                        .as_secs_f64() *
+                        1000.0;
+                    streamer.add_event(ev)?;
+                },
+            }
+        }
+    }
+
+    Ok(())
+}
+
 fn from_qlog_stream_type_set(
     st: &StreamTypeSet, ex_data: &ExData,
 ) -> Vec<Action> {
@@ -808,4 +954,54 @@ mod tests {
 
         assert_eq!(actions.0[0], expected);
     }
+
+    #[test]
+    fn frames_round_trip_through_qlog() {
+        let actions = vec![
+            Action::SendFrame {
+                stream_id: 2,
+                fin_stream: false,
+                frame: Frame::Unknown {
+                    raw_type: 0x21,
+                    payload: vec![0x00, 0xff, 0x10],
+                },
+                expected_result: Default::default(),
+            },
+            Action::SendFrame {
+                stream_id: 2,
+                fin_stream: false,
+                frame: Frame::PriorityUpdatePush {
+                    prioritized_element_id: 3,
+                    priority_field_value: b"u=1".to_vec(),
+                },
+                expected_result: Default::default(),
+            },
+            Action::SendFrame {
+                stream_id: 2,
+                fin_stream: true,
+                frame: Frame::MaxPushId { push_id: 7 },
+                expected_result: Default::default(),
+            },
+        ];
+
+        for action in actions {
+            let events: QlogEvents = (&action).into();
+            assert_eq!(events.len(), 1);
+
+            let QlogEvent::Event { data, ex_data } =
+                events.into_iter().next().unwrap()
+            else {
+                panic!("expected a qlog event");
+            };
+
+            // Go through JSON to make sure nothing is lost on the way.
+            let ev = Event::with_time_ex(NOW, *data, ex_data);
+            let serialized = serde_json::to_string(&ev).unwrap();
+            let deserialized =
+                serde_json::from_str::<Event>(&serialized).unwrap();
+
+            let replayed = actions_from_qlog(deserialized, None);
+            assert_eq!(replayed.0, vec![action]);
+        }
+    }
 }
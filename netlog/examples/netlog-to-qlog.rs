@@ -0,0 +1,103 @@
+// Copyright (C) 2025, Cloudflare, Inc.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are
+// met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//
+//     * Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in the
+//       documentation and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS
+// IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO,
+// THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR
+// PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Converts the QUIC sessions of a netlog into qlog traces, one file per
+//! session named after its source ID.
+
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::path::Path;
+
+use netlog::convert::QlogConverter;
+use netlog::reader::NetlogFilter;
+use netlog::reader::NetlogReader;
+use netlog::reader::Session;
+use netlog::reader::SessionProtocol;
+
+fn main() {
+    let mut args = std::env::args();
+
+    let cmd = &args.next().unwrap();
+
+    if args.len() < 2 {
+        println!("Usage: {cmd} NETLOG OUTPUT_DIR [SESSION_ID...]");
+        return;
+    }
+
+    let netlog_path = args.next().unwrap();
+    let out_dir = args.next().unwrap();
+
+    let filter = NetlogFilter {
+        session_ids: args.map(|id| id.parse().unwrap()).collect(),
+        ..Default::default()
+    };
+
+    let file = File::open(&netlog_path).unwrap();
+    let mut netlog = NetlogReader::new(BufReader::new(file), filter).unwrap();
+
+    let mut converters = BTreeMap::new();
+
+    while let Some((event_hdr, event)) = netlog.next() {
+        let source_id = event_hdr.source.id;
+
+        let converter = match converters.entry(source_id) {
+            Entry::Occupied(e) => e.into_mut(),
+
+            Entry::Vacant(e) => match netlog.sessions().get(&source_id) {
+                Some(session) if session.protocol == SessionProtocol::Quic =>
+                    e.insert(create_converter(&out_dir, session)),
+
+                _ => continue,
+            },
+        };
+
+        converter.add_event(&event_hdr, &event).unwrap();
+    }
+
+    if netlog.is_truncated() {
+        println!(
+            "netlog is truncated, converted events up to the last complete one"
+        );
+    }
+
+    for converter in converters.into_values() {
+        converter.finish().unwrap();
+    }
+}
+
+/// Creates a converter for `session`, writing to `<out_dir>/<id>.sqlog`.
+fn create_converter(out_dir: &str, session: &Session) -> QlogConverter {
+    let path = Path::new(out_dir).join(format!("{}.sqlog", session.id));
+    let writer = BufWriter::new(File::create(&path).unwrap());
+
+    println!("{}: {} -> {}", session.id, session.host, path.display());
+
+    QlogConverter::new(session.id, Some(session.host.clone()), Box::new(writer))
+        .unwrap()
+}
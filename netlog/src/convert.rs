@@ -0,0 +1,1207 @@
+// Copyright (C) 2025, Cloudflare, Inc.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are
+// met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//
+//     * Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in the
+//       documentation and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS
+// IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO,
+// THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR
+// PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Conversion of a netlog QUIC session into a qlog trace.
+//!
+//! A [`QlogConverter`] consumes the parsed events of one QUIC session, i.e.
+//! those whose source is the `QUIC_SESSION`, and writes them as a qlog
+//! JSON-SEQ trace using a [`QlogStreamer`]. Only events that this crate
+//! parses are converted.
+//!
+//! Netlog logs frames as separate events. Frames sent are logged before the
+//! `QUIC_SESSION_PACKET_SENT` event of their packet, and frames received
+//! after the `QUIC_SESSION_UNAUTHENTICATED_PACKET_HEADER_RECEIVED` event of
+//! their packet, so the converter groups them into the `frames` of qlog
+//! `quic:packet_sent` and `quic:packet_received` events.
+//!
+//! ```no_run
+//! use netlog::convert::QlogConverter;
+//! # let session_source_id = 0;
+//! # let events: Vec<(netlog::EventHeader, netlog::Event)> = vec![];
+//!
+//! let file = std::fs::File::create("session.sqlog").unwrap();
+//! let mut converter =
+//!     QlogConverter::new(session_source_id, None, Box::new(file)).unwrap();
+//!
+//! for (event_hdr, event) in &events {
+//!     converter.add_event(event_hdr, event).unwrap();
+//! }
+//!
+//! converter.finish().unwrap();
+//! ```
+
+use std::collections::BTreeMap;
+
+use qlog::events::http3;
+use qlog::events::http3::Http3Frame;
+use qlog::events::http3::HttpHeader;
+use qlog::events::quic;
+use qlog::events::quic::AckRange;
+use qlog::events::quic::PacketHeader;
+use qlog::events::quic::PacketType;
+use qlog::events::quic::QuicFrame;
+use qlog::events::quic::TransportInitiator;
+use qlog::events::ApplicationError;
+use qlog::events::EventData;
+use qlog::events::EventImportance;
+use qlog::events::RawInfo;
+use qlog::streamer::EventTimePrecision;
+use qlog::streamer::QlogStreamer;
+use qlog::CommonFields;
+use qlog::ReferenceTime;
+use qlog::TraceSeq;
+use qlog::VantagePoint;
+use qlog::VantagePointType;
+
+use crate::h3;
+use crate::quic::Event::*;
+use crate::quic::TransportParameters;
+use crate::Event;
+use crate::EventHeader;
+
+/// Netlog logs connection-level flow control with this stream ID.
+const CONNECTION_STREAM_ID: i64 = -1;
+
+/// Converts the events of a netlog QUIC session into a qlog trace.
+pub struct QlogConverter {
+    streamer: QlogStreamer,
+    source_id: i64,
+
+    /// Frames logged since the last packet sent.
+    sent_frames: Vec<QuicFrame>,
+
+    /// Type of packets sent, since netlog doesn't log it for lost packets.
+    sent_packet_types: BTreeMap<u64, PacketType>,
+
+    /// The last packet received, which frames received are added to.
+    received: Option<(f64, quic::PacketReceived)>,
+
+    /// Events logged while a packet received is pending, written after it
+    /// to keep the trace in time order.
+    deferred: Vec<qlog::events::Event>,
+
+    /// Compressed length of headers received but not decoded yet, per
+    /// stream.
+    headers_length: BTreeMap<u64, u64>,
+}
+
+impl QlogConverter {
+    /// Creates a converter for the QUIC session with the netlog source ID
+    /// `source_id`, and writes the qlog header to `writer`.
+    ///
+    /// The trace uses the client vantage point, since netlogs are produced
+    /// by browsers.
+    pub fn new(
+        source_id: i64, title: Option<String>,
+        writer: Box<dyn std::io::Write + Send + Sync>,
+    ) -> qlog::Result<Self> {
+        let trace = TraceSeq::new(
+            title.clone(),
+            Some(format!("converted from netlog source {source_id}")),
+            Some(CommonFields {
+                reference_time: ReferenceTime::new_monotonic(None),
+                ..Default::default()
+            }),
+            Some(VantagePoint {
+                name: None,
+                ty: VantagePointType::Client,
+                flow: None,
+            }),
+            vec![
+                qlog::events::QUIC_URI.to_string(),
+                qlog::events::HTTP3_URI.to_string(),
+            ],
+        );
+
+        let mut streamer = QlogStreamer::new(
+            title,
+            None,
+            std::time::Instant::now(),
+            trace,
+            EventImportance::Extra,
+            EventTimePrecision::MilliSeconds,
+            writer,
+        );
+
+        streamer.start_log()?;
+
+        Ok(Self {
+            streamer,
+            source_id,
+            sent_frames: vec![],
+            sent_packet_types: BTreeMap::new(),
+            received: None,
+            deferred: vec![],
+            headers_length: BTreeMap::new(),
+        })
+    }
+
+    /// Converts `event`. Events of other sources than the session are
+    /// ignored.
+    ///
+    /// Event times are relative to the start of the session.
+    pub fn add_event(
+        &mut self, event_hdr: &EventHeader, event: &Event,
+    ) -> qlog::Result<()> {
+        if event_hdr.source.id != self.source_id {
+            return Ok(());
+        }
+
+        let time = relative_time(event_hdr);
+
+        match event {
+            Event::Quic(ev) => self.add_quic_event(time, ev),
+
+            Event::H3(ev) => self.add_h3_event(time, ev),
+
+            Event::Http(_) | Event::H2(_) | Event::Socket(_) | Event::Dns(_) =>
+                Ok(()),
+        }
+    }
+
+    /// Writes any pending events and finishes the trace.
+    ///
+    /// Frames sent after the last packet sent are dropped, since the packet
+    /// they belong to is unknown.
+    pub fn finish(mut self) -> qlog::Result<()> {
+        self.flush_received()?;
+
+        self.streamer.finish_log()
+    }
+
+    fn add_quic_event(
+        &mut self, time: f64, ev: &crate::quic::Event,
+    ) -> qlog::Result<()> {
+        match ev {
+            QuicSession(_) => Ok(()),
+
+            QuicSessionTransportParametersSent(e) => {
+                let tp: TransportParameters =
+                    e.params.quic_transport_parameters.clone().into();
+
+                self.write(
+                    time,
+                    EventData::QuicParametersSet(Box::new(parameters_set(
+                        TransportInitiator::Local,
+                        tp,
+                    ))),
+                )
+            },
+
+            QuicSessionTransportParametersReceived(e) => {
+                let tp: TransportParameters =
+                    e.params.quic_transport_parameters.clone().into();
+
+                self.write(
+                    time,
+                    EventData::QuicParametersSet(Box::new(parameters_set(
+                        TransportInitiator::Remote,
+                        tp,
+                    ))),
+                )
+            },
+
+            QuicSessionUnauthenticatedPacketHeaderReceived(e) => {
+                self.flush_received()?;
+
+                let ty = packet_type_from_header(
+                    &e.params.header_format,
+                    &e.params.long_header_type,
+                );
+
+                self.received = Some((time, quic::PacketReceived {
+                    header: PacketHeader::with_type(
+                        ty,
+                        Some(e.params.packet_number),
+                        None,
+                        None,
+                        None,
+                    ),
+                    ..Default::default()
+                }));
+
+                Ok(())
+            },
+
+            QuicSessionPacketSent(e) => {
+                self.flush_received()?;
+
+                let ty =
+                    packet_type_from_encryption_level(&e.params.encryption_level);
+                let frames = std::mem::take(&mut self.sent_frames);
+
+                self.sent_packet_types
+                    .insert(e.params.packet_number, ty.clone());
+
+                self.write(
+                    time,
+                    EventData::QuicPacketSent(quic::PacketSent {
+                        header: PacketHeader::with_type(
+                            ty,
+                            Some(e.params.packet_number),
+                            None,
+                            None,
+                            None,
+                        ),
+                        frames: (!frames.is_empty()).then_some(frames),
+                        ..Default::default()
+                    }),
+                )
+            },
+
+            QuicSessionAckFrameSent(e) => {
+                self.sent_frames.push(ack_frame(
+                    e.params.smallest_observed,
+                    e.params.largest_observed,
+                    &e.params.missing_packets,
+                    e.params.delta_time_largest_observed_us,
+                ));
+
+                Ok(())
+            },
+
+            QuicSessionAckFrameReceived(e) => self.add_received_frame(
+                time,
+                ack_frame(
+                    e.params.smallest_observed,
+                    e.params.largest_observed,
+                    &e.params.missing_packets,
+                    e.params.delta_time_largest_observed_us,
+                ),
+            ),
+
+            QuicSessionStreamFrameSent(e) => {
+                self.sent_frames.push(stream_frame(
+                    e.params.stream_id,
+                    e.params.offset,
+                    e.params.length,
+                    e.params.fin,
+                ));
+
+                Ok(())
+            },
+
+            QuicSessionStreamFrameReceived(e) => self.add_received_frame(
+                time,
+                stream_frame(
+                    e.params.stream_id,
+                    e.params.offset,
+                    e.params.length,
+                    e.params.fin,
+                ),
+            ),
+
+            QuicSessionStopSendingFrameSent(e) => {
+                self.sent_frames.push(QuicFrame::StopSending {
+                    stream_id: e.params.stream_id,
+                    error: ApplicationError::Unknown,
+                    error_code: Some(e.params.quic_rst_stream_error),
+                    raw: None,
+                });
+
+                Ok(())
+            },
+
+            QuicSessionRstStreamFrameSent(e) => {
+                self.sent_frames.push(QuicFrame::ResetStream {
+                    stream_id: e.params.stream_id,
+                    error: ApplicationError::Unknown,
+                    error_code: Some(e.params.quic_rst_stream_error),
+                    final_size: e.params.offset,
+                    raw: None,
+                });
+
+                Ok(())
+            },
+
+            QuicSessionRstStreamFrameReceived(e) =>
+                self.add_received_frame(time, QuicFrame::ResetStream {
+                    stream_id: e.params.stream_id,
+                    error: ApplicationError::Unknown,
+                    error_code: Some(e.params.quic_rst_stream_error),
+                    final_size: e.params.offset,
+                    raw: None,
+                }),
+
+            // Netlog doesn't log the limit, which qlog requires.
+            QuicSessionBlockedFrameReceived(_) => Ok(()),
+
+            QuicSessionWindowUpdateFrameSent(e) => {
+                let frame = if e.params.stream_id == CONNECTION_STREAM_ID {
+                    QuicFrame::MaxData {
+                        maximum: e.params.byte_offset,
+                        raw: None,
+                    }
+                } else {
+                    QuicFrame::MaxStreamData {
+                        stream_id: e.params.stream_id as u64,
+                        maximum: e.params.byte_offset,
+                        raw: None,
+                    }
+                };
+
+                self.sent_frames.push(frame);
+
+                Ok(())
+            },
+
+            QuicSessionCryptoFrameSent(e) => {
+                self.sent_frames
+                    .push(crypto_frame(e.params.offset, e.params.data_length));
+
+                Ok(())
+            },
+
+            QuicSessionCryptoFrameReceived(e) => self.add_received_frame(
+                time,
+                crypto_frame(e.params.offset, e.params.data_length),
+            ),
+
+            QuicSessionHandshakeDoneFrameReceived(_) => self
+                .add_received_frame(time, QuicFrame::HandshakeDone { raw: None }),
+
+            QuicSessionPathChallengeFrameSent(e) => {
+                self.sent_frames.push(QuicFrame::PathChallenge {
+                    data: Some(e.params.data.clone()),
+                    raw: None,
+                });
+
+                Ok(())
+            },
+
+            QuicSessionPathChallengeFrameReceived(e) =>
+                self.add_received_frame(time, QuicFrame::PathChallenge {
+                    data: Some(e.params.data.clone()),
+                    raw: None,
+                }),
+
+            QuicSessionPathResponseFrameSent(e) => {
+                self.sent_frames.push(QuicFrame::PathResponse {
+                    data: Some(e.params.data.clone()),
+                    raw: None,
+                });
+
+                Ok(())
+            },
+
+            QuicSessionPathResponseFrameReceived(e) =>
+                self.add_received_frame(time, QuicFrame::PathResponse {
+                    data: Some(e.params.data.clone()),
+                    raw: None,
+                }),
+
+            QuicSessionNewConnectionIdFrameReceived(e) => {
+                // Connection IDs are hex encoded.
+                let len = e.params.connection_id.len() / 2;
+
+                self.add_received_frame(time, QuicFrame::NewConnectionId {
+                    sequence_number: e.params.sequence_number,
+                    retire_prior_to: e.params.retire_prior_to,
+                    connection_id_length: u8::try_from(len).ok(),
+                    connection_id: e.params.connection_id.clone(),
+                    stateless_reset_token: None,
+                    raw: None,
+                })
+            },
+
+            QuicSessionPacketLost(e) => {
+                let ty = self
+                    .sent_packet_types
+                    .remove(&e.params.packet_number)
+                    .unwrap_or(PacketType::Unknown);
+
+                self.write(
+                    time,
+                    EventData::QuicPacketLost(quic::PacketLost {
+                        header: Some(PacketHeader::with_type(
+                            ty,
+                            Some(e.params.packet_number),
+                            None,
+                            None,
+                            None,
+                        )),
+                        ..Default::default()
+                    }),
+                )
+            },
+
+            QuicSessionVersionNegotiated(e) => self.write(
+                time,
+                EventData::QuicVersionInformation(quic::QuicVersionInformation {
+                    chosen_version: Some(e.params.version.clone()),
+                    ..Default::default()
+                }),
+            ),
+
+            QuicSessionVersionNegotiationPacketReceived(e) => self.write(
+                time,
+                EventData::QuicVersionInformation(quic::QuicVersionInformation {
+                    server_versions: Some(e.params.versions.clone()),
+                    ..Default::default()
+                }),
+            ),
+
+            // qlog has no equivalent events. Migration events are also logged
+            // against another source than the session.
+            QuicSessionPacketRetransmitted(_) |
+            QuicSessionCertificateVerified(_) |
+            QuicConnectionMigrationTriggered(_) |
+            QuicConnectionMigrationSuccess(_) |
+            QuicConnectionMigrationFailure(_) => Ok(()),
+
+            QuicSessionClosed(e) => {
+                self.flush_received()?;
+
+                let initiator = if e.params.from_peer {
+                    TransportInitiator::Remote
+                } else {
+                    TransportInitiator::Local
+                };
+
+                self.write(
+                    time,
+                    EventData::QuicConnectionClosed(quic::ConnectionClosed {
+                        initiator: Some(initiator),
+                        connection_error: None,
+                        application_error: None,
+                        error_code: None,
+                        internal_code: u64::try_from(e.params.quic_error).ok(),
+                        reason: Some(e.params.details.clone()),
+                        trigger: None,
+                    }),
+                )
+            },
+        }
+    }
+
+    fn add_h3_event(&mut self, time: f64, ev: &h3::Event) -> qlog::Result<()> {
+        match ev {
+            h3::Event::Http3HeadersSent(e) => self.write(
+                time,
+                EventData::Http3FrameCreated(http3::FrameCreated {
+                    stream_id: e.params.stream_id,
+                    frame: Http3Frame::Headers {
+                        headers: http_headers(&e.params.headers),
+                        raw: None,
+                    },
+                    ..Default::default()
+                }),
+            ),
+
+            h3::Event::Http3DataSent(e) => self.write(
+                time,
+                EventData::Http3FrameCreated(http3::FrameCreated {
+                    stream_id: e.params.stream_id,
+                    frame: Http3Frame::Data {
+                        raw: Some(payload_length(e.params.payload_length)),
+                    },
+                    ..Default::default()
+                }),
+            ),
+
+            h3::Event::Http3HeadersReceived(e) => {
+                self.headers_length.insert(
+                    e.params.stream_id,
+                    e.params.compressed_headers_length,
+                );
+
+                Ok(())
+            },
+
+            h3::Event::Http3HeadersDecoded(e) => {
+                let length = self.headers_length.remove(&e.params.stream_id);
+
+                self.write(
+                    time,
+                    EventData::Http3FrameParsed(http3::FrameParsed {
+                        stream_id: e.params.stream_id,
+                        frame: Http3Frame::Headers {
+                            headers: http_headers(&e.params.headers),
+                            raw: length.map(payload_length),
+                        },
+                        ..Default::default()
+                    }),
+                )
+            },
+
+            h3::Event::Http3DataFrameReceived(e) => self.write(
+                time,
+                EventData::Http3FrameParsed(http3::FrameParsed {
+                    stream_id: e.params.stream_id,
+                    frame: Http3Frame::Data {
+                        raw: Some(payload_length(e.params.payload_length)),
+                    },
+                    ..Default::default()
+                }),
+            ),
+
+            h3::Event::Http3PriorityUpdateSent(e) => {
+                let id = Some(e.params.prioritized_element_id);
+                let (stream_id, push_id) = match e.params.ty {
+                    Some(h3::PrioritizedElementType::PushStream) => (None, id),
+
+                    _ => (id, None),
+                };
+
+                self.write(
+                    time,
+                    EventData::Http3PriorityUpdated(http3::PriorityUpdated {
+                        stream_id,
+                        push_id,
+                        new: e.params.priority_field_value.clone(),
+                        trigger: Some(http3::PriorityUpdatedTrigger::Local),
+                        ..Default::default()
+                    }),
+                )
+            },
+        }
+    }
+
+    fn add_received_frame(
+        &mut self, time: f64, frame: QuicFrame,
+    ) -> qlog::Result<()> {
+        match &mut self.received {
+            Some((_, pkt)) => {
+                pkt.frames.get_or_insert_with(Vec::new).push(frame);
+
+                Ok(())
+            },
+
+            // The packet header wasn't logged, write the frame on its own.
+            None => self.write(
+                time,
+                EventData::QuicFramesProcessed(quic::FramesProcessed {
+                    frames: vec![frame],
+                    packet_numbers: None,
+                }),
+            ),
+        }
+    }
+
+    fn write(&mut self, time: f64, data: EventData) -> qlog::Result<()> {
+        let event = qlog::events::Event::with_time(time, data);
+
+        if self.received.is_some() {
+            self.deferred.push(event);
+            return Ok(());
+        }
+
+        self.streamer.add_event(event)
+    }
+
+    /// Writes the pending packet received, followed by events deferred
+    /// while it was pending.
+    fn flush_received(&mut self) -> qlog::Result<()> {
+        if let Some((time, pkt)) = self.received.take() {
+            self.streamer.add_event(qlog::events::Event::with_time(
+                time,
+                EventData::QuicPacketReceived(pkt),
+            ))?;
+        }
+
+        for event in std::mem::take(&mut self.deferred) {
+            self.streamer.add_event(event)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the time of the event relative to the start of its source, in
+/// milliseconds.
+fn relative_time(event_hdr: &EventHeader) -> f64 {
+    let time = event_hdr.time.parse().unwrap_or(event_hdr.time_num);
+    let start = event_hdr
+        .source
+        .start_time
+        .parse()
+        .unwrap_or(event_hdr.source.start_time_int);
+
+    time.saturating_sub(start) as f64
+}
+
+fn packet_type_from_header(
+    header_format: &str, long_header_type: &Option<String>,
+) -> PacketType {
+    match (header_format, long_header_type.as_deref()) {
+        ("IETF_QUIC_LONG_HEADER_PACKET", Some("INITIAL")) => PacketType::Initial,
+
+        ("IETF_QUIC_LONG_HEADER_PACKET", Some("HANDSHAKE")) =>
+            PacketType::Handshake,
+
+        ("IETF_QUIC_LONG_HEADER_PACKET", Some("ZERO_RTT_PROTECTED")) =>
+            PacketType::ZeroRtt,
+
+        ("IETF_QUIC_LONG_HEADER_PACKET", Some("RETRY")) => PacketType::Retry,
+
+        ("IETF_QUIC_SHORT_HEADER_PACKET", _) => PacketType::OneRtt,
+
+        _ => PacketType::Unknown,
+    }
+}
+
+fn packet_type_from_encryption_level(encryption_level: &str) -> PacketType {
+    match encryption_level {
+        "ENCRYPTION_INITIAL" => PacketType::Initial,
+
+        "ENCRYPTION_HANDSHAKE" => PacketType::Handshake,
+
+        "ENCRYPTION_ZERO_RTT" => PacketType::ZeroRtt,
+
+        "ENCRYPTION_FORWARD_SECURE" => PacketType::OneRtt,
+
+        _ => PacketType::Unknown,
+    }
+}
+
+/// Builds an ACK frame acknowledging `smallest` to `largest`, except
+/// `missing` packets.
+fn ack_frame(
+    smallest: u64, largest: u64, missing: &[u64], ack_delay_us: u64,
+) -> QuicFrame {
+    let mut missing = missing.to_vec();
+    missing.sort_unstable();
+
+    let mut acked_ranges = vec![];
+    let mut start = smallest;
+
+    for pn in missing {
+        if pn < start || pn > largest {
+            continue;
+        }
+
+        if pn > start {
+            acked_ranges.push(AckRange::new(start, pn - 1));
+        }
+
+        start = pn + 1;
+    }
+
+    if start <= largest {
+        acked_ranges.push(AckRange::new(start, largest));
+    }
+
+    QuicFrame::Ack {
+        ack_delay: Some(ack_delay_us as f32 / 1000.0),
+        acked_ranges: Some(acked_ranges),
+        ect1: None,
+        ect0: None,
+        ce: None,
+        raw: None,
+    }
+}
+
+fn stream_frame(
+    stream_id: u64, offset: u64, length: u64, fin: bool,
+) -> QuicFrame {
+    QuicFrame::Stream {
+        stream_id,
+        offset: Some(offset),
+        fin: fin.then_some(true),
+        raw: Some(Box::new(payload_length(length))),
+    }
+}
+
+fn crypto_frame(offset: u64, length: u64) -> QuicFrame {
+    QuicFrame::Crypto {
+        offset,
+        raw: Some(Box::new(payload_length(length))),
+    }
+}
+
+fn payload_length(length: u64) -> RawInfo {
+    RawInfo {
+        length: None,
+        payload_length: Some(length),
+        data: None,
+    }
+}
+
+/// Converts headers logged as `name: value` strings.
+fn http_headers(headers: &[String]) -> Vec<HttpHeader> {
+    headers
+        .iter()
+        .map(|hdr| {
+            let (name, value) = hdr.split_once(": ").unwrap_or((hdr, ""));
+
+            HttpHeader {
+                name: Some(name.to_string()),
+                name_bytes: None,
+                value: Some(value.to_string()),
+                value_bytes: None,
+            }
+        })
+        .collect()
+}
+
+fn parameters_set(
+    initiator: TransportInitiator, tp: TransportParameters,
+) -> quic::ParametersSet {
+    quic::ParametersSet {
+        initiator: Some(initiator),
+        max_idle_timeout: tp.max_idle_timeout,
+        max_udp_payload_size: tp.max_udp_payload_size,
+        initial_max_data: tp.initial_max_data,
+        initial_max_stream_data_bidi_local: tp.initial_max_stream_data_bidi_local,
+        initial_max_stream_data_bidi_remote: tp
+            .initial_max_stream_data_bidi_remote,
+        initial_max_stream_data_uni: tp.initial_max_stream_data_uni,
+        initial_max_streams_bidi: tp.initial_max_streams_bidi,
+        initial_max_streams_uni: tp.initial_max_streams_uni,
+        initial_source_connection_id: tp.initial_source_connection_id,
+        max_datagram_frame_size: tp.max_datagram_frame_size,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EventSource;
+    use serde_json::json;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    const SESSION_ID: i64 = 7;
+
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Parses a netlog event of the session, logged `time` ms after it
+    /// started.
+    fn event(
+        time: u64, ty: &str, params: serde_json::Value,
+    ) -> (EventHeader, Event) {
+        event_of(SESSION_ID, time, ty, params)
+    }
+
+    fn event_of(
+        source_id: i64, time: u64, ty: &str, params: serde_json::Value,
+    ) -> (EventHeader, Event) {
+        let event_hdr = EventHeader {
+            ty_string: ty.to_string(),
+            phase_string: "PHASE_NONE".to_string(),
+            time_num: 1000 + time,
+            time: (1000 + time).to_string(),
+            source: EventSource {
+                start_time_int: 1000,
+                id: source_id,
+                start_time: "1000".to_string(),
+                ty: 0,
+            },
+            ..Default::default()
+        };
+
+        let record = json!({ "params": params }).to_string();
+        let event = crate::parse_event(&event_hdr, record.as_bytes()).unwrap();
+
+        (event_hdr, event)
+    }
+
+    fn packet_header_received(time: u64, pn: u64) -> (EventHeader, Event) {
+        event(
+            time,
+            "QUIC_SESSION_UNAUTHENTICATED_PACKET_HEADER_RECEIVED",
+            json!({
+                "connection_id": "0102030405060708",
+                "header_format": "IETF_QUIC_SHORT_HEADER_PACKET",
+                "packet_number": pn,
+            }),
+        )
+    }
+
+    fn packet_sent(time: u64, level: &str, pn: u64) -> (EventHeader, Event) {
+        event(
+            time,
+            "QUIC_SESSION_PACKET_SENT",
+            json!({
+                "encryption_level": level,
+                "packet_number": pn,
+                "sent_time_us": time * 1000,
+                "transmission_type": "NOT_RETRANSMISSION",
+            }),
+        )
+    }
+
+    fn stream_frame_event(
+        time: u64, ty: &str, stream_id: u64, length: u64, fin: bool,
+    ) -> (EventHeader, Event) {
+        event(
+            time,
+            ty,
+            json!({
+                "stream_id": stream_id,
+                "fin": fin,
+                "offset": 0,
+                "length": length,
+            }),
+        )
+    }
+
+    fn ack_frame_event(
+        time: u64, ty: &str, smallest: u64, largest: u64, missing: &[u64],
+    ) -> (EventHeader, Event) {
+        event(
+            time,
+            ty,
+            json!({
+                "delta_time_largest_observed_us": 2500,
+                "largest_observed": largest,
+                "missing_packets": missing,
+                "received_packet_times": [],
+                "smallest_observed": smallest,
+            }),
+        )
+    }
+
+    /// Converts `events` and reads back the qlog events written.
+    fn convert(events: &[(EventHeader, Event)]) -> Vec<qlog::events::Event> {
+        let buf = SharedBuf::default();
+
+        let mut converter =
+            QlogConverter::new(SESSION_ID, None, Box::new(buf.clone())).unwrap();
+
+        for (event_hdr, event) in events {
+            converter.add_event(event_hdr, event).unwrap();
+        }
+
+        converter.finish().unwrap();
+
+        let output = String::from_utf8(buf.0.lock().unwrap().clone()).unwrap();
+
+        // Skip the header record.
+        output
+            .split('\u{1e}')
+            .filter(|record| !record.trim().is_empty())
+            .skip(1)
+            .map(|record| serde_json::from_str(record).unwrap())
+            .collect()
+    }
+
+    fn assert_header(header: &PacketHeader, ty: PacketType, pn: u64) {
+        assert_eq!(header.packet_type, ty);
+        assert_eq!(header.packet_number, Some(pn));
+    }
+
+    #[test]
+    fn packet_sent_frames() {
+        let events = convert(&[
+            stream_frame_event(1, "QUIC_SESSION_STREAM_FRAME_SENT", 0, 100, true),
+            ack_frame_event(1, "QUIC_SESSION_ACK_FRAME_SENT", 0, 2, &[]),
+            packet_sent(2, "ENCRYPTION_FORWARD_SECURE", 3),
+            packet_sent(3, "ENCRYPTION_FORWARD_SECURE", 4),
+        ]);
+
+        assert_eq!(events.len(), 2);
+
+        let EventData::QuicPacketSent(pkt) = &events[0].data else {
+            panic!("unexpected event {:?}", events[0]);
+        };
+        assert_eq!(events[0].time, 2.0);
+        assert_header(&pkt.header, PacketType::OneRtt, 3);
+        assert_eq!(
+            pkt.frames,
+            Some(vec![
+                stream_frame(0, 0, 100, true),
+                ack_frame(0, 2, &[], 2500),
+            ])
+        );
+
+        // Frames are only grouped into the packet sent after them.
+        let EventData::QuicPacketSent(pkt) = &events[1].data else {
+            panic!("unexpected event {:?}", events[1]);
+        };
+        assert_header(&pkt.header, PacketType::OneRtt, 4);
+        assert_eq!(pkt.frames, None);
+    }
+
+    #[test]
+    fn packet_received_frames() {
+        let events = convert(&[
+            packet_header_received(1, 7),
+            stream_frame_event(
+                1,
+                "QUIC_SESSION_STREAM_FRAME_RECEIVED",
+                3,
+                10,
+                false,
+            ),
+            event(
+                1,
+                "QUIC_SESSION_CRYPTO_FRAME_RECEIVED",
+                json!({
+                    "encryption_level": "ENCRYPTION_FORWARD_SECURE",
+                    "data_length": 20,
+                    "offset": 5,
+                }),
+            ),
+            packet_header_received(2, 8),
+        ]);
+
+        assert_eq!(events.len(), 2);
+
+        let EventData::QuicPacketReceived(pkt) = &events[0].data else {
+            panic!("unexpected event {:?}", events[0]);
+        };
+        assert_eq!(events[0].time, 1.0);
+        assert_header(&pkt.header, PacketType::OneRtt, 7);
+        assert_eq!(
+            pkt.frames,
+            Some(vec![stream_frame(3, 0, 10, false), crypto_frame(5, 20)])
+        );
+
+        // The last packet is written when the converter finishes.
+        let EventData::QuicPacketReceived(pkt) = &events[1].data else {
+            panic!("unexpected event {:?}", events[1]);
+        };
+        assert_eq!(events[1].time, 2.0);
+        assert_header(&pkt.header, PacketType::OneRtt, 8);
+        assert_eq!(pkt.frames, None);
+    }
+
+    #[test]
+    fn frame_without_packet() {
+        let events = convert(&[stream_frame_event(
+            1,
+            "QUIC_SESSION_STREAM_FRAME_RECEIVED",
+            3,
+            10,
+            false,
+        )]);
+
+        assert_eq!(events.len(), 1);
+
+        let EventData::QuicFramesProcessed(ev) = &events[0].data else {
+            panic!("unexpected event {:?}", events[0]);
+        };
+        assert_eq!(ev.frames, vec![stream_frame(3, 0, 10, false)]);
+    }
+
+    #[test]
+    fn ack_ranges() {
+        let events = convert(&[
+            packet_header_received(1, 7),
+            ack_frame_event(1, "QUIC_SESSION_ACK_FRAME_RECEIVED", 1, 10, &[
+                8, 3, 4, 12,
+            ]),
+        ]);
+
+        let EventData::QuicPacketReceived(pkt) = &events[0].data else {
+            panic!("unexpected event {:?}", events[0]);
+        };
+
+        let Some(
+            [QuicFrame::Ack {
+                ack_delay,
+                acked_ranges,
+                ..
+            }],
+        ) = pkt.frames.as_deref()
+        else {
+            panic!("unexpected frames {:?}", pkt.frames);
+        };
+
+        assert_eq!(*ack_delay, Some(2.5));
+        assert_eq!(
+            acked_ranges.as_deref(),
+            Some(
+                &[
+                    AckRange::new(1, 2),
+                    AckRange::new(5, 7),
+                    AckRange::new(9, 10)
+                ][..]
+            )
+        );
+    }
+
+    #[test]
+    fn ack_ranges_missing_edges() {
+        let QuicFrame::Ack { acked_ranges, .. } = ack_frame(1, 10, &[1, 10], 0)
+        else {
+            unreachable!();
+        };
+        assert_eq!(acked_ranges, Some(vec![AckRange::new(2, 9)]));
+
+        let QuicFrame::Ack { acked_ranges, .. } = ack_frame(4, 4, &[], 0) else {
+            unreachable!();
+        };
+        assert_eq!(acked_ranges, Some(vec![AckRange::new(4, 4)]));
+
+        let QuicFrame::Ack { acked_ranges, .. } = ack_frame(4, 5, &[4, 5], 0)
+        else {
+            unreachable!();
+        };
+        assert_eq!(acked_ranges, Some(vec![]));
+    }
+
+    #[test]
+    fn deferred_event_order() {
+        let events = convert(&[
+            packet_header_received(1, 7),
+            event(
+                1,
+                "HTTP3_HEADERS_RECEIVED",
+                json!({ "stream_id": 0, "compressed_headers_length": 42 }),
+            ),
+            stream_frame_event(
+                1,
+                "QUIC_SESSION_STREAM_FRAME_RECEIVED",
+                0,
+                50,
+                false,
+            ),
+            event(
+                2,
+                "HTTP3_HEADERS_DECODED",
+                json!({ "stream_id": 0, "headers": [":status: 200"] }),
+            ),
+            packet_sent(3, "ENCRYPTION_FORWARD_SECURE", 1),
+        ]);
+
+        // Events logged while the packet received was pending follow it.
+        let names: Vec<_> = events
+            .iter()
+            .map(|ev| match &ev.data {
+                EventData::QuicPacketReceived(_) => "packet_received",
+                EventData::Http3FrameParsed(_) => "frame_parsed",
+                EventData::QuicPacketSent(_) => "packet_sent",
+                _ => panic!("unexpected event {ev:?}"),
+            })
+            .collect();
+
+        assert_eq!(names, vec![
+            "packet_received",
+            "frame_parsed",
+            "packet_sent"
+        ]);
+        assert_eq!(events.iter().map(|ev| ev.time).collect::<Vec<_>>(), vec![
+            1.0, 2.0, 3.0
+        ]);
+    }
+
+    #[test]
+    fn h3_headers() {
+        let events = convert(&[
+            event(
+                1,
+                "HTTP3_HEADERS_SENT",
+                json!({
+                    "stream_id": 0,
+                    "headers": [":method: GET", ":path: /", "x-empty"],
+                }),
+            ),
+            event(
+                2,
+                "HTTP3_HEADERS_RECEIVED",
+                json!({ "stream_id": 0, "compressed_headers_length": 42 }),
+            ),
+            event(
+                2,
+                "HTTP3_HEADERS_DECODED",
+                json!({ "stream_id": 0, "headers": [":status: 200"] }),
+            ),
+        ]);
+
+        assert_eq!(events.len(), 2);
+
+        let EventData::Http3FrameCreated(ev) = &events[0].data else {
+            panic!("unexpected event {:?}", events[0]);
+        };
+        assert_eq!(ev.stream_id, 0);
+        assert_eq!(ev.frame, Http3Frame::Headers {
+            headers: http_headers(&[
+                ":method: GET".to_string(),
+                ":path: /".to_string(),
+                "x-empty".to_string(),
+            ]),
+            raw: None,
+        });
+
+        let Http3Frame::Headers { headers, .. } = &ev.frame else {
+            unreachable!();
+        };
+        assert_eq!(headers[1].name.as_deref(), Some(":path"));
+        assert_eq!(headers[1].value.as_deref(), Some("/"));
+        assert_eq!(headers[2].name.as_deref(), Some("x-empty"));
+        assert_eq!(headers[2].value.as_deref(), Some(""));
+
+        // The compressed length comes from the event received before decoding.
+        let EventData::Http3FrameParsed(ev) = &events[1].data else {
+            panic!("unexpected event {:?}", events[1]);
+        };
+        assert_eq!(ev.frame, Http3Frame::Headers {
+            headers: http_headers(&[":status: 200".to_string()]),
+            raw: Some(payload_length(42)),
+        });
+    }
+
+    #[test]
+    fn lost_packet_type() {
+        let events = convert(&[
+            packet_sent(1, "ENCRYPTION_INITIAL", 0),
+            event(
+                2,
+                "QUIC_SESSION_PACKET_LOST",
+                json!({
+                    "transmission_type": "NOT_RETRANSMISSION",
+                    "packet_number": 0,
+                    "detection_time_us": 2000,
+                }),
+            ),
+        ]);
+
+        let EventData::QuicPacketLost(ev) = &events[1].data else {
+            panic!("unexpected event {:?}", events[1]);
+        };
+        assert_header(ev.header.as_ref().unwrap(), PacketType::Initial, 0);
+    }
+
+    #[test]
+    fn other_sources_ignored() {
+        let events = convert(&[
+            event_of(
+                SESSION_ID + 1,
+                1,
+                "HTTP3_HEADERS_SENT",
+                json!({ "stream_id": 0, "headers": [] }),
+            ),
+            packet_sent(1, "ENCRYPTION_INITIAL", 0),
+        ]);
+
+        assert_eq!(events.len(), 1);
+        assert!(matches!(events[0].data, EventData::QuicPacketSent(_)));
+    }
+}
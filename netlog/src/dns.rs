@@ -0,0 +1,172 @@
+// Copyright (C) 2025, Cloudflare, Inc.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are
+// met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//
+//     * Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in the
+//       documentation and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS
+// IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO,
+// THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR
+// PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Host and proxy resolution events, which happen before a connection to a
+//! server is made.
+
+use serde::Deserialize;
+
+use super::deserialize_event;
+use super::EventHeader;
+use super::NetErrorParams;
+
+#[derive(Debug)]
+pub enum Event {
+    HostResolverRequest(HostResolverRequestEvent),
+    HostResolverRequestEnd(HostResolverRequestEndEvent),
+    HostResolverCacheHit(HostResolverCacheHitEvent),
+    ProxyResolutionServiceResolvedProxyList(
+        ProxyResolutionServiceResolvedProxyListEvent,
+    ),
+    ProxyResolutionServiceEnd(ProxyResolutionServiceEndEvent),
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct HostResolverRequestParams {
+    /// The resolved host, with a scheme and port in recent netlogs, e.g.
+    /// `https://example.com:443`.
+    pub host: Option<String>,
+    pub dns_query_type: Option<String>,
+    pub allow_cached_response: Option<bool>,
+    pub is_speculative: Option<bool>,
+    pub secure_dns_policy: Option<String>,
+}
+
+/// The start of a host resolution request.
+#[derive(Deserialize, Debug, Default)]
+pub struct HostResolverRequestEvent {
+    pub params: HostResolverRequestParams,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct HostResolverRequestEndParams {
+    pub net_error: Option<i64>,
+    /// The resolved addresses and metadata, such as HTTPS records. Their
+    /// layout depends on the browser version, so they are left as JSON.
+    pub results: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct HostResolverRequestEndEvent {
+    #[serde(default)]
+    pub params: HostResolverRequestEndParams,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct HostResolverCacheHitParams {
+    pub results: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct HostResolverCacheHitEvent {
+    #[serde(default)]
+    pub params: HostResolverCacheHitParams,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct ProxyResolutionServiceResolvedProxyListParams {
+    /// The proxies to use, in PAC format, e.g. `DIRECT`.
+    pub proxy_info: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct ProxyResolutionServiceResolvedProxyListEvent {
+    pub params: ProxyResolutionServiceResolvedProxyListParams,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct ProxyResolutionServiceEndEvent {
+    #[serde(default)]
+    pub params: NetErrorParams,
+}
+
+/// Parses the provided `event` based on the event type provided in `event_hdr`.
+pub fn parse_event(
+    event_hdr: &EventHeader, event: &[u8],
+) -> Option<super::Event> {
+    let begin = event_hdr.phase_string == "PHASE_BEGIN";
+    let end = event_hdr.phase_string == "PHASE_END";
+
+    match event_hdr.ty_string.as_str() {
+        // Older netlogs use the HOST_RESOLVER_IMPL prefix.
+        "HOST_RESOLVER_MANAGER_REQUEST" | "HOST_RESOLVER_IMPL_REQUEST" =>
+            if begin {
+                let ev: HostResolverRequestEvent =
+                    deserialize_event(event_hdr, event)?;
+                return Some(super::Event::Dns(Event::HostResolverRequest(ev)));
+            } else if end {
+                let ev: HostResolverRequestEndEvent =
+                    deserialize_event(event_hdr, event)?;
+                return Some(super::Event::Dns(Event::HostResolverRequestEnd(
+                    ev,
+                )));
+            },
+
+        "HOST_RESOLVER_MANAGER_CACHE_HIT" | "HOST_RESOLVER_IMPL_CACHE_HIT" => {
+            let ev: HostResolverCacheHitEvent =
+                deserialize_event(event_hdr, event)?;
+            return Some(super::Event::Dns(Event::HostResolverCacheHit(ev)));
+        },
+
+        "PROXY_RESOLUTION_SERVICE_RESOLVED_PROXY_LIST" => {
+            let ev: ProxyResolutionServiceResolvedProxyListEvent =
+                deserialize_event(event_hdr, event)?;
+            return Some(super::Event::Dns(
+                Event::ProxyResolutionServiceResolvedProxyList(ev),
+            ));
+        },
+
+        "PROXY_RESOLUTION_SERVICE" =>
+            if end {
+                let ev: ProxyResolutionServiceEndEvent =
+                    deserialize_event(event_hdr, event)?;
+                return Some(super::Event::Dns(
+                    Event::ProxyResolutionServiceEnd(ev),
+                ));
+            },
+
+        // Other events observed in netlogs but not currently supported.
+        "HOST_RESOLVER_MANAGER_ATTEMPT_STARTED" |
+        "HOST_RESOLVER_MANAGER_ATTEMPT_FINISHED" |
+        "HOST_RESOLVER_MANAGER_CREATE_JOB" |
+        "HOST_RESOLVER_MANAGER_JOB" |
+        "HOST_RESOLVER_MANAGER_JOB_ATTACH" |
+        "HOST_RESOLVER_MANAGER_JOB_REQUEST_ATTACH" |
+        "HOST_RESOLVER_MANAGER_JOB_REQUEST_DETACH" |
+        "HOST_RESOLVER_MANAGER_JOB_STARTED" |
+        "HOST_RESOLVER_DNS_TASK" |
+        "HOST_RESOLVER_SYSTEM_TASK" => (),
+
+        // The netlog format is continually evolving, log any unknown types
+        // in case they are interesting.
+        _ => log::trace!(
+            "skipping unknown resolution type....{}",
+            event_hdr.ty_string
+        ),
+    }
+
+    None
+}
@@ -28,7 +28,9 @@ use std::collections::BTreeMap;
 
 use serde::Deserialize;
 
+use super::deserialize_event;
 use super::EventHeader;
+use super::NetErrorParams;
 use super::SourceDependency;
 
 #[derive(Debug)]
@@ -44,10 +46,23 @@ pub enum Event {
     HttpStreamJobBoundToRequest(HttpStreamJobBoundToRequestEvent),
     HttpStreamRequestBoundToJob(HttpStreamRequestBoundToJobEvent),
     HttpStreamRequestBoundToQuicSession(HttpStreamRequestBoundToQuicSessionEvent),
+    CorsRequest(CorsRequestEvent),
+    HttpStreamJobController(HttpStreamJobControllerEvent),
+    HttpStreamJobControllerBound(HttpStreamJobControllerBoundEvent),
+    HttpStreamJobControllerAltSvcFound(HttpStreamJobControllerAltSvcFoundEvent),
+    HttpStreamRequestStartedJob(HttpStreamRequestStartedJobEvent),
+    HttpStreamJob(HttpStreamJobEvent),
+    HttpStreamJobDelayed(HttpStreamJobDelayedEvent),
+    HttpStreamJobResumed,
+    HttpStreamJobOrphaned,
+    HttpStreamJobInitConnectionEnd(HttpStreamJobInitConnectionEndEvent),
 }
 #[derive(Deserialize, Debug, Default)]
 pub struct HttpTransactionSendRequestHeadersParams {
     pub headers: Vec<String>,
+    /// The HTTP/1.1 request line, e.g. `GET / HTTP/1.1\r\n`. Only present
+    /// for HTTP/1.1 requests.
+    pub line: Option<String>,
 }
 
 #[derive(Deserialize, Debug, Default)]
@@ -119,6 +134,99 @@ pub struct HttpStreamRequestBoundToQuicSessionParams {
     pub source_dependency: SourceDependency,
 }
 
+#[derive(Deserialize, Debug, Default)]
+pub struct HttpStreamJobControllerParams {
+    pub url: Option<String>,
+    pub is_preconnect: Option<bool>,
+}
+
+/// The start of a stream job controller, which races the jobs that connect a
+/// request to a server over TCP or QUIC.
+#[derive(Deserialize, Debug, Default)]
+pub struct HttpStreamJobControllerEvent {
+    pub params: HttpStreamJobControllerParams,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct HttpStreamJobControllerBoundParams {
+    pub source_dependency: SourceDependency,
+}
+
+/// A request being bound to a stream job controller. The dependency is the
+/// controller.
+#[derive(Deserialize, Debug, Default)]
+pub struct HttpStreamJobControllerBoundEvent {
+    pub params: HttpStreamJobControllerBoundParams,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct HttpStreamJobControllerAltSvcFoundParams {
+    pub alt_svc: String,
+    /// Whether the alternative service was marked broken after a previous
+    /// failure, in which case it isn't used.
+    pub is_broken: bool,
+}
+
+/// An alternative service, such as HTTP/3, known for the origin of a request.
+#[derive(Deserialize, Debug, Default)]
+pub struct HttpStreamJobControllerAltSvcFoundEvent {
+    pub params: HttpStreamJobControllerAltSvcFoundParams,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct HttpStreamRequestStartedJobParams {
+    pub source_dependency: SourceDependency,
+}
+
+/// A stream job controller starting a job. The dependency is the job.
+#[derive(Deserialize, Debug, Default)]
+pub struct HttpStreamRequestStartedJobEvent {
+    pub params: HttpStreamRequestStartedJobParams,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct HttpStreamJobParams {
+    /// The stream job controller that started the job.
+    pub source_dependency: Option<SourceDependency>,
+    pub logical_url: Option<String>,
+    pub original_url: Option<String>,
+    pub url: Option<String>,
+    pub alternative_service: Option<String>,
+    pub expect_spdy: Option<bool>,
+    pub using_quic: Option<bool>,
+    pub priority: Option<String>,
+    /// The kind of job, e.g. `main`, `alternative`, `dns_alpn_h3` or
+    /// `preconnect`.
+    #[serde(rename = "type")]
+    pub ty: Option<String>,
+}
+
+/// The start of a stream job, which connects over a single protocol.
+#[derive(Deserialize, Debug, Default)]
+pub struct HttpStreamJobEvent {
+    pub params: HttpStreamJobParams,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct HttpStreamJobDelayedParams {
+    /// Milliseconds that the job waits for to give the racing QUIC job a
+    /// head start.
+    pub delay: Option<u64>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct HttpStreamJobDelayedEvent {
+    #[serde(default)]
+    pub params: HttpStreamJobDelayedParams,
+}
+
+/// The end of a stream job connecting. The error is only present on failure.
+#[derive(Deserialize, Debug, Default)]
+pub struct HttpStreamJobInitConnectionEndEvent {
+    #[serde(default)]
+    pub params: NetErrorParams,
+}
+
 pub fn headers_to_map(hdrs: &[String]) -> BTreeMap<String, String> {
     let mut ret = BTreeMap::new();
 
@@ -143,6 +251,11 @@ pub fn headers_to_map(hdrs: &[String]) -> BTreeMap<String, String> {
     ret
 }
 
+/// The start of a request, which is the earliest event related to it. Only
+/// its time is of interest, so params are ignored.
+#[derive(Deserialize, Debug, Default)]
+pub struct CorsRequestEvent {}
+
 /// Parses the provided `event` based on the event type provided in `event_hdr`.
 pub fn parse_event(
     event_hdr: &EventHeader, event: &[u8],
@@ -204,6 +317,72 @@ pub fn parse_event(
             ));
         },
 
+        "CORS_REQUEST" =>
+            if event_hdr.phase_string == "PHASE_BEGIN" {
+                let ev: CorsRequestEvent = serde_json::from_slice(event).unwrap();
+                return Some(super::Event::Http(Event::CorsRequest(ev)));
+            },
+
+        "HTTP_STREAM_JOB_CONTROLLER" =>
+            if event_hdr.phase_string == "PHASE_BEGIN" {
+                let ev: HttpStreamJobControllerEvent =
+                    deserialize_event(event_hdr, event)?;
+                return Some(super::Event::Http(Event::HttpStreamJobController(
+                    ev,
+                )));
+            },
+
+        "HTTP_STREAM_JOB_CONTROLLER_BOUND" => {
+            let ev: HttpStreamJobControllerBoundEvent =
+                deserialize_event(event_hdr, event)?;
+            return Some(super::Event::Http(
+                Event::HttpStreamJobControllerBound(ev),
+            ));
+        },
+
+        "HTTP_STREAM_JOB_CONTROLLER_ALT_SVC_FOUND" => {
+            let ev: HttpStreamJobControllerAltSvcFoundEvent =
+                deserialize_event(event_hdr, event)?;
+            return Some(super::Event::Http(
+                Event::HttpStreamJobControllerAltSvcFound(ev),
+            ));
+        },
+
+        "HTTP_STREAM_REQUEST_STARTED_JOB" => {
+            let ev: HttpStreamRequestStartedJobEvent =
+                deserialize_event(event_hdr, event)?;
+            return Some(super::Event::Http(Event::HttpStreamRequestStartedJob(
+                ev,
+            )));
+        },
+
+        "HTTP_STREAM_JOB" =>
+            if event_hdr.phase_string == "PHASE_BEGIN" {
+                let ev: HttpStreamJobEvent = deserialize_event(event_hdr, event)?;
+                return Some(super::Event::Http(Event::HttpStreamJob(ev)));
+            },
+
+        "HTTP_STREAM_JOB_DELAYED" => {
+            let ev: HttpStreamJobDelayedEvent =
+                deserialize_event(event_hdr, event)?;
+            return Some(super::Event::Http(Event::HttpStreamJobDelayed(ev)));
+        },
+
+        "HTTP_STREAM_JOB_RESUMED" =>
+            return Some(super::Event::Http(Event::HttpStreamJobResumed)),
+
+        "HTTP_STREAM_JOB_ORPHANED" =>
+            return Some(super::Event::Http(Event::HttpStreamJobOrphaned)),
+
+        "HTTP_STREAM_JOB_INIT_CONNECTION" =>
+            if event_hdr.phase_string == "PHASE_END" {
+                let ev: HttpStreamJobInitConnectionEndEvent =
+                    deserialize_event(event_hdr, event)?;
+                return Some(super::Event::Http(
+                    Event::HttpStreamJobInitConnectionEnd(ev),
+                ));
+            },
+
         // ignore these for now
         "HTTP_TRANSACTION_READ_EARLY_HINTS_RESPONSE_HEADERS" |
         "HTTP_TRANSACTION_READ_TUNNEL_RESPONSE_HEADERS" |
@@ -224,3 +403,48 @@ pub fn parse_event(
 
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(ty: &str, record: &str) -> Option<crate::Event> {
+        let event_hdr = EventHeader {
+            ty_string: ty.to_string(),
+            phase_string: "PHASE_NONE".to_string(),
+            ..Default::default()
+        };
+
+        parse_event(&event_hdr, record.as_bytes())
+    }
+
+    #[test]
+    fn alt_svc_found() {
+        let ev = parse(
+            "HTTP_STREAM_JOB_CONTROLLER_ALT_SVC_FOUND",
+            r#"{"params":{"alt_svc":"h3 example.com:443, expires 2026-10-19","is_broken":false}}"#,
+        );
+
+        let Some(crate::Event::Http(Event::HttpStreamJobControllerAltSvcFound(
+            ev,
+        ))) = ev
+        else {
+            panic!("unexpected event {ev:?}");
+        };
+        assert_eq!(ev.params.alt_svc, "h3 example.com:443, expires 2026-10-19");
+        assert!(!ev.params.is_broken);
+    }
+
+    #[test]
+    fn missing_params_are_skipped() {
+        // Records without the required fields are skipped.
+        let ev = parse(
+            "HTTP_STREAM_JOB_CONTROLLER_ALT_SVC_FOUND",
+            r#"{"params":{"alt_svc":"h3 example.com:443"}}"#,
+        );
+        assert!(ev.is_none());
+
+        let ev = parse("HTTP_STREAM_REQUEST_STARTED_JOB", r#"{"params":{}}"#);
+        assert!(ev.is_none());
+    }
+}
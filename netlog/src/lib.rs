@@ -25,7 +25,8 @@
 // SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 //! The netlog crate is a reverse-engineered deserializer for the Chrome
-//! [netlog] format. It supports QUIC and HTTP(/2 and /3) events.
+//! [netlog] format. It supports QUIC, HTTP(/1.1, /2 and /3), socket and DNS
+//! events.
 //!
 //! # Overview
 //!
@@ -167,6 +168,13 @@ pub struct SourceDependency {
     pub ty: i64,
 }
 
+/// Params of events that only carry a result, such as the end of a
+/// connection attempt. Netlog omits the params on success.
+#[derive(Deserialize, Debug, Default)]
+pub struct NetErrorParams {
+    pub net_error: Option<i64>,
+}
+
 /// The core netlog event type with several domain-specific variants.
 #[derive(Debug)]
 pub enum Event {
@@ -174,6 +182,8 @@ pub enum Event {
     H2(h2::Event),
     H3(h3::Event),
     Quic(quic::Event),
+    Socket(socket::Event),
+    Dns(dns::Event),
 }
 
 /// Read the netlog constants from a netlog file accessed by a BufRead.
@@ -184,7 +194,17 @@ pub fn read_netlog_constants<R: BufRead>(
 
     // Read the constants line and replace the trailing comma (,) with a brace
     // (}) to close the object and make it parseable.
-    let len = reader.read_until(b'\n', &mut buf).unwrap();
+    let len = reader
+        .read_until(b'\n', &mut buf)
+        .map_err(serde_json::Error::io)?;
+
+    if len < 2 {
+        return Err(serde_json::Error::io(std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            "missing netlog constants",
+        )));
+    }
+
     buf[len - 2] = b'}';
 
     let res: Result<ConstantsLine, serde_json::Error> =
@@ -208,7 +228,7 @@ pub fn read_netlog_constants<R: BufRead>(
 /// Reads a single record from a netlog file accessed by a BufRead.
 pub fn read_netlog_record<R: BufRead>(reader: &mut R) -> Option<Vec<u8>> {
     let mut buf = Vec::<u8>::new();
-    let size = reader.read_until(b'\n', &mut buf).unwrap();
+    let size = reader.read_until(b'\n', &mut buf).ok()?;
 
     if size <= 1 {
         return None;
@@ -219,11 +239,17 @@ pub fn read_netlog_record<R: BufRead>(reader: &mut R) -> Option<Vec<u8>> {
         return None;
     }
 
-    // Remove trailing comma and newline
-    buf.truncate(buf.len() - 2);
+    // Remove trailing comma and newline. The last line of a truncated file
+    // might have neither.
+    while buf
+        .last()
+        .is_some_and(|b| matches!(b, b',' | b'\n' | b'\r'))
+    {
+        buf.pop();
+    }
 
     // Last line of events closes array. Lets ignore it.
-    if buf[buf.len() - 1] == b']' {
+    if buf.last() == Some(&b']') {
         buf.truncate(buf.len() - 1);
     }
 
@@ -237,7 +263,9 @@ pub fn read_netlog_record<R: BufRead>(reader: &mut R) -> Option<Vec<u8>> {
 
 /// Parses the provided `event` based on the event type provided in `event_hdr`.
 pub fn parse_event(event_hdr: &EventHeader, event: &[u8]) -> Option<Event> {
-    if event_hdr.ty_string.starts_with("HTTP_") {
+    if event_hdr.ty_string.starts_with("HTTP_") ||
+        event_hdr.ty_string == "CORS_REQUEST"
+    {
         return http::parse_event(event_hdr, event);
     } else if event_hdr.ty_string.starts_with("HTTP2_") {
         return h2::parse_event(event_hdr, event);
@@ -245,13 +273,44 @@ pub fn parse_event(event_hdr: &EventHeader, event: &[u8]) -> Option<Event> {
         return h3::parse_event(event_hdr, event);
     } else if event_hdr.ty_string.starts_with("QUIC") {
         return quic::parse_event(event_hdr, event);
+    } else if event_hdr.ty_string.starts_with("TCP_") ||
+        event_hdr.ty_string.starts_with("UDP_") ||
+        event_hdr.ty_string.starts_with("SSL_") ||
+        event_hdr.ty_string.starts_with("SOCKET_")
+    {
+        return socket::parse_event(event_hdr, event);
+    } else if event_hdr.ty_string.starts_with("HOST_RESOLVER_") ||
+        event_hdr.ty_string.starts_with("PROXY_RESOLUTION_")
+    {
+        return dns::parse_event(event_hdr, event);
     }
 
     None
 }
 
+/// Deserializes an `event` of the type in `event_hdr`. Records whose params
+/// lack required fields are logged and skipped rather than failing the whole
+/// netlog, since the format changes between Chrome versions.
+pub(crate) fn deserialize_event<'a, T: Deserialize<'a>>(
+    event_hdr: &EventHeader, event: &'a [u8],
+) -> Option<T> {
+    match serde_json::from_slice(event) {
+        Ok(ev) => Some(ev),
+
+        Err(e) => {
+            log::error!("failed to parse {} event: {}", event_hdr.ty_string, e);
+            None
+        },
+    }
+}
+
 pub mod constants;
+#[cfg(feature = "qlog")]
+pub mod convert;
+pub mod dns;
 pub mod h2;
 pub mod h3;
 pub mod http;
 pub mod quic;
+pub mod reader;
+pub mod socket;
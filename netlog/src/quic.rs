@@ -27,6 +27,7 @@
 use serde::Deserialize;
 use serde::Serialize;
 
+use super::deserialize_event;
 use super::EventHeader;
 
 #[derive(Debug)]
@@ -42,12 +43,39 @@ pub enum Event {
     QuicSessionPacketSent(QuicSessionPacketSent),
     QuicSessionAckFrameSent(QuicSessionAckFrameSent),
     QuicSessionAckFrameReceived(QuicSessionAckFrameReceived),
+    QuicSessionStreamFrameSent(QuicSessionStreamFrameSentEvent),
     QuicSessionStreamFrameReceived(QuicSessionStreamFrameReceivedEvent),
     QuicSessionStopSendingFrameSent(QuicSessionStopSendingFrameSentEvent),
     QuicSessionRstStreamFrameSent(QuicSessionRstStreamFrameSentEvent),
     QuicSessionRstStreamFrameReceived(QuicSessionRstStreamFrameReceivedEvent),
     QuicSessionBlockedFrameReceived(QuicSessionBlockedFrameReceivedEvent),
     QuicSessionWindowUpdateFrameSent(QuicSessionWindowUpdateFrameSentEvent),
+    QuicSessionCryptoFrameSent(QuicSessionCryptoFrameSentEvent),
+    QuicSessionCryptoFrameReceived(QuicSessionCryptoFrameReceivedEvent),
+    QuicSessionHandshakeDoneFrameReceived(
+        QuicSessionHandshakeDoneFrameReceivedEvent,
+    ),
+    QuicSessionPathChallengeFrameSent(QuicSessionPathChallengeFrameSentEvent),
+    QuicSessionPathChallengeFrameReceived(
+        QuicSessionPathChallengeFrameReceivedEvent,
+    ),
+    QuicSessionPathResponseFrameSent(QuicSessionPathResponseFrameSentEvent),
+    QuicSessionPathResponseFrameReceived(
+        QuicSessionPathResponseFrameReceivedEvent,
+    ),
+    QuicSessionNewConnectionIdFrameReceived(
+        QuicSessionNewConnectionIdFrameReceivedEvent,
+    ),
+    QuicSessionPacketLost(QuicSessionPacketLostEvent),
+    QuicSessionPacketRetransmitted(QuicSessionPacketRetransmittedEvent),
+    QuicSessionVersionNegotiated(QuicSessionVersionNegotiatedEvent),
+    QuicSessionVersionNegotiationPacketReceived(
+        QuicSessionVersionNegotiationPacketReceivedEvent,
+    ),
+    QuicSessionCertificateVerified(QuicSessionCertificateVerifiedEvent),
+    QuicConnectionMigrationTriggered(QuicConnectionMigrationTriggeredEvent),
+    QuicConnectionMigrationSuccess(QuicConnectionMigrationSuccessEvent),
+    QuicConnectionMigrationFailure(QuicConnectionMigrationFailureEvent),
     QuicSessionClosed(QuicSessionClosedEvent),
 }
 
@@ -248,6 +276,19 @@ pub struct QuicSessionAckFrameReceived {
     pub params: QuicSessionAckFrameReceivedParams,
 }
 
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QuicSessionStreamFrameSentParams {
+    pub stream_id: u64,
+    pub fin: bool,
+    pub offset: u64,
+    pub length: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QuicSessionStreamFrameSentEvent {
+    pub params: QuicSessionStreamFrameSentParams,
+}
+
 #[derive(Serialize, Deserialize, Debug, Default)]
 pub struct QuicSessionStreamFrameReceivedParams {
     pub stream_id: u64,
@@ -317,6 +358,161 @@ pub struct QuicSessionWindowUpdateFrameSentEvent {
     pub params: QuicSessionWindowUpdateFrameSentParams,
 }
 
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QuicSessionCryptoFrameSentParams {
+    pub encryption_level: String,
+    pub data_length: u64,
+    pub offset: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QuicSessionCryptoFrameSentEvent {
+    pub params: QuicSessionCryptoFrameSentParams,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QuicSessionCryptoFrameReceivedParams {
+    pub encryption_level: String,
+    pub data_length: u64,
+    pub offset: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QuicSessionCryptoFrameReceivedEvent {
+    pub params: QuicSessionCryptoFrameReceivedParams,
+}
+
+/// HANDSHAKE_DONE frames have no fields, so the event has no params.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QuicSessionHandshakeDoneFrameReceivedEvent {}
+
+/// PATH_CHALLENGE and PATH_RESPONSE frame data, base64 encoded.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QuicSessionPathFrameParams {
+    pub data: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QuicSessionPathChallengeFrameSentEvent {
+    pub params: QuicSessionPathFrameParams,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QuicSessionPathChallengeFrameReceivedEvent {
+    pub params: QuicSessionPathFrameParams,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QuicSessionPathResponseFrameSentEvent {
+    pub params: QuicSessionPathFrameParams,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QuicSessionPathResponseFrameReceivedEvent {
+    pub params: QuicSessionPathFrameParams,
+}
+
+/// A connection ID issued by the peer, hex encoded.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QuicSessionNewConnectionIdFrameParams {
+    pub connection_id: String,
+    pub sequence_number: u64,
+    pub retire_prior_to: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QuicSessionNewConnectionIdFrameReceivedEvent {
+    pub params: QuicSessionNewConnectionIdFrameParams,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QuicSessionPacketLostParams {
+    pub transmission_type: String,
+    pub packet_number: u64,
+    pub detection_time_us: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QuicSessionPacketLostEvent {
+    pub params: QuicSessionPacketLostParams,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QuicSessionPacketRetransmittedParams {
+    pub old_packet_number: u64,
+    pub new_packet_number: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QuicSessionPacketRetransmittedEvent {
+    pub params: QuicSessionPacketRetransmittedParams,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QuicSessionVersionNegotiatedParams {
+    pub version: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QuicSessionVersionNegotiatedEvent {
+    pub params: QuicSessionVersionNegotiatedParams,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QuicSessionVersionNegotiationPacketReceivedParams {
+    pub versions: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QuicSessionVersionNegotiationPacketReceivedEvent {
+    pub params: QuicSessionVersionNegotiationPacketReceivedParams,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QuicSessionCertificateVerifiedParams {
+    pub subjects: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QuicSessionCertificateVerifiedEvent {
+    pub params: QuicSessionCertificateVerifiedParams,
+}
+
+// Connection migration events are logged against their own
+// QUIC_CONNECTION_MIGRATION source rather than the session. The connection ID
+// of the result links them to the session, it's either the one the session
+// started with or one the peer issued later.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QuicConnectionMigrationTriggeredParams {
+    pub trigger: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QuicConnectionMigrationTriggeredEvent {
+    pub params: QuicConnectionMigrationTriggeredParams,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QuicConnectionMigrationSuccessParams {
+    pub connection_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QuicConnectionMigrationSuccessEvent {
+    pub params: QuicConnectionMigrationSuccessParams,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QuicConnectionMigrationFailureParams {
+    pub connection_id: String,
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QuicConnectionMigrationFailureEvent {
+    pub params: QuicConnectionMigrationFailureParams,
+}
+
 #[derive(Serialize, Deserialize, Debug, Default)]
 pub struct QuicSessionClosedParams {
     pub details: String,
@@ -356,6 +552,14 @@ pub fn parse_event(
             ));
         },
 
+        "QUIC_SESSION_STREAM_FRAME_SENT" => {
+            let ev: QuicSessionStreamFrameSentEvent =
+                deserialize_event(event_hdr, event)?;
+            return Some(super::Event::Quic(Event::QuicSessionStreamFrameSent(
+                ev,
+            )));
+        },
+
         "QUIC_SESSION_STREAM_FRAME_RECEIVED" => {
             let ev: QuicSessionStreamFrameReceivedEvent =
                 serde_json::from_slice(event).unwrap();
@@ -409,7 +613,11 @@ pub fn parse_event(
         },
 
         "QUIC_SESSION_PACKET_RETRANSMITTED" => {
-            // TODO
+            let ev: QuicSessionPacketRetransmittedEvent =
+                deserialize_event(event_hdr, event)?;
+            return Some(super::Event::Quic(
+                Event::QuicSessionPacketRetransmitted(ev),
+            ));
         },
 
         "QUIC_SESSION_ACK_FRAME_SENT" => {
@@ -429,7 +637,9 @@ pub fn parse_event(
         },
 
         "QUIC_SESSION_PACKET_LOST" => {
-            // TODO
+            let ev: QuicSessionPacketLostEvent =
+                deserialize_event(event_hdr, event)?;
+            return Some(super::Event::Quic(Event::QuicSessionPacketLost(ev)));
         },
 
         "QUIC_SESSION_CLOSED" => {
@@ -459,13 +669,126 @@ pub fn parse_event(
             ));
         },
 
+        "QUIC_SESSION_CRYPTO_FRAME_SENT" => {
+            let ev: QuicSessionCryptoFrameSentEvent =
+                deserialize_event(event_hdr, event)?;
+            return Some(super::Event::Quic(Event::QuicSessionCryptoFrameSent(
+                ev,
+            )));
+        },
+
+        "QUIC_SESSION_CRYPTO_FRAME_RECEIVED" => {
+            let ev: QuicSessionCryptoFrameReceivedEvent =
+                deserialize_event(event_hdr, event)?;
+            return Some(super::Event::Quic(
+                Event::QuicSessionCryptoFrameReceived(ev),
+            ));
+        },
+
+        "QUIC_SESSION_HANDSHAKE_DONE_FRAME_RECEIVED" => {
+            let ev: QuicSessionHandshakeDoneFrameReceivedEvent =
+                deserialize_event(event_hdr, event)?;
+            return Some(super::Event::Quic(
+                Event::QuicSessionHandshakeDoneFrameReceived(ev),
+            ));
+        },
+
+        "QUIC_SESSION_PATH_CHALLENGE_FRAME_SENT" => {
+            let ev: QuicSessionPathChallengeFrameSentEvent =
+                deserialize_event(event_hdr, event)?;
+            return Some(super::Event::Quic(
+                Event::QuicSessionPathChallengeFrameSent(ev),
+            ));
+        },
+
+        "QUIC_SESSION_PATH_CHALLENGE_FRAME_RECEIVED" => {
+            let ev: QuicSessionPathChallengeFrameReceivedEvent =
+                deserialize_event(event_hdr, event)?;
+            return Some(super::Event::Quic(
+                Event::QuicSessionPathChallengeFrameReceived(ev),
+            ));
+        },
+
+        "QUIC_SESSION_PATH_RESPONSE_FRAME_SENT" => {
+            let ev: QuicSessionPathResponseFrameSentEvent =
+                deserialize_event(event_hdr, event)?;
+            return Some(super::Event::Quic(
+                Event::QuicSessionPathResponseFrameSent(ev),
+            ));
+        },
+
+        "QUIC_SESSION_PATH_RESPONSE_FRAME_RECEIVED" => {
+            let ev: QuicSessionPathResponseFrameReceivedEvent =
+                deserialize_event(event_hdr, event)?;
+            return Some(super::Event::Quic(
+                Event::QuicSessionPathResponseFrameReceived(ev),
+            ));
+        },
+
+        "QUIC_SESSION_NEW_CONNECTION_ID_FRAME_RECEIVED" => {
+            let ev: QuicSessionNewConnectionIdFrameReceivedEvent =
+                deserialize_event(event_hdr, event)?;
+            return Some(super::Event::Quic(
+                Event::QuicSessionNewConnectionIdFrameReceived(ev),
+            ));
+        },
+
+        "QUIC_SESSION_VERSION_NEGOTIATED" => {
+            let ev: QuicSessionVersionNegotiatedEvent =
+                deserialize_event(event_hdr, event)?;
+            return Some(super::Event::Quic(
+                Event::QuicSessionVersionNegotiated(ev),
+            ));
+        },
+
+        "QUIC_SESSION_VERSION_NEGOTIATION_PACKET_RECEIVED" => {
+            let ev: QuicSessionVersionNegotiationPacketReceivedEvent =
+                deserialize_event(event_hdr, event)?;
+            return Some(super::Event::Quic(
+                Event::QuicSessionVersionNegotiationPacketReceived(ev),
+            ));
+        },
+
+        "QUIC_SESSION_CERTIFICATE_VERIFIED" => {
+            let ev: QuicSessionCertificateVerifiedEvent =
+                deserialize_event(event_hdr, event)?;
+            return Some(super::Event::Quic(
+                Event::QuicSessionCertificateVerified(ev),
+            ));
+        },
+
+        // The end of the migration has no params.
+        "QUIC_CONNECTION_MIGRATION_TRIGGERED" =>
+            if event_hdr.phase_string == "PHASE_BEGIN" {
+                let ev: QuicConnectionMigrationTriggeredEvent =
+                    deserialize_event(event_hdr, event)?;
+                return Some(super::Event::Quic(
+                    Event::QuicConnectionMigrationTriggered(ev),
+                ));
+            },
+
+        "QUIC_CONNECTION_MIGRATION_SUCCESS" => {
+            let ev: QuicConnectionMigrationSuccessEvent =
+                deserialize_event(event_hdr, event)?;
+            return Some(super::Event::Quic(
+                Event::QuicConnectionMigrationSuccess(ev),
+            ));
+        },
+
+        "QUIC_CONNECTION_MIGRATION_FAILURE" => {
+            let ev: QuicConnectionMigrationFailureEvent =
+                deserialize_event(event_hdr, event)?;
+            return Some(super::Event::Quic(
+                Event::QuicConnectionMigrationFailure(ev),
+            ));
+        },
+
         // Other events observed in netlogs but not currently supported.
         "QUIC_ACCEPT_CH_FRAME_RECEIVED" |
         "QUIC_CHROMIUM_CLIENT_STREAM_READ_EARLY_HINTS_RESPONSE_HEADERS" |
         "QUIC_CHROMIUM_CLIENT_STREAM_READ_RESPONSE_HEADERS" |
         "QUIC_CHROMIUM_CLIENT_STREAM_READ_RESPONSE_TRAILERS" |
         "QUIC_CHROMIUM_CLIENT_STREAM_SEND_REQUEST_HEADERS" |
-        "QUIC_CONNECTION_MIGRATION_FAILURE" |
         "QUIC_CONNECTION_MIGRATION_FAILURE_AFTER_PROBING" |
         "QUIC_CONNECTION_MIGRATION_ON_MIGRATE_BACK" |
         "QUIC_CONNECTION_MIGRATION_ON_NETWORK_CONNECTED" |
@@ -474,9 +797,7 @@ pub fn parse_event(
         "QUIC_CONNECTION_MIGRATION_ON_PATH_DEGRADING" |
         "QUIC_CONNECTION_MIGRATION_ON_WRITE_ERROR" |
         "QUIC_CONNECTION_MIGRATION_PLATFORM_NOTIFICATION" |
-        "QUIC_CONNECTION_MIGRATION_SUCCESS" |
         "QUIC_CONNECTION_MIGRATION_SUCCESS_AFTER_PROBING" |
-        "QUIC_CONNECTION_MIGRATION_TRIGGERED" |
         "QUIC_CONNECTIVITY_PROBING_MANAGER_CANCEL_PROBING" |
         "QUIC_CONNECTIVITY_PROBING_MANAGER_PROBE_RECEIVED" |
         "QUIC_CONNECTIVITY_PROBING_MANAGER_PROBE_SENT" |
@@ -506,14 +827,9 @@ pub fn parse_event(
         "QUIC_SESSION_MESSAGE_FRAME_RECEIVED" |
         "QUIC_SESSION_MESSAGE_FRAME_SENT" |
         "QUIC_SESSION_MTU_DISCOVERY_FRAME_SENT" |
-        "QUIC_SESSION_NEW_CONNECTION_ID_FRAME_RECEIVED" |
         "QUIC_SESSION_NEW_CONNECTION_ID_FRAME_SENT" |
         "QUIC_SESSION_NEW_TOKEN_FRAME_RECEIVED" |
         "QUIC_SESSION_NEW_TOKEN_FRAME_SENT" |
-        "QUIC_SESSION_PATH_CHALLENGE_FRAME_RECEIVED" |
-        "QUIC_SESSION_PATH_CHALLENGE_FRAME_SENT" |
-        "QUIC_SESSION_PATH_RESPONSE_FRAME_RECEIVED" |
-        "QUIC_SESSION_PATH_RESPONSE_FRAME_SENT" |
         "QUIC_SESSION_PING_FRAME_RECEIVED" |
         "QUIC_SESSION_PING_FRAME_SENT" |
         "QUIC_SESSION_PUBLIC_RESET_PACKET_RECEIVED" |
@@ -527,22 +843,15 @@ pub fn parse_event(
         "QUIC_SESSION_WEBTRANSPORT_CLIENT_STATE_CHANGED" |
         "QUIC_SESSION_WINDOW_UPDATE_FRAME_RECEIVED" |
         "QUIC_SESSION_ZERO_RTT_REJECTED" |
-        "QUIC_SESSION_CERTIFICATE_VERIFIED" |
         "QUIC_SESSION_CERTIFICATE_VERIFY_FAILED" |
         "QUIC_SESSION_COALESCED_PACKET_SENT" |
-        "QUIC_SESSION_CRYPTO_FRAME_RECEIVED" |
-        "QUIC_SESSION_CRYPTO_FRAME_SENT" |
         "QUIC_SESSION_CRYPTO_HANDSHAKE_MESSAGE_RECEIVED" |
         "QUIC_SESSION_CRYPTO_HANDSHAKE_MESSAGE_SENT" |
-        "QUIC_SESSION_HANDSHAKE_DONE_FRAME_RECEIVED" |
         "QUIC_SESSION_PACKET_HEADER_REVIVED" |
         "QUIC_SESSION_PADDING_FRAME_RECEIVED" |
         "QUIC_SESSION_PADDING_FRAME_SENT" |
         "QUIC_SESSION_STREAMS_BLOCKED_FRAME_SENT" |
-        "QUIC_SESSION_STREAM_FRAME_COALESCED" |
-        "QUIC_SESSION_STREAM_FRAME_SENT" |
-        "QUIC_SESSION_VERSION_NEGOTIATED" |
-        "QUIC_SESSION_VERSION_NEGOTIATION_PACKET_RECEIVED" => (),
+        "QUIC_SESSION_STREAM_FRAME_COALESCED" => (),
 
         // Most likely uninteresting events
         "QUIC_SESSION_PACKET_RECEIVED" |
@@ -566,3 +875,282 @@ pub fn parse_event(
 
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parse(ty: &str, phase: &str, record: serde_json::Value) -> Option<Event> {
+        let event_hdr = EventHeader {
+            ty_string: ty.to_string(),
+            phase_string: phase.to_string(),
+            ..Default::default()
+        };
+
+        match parse_event(&event_hdr, record.to_string().as_bytes()) {
+            Some(crate::Event::Quic(ev)) => Some(ev),
+            Some(ev) => panic!("unexpected event {ev:?}"),
+            None => None,
+        }
+    }
+
+    /// Parses a record with a `PHASE_NONE` phase, in the format Chrome logs
+    /// it.
+    fn parse_none(ty: &str, params: serde_json::Value) -> Event {
+        let record = json!({
+            "params": params,
+            "phase": 0,
+            "source": { "id": 12, "start_time": "2316187", "type": 10 },
+            "time": "2316250",
+            "type": 255,
+        });
+
+        parse(ty, "PHASE_NONE", record).unwrap()
+    }
+
+    #[test]
+    fn packet_lost() {
+        let ev = parse_none(
+            "QUIC_SESSION_PACKET_LOST",
+            json!({
+                "detection_time_us": 2316250123_u64,
+                "packet_number": 17,
+                "transmission_type": "NOT_RETRANSMISSION",
+            }),
+        );
+
+        let Event::QuicSessionPacketLost(ev) = ev else {
+            panic!("unexpected event {ev:?}");
+        };
+        assert_eq!(ev.params.transmission_type, "NOT_RETRANSMISSION");
+        assert_eq!(ev.params.packet_number, 17);
+        assert_eq!(ev.params.detection_time_us, 2316250123);
+    }
+
+    #[test]
+    fn packet_retransmitted() {
+        let ev = parse_none(
+            "QUIC_SESSION_PACKET_RETRANSMITTED",
+            json!({ "new_packet_number": 21, "old_packet_number": 17 }),
+        );
+
+        let Event::QuicSessionPacketRetransmitted(ev) = ev else {
+            panic!("unexpected event {ev:?}");
+        };
+        assert_eq!(ev.params.old_packet_number, 17);
+        assert_eq!(ev.params.new_packet_number, 21);
+    }
+
+    #[test]
+    fn crypto_frames() {
+        let params = json!({
+            "data_length": 1162,
+            "encryption_level": "ENCRYPTION_HANDSHAKE",
+            "offset": 0,
+        });
+
+        let ev = parse_none("QUIC_SESSION_CRYPTO_FRAME_SENT", params.clone());
+        let Event::QuicSessionCryptoFrameSent(ev) = ev else {
+            panic!("unexpected event {ev:?}");
+        };
+        assert_eq!(ev.params.encryption_level, "ENCRYPTION_HANDSHAKE");
+        assert_eq!(ev.params.data_length, 1162);
+        assert_eq!(ev.params.offset, 0);
+
+        let ev = parse_none("QUIC_SESSION_CRYPTO_FRAME_RECEIVED", params);
+        let Event::QuicSessionCryptoFrameReceived(ev) = ev else {
+            panic!("unexpected event {ev:?}");
+        };
+        assert_eq!(ev.params.encryption_level, "ENCRYPTION_HANDSHAKE");
+        assert_eq!(ev.params.data_length, 1162);
+        assert_eq!(ev.params.offset, 0);
+    }
+
+    #[test]
+    fn path_frames() {
+        let params = json!({ "data": "AQIDBAUGBwg=" });
+
+        let ev =
+            parse_none("QUIC_SESSION_PATH_CHALLENGE_FRAME_SENT", params.clone());
+        let Event::QuicSessionPathChallengeFrameSent(ev) = ev else {
+            panic!("unexpected event {ev:?}");
+        };
+        assert_eq!(ev.params.data, "AQIDBAUGBwg=");
+
+        let ev = parse_none(
+            "QUIC_SESSION_PATH_CHALLENGE_FRAME_RECEIVED",
+            params.clone(),
+        );
+        assert!(matches!(
+            ev,
+            Event::QuicSessionPathChallengeFrameReceived(_)
+        ));
+
+        let ev =
+            parse_none("QUIC_SESSION_PATH_RESPONSE_FRAME_SENT", params.clone());
+        assert!(matches!(ev, Event::QuicSessionPathResponseFrameSent(_)));
+
+        let ev = parse_none("QUIC_SESSION_PATH_RESPONSE_FRAME_RECEIVED", params);
+        let Event::QuicSessionPathResponseFrameReceived(ev) = ev else {
+            panic!("unexpected event {ev:?}");
+        };
+        assert_eq!(ev.params.data, "AQIDBAUGBwg=");
+    }
+
+    #[test]
+    fn new_connection_id_frame() {
+        let ev = parse_none(
+            "QUIC_SESSION_NEW_CONNECTION_ID_FRAME_RECEIVED",
+            json!({
+                "connection_id": "c3a1b2d4e5f60718",
+                "retire_prior_to": 0,
+                "sequence_number": 1,
+            }),
+        );
+
+        let Event::QuicSessionNewConnectionIdFrameReceived(ev) = ev else {
+            panic!("unexpected event {ev:?}");
+        };
+        assert_eq!(ev.params.connection_id, "c3a1b2d4e5f60718");
+        assert_eq!(ev.params.sequence_number, 1);
+        assert_eq!(ev.params.retire_prior_to, 0);
+    }
+
+    #[test]
+    fn version_negotiation() {
+        let ev = parse_none(
+            "QUIC_SESSION_VERSION_NEGOTIATION_PACKET_RECEIVED",
+            json!({ "versions": ["RFCv1", "Draft29"] }),
+        );
+        let Event::QuicSessionVersionNegotiationPacketReceived(ev) = ev else {
+            panic!("unexpected event {ev:?}");
+        };
+        assert_eq!(ev.params.versions, ["RFCv1", "Draft29"]);
+
+        let ev = parse_none(
+            "QUIC_SESSION_VERSION_NEGOTIATED",
+            json!({ "version": "RFCv1" }),
+        );
+        let Event::QuicSessionVersionNegotiated(ev) = ev else {
+            panic!("unexpected event {ev:?}");
+        };
+        assert_eq!(ev.params.version, "RFCv1");
+    }
+
+    #[test]
+    fn malformed_params_are_skipped() {
+        // Records whose params don't match the expected types are skipped.
+        let record = json!({
+            "params": { "packet_number": "17" },
+            "phase": 0,
+            "type": 255,
+        });
+        assert!(parse("QUIC_SESSION_PACKET_LOST", "PHASE_NONE", record).is_none());
+
+        let record = json!({ "params": {}, "phase": 0, "type": 255 });
+        assert!(parse(
+            "QUIC_SESSION_VERSION_NEGOTIATED",
+            "PHASE_NONE",
+            record.clone()
+        )
+        .is_none());
+        assert!(parse(
+            "QUIC_CONNECTION_MIGRATION_TRIGGERED",
+            "PHASE_BEGIN",
+            record
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn stream_frame_sent() {
+        let ev = parse_none(
+            "QUIC_SESSION_STREAM_FRAME_SENT",
+            json!({ "stream_id": 4, "fin": true, "offset": 10, "length": 5 }),
+        );
+
+        let Event::QuicSessionStreamFrameSent(ev) = ev else {
+            panic!("unexpected event {ev:?}");
+        };
+        assert_eq!(ev.params.stream_id, 4);
+        assert!(ev.params.fin);
+        assert_eq!(ev.params.offset, 10);
+        assert_eq!(ev.params.length, 5);
+
+        let record = json!({
+            "params": { "stream_id": "4", "fin": true },
+            "phase": 0,
+            "type": 255,
+        });
+        assert!(
+            parse("QUIC_SESSION_STREAM_FRAME_SENT", "PHASE_NONE", record)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn certificate_verified() {
+        let ev = parse_none(
+            "QUIC_SESSION_CERTIFICATE_VERIFIED",
+            json!({ "subjects": ["www.example.com", "example.com"] }),
+        );
+
+        let Event::QuicSessionCertificateVerified(ev) = ev else {
+            panic!("unexpected event {ev:?}");
+        };
+        assert_eq!(ev.params.subjects, ["www.example.com", "example.com"]);
+    }
+
+    #[test]
+    fn connection_migration() {
+        let begin = json!({
+            "params": { "trigger": "OnNetworkDisconnected" },
+            "phase": 1,
+            "source": { "id": 31, "start_time": "2317001", "type": 28 },
+            "time": "2317001",
+            "type": 270,
+        });
+        let ev =
+            parse("QUIC_CONNECTION_MIGRATION_TRIGGERED", "PHASE_BEGIN", begin)
+                .unwrap();
+        let Event::QuicConnectionMigrationTriggered(ev) = ev else {
+            panic!("unexpected event {ev:?}");
+        };
+        assert_eq!(ev.params.trigger, "OnNetworkDisconnected");
+
+        // The end of the migration carries no params.
+        let end = json!({
+            "phase": 2,
+            "source": { "id": 31, "start_time": "2317001", "type": 28 },
+            "time": "2317090",
+            "type": 270,
+        });
+        assert!(
+            parse("QUIC_CONNECTION_MIGRATION_TRIGGERED", "PHASE_END", end)
+                .is_none()
+        );
+
+        let ev = parse_none(
+            "QUIC_CONNECTION_MIGRATION_SUCCESS",
+            json!({ "connection_id": "c3a1b2d4e5f60718" }),
+        );
+        let Event::QuicConnectionMigrationSuccess(ev) = ev else {
+            panic!("unexpected event {ev:?}");
+        };
+        assert_eq!(ev.params.connection_id, "c3a1b2d4e5f60718");
+
+        let ev = parse_none(
+            "QUIC_CONNECTION_MIGRATION_FAILURE",
+            json!({
+                "connection_id": "c3a1b2d4e5f60718",
+                "reason": "No alternate network found",
+            }),
+        );
+        let Event::QuicConnectionMigrationFailure(ev) = ev else {
+            panic!("unexpected event {ev:?}");
+        };
+        assert_eq!(ev.params.connection_id, "c3a1b2d4e5f60718");
+        assert_eq!(ev.params.reason, "No alternate network found");
+    }
+}
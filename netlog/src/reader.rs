@@ -0,0 +1,584 @@
+// Copyright (C) 2025, Cloudflare, Inc.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are
+// met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//
+//     * Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in the
+//       documentation and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS
+// IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO,
+// THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR
+// PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Streaming netlog parsing.
+//!
+//! A [`NetlogReader`] reads a netlog file one record at a time and yields the
+//! events that this crate parses, so that files don't need to fit in memory.
+//! Sessions can be filtered by hostname or ID while reading, and events of
+//! filtered out sessions are dropped without being parsed.
+//!
+//! Netlogs written by a browser that didn't shut down cleanly often end
+//! abruptly, without the closing `]}` or partway through a record. The reader
+//! stops at the last complete record in that case.
+//!
+//! ```no_run
+//! use netlog::reader::NetlogFilter;
+//! use netlog::reader::NetlogReader;
+//! use std::fs::File;
+//! use std::io::BufReader;
+//!
+//! let file = File::open("chrome-net-export-log-error.json").unwrap();
+//! let filter = NetlogFilter {
+//!     hostnames: ["example.com:443".to_string()].into(),
+//!     ..Default::default()
+//! };
+//!
+//! let mut reader = NetlogReader::new(BufReader::new(file), filter).unwrap();
+//!
+//! for (event_hdr, event) in reader.by_ref() {
+//!     // Handle parsed event.
+//! }
+//!
+//! for session in reader.sessions().values() {
+//!     if !reader.is_skipped(session.id) {
+//!         println!("{} {}", session.id, session.host);
+//!     }
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::io::BufRead;
+
+use crate::constants::Constants;
+use crate::h2::Http2SessionEvent;
+use crate::quic::QuicSessionEvent;
+use crate::read_netlog_constants;
+use crate::Event;
+use crate::EventHeader;
+
+/// Sessions to read events of. Empty sets don't filter anything.
+#[derive(Clone, Debug, Default)]
+pub struct NetlogFilter {
+    /// Session hostnames. QUIC session hostnames include the port, e.g.
+    /// `example.com:443`, HTTP/2 ones don't.
+    pub hostnames: HashSet<String>,
+
+    /// Session IDs, i.e. the source ID of the session events.
+    pub session_ids: HashSet<i64>,
+}
+
+impl NetlogFilter {
+    fn accepts(&self, id: i64, host: &str) -> bool {
+        (self.hostnames.is_empty() || self.hostnames.contains(host)) &&
+            (self.session_ids.is_empty() || self.session_ids.contains(&id))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SessionProtocol {
+    Quic,
+    Http2,
+}
+
+/// A QUIC or HTTP/2 session found while reading.
+#[derive(Clone, Debug)]
+pub struct Session {
+    pub id: i64,
+    pub protocol: SessionProtocol,
+    pub host: String,
+    pub start_time: u64,
+}
+
+/// Iterator over the parsed events of a netlog file.
+///
+/// Events of sessions that don't match the filter are skipped. Events of
+/// other sources, such as the requests and stream jobs that use sessions, are
+/// always yielded, since linking them to sessions needs the whole file.
+pub struct NetlogReader<R> {
+    reader: R,
+    constants: Constants,
+    filter: NetlogFilter,
+
+    sessions: BTreeMap<i64, Session>,
+    skipped_sessions: HashSet<i64>,
+
+    buf: Vec<u8>,
+    done: bool,
+    truncated: bool,
+}
+
+impl<R: BufRead> NetlogReader<R> {
+    /// Reads the netlog constants from `reader`, and prepares to read events.
+    pub fn new(
+        mut reader: R, filter: NetlogFilter,
+    ) -> Result<Self, serde_json::Error> {
+        let constants = read_netlog_constants(&mut reader)?;
+
+        let mut netlog = Self {
+            reader,
+            constants,
+            filter,
+            sessions: BTreeMap::new(),
+            skipped_sessions: HashSet::new(),
+            buf: Vec::new(),
+            done: false,
+            truncated: false,
+        };
+
+        // The second line of a netlog is always `"events": [`.
+        netlog.read_line();
+
+        Ok(netlog)
+    }
+
+    pub fn constants(&self) -> &Constants {
+        &self.constants
+    }
+
+    /// Sessions read so far, including those that don't match the filter,
+    /// keyed by ID.
+    pub fn sessions(&self) -> &BTreeMap<i64, Session> {
+        &self.sessions
+    }
+
+    /// Whether events of the session `id` are skipped by the filter.
+    pub fn is_skipped(&self, id: i64) -> bool {
+        self.skipped_sessions.contains(&id)
+    }
+
+    /// Whether the file ended before the end of the events array.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Reads the next line into `buf`. Returns `None` at the end of the file,
+    /// and whether the line is complete otherwise.
+    fn read_line(&mut self) -> Option<bool> {
+        self.buf.clear();
+
+        match self.reader.read_until(b'\n', &mut self.buf) {
+            Ok(0) => None,
+
+            Ok(_) => Some(self.buf.ends_with(b"\n")),
+
+            Err(e) => {
+                log::error!("Error reading netlog: {}", e);
+                None
+            },
+        }
+    }
+
+    /// Reads the next event record. Returns `None` once there are no more
+    /// events to read.
+    fn next_record(&mut self) -> Option<Vec<u8>> {
+        while !self.done {
+            let Some(complete) = self.read_line() else {
+                // Events should be followed by other netlog fields.
+                self.truncated = true;
+                self.done = true;
+                break;
+            };
+
+            let Some((record, last)) = event_record(&self.buf) else {
+                // Skip blank lines.
+                if self.buf.trim_ascii().is_empty() {
+                    continue;
+                }
+
+                // Any other line follows the events, e.g. `"polledData"`.
+                self.done = true;
+                break;
+            };
+
+            if !complete {
+                self.truncated = true;
+                self.done = true;
+            }
+
+            if last {
+                self.done = true;
+            }
+
+            return Some(record.to_vec());
+        }
+
+        None
+    }
+
+    /// Records sessions as they begin, and returns whether events of the
+    /// source of `event_hdr` should be yielded.
+    fn filter_event(&mut self, event_hdr: &EventHeader, record: &[u8]) -> bool {
+        let id = event_hdr.source.id;
+
+        if self.skipped_sessions.contains(&id) {
+            return false;
+        }
+
+        if event_hdr.phase_string != "PHASE_BEGIN" {
+            return true;
+        }
+
+        let session = match event_hdr.ty_string.as_str() {
+            "QUIC_SESSION" => serde_json::from_slice::<QuicSessionEvent>(record)
+                .ok()
+                .map(|ev| {
+                    // QUIC sessions split host and port, merge them so that
+                    // hostnames are unique.
+                    let host = format!("{}:{}", ev.params.host, ev.params.port);
+                    (SessionProtocol::Quic, host)
+                }),
+
+            "HTTP2_SESSION" =>
+                serde_json::from_slice::<Http2SessionEvent>(record)
+                    .ok()
+                    .map(|ev| (SessionProtocol::Http2, ev.params.host)),
+
+            _ => return true,
+        };
+
+        let Some((protocol, host)) = session else {
+            return true;
+        };
+
+        let accepted = self.filter.accepts(id, &host);
+
+        if !accepted {
+            self.skipped_sessions.insert(id);
+        }
+
+        self.sessions.insert(id, Session {
+            id,
+            protocol,
+            host,
+            start_time: event_hdr.time_num,
+        });
+
+        accepted
+    }
+}
+
+impl<R: BufRead> Iterator for NetlogReader<R> {
+    type Item = (EventHeader, Event);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(record) = self.next_record() {
+            let mut event_hdr: EventHeader = match serde_json::from_slice(&record)
+            {
+                Ok(v) => v,
+
+                Err(e) => {
+                    // The last record of a truncated file is usually partial.
+                    if !self.done {
+                        log::error!("Error deserializing: {}", e);
+                    }
+
+                    continue;
+                },
+            };
+
+            // Constants are specific to the browser version, skip events that
+            // they don't describe rather than guessing.
+            if !self
+                .constants
+                .log_event_types_id_keyed
+                .contains_key(&event_hdr.ty) ||
+                !self
+                    .constants
+                    .log_event_phase_id_keyed
+                    .contains_key(&event_hdr.phase)
+            {
+                continue;
+            }
+
+            event_hdr.populate_strings(&self.constants);
+            event_hdr.time_num = event_hdr.time.parse().unwrap_or_default();
+
+            if !self.filter_event(&event_hdr, &record) {
+                continue;
+            }
+
+            if let Some(ev) = crate::parse_event(&event_hdr, &record) {
+                return Some((event_hdr, ev));
+            }
+        }
+
+        None
+    }
+}
+
+/// Returns the event object of a line of the events array, without the
+/// trailing comma or closing bracket, and whether it closes the array.
+fn event_record(line: &[u8]) -> Option<(&[u8], bool)> {
+    let line = line.trim_ascii();
+
+    if !line.starts_with(b"{") {
+        return None;
+    }
+
+    let line = line.strip_suffix(b",").unwrap_or(line);
+
+    match line.strip_suffix(b"]") {
+        Some(line) => Some((line, true)),
+
+        None => Some((line, false)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    const QUIC_SESSION: i64 = 1;
+    const QUIC_SESSION_TRANSPORT_PARAMETERS_SENT: i64 = 2;
+    const HTTP2_SESSION: i64 = 3;
+
+    const PHASE_NONE: i64 = 0;
+    const PHASE_BEGIN: i64 = 1;
+
+    fn constants_line() -> String {
+        let types = format!(
+            r#"{{"QUIC_SESSION":{QUIC_SESSION},"QUIC_SESSION_TRANSPORT_PARAMETERS_SENT":{QUIC_SESSION_TRANSPORT_PARAMETERS_SENT},"HTTP2_SESSION":{HTTP2_SESSION}}}"#
+        );
+        let phases = format!(
+            r#"{{"PHASE_NONE":{PHASE_NONE},"PHASE_BEGIN":{PHASE_BEGIN},"PHASE_END":2}}"#
+        );
+
+        // The constants object is closed by the reader, in place of the
+        // trailing comma.
+        format!(
+            r#"{{"constants":{{"activeFieldTrialGroups":[],"addressFamily":{{}},"certPathBuilderDigestPolicy":{{}},"certStatusFlag":{{}},"certVerifierFlags":{{}},"clientInfo":{{}},"dnsQueryType":{{}},"loadFlag":{{}},"loadState":{{}},"logEventPhase":{phases},"logEventTypes":{types},"logFormatVersion":1,"logSourceType":{{}},"netError":{{}},"quicError":{{}},"quicRstStreamError":{{}},"secureDnsMode":{{}},"timeTickOffset":0}},"#
+        )
+    }
+
+    fn event(ty: i64, phase: i64, id: i64, params: &str) -> String {
+        format!(
+            r#"{{"params":{params},"phase":{phase},"source":{{"id":{id},"start_time":"1","type":1}},"time":"{id}","type":{ty}}}"#
+        )
+    }
+
+    fn quic_session(id: i64, host: &str) -> String {
+        let params = format!(
+            r#"{{"cert_verify_flags":0,"connection_id":"01","host":"{host}","port":443,"network_isolation_key":"","privacy_mode":"disabled"}}"#
+        );
+
+        event(QUIC_SESSION, PHASE_BEGIN, id, &params)
+    }
+
+    fn http2_session(id: i64, host: &str) -> String {
+        let params = format!(r#"{{"host":"{host}","proxy":"DIRECT"}}"#);
+
+        event(HTTP2_SESSION, PHASE_BEGIN, id, &params)
+    }
+
+    fn transport_params_sent(id: i64) -> String {
+        event(
+            QUIC_SESSION_TRANSPORT_PARAMETERS_SENT,
+            PHASE_NONE,
+            id,
+            r#"{"quic_transport_parameters":"[]"}"#,
+        )
+    }
+
+    /// Builds a netlog whose events array holds `events`, one per line.
+    fn netlog(events: &[String]) -> String {
+        format!(
+            "{}\n\"events\": [\n{}]\n}}\n",
+            constants_line(),
+            events.join(",\n")
+        )
+    }
+
+    fn open(
+        input: String, filter: NetlogFilter,
+    ) -> NetlogReader<Cursor<Vec<u8>>> {
+        NetlogReader::new(Cursor::new(input.into_bytes()), filter).unwrap()
+    }
+
+    /// Reads all events, returning their source ID and type.
+    fn read_all(
+        reader: &mut NetlogReader<Cursor<Vec<u8>>>,
+    ) -> Vec<(i64, String)> {
+        reader
+            .by_ref()
+            .map(|(hdr, _)| (hdr.source.id, hdr.ty_string))
+            .collect()
+    }
+
+    fn sample_events() -> Vec<String> {
+        vec![
+            quic_session(1, "a.example"),
+            transport_params_sent(1),
+            http2_session(2, "b.example"),
+            quic_session(3, "b.example"),
+            transport_params_sent(3),
+        ]
+    }
+
+    fn ids(events: &[(i64, String)]) -> Vec<i64> {
+        events.iter().map(|(id, _)| *id).collect()
+    }
+
+    #[test]
+    fn complete() {
+        let mut reader = open(netlog(&sample_events()), Default::default());
+
+        let events = read_all(&mut reader);
+
+        assert_eq!(ids(&events), vec![1, 1, 2, 3, 3]);
+        assert_eq!(events[1].1, "QUIC_SESSION_TRANSPORT_PARAMETERS_SENT");
+        assert!(!reader.is_truncated());
+
+        let sessions = reader.sessions();
+        assert_eq!(sessions.len(), 3);
+        assert_eq!(sessions[&1].host, "a.example:443");
+        assert_eq!(sessions[&1].protocol, SessionProtocol::Quic);
+        assert_eq!(sessions[&2].host, "b.example");
+        assert_eq!(sessions[&2].protocol, SessionProtocol::Http2);
+    }
+
+    #[test]
+    fn missing_end_of_events() {
+        // The file ends after a complete record, without `]}`.
+        let input = format!(
+            "{}\n\"events\": [\n{},\n",
+            constants_line(),
+            sample_events().join(",\n")
+        );
+        let mut reader = open(input, Default::default());
+
+        assert_eq!(ids(&read_all(&mut reader)), vec![1, 1, 2, 3, 3]);
+        assert!(reader.is_truncated());
+    }
+
+    #[test]
+    fn partial_last_line() {
+        let last = transport_params_sent(4);
+        let input = format!(
+            "{}\n\"events\": [\n{},\n{}",
+            constants_line(),
+            sample_events().join(",\n"),
+            &last[..last.len() / 2]
+        );
+        let mut reader = open(input, Default::default());
+
+        assert_eq!(ids(&read_all(&mut reader)), vec![1, 1, 2, 3, 3]);
+        assert!(reader.is_truncated());
+    }
+
+    #[test]
+    fn trailing_polled_data() {
+        // The events array closed on its own line, followed by other fields.
+        let input = format!(
+            "{}\n\"events\": [\n{}\n],\n\"polledData\": {{}}\n}}\n",
+            constants_line(),
+            sample_events().join(",\n")
+        );
+        let mut reader = open(input, Default::default());
+
+        assert_eq!(ids(&read_all(&mut reader)), vec![1, 1, 2, 3, 3]);
+        assert!(!reader.is_truncated());
+
+        // The array closed on the line of the last event.
+        let input = format!(
+            "{}\n\"events\": [\n{}],\n\"polledData\": {{\"{}\": 1}}\n}}\n",
+            constants_line(),
+            sample_events().join(",\n"),
+            transport_params_sent(5).replace('"', "'"),
+        );
+        let mut reader = open(input, Default::default());
+
+        assert_eq!(ids(&read_all(&mut reader)), vec![1, 1, 2, 3, 3]);
+        assert!(!reader.is_truncated());
+    }
+
+    #[test]
+    fn blank_lines() {
+        let events = sample_events();
+        let input = format!(
+            "{}\n\"events\": [\n\n{},\n  \n\r\n{}]\n}}\n",
+            constants_line(),
+            events[..2].join(",\n"),
+            events[2..].join(",\n")
+        );
+        let mut reader = open(input, Default::default());
+
+        assert_eq!(ids(&read_all(&mut reader)), vec![1, 1, 2, 3, 3]);
+        assert!(!reader.is_truncated());
+    }
+
+    #[test]
+    fn filter_by_hostname() {
+        let filter = NetlogFilter {
+            hostnames: ["b.example:443".to_string()].into(),
+            ..Default::default()
+        };
+        let mut reader = open(netlog(&sample_events()), filter);
+
+        assert_eq!(ids(&read_all(&mut reader)), vec![3, 3]);
+
+        // Skipped sessions are still listed.
+        assert_eq!(reader.sessions().len(), 3);
+        assert!(reader.is_skipped(1));
+        assert!(reader.is_skipped(2));
+        assert!(!reader.is_skipped(3));
+    }
+
+    #[test]
+    fn filter_by_session_id() {
+        let filter = NetlogFilter {
+            session_ids: [1, 2].into(),
+            ..Default::default()
+        };
+        let mut reader = open(netlog(&sample_events()), filter);
+
+        assert_eq!(ids(&read_all(&mut reader)), vec![1, 1, 2]);
+        assert!(reader.is_skipped(3));
+
+        // Both filters have to match.
+        let filter = NetlogFilter {
+            hostnames: ["b.example".to_string()].into(),
+            session_ids: [1, 2].into(),
+        };
+        let mut reader = open(netlog(&sample_events()), filter);
+
+        assert_eq!(ids(&read_all(&mut reader)), vec![2]);
+    }
+
+    #[test]
+    fn event_records() {
+        assert_eq!(
+            event_record(b"{\"a\":1},\n"),
+            Some((&b"{\"a\":1}"[..], false))
+        );
+        assert_eq!(
+            event_record(b"{\"a\":1}]\n"),
+            Some((&b"{\"a\":1}"[..], true))
+        );
+        assert_eq!(
+            event_record(b"{\"a\":1}],\n"),
+            Some((&b"{\"a\":1}"[..], true))
+        );
+        assert_eq!(
+            event_record(b"  {\"a\":1}"),
+            Some((&b"{\"a\":1}"[..], false))
+        );
+        assert_eq!(event_record(b"\"polledData\": {}\n"), None);
+        assert_eq!(event_record(b"\n"), None);
+    }
+}
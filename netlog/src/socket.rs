@@ -0,0 +1,200 @@
+// Copyright (C) 2025, Cloudflare, Inc.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are
+// met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//
+//     * Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in the
+//       documentation and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS
+// IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO,
+// THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR
+// PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Socket events, which describe the TCP, UDP and TLS connections that
+//! sessions are established over.
+
+use serde::Deserialize;
+
+use super::deserialize_event;
+use super::EventHeader;
+use super::NetErrorParams;
+use super::SourceDependency;
+
+#[derive(Debug)]
+pub enum Event {
+    TcpConnect(TcpConnectEvent),
+    TcpConnectEnd(TcpConnectEndEvent),
+    TcpConnectAttempt(TcpConnectAttemptEvent),
+    TcpConnectAttemptEnd(TcpConnectAttemptEndEvent),
+    UdpConnect(UdpConnectEvent),
+    SslConnectEnd(SslConnectEndEvent),
+    SocketPoolBoundToSocket(SocketPoolBoundToSocketEvent),
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct TcpConnectParams {
+    pub address_list: Vec<String>,
+}
+
+/// The start of a TCP connection, which tries each resolved address in turn.
+#[derive(Deserialize, Debug, Default)]
+pub struct TcpConnectEvent {
+    pub params: TcpConnectParams,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct TcpConnectEndParams {
+    pub source_address: Option<String>,
+    pub net_error: Option<i64>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct TcpConnectEndEvent {
+    #[serde(default)]
+    pub params: TcpConnectEndParams,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct TcpConnectAttemptParams {
+    pub address: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct TcpConnectAttemptEvent {
+    pub params: TcpConnectAttemptParams,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct TcpConnectAttemptEndEvent {
+    #[serde(default)]
+    pub params: NetErrorParams,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct UdpConnectParams {
+    pub address: String,
+}
+
+/// The UDP socket of a QUIC session connecting to the server address.
+#[derive(Deserialize, Debug, Default)]
+pub struct UdpConnectEvent {
+    pub params: UdpConnectParams,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct SslConnectEndParams {
+    pub version: Option<String>,
+    pub cipher_suite: Option<u16>,
+    pub is_resumed: Option<bool>,
+    /// The negotiated ALPN protocol, e.g. `h2` or `http/1.1`.
+    pub next_proto: Option<String>,
+    pub net_error: Option<i64>,
+}
+
+/// The end of a TLS handshake over TCP.
+#[derive(Deserialize, Debug, Default)]
+pub struct SslConnectEndEvent {
+    #[serde(default)]
+    pub params: SslConnectEndParams,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct SocketPoolBoundToSocketParams {
+    pub source_dependency: SourceDependency,
+}
+
+/// A stream job or request being handed a connected socket. The dependency is
+/// the socket source, which logs the TCP and TLS events.
+#[derive(Deserialize, Debug, Default)]
+pub struct SocketPoolBoundToSocketEvent {
+    pub params: SocketPoolBoundToSocketParams,
+}
+
+/// Parses the provided `event` based on the event type provided in `event_hdr`.
+pub fn parse_event(
+    event_hdr: &EventHeader, event: &[u8],
+) -> Option<super::Event> {
+    let begin = event_hdr.phase_string == "PHASE_BEGIN";
+    let end = event_hdr.phase_string == "PHASE_END";
+
+    match event_hdr.ty_string.as_str() {
+        "TCP_CONNECT" =>
+            if begin {
+                let ev: TcpConnectEvent = deserialize_event(event_hdr, event)?;
+                return Some(super::Event::Socket(Event::TcpConnect(ev)));
+            } else if end {
+                let ev: TcpConnectEndEvent = deserialize_event(event_hdr, event)?;
+                return Some(super::Event::Socket(Event::TcpConnectEnd(ev)));
+            },
+
+        "TCP_CONNECT_ATTEMPT" =>
+            if begin {
+                let ev: TcpConnectAttemptEvent =
+                    deserialize_event(event_hdr, event)?;
+                return Some(super::Event::Socket(Event::TcpConnectAttempt(ev)));
+            } else if end {
+                let ev: TcpConnectAttemptEndEvent =
+                    deserialize_event(event_hdr, event)?;
+                return Some(super::Event::Socket(Event::TcpConnectAttemptEnd(
+                    ev,
+                )));
+            },
+
+        "UDP_CONNECT" =>
+            if begin {
+                let ev: UdpConnectEvent = deserialize_event(event_hdr, event)?;
+                return Some(super::Event::Socket(Event::UdpConnect(ev)));
+            },
+
+        "SSL_CONNECT" =>
+            if end {
+                let ev: SslConnectEndEvent = deserialize_event(event_hdr, event)?;
+                return Some(super::Event::Socket(Event::SslConnectEnd(ev)));
+            },
+
+        "SOCKET_POOL_BOUND_TO_SOCKET" => {
+            let ev: SocketPoolBoundToSocketEvent =
+                deserialize_event(event_hdr, event)?;
+            return Some(super::Event::Socket(Event::SocketPoolBoundToSocket(
+                ev,
+            )));
+        },
+
+        // Other events observed in netlogs but not currently supported.
+        "SOCKET_ALIVE" |
+        "SOCKET_BYTES_RECEIVED" |
+        "SOCKET_BYTES_SENT" |
+        "SOCKET_IN_USE" |
+        "SOCKET_POOL_BOUND_TO_CONNECT_JOB" |
+        "SOCKET_POOL_REUSED_AN_EXISTING_SOCKET" |
+        "SSL_CERTIFICATES_RECEIVED" |
+        "SSL_HANDSHAKE_MESSAGE_RECEIVED" |
+        "SSL_HANDSHAKE_MESSAGE_SENT" |
+        "SSL_SOCKET_BYTES_RECEIVED" |
+        "SSL_SOCKET_BYTES_SENT" |
+        "UDP_BYTES_RECEIVED" |
+        "UDP_BYTES_SENT" |
+        "UDP_LOCAL_ADDRESS" => (),
+
+        // The netlog format is continually evolving, log any unknown types
+        // in case they are interesting.
+        _ =>
+            log::trace!("skipping unknown socket type....{}", event_hdr.ty_string),
+    }
+
+    None
+}
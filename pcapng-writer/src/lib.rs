@@ -0,0 +1,715 @@
+// Copyright (C) 2025, Cloudflare, Inc.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are
+// met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//
+//     * Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in the
+//       documentation and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS
+// IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO,
+// THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR
+// PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Wire-image capture of QUIC connections in the [pcapng] format.
+//!
+//! [`PcapWriter`] records UDP datagrams as Enhanced Packet Blocks, wrapping
+//! each payload in a synthesized IPv4 or IPv6 and UDP header so the capture
+//! can be opened as-is by Wireshark and friends.
+//!
+//! TLS secrets emitted by quiche's key log are embedded in the capture as
+//! Decryption Secrets Blocks, using [`KeylogWriter`] as the connection's
+//! keylog writer. This makes captures self-contained: they can be decrypted
+//! without shipping a separate `SSLKEYLOGFILE` around.
+//!
+//! Event loops sharing one capture between their connections can use
+//! [`make_pcap_writer()`], [`capture_datagram()`] and [`keylog_writer()`].
+//!
+//! ```no_run
+//! # fn main() -> std::io::Result<()> {
+//! use pcapng_writer::Direction;
+//! use pcapng_writer::PcapWriter;
+//!
+//! let mut pcap = PcapWriter::create("client.pcapng", "example")?;
+//!
+//! let from = "192.0.2.1:4433".parse().unwrap();
+//! let to = "192.0.2.2:443".parse().unwrap();
+//!
+//! pcap.write_datagram(Direction::Outbound, from, to, b"datagram payload")?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [pcapng]: https://www.ietf.org/archive/id/draft-ietf-opsawg-pcapng-02.html
+
+use std::io;
+use std::io::Write;
+use std::net::IpAddr;
+use std::net::Ipv6Addr;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+const SECTION_HEADER_BLOCK: u32 = 0x0a0d_0d0a;
+const INTERFACE_DESCRIPTION_BLOCK: u32 = 0x0000_0001;
+const ENHANCED_PACKET_BLOCK: u32 = 0x0000_0006;
+const DECRYPTION_SECRETS_BLOCK: u32 = 0x0000_000a;
+
+const BYTE_ORDER_MAGIC: u32 = 0x1a2b_3c4d;
+
+/// Secrets type for NSS key log formatted TLS secrets.
+const SECRETS_TYPE_TLS_KEY_LOG: u32 = 0x544c_534b;
+
+/// Raw IP link type, the IP version is inferred from the first nibble.
+const LINKTYPE_RAW: u16 = 101;
+
+const OPT_ENDOFOPT: u16 = 0;
+const OPT_SHB_USERAPPL: u16 = 4;
+const OPT_EPB_FLAGS: u16 = 2;
+
+const IPPROTO_UDP: u8 = 17;
+const UDP_HEADER_LEN: usize = 8;
+const IPV4_HEADER_LEN: usize = 20;
+const IPV6_HEADER_LEN: usize = 40;
+const DEFAULT_TTL: u8 = 64;
+
+/// The direction of a captured datagram, relative to the capturing endpoint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// The datagram was received from the peer.
+    Inbound,
+    /// The datagram was sent to the peer.
+    Outbound,
+}
+
+impl Direction {
+    fn epb_flags(self) -> u32 {
+        match self {
+            Direction::Inbound => 0x1,
+            Direction::Outbound => 0x2,
+        }
+    }
+}
+
+/// A [`PcapWriter`] shared between an event loop and the [`KeylogWriter`]s of
+/// its connections.
+pub type SharedPcapWriter<W> = Arc<Mutex<PcapWriter<W>>>;
+
+/// Writes UDP datagrams and TLS secrets to a pcapng capture.
+///
+/// The Section Header Block and a single raw IP interface are written when the
+/// writer is created. Each datagram is then written as an Enhanced Packet
+/// Block and each batch of key log lines as a Decryption Secrets Block.
+///
+/// Wireshark only applies secrets to packets that follow them in the file.
+/// Since the secrets needed to decrypt a received datagram are usually
+/// derived while quiche processes that same datagram, the most recently
+/// captured datagram is held back until the next block is written, or the
+/// writer is flushed.
+pub struct PcapWriter<W: Write> {
+    writer: W,
+
+    pending: Option<Vec<u8>>,
+}
+
+impl PcapWriter<io::BufWriter<std::fs::File>> {
+    /// Creates a buffered capture file at `path`.
+    ///
+    /// `application` is recorded as the name of the application that wrote
+    /// the capture.
+    pub fn create<P: AsRef<Path>>(
+        path: P, application: &str,
+    ) -> io::Result<Self> {
+        let file = std::fs::File::create(path)?;
+
+        Self::new(io::BufWriter::new(file), application)
+    }
+}
+
+impl<W: Write> PcapWriter<W> {
+    /// Creates a new capture, writing its Section Header Block and Interface
+    /// Description Block to `writer`.
+    ///
+    /// `application` is recorded as the name of the application that wrote
+    /// the capture.
+    pub fn new(writer: W, application: &str) -> io::Result<Self> {
+        let mut pcap = PcapWriter {
+            writer,
+            pending: None,
+        };
+
+        let mut shb = Vec::new();
+        shb.extend_from_slice(&BYTE_ORDER_MAGIC.to_le_bytes());
+        // Major and minor version.
+        shb.extend_from_slice(&1u16.to_le_bytes());
+        shb.extend_from_slice(&0u16.to_le_bytes());
+        // Section length is not specified.
+        shb.extend_from_slice(&(-1i64).to_le_bytes());
+        push_option(&mut shb, OPT_SHB_USERAPPL, application.as_bytes());
+        push_option(&mut shb, OPT_ENDOFOPT, &[]);
+
+        pcap.write_block(SECTION_HEADER_BLOCK, &shb)?;
+
+        let mut idb = Vec::new();
+        idb.extend_from_slice(&LINKTYPE_RAW.to_le_bytes());
+        // Reserved.
+        idb.extend_from_slice(&0u16.to_le_bytes());
+        // No snapshot length limit.
+        idb.extend_from_slice(&0u32.to_le_bytes());
+
+        pcap.write_block(INTERFACE_DESCRIPTION_BLOCK, &idb)?;
+
+        Ok(pcap)
+    }
+
+    /// Converts the writer into a [`SharedPcapWriter`].
+    pub fn into_shared(self) -> SharedPcapWriter<W> {
+        Arc::new(Mutex::new(self))
+    }
+
+    /// Captures a UDP datagram sent from `from` to `to`, timestamped with the
+    /// current time.
+    ///
+    /// If the addresses are of a different IP family, IPv4 addresses are
+    /// recorded as IPv4-mapped IPv6 addresses.
+    pub fn write_datagram(
+        &mut self, direction: Direction, from: SocketAddr, to: SocketAddr,
+        payload: &[u8],
+    ) -> io::Result<()> {
+        self.write_datagram_at(direction, from, to, payload, SystemTime::now())
+    }
+
+    /// Captures a UDP datagram sent from `from` to `to` at the given `time`.
+    pub fn write_datagram_at(
+        &mut self, direction: Direction, from: SocketAddr, to: SocketAddr,
+        payload: &[u8], time: SystemTime,
+    ) -> io::Result<()> {
+        let packet = ip_udp_packet(from, to, payload)?;
+        let packet_len = u32::try_from(packet.len())
+            .map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+
+        let micros = time
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_micros() as u64)
+            .unwrap_or(0);
+
+        let mut epb = Vec::with_capacity(packet.len() + 40);
+        // Interface ID.
+        epb.extend_from_slice(&0u32.to_le_bytes());
+        epb.extend_from_slice(&((micros >> 32) as u32).to_le_bytes());
+        epb.extend_from_slice(&(micros as u32).to_le_bytes());
+        // Captured and original packet length.
+        epb.extend_from_slice(&packet_len.to_le_bytes());
+        epb.extend_from_slice(&packet_len.to_le_bytes());
+        epb.extend_from_slice(&packet);
+        pad(&mut epb);
+        push_option(
+            &mut epb,
+            OPT_EPB_FLAGS,
+            &direction.epb_flags().to_le_bytes(),
+        );
+        push_option(&mut epb, OPT_ENDOFOPT, &[]);
+
+        self.write_pending()?;
+        self.pending = Some(epb);
+
+        Ok(())
+    }
+
+    /// Embeds NSS key log formatted `secrets` in the capture.
+    ///
+    /// Any datagram held back by [`write_datagram()`] is written after the
+    /// secrets.
+    ///
+    /// [`write_datagram()`]: PcapWriter::write_datagram
+    pub fn write_secrets(&mut self, secrets: &[u8]) -> io::Result<()> {
+        let secrets_len = u32::try_from(secrets.len())
+            .map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+
+        let mut dsb = Vec::with_capacity(secrets.len() + 12);
+        dsb.extend_from_slice(&SECRETS_TYPE_TLS_KEY_LOG.to_le_bytes());
+        dsb.extend_from_slice(&secrets_len.to_le_bytes());
+        dsb.extend_from_slice(secrets);
+
+        self.write_block(DECRYPTION_SECRETS_BLOCK, &dsb)?;
+
+        self.write_pending()
+    }
+
+    /// Writes any held back datagram and flushes the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.write_pending()?;
+
+        self.writer.flush()
+    }
+
+    fn write_pending(&mut self) -> io::Result<()> {
+        if let Some(epb) = self.pending.take() {
+            self.write_block(ENHANCED_PACKET_BLOCK, &epb)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_block(&mut self, block_type: u32, body: &[u8]) -> io::Result<()> {
+        let padding = padding_len(body.len());
+        let total_len = u32::try_from(12 + body.len() + padding)
+            .map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+
+        self.writer.write_all(&block_type.to_le_bytes())?;
+        self.writer.write_all(&total_len.to_le_bytes())?;
+        self.writer.write_all(body)?;
+        self.writer.write_all(&[0; 3][..padding])?;
+        self.writer.write_all(&total_len.to_le_bytes())
+    }
+}
+
+impl<W: Write> Drop for PcapWriter<W> {
+    fn drop(&mut self) {
+        self.flush().ok();
+    }
+}
+
+/// A key log writer that embeds secrets into a capture.
+///
+/// It's meant to be passed to quiche's `Connection::set_keylog()`. Complete
+/// key log lines are written to the capture as Decryption Secrets Blocks and,
+/// optionally, copied verbatim to another writer such as the
+/// `SSLKEYLOGFILE`.
+pub struct KeylogWriter<W: Write> {
+    pcap: SharedPcapWriter<W>,
+
+    tee: Option<Box<dyn Write + Send + Sync>>,
+
+    line: Vec<u8>,
+}
+
+impl<W: Write> KeylogWriter<W> {
+    /// Creates a new key log writer for the `pcap` capture, also copying
+    /// secrets to `tee` if provided.
+    pub fn new(
+        pcap: SharedPcapWriter<W>, tee: Option<Box<dyn Write + Send + Sync>>,
+    ) -> Self {
+        KeylogWriter {
+            pcap,
+            tee,
+            line: Vec::new(),
+        }
+    }
+}
+
+impl<W: Write> Write for KeylogWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Some(tee) = &mut self.tee {
+            tee.write_all(buf)?;
+        }
+
+        self.line.extend_from_slice(buf);
+
+        // Only embed complete lines, as that's what Wireshark expects.
+        if let Some(end) = self.line.iter().rposition(|&b| b == b'\n') {
+            let lines: Vec<u8> = self.line.drain(..=end).collect();
+
+            self.pcap
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .write_secrets(&lines)?;
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if let Some(tee) = &mut self.tee {
+            tee.flush()?;
+        }
+
+        self.pcap
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .flush()
+    }
+}
+
+/// A [`SharedPcapWriter`] for a buffered capture file.
+pub type SharedPcapFile = SharedPcapWriter<io::BufWriter<std::fs::File>>;
+
+/// Creates a shared capture file called `name` in `dir`.
+///
+/// `application` is recorded as the name of the application that wrote the
+/// capture. Errors mention the attempted path.
+pub fn make_pcap_writer<P: AsRef<Path>>(
+    dir: P, name: &str, application: &str,
+) -> io::Result<SharedPcapFile> {
+    let path = dir.as_ref().join(name);
+
+    PcapWriter::create(&path, application)
+        .map(PcapWriter::into_shared)
+        .map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!(
+                    "error creating pcap file attempted path was {path:?}: {e}"
+                ),
+            )
+        })
+}
+
+/// Records a UDP datagram in the `pcap` capture, if there is one.
+///
+/// Failures are logged rather than returned, so that a broken capture doesn't
+/// interrupt the connection being captured.
+pub fn capture_datagram<W: Write>(
+    pcap: Option<&SharedPcapWriter<W>>, direction: Direction, from: SocketAddr,
+    to: SocketAddr, buf: &[u8],
+) {
+    if let Some(pcap) = pcap {
+        let mut pcap = pcap.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if let Err(e) = pcap.write_datagram(direction, from, to, buf) {
+            log::error!("failed to capture datagram: {e:?}");
+        }
+    }
+}
+
+/// Makes a key log writer for quiche's `Connection::set_keylog()`.
+///
+/// Secrets are appended to the `keylog` file, and embedded in the `pcap`
+/// capture when there is one. Returns `None` when there is neither.
+pub fn keylog_writer<W: Write + Send + 'static>(
+    pcap: Option<&SharedPcapWriter<W>>, keylog: Option<&std::fs::File>,
+) -> Option<Box<dyn Write + Send + Sync>> {
+    let keylog = keylog.and_then(|f| f.try_clone().ok());
+
+    match (keylog, pcap) {
+        (keylog, Some(pcap)) => {
+            let tee = keylog.map(|f| Box::new(f) as Box<dyn Write + Send + Sync>);
+
+            Some(Box::new(KeylogWriter::new(pcap.clone(), tee)))
+        },
+
+        (Some(keylog), None) => Some(Box::new(keylog)),
+
+        (None, None) => None,
+    }
+}
+
+fn padding_len(len: usize) -> usize {
+    (4 - len % 4) % 4
+}
+
+fn pad(buf: &mut Vec<u8>) {
+    buf.resize(buf.len() + padding_len(buf.len()), 0);
+}
+
+fn push_option(buf: &mut Vec<u8>, code: u16, value: &[u8]) {
+    buf.extend_from_slice(&code.to_le_bytes());
+    buf.extend_from_slice(&(value.len() as u16).to_le_bytes());
+    buf.extend_from_slice(value);
+    pad(buf);
+}
+
+/// Builds an IP packet carrying `payload` in a UDP datagram.
+fn ip_udp_packet(
+    from: SocketAddr, to: SocketAddr, payload: &[u8],
+) -> io::Result<Vec<u8>> {
+    let udp_len = u16::try_from(UDP_HEADER_LEN + payload.len())
+        .map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+
+    let mut udp = Vec::with_capacity(udp_len as usize);
+    udp.extend_from_slice(&from.port().to_be_bytes());
+    udp.extend_from_slice(&to.port().to_be_bytes());
+    udp.extend_from_slice(&udp_len.to_be_bytes());
+    // Checksum, filled in below.
+    udp.extend_from_slice(&[0, 0]);
+    udp.extend_from_slice(payload);
+
+    let mut packet;
+
+    match (from.ip(), to.ip()) {
+        (IpAddr::V4(src), IpAddr::V4(dst)) => {
+            let total_len = u16::try_from(IPV4_HEADER_LEN + udp.len())
+                .map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+
+            packet = Vec::with_capacity(total_len as usize);
+            // Version 4, 5 words header, no DSCP.
+            packet.extend_from_slice(&[0x45, 0]);
+            packet.extend_from_slice(&total_len.to_be_bytes());
+            // Identification and Don't Fragment.
+            packet.extend_from_slice(&[0, 0, 0x40, 0]);
+            packet.extend_from_slice(&[DEFAULT_TTL, IPPROTO_UDP]);
+            // Header checksum, filled in below.
+            packet.extend_from_slice(&[0, 0]);
+            packet.extend_from_slice(&src.octets());
+            packet.extend_from_slice(&dst.octets());
+
+            let csum = checksum(&[&packet]);
+            packet[10..12].copy_from_slice(&csum.to_be_bytes());
+
+            let mut pseudo = Vec::with_capacity(12);
+            pseudo.extend_from_slice(&src.octets());
+            pseudo.extend_from_slice(&dst.octets());
+            pseudo.extend_from_slice(&[0, IPPROTO_UDP]);
+            pseudo.extend_from_slice(&udp_len.to_be_bytes());
+
+            fill_udp_checksum(&pseudo, &mut udp);
+        },
+
+        (src, dst) => {
+            let src = to_ipv6(src);
+            let dst = to_ipv6(dst);
+
+            packet = Vec::with_capacity(IPV6_HEADER_LEN + udp.len());
+            // Version 6, no traffic class or flow label.
+            packet.extend_from_slice(&[0x60, 0, 0, 0]);
+            packet.extend_from_slice(&udp_len.to_be_bytes());
+            packet.extend_from_slice(&[IPPROTO_UDP, DEFAULT_TTL]);
+            packet.extend_from_slice(&src.octets());
+            packet.extend_from_slice(&dst.octets());
+
+            let mut pseudo = Vec::with_capacity(40);
+            pseudo.extend_from_slice(&src.octets());
+            pseudo.extend_from_slice(&dst.octets());
+            pseudo.extend_from_slice(&u32::from(udp_len).to_be_bytes());
+            pseudo.extend_from_slice(&[0, 0, 0, IPPROTO_UDP]);
+
+            fill_udp_checksum(&pseudo, &mut udp);
+        },
+    }
+
+    packet.extend_from_slice(&udp);
+
+    Ok(packet)
+}
+
+fn to_ipv6(ip: IpAddr) -> Ipv6Addr {
+    match ip {
+        IpAddr::V4(ip) => ip.to_ipv6_mapped(),
+        IpAddr::V6(ip) => ip,
+    }
+}
+
+fn fill_udp_checksum(pseudo: &[u8], udp: &mut [u8]) {
+    let csum = match checksum(&[pseudo, udp]) {
+        // A zero checksum means "no checksum", so it's sent as all ones.
+        0 => 0xffff,
+        v => v,
+    };
+
+    udp[6..8].copy_from_slice(&csum.to_be_bytes());
+}
+
+/// Computes the Internet checksum over the concatenation of `chunks`.
+///
+/// All chunks but the last must have an even length.
+fn checksum(chunks: &[&[u8]]) -> u16 {
+    let mut sum: u64 = 0;
+
+    for chunk in chunks {
+        let mut words = chunk.chunks_exact(2);
+
+        for word in &mut words {
+            sum += u64::from(u16::from_be_bytes([word[0], word[1]]));
+        }
+
+        if let [last] = words.remainder() {
+            sum += u64::from(u16::from_be_bytes([*last, 0]));
+        }
+    }
+
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+
+    !(sum as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blocks(mut buf: &[u8]) -> Vec<(u32, Vec<u8>)> {
+        let mut blocks = Vec::new();
+
+        while !buf.is_empty() {
+            let ty = u32::from_le_bytes(buf[0..4].try_into().unwrap());
+            let len = u32::from_le_bytes(buf[4..8].try_into().unwrap()) as usize;
+
+            assert_eq!(len % 4, 0);
+            assert_eq!(buf[len - 4..len], buf[4..8]);
+
+            blocks.push((ty, buf[8..len - 4].to_vec()));
+            buf = &buf[len..];
+        }
+
+        blocks
+    }
+
+    fn addrs() -> (SocketAddr, SocketAddr) {
+        (
+            "192.0.2.1:4433".parse().unwrap(),
+            "192.0.2.2:443".parse().unwrap(),
+        )
+    }
+
+    #[test]
+    fn header_blocks() {
+        let mut out = Vec::new();
+        drop(PcapWriter::new(&mut out, "test").unwrap());
+
+        let blocks = blocks(&out);
+        assert_eq!(blocks.len(), 2);
+
+        let (ty, shb) = &blocks[0];
+        assert_eq!(*ty, SECTION_HEADER_BLOCK);
+        assert_eq!(shb[0..4], BYTE_ORDER_MAGIC.to_le_bytes());
+
+        let (ty, idb) = &blocks[1];
+        assert_eq!(*ty, INTERFACE_DESCRIPTION_BLOCK);
+        assert_eq!(idb[0..2], LINKTYPE_RAW.to_le_bytes());
+    }
+
+    #[test]
+    fn ipv4_datagram() {
+        let (from, to) = addrs();
+
+        let mut out = Vec::new();
+        let mut pcap = PcapWriter::new(&mut out, "test").unwrap();
+        pcap.write_datagram(Direction::Outbound, from, to, b"hello")
+            .unwrap();
+        drop(pcap);
+
+        let blocks = blocks(&out);
+        let (ty, epb) = &blocks[2];
+        assert_eq!(*ty, ENHANCED_PACKET_BLOCK);
+
+        let len = u32::from_le_bytes(epb[12..16].try_into().unwrap()) as usize;
+        assert_eq!(len, IPV4_HEADER_LEN + UDP_HEADER_LEN + 5);
+
+        let packet = &epb[20..20 + len];
+        assert_eq!(packet[0], 0x45);
+        assert_eq!(packet[9], IPPROTO_UDP);
+        // A valid header checksums to zero.
+        assert_eq!(checksum(&[&packet[..IPV4_HEADER_LEN]]), 0);
+
+        let udp = &packet[IPV4_HEADER_LEN..];
+        assert_eq!(udp[0..2], 4433u16.to_be_bytes());
+        assert_eq!(udp[2..4], 443u16.to_be_bytes());
+        assert_eq!(&udp[UDP_HEADER_LEN..], b"hello");
+    }
+
+    #[test]
+    fn mixed_family_datagram() {
+        let from = "[::]:4433".parse().unwrap();
+        let (_, to) = addrs();
+
+        let packet = ip_udp_packet(from, to, b"hello").unwrap();
+        assert_eq!(packet[0] >> 4, 6);
+        assert_eq!(
+            packet[24..40],
+            "::ffff:192.0.2.2".parse::<Ipv6Addr>().unwrap().octets()
+        );
+    }
+
+    #[test]
+    fn secrets_precede_held_back_datagram() {
+        let (from, to) = addrs();
+
+        let mut out = Vec::new();
+        let mut pcap = PcapWriter::new(&mut out, "test").unwrap();
+        pcap.write_datagram(Direction::Inbound, to, from, b"initial")
+            .unwrap();
+        pcap.write_secrets(b"CLIENT_HANDSHAKE_TRAFFIC_SECRET 00 11\n")
+            .unwrap();
+        pcap.write_datagram(Direction::Outbound, from, to, b"handshake")
+            .unwrap();
+        drop(pcap);
+
+        let types: Vec<u32> = blocks(&out).iter().map(|(ty, _)| *ty).collect();
+        assert_eq!(types, vec![
+            SECTION_HEADER_BLOCK,
+            INTERFACE_DESCRIPTION_BLOCK,
+            DECRYPTION_SECRETS_BLOCK,
+            ENHANCED_PACKET_BLOCK,
+            ENHANCED_PACKET_BLOCK,
+        ]);
+    }
+
+    #[test]
+    fn keylog_complete_lines() {
+        let pcap = PcapWriter::new(Vec::new(), "test").unwrap().into_shared();
+        let mut keylog = KeylogWriter::new(pcap.clone(), None);
+
+        keylog
+            .write_all(b"SERVER_HANDSHAKE_TRAFFIC_SECRET")
+            .unwrap();
+        keylog.write_all(b" 00 11\nCLIENT_").unwrap();
+        keylog.flush().unwrap();
+
+        let pcap = pcap.lock().unwrap();
+        let blocks = blocks(&pcap.writer);
+        assert_eq!(blocks.len(), 3);
+
+        let (ty, dsb) = &blocks[2];
+        assert_eq!(*ty, DECRYPTION_SECRETS_BLOCK);
+        assert_eq!(dsb[0..4], SECRETS_TYPE_TLS_KEY_LOG.to_le_bytes());
+        assert_eq!(&dsb[8..], b"SERVER_HANDSHAKE_TRAFFIC_SECRET 00 11\n\0\0");
+    }
+
+    #[test]
+    fn keylog_writer_tee() {
+        let dir = std::env::temp_dir()
+            .join(format!("pcapng-writer-keylog-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let keylog_path = dir.join("keylog.txt");
+        let keylog = std::fs::File::create(&keylog_path).unwrap();
+
+        let pcap = make_pcap_writer(&dir, "test.pcapng", "test").unwrap();
+
+        assert!(keylog_writer::<Vec<u8>>(None, None).is_none());
+
+        let mut writer = keylog_writer(Some(&pcap), Some(&keylog)).unwrap();
+        writer.write_all(b"CLIENT_RANDOM 00 11\n").unwrap();
+        writer.flush().unwrap();
+        drop(writer);
+        drop(pcap);
+
+        assert_eq!(
+            std::fs::read(&keylog_path).unwrap(),
+            b"CLIENT_RANDOM 00 11\n"
+        );
+
+        let capture = std::fs::read(dir.join("test.pcapng")).unwrap();
+        let (ty, _) = &blocks(&capture)[2];
+        assert_eq!(*ty, DECRYPTION_SECRETS_BLOCK);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn make_pcap_writer_error() {
+        let dir = std::env::temp_dir().join("pcapng-writer-does-not-exist");
+
+        let err = make_pcap_writer(&dir, "test.pcapng", "test").err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert!(err.to_string().contains("test.pcapng"));
+    }
+}
@@ -44,6 +44,7 @@ const STREAM_REL_DL_CANVAS = "rel_dl_canvas"
 const STREAM_ABS_UL_CANVAS = "abs_ul_canvas"
 const STREAM_REL_UL_CANVAS = "rel_ul_canvas"
 const PENDING_CANVAS = "pending_canvas"
+const WATERFALL_CANVAS = "waterfall_canvas"
 
 let canvasMap = new Map();
 canvasMap.set(OVERVIEW_CANVAS, {show_legend: false, x_start: null, x_end: null});
@@ -61,6 +62,7 @@ canvasMap.set(STREAM_REL_DL_CANVAS, {show_legend: false, x_start: null, x_end: n
 canvasMap.set(STREAM_ABS_UL_CANVAS, {show_legend: false, x_start: null, x_end: null});
 canvasMap.set(STREAM_REL_UL_CANVAS, {show_legend: false, x_start: null, x_end: null});
 canvasMap.set(PENDING_CANVAS, {show_legend: false, x_start: null, x_end: null});
+canvasMap.set(WATERFALL_CANVAS, {show_legend: true, x_start: null, x_end: null});
 
 
 function setupDarkmode() {
@@ -203,6 +205,8 @@ function redrawByCanvasId(canvas_id) {
         drawSparks(true)
     } else if(canvas_id === "pending_canvas") {
         drawPending(true)
+    } else if(canvas_id === WATERFALL_CANVAS) {
+        drawWaterfall(true)
     } else {
         console.log("unknown canvas id is " + canvas_id)
     }
@@ -330,6 +334,20 @@ function drawPending(resize) {
     }
 }
 
+function drawWaterfall(resize) {
+    if (resize || !window.waterfallDrawn) {
+        let sub_start_draw = Date.now();
+        let canvas = canvasMap.get(WATERFALL_CANVAS);
+        // One row per request, so the height depends on the log.
+        document.getElementById(WATERFALL_CANVAS).height = qlog_dancer.waterfall_height();
+        qlog_dancer.draw_waterfall(WATERFALL_CANVAS, canvas.show_legend, canvas.x_start, canvas.x_end);
+        console.log(`waterfall draw duration: ${Date.now() - sub_start_draw} ms`);
+        window.waterfallDrawn = true;
+    } else {
+        console.log("no need to redraw")
+    }
+}
+
 function toggleOverview() {
     if (document.getElementById("overview-btn").innerText == "Overview shown") {
         document.getElementById("overview-btn").innerText = "Overview hidden";
@@ -396,6 +414,17 @@ function togglePending() {
     }
 }
 
+function toggleWaterfall() {
+    if (document.getElementById("waterfall-btn").innerText == "Request waterfall shown") {
+        document.getElementById("waterfall-btn").innerText = "Request waterfall hidden";
+    } else {
+        document.getElementById("waterfall-btn").innerText = "Request waterfall shown";
+    }
+    if (toggleElem(document.getElementById("waterfall"))) {
+        drawWaterfall()
+    }
+}
+
 function toggleSparks() {
     if (document.getElementById("sparks-btn").innerText == "Stream sparks shown") {
         document.getElementById("sparks-btn").innerText = "Stream sparks hidden";
@@ -467,6 +496,17 @@ function setupTable() {
         });
 }
 
+function exportSeries(vega_lite) {
+    let json = qlog_dancer.export_series(vega_lite);
+    let blob = new Blob([json], {type: "application/json"});
+
+    let link = document.createElement("a");
+    link.href = URL.createObjectURL(blob);
+    link.download = vega_lite ? "series.vl.json" : "series.json";
+    link.click();
+    URL.revokeObjectURL(link.href);
+}
+
 function findCanvasIdInDiv(event) {
     let canvas_id = null;
     // Get the div where the click happened
@@ -508,8 +548,11 @@ document.getElementById("pkt-tx-btn").addEventListener("click", togglePacketSent
 document.getElementById("stream-multiplex-btn").addEventListener("click", toggleStreamMultiplex);
 document.getElementById("sparks-btn").addEventListener("click", toggleSparks);
 document.getElementById("pending-btn").addEventListener("click", togglePending);
+document.getElementById("waterfall-btn").addEventListener("click", toggleWaterfall);
 document.getElementById("event-list-btn").addEventListener("click", toggleEventList);
 document.getElementById("render-events-btn").addEventListener("click", render_events_table);
+document.getElementById("export-json-btn").addEventListener("click", () => exportSeries(false));
+document.getElementById("export-vega-lite-btn").addEventListener("click", () => exportSeries(true));
 
 document.getElementById("overview-reset-btn").addEventListener("click", resetZoom);
 document.getElementById("cc-reset-btn").addEventListener("click", resetZoom);
@@ -526,6 +569,7 @@ document.getElementById("pkt-tx-toggle-legend-btn").addEventListener("click", to
 document.getElementById("pkt-tx-counts-toggle-legend-btn").addEventListener("click", toggleLegend);
 document.getElementById("pkt-tx-delta-toggle-legend-btn").addEventListener("click", toggleLegend);
 document.getElementById("pkt-tx-pacing-toggle-legend-btn").addEventListener("click", toggleLegend);
+document.getElementById("waterfall-toggle-legend-btn").addEventListener("click", toggleLegend);
 
 function setupCanvasResizer(canvas_id) {
     const canvas = document.getElementById(canvas_id);
@@ -0,0 +1,637 @@
+// Copyright (C) 2025, Cloudflare, Inc.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are
+// met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//
+//     * Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in the
+//       documentation and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS
+// IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO,
+// THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR
+// PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Aggregate per-connection statistics across many log files.
+
+use std::panic::AssertUnwindSafe;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+
+use log::warn;
+
+use crate::config::AppConfig;
+use crate::datastore::Datastore;
+use crate::parse_log_file;
+use crate::plots::compare::stream_progress;
+
+/// File name suffixes of logs picked up when walking a directory.
+const LOG_SUFFIXES: &[&str] =
+    &[".sqlog", ".sqlog.gz", ".sqlog.zst", ".qlog", ".json"];
+
+/// Metrics of a single connection.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConnectionSummary {
+    pub file: String,
+    pub session_id: Option<i64>,
+    pub host: Option<String>,
+
+    /// Time from the start of the log until the handshake completed, in ms.
+    pub handshake_time: Option<f64>,
+
+    pub packets_sent: u64,
+    pub packets_lost: u64,
+
+    /// 50th, 90th and 99th percentile of the latest RTT samples, in ms.
+    pub rtt_percentiles: Option<[f32; 3]>,
+
+    /// Stream data throughput, in Mbps.
+    pub goodput: Option<f64>,
+
+    /// Duration of the log, in ms.
+    pub duration: f64,
+
+    pub close_reason: Option<String>,
+}
+
+impl ConnectionSummary {
+    pub const COLUMNS: [&'static str; 13] = [
+        "file",
+        "session_id",
+        "host",
+        "handshake_time_ms",
+        "packets_sent",
+        "packets_lost",
+        "loss_rate",
+        "rtt_p50_ms",
+        "rtt_p90_ms",
+        "rtt_p99_ms",
+        "goodput_mbps",
+        "duration_ms",
+        "close_reason",
+    ];
+
+    pub fn from_datastore(file: &str, ds: &Datastore) -> Self {
+        let packets_sent = ds.packet_sent.values().map(|p| p.len() as u64).sum();
+
+        // Older netlogs don't log packet losses, so fall back to the packets
+        // reported missing by the peer.
+        let netlog_packets_lost = if ds.netlog_packets_lost > 0 {
+            ds.netlog_packets_lost
+        } else {
+            ds.netlog_ack_received_missing_packet
+                .values()
+                .map(|p| p.len() as u64)
+                .sum::<u64>()
+        };
+
+        let packets_lost = ds.qlog_packets_lost + netlog_packets_lost;
+
+        let mut rtts: Vec<f32> =
+            ds.local_latest_rtt.iter().map(|p| p.1).collect();
+        rtts.sort_by(f32::total_cmp);
+
+        let rtt_percentiles = (!rtts.is_empty()).then(|| {
+            [0.5, 0.9, 0.99]
+                .map(|p| rtts[((rtts.len() - 1) as f64 * p).round() as usize])
+        });
+
+        // The bytes of the first chunk arrived before the measured interval
+        // starts, so they don't count towards the rate.
+        let progress = stream_progress(ds);
+        let goodput = match (progress.first(), progress.last()) {
+            (Some(first), Some(last)) if last.0 > first.0 => Some(
+                (last.1 - first.1) as f64 * 8.0 / ((last.0 - first.0) * 1000.0),
+            ),
+
+            _ => None,
+        };
+
+        ConnectionSummary {
+            file: file.to_string(),
+            session_id: ds.session_id,
+            host: ds.host.clone(),
+            handshake_time: ds.handshake_complete_time,
+            packets_sent,
+            packets_lost,
+            rtt_percentiles,
+            goodput,
+            duration: ds.last_event_time,
+            close_reason: close_reason(ds),
+        }
+    }
+
+    /// Ratio of lost to sent packets.
+    pub fn loss_rate(&self) -> Option<f64> {
+        (self.packets_sent > 0)
+            .then(|| self.packets_lost as f64 / self.packets_sent as f64)
+    }
+
+    /// Returns the values of [`Self::COLUMNS`], formatted as strings.
+    pub fn row(&self) -> Vec<String> {
+        fn opt<T: ToString>(v: Option<T>) -> String {
+            v.map(|v| v.to_string()).unwrap_or_default()
+        }
+
+        let rtt = |i: usize| opt(self.rtt_percentiles.map(|p| p[i]));
+
+        vec![
+            self.file.clone(),
+            opt(self.session_id),
+            opt(self.host.clone()),
+            opt(self.handshake_time),
+            self.packets_sent.to_string(),
+            self.packets_lost.to_string(),
+            opt(self.loss_rate().map(|r| format!("{r:.4}"))),
+            rtt(0),
+            rtt(1),
+            rtt(2),
+            opt(self.goodput.map(|g| format!("{g:.3}"))),
+            self.duration.to_string(),
+            opt(self.close_reason.clone()),
+        ]
+    }
+}
+
+fn close_reason(ds: &Datastore) -> Option<String> {
+    if let Some(cc) = &ds.qlog_connection_closed {
+        let as_string = |v: serde_json::Value| match v {
+            serde_json::Value::String(s) => s,
+
+            v => v.to_string(),
+        };
+
+        let reason = cc
+            .trigger
+            .as_ref()
+            .and_then(|t| serde_json::to_value(t).ok())
+            .or_else(|| {
+                cc.connection_error
+                    .as_ref()
+                    .and_then(|e| serde_json::to_value(e).ok())
+            })
+            .or_else(|| {
+                cc.application_error
+                    .as_ref()
+                    .and_then(|e| serde_json::to_value(e).ok())
+            })
+            .map(as_string)
+            .or_else(|| cc.error_code.map(|c| format!("error_code={c}")))
+            .unwrap_or_else(|| "unknown".to_string());
+
+        return Some(reason);
+    }
+
+    if let Some(close) = &ds.quic_session_close {
+        return Some(close.quic_error_pretty.to_string());
+    }
+
+    ds.h2_session_close
+        .as_ref()
+        .map(|close| close.net_err_pretty.to_string())
+}
+
+/// Returns all log files in `dir` and its subdirectories, sorted by path.
+pub fn find_log_files(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    let mut dirs = vec![dir.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+
+            if path.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            if LOG_SUFFIXES.iter().any(|s| name.ends_with(s)) {
+                files.push(path);
+            }
+        }
+    }
+
+    files.sort();
+
+    Ok(files)
+}
+
+/// Parses `files` using `jobs` threads and summarizes every connection in
+/// them.
+///
+/// Files that fail to parse are logged and skipped. Returns the summaries
+/// along with the number of skipped files.
+pub fn summarize_files(
+    files: &[PathBuf], jobs: usize, config: impl Fn(&str) -> AppConfig + Sync,
+) -> (Vec<ConnectionSummary>, usize) {
+    let next = AtomicUsize::new(0);
+    let failed = AtomicUsize::new(0);
+    let summaries = Mutex::new(vec![]);
+
+    std::thread::scope(|s| {
+        for _ in 0..jobs.max(1) {
+            s.spawn(|| {
+                while let Some(path) =
+                    files.get(next.fetch_add(1, Ordering::Relaxed))
+                {
+                    let file = path.to_string_lossy();
+
+                    // Datastore population panics on some malformed logs, which
+                    // shouldn't abort the whole batch.
+                    let result =
+                        std::panic::catch_unwind(AssertUnwindSafe(|| {
+                            parse_log_file(&config(&file)).map(|log_file| {
+                                log_file
+                                    .data
+                                    .iter()
+                                    .map(|data| {
+                                        ConnectionSummary::from_datastore(
+                                            &file,
+                                            &data.datastore,
+                                        )
+                                    })
+                                    .collect::<Vec<_>>()
+                            })
+                        }));
+
+                    match result {
+                        Ok(Ok(v)) => summaries.lock().unwrap().extend(v),
+
+                        Ok(Err(e)) => {
+                            warn!("skipping {}: {}", file, e);
+                            failed.fetch_add(1, Ordering::Relaxed);
+                        },
+
+                        Err(_) => {
+                            warn!("skipping {}: parsing panicked", file);
+                            failed.fetch_add(1, Ordering::Relaxed);
+                        },
+                    }
+                }
+            });
+        }
+    });
+
+    let mut summaries = summaries.into_inner().unwrap();
+    summaries
+        .sort_by(|a, b| (&a.file, a.session_id).cmp(&(&b.file, b.session_id)));
+
+    (summaries, failed.into_inner())
+}
+
+/// Writes summaries as CSV, with a header row.
+pub fn write_csv<W: std::io::Write>(
+    summaries: &[ConnectionSummary], mut writer: W,
+) -> std::io::Result<()> {
+    fn escape(field: &str) -> std::borrow::Cow<'_, str> {
+        if field.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", field.replace('"', "\"\"")).into()
+        } else {
+            field.into()
+        }
+    }
+
+    writeln!(writer, "{}", ConnectionSummary::COLUMNS.join(","))?;
+
+    for summary in summaries {
+        let row = summary.row();
+        let fields: Vec<_> = row.iter().map(|f| escape(f)).collect();
+        writeln!(writer, "{}", fields.join(","))?;
+    }
+
+    writer.flush()
+}
+
+/// Parquet schema of summaries, whose columns match
+/// [`ConnectionSummary::COLUMNS`].
+#[cfg(feature = "parquet")]
+const PARQUET_SCHEMA: &str = "
+    message connection {
+        REQUIRED BYTE_ARRAY file (UTF8);
+        OPTIONAL INT64 session_id;
+        OPTIONAL BYTE_ARRAY host (UTF8);
+        OPTIONAL DOUBLE handshake_time_ms;
+        REQUIRED INT64 packets_sent;
+        REQUIRED INT64 packets_lost;
+        OPTIONAL DOUBLE loss_rate;
+        OPTIONAL FLOAT rtt_p50_ms;
+        OPTIONAL FLOAT rtt_p90_ms;
+        OPTIONAL FLOAT rtt_p99_ms;
+        OPTIONAL DOUBLE goodput_mbps;
+        REQUIRED DOUBLE duration_ms;
+        OPTIONAL BYTE_ARRAY close_reason (UTF8);
+    }
+";
+
+/// Writes summaries as a Parquet file with a single row group.
+#[cfg(feature = "parquet")]
+pub fn write_parquet<W: std::io::Write + Send>(
+    summaries: &[ConnectionSummary], writer: W,
+) -> std::io::Result<()> {
+    use std::sync::Arc;
+
+    use parquet::basic::Compression;
+    use parquet::data_type::ByteArray;
+    use parquet::data_type::ByteArrayType;
+    use parquet::data_type::DoubleType;
+    use parquet::data_type::FloatType;
+    use parquet::data_type::Int64Type;
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::parser::parse_message_type;
+
+    let write = || -> parquet::errors::Result<()> {
+        let schema = Arc::new(parse_message_type(PARQUET_SCHEMA)?);
+        let props = Arc::new(
+            WriterProperties::builder()
+                .set_compression(Compression::SNAPPY)
+                .build(),
+        );
+
+        let mut writer = SerializedFileWriter::new(writer, schema, props)?;
+        let mut row_group = writer.next_row_group()?;
+
+        let text = |v: &str| ByteArray::from(v);
+        let rtt =
+            |s: &ConnectionSummary, i: usize| s.rtt_percentiles.map(|p| p[i]);
+
+        let rows = summaries.iter();
+
+        write_column::<ByteArrayType, _>(
+            &mut row_group,
+            rows.clone().map(|s| Some(text(&s.file))),
+        )?;
+        write_column::<Int64Type, _>(
+            &mut row_group,
+            rows.clone().map(|s| s.session_id),
+        )?;
+        write_column::<ByteArrayType, _>(
+            &mut row_group,
+            rows.clone().map(|s| s.host.as_deref().map(text)),
+        )?;
+        write_column::<DoubleType, _>(
+            &mut row_group,
+            rows.clone().map(|s| s.handshake_time),
+        )?;
+        write_column::<Int64Type, _>(
+            &mut row_group,
+            rows.clone().map(|s| Some(s.packets_sent as i64)),
+        )?;
+        write_column::<Int64Type, _>(
+            &mut row_group,
+            rows.clone().map(|s| Some(s.packets_lost as i64)),
+        )?;
+        write_column::<DoubleType, _>(
+            &mut row_group,
+            rows.clone().map(|s| s.loss_rate()),
+        )?;
+
+        for i in 0..3 {
+            write_column::<FloatType, _>(
+                &mut row_group,
+                rows.clone().map(|s| rtt(s, i)),
+            )?;
+        }
+
+        write_column::<DoubleType, _>(
+            &mut row_group,
+            rows.clone().map(|s| s.goodput),
+        )?;
+        write_column::<DoubleType, _>(
+            &mut row_group,
+            rows.clone().map(|s| Some(s.duration)),
+        )?;
+        write_column::<ByteArrayType, _>(
+            &mut row_group,
+            rows.map(|s| s.close_reason.as_deref().map(text)),
+        )?;
+
+        row_group.close()?;
+        writer.close()?;
+
+        Ok(())
+    };
+
+    write().map_err(std::io::Error::other)
+}
+
+/// Writes the next column of `row_group`. Missing values are written as
+/// nulls.
+#[cfg(feature = "parquet")]
+fn write_column<T: parquet::data_type::DataType, W: std::io::Write + Send>(
+    row_group: &mut parquet::file::writer::SerializedRowGroupWriter<'_, W>,
+    values: impl Iterator<Item = Option<T::T>>,
+) -> parquet::errors::Result<()> {
+    let Some(mut column) = row_group.next_column()? else {
+        return Err(parquet::errors::ParquetError::General(
+            "schema has fewer columns than the summary".to_string(),
+        ));
+    };
+
+    let mut present = vec![];
+    let mut def_levels = vec![];
+
+    for v in values {
+        def_levels.push(v.is_some() as i16);
+        present.extend(v);
+    }
+
+    let writer = column.typed::<T>();
+
+    // Required columns have no definition levels.
+    let def_levels = (writer.get_descriptor().max_def_level() > 0)
+        .then_some(def_levels.as_slice());
+
+    writer.write_batch(&present, def_levels, None)?;
+
+    column.close()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datastore::PacketInfoStub;
+    use crate::datastore::StreamDatapoint;
+    use crate::datastore::VantagePoint;
+    use crate::PacketType;
+
+    #[test]
+    fn summary() {
+        let mut ds = Datastore {
+            vantage_point: VantagePoint::Client,
+            handshake_complete_time: Some(12.5),
+            last_event_time: 1000.0,
+            qlog_packets_lost: 1,
+            local_latest_rtt: (1..=100).map(|i| (i as f64, i as f32)).collect(),
+            ..Default::default()
+        };
+
+        ds.packet_sent.insert(
+            PacketType::OneRtt,
+            (0..4)
+                .map(|number| {
+                    (number, PacketInfoStub {
+                        acked: None,
+                        raw: None,
+                        created_time: number as f64,
+                        send_at_time: None,
+                        ty: PacketType::OneRtt,
+                        number,
+                    })
+                })
+                .collect(),
+        );
+
+        ds.received_stream_frames.insert(0, vec![
+            (100.0, StreamDatapoint {
+                offset: 0,
+                length: 250_000,
+            }),
+            (600.0, StreamDatapoint {
+                offset: 250_000,
+                length: 750_000,
+            }),
+        ]);
+
+        let summary = ConnectionSummary::from_datastore("a.sqlog", &ds);
+        assert_eq!(summary.handshake_time, Some(12.5));
+        assert_eq!(summary.loss_rate(), Some(0.25));
+        assert_eq!(summary.rtt_percentiles, Some([51.0, 90.0, 99.0]));
+        assert_eq!(summary.goodput, Some(12.0));
+        assert_eq!(summary.close_reason, None);
+        assert_eq!(summary.row().len(), ConnectionSummary::COLUMNS.len());
+    }
+
+    #[test]
+    fn goodput() {
+        // 125 kB every 100 ms is 10 Mbps.
+        let mut ds = Datastore {
+            vantage_point: VantagePoint::Client,
+            ..Default::default()
+        };
+
+        ds.received_stream_frames.insert(
+            0,
+            (0..5)
+                .map(|i| {
+                    (i as f64 * 100.0, StreamDatapoint {
+                        offset: i * 125_000,
+                        length: 125_000,
+                    })
+                })
+                .collect(),
+        );
+
+        let summary = ConnectionSummary::from_datastore("a.sqlog", &ds);
+        assert_eq!(summary.goodput, Some(10.0));
+
+        // A single chunk has no transfer time to measure.
+        ds.received_stream_frames.get_mut(&0).unwrap().truncate(1);
+
+        let summary = ConnectionSummary::from_datastore("a.sqlog", &ds);
+        assert_eq!(summary.goodput, None);
+    }
+
+    #[test]
+    fn csv() {
+        let summary = ConnectionSummary {
+            file: "a.sqlog".to_string(),
+            close_reason: Some("idle, \"timeout\"".to_string()),
+            ..Default::default()
+        };
+
+        let mut out = vec![];
+        write_csv(&[summary], &mut out).unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        let mut lines = out.lines();
+
+        assert_eq!(lines.next().unwrap(), ConnectionSummary::COLUMNS.join(","));
+        assert_eq!(
+            lines.next().unwrap(),
+            "a.sqlog,,,,0,0,,,,,,0,\"idle, \"\"timeout\"\"\""
+        );
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn parquet() {
+        use parquet::file::reader::FileReader;
+        use parquet::file::reader::SerializedFileReader;
+        use parquet::record::RowAccessor;
+
+        let summaries = [
+            ConnectionSummary {
+                file: "a.sqlog".to_string(),
+                host: Some("example.com".to_string()),
+                packets_sent: 4,
+                packets_lost: 1,
+                rtt_percentiles: Some([51.0, 90.0, 99.0]),
+                duration: 1000.0,
+                ..Default::default()
+            },
+            ConnectionSummary {
+                file: "b.json".to_string(),
+                session_id: Some(7),
+                close_reason: Some("idle timeout".to_string()),
+                ..Default::default()
+            },
+        ];
+
+        let path = std::env::temp_dir()
+            .join(format!("qlog-dancer-{}.parquet", std::process::id()));
+        let file = std::fs::File::create(&path).unwrap();
+        write_parquet(&summaries, file).unwrap();
+
+        let reader =
+            SerializedFileReader::new(std::fs::File::open(&path).unwrap())
+                .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let columns: Vec<_> = reader
+            .metadata()
+            .file_metadata()
+            .schema_descr()
+            .columns()
+            .iter()
+            .map(|c| c.name().to_string())
+            .collect();
+        assert_eq!(columns, ConnectionSummary::COLUMNS);
+
+        let rows: Vec<_> = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| row.unwrap())
+            .collect();
+        assert_eq!(rows.len(), 2);
+
+        assert_eq!(rows[0].get_string(0).unwrap(), "a.sqlog");
+        assert_eq!(rows[0].get_string(2).unwrap(), "example.com");
+        assert_eq!(rows[0].get_long(4).unwrap(), 4);
+        assert_eq!(rows[0].get_double(6).unwrap(), 0.25);
+        assert_eq!(rows[0].get_float(8).unwrap(), 90.0);
+        assert_eq!(rows[0].get_double(11).unwrap(), 1000.0);
+        assert!(rows[0].get_string(12).is_err());
+
+        assert_eq!(rows[1].get_string(0).unwrap(), "b.json");
+        assert_eq!(rows[1].get_long(1).unwrap(), 7);
+        assert!(rows[1].get_double(6).is_err());
+        assert_eq!(rows[1].get_string(12).unwrap(), "idle timeout");
+    }
+}
@@ -25,19 +25,411 @@
 // SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use std::collections::HashSet;
+use std::time::Duration;
 
 use clap::Arg;
 use clap::ArgAction;
+use clap::ArgMatches;
 use clap::Command;
 
 use crate::datastore::PrintStatsConfig;
+use crate::export::SeriesExportFormat;
 use crate::plots::colors::PlotColors;
 use crate::plots::colors::DARK_MODE;
 use crate::plots::colors::LIGHT_MODE;
+use crate::plots::compare::CompareAlignment;
 use crate::plots::stream_sparks::SparkPlotsParams;
 use crate::plots::ClampParams;
+use crate::reports::anomalies::AnomalyThresholds;
 use crate::SerializationFormat;
 
+/// The action selected on the command line.
+#[derive(Debug)]
+pub enum AppCommand {
+    /// Analyze a single log file.
+    Analyze(Box<AppConfig>),
+
+    /// Merge traces of the same connection from multiple vantage points.
+    Merge(MergeConfig),
+
+    /// Overlay plots of several log files.
+    Compare(CompareConfig),
+
+    /// Aggregate statistics of all log files in a directory.
+    Batch(BatchConfig),
+
+    /// Redraw charts of a qlog while it is being written.
+    Live(LiveConfig),
+}
+
+impl AppCommand {
+    pub fn from_clap() -> std::result::Result<Self, String> {
+        let mut matches = AppConfig::command().get_matches();
+
+        match matches.remove_subcommand() {
+            Some((name, matches)) if name == "merge" =>
+                MergeConfig::from_matches(matches).map(AppCommand::Merge),
+
+            Some((name, matches)) if name == "compare" =>
+                CompareConfig::from_matches(matches).map(AppCommand::Compare),
+
+            Some((name, matches)) if name == "batch" =>
+                BatchConfig::from_matches(matches).map(AppCommand::Batch),
+
+            Some((name, matches)) if name == "live" =>
+                LiveConfig::from_matches(matches).map(AppCommand::Live),
+
+            _ => AppConfig::from_matches(matches)
+                .map(|config| AppCommand::Analyze(Box::new(config))),
+        }
+    }
+}
+
+fn start_arg() -> Arg {
+    Arg::new("start")
+        .short('s')
+        .help("Relative start time for plots")
+        .value_parser(clap::value_parser!(f64))
+}
+
+fn end_arg() -> Arg {
+    Arg::new("end")
+        .short('e')
+        .help("Relative end time for plots")
+        .value_parser(clap::value_parser!(f64))
+}
+
+fn cwnd_y_axis_max_arg() -> Arg {
+    Arg::new("cwnd_y_axis_max")
+        .long("cwnd-y-axis-max")
+        .help("Maximum value of Y axis on cwnd-related charts")
+        .value_parser(clap::value_parser!(u64))
+}
+
+fn stream_y_axis_max_arg() -> Arg {
+    Arg::new("stream_y_axis_max")
+        .long("stream-y-axis-max")
+        .help("Maximum value of Y axis on stream-related charts")
+        .value_parser(clap::value_parser!(u64))
+}
+
+fn netlog_filter_arg() -> Arg {
+    Arg::new("netlog_filter")
+        .long("netlog-filter")
+        .help("A comma-separated list of hostnames to filter in to netlog analysis. By default, all hostnames are analysed.")
+}
+
+fn netlog_session_arg() -> Arg {
+    Arg::new("netlog_session")
+        .long("netlog-session")
+        .help("A comma-separated list of session IDs to filter in to netlog analysis. By default, all sessions are analysed.")
+}
+
+fn wirefilter_arg() -> Arg {
+    Arg::new("wirefilter")
+        .long("wirefilter")
+        .alias("qlog-wirefilter")
+        .help("A Wirefilter expression for Wireshark-like matching of qlog and netlog events. Applies to plots and reports.")
+}
+
+fn dark_mode_arg() -> Arg {
+    Arg::new("dark_mode")
+        .long("dark_mode")
+        .help("Generate outputs in a dark mode style")
+        .action(ArgAction::SetTrue)
+}
+
+/// Returns the hostnames given to [`netlog_filter_arg()`].
+fn netlog_filter(matches: &mut ArgMatches) -> HashSet<String> {
+    matches
+        .remove_one::<String>("netlog_filter")
+        .map(|f| f.split(',').map(|v| v.to_string()).collect())
+        .unwrap_or_default()
+}
+
+#[derive(Debug)]
+pub struct MergeConfig {
+    pub files: Vec<String>,
+    pub output: String,
+    pub odcid: Option<String>,
+}
+
+impl MergeConfig {
+    fn command() -> Command {
+        Command::new("merge")
+            .about("Merges client and server qlogs of a connection into one multi-vantage-point qlog, aligning their clocks")
+            .arg(
+                Arg::new("LOG FILES")
+                    .help("The sqlog files to merge. The first one is used as the clock reference")
+                    .required(true)
+                    .num_args(2..)
+                    .index(1),
+            )
+            .arg(
+                Arg::new("output")
+                    .short('o')
+                    .long("output")
+                    .help("Sets the output qlog file")
+                    .default_value("merged.qlog"),
+            )
+            .arg(
+                Arg::new("odcid")
+                    .long("odcid")
+                    .help("Only merge traces of the connection with this original destination connection ID"),
+            )
+    }
+
+    fn from_matches(
+        mut matches: ArgMatches,
+    ) -> std::result::Result<Self, String> {
+        let files = matches
+            .remove_many::<String>("LOG FILES")
+            .unwrap()
+            .collect();
+        let output = matches.remove_one::<String>("output").unwrap();
+        let odcid = matches.remove_one::<String>("odcid");
+
+        Ok(Self {
+            files,
+            output,
+            odcid,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct CompareConfig {
+    pub files: Vec<String>,
+    pub charts_dir: String,
+    pub alignment: CompareAlignment,
+    pub start: Option<f64>,
+    pub end: Option<f64>,
+    pub cwnd_y_max: Option<u64>,
+    pub stream_y_max: Option<u64>,
+    pub netlog_filter: HashSet<String>,
+    pub dark_mode: bool,
+}
+
+impl CompareConfig {
+    fn command() -> Command {
+        Command::new("compare")
+            .about("Overlays cwnd, bytes in flight, RTT and stream progress of several qlog or netlog files on shared axes")
+            .arg(
+                Arg::new("LOG FILES")
+                    .help("The log files to compare")
+                    .required(true)
+                    .num_args(2..)
+                    .index(1),
+            )
+            .arg(
+                Arg::new("charts_directory")
+                    .long("charts-directory")
+                    .help("Sets the output directory for charts")
+                    .default_value("compare-charts"),
+            )
+            .arg(
+                Arg::new("align")
+                    .long("align")
+                    .help("Align the time axes at the start of each log, or at handshake completion")
+                    .value_parser(["start", "handshake"])
+                    .default_value("start"),
+            )
+            .arg(start_arg())
+            .arg(end_arg())
+            .arg(cwnd_y_axis_max_arg())
+            .arg(stream_y_axis_max_arg())
+            .arg(netlog_filter_arg())
+            .arg(dark_mode_arg())
+    }
+
+    fn from_matches(
+        mut matches: ArgMatches,
+    ) -> std::result::Result<Self, String> {
+        let files = matches
+            .remove_many::<String>("LOG FILES")
+            .unwrap()
+            .collect();
+        let charts_dir =
+            matches.remove_one::<String>("charts_directory").unwrap();
+
+        let alignment = match matches.remove_one::<String>("align").as_deref() {
+            Some("handshake") => CompareAlignment::Handshake,
+
+            _ => CompareAlignment::Start,
+        };
+
+        let start = matches.remove_one::<f64>("start");
+        let end = matches.remove_one::<f64>("end");
+
+        if end.is_some() && end < start {
+            return Err("End time cannot be earlier than start time.".into());
+        }
+
+        let netlog_filter = netlog_filter(&mut matches);
+
+        Ok(Self {
+            files,
+            charts_dir,
+            alignment,
+            start,
+            end,
+            cwnd_y_max: matches.remove_one::<u64>("cwnd_y_axis_max"),
+            stream_y_max: matches.remove_one::<u64>("stream_y_axis_max"),
+            netlog_filter,
+            dark_mode: matches.get_flag("dark_mode"),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct BatchConfig {
+    pub dir: String,
+    pub output_dir: String,
+    pub jobs: usize,
+    pub netlog_filter: HashSet<String>,
+}
+
+impl BatchConfig {
+    fn command() -> Command {
+        Command::new("batch")
+            .about("Parses all qlog and netlog files in a directory and reports aggregate per-connection statistics")
+            .arg(
+                Arg::new("DIRECTORY")
+                    .help("The directory to search for log files, recursively")
+                    .required(true)
+                    .index(1),
+            )
+            .arg(
+                Arg::new("output_directory")
+                    .short('o')
+                    .long("output-directory")
+                    .help("Sets the output directory for the reports")
+                    .default_value("batch-report"),
+            )
+            .arg(
+                Arg::new("jobs")
+                    .short('j')
+                    .long("jobs")
+                    .help("Number of files to parse in parallel. Defaults to the number of CPUs")
+                    .value_parser(clap::value_parser!(usize)),
+            )
+            .arg(netlog_filter_arg())
+    }
+
+    fn from_matches(
+        mut matches: ArgMatches,
+    ) -> std::result::Result<Self, String> {
+        let dir = matches.remove_one::<String>("DIRECTORY").unwrap();
+        let output_dir =
+            matches.remove_one::<String>("output_directory").unwrap();
+
+        let jobs = match matches.remove_one::<usize>("jobs") {
+            Some(0) => return Err("Number of jobs must be at least 1.".into()),
+
+            Some(jobs) => jobs,
+
+            None => std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1),
+        };
+
+        let netlog_filter = netlog_filter(&mut matches);
+
+        Ok(Self {
+            dir,
+            output_dir,
+            jobs,
+            netlog_filter,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct LiveConfig {
+    pub file: Option<String>,
+    pub socket: Option<String>,
+    pub charts_dir: String,
+    pub interval: Duration,
+    pub idle_timeout: Option<Duration>,
+    pub cwnd_y_max: Option<u64>,
+    pub stream_y_max: Option<u64>,
+    pub dark_mode: bool,
+}
+
+impl LiveConfig {
+    fn command() -> Command {
+        Command::new("live")
+            .about("Follows a growing sqlog file, or qlogs streamed to a UNIX socket, and redraws cwnd, RTT and stream progress charts as events arrive")
+            .arg(
+                Arg::new("LOG FILE")
+                    .help("The sqlog file to follow")
+                    .required_unless_present("socket")
+                    .conflicts_with("socket")
+                    .index(1),
+            )
+            .arg(
+                Arg::new("socket")
+                    .long("socket")
+                    .help("Listen on a UNIX socket at this path, reading a trace from each connection"),
+            )
+            .arg(
+                Arg::new("charts_directory")
+                    .long("charts-directory")
+                    .help("Sets the output directory for charts and the index.html page showing them")
+                    .default_value("live-charts"),
+            )
+            .arg(
+                Arg::new("interval")
+                    .long("interval")
+                    .help("Time in ms between chart updates")
+                    .value_parser(clap::value_parser!(u64).range(1..))
+                    .default_value("1000"),
+            )
+            .arg(
+                Arg::new("idle_timeout")
+                    .long("idle-timeout")
+                    .help("Stop following the file once it hasn't grown for this time in ms. By default, follow it until interrupted")
+                    .value_parser(clap::value_parser!(u64)),
+            )
+            .arg(cwnd_y_axis_max_arg())
+            .arg(stream_y_axis_max_arg())
+            .arg(dark_mode_arg())
+    }
+
+    fn from_matches(
+        mut matches: ArgMatches,
+    ) -> std::result::Result<Self, String> {
+        let file = matches.remove_one::<String>("LOG FILE");
+
+        if let Some(file) = &file {
+            if !file.ends_with(".sqlog") {
+                return Err(format!(
+                    "{} is not an uncompressed sqlog file, which is required to follow it",
+                    file
+                ));
+            }
+        }
+
+        let charts_dir =
+            matches.remove_one::<String>("charts_directory").unwrap();
+
+        Ok(Self {
+            file,
+            socket: matches.remove_one::<String>("socket"),
+            charts_dir,
+            interval: Duration::from_millis(
+                matches.remove_one::<u64>("interval").unwrap(),
+            ),
+            idle_timeout: matches
+                .remove_one::<u64>("idle_timeout")
+                .map(Duration::from_millis),
+            cwnd_y_max: matches.remove_one::<u64>("cwnd_y_axis_max"),
+            stream_y_max: matches.remove_one::<u64>("stream_y_axis_max"),
+            dark_mode: matches.get_flag("dark_mode"),
+        })
+    }
+}
+
 #[derive(Debug)]
 pub struct AppConfig {
     pub file: String,
@@ -68,11 +460,16 @@ pub struct AppConfig {
     pub cwnd_y_max: Option<u64>,
 
     pub netlog_filter: HashSet<String>,
-    pub qlog_wirefilter: Option<String>,
+    pub netlog_sessions: HashSet<i64>,
+    pub wirefilter: Option<String>,
 
     pub stats_config: PrintStatsConfig,
     pub ignore_acks: bool,
 
+    pub anomaly_thresholds: AnomalyThresholds,
+
+    pub export_series: Option<SeriesExportFormat>,
+
     pub log_format: SerializationFormat,
 }
 
@@ -85,8 +482,8 @@ impl AppConfig {
         }
     }
 
-    pub fn from_clap() -> std::result::Result<Self, String> {
-        let mut matches = Command::new("qlog-dancer")
+    fn command() -> Command {
+        Command::new("qlog-dancer")
         .version("v0.1.0")
         .about("dances with qlog (and more!)")
         .arg(
@@ -123,42 +520,14 @@ impl AppConfig {
                 .value_parser(["grid", "vert"])
                 .default_value("vert"),
         )
-        .arg(
-            Arg::new("start")
-                .short('s')
-                .help("Relative start time for plots")
-        )
-        .arg(
-            Arg::new("end")
-                .short('e')
-                .help("Relative end time for plots")
-        )
-        .arg(
-            Arg::new("stream_y_axis_max")
-                .long("stream-y-axis_max")
-                .help("Maximum value of Y axis on stream-related charts")
-        )
-        .arg(
-            Arg::new("cwnd_y_axis_max")
-                .long("cwnd-y-axis-max")
-                .help("Maximum value of Y axis on cwnd-related charts")
-        )
-        .arg(
-            Arg::new("netlog_filter")
-                .long("netlog-filter")
-                .help("A comma-seperated list of hostnames to filter in to netlog analysis. By default, all hostname are analysed.")
-        )
-        .arg(
-            Arg::new("qlog_wirefilter")
-                .long("qlog-wirefilter")
-                .help("A Wirefilter expression for Wireshark-like matching of qlog events.")
-        )
-        .arg(
-            Arg::new("dark_mode")
-                .long("dark_mode")
-                .help("Generate outputs in a dark mode style")
-                .action(ArgAction::SetTrue),
-        )
+        .arg(start_arg())
+        .arg(end_arg())
+        .arg(stream_y_axis_max_arg().alias("stream-y-axis_max"))
+        .arg(cwnd_y_axis_max_arg())
+        .arg(netlog_filter_arg())
+        .arg(netlog_session_arg())
+        .arg(wirefilter_arg())
+        .arg(dark_mode_arg())
         .arg(
             Arg::new("print_stats")
                 .long("print-stats")
@@ -196,8 +565,78 @@ impl AppConfig {
                 .help("Tables printed in a CSV-compatible format")
                 .action(ArgAction::SetTrue),
         )
-        .get_matches();
+        .arg(
+            Arg::new("export_series")
+                .long("export-series")
+                .help("Export plot series of each connection as JSON or Vega-Lite specifications")
+                .value_parser(["json", "vega-lite"]),
+        )
+        .arg(
+            Arg::new("stall_threshold")
+                .long("stall-threshold")
+                .help("Minimum duration in ms of flow control stalls reported as anomalies")
+                .value_parser(clap::value_parser!(f64))
+                .default_value("100"),
+        )
+        .arg(
+            Arg::new("idle_threshold")
+                .long("idle-threshold")
+                .help("Minimum duration in ms of idle periods reported as anomalies")
+                .value_parser(clap::value_parser!(f64))
+                .default_value("1000"),
+        )
+        .args_conflicts_with_subcommands(true)
+        .subcommand_negates_reqs(true)
+        .subcommand(MergeConfig::command())
+        .subcommand(CompareConfig::command())
+        .subcommand(BatchConfig::command())
+        .subcommand(LiveConfig::command())
+    }
+
+    /// Returns a configuration that only parses `file`, without producing any
+    /// charts or reports.
+    pub fn with_file(file: &str) -> Self {
+        let filename = std::path::Path::new(file)
+            .file_name()
+            .and_then(|f| f.to_str())
+            .unwrap_or(file);
 
+        Self {
+            file: file.to_string(),
+            filename: filename.to_string(),
+            charts_dir: format!("{filename}-charts"),
+            plot_conn_overview: false,
+            plot_pkt_sent: false,
+            plot_pkt_received: false,
+            plot_conn_flow_control: false,
+            plot_sparks: false,
+            plot_multiplex: false,
+            plot_pending: false,
+            sparks_layout: Default::default(),
+            report_text: false,
+            report_omit_upload: false,
+            report_omit_priorities: false,
+            report_text_csv: false,
+            report_html: false,
+            dark_mode: false,
+            start: None,
+            end: None,
+            stream_y_max: None,
+            cwnd_y_max: None,
+            netlog_filter: HashSet::new(),
+            netlog_sessions: HashSet::new(),
+            wirefilter: None,
+            stats_config: Default::default(),
+            ignore_acks: false,
+            anomaly_thresholds: Default::default(),
+            export_series: None,
+            log_format: SerializationFormat::from_filename(file),
+        }
+    }
+
+    fn from_matches(
+        mut matches: ArgMatches,
+    ) -> std::result::Result<Self, String> {
         let file = matches.remove_one::<String>("LOG FILE").unwrap();
         let filename = std::path::Path::new(&file)
             .file_name()
@@ -228,21 +667,8 @@ impl AppConfig {
             }
         }
 
-        let stream_y_max =
-            matches.remove_one::<i64>("stream_y_axis_max").unwrap_or(-1);
-        let stream_y_max = if stream_y_max == -1 {
-            None
-        } else {
-            Some(stream_y_max as u64)
-        };
-
-        let cwnd_y_max =
-            matches.remove_one::<i64>("cwnd_y_axis_max").unwrap_or(-1);
-        let cwnd_y_max = if cwnd_y_max == -1 {
-            None
-        } else {
-            Some(cwnd_y_max as u64)
-        };
+        let stream_y_max = matches.remove_one::<u64>("stream_y_axis_max");
+        let cwnd_y_max = matches.remove_one::<u64>("cwnd_y_axis_max");
 
         let dark_mode = matches.get_flag("dark_mode");
 
@@ -304,16 +730,26 @@ impl AppConfig {
             }
         };
 
-        let netlog_filter = match matches.remove_one::<String>("netlog_filter") {
+        let netlog_filter = netlog_filter(&mut matches);
+
+        let netlog_sessions = match matches.remove_one::<String>("netlog_session")
+        {
             Some(filter_string) => filter_string
                 .split(',')
-                .map(|v| v.to_string())
-                .collect::<HashSet<String>>(),
+                .map(|v| {
+                    v.trim().parse::<i64>().map_err(|_| {
+                        format!("Invalid netlog session ID \"{v}\".")
+                    })
+                })
+                .collect::<std::result::Result<HashSet<i64>, String>>()?,
 
             None => HashSet::new(),
         };
 
-        let qlog_wirefilter = matches.remove_one::<String>("qlog_wirefilter");
+        let wirefilter = matches.remove_one::<String>("wirefilter");
+        if let Some(filter) = &wirefilter {
+            crate::wirefilter::EventFilter::new(filter)?;
+        }
 
         let report_text = matches.get_flag("report_text");
         let report_omit_upload = matches.get_flag("report_omit_upload");
@@ -364,6 +800,23 @@ impl AppConfig {
 
         let ignore_acks = false;
 
+        let export_series =
+            matches.remove_one::<String>("export_series").map(|format| {
+                match format.as_str() {
+                    "json" => SeriesExportFormat::Json,
+
+                    "vega-lite" => SeriesExportFormat::VegaLite,
+
+                    _ => unreachable!(),
+                }
+            });
+
+        let anomaly_thresholds = AnomalyThresholds {
+            stall: matches.remove_one::<f64>("stall_threshold").unwrap(),
+            idle_gap: matches.remove_one::<f64>("idle_threshold").unwrap(),
+            ..Default::default()
+        };
+
         let log_format = SerializationFormat::from_filename(&file);
 
         let config = Self {
@@ -389,12 +842,61 @@ impl AppConfig {
             stream_y_max,
             cwnd_y_max,
             netlog_filter,
-            qlog_wirefilter,
+            netlog_sessions,
+            wirefilter,
             stats_config,
             ignore_acks,
+            anomaly_thresholds,
+            export_series,
             log_format,
         };
 
         Ok(config)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shared_args() {
+        AppConfig::command().debug_assert();
+
+        let matches = AppConfig::command()
+            .try_get_matches_from([
+                "qlog-dancer",
+                "trace.sqlog",
+                "-s",
+                "1.5",
+                "--stream-y-axis_max",
+                "100",
+                "--netlog-filter",
+                "a.example,b.example",
+            ])
+            .unwrap();
+        let config = AppConfig::from_matches(matches).unwrap();
+
+        assert_eq!(config.start, Some(1.5));
+        assert_eq!(config.stream_y_max, Some(100));
+        assert_eq!(config.netlog_filter.len(), 2);
+
+        let mut matches = AppConfig::command()
+            .try_get_matches_from([
+                "qlog-dancer",
+                "compare",
+                "a.sqlog",
+                "b.sqlog",
+                "--cwnd-y-axis-max",
+                "50",
+                "--netlog-filter",
+                "a.example",
+            ])
+            .unwrap();
+        let (_, matches) = matches.remove_subcommand().unwrap();
+        let config = CompareConfig::from_matches(matches).unwrap();
+
+        assert_eq!(config.cwnd_y_max, Some(50));
+        assert!(config.netlog_filter.contains("a.example"));
+    }
+}
@@ -27,7 +27,6 @@
 use std::collections::BTreeMap;
 use std::collections::BTreeSet;
 use std::collections::HashMap;
-use std::collections::HashSet;
 use std::convert::TryFrom;
 use std::fmt::Debug;
 use std::fmt::Display;
@@ -46,8 +45,12 @@ use tabled::Tabled;
 use crate::request_stub::find_header_value;
 use crate::request_stub::HttpRequestStub;
 use crate::request_stub::NaOption;
+use crate::trackers::MigrationTracker;
+use crate::trackers::ProtocolSelection;
+use crate::trackers::ProtocolSelectionTracker;
 use crate::trackers::StreamBufferTracker;
 use crate::trackers::StreamMaxTracker;
+use crate::wirefilter::EventFilter;
 use crate::LogFileData;
 use crate::PacketType;
 use crate::QlogPointRtt;
@@ -63,7 +66,8 @@ use netlog::http;
 use netlog::quic;
 use netlog::quic::Event::*;
 use netlog::quic::*;
-use netlog::read_netlog_record;
+use netlog::reader::NetlogReader;
+use netlog::reader::SessionProtocol;
 
 pub type ParseResult<T> = Result<T, serde_json::Error>;
 #[derive(Debug, Clone)]
@@ -76,6 +80,14 @@ pub struct PacketInfoStub {
     pub number: u64,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LostPacket {
+    pub time: f64,
+    pub ty: PacketType,
+    pub number: u64,
+    pub mtu_probe: bool,
+}
+
 #[derive(Clone, Debug)]
 pub struct StreamAccess {
     pub offset: u64,
@@ -256,6 +268,12 @@ pub struct Datastore {
 
     pub last_event_time: f64,
 
+    /// Time at which the handshake completed, if known. For qlog this is when
+    /// the HANDSHAKE_DONE frame was sent or received. For netlog it is when
+    /// the HANDSHAKE_DONE frame was received, or approximated by the first
+    /// 1-RTT packet received if that isn't logged.
+    pub handshake_complete_time: Option<f64>,
+
     // There are several packet spaces, so store a map of all packets sent
     // according to packet space. Each space then contains a map of packet
     // header info keyed off the packet number.
@@ -269,6 +287,7 @@ pub struct Datastore {
     pub netlog_ack_received_missing_packet: BTreeMap<PacketType, BTreeSet<u64>>,
 
     pub packet_acked: Vec<QlogPointu64>,
+    pub lost_packets: Vec<LostPacket>,
 
     pub local_cwnd: Vec<QlogPointu64>,
     pub local_bytes_in_flight: Vec<QlogPointu64>,
@@ -277,6 +296,7 @@ pub struct Datastore {
     pub local_delivery_rate: Vec<QlogPointu64>,
     pub local_send_rate: Vec<QlogPointu64>,
     pub local_ack_rate: Vec<QlogPointu64>,
+    pub local_pto_count: Vec<QlogPointu64>,
 
     pub local_min_rtt: Vec<QlogPointRtt>,
     pub local_latest_rtt: Vec<QlogPointRtt>,
@@ -284,6 +304,11 @@ pub struct Datastore {
 
     pub congestion_state_updates: Vec<(f64, u64, String)>,
 
+    /// Connection milestones, such as version negotiation, certificate
+    /// verification, handshake completion and path validation. Only netlog
+    /// logs these as separate events.
+    pub connection_events: Vec<(f64, String)>,
+
     pub received_max_data: Vec<QlogPointu64>,
 
     /// Tracks per-stream max data: full history, current max, and cumulative
@@ -296,6 +321,13 @@ pub struct Datastore {
     /// sum.
     pub sent_stream_max_data_tracker: StreamMaxTracker,
 
+    /// DATA_BLOCKED and STREAM_DATA_BLOCKED limits, keyed by stream ID for the
+    /// latter.
+    pub sent_data_blocked: Vec<QlogPointu64>,
+    pub received_data_blocked: Vec<QlogPointu64>,
+    pub sent_stream_data_blocked: BTreeMap<u64, Vec<QlogPointu64>>,
+    pub received_stream_data_blocked: BTreeMap<u64, Vec<QlogPointu64>>,
+
     /// Tracks stream buffer reads: per-stream history, current max, and running
     /// sum.
     pub stream_buffer_reads_tracker: StreamBufferTracker,
@@ -368,9 +400,19 @@ pub struct Datastore {
     pub max_ack_received_missing_packets_size: usize,
 
     pub quic_session_close: Option<QuicSessionClose>,
+    pub qlog_connection_closed: Option<qlog::events::quic::ConnectionClosed>,
+    pub qlog_packets_lost: u64,
+    pub netlog_packets_lost: u64,
+    pub netlog_packets_retransmitted: u64,
     pub h2_session_close: Option<H2SessionClose>,
 
     pub h2_concurrent_requests: u64,
+
+    /// Lengths of `local_cwnd` and `local_pacing_rate` after [`finalize()`]
+    /// last extended them.
+    ///
+    /// [`finalize()`]: Datastore::finalize
+    pub(crate) finalized_lens: (Option<usize>, Option<usize>),
 }
 
 fn is_bidi(stream_id: u64) -> bool {
@@ -403,6 +445,10 @@ impl Datastore {
             ),
             netlog::Event::Quic(e) =>
                 self.consume_netlog_quic(session_start_time, ev_hdr, e, constants),
+
+            // socket and DNS events describe how sessions were established,
+            // see `ProtocolSelectionTracker`
+            netlog::Event::Socket(_) | netlog::Event::Dns(_) => (),
         }
     }
 
@@ -486,6 +532,10 @@ impl Datastore {
                     number: e.params.packet_number,
                 };
 
+                if packet_type == PacketType::OneRtt {
+                    self.handshake_complete_time.get_or_insert(rel_event_time);
+                }
+
                 self.received_packets_netlog
                     .push((rel_event_time, packet_info.clone()));
 
@@ -603,6 +653,7 @@ impl Datastore {
                 // right now
                 if let Some(req) = self.http_requests.get_mut(&e.params.stream_id)
                 {
+                    req.time_reset.get_or_insert(rel_event_time);
                     req.quic_stream_reset_received = Some(QuicStreamReset {
                         offset: e.params.offset,
                         quic_rst_stream_error: e.params.quic_rst_stream_error,
@@ -619,6 +670,7 @@ impl Datastore {
                 // right now
                 if let Some(req) = self.http_requests.get_mut(&e.params.stream_id)
                 {
+                    req.time_reset.get_or_insert(rel_event_time);
                     req.quic_stream_reset_sent = Some(QuicStreamReset {
                         offset: e.params.offset,
                         quic_rst_stream_error: e.params.quic_rst_stream_error,
@@ -678,8 +730,95 @@ impl Datastore {
                 ));
             },
 
+            QuicSessionPacketLost(e) => {
+                self.netlog_packets_lost += 1;
+
+                // Netlog doesn't log the type of lost packets, so find the
+                // space they were sent in. Packet numbers can overlap between
+                // spaces, in which case 1-RTT wins.
+                let ty = [
+                    PacketType::OneRtt,
+                    PacketType::Handshake,
+                    PacketType::Initial,
+                    PacketType::ZeroRtt,
+                ]
+                .into_iter()
+                .find(|ty| {
+                    self.packet_sent.get(ty).is_some_and(|pkts| {
+                        pkts.contains_key(&e.params.packet_number)
+                    })
+                })
+                .unwrap_or(PacketType::Unknown);
+
+                self.lost_packets.push(LostPacket {
+                    time: rel_event_time,
+                    ty,
+                    number: e.params.packet_number,
+                    mtu_probe: false,
+                });
+            },
+
+            QuicSessionPacketRetransmitted(_) =>
+                self.netlog_packets_retransmitted += 1,
+
+            QuicSessionHandshakeDoneFrameReceived(_) => {
+                self.handshake_complete_time = Some(rel_event_time);
+
+                self.connection_events
+                    .push((rel_event_time, "handshake done".to_string()));
+            },
+
+            QuicSessionCertificateVerified(_) => self
+                .connection_events
+                .push((rel_event_time, "certificate verified".to_string())),
+
+            QuicSessionVersionNegotiated(e) => self
+                .connection_events
+                .push((rel_event_time, format!("version {}", e.params.version))),
+
+            QuicSessionVersionNegotiationPacketReceived(_) =>
+                self.connection_events.push((
+                    rel_event_time,
+                    "version negotiation received".to_string(),
+                )),
+
+            QuicSessionPathChallengeFrameSent(_) => self
+                .connection_events
+                .push((rel_event_time, "path challenge sent".to_string())),
+
+            QuicSessionPathChallengeFrameReceived(_) => self
+                .connection_events
+                .push((rel_event_time, "path challenge received".to_string())),
+
+            QuicSessionPathResponseFrameSent(_) => self
+                .connection_events
+                .push((rel_event_time, "path response sent".to_string())),
+
+            QuicSessionPathResponseFrameReceived(_) => self
+                .connection_events
+                .push((rel_event_time, "path response received".to_string())),
+
+            QuicConnectionMigrationTriggered(e) => self.connection_events.push((
+                rel_event_time,
+                format!("migration triggered: {}", e.params.trigger),
+            )),
+
+            QuicConnectionMigrationSuccess(_) => self
+                .connection_events
+                .push((rel_event_time, "migration succeeded".to_string())),
+
+            QuicConnectionMigrationFailure(e) => self.connection_events.push((
+                rel_event_time,
+                format!("migration failed: {}", e.params.reason),
+            )),
+
             // ignore the other events for now
-            QuicSession(_) | QuicSessionTransportParametersReceived(_) => (),
+            QuicSession(_) |
+            QuicSessionTransportParametersReceived(_) |
+            QuicSessionNewConnectionIdFrameReceived(_) |
+            QuicSessionStreamFrameSent(_) |
+            QuicSessionCryptoFrameSent(_) |
+            QuicSessionCryptoFrameReceived(_) => (),
         }
     }
 
@@ -695,6 +834,7 @@ impl Datastore {
                     self.get_or_insert_http_req(e.params.prioritized_element_id);
                 req.priority_updates
                     .push(e.params.priority_field_value.clone());
+                req.time_priority_updates.push(rel_event_time);
             },
 
             Http3HeadersSent(e) => {
@@ -1050,6 +1190,7 @@ impl Datastore {
                 if let Some(req) =
                     self.http_requests.get_mut(&(e.params.stream_id as u64))
                 {
+                    req.time_reset.get_or_insert(rel_event_time);
                     req.h2_stream_reset_sent = Some(H2StreamReset {
                         error: e.params.error_code.clone(),
                         description: e.params.description.clone(),
@@ -1061,6 +1202,7 @@ impl Datastore {
                 if let Some(req) =
                     self.http_requests.get_mut(&(e.params.stream_id as u64))
                 {
+                    req.time_reset.get_or_insert(rel_event_time);
                     req.h2_stream_reset_receive = Some(H2StreamReset {
                         error: e.params.error_code.clone(),
                         description: "".to_string(),
@@ -1114,6 +1256,26 @@ impl Datastore {
             EventData::QuicCongestionStateUpdated(v) =>
                 self.consume_qlog_congestion_state_updated(v, ev_time),
 
+            EventData::QuicPacketLost(v) => {
+                self.qlog_packets_lost += 1;
+
+                if let Some(header) = &v.header {
+                    if let Some(number) = header.packet_number {
+                        self.lost_packets.push(LostPacket {
+                            time: ev_time,
+                            ty: PacketType::from_qlog_packet_type(
+                                &header.packet_type,
+                            ),
+                            number,
+                            mtu_probe: v.is_mtu_probe_packet.unwrap_or(false),
+                        });
+                    }
+                }
+            },
+
+            EventData::QuicConnectionClosed(v) =>
+                self.qlog_connection_closed = Some(v.clone()),
+
             EventData::Http3FrameCreated(v) => match self.vantage_point {
                 VantagePoint::Client =>
                     self.consume_qlog_h3_frame_created_client(v, ev_time),
@@ -1157,14 +1319,31 @@ impl Datastore {
         ds
     }
 
+    /// Extends the congestion window and pacing rate series to the last event.
+    ///
+    /// This can be called again as more events are added, e.g. by live mode.
+    /// The points added by the previous call are replaced if no other point
+    /// was added since, so that they don't pile up.
     pub fn finalize(&mut self) {
+        let (cwnd_len, pacing_len) = std::mem::take(&mut self.finalized_lens);
+
+        if cwnd_len == Some(self.local_cwnd.len()) {
+            self.local_cwnd.pop();
+        }
+
+        if pacing_len == Some(self.local_pacing_rate.len()) {
+            self.local_pacing_rate.pop();
+        }
+
         if let Some(last) = self.local_cwnd.last().cloned() {
             self.local_cwnd.push((self.last_event_time, last.1));
+            self.finalized_lens.0 = Some(self.local_cwnd.len());
         }
 
         if let Some(last) = self.local_pacing_rate.last().cloned() {
             trace!("pushing last {:?}", last);
             self.local_pacing_rate.push((self.last_event_time, last.1));
+            self.finalized_lens.1 = Some(self.local_pacing_rate.len());
         }
     }
 
@@ -1253,6 +1432,24 @@ impl Datastore {
                         self.received_max_data.push((ev_time, *maximum));
                     },
 
+                    QuicFrame::DataBlocked { limit, .. } => {
+                        self.received_data_blocked.push((ev_time, *limit));
+                    },
+
+                    QuicFrame::StreamDataBlocked {
+                        stream_id, limit, ..
+                    } => {
+                        let s = self
+                            .received_stream_data_blocked
+                            .entry(*stream_id)
+                            .or_default();
+                        s.push((ev_time, *limit));
+                    },
+
+                    QuicFrame::HandshakeDone { .. } => {
+                        self.handshake_complete_time.get_or_insert(ev_time);
+                    },
+
                     QuicFrame::MaxStreamData {
                         stream_id, maximum, ..
                     } => {
@@ -1271,6 +1468,10 @@ impl Datastore {
                             .entry(*stream_id)
                             .or_default();
                         s.push(frame.clone());
+
+                        if let Some(req) = self.http_requests.get_mut(stream_id) {
+                            req.time_reset.get_or_insert(ev_time);
+                        }
                     },
 
                     QuicFrame::Stream {
@@ -1352,6 +1553,10 @@ impl Datastore {
                         self.sent_max_data.push((event_time, *maximum));
                     },
 
+                    QuicFrame::HandshakeDone { .. } => {
+                        self.handshake_complete_time.get_or_insert(event_time);
+                    },
+
                     QuicFrame::MaxStreamData {
                         stream_id, maximum, ..
                     } => {
@@ -1368,6 +1573,10 @@ impl Datastore {
                         let s =
                             self.sent_reset_stream.entry(*stream_id).or_default();
                         s.push(frame.clone());
+
+                        if let Some(req) = self.http_requests.get_mut(stream_id) {
+                            req.time_reset.get_or_insert(event_time);
+                        }
                     },
 
                     QuicFrame::Stream { stream_id, .. } => {
@@ -1390,22 +1599,17 @@ impl Datastore {
                     },
 
                     QuicFrame::DataBlocked { limit, .. } => {
-                        trace!(
-                            "todo DATA_BLOCKED t={} limit={}",
-                            event_time,
-                            limit
-                        );
+                        self.sent_data_blocked.push((event_time, *limit));
                     },
 
                     QuicFrame::StreamDataBlocked {
                         stream_id, limit, ..
                     } => {
-                        trace!(
-                            "todo STREAM_DATA_BLOCKED t={} stream={} limit={}",
-                            event_time,
-                            stream_id,
-                            limit
-                        );
+                        let s = self
+                            .sent_stream_data_blocked
+                            .entry(*stream_id)
+                            .or_default();
+                        s.push((event_time, *limit));
                     },
 
                     _ => (),
@@ -1474,6 +1678,10 @@ impl Datastore {
             self.local_pacing_rate.push((ev_time, pacing_rate));
         }
 
+        if let Some(pto_count) = mu.pto_count {
+            self.local_pto_count.push((ev_time, pto_count as u64));
+        }
+
         // Extract rate metrics from ex_data
         if let Some(rate) =
             mu.ex_data.get("cf_delivery_rate").and_then(|v| v.as_u64())
@@ -1550,6 +1758,7 @@ impl Datastore {
                 let req: &mut HttpRequestStub =
                     self.get_or_insert_http_req(*stream_id);
                 req.priority_updates.push(priority_field_value.clone());
+                req.time_priority_updates.push(ev_time);
             },
 
             // ignore other frames
@@ -1658,6 +1867,7 @@ impl Datastore {
             } => {
                 let req = self.get_or_insert_http_req(*stream_id);
                 req.priority_updates.push(priority_field_value.clone());
+                req.time_priority_updates.push(ev_time);
             },
 
             // ignore other frames
@@ -1665,33 +1875,45 @@ impl Datastore {
         }
     }
 
-    pub fn with_sqlog_reader_events(
-        events: &[qlog::reader::Event], vantage_point: &qlog::VantagePointType,
-        process_acks: bool,
-    ) -> Self {
+    /// Creates an empty Datastore for a sqlog trace, that events are added to
+    /// with [`Datastore::consume_sqlog_reader_event`].
+    pub fn with_vantage_point(vantage_point: &qlog::VantagePointType) -> Self {
         let vp = match vantage_point {
             qlog::VantagePointType::Client => VantagePoint::Client,
             qlog::VantagePointType::Server => VantagePoint::Server,
             _ => panic!("unknown vantage point type"),
         };
 
-        let mut ds = Datastore {
+        Datastore {
             total_sent_stream_frame_count: 0,
             vantage_point: vp,
             ..Default::default()
-        };
+        }
+    }
 
-        for event in events {
-            match event {
-                qlog::reader::Event::Qlog(ev) => {
-                    ds.consume_qlog_event(ev, process_acks);
-                },
+    pub fn consume_sqlog_reader_event(
+        &mut self, event: &qlog::reader::Event, process_acks: bool,
+    ) {
+        match event {
+            qlog::reader::Event::Qlog(ev) => {
+                self.consume_qlog_event(ev, process_acks);
+            },
 
-                qlog::reader::Event::Json(ev) => {
-                    // Just swallow the failure and move on
-                    error!("unhandled Json event {:?}", ev);
-                },
-            }
+            qlog::reader::Event::Json(ev) => {
+                // Just swallow the failure and move on
+                error!("unhandled Json event {:?}", ev);
+            },
+        }
+    }
+
+    pub fn with_sqlog_reader_events(
+        events: &[qlog::reader::Event], vantage_point: &qlog::VantagePointType,
+        process_acks: bool,
+    ) -> Self {
+        let mut ds = Datastore::with_vantage_point(vantage_point);
+
+        for event in events {
+            ds.consume_sqlog_reader_event(event, process_acks);
         }
 
         ds.hydrate_http_requests();
@@ -1747,17 +1969,15 @@ pub struct ReqOverH3 {
 pub type RequestOverH3Map = BTreeMap<i64, Vec<ReqOverH3>>;
 
 pub fn with_netlog_reader<R: std::io::BufRead>(
-    reader: &mut R, hostname_filter: HashSet<String>,
-    constants: &netlog::constants::Constants,
-) -> (Vec<LogFileData>, BTreeMap<i64, NetlogSession>) {
-    // second line in a netlog is always `"events": [` so skip it
-    read_netlog_record(reader);
-
-    let mut sessions: BTreeMap<i64, NetlogSession> = BTreeMap::new();
-    let mut session_events: BTreeMap<
-        i64,
-        Vec<(netlog::EventHeader, netlog::Event)>,
-    > = BTreeMap::new();
+    netlog: &mut NetlogReader<R>, filter: Option<&EventFilter>,
+) -> (
+    Vec<LogFileData>,
+    BTreeMap<i64, NetlogSession>,
+    Vec<ProtocolSelection>,
+) {
+    // Each session's Datastore is built as its events are read, so that
+    // events don't need to be held in memory.
+    let mut datastores: BTreeMap<i64, Datastore> = BTreeMap::new();
 
     let mut h3_session_requests: RequestOverH3Map = BTreeMap::new();
 
@@ -1766,198 +1986,186 @@ pub fn with_netlog_reader<R: std::io::BufRead>(
     let mut request_discovery: RequestDiscoveryMap = BTreeMap::new();
     let mut stream_bind: StreamBindingMap = BTreeMap::new();
 
-    while let Some(event) = read_netlog_record(reader) {
-        let res: Result<netlog::EventHeader, serde_json::Error> =
-            serde_json::from_slice(&event);
-
-        match res {
-            Ok(mut event_hdr) => {
-                event_hdr.populate_strings(constants);
-                event_hdr.time_num = event_hdr.time.parse::<u64>().unwrap();
-
-                // If this is a session creation, store the session, so we can
-                // link events with it. The source ID of these events is the
-                // unique value that we will use to link things together.
-                // This assumes events belonging to a session do not occur
-                // before the session is created.
-                if event_hdr.phase_string == "PHASE_BEGIN" {
-                    match event_hdr.ty_string.as_str() {
-                        "QUIC_SESSION" => {
-                            let ev: QuicSessionEvent =
-                                serde_json::from_slice(&event).unwrap();
-
-                            // QUIC sessions split host and port, which
-                            // interferes with filter expression, so merge them
-                            let host =
-                                format!("{}:{}", ev.params.host, ev.params.port,);
-
-                            sessions.insert(event_hdr.source.id, NetlogSession {
-                                session_id: event_hdr.source.id,
-                                application_proto: ApplicationProto::Http3,
-                                host: host.clone(),
-                                start_time: event_hdr
-                                    .time
-                                    .parse::<u64>()
-                                    .unwrap(),
-                            });
-
-                            let do_insert = hostname_filter.is_empty() ||
-                                hostname_filter.contains(&host);
-
-                            if do_insert {
-                                session_events
-                                    .insert(event_hdr.source.id, Vec::new());
-
-                                h3_session_requests
-                                    .insert(event_hdr.source.id, Vec::new());
-                            }
-                        },
-
-                        "HTTP2_SESSION" => {
-                            let ev: Http2SessionEvent =
-                                serde_json::from_slice(&event).unwrap();
-
-                            sessions.insert(event_hdr.source.id, NetlogSession {
-                                session_id: event_hdr.source.id,
-                                application_proto: ApplicationProto::Http2,
-                                host: ev.params.host.clone(),
-                                start_time: event_hdr
-                                    .time
-                                    .parse::<u64>()
-                                    .unwrap(),
-                            });
-
-                            let do_insert = hostname_filter.is_empty() ||
-                                hostname_filter.contains(&ev.params.host);
-
-                            if do_insert {
-                                session_events
-                                    .insert(event_hdr.source.id, Vec::new());
-                            }
-                        },
+    let mut protocol_selection = ProtocolSelectionTracker::new();
+    let mut migrations = MigrationTracker::new();
+
+    while let Some((event_hdr, event)) = netlog.next() {
+        // The reader only yields events of sessions that match the filter, so
+        // a known session seen for the first time is one to analyze. The
+        // source ID of session events is the unique value that we will use to
+        // link things together. This assumes events belonging to a session do
+        // not occur before the session is created.
+        if !datastores.contains_key(&event_hdr.source.id) {
+            if let Some(session) = netlog.sessions().get(&event_hdr.source.id) {
+                let application_proto = match session.protocol {
+                    SessionProtocol::Quic => ApplicationProto::Http3,
+
+                    SessionProtocol::Http2 => ApplicationProto::Http2,
+                };
 
-                        "CORS_REQUEST" => {
-                            // Seems to be the earliest netlog event related to
-                            // any request.
-                            request_discovery.insert(
-                                event_hdr.source.id,
-                                RequestDiscovery {
-                                    time: event_hdr.time_num,
-                                    stream_job_id: None,
-                                },
-                            );
-                        },
+                datastores.insert(session.id, Datastore {
+                    session_id: Some(session.id),
+                    application_proto,
+                    host: Some(session.host.clone()),
+                    total_sent_stream_frame_count: 0,
+                    ..Default::default()
+                });
 
-                        _ => (),
-                    }
+                if session.protocol == SessionProtocol::Quic {
+                    h3_session_requests.insert(session.id, Vec::new());
                 }
+            }
+        }
 
-                if event_hdr.ty_string.starts_with("HTTP_") {
-                    let event = netlog::http::parse_event(&event_hdr, &event);
-
-                    // This will eventually deal with other events, and having
-                    // to refactor back and forth is a waste.
-                    #[allow(clippy::single_match)]
-                    match event {
-                        Some(netlog::Event::Http(e)) => {
-                            match e {
-                                http::Event::HttpStreamJobBoundToRequest(v)  => {
-                                    let request_discovery_id = v.params.source_dependency.id;
-                                    if let Some(rd) = request_discovery.get_mut(&request_discovery_id) {
-                                        stream_bind.insert(event_hdr.source.id, StreamBind{time: event_hdr.time_num, request_discovery_id, request_discovery_time: rd.time});
-                                        rd.stream_job_id = Some(event_hdr.source.id);
-                                    }
-                                },
+        protocol_selection.add_event(&event_hdr, &event);
+
+        if let netlog::Event::Http(e) = &event {
+            match e {
+                http::Event::CorsRequest(_) => {
+                    // Seems to be the earliest netlog event related to any
+                    // request.
+                    request_discovery.insert(
+                        event_hdr.source.id,
+                        RequestDiscovery {
+                            time: event_hdr.time_num,
+                            stream_job_id: None,
+                        },
+                    );
+                },
 
-                                http::Event::HttpStreamRequestBoundToQuicSession(v) => {
-                                    let request_discovery_id = event_hdr.source.id;
-                                    if let Some(rd) = request_discovery.get(&request_discovery_id) {
+                http::Event::HttpStreamJobBoundToRequest(v) => {
+                    let request_discovery_id = v.params.source_dependency.id;
+                    if let Some(rd) =
+                        request_discovery.get_mut(&request_discovery_id)
+                    {
+                        stream_bind.insert(event_hdr.source.id, StreamBind {
+                            time: event_hdr.time_num,
+                            request_discovery_id,
+                            request_discovery_time: rd.time,
+                        });
+                        rd.stream_job_id = Some(event_hdr.source.id);
+                    }
+                },
 
-                                        if let Some(session_requests) = h3_session_requests.get_mut(&v.params.source_dependency.id) {
-                                            let req = ReqOverH3{id: event_hdr.source.id, discover_time: rd.time, session_id: Some(v.params.source_dependency.id), quic_stream_id: None };
-                                            session_requests.push(req);
+                http::Event::HttpStreamRequestBoundToQuicSession(v) => {
+                    let request_discovery_id = event_hdr.source.id;
+                    if let Some(rd) = request_discovery.get(&request_discovery_id)
+                    {
+                        if let Some(session_requests) = h3_session_requests
+                            .get_mut(&v.params.source_dependency.id)
+                        {
+                            let req = ReqOverH3 {
+                                id: event_hdr.source.id,
+                                discover_time: rd.time,
+                                session_id: Some(v.params.source_dependency.id),
+                                quic_stream_id: None,
+                            };
+                            session_requests.push(req);
+
+                            // populate reverse mapping, each unique request ID
+                            // has a session ID
+                            req_id_to_session_id.insert(
+                                event_hdr.source.id,
+                                v.params.source_dependency.id,
+                            );
+                        }
+                    }
+                },
 
-                                            // populate reverse mapping, each unique request ID has a session ID
-                                            req_id_to_session_id.insert(event_hdr.source.id, v.params.source_dependency.id);
-                                        }
-                                    }
+                http::Event::HttpTransactionQuicSendRequestHeaders(v) => {
+                    let req_id = event_hdr.source.id;
+                    if let Some(session_id) = req_id_to_session_id.get(&req_id) {
+                        if let Some(reqs) =
+                            h3_session_requests.get_mut(session_id)
+                        {
+                            // todo replace vec with map?
+                            for req in reqs {
+                                if req.id == req_id {
+                                    req.quic_stream_id =
+                                        Some(v.params.quic_stream_id);
+                                    break;
                                 }
+                            }
+                        }
+                    }
+                },
 
-                                http::Event::HttpTransactionQuicSendRequestHeaders(v) => {
-                                    let req_id = event_hdr.source.id;
-                                    if let Some(session_id) = req_id_to_session_id.get(&req_id) {
-                                        if let Some(reqs) = h3_session_requests.get_mut(session_id) {
-                                            // todo replace vec with map?
-                                            for req in reqs {
-                                                if req.id == req_id {
-                                                    req.quic_stream_id = Some(v.params.quic_stream_id);
-                                                    break;
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
+                _ => (),
+            }
+        }
 
-                                _ => (),
-                            }
-                        },
+        // Connection migrations are logged against a source of their own, so
+        // their events are consumed by the session they are linked to.
+        let Some((session_id, events)) = migrations.link(event_hdr, event) else {
+            continue;
+        };
 
-                        // ignore other events
-                        _ => (),
-                    }
-                }
+        // Request discovery and stream binding events precede the session
+        // events that refer to them, so they are already known here.
+        if let Some(ds) = datastores.get_mut(&session_id) {
+            let start_time = netlog.sessions()[&session_id].start_time;
 
-                if let Some(session) =
-                    session_events.get_mut(&event_hdr.source.id)
-                {
-                    if let Some(ev) = netlog::parse_event(&event_hdr, &event) {
-                        session.push((event_hdr, ev));
-                    }
+            for (event_hdr, event) in events {
+                if filter.is_some_and(|filter| {
+                    !filter.matches_netlog(&event_hdr, &event, start_time)
+                }) {
+                    continue;
                 }
-            },
-
-            Err(e) => {
-                error!("Error deserializing: {}", e);
-                error!("input value {}", String::from_utf8_lossy(&event));
 
-                // Just swallow the failure and move on
-            },
+                ds.consume_netlog_event(
+                    start_time,
+                    &event_hdr,
+                    &event,
+                    netlog.constants(),
+                    &stream_bind,
+                    h3_session_requests.get(&session_id),
+                );
+            }
         }
     }
 
-    println!("All sessions in this netlog = {:#?}", sessions);
+    if netlog.is_truncated() {
+        println!("netlog is truncated, using events up to the last complete one");
+    }
 
-    let mut log_file_data = Vec::new();
+    let sessions: BTreeMap<i64, NetlogSession> = netlog
+        .sessions()
+        .values()
+        .map(|s| {
+            let application_proto = match s.protocol {
+                SessionProtocol::Quic => ApplicationProto::Http3,
 
-    for (session_id, details) in &sessions {
-        if let Some(events) = session_events.get(session_id) {
-            let mut ds = Datastore {
-                session_id: Some(*session_id),
-                application_proto: details.application_proto,
-                host: Some(details.host.clone()),
-                total_sent_stream_frame_count: 0,
-                ..Default::default()
+                SessionProtocol::Http2 => ApplicationProto::Http2,
             };
 
-            for (ev_hdr, event) in events {
-                ds.consume_netlog_event(
-                    details.start_time,
-                    ev_hdr,
-                    event,
-                    constants,
-                    &stream_bind,
-                    h3_session_requests.get(session_id),
-                );
-            }
+            (s.id, NetlogSession {
+                session_id: s.id,
+                application_proto,
+                host: s.host.clone(),
+                start_time: s.start_time,
+            })
+        })
+        .collect();
+
+    println!("All sessions in this netlog = {:#?}", sessions);
+
+    let constants = netlog.constants();
 
+    let log_file_data = datastores
+        .into_values()
+        .map(|mut ds| {
             ds.hydrate_http_requests();
             ds.finalize();
-            log_file_data.push(LogFileData {
+
+            LogFileData {
                 datastore: ds,
                 raw: Netlog,
-            });
-        }
-    }
+            }
+        })
+        .collect();
+
+    let protocol_selections =
+        protocol_selection.selections(&constants.net_error_id_keyed);
 
-    (log_file_data, sessions)
+    (log_file_data, sessions, protocol_selections)
 }
@@ -0,0 +1,606 @@
+// Copyright (C) 2025, Cloudflare, Inc.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are
+// met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//
+//     * Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in the
+//       documentation and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS
+// IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO,
+// THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR
+// PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Export of plot series as JSON or Vega-Lite specifications, for use in
+//! notebooks, dashboards or other plotting tools.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use serde::Serialize;
+use serde_json::json;
+
+use crate::config::AppConfig;
+use crate::create_file_recursive;
+use crate::datastore::Datastore;
+use crate::request_stub::NaOption;
+use crate::seriesstore::SeriesStore;
+use crate::LogFileParseResult;
+
+const VEGA_LITE_SCHEMA: &str = "https://vega.github.io/schema/vega-lite/v5.json";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SeriesExportFormat {
+    /// Plain series, as lists of points.
+    Json,
+
+    /// A Vega-Lite specification embedding the series.
+    VegaLite,
+}
+
+impl SeriesExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SeriesExportFormat::Json => "json",
+            SeriesExportFormat::VegaLite => "vl.json",
+        }
+    }
+}
+
+/// The kind of values in a series. Series of the same group share axes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SeriesGroup {
+    Congestion,
+    Rtt,
+    Rate,
+    PacketNumber,
+    PacketCount,
+    PacketDelta,
+    FlowControl,
+    StreamBuffer,
+    StreamFrames,
+    H2Window,
+    ConnectionEvents,
+}
+
+impl SeriesGroup {
+    pub fn title(&self) -> &'static str {
+        match self {
+            SeriesGroup::Congestion => "Congestion control",
+            SeriesGroup::Rtt => "RTT",
+            SeriesGroup::Rate => "Pacing, delivery, send and ack rates",
+            SeriesGroup::PacketNumber => "Packets sent, lost and received",
+            SeriesGroup::PacketCount => "Packet counts",
+            SeriesGroup::PacketDelta => "Packet created to sent delta",
+            SeriesGroup::FlowControl => "Flow control",
+            SeriesGroup::StreamBuffer => "Stream buffers",
+            SeriesGroup::StreamFrames => "Stream and DATA frames",
+            SeriesGroup::H2Window => "HTTP/2 send window",
+            SeriesGroup::ConnectionEvents => "Migration, version and TLS events",
+        }
+    }
+
+    pub fn x_title(&self) -> &'static str {
+        match self {
+            SeriesGroup::PacketDelta => "Packet Number",
+            _ => "Relative time (ms)",
+        }
+    }
+
+    pub fn y_title(&self) -> &'static str {
+        match self {
+            SeriesGroup::Congestion => "Data (bytes)",
+            SeriesGroup::Rtt => "RTT (ms)",
+            SeriesGroup::Rate => "Rate (bytes/sec)",
+            SeriesGroup::PacketNumber => "Packet Number",
+            SeriesGroup::PacketCount => "Packet Count",
+            SeriesGroup::PacketDelta => "Delta time (ms)",
+            SeriesGroup::FlowControl => "Bytes",
+            SeriesGroup::StreamBuffer => "Bytes",
+            SeriesGroup::StreamFrames => "Offset (bytes)",
+            SeriesGroup::H2Window => "Window (bytes)",
+            SeriesGroup::ConnectionEvents => "Event",
+        }
+    }
+
+    /// Packet numbers are drawn as points, events as rules and everything
+    /// else as lines.
+    fn mark(&self) -> &'static str {
+        match self {
+            SeriesGroup::PacketNumber | SeriesGroup::PacketDelta => "point",
+            SeriesGroup::ConnectionEvents => "rule",
+            _ => "line",
+        }
+    }
+}
+
+/// A single series of a [`SeriesStore`].
+#[derive(Debug, Serialize)]
+pub struct Series {
+    pub name: &'static str,
+    pub group: SeriesGroup,
+
+    /// The stream of per-stream series.
+    pub stream_id: Option<u64>,
+
+    pub points: Vec<(f64, f64)>,
+
+    /// The label of each point, for series of event markers.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+}
+
+trait SeriesValue: Copy {
+    fn to_f64(self) -> f64;
+}
+
+impl SeriesValue for f64 {
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
+
+impl SeriesValue for f32 {
+    fn to_f64(self) -> f64 {
+        f64::from(self)
+    }
+}
+
+impl SeriesValue for u64 {
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl SeriesValue for i32 {
+    fn to_f64(self) -> f64 {
+        f64::from(self)
+    }
+}
+
+#[derive(Default)]
+struct SeriesList(Vec<Series>);
+
+impl SeriesList {
+    fn add<X: SeriesValue, Y: SeriesValue>(
+        &mut self, name: &'static str, group: SeriesGroup, points: &[(X, Y)],
+    ) {
+        self.add_stream(name, group, None, points);
+    }
+
+    fn add_stream<X: SeriesValue, Y: SeriesValue>(
+        &mut self, name: &'static str, group: SeriesGroup,
+        stream_id: Option<u64>, points: &[(X, Y)],
+    ) {
+        if points.is_empty() {
+            return;
+        }
+
+        self.0.push(Series {
+            name,
+            group,
+            stream_id,
+            points: points
+                .iter()
+                .map(|(x, y)| (x.to_f64(), y.to_f64()))
+                .collect(),
+            labels: Vec::new(),
+        });
+    }
+
+    /// Adds a series of labelled event markers. Markers only have a time, so
+    /// they are numbered in order.
+    fn add_markers(
+        &mut self, name: &'static str, group: SeriesGroup,
+        markers: &[(f64, String)],
+    ) {
+        if markers.is_empty() {
+            return;
+        }
+
+        self.0.push(Series {
+            name,
+            group,
+            stream_id: None,
+            points: markers
+                .iter()
+                .zip(1..)
+                .map(|((t, _), count)| (*t, f64::from(count)))
+                .collect(),
+            labels: markers.iter().map(|(_, label)| label.clone()).collect(),
+        });
+    }
+
+    /// Adds a series per stream. Keys that aren't valid stream IDs, such as
+    /// the -1 netlog uses for the connection, have no stream ID.
+    fn add_streams<K, X, Y>(
+        &mut self, name: &'static str, group: SeriesGroup,
+        streams: &BTreeMap<K, Vec<(X, Y)>>,
+    ) where
+        K: Copy + TryInto<u64>,
+        X: SeriesValue,
+        Y: SeriesValue,
+    {
+        for (stream_id, points) in streams {
+            self.add_stream(name, group, (*stream_id).try_into().ok(), points);
+        }
+    }
+}
+
+/// Returns all non-empty series of `ss`.
+pub fn series(ss: &SeriesStore) -> Vec<Series> {
+    use SeriesGroup::*;
+
+    let mut list = SeriesList::default();
+
+    list.add("cwnd", Congestion, &ss.local_cwnd);
+    list.add("bytes_in_flight", Congestion, &ss.local_bytes_in_flight);
+    list.add("ssthresh", Congestion, &ss.local_ssthresh);
+
+    list.add("min_rtt", Rtt, &ss.local_min_rtt);
+    list.add("latest_rtt", Rtt, &ss.local_latest_rtt);
+    list.add("smoothed_rtt", Rtt, &ss.local_smoothed_rtt);
+
+    list.add("pacing_rate", Rate, &ss.local_pacing_rate);
+    list.add("delivery_rate", Rate, &ss.local_delivery_rate);
+    list.add("send_rate", Rate, &ss.local_send_rate);
+    list.add("ack_rate", Rate, &ss.local_ack_rate);
+
+    list.add("packet_created", PacketNumber, &ss.onertt_packet_created);
+    list.add("packet_sent", PacketNumber, &ss.onertt_packet_sent);
+    list.add("packet_lost", PacketNumber, &ss.onertt_packet_lost_hacky);
+    list.add("packet_lost_detected", PacketNumber, &ss.onertt_packet_lost);
+    list.add("packet_received", PacketNumber, &ss.onertt_packet_received);
+
+    list.add(
+        "packets_sent",
+        PacketCount,
+        &ss.onertt_packet_sent_aggregate_count,
+    );
+    list.add(
+        "packets_lost",
+        PacketCount,
+        &ss.onertt_packet_lost_aggregate_count,
+    );
+    list.add(
+        "packets_delivered",
+        PacketCount,
+        &ss.onertt_packet_delivered_aggregate_count,
+    );
+
+    // Missing packets are only timestamps, count them instead.
+    let missing: Vec<(f64, u64)> = ss
+        .netlog_missing_packets
+        .iter()
+        .zip(1..)
+        .map(|(t, count)| (*t, count))
+        .collect();
+    list.add("packets_missing", PacketCount, &missing);
+
+    list.add(
+        "packet_created_sent_delta",
+        PacketDelta,
+        &ss.onertt_packet_created_sent_delta,
+    );
+
+    list.add("sent_max_data", FlowControl, &ss.sent_max_data);
+    list.add("received_max_data", FlowControl, &ss.received_max_data);
+    list.add_streams(
+        "sent_stream_max_data",
+        FlowControl,
+        &ss.sent_stream_max_data,
+    );
+    list.add_streams(
+        "received_stream_max_data",
+        FlowControl,
+        &ss.received_stream_max_data,
+    );
+    list.add(
+        "sum_sent_stream_max_data",
+        FlowControl,
+        &ss.sum_sent_stream_max_data,
+    );
+    list.add(
+        "sum_received_stream_max_data",
+        FlowControl,
+        &ss.sum_received_stream_max_data,
+    );
+    list.add(
+        "h2_received_connection_cumulative",
+        FlowControl,
+        &ss.netlog_h2_stream_received_connection_cumulative,
+    );
+    list.add(
+        "quic_received_connection_cumulative",
+        FlowControl,
+        &ss.netlog_quic_stream_received_connection_cumulative,
+    );
+    list.add_streams(
+        "quic_client_side_window_updates",
+        FlowControl,
+        &ss.netlog_quic_client_side_window_updates,
+    );
+
+    list.add_streams(
+        "stream_buffer_reads",
+        StreamBuffer,
+        &ss.stream_buffer_reads,
+    );
+    list.add(
+        "sum_stream_buffer_reads",
+        StreamBuffer,
+        &ss.sum_stream_buffer_reads,
+    );
+    list.add_streams(
+        "stream_buffer_writes",
+        StreamBuffer,
+        &ss.stream_buffer_writes,
+    );
+    list.add(
+        "sum_stream_buffer_writes",
+        StreamBuffer,
+        &ss.sum_stream_buffer_writes,
+    );
+    list.add_streams(
+        "stream_buffer_dropped",
+        StreamBuffer,
+        &ss.stream_buffer_dropped,
+    );
+    list.add(
+        "sum_stream_buffer_dropped",
+        StreamBuffer,
+        &ss.sum_stream_buffer_dropped,
+    );
+
+    list.add_streams(
+        "sent_stream_frames",
+        StreamFrames,
+        &ss.sent_stream_frames_series,
+    );
+    list.add_streams(
+        "received_stream_frames",
+        StreamFrames,
+        &ss.received_stream_frames_series,
+    );
+    list.add_streams(
+        "sent_data_frames",
+        StreamFrames,
+        &ss.sent_data_frames_series,
+    );
+    list.add_streams(
+        "received_data_frames",
+        StreamFrames,
+        &ss.received_data_frames_series,
+    );
+
+    list.add_streams(
+        "h2_send_window_balanced",
+        H2Window,
+        &ss.h2_send_window_series_balanced,
+    );
+    list.add_streams(
+        "h2_send_window_absolute",
+        H2Window,
+        &ss.h2_send_window_series_absolute,
+    );
+
+    list.add_markers(
+        "connection_events",
+        ConnectionEvents,
+        &ss.connection_events,
+    );
+
+    list.0
+}
+
+/// Returns a title identifying the connection of `ds`.
+pub fn title(ds: &Datastore) -> String {
+    format!(
+        "session ID: {}, app proto: {:?}, host: {}",
+        NaOption::new(ds.session_id),
+        ds.application_proto,
+        ds.host.as_deref().unwrap_or("n/a")
+    )
+}
+
+/// Returns all series of `ss` as JSON.
+pub fn series_json(title: &str, ss: &SeriesStore) -> serde_json::Value {
+    json!({
+        "title": title,
+        "series": series(ss),
+    })
+}
+
+/// Returns a Vega-Lite specification with a chart per [`SeriesGroup`].
+///
+/// Each chart can be zoomed with the mouse wheel and panned by dragging.
+pub fn vega_lite(title: &str, ss: &SeriesStore) -> serde_json::Value {
+    let mut groups: BTreeMap<SeriesGroup, Vec<serde_json::Value>> =
+        BTreeMap::new();
+
+    for series in series(ss) {
+        let values = groups.entry(series.group).or_default();
+
+        values.extend(series.points.iter().enumerate().map(|(i, (x, y))| {
+            let mut value = json!({
+                "series": series.name,
+                "stream_id": series.stream_id,
+                "x": x,
+                "y": y,
+            });
+
+            if let Some(label) = series.labels.get(i) {
+                value["label"] = label.as_str().into();
+            }
+
+            value
+        }));
+    }
+
+    let charts: Vec<_> = groups
+        .into_iter()
+        .map(|(group, values)| {
+            let mut chart = json!({
+                "title": group.title(),
+                "width": 1200,
+                "height": 300,
+                "data": { "values": values },
+                "params": [{
+                    "name": "zoom",
+                    "select": "interval",
+                    "bind": "scales",
+                }],
+                "mark": { "type": group.mark(), "tooltip": true },
+                "encoding": {
+                    "x": {
+                        "field": "x",
+                        "type": "quantitative",
+                        "title": group.x_title(),
+                    },
+                    "y": {
+                        "field": "y",
+                        "type": "quantitative",
+                        "title": group.y_title(),
+                    },
+                    "color": { "field": "series", "type": "nominal" },
+                    "detail": { "field": "stream_id", "type": "nominal" },
+                },
+            });
+
+            // Event markers only have a time, so they span the whole chart and
+            // show their label on hover.
+            if group == SeriesGroup::ConnectionEvents {
+                let encoding = &mut chart["encoding"];
+                encoding.as_object_mut().unwrap().remove("y");
+                encoding["tooltip"] = json!([
+                    { "field": "x", "type": "quantitative" },
+                    { "field": "label", "type": "nominal" },
+                ]);
+            }
+
+            chart
+        })
+        .collect();
+
+    json!({
+        "$schema": VEGA_LITE_SCHEMA,
+        "title": title,
+        "vconcat": charts,
+    })
+}
+
+/// Writes the series of each connection in `log_file` to
+/// `{filename}-series/`.
+pub fn export_series(
+    log_file: &LogFileParseResult, config: &AppConfig, format: SeriesExportFormat,
+) -> std::io::Result<()> {
+    for (i, data) in log_file.data.iter().enumerate() {
+        let ds = &data.datastore;
+        let ss = SeriesStore::from_datastore(ds);
+        let title = title(ds);
+
+        let value = match format {
+            SeriesExportFormat::Json => series_json(&title, &ss),
+            SeriesExportFormat::VegaLite => vega_lite(&title, &ss),
+        };
+
+        let connection = match ds.session_id {
+            Some(id) => format!("session{}", id),
+            None => format!("connection{}", i),
+        };
+
+        let filename = format!(
+            "{}-series/{}.{}",
+            config.filename,
+            connection,
+            format.extension()
+        );
+
+        let mut file = create_file_recursive(&filename)?;
+        serde_json::to_writer(&mut file, &value)?;
+        file.flush()?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn series_and_spec() {
+        let mut ss = SeriesStore {
+            local_cwnd: vec![(0.0, 12000), (10.0, 24000)],
+            local_latest_rtt: vec![(5.0, 20.5)],
+            ..Default::default()
+        };
+        ss.sent_stream_frames_series.insert(4, vec![(1.0, 100)]);
+        ss.netlog_quic_client_side_window_updates
+            .insert(-1, vec![(2.0, 1000)]);
+
+        let series = series(&ss);
+        let names: Vec<_> = series
+            .iter()
+            .map(|s| (s.name, s.group, s.stream_id))
+            .collect();
+
+        assert_eq!(names, [
+            ("cwnd", SeriesGroup::Congestion, None),
+            ("latest_rtt", SeriesGroup::Rtt, None),
+            (
+                "quic_client_side_window_updates",
+                SeriesGroup::FlowControl,
+                None
+            ),
+            ("sent_stream_frames", SeriesGroup::StreamFrames, Some(4)),
+        ]);
+        assert_eq!(series[0].points, [(0.0, 12000.0), (10.0, 24000.0)]);
+
+        let spec = vega_lite("test", &ss);
+        let charts = spec["vconcat"].as_array().unwrap();
+        assert_eq!(charts.len(), 4);
+        assert_eq!(charts[0]["title"], "Congestion control");
+        assert_eq!(charts[0]["data"]["values"].as_array().unwrap().len(), 2);
+        assert_eq!(charts[1]["data"]["values"][0]["y"], 20.5);
+        assert_eq!(charts[3]["data"]["values"][0]["stream_id"], 4);
+    }
+
+    #[test]
+    fn connection_events() {
+        let ss = SeriesStore {
+            connection_events: vec![
+                (3.0, "version RFCv1".to_string()),
+                (7.5, "migration succeeded".to_string()),
+            ],
+            ..Default::default()
+        };
+
+        let series = series(&ss);
+        assert_eq!(series.len(), 1);
+        assert_eq!(series[0].name, "connection_events");
+        assert_eq!(series[0].group, SeriesGroup::ConnectionEvents);
+        assert_eq!(series[0].points, [(3.0, 1.0), (7.5, 2.0)]);
+        assert_eq!(series[0].labels, ["version RFCv1", "migration succeeded"]);
+
+        let spec = vega_lite("test", &ss);
+        let chart = &spec["vconcat"][0];
+        assert_eq!(chart["mark"]["type"], "rule");
+        assert!(chart["encoding"].get("y").is_none());
+        assert_eq!(chart["data"]["values"][1]["label"], "migration succeeded");
+    }
+}
@@ -38,12 +38,18 @@ use datastore::NetlogSession;
 use log::debug;
 use log::error;
 
+use netlog::reader::NetlogFilter;
+use netlog::reader::NetlogReader;
+
 use qlog::reader::QlogSeqReader;
 use qlog::Qlog;
 
 use serde::ser::Serialize;
 
+use crate::trackers::ProtocolSelection;
+use crate::wirefilter::filter_qlog_events;
 use crate::wirefilter::filter_sqlog_events;
+use crate::wirefilter::EventFilter;
 
 pub type QlogPointu64 = (f64, u64);
 pub type QlogPointRtt = (f64, f32);
@@ -169,6 +175,7 @@ pub struct LogFileDetails {
     pub serialization_format: String,
     pub qlog_vantage_point_type: VantagePointTypeShim,
     pub sessions: BTreeMap<i64, NetlogSession>,
+    pub protocol_selections: Vec<ProtocolSelection>,
 }
 
 pub enum RawLogEvents {
@@ -190,11 +197,17 @@ pub struct LogFileParseResult {
 pub fn parse_log_file(
     config: &AppConfig,
 ) -> Result<LogFileParseResult, Box<dyn Error>> {
+    let wirefilter = config
+        .wirefilter
+        .as_deref()
+        .map(EventFilter::new)
+        .transpose()?;
+
     match config.log_format {
         SerializationFormat::QlogJson => {
             println!("parsing qlog as JSON...");
             let mark = std::time::Instant::now();
-            let qlog = read_qlog_from_file(config.file.clone())?;
+            let mut qlog = read_qlog_from_file(config.file.clone())?;
             let vp = qlog.traces[0].vantage_point.clone().unwrap_or_default().ty;
 
             let details = LogFileDetails {
@@ -202,9 +215,15 @@ pub fn parse_log_file(
                 serialization_format: qlog.serialization_format.clone(),
                 qlog_vantage_point_type: VantagePointTypeShim { inner: vp },
                 sessions: BTreeMap::new(),
+                protocol_selections: vec![],
             };
             debug!("\tcomplete in {:?}", std::time::Instant::now() - mark);
 
+            if let Some(filter) = &wirefilter {
+                let events = std::mem::take(&mut qlog.traces[0].events);
+                qlog.traces[0].events = filter_qlog_events(events, filter);
+            }
+
             println!("populating datastore...");
             let mark = std::time::Instant::now();
             // TODO: support more than one trace in a file
@@ -237,6 +256,12 @@ pub fn parse_log_file(
             let mark = std::time::Instant::now();
             let events: Vec<qlog::reader::Event> =
                 qlog_reader.into_iter().collect();
+            let events = if let Some(filter) = &wirefilter {
+                filter_sqlog_events(events, filter)
+            } else {
+                events
+            };
+
             let datastore: Datastore = Datastore::with_sqlog_reader_events(
                 &events,
                 &details.qlog_vantage_point_type.inner,
@@ -244,12 +269,6 @@ pub fn parse_log_file(
             );
             debug!("\tcomplete in {:?}", std::time::Instant::now() - mark);
 
-            let events = if let Some(filter) = &config.qlog_wirefilter {
-                filter_sqlog_events(events, filter)
-            } else {
-                events
-            };
-
             let raw = RawLogEvents::QlogJsonSeq { events };
 
             Ok(LogFileParseResult {
@@ -263,31 +282,35 @@ pub fn parse_log_file(
             let mark = std::time::Instant::now();
 
             let file = std::fs::File::open(config.file.clone())?;
-            let mut reader = BufReader::new(file);
+            let filter = NetlogFilter {
+                hostnames: config.netlog_filter.clone(),
+                session_ids: config.netlog_sessions.clone(),
+            };
 
-            let constants = netlog_with_reader(&mut reader).unwrap();
+            let mut netlog = NetlogReader::new(BufReader::new(file), filter)?;
 
-            // trace!("{:?}", constants);
             debug!("\tcomplete in {:?}", std::time::Instant::now() - mark);
 
             println!("parsing file ...");
             println!("Filtering on hostnames: {:#?}", config.netlog_filter);
+            println!("Filtering on sessions: {:#?}", config.netlog_sessions);
             let mark = std::time::Instant::now();
 
-            let (data, sessions) = datastore::with_netlog_reader(
-                &mut reader,
-                config.netlog_filter.clone(),
-                &constants,
-            );
+            let (data, sessions, protocol_selections) =
+                datastore::with_netlog_reader(
+                    &mut netlog,
+                    wirefilter.as_ref(),
+                );
             debug!("\tcomplete in {:?}", std::time::Instant::now() - mark);
 
             let details = LogFileDetails {
-                file_schema: constants.log_format_version.to_string(),
+                file_schema: netlog.constants().log_format_version.to_string(),
                 serialization_format: "Chrome netlog".to_string(),
                 qlog_vantage_point_type: VantagePointTypeShim {
                     inner: qlog::VantagePointType::Client,
                 },
                 sessions,
+                protocol_selections,
             };
 
             Ok(LogFileParseResult { details, data })
@@ -306,7 +329,8 @@ pub fn read_qlog_from_file<P: AsRef<Path>>(
     let file = File::open(path)?;
     let reader = BufReader::new(file);
 
-    let qlog = serde_json::from_reader(reader)?;
+    let value = serde_json::from_reader(reader)?;
+    let qlog = qlog::version::qlog_from_value(value)?;
 
     Ok(qlog)
 }
@@ -331,47 +355,12 @@ pub fn qlog_seq_reader(
         serialization_format: qlog_reader.qlog.serialization_format.clone(),
         qlog_vantage_point_type: VantagePointTypeShim { inner: vp },
         sessions: BTreeMap::new(),
+        protocol_selections: vec![],
     };
 
     Ok((qlog_reader, log_file_details))
 }
 
-pub fn netlog_with_reader<R: std::io::BufRead>(
-    reader: &mut R,
-) -> Result<netlog::constants::Constants, Box<dyn Error>> {
-    // Netlog format is sort of newline-delimited. It starts off creating a JSON
-    // object, within that is an object containing constants, followed by an
-    // array of line-delimited events. This franken-JSON needs a bit of molding
-    // to fit serde parsing.
-    let mut buf = Vec::<u8>::new();
-
-    // read the constants line
-    let len = reader.read_until(b'\n', &mut buf).unwrap();
-
-    // replace the trailing comma (,) with a brace (}) to close the object and
-    // make it parseable.
-    buf[len - 2] = b'}';
-
-    let res: Result<netlog::constants::ConstantsLine, serde_json::Error> =
-        serde_json::from_slice(&buf);
-
-    match res {
-        Ok(mut line) => {
-            line.constants.populate_id_keyed();
-
-            Ok(line.constants)
-        },
-
-        Err(e) => {
-            error!("Error deserializing: {}", e);
-
-            // Just swallow the failure and move on
-
-            Err(e.into())
-        },
-    }
-}
-
 pub fn stringify_last<T>(src: &[T]) -> String
 where
     T: std::fmt::Debug,
@@ -426,8 +415,12 @@ pub fn category_and_type_from_event<T: Serialize>(ev: &T) -> (String, String) {
     category_and_type_from_name(&name)
 }
 
+pub mod batch;
 pub mod config;
 pub mod datastore;
+pub mod export;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod live;
 pub mod plots;
 pub mod reports;
 pub mod request_stub;
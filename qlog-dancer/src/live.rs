@@ -0,0 +1,629 @@
+// Copyright (C) 2025, Cloudflare, Inc.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are
+// met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//
+//     * Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in the
+//       documentation and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS
+// IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO,
+// THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR
+// PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Live charts of qlogs that are still being written.
+//!
+//! Events are read from a growing sqlog file, or from connections to a UNIX
+//! socket that each stream a single trace, e.g. as written by tokio-quiche
+//! with the `qlog_socket` setting. Charts are redrawn periodically while
+//! events arrive.
+
+use std::collections::BTreeMap;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Read;
+#[cfg(unix)]
+use std::os::unix::net::UnixListener;
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::mpsc::Sender;
+use std::time::Duration;
+use std::time::Instant;
+
+use log::info;
+use log::warn;
+use qlog::reader::QlogSeqReader;
+
+use crate::config::AppConfig;
+use crate::config::LiveConfig;
+use crate::datastore::Datastore;
+use crate::plots::chart_filepath;
+use crate::plots::conn_overview;
+use crate::plots::conn_overview::OverviewChartOutputType;
+use crate::plots::AreaMargin;
+use crate::plots::ChartId;
+use crate::plots::ChartMargin;
+use crate::plots::ChartSize;
+use crate::plots::ClampParams;
+use crate::plots::PlotParameters;
+use crate::seriesstore::SeriesStore;
+
+/// How long to wait before reading again once the end of a file is reached.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A reader that waits for more data at the end of its input, like
+/// `tail -f`.
+///
+/// The end of the input is only reported once no data was read for
+/// `idle_timeout`, or never if there is no timeout.
+pub struct FollowReader<R> {
+    inner: R,
+    poll_interval: Duration,
+    idle_timeout: Option<Duration>,
+    last_read: Instant,
+}
+
+impl<R: Read> FollowReader<R> {
+    pub fn new(
+        inner: R, poll_interval: Duration, idle_timeout: Option<Duration>,
+    ) -> Self {
+        Self {
+            inner,
+            poll_interval,
+            idle_timeout,
+            last_read: Instant::now(),
+        }
+    }
+}
+
+impl<R: Read> Read for FollowReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            let n = self.inner.read(buf)?;
+
+            if n > 0 || buf.is_empty() {
+                self.last_read = Instant::now();
+                return Ok(n);
+            }
+
+            if let Some(timeout) = self.idle_timeout {
+                if self.last_read.elapsed() >= timeout {
+                    return Ok(0);
+                }
+            }
+
+            std::thread::sleep(self.poll_interval);
+        }
+    }
+}
+
+/// Progress of a trace being read.
+enum LiveUpdate {
+    Started {
+        id: usize,
+        name: String,
+        vantage_point: qlog::VantagePointType,
+    },
+
+    Event {
+        id: usize,
+        event: Box<qlog::reader::Event>,
+    },
+
+    Finished {
+        id: usize,
+    },
+}
+
+/// The events of a trace read so far.
+struct LiveTrace {
+    name: String,
+    datastore: Datastore,
+    changed: bool,
+    finished: bool,
+}
+
+/// Reads the trace of `reader` in a new thread, reporting progress to `tx`.
+fn spawn_trace_reader(
+    id: usize, name: String, reader: Box<dyn BufRead + Send + Sync>,
+    tx: Sender<LiveUpdate>,
+) {
+    std::thread::spawn(move || {
+        match QlogSeqReader::new(reader) {
+            Ok(reader) => {
+                let vantage_point = reader
+                    .qlog
+                    .trace
+                    .vantage_point
+                    .clone()
+                    .unwrap_or_default()
+                    .ty;
+
+                if tx
+                    .send(LiveUpdate::Started {
+                        id,
+                        name: name.clone(),
+                        vantage_point,
+                    })
+                    .is_err()
+                {
+                    return;
+                }
+
+                for event in reader {
+                    let event = Box::new(event);
+                    if tx.send(LiveUpdate::Event { id, event }).is_err() {
+                        return;
+                    }
+                }
+
+                info!("trace {} finished", name);
+            },
+
+            Err(e) => warn!("failed to read trace {}: {}", name, e),
+        }
+
+        let _ = tx.send(LiveUpdate::Finished { id });
+    });
+}
+
+/// Follows the sqlog file at `path` until it stops growing for
+/// `idle_timeout`.
+fn follow_file(
+    path: &str, idle_timeout: Option<Duration>, tx: Sender<LiveUpdate>,
+) -> std::io::Result<()> {
+    let file = std::fs::File::open(path)?;
+    let reader = FollowReader::new(file, POLL_INTERVAL, idle_timeout);
+
+    spawn_trace_reader(0, file_name(path), Box::new(BufReader::new(reader)), tx);
+
+    Ok(())
+}
+
+/// Removes the socket file at its path when dropped.
+#[cfg_attr(not(unix), allow(dead_code))]
+struct SocketFile(PathBuf);
+
+impl Drop for SocketFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Binds the UNIX socket at `path`, replacing a stale socket file left by a
+/// previous run that didn't exit cleanly.
+#[cfg(unix)]
+fn bind_socket(path: &str) -> std::io::Result<UnixListener> {
+    match UnixListener::bind(path) {
+        Err(e) if e.kind() == std::io::ErrorKind::AddrInUse => {
+            // Don't take over a socket that is still being listened on.
+            if UnixStream::connect(path).is_ok() {
+                return Err(e);
+            }
+
+            warn!("removing stale socket {}", path);
+            std::fs::remove_file(path)?;
+
+            UnixListener::bind(path)
+        },
+
+        res => res,
+    }
+}
+
+/// Listens on the UNIX socket at `path` and reads a trace from each accepted
+/// connection, until the connection is closed.
+///
+/// The socket file is removed when the returned value is dropped, or when the
+/// process is interrupted.
+#[cfg(unix)]
+fn listen_socket(
+    path: &str, tx: Sender<LiveUpdate>,
+) -> std::io::Result<SocketFile> {
+    let listener = bind_socket(path)?;
+    let socket_file = SocketFile(PathBuf::from(path));
+
+    let interrupted_path = socket_file.0.clone();
+    ctrlc::set_handler(move || {
+        let _ = std::fs::remove_file(&interrupted_path);
+        std::process::exit(130);
+    })
+    .map_err(std::io::Error::other)?;
+
+    let name = file_name(path);
+
+    std::thread::spawn(move || {
+        for (id, stream) in listener.incoming().enumerate() {
+            match stream {
+                Ok(stream) => {
+                    info!("trace {}-{} connected", name, id);
+
+                    spawn_trace_reader(
+                        id,
+                        format!("{}-{}", name, id),
+                        Box::new(BufReader::new(stream)),
+                        tx.clone(),
+                    );
+                },
+
+                Err(e) => warn!("failed to accept qlog connection: {}", e),
+            }
+        }
+    });
+
+    Ok(socket_file)
+}
+
+#[cfg(not(unix))]
+fn listen_socket(
+    path: &str, _tx: Sender<LiveUpdate>,
+) -> std::io::Result<SocketFile> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        format!("can't listen on {path}, sockets require UNIX"),
+    ))
+}
+
+fn file_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(path)
+        .to_string()
+}
+
+/// Draws the connection overview of `trace`, returning the path of the chart
+/// or `None` if there is nothing to draw yet.
+fn draw_trace(
+    trace: &mut LiveTrace, params: &PlotParameters, ty: &OverviewChartOutputType,
+) -> Option<String> {
+    let ds = &mut trace.datastore;
+
+    // Finalizing extends series to the last event, replacing the points it
+    // added on the previous redraw.
+    ds.hydrate_http_requests();
+    ds.finalize();
+
+    let ss = SeriesStore::from_datastore(ds);
+
+    if ss.local_cwnd.is_empty() && ss.onertt_packet_sent.is_empty() {
+        return None;
+    }
+
+    conn_overview::plot_connection_overview(params, &trace.name, &ss, ds, ty);
+
+    Some(chart_filepath(
+        ChartId::ConnOverview,
+        &params.clamp,
+        &trace.name,
+        ds,
+        &ty.clone().into(),
+    ))
+}
+
+/// Escapes `s` for use in HTML text and quoted attribute values.
+fn escape_html(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Writes an HTML page showing `charts`, that reloads itself every
+/// `interval`.
+fn write_index(
+    charts_dir: &str, charts: &BTreeMap<usize, (String, String)>,
+    interval: Duration,
+) -> std::io::Result<()> {
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta http-equiv=\"refresh\" content=\"{}\">\n\
+         <title>qlog-dancer live</title>\n</head>\n<body>\n",
+        interval.as_secs().max(1)
+    );
+
+    if charts.is_empty() {
+        html.push_str("<p>Waiting for events...</p>\n");
+    }
+
+    for (name, path) in charts.values() {
+        // Trace names come from file and socket names, so they can't be
+        // trusted to be valid HTML.
+        let name = escape_html(name);
+        let src = escape_html(
+            Path::new(path)
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default(),
+        );
+
+        html.push_str(&format!(
+            "<h2>{}</h2>\n<img src=\"{}\" alt=\"{}\" width=\"100%\">\n",
+            name, src, name
+        ));
+    }
+
+    html.push_str("</body>\n</html>\n");
+
+    std::fs::create_dir_all(charts_dir)?;
+    std::fs::write(format!("{}/index.html", charts_dir), html)
+}
+
+/// Reads the traces selected by `config` and redraws their charts every
+/// `config.interval` while events arrive.
+///
+/// Returns once all traces of a file are finished. Traces are read from a
+/// socket until the process is terminated.
+pub fn run(config: &LiveConfig) -> std::io::Result<()> {
+    let (tx, rx) = mpsc::channel();
+
+    // Kept until returning, so that the socket file is removed.
+    let _socket_file = match (&config.file, &config.socket) {
+        (_, Some(socket)) => Some(listen_socket(socket, tx)?),
+
+        (Some(file), None) => {
+            follow_file(file, config.idle_timeout, tx)?;
+
+            None
+        },
+
+        (None, None) =>
+            return Err(std::io::Error::other("no file or socket to read")),
+    };
+
+    let params = PlotParameters {
+        clamp: ClampParams {
+            start: None,
+            end: None,
+            stream_y_max: config.stream_y_max,
+        },
+        cwnd_y_max: config.cwnd_y_max,
+        chart_size: ChartSize {
+            width: 1600,
+            height: 1200,
+        },
+        colors: AppConfig::colors(config.dark_mode),
+        chart_margin: ChartMargin {
+            top: 20,
+            bottom: 20,
+            left: 20,
+            right: 20,
+        },
+        area_margin: AreaMargin { x: 40, y: 80 },
+        display_chart_title: true,
+        display_legend: true,
+        display_minor_lines: true,
+    };
+
+    let ty = OverviewChartOutputType::Png {
+        output_dir: config.charts_dir.clone(),
+        cwnd_y_max: config.cwnd_y_max,
+        stream_y_max: config.stream_y_max,
+    };
+
+    let mut traces = BTreeMap::new();
+    let mut charts = BTreeMap::new();
+
+    write_index(&config.charts_dir, &charts, config.interval)?;
+
+    loop {
+        let deadline = Instant::now() + config.interval;
+        let mut disconnected = false;
+
+        // Collect events until it's time to redraw.
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+
+            match rx.recv_timeout(timeout) {
+                Ok(LiveUpdate::Started {
+                    id,
+                    name,
+                    vantage_point,
+                }) => {
+                    traces.insert(id, LiveTrace {
+                        name,
+                        datastore: Datastore::with_vantage_point(&vantage_point),
+                        changed: false,
+                        finished: false,
+                    });
+                },
+
+                Ok(LiveUpdate::Event { id, event }) => {
+                    if let Some(trace) = traces.get_mut(&id) {
+                        trace.datastore.consume_sqlog_reader_event(&event, true);
+                        trace.changed = true;
+                    }
+                },
+
+                Ok(LiveUpdate::Finished { id }) => {
+                    if let Some(trace) = traces.get_mut(&id) {
+                        trace.finished = true;
+                    }
+                },
+
+                Err(RecvTimeoutError::Timeout) => break,
+
+                Err(RecvTimeoutError::Disconnected) => {
+                    disconnected = true;
+                    break;
+                },
+            }
+        }
+
+        let mut redrawn = false;
+        for (id, trace) in traces.iter_mut().filter(|(_, t)| t.changed) {
+            trace.changed = false;
+
+            if let Some(path) = draw_trace(trace, &params, &ty) {
+                charts.insert(*id, (trace.name.clone(), path));
+                redrawn = true;
+            }
+        }
+
+        if redrawn {
+            write_index(&config.charts_dir, &charts, config.interval)?;
+        }
+
+        // Finished traces are drawn one last time above, so their data can be
+        // dropped.
+        traces.retain(|_, t| !t.finished);
+
+        if disconnected {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follow_reader_waits_for_data() {
+        let path = std::env::temp_dir()
+            .join(format!("qlog-dancer-follow-{}", std::process::id()));
+        std::fs::write(&path, b"abc").unwrap();
+
+        let mut reader = FollowReader::new(
+            std::fs::File::open(&path).unwrap(),
+            Duration::from_millis(1),
+            Some(Duration::from_millis(500)),
+        );
+
+        let writer_path = path.clone();
+        let writer = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+
+            let mut file = std::fs::OpenOptions::new()
+                .append(true)
+                .open(writer_path)
+                .unwrap();
+            std::io::Write::write_all(&mut file, b"def").unwrap();
+        });
+
+        let mut buf = String::new();
+        reader.read_to_string(&mut buf).unwrap();
+        writer.join().unwrap();
+
+        assert_eq!(buf, "abcdef");
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn repeated_finalize() {
+        let mut ds = Datastore {
+            local_cwnd: vec![(1.0, 12000)],
+            local_pacing_rate: vec![(1.0, 1000)],
+            last_event_time: 5.0,
+            ..Default::default()
+        };
+
+        // Finalizing on every redraw doesn't pile up points.
+        ds.finalize();
+        ds.finalize();
+        assert_eq!(ds.local_cwnd, [(1.0, 12000), (5.0, 12000)]);
+        assert_eq!(ds.local_pacing_rate, [(1.0, 1000), (5.0, 1000)]);
+
+        // The extension follows the last event as more are added.
+        ds.last_event_time = 8.0;
+        ds.finalize();
+        assert_eq!(ds.local_cwnd, [(1.0, 12000), (8.0, 12000)]);
+
+        ds.local_cwnd.push((9.0, 14000));
+        ds.last_event_time = 10.0;
+        ds.finalize();
+        assert_eq!(ds.local_cwnd, [
+            (1.0, 12000),
+            (8.0, 12000),
+            (9.0, 14000),
+            (10.0, 14000)
+        ]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stale_socket() {
+        let path = std::env::temp_dir()
+            .join(format!("qlog-dancer-stale-{}.sock", std::process::id()));
+        let path_str = path.to_str().unwrap();
+
+        // A listener that went away leaves its socket file behind.
+        drop(UnixListener::bind(&path).unwrap());
+        assert!(path.exists());
+
+        let listener = bind_socket(path_str).unwrap();
+        let socket_file = SocketFile(path.clone());
+
+        // A socket that is listened on isn't replaced.
+        assert_eq!(
+            bind_socket(path_str).unwrap_err().kind(),
+            std::io::ErrorKind::AddrInUse
+        );
+
+        drop(listener);
+        drop(socket_file);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn follow_reader_idle_timeout() {
+        let mut reader = FollowReader::new(
+            std::io::empty(),
+            Duration::from_millis(1),
+            Some(Duration::from_millis(10)),
+        );
+
+        let mut buf = [0; 8];
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn index_escapes_names() {
+        let dir = std::env::temp_dir()
+            .join(format!("qlog-dancer-index-{}", std::process::id()));
+        let dir = dir.to_str().unwrap();
+
+        let mut charts = BTreeMap::new();
+        charts.insert(0, (
+            "<script>alert(1)</script>".to_string(),
+            format!("{dir}/a\"b&c.png"),
+        ));
+
+        write_index(dir, &charts, Duration::from_secs(1)).unwrap();
+
+        let html = std::fs::read_to_string(format!("{dir}/index.html")).unwrap();
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("<h2>&lt;script&gt;alert(1)&lt;/script&gt;</h2>"));
+        assert!(html.contains("src=\"a&quot;b&amp;c.png\""));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
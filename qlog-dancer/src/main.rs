@@ -32,12 +32,25 @@ use log::debug;
 use log::error;
 use log::info;
 use log::warn;
+use qlog::merge::TraceLog;
+use qlog::reader::QlogSeqReader;
+use qlog_dancer::batch;
+use qlog_dancer::config::AppCommand;
 use qlog_dancer::config::AppConfig;
+use qlog_dancer::config::BatchConfig;
+use qlog_dancer::config::CompareConfig;
+use qlog_dancer::config::LiveConfig;
+use qlog_dancer::config::MergeConfig;
+use qlog_dancer::export::export_series;
+use qlog_dancer::live;
 use qlog_dancer::parse_log_file;
 use qlog_dancer::plots;
+use qlog_dancer::plots::compare;
+use qlog_dancer::plots::compare::CompareSeries;
 use qlog_dancer::plots::conn_flow_control;
 use qlog_dancer::plots::conn_overview;
 use qlog_dancer::plots::conn_overview::OverviewChartOutputType;
+use qlog_dancer::plots::distribution::histogram_svg;
 use qlog_dancer::plots::packet_received;
 use qlog_dancer::plots::packet_sent;
 use qlog_dancer::plots::pending::PendingPlotParams;
@@ -50,6 +63,7 @@ use qlog_dancer::plots::ChartOutputType;
 use qlog_dancer::plots::ChartSize;
 use qlog_dancer::plots::ClampParams;
 use qlog_dancer::plots::PlotParameters;
+use qlog_dancer::reports::html;
 use qlog_dancer::reports::report;
 use qlog_dancer::seriesstore::SeriesStore;
 use qlog_dancer::SerializationFormat;
@@ -63,8 +77,16 @@ fn main() {
 fn run() -> i32 {
     env_logger::builder().init();
 
-    let mut config = match AppConfig::from_clap() {
-        Ok(v) => v,
+    let mut config = match AppCommand::from_clap() {
+        Ok(AppCommand::Analyze(v)) => *v,
+
+        Ok(AppCommand::Merge(v)) => return merge(&v),
+
+        Ok(AppCommand::Compare(v)) => return compare(&v),
+
+        Ok(AppCommand::Batch(v)) => return batch(&v),
+
+        Ok(AppCommand::Live(v)) => return live(&v),
 
         Err(e) => {
             error!("Error loading configuration, exiting: {}", e);
@@ -116,7 +138,7 @@ fn run() -> i32 {
         config.plot_pending
     {
         if log_file.data.is_empty() {
-            error!("File exists but trace information was empty or invalid. If you used a netlog-filter, check it exactly matched session host in the file.");
+            error!("File exists but trace information was empty or invalid. If you used a netlog-filter or netlog-session, check it exactly matched a session in the file.");
             return 1;
         }
 
@@ -303,6 +325,15 @@ fn run() -> i32 {
         }
     }
 
+    if let Some(format) = config.export_series {
+        info!("exporting plot series...");
+
+        if let Err(e) = export_series(&log_file, &config, format) {
+            error!("Failed to export series: {}", e);
+            return 1;
+        }
+    }
+
     println!();
 
     report(&log_file, &config);
@@ -311,3 +342,290 @@ fn run() -> i32 {
 
     0
 }
+
+fn merge(config: &MergeConfig) -> i32 {
+    let mut traces = vec![];
+
+    for file in &config.files {
+        let reader = match QlogSeqReader::with_file(file) {
+            Ok(v) => v,
+
+            Err(e) => {
+                error!("Failed to read {}: {}", file, e);
+                return 1;
+            },
+        };
+
+        let trace = TraceLog::from_reader(reader);
+        let odcid = trace.odcid();
+
+        match (&config.odcid, &odcid) {
+            (Some(wanted), Some(odcid)) if wanted != odcid => {
+                info!("skipping {} with ODCID {}", file, odcid);
+                continue;
+            },
+
+            (Some(_), None) => {
+                info!("skipping {} without an ODCID", file);
+                continue;
+            },
+
+            (None, None) => warn!("no ODCID found in {}", file),
+
+            _ => (),
+        }
+
+        traces.push((file, odcid, trace));
+    }
+
+    if traces.is_empty() {
+        match &config.odcid {
+            Some(odcid) => error!("No trace matches ODCID {}", odcid),
+
+            None => error!("No traces to merge"),
+        }
+
+        return 1;
+    }
+
+    let mut odcids: Vec<_> = traces
+        .iter()
+        .filter_map(|(_, odcid, _)| odcid.as_ref())
+        .collect();
+    odcids.sort();
+    odcids.dedup();
+
+    if odcids.len() > 1 {
+        error!(
+            "Traces belong to different connections {:?}, select one with --odcid",
+            odcids
+        );
+        return 1;
+    }
+
+    let files: Vec<_> = traces.iter().map(|(file, ..)| *file).collect();
+
+    let merged = match qlog::merge::merge(
+        traces.into_iter().map(|(_, _, trace)| trace).collect(),
+    ) {
+        Ok(v) => v,
+
+        Err(e) => {
+            error!("Failed to merge traces: {}", e);
+            return 1;
+        },
+    };
+
+    println!();
+    println!("== Clock offsets ==");
+    for (file, offset) in files.iter().zip(&merged.offsets) {
+        match offset {
+            Some(o) => println!(
+                "  {}: {:.3} ms (one-way delay {:.3} ms, {} packets)",
+                file, o.offset, o.one_way_delay, o.samples
+            ),
+
+            None => println!("  {}: unknown, not aligned", file),
+        }
+    }
+    println!();
+
+    let written = std::fs::File::create(&config.output)
+        .map_err(|e| e.to_string())
+        .and_then(|f| {
+            serde_json::to_writer(std::io::BufWriter::new(f), &merged.qlog)
+                .map_err(|e| e.to_string())
+        });
+
+    if let Err(e) = written {
+        error!("Failed to write {}: {}", config.output, e);
+        return 1;
+    }
+
+    println!("merged {} traces into {}", files.len(), config.output);
+
+    0
+}
+
+fn compare(config: &CompareConfig) -> i32 {
+    let mut series = vec![];
+
+    for file in &config.files {
+        let mut file_config = AppConfig::with_file(file);
+        file_config.netlog_filter = config.netlog_filter.clone();
+
+        let log_file = match parse_log_file(&file_config) {
+            Ok(v) => v,
+
+            Err(e) => {
+                error!("Failed to parse {}: {}", file, e);
+                return 1;
+            },
+        };
+
+        let sessions = log_file.data.len();
+        for data in &log_file.data {
+            let ss = SeriesStore::from_datastore(&data.datastore);
+
+            let label = if sessions > 1 {
+                format!(
+                    "{} {} session {}",
+                    file_config.filename,
+                    data.datastore.host.as_deref().unwrap_or_default(),
+                    data.datastore.session_id.unwrap_or_default()
+                )
+            } else {
+                file_config.filename.clone()
+            };
+
+            series.push(CompareSeries::new(
+                label,
+                &ss,
+                &data.datastore,
+                config.alignment,
+            ));
+        }
+    }
+
+    let plot_params = PlotParameters {
+        clamp: ClampParams {
+            start: config.start,
+            end: config.end,
+            stream_y_max: config.stream_y_max,
+        },
+        cwnd_y_max: config.cwnd_y_max,
+        chart_size: ChartSize {
+            width: 1600,
+            height: 2000,
+        },
+        colors: AppConfig::colors(config.dark_mode),
+        chart_margin: ChartMargin {
+            top: 20,
+            bottom: 20,
+            left: 20,
+            right: 20,
+        },
+        area_margin: AreaMargin { x: 40, y: 80 },
+        display_chart_title: true,
+        display_legend: true,
+        display_minor_lines: true,
+    };
+
+    info!("drawing comparison of {} connections...", series.len());
+    compare::plot_compare(&plot_params, &config.charts_dir, &series);
+
+    println!("comparison chart written to {}", config.charts_dir);
+
+    0
+}
+
+fn batch(config: &BatchConfig) -> i32 {
+    let files = match batch::find_log_files(std::path::Path::new(&config.dir)) {
+        Ok(v) => v,
+
+        Err(e) => {
+            error!("Failed to read {}: {}", config.dir, e);
+            return 1;
+        },
+    };
+
+    info!(
+        "summarizing {} files using {} jobs...",
+        files.len(),
+        config.jobs
+    );
+
+    let (summaries, failed) =
+        batch::summarize_files(&files, config.jobs, |file| {
+            let mut file_config = AppConfig::with_file(file);
+            file_config.netlog_filter = config.netlog_filter.clone();
+            file_config
+        });
+
+    let csv_path = format!("{}/connections.csv", config.output_dir);
+    let written = std::fs::create_dir_all(&config.output_dir)
+        .and_then(|_| std::fs::File::create(&csv_path))
+        .and_then(|f| batch::write_csv(&summaries, std::io::BufWriter::new(f)));
+
+    if let Err(e) = written {
+        error!("Failed to write {}: {}", csv_path, e);
+        return 1;
+    }
+
+    #[cfg(feature = "parquet")]
+    {
+        let parquet_path = format!("{}/connections.parquet", config.output_dir);
+        let written = std::fs::File::create(&parquet_path)
+            .and_then(|f| batch::write_parquet(&summaries, f));
+
+        if let Err(e) = written {
+            error!("Failed to write {}: {}", parquet_path, e);
+            return 1;
+        }
+    }
+
+    let colors = AppConfig::colors(false);
+    let histogram = |title: &str, x_desc: &str, values: Vec<f64>| {
+        histogram_svg(title, x_desc, &values, 20, &colors, (600, 400))
+    };
+
+    let histograms = [
+        histogram(
+            "Handshake time",
+            "Time (ms)",
+            summaries.iter().filter_map(|s| s.handshake_time).collect(),
+        ),
+        histogram(
+            "Loss rate",
+            "Lost / sent packets",
+            summaries.iter().filter_map(|s| s.loss_rate()).collect(),
+        ),
+        histogram(
+            "Median RTT",
+            "RTT (ms)",
+            summaries
+                .iter()
+                .filter_map(|s| s.rtt_percentiles.map(|p| p[0] as f64))
+                .collect(),
+        ),
+        histogram(
+            "Goodput",
+            "Throughput (Mbps)",
+            summaries.iter().filter_map(|s| s.goodput).collect(),
+        ),
+    ];
+
+    let html_path = format!("{}/summary.html", config.output_dir);
+    if let Err(e) =
+        html::batch_summary(&summaries, failed, &histograms, &html_path)
+    {
+        error!("Failed to write {}: {}", html_path, e);
+        return 1;
+    }
+
+    println!(
+        "summarized {} connections from {} files ({} skipped) into {}",
+        summaries.len(),
+        files.len() - failed,
+        failed,
+        config.output_dir
+    );
+
+    0
+}
+
+fn live(config: &LiveConfig) -> i32 {
+    println!(
+        "drawing live charts, open {}/index.html to view them",
+        config.charts_dir
+    );
+
+    if let Err(e) = live::run(config) {
+        error!("Failed to draw live charts: {}", e);
+        return 1;
+    }
+
+    println!("live charts written to {}", config.charts_dir);
+
+    0
+}
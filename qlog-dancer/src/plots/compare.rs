@@ -0,0 +1,451 @@
+// Copyright (C) 2025, Cloudflare, Inc.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are
+// met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//
+//     * Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in the
+//       documentation and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS
+// IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO,
+// THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR
+// PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Overlay of several connections on shared axes, for A/B comparisons.
+
+use std::collections::BTreeMap;
+
+use log::warn;
+use plotters::coord::types::RangedCoordf32;
+use plotters::coord::types::RangedCoordf64;
+use plotters::coord::types::RangedCoordu64;
+use plotters::coord::Shift;
+use qlog::events::quic::QuicFrame;
+
+use crate::datastore::Datastore;
+use crate::datastore::VantagePoint;
+use crate::plots::colors::ColorCycle;
+use crate::plots::minmax::XMinMax;
+use crate::plots::*;
+use crate::seriesstore::SeriesStore;
+use crate::QlogPointRtt;
+use crate::QlogPointu64;
+
+/// How the time axes of compared connections are aligned.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CompareAlignment {
+    /// Each connection's time starts at the beginning of its log.
+    #[default]
+    Start,
+
+    /// Time 0 is the completion of each connection's handshake.
+    Handshake,
+}
+
+/// The series of one connection in a comparison.
+pub struct CompareSeries {
+    pub label: String,
+    pub cwnd: Vec<QlogPointu64>,
+    pub bytes_in_flight: Vec<QlogPointu64>,
+    pub smoothed_rtt: Vec<QlogPointRtt>,
+    pub stream_progress: Vec<QlogPointu64>,
+}
+
+impl CompareSeries {
+    pub fn new(
+        label: String, ss: &SeriesStore, ds: &Datastore,
+        alignment: CompareAlignment,
+    ) -> Self {
+        let shift = match alignment {
+            CompareAlignment::Start => 0.0,
+
+            CompareAlignment::Handshake => match ds.handshake_complete_time {
+                Some(t) => t,
+
+                None => {
+                    warn!(
+                        "{}: handshake completion not found, not aligned",
+                        label
+                    );
+                    0.0
+                },
+            },
+        };
+
+        fn shifted<Y: Copy>(data: &[(f64, Y)], shift: f64) -> Vec<(f64, Y)> {
+            data.iter().map(|(x, y)| (x - shift, *y)).collect()
+        }
+
+        CompareSeries {
+            cwnd: shifted(&ss.local_cwnd, shift),
+            bytes_in_flight: shifted(&ss.local_bytes_in_flight, shift),
+            smoothed_rtt: shifted(&ss.local_smoothed_rtt, shift),
+            stream_progress: shifted(&stream_progress(ds), shift),
+            label,
+        }
+    }
+
+    fn x_range(&self) -> Option<(f64, f64)> {
+        let xs = self
+            .cwnd
+            .iter()
+            .chain(&self.bytes_in_flight)
+            .chain(&self.stream_progress)
+            .map(|p| p.0)
+            .chain(self.smoothed_rtt.iter().map(|p| p.0));
+
+        xs.fold(None, |acc, x| match acc {
+            None => Some((x, x)),
+
+            Some((min, max)) => Some((f64::min(min, x), f64::max(max, x))),
+        })
+    }
+}
+
+/// Returns the cumulative stream data transferred over time, excluding
+/// retransmissions.
+///
+/// This is the data received by a client or sent by a server, since that is
+/// usually the direction of bulk transfers.
+pub(crate) fn stream_progress(ds: &Datastore) -> Vec<QlogPointu64> {
+    if !ds
+        .netlog_quic_stream_received_connection_cumulative
+        .is_empty()
+    {
+        return ds.netlog_quic_stream_received_connection_cumulative.clone();
+    }
+
+    if !ds
+        .netlog_h2_stream_received_connection_cumulative
+        .is_empty()
+    {
+        return ds.netlog_h2_stream_received_connection_cumulative.clone();
+    }
+
+    // (time, stream ID, end offset)
+    let mut frames: Vec<(f64, u64, u64)> = match ds.vantage_point {
+        VantagePoint::Client => ds
+            .received_stream_frames
+            .iter()
+            .flat_map(|(id, frames)| {
+                frames
+                    .iter()
+                    .map(move |(t, f)| (*t, *id, f.offset + f.length))
+            })
+            .collect(),
+
+        VantagePoint::Server => ds
+            .sent_stream_frames
+            .iter()
+            .flat_map(|(id, frames)| {
+                frames.iter().filter_map(move |(t, f)| match f {
+                    QuicFrame::Stream {
+                        offset,
+                        raw: Some(raw),
+                        ..
+                    } => Some((
+                        *t,
+                        *id,
+                        offset.unwrap_or_default() +
+                            raw.payload_length.unwrap_or_default(),
+                    )),
+
+                    _ => None,
+                })
+            })
+            .collect(),
+    };
+
+    frames.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut max_offsets = BTreeMap::new();
+    let mut total = 0;
+    let mut progress = vec![];
+
+    for (time, id, end) in frames {
+        let max = max_offsets.entry(id).or_insert(0);
+
+        if end > *max {
+            total += end - *max;
+            *max = end;
+
+            progress.push((time, total));
+        }
+    }
+
+    progress
+}
+
+fn y_max_u64(
+    series: &[CompareSeries], f: impl Fn(&CompareSeries) -> &[QlogPointu64],
+) -> u64 {
+    let max = series
+        .iter()
+        .flat_map(|s| f(s).iter().map(|p| p.1))
+        .max()
+        .unwrap_or_default();
+
+    // add a bit of margin
+    (max + max / 10).max(1)
+}
+
+fn draw_legend<'a, DB: DrawingBackend + 'a, X: Ranged, Y: Ranged>(
+    params: &PlotParameters, chart: &mut ChartContext<'a, DB, Cartesian2d<X, Y>>,
+) {
+    if params.display_legend {
+        chart
+            .configure_series_labels()
+            .label_font(chart_label_style(&params.colors.caption))
+            .background_style(params.colors.fill.mix(0.8))
+            .border_style(params.colors.axis)
+            .position(SeriesLabelPosition::UpperLeft)
+            .draw()
+            .unwrap();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_u64_plot<DB: DrawingBackend>(
+    params: &PlotParameters, title: &str, y_desc: &str, x: XMinMax, y_max: u64,
+    series: &[CompareSeries], f: impl Fn(&CompareSeries) -> &[QlogPointu64],
+    plot: &plotters::drawing::DrawingArea<DB, Shift>,
+) {
+    let mut builder = ChartBuilder::on(plot);
+    builder
+        .x_label_area_size(params.area_margin.x)
+        .y_label_area_size(params.area_margin.y);
+
+    if params.display_chart_title {
+        builder.caption(title, chart_subtitle_style(&params.colors.caption));
+    }
+
+    let mut chart: ChartContext<DB, Cartesian2d<RangedCoordf64, RangedCoordu64>> =
+        builder.build_cartesian_2d(x.range(), 0..y_max).unwrap();
+
+    draw_mesh(
+        &params.colors,
+        "Relative time (ms)",
+        y_desc,
+        false,
+        &mut chart,
+    );
+
+    let mut colors = ColorCycle::default();
+    for s in series {
+        draw_line(f(s), Some(&s.label), colors.next_color(), &mut chart);
+    }
+
+    draw_legend(params, &mut chart);
+}
+
+fn draw_rtt_plot<DB: DrawingBackend>(
+    params: &PlotParameters, x: XMinMax, series: &[CompareSeries],
+    plot: &plotters::drawing::DrawingArea<DB, Shift>,
+) {
+    let y_max = series
+        .iter()
+        .flat_map(|s| s.smoothed_rtt.iter().map(|p| p.1))
+        .fold(1.0f32, f32::max);
+
+    let mut builder = ChartBuilder::on(plot);
+    builder
+        .x_label_area_size(params.area_margin.x)
+        .y_label_area_size(params.area_margin.y);
+
+    if params.display_chart_title {
+        builder.caption(
+            "Smoothed RTT",
+            chart_subtitle_style(&params.colors.caption),
+        );
+    }
+
+    let mut chart: ChartContext<DB, Cartesian2d<RangedCoordf64, RangedCoordf32>> =
+        builder
+            .build_cartesian_2d(x.range(), 0.0f32..(y_max + y_max / 10.0))
+            .unwrap();
+
+    draw_mesh(
+        &params.colors,
+        "Relative time (ms)",
+        "RTT (ms)",
+        false,
+        &mut chart,
+    );
+
+    let mut colors = ColorCycle::default();
+    for s in series {
+        let colour = colors.next_color();
+
+        chart
+            .draw_series(LineSeries::new(s.smoothed_rtt.clone(), colour))
+            .unwrap()
+            .label(&s.label)
+            .legend(move |(x, y)| {
+                PathElement::new(vec![(x, y), (x + 20, y)], colour)
+            });
+    }
+
+    draw_legend(params, &mut chart);
+}
+
+/// Plots cwnd, bytes in flight, RTT and stream progress of all `series`
+/// overlaid on shared axes, to `output_dir/compare.png`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn plot_compare(
+    params: &PlotParameters, output_dir: &str, series: &[CompareSeries],
+) {
+    if let Err(e) = std::fs::create_dir_all(output_dir) {
+        warn!("failed to create {}: {}", output_dir, e);
+        return;
+    }
+
+    let chart_path = format!("{}/compare.png", output_dir);
+
+    let root = make_chart_bitmap_area(
+        &chart_path,
+        params.chart_size,
+        params.colors,
+        params.chart_margin,
+    );
+
+    let (x_min, x_max) = series
+        .iter()
+        .filter_map(CompareSeries::x_range)
+        .fold((0.0, 1.0), |(min, max), (s_min, s_max)| {
+            (f64::min(min, s_min), f64::max(max, s_max))
+        });
+    let x = XMinMax::new(x_min, x_max, params.clamp.start, params.clamp.end);
+
+    let cwnd_y_max = params.cwnd_y_max.unwrap_or_else(|| {
+        y_max_u64(series, |s| s.cwnd.as_slice())
+            .max(y_max_u64(series, |s| &s.bytes_in_flight))
+    });
+
+    let stream_y_max = params
+        .clamp
+        .stream_y_max
+        .unwrap_or_else(|| y_max_u64(series, |s| s.stream_progress.as_slice()));
+
+    let (top_margin, bottom) = root.split_vertically((5).percent());
+    let areas = bottom.split_evenly((4, 1));
+
+    top_margin
+        .draw_text(
+            "Connection comparison",
+            &chart_title_style(&params.colors.caption),
+            (0, 0),
+        )
+        .unwrap();
+
+    draw_u64_plot(
+        params,
+        "Congestion window",
+        "Data (bytes)",
+        x,
+        cwnd_y_max,
+        series,
+        |s| s.cwnd.as_slice(),
+        &areas[0],
+    );
+    draw_u64_plot(
+        params,
+        "Bytes in flight",
+        "Data (bytes)",
+        x,
+        cwnd_y_max,
+        series,
+        |s| s.bytes_in_flight.as_slice(),
+        &areas[1],
+    );
+    draw_rtt_plot(params, x, series, &areas[2]);
+    draw_u64_plot(
+        params,
+        "Stream progress",
+        "Data (bytes)",
+        x,
+        stream_y_max,
+        series,
+        |s| s.stream_progress.as_slice(),
+        &areas[3],
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datastore::StreamDatapoint;
+
+    #[test]
+    fn progress_ignores_retransmissions() {
+        let mut ds = Datastore {
+            vantage_point: VantagePoint::Client,
+            ..Default::default()
+        };
+
+        ds.received_stream_frames.insert(0, vec![
+            (1.0, StreamDatapoint {
+                offset: 0,
+                length: 100,
+            }),
+            (3.0, StreamDatapoint {
+                offset: 0,
+                length: 100,
+            }),
+            (4.0, StreamDatapoint {
+                offset: 100,
+                length: 50,
+            }),
+        ]);
+        ds.received_stream_frames
+            .insert(4, vec![(2.0, StreamDatapoint {
+                offset: 0,
+                length: 10,
+            })]);
+
+        assert_eq!(stream_progress(&ds), [(1.0, 100), (2.0, 110), (4.0, 160)]);
+    }
+
+    #[test]
+    fn handshake_alignment() {
+        let ds = Datastore {
+            vantage_point: VantagePoint::Client,
+            handshake_complete_time: Some(10.0),
+            ..Default::default()
+        };
+
+        let ss = SeriesStore {
+            local_cwnd: vec![(5.0, 1000), (20.0, 2000)],
+            ..Default::default()
+        };
+
+        let series = CompareSeries::new(
+            "a".to_string(),
+            &ss,
+            &ds,
+            CompareAlignment::Handshake,
+        );
+        assert_eq!(series.cwnd, [(-5.0, 1000), (10.0, 2000)]);
+        assert_eq!(series.x_range(), Some((-5.0, 10.0)));
+
+        let series = CompareSeries::new(
+            "a".to_string(),
+            &ss,
+            &ds,
+            CompareAlignment::Start,
+        );
+        assert_eq!(series.cwnd, ss.local_cwnd);
+    }
+}
@@ -193,7 +193,7 @@ pub fn plot_conn_flow_control_canvas<'a>(
 ) -> ChartContext<'a, CanvasBackend, Cartesian2d<RangedCoordf64, RangedCoordu64>>
 {
     let chart_config =
-        make_chart_config("flow_control", params, filename, ds, ty);
+        make_chart_config(ChartId::FlowControl, params, filename, ds, ty);
 
     let canvas_id: String = chart_config.canvas_id().unwrap_or_default();
     let root =
@@ -226,7 +226,7 @@ pub fn plot_conn_flow_control(
     ty: &ChartOutputType,
 ) {
     let chart_config =
-        make_chart_config("flow_control", params, filename, ds, ty);
+        make_chart_config(ChartId::FlowControl, params, filename, ds, ty);
     let chart_path = chart_config.chart_filepath();
 
     let root = make_chart_bitmap_area(
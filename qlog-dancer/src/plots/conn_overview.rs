@@ -444,7 +444,7 @@ pub fn plot_connection_overview(
     ty: &OverviewChartOutputType,
 ) {
     let chart_config = ChartConfig {
-        title: "conn-overview".into(),
+        title: ChartId::ConnOverview.title().into(),
         input_filename: filename.into(),
         clamp: params.clamp.clone(),
         app_proto: ds.application_proto,
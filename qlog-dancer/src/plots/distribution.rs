@@ -0,0 +1,124 @@
+// Copyright (C) 2025, Cloudflare, Inc.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are
+// met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//
+//     * Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in the
+//       documentation and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS
+// IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO,
+// THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR
+// PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Histograms of a metric across many connections.
+
+use plotters::coord::types::RangedCoordf64;
+use plotters::coord::types::RangedCoordu64;
+
+use crate::plots::colors::PlotColors;
+use crate::plots::colors::TEAL;
+use crate::plots::*;
+
+/// Splits `values` into `buckets` equal-width buckets.
+///
+/// Returns the lower bound of the first bucket, the width of each bucket and
+/// the number of values in each bucket. Non-finite values are ignored.
+pub fn bucketize(values: &[f64], buckets: usize) -> (f64, f64, Vec<u64>) {
+    let finite = || values.iter().copied().filter(|v| v.is_finite());
+
+    let min = finite().fold(f64::INFINITY, f64::min);
+    let max = finite().fold(f64::NEG_INFINITY, f64::max);
+
+    if buckets == 0 || min > max {
+        return (0.0, 1.0, vec![]);
+    }
+
+    // Avoid a zero width when all values are equal.
+    let width = if max > min {
+        (max - min) / buckets as f64
+    } else {
+        1.0
+    };
+
+    let mut counts = vec![0; buckets];
+    for v in finite() {
+        let i = (((v - min) / width) as usize).min(buckets - 1);
+        counts[i] += 1;
+    }
+
+    (min, width, counts)
+}
+
+/// Draws a histogram of `values` and returns it as an SVG document.
+pub fn histogram_svg(
+    title: &str, x_desc: &str, values: &[f64], buckets: usize,
+    colors: &PlotColors, size: (u32, u32),
+) -> String {
+    let (min, width, counts) = bucketize(values, buckets);
+    let y_max = counts.iter().copied().max().unwrap_or_default().max(1);
+    let x_max = min + width * counts.len().max(1) as f64;
+
+    let mut svg = String::new();
+
+    {
+        let root = SVGBackend::with_string(&mut svg, size).into_drawing_area();
+        root.fill(&colors.fill).unwrap();
+
+        let mut chart: ChartContext<
+            SVGBackend,
+            Cartesian2d<RangedCoordf64, RangedCoordu64>,
+        > = ChartBuilder::on(&root)
+            .caption(title, chart_subtitle_style(&colors.caption))
+            .margin(10)
+            .x_label_area_size(40)
+            .y_label_area_size(60)
+            .build_cartesian_2d(min..x_max, 0..y_max + y_max / 10)
+            .unwrap();
+
+        draw_mesh(colors, x_desc, "Connections", false, &mut chart);
+
+        chart
+            .draw_series(counts.iter().enumerate().map(|(i, count)| {
+                let x0 = min + width * i as f64;
+
+                Rectangle::new([(x0, 0), (x0 + width, *count)], TEAL.filled())
+            }))
+            .unwrap();
+
+        root.present().unwrap();
+    }
+
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets() {
+        let (min, width, counts) =
+            bucketize(&[0.0, 1.0, 2.5, 9.9, 10.0, f64::NAN], 4);
+
+        assert_eq!(min, 0.0);
+        assert_eq!(width, 2.5);
+        assert_eq!(counts, [2, 1, 0, 2]);
+
+        assert_eq!(bucketize(&[3.0, 3.0], 4), (3.0, 1.0, vec![2, 0, 0, 0]));
+        assert_eq!(bucketize(&[], 4), (0.0, 1.0, vec![]));
+    }
+}
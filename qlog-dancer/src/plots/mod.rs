@@ -47,6 +47,26 @@ pub enum ChartOutputType {
     },
 }
 
+/// Charts that are referred to from outside the module that plots them, e.g.
+/// to link to them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChartId {
+    ConnOverview,
+    FlowControl,
+    PacketSent,
+}
+
+impl ChartId {
+    /// The title of the chart, which is part of its file name.
+    pub fn title(&self) -> &'static str {
+        match self {
+            ChartId::ConnOverview => "conn-overview",
+            ChartId::FlowControl => "flow_control",
+            ChartId::PacketSent => "packet-sent",
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 struct ChartConfig {
     pub title: String,
@@ -168,6 +188,24 @@ impl ChartConfig {
     }
 }
 
+/// Returns the path of the chart `id` that plotting `ds` with `clamp` writes,
+/// e.g. to link to it.
+pub fn chart_filepath(
+    id: ChartId, clamp: &ClampParams, filename: &str, ds: &Datastore,
+    ty: &ChartOutputType,
+) -> String {
+    ChartConfig {
+        title: id.title().into(),
+        input_filename: filename.into(),
+        clamp: clamp.clone(),
+        app_proto: ds.application_proto,
+        host: ds.host.clone(),
+        session_id: ds.session_id,
+        ty: ty.clone(),
+    }
+    .chart_filepath()
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 pub fn make_chart_bitmap_area(
     path: &str, size: ChartSize, colors: PlotColors, margin: ChartMargin,
@@ -190,11 +228,11 @@ pub fn make_chart_canvas_area(
 }
 
 fn make_chart_config(
-    title: &str, params: &PlotParameters, filename: &str, ds: &Datastore,
+    id: ChartId, params: &PlotParameters, filename: &str, ds: &Datastore,
     ty: &ChartOutputType,
 ) -> ChartConfig {
     let chart_config = ChartConfig {
-        title: title.into(),
+        title: id.title().into(),
         input_filename: filename.into(),
         clamp: params.clamp.clone(),
         app_proto: ds.application_proto,
@@ -305,9 +343,11 @@ fn draw_line<DB: DrawingBackend>(
 }
 
 pub mod colors;
+pub mod compare;
 pub mod congestion_control;
 pub mod conn_flow_control;
 pub mod conn_overview;
+pub mod distribution;
 pub mod minmax;
 pub mod packet_received;
 pub mod packet_sent;
@@ -315,3 +355,4 @@ pub mod pending;
 pub mod rtt;
 pub mod stream_multiplex;
 pub mod stream_sparks;
+pub mod waterfall;
@@ -122,6 +122,22 @@ pub fn draw_packet_sent_received_plot<'a, DB: DrawingBackend + 'a>(
             .unwrap()
             .label("packet lost (packet number)")
             .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], PURPLE));
+
+        // Only some logs report when losses were detected.
+        if !ss.onertt_packet_lost.is_empty() {
+            let detected_losses = ss
+                .onertt_packet_lost
+                .iter()
+                .map(|point| Circle::new(*point, 3, RED));
+
+            chart
+                .draw_series(detected_losses)
+                .unwrap()
+                .label("packet loss detected (packet number)")
+                .legend(|(x, y)| {
+                    PathElement::new(vec![(x, y), (x + 20, y)], RED)
+                });
+        }
     } else {
         let missing_packets_line = ss
             .netlog_missing_packets
@@ -144,6 +160,8 @@ pub fn draw_packet_sent_received_plot<'a, DB: DrawingBackend + 'a>(
             .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], ORANGE));
     }
 
+    draw_connection_events(&ss.connection_events, y_max, &mut chart);
+
     if params.display_legend {
         chart
             .configure_series_labels()
@@ -158,6 +176,41 @@ pub fn draw_packet_sent_received_plot<'a, DB: DrawingBackend + 'a>(
     chart
 }
 
+fn draw_connection_events<DB: DrawingBackend>(
+    events: &[(f64, String)], y_max: u64,
+    chart: &mut ChartContext<DB, Cartesian2d<RangedCoordf64, RangedCoordu64>>,
+) {
+    if events.is_empty() {
+        return;
+    }
+
+    let lines = events
+        .iter()
+        .map(|(t, _)| PathElement::new([(*t, 0), (*t, y_max)], BLACK.mix(0.5)));
+
+    chart
+        .draw_series(lines)
+        .unwrap()
+        .label("connection events")
+        .legend(|(x, y)| {
+            PathElement::new(vec![(x, y), (x + 20, y)], BLACK.mix(0.5))
+        });
+
+    // Stagger labels so that events close in time don't overlap.
+    let labels = events.iter().enumerate().map(|(i, (t, name))| {
+        let y_offset = (i % 4) as i32 * 14;
+
+        EmptyElement::at((*t, y_max)) +
+            Text::new(
+                name.clone(),
+                (3, y_offset),
+                ("sans-serif", 12.0).into_font().color(&BLACK),
+            )
+    });
+
+    chart.draw_series(labels).unwrap();
+}
+
 pub fn draw_packet_sent_lost_delivered_count_plot<'a, DB: DrawingBackend + 'a>(
     params: &PlotParameters, ss: &SeriesStore,
     plot: &plotters::drawing::DrawingArea<DB, Shift>,
@@ -372,7 +425,7 @@ pub fn plot_packet_sent(
     params: &PlotParameters, filename: &str, ss: &SeriesStore, ds: &Datastore,
     ty: &ChartOutputType,
 ) {
-    let chart_config = make_chart_config("packet-sent", params, filename, ds, ty);
+    let chart_config = make_chart_config(ChartId::PacketSent, params, filename, ds, ty);
 
     chart_config.init_chart_dir();
 
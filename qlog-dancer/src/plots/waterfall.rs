@@ -0,0 +1,376 @@
+// Copyright (C) 2025, Cloudflare, Inc.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are
+// met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//
+//     * Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in the
+//       documentation and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS
+// IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO,
+// THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR
+// PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Browser devtools-style waterfall of the HTTP requests in a session.
+//!
+//! Each request gets a row showing how long it was queued, waiting for a
+//! response and transferring it, along with priority updates and resets.
+
+use plotters::coord::ranged1d::SegmentValue;
+use plotters::coord::ranged1d::SegmentedCoord;
+use plotters::coord::types::RangedCoordf64;
+use plotters::coord::types::RangedCoordu32;
+use plotters::coord::Shift;
+
+use crate::datastore::Datastore;
+use crate::datastore::VantagePoint;
+use crate::plots::*;
+
+/// Height of each request's row, in pixels.
+pub const ROW_HEIGHT: u32 = 20;
+
+const MAX_LABEL_LENGTH: usize = 60;
+
+#[derive(Debug, Default)]
+pub struct WaterfallPlotParams {
+    pub clamp: ClampParams,
+    pub width: u32,
+    pub colors: PlotColors,
+    pub display_chart_title: bool,
+    pub display_legend: bool,
+}
+
+/// The timeline of a single request.
+///
+/// At a client, the request is sent when its headers are sent and its first
+/// byte is the first response byte received. At a server, it's the other way
+/// around.
+#[derive(Debug, Default, PartialEq)]
+pub struct WaterfallRow {
+    pub stream_id: u64,
+    pub label: String,
+
+    /// From discovery of the request until it was sent.
+    pub queued: Option<(f64, f64)>,
+
+    /// From sending the request until its first response byte. If there's no
+    /// response, this lasts until the stream was reset or the log ended.
+    pub waiting: Option<(f64, f64)>,
+
+    /// From the first until the last response byte.
+    pub transfer: Option<(f64, f64)>,
+
+    pub priority_updates: Vec<f64>,
+    pub reset: Option<f64>,
+}
+
+impl WaterfallRow {
+    fn start(&self) -> f64 {
+        [self.queued, self.waiting, self.transfer]
+            .iter()
+            .flatten()
+            .map(|span| span.0)
+            .chain(self.reset)
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    fn end(&self) -> f64 {
+        [self.queued, self.waiting, self.transfer]
+            .iter()
+            .flatten()
+            .map(|span| span.1)
+            .chain(self.reset)
+            .chain(self.priority_updates.iter().copied())
+            .fold(0.0, f64::max)
+    }
+}
+
+/// Returns the timelines of all requests in `ds`, ordered by start time.
+pub fn waterfall_rows(ds: &Datastore) -> Vec<WaterfallRow> {
+    let mut rows: Vec<WaterfallRow> = ds
+        .http_requests
+        .values()
+        .map(|req| {
+            let (request, first_byte, last_byte) = match ds.vantage_point {
+                VantagePoint::Client => (
+                    req.time_first_headers_tx,
+                    req.time_first_headers_rx.or(req.time_first_data_rx),
+                    req.time_last_data_rx,
+                ),
+
+                VantagePoint::Server => (
+                    req.time_first_headers_rx,
+                    req.time_first_headers_tx.or(req.time_first_data_tx),
+                    req.time_last_data_tx,
+                ),
+            };
+
+            let queued = req.time_discovery.zip(request);
+
+            let waiting = request.map(|request| {
+                let end =
+                    first_byte.or(req.time_reset).unwrap_or(ds.last_event_time);
+
+                (request, end)
+            });
+
+            let transfer =
+                first_byte.map(|first| (first, last_byte.unwrap_or(first)));
+
+            let mut path = req.path.to_string();
+            if path.chars().count() > MAX_LABEL_LENGTH {
+                path = path.chars().take(MAX_LABEL_LENGTH).collect();
+                path.push_str("...");
+            }
+
+            WaterfallRow {
+                stream_id: req.stream_id,
+                label: format!("{} {} {}", req.stream_id, req.method, path),
+                queued,
+                waiting,
+                transfer,
+                priority_updates: req.time_priority_updates.clone(),
+                reset: req.time_reset,
+            }
+        })
+        .filter(|row| row.start().is_finite())
+        .collect();
+
+    rows.sort_by(|a, b| {
+        a.start()
+            .total_cmp(&b.start())
+            .then(a.stream_id.cmp(&b.stream_id))
+    });
+
+    rows
+}
+
+/// Height of a waterfall chart of `rows`, in pixels.
+pub fn chart_height(rows: &[WaterfallRow]) -> u32 {
+    rows.len() as u32 * ROW_HEIGHT + 120
+}
+
+pub fn draw_waterfall<DB: DrawingBackend>(
+    params: &WaterfallPlotParams, title: &str, rows: &[WaterfallRow],
+    root: &DrawingArea<DB, Shift>,
+) {
+    let n = rows.len() as u32;
+
+    let x_start = params.clamp.start.unwrap_or(0.0);
+    let x_end = params.clamp.end.unwrap_or_else(|| {
+        let end = rows.iter().map(WaterfallRow::end).fold(1.0, f64::max);
+        end + end / 20.0
+    });
+
+    // The first request is drawn at the top.
+    let y = |i: usize| n - 1 - i as u32;
+
+    let mut builder = ChartBuilder::on(root);
+    builder
+        .set_label_area_size(LabelAreaPosition::Left, 300)
+        .set_label_area_size(LabelAreaPosition::Bottom, 40);
+
+    if params.display_chart_title {
+        builder.caption(title, chart_title_style(&params.colors.caption));
+    }
+
+    let mut ctx: ChartContext<
+        DB,
+        Cartesian2d<RangedCoordf64, SegmentedCoord<RangedCoordu32>>,
+    > = builder
+        .build_cartesian_2d(x_start..x_end, (0..n.max(1)).into_segmented())
+        .unwrap();
+
+    ctx.configure_mesh()
+        .axis_style(params.colors.axis)
+        .bold_line_style(params.colors.bold_line.mix(0.5))
+        .light_line_style(params.colors.light_line.mix(0.2))
+        .label_style(chart_label_style(&params.colors.caption))
+        .x_desc("Relative time (ms)")
+        .y_labels(n as usize)
+        .y_label_formatter(&|v| match v {
+            SegmentValue::CenterOf(i) if *i < n =>
+                rows[(n - 1 - i) as usize].label.clone(),
+
+            _ => "".to_string(),
+        })
+        .disable_y_mesh()
+        .draw()
+        .unwrap();
+
+    type Span = fn(&WaterfallRow) -> Option<(f64, f64)>;
+
+    let spans: [(&str, RGBColor, u32, Span); 3] = [
+        ("Queued", colors::MID_GREY, 4, |r: &WaterfallRow| r.queued),
+        ("Waiting", colors::FOREST_GREEN, 3, |r: &WaterfallRow| {
+            r.waiting
+        }),
+        ("Transfer", colors::TEAL, 2, |r: &WaterfallRow| r.transfer),
+    ];
+
+    for (label, colour, inset, span) in spans {
+        ctx.draw_series(rows.iter().enumerate().filter_map(|(i, row)| {
+            let (start, end) = span(row)?;
+
+            let mut bar = Rectangle::new(
+                [
+                    (start, SegmentValue::Exact(y(i))),
+                    (end, SegmentValue::Exact(y(i) + 1)),
+                ],
+                colour.filled(),
+            );
+            bar.set_margin(inset, inset, 0, 0);
+
+            Some(bar)
+        }))
+        .unwrap()
+        .label(label)
+        .legend(move |(x, y)| {
+            Rectangle::new([(x, y - 5), (x + 20, y + 5)], colour.filled())
+        });
+    }
+
+    ctx.draw_series(rows.iter().enumerate().flat_map(|(i, row)| {
+        row.priority_updates.iter().map(move |t| {
+            Circle::new(
+                (*t, SegmentValue::CenterOf(y(i))),
+                4,
+                colors::PURPLE.filled(),
+            )
+        })
+    }))
+    .unwrap()
+    .label("Priority update")
+    .legend(|(x, y)| Circle::new((x + 10, y), 4, colors::PURPLE.filled()));
+
+    ctx.draw_series(rows.iter().enumerate().filter_map(|(i, row)| {
+        row.reset.map(|t| {
+            Cross::new((t, SegmentValue::CenterOf(y(i))), 5, RED.stroke_width(2))
+        })
+    }))
+    .unwrap()
+    .label("Reset")
+    .legend(|(x, y)| Cross::new((x + 10, y), 5, RED.stroke_width(2)));
+
+    if params.display_legend {
+        ctx.configure_series_labels()
+            .label_font(chart_label_style(&params.colors.caption))
+            .background_style(params.colors.fill.mix(0.8))
+            .border_style(params.colors.axis)
+            .position(SeriesLabelPosition::UpperRight)
+            .draw()
+            .unwrap();
+    }
+}
+
+/// Draws a waterfall chart of `rows` and returns it as an SVG document.
+pub fn waterfall_svg(
+    params: &WaterfallPlotParams, title: &str, rows: &[WaterfallRow],
+) -> String {
+    let mut svg = String::new();
+
+    {
+        let root =
+            SVGBackend::with_string(&mut svg, (params.width, chart_height(rows)))
+                .into_drawing_area();
+        root.fill(&params.colors.fill).unwrap();
+
+        draw_waterfall(params, title, rows, &root);
+
+        root.present().unwrap();
+    }
+
+    svg
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn plot_waterfall_canvas(
+    params: &WaterfallPlotParams, rows: &[WaterfallRow], canvas_id: &str,
+) {
+    let root =
+        make_chart_canvas_area(canvas_id, params.colors, ChartMargin::default());
+
+    draw_waterfall(params, "", rows, &root);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request_stub::HttpRequestStub;
+    use crate::request_stub::NaOption;
+
+    #[test]
+    fn client_rows() {
+        let mut ds = Datastore {
+            vantage_point: VantagePoint::Client,
+            last_event_time: 500.0,
+            ..Default::default()
+        };
+
+        ds.http_requests.insert(4, HttpRequestStub {
+            stream_id: 4,
+            method: NaOption::new(Some("GET".to_string())),
+            path: NaOption::new(Some("/style.css".to_string())),
+            time_first_headers_tx: Some(20.0),
+            time_reset: Some(80.0),
+            ..Default::default()
+        });
+
+        ds.http_requests.insert(0, HttpRequestStub {
+            stream_id: 0,
+            method: NaOption::new(Some("GET".to_string())),
+            path: NaOption::new(Some("/".to_string())),
+            time_discovery: Some(5.0),
+            time_first_headers_tx: Some(10.0),
+            time_first_headers_rx: Some(50.0),
+            time_first_data_rx: Some(60.0),
+            time_last_data_rx: Some(200.0),
+            time_priority_updates: vec![30.0],
+            ..Default::default()
+        });
+
+        // Control streams and the like have no timings.
+        ds.http_requests.insert(8, HttpRequestStub {
+            stream_id: 8,
+            ..Default::default()
+        });
+
+        let rows = waterfall_rows(&ds);
+        assert_eq!(rows, [
+            WaterfallRow {
+                stream_id: 0,
+                label: "0 GET /".to_string(),
+                queued: Some((5.0, 10.0)),
+                waiting: Some((10.0, 50.0)),
+                transfer: Some((50.0, 200.0)),
+                priority_updates: vec![30.0],
+                reset: None,
+            },
+            WaterfallRow {
+                stream_id: 4,
+                label: "4 GET /style.css".to_string(),
+                queued: None,
+                waiting: Some((20.0, 80.0)),
+                transfer: None,
+                priority_updates: vec![],
+                reset: Some(80.0),
+            },
+        ]);
+
+        assert_eq!(rows[0].end(), 200.0);
+        assert_eq!(chart_height(&rows), 2 * ROW_HEIGHT + 120);
+    }
+}
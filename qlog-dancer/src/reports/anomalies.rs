@@ -0,0 +1,673 @@
+// Copyright (C) 2025, Cloudflare, Inc.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are
+// met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//
+//     * Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in the
+//       documentation and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS
+// IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO,
+// THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR
+// PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Heuristics that flag notable conditions in a connection, such as loss
+//! recovery misbehaving or flow control stalling transfers.
+
+use std::collections::HashSet;
+use std::fmt::Display;
+
+use tabled::Tabled;
+
+use crate::datastore::Datastore;
+use crate::plots::ChartId;
+use crate::PacketType;
+use crate::QlogPointu64;
+
+/// Losses closer together than this are reported as one episode, in ms.
+const EPISODE_GAP: f64 = 100.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AnomalyKind {
+    /// Packets declared lost that were acknowledged later.
+    SpuriousRetransmission,
+
+    /// The congestion window collapsed to its minimum.
+    PersistentCongestion,
+
+    /// A connection or stream was blocked by flow control.
+    FlowControlStall,
+
+    /// Several probe timeouts fired without an acknowledgement in between.
+    PtoStorm,
+
+    /// Large packets were lost while smaller ones got through.
+    PmtuBlackHole,
+
+    /// The RTT rose well above the minimum RTT.
+    RttInflation,
+
+    /// No packets were sent or received.
+    IdleGap,
+}
+
+impl AnomalyKind {
+    /// The chart that best shows this kind of anomaly.
+    pub fn chart(&self) -> ChartId {
+        match self {
+            AnomalyKind::PersistentCongestion | AnomalyKind::RttInflation =>
+                ChartId::ConnOverview,
+
+            AnomalyKind::FlowControlStall => ChartId::FlowControl,
+
+            AnomalyKind::SpuriousRetransmission |
+            AnomalyKind::PtoStorm |
+            AnomalyKind::PmtuBlackHole |
+            AnomalyKind::IdleGap => ChartId::PacketSent,
+        }
+    }
+}
+
+impl Display for AnomalyKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let v = match self {
+            AnomalyKind::SpuriousRetransmission => "Spurious retransmission",
+            AnomalyKind::PersistentCongestion => "Persistent congestion",
+            AnomalyKind::FlowControlStall => "Flow control stall",
+            AnomalyKind::PtoStorm => "PTO storm",
+            AnomalyKind::PmtuBlackHole => "PMTU black hole",
+            AnomalyKind::RttInflation => "RTT inflation",
+            AnomalyKind::IdleGap => "Idle gap",
+        };
+
+        write!(f, "{}", v)
+    }
+}
+
+/// A notable condition, between two times relative to the start of the log.
+#[derive(Clone, Debug, PartialEq, Tabled)]
+pub struct Finding {
+    #[tabled(rename = "Anomaly")]
+    pub kind: AnomalyKind,
+    #[tabled(rename = "Start (ms)")]
+    pub start: f64,
+    #[tabled(rename = "End (ms)")]
+    pub end: f64,
+    #[tabled(rename = "Description")]
+    pub description: String,
+}
+
+/// Thresholds above which conditions are reported.
+#[derive(Clone, Debug)]
+pub struct AnomalyThresholds {
+    /// Minimum duration of a flow control stall, in ms.
+    pub stall: f64,
+
+    /// Minimum time without any packets sent or received, in ms.
+    pub idle_gap: f64,
+
+    /// Minimum number of consecutive PTOs.
+    pub pto_count: u64,
+
+    /// Minimum ratio of the latest to the minimum RTT.
+    pub rtt_inflation_ratio: f32,
+
+    /// Minimum duration of RTT inflation, in ms.
+    pub rtt_inflation_duration: f64,
+
+    /// Packets up to this size are expected to always fit the path MTU.
+    pub pmtu_base_size: u64,
+
+    /// Minimum number of consecutive lost packets larger than
+    /// `pmtu_base_size`.
+    pub pmtu_lost_packets: usize,
+
+    /// Congestion window at or below which congestion is considered
+    /// persistent, in bytes.
+    pub min_cwnd: u64,
+}
+
+impl Default for AnomalyThresholds {
+    fn default() -> Self {
+        Self {
+            stall: 100.0,
+            idle_gap: 1000.0,
+            pto_count: 3,
+            rtt_inflation_ratio: 3.0,
+            rtt_inflation_duration: 500.0,
+            pmtu_base_size: 1200,
+            pmtu_lost_packets: 5,
+            min_cwnd: 2 * 1350,
+        }
+    }
+}
+
+/// Runs all heuristics on `ds`. Findings are ordered by start time.
+pub fn detect(ds: &Datastore, thresholds: &AnomalyThresholds) -> Vec<Finding> {
+    let mut findings = vec![];
+
+    spurious_retransmissions(ds, &mut findings);
+    persistent_congestion(ds, thresholds, &mut findings);
+    flow_control_stalls(ds, thresholds, &mut findings);
+    pto_storms(ds, thresholds, &mut findings);
+    pmtu_black_holes(ds, thresholds, &mut findings);
+    rtt_inflation(ds, thresholds, &mut findings);
+    idle_gaps(ds, thresholds, &mut findings);
+
+    findings
+        .sort_by(|a, b| a.start.total_cmp(&b.start).then(a.kind.cmp(&b.kind)));
+
+    findings
+}
+
+fn spurious_retransmissions(ds: &Datastore, findings: &mut Vec<Finding>) {
+    let mut episode: Option<(f64, f64, usize)> = None;
+
+    let mut flush = |episode: (f64, f64, usize)| {
+        findings.push(Finding {
+            kind: AnomalyKind::SpuriousRetransmission,
+            start: episode.0,
+            end: episode.1,
+            description: format!(
+                "{} packets declared lost were acknowledged later",
+                episode.2
+            ),
+        })
+    };
+
+    let spurious = ds.lost_packets.iter().filter(|lost| {
+        ds.packet_sent
+            .get(&lost.ty)
+            .and_then(|space| space.get(&lost.number))
+            .is_some_and(|pkt| pkt.acked == Some(true))
+    });
+
+    for lost in spurious {
+        episode = match episode {
+            Some((start, end, count)) if lost.time - end <= EPISODE_GAP =>
+                Some((start, lost.time, count + 1)),
+
+            Some(prev) => {
+                flush(prev);
+                Some((lost.time, lost.time, 1))
+            },
+
+            None => Some((lost.time, lost.time, 1)),
+        };
+    }
+
+    if let Some(episode) = episode {
+        flush(episode);
+    }
+}
+
+fn persistent_congestion(
+    ds: &Datastore, thresholds: &AnomalyThresholds, findings: &mut Vec<Finding>,
+) {
+    let mut above = false;
+    let mut collapsed_at: Option<(f64, u64)> = None;
+
+    for &(time, cwnd) in &ds.local_cwnd {
+        if cwnd > thresholds.min_cwnd {
+            if let Some((start, min)) = collapsed_at.take() {
+                findings.push(persistent_congestion_finding(start, time, min));
+            }
+
+            above = true;
+        } else if above && collapsed_at.is_none() {
+            collapsed_at = Some((time, cwnd));
+        }
+    }
+
+    if let Some((start, min)) = collapsed_at {
+        findings.push(persistent_congestion_finding(
+            start,
+            ds.last_event_time,
+            min,
+        ));
+    }
+}
+
+fn persistent_congestion_finding(start: f64, end: f64, cwnd: u64) -> Finding {
+    Finding {
+        kind: AnomalyKind::PersistentCongestion,
+        start,
+        end,
+        description: format!("Congestion window collapsed to {} bytes", cwnd),
+    }
+}
+
+/// Returns the periods during which `blocked` limits weren't raised by
+/// `limits`, as (start, end, limit) tuples.
+fn stalls(
+    blocked: &[QlogPointu64], limits: &[QlogPointu64], end_of_log: f64,
+) -> Vec<(f64, f64, u64)> {
+    let mut stalls = vec![];
+    let mut last_limit = None;
+
+    for &(time, limit) in blocked {
+        // Repeated BLOCKED frames for the same limit are part of one stall.
+        if last_limit == Some(limit) {
+            continue;
+        }
+
+        last_limit = Some(limit);
+
+        let end = limits
+            .iter()
+            .find(|(t, max)| *t >= time && *max > limit)
+            .map(|(t, _)| *t)
+            .unwrap_or(end_of_log);
+
+        stalls.push((time, end, limit));
+    }
+
+    stalls
+}
+
+fn flow_control_stalls(
+    ds: &Datastore, thresholds: &AnomalyThresholds, findings: &mut Vec<Finding>,
+) {
+    let mut push = |what: String, stalls: Vec<(f64, f64, u64)>| {
+        for (start, end, limit) in stalls {
+            if end - start >= thresholds.stall {
+                findings.push(Finding {
+                    kind: AnomalyKind::FlowControlStall,
+                    start,
+                    end,
+                    description: format!(
+                        "{} at {} bytes for {:.1} ms",
+                        what,
+                        limit,
+                        end - start
+                    ),
+                });
+            }
+        }
+    };
+
+    push(
+        "Connection blocked by peer flow control".to_string(),
+        stalls(
+            &ds.sent_data_blocked,
+            &ds.received_max_data,
+            ds.last_event_time,
+        ),
+    );
+
+    push(
+        "Peer blocked by connection flow control".to_string(),
+        stalls(
+            &ds.received_data_blocked,
+            &ds.sent_max_data,
+            ds.last_event_time,
+        ),
+    );
+
+    for (stream_id, blocked) in &ds.sent_stream_data_blocked {
+        let limits = ds
+            .received_stream_max_data_tracker
+            .per_stream
+            .get(stream_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        push(
+            format!("Stream {} blocked by peer flow control", stream_id),
+            stalls(blocked, limits, ds.last_event_time),
+        );
+    }
+
+    for (stream_id, blocked) in &ds.received_stream_data_blocked {
+        let limits = ds
+            .sent_stream_max_data_tracker
+            .per_stream
+            .get(stream_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        push(
+            format!("Peer blocked on stream {} by flow control", stream_id),
+            stalls(blocked, limits, ds.last_event_time),
+        );
+    }
+}
+
+fn pto_storms(
+    ds: &Datastore, thresholds: &AnomalyThresholds, findings: &mut Vec<Finding>,
+) {
+    // (start of the PTO run, peak PTO count)
+    let mut run: Option<(f64, u64)> = None;
+
+    let mut flush = |run: (f64, u64), end: f64| {
+        if run.1 >= thresholds.pto_count {
+            findings.push(Finding {
+                kind: AnomalyKind::PtoStorm,
+                start: run.0,
+                end,
+                description: format!("{} consecutive PTOs", run.1),
+            });
+        }
+    };
+
+    for &(time, count) in &ds.local_pto_count {
+        run = match run {
+            _ if count == 0 => {
+                if let Some(run) = run {
+                    flush(run, time);
+                }
+
+                None
+            },
+
+            Some((start, peak)) => Some((start, peak.max(count))),
+
+            None => Some((time, count)),
+        };
+    }
+
+    if let Some(run) = run {
+        flush(run, ds.last_event_time);
+    }
+}
+
+fn pmtu_black_holes(
+    ds: &Datastore, thresholds: &AnomalyThresholds, findings: &mut Vec<Finding>,
+) {
+    let Some(space) = ds.packet_sent.get(&PacketType::OneRtt) else {
+        return;
+    };
+
+    let lost: HashSet<u64> = ds
+        .lost_packets
+        .iter()
+        .filter(|lost| lost.ty == PacketType::OneRtt && !lost.mtu_probe)
+        .map(|lost| lost.number)
+        .collect();
+
+    // Send times of consecutive large lost packets, and whether smaller
+    // packets were acknowledged meanwhile.
+    let mut run: Vec<f64> = vec![];
+    let mut small_acked = false;
+
+    let mut flush = |run: &mut Vec<f64>, small_acked: bool| {
+        if run.len() >= thresholds.pmtu_lost_packets && small_acked {
+            findings.push(Finding {
+                kind: AnomalyKind::PmtuBlackHole,
+                start: run[0],
+                end: run[run.len() - 1],
+                description: format!(
+                    "{} packets larger than {} bytes were lost while smaller packets were acknowledged",
+                    run.len(),
+                    thresholds.pmtu_base_size
+                ),
+            });
+        }
+
+        run.clear();
+    };
+
+    for (number, pkt) in space {
+        let Some(size) = pkt.raw.as_ref().and_then(|raw| raw.length) else {
+            continue;
+        };
+
+        if size > thresholds.pmtu_base_size {
+            if lost.contains(number) {
+                run.push(pkt.created_time);
+            } else if pkt.acked == Some(true) {
+                flush(&mut run, small_acked);
+                small_acked = false;
+            }
+        } else if pkt.acked == Some(true) && !run.is_empty() {
+            small_acked = true;
+        }
+    }
+
+    flush(&mut run, small_acked);
+}
+
+fn rtt_inflation(
+    ds: &Datastore, thresholds: &AnomalyThresholds, findings: &mut Vec<Finding>,
+) {
+    let mut min_rtts = ds.local_min_rtt.iter().peekable();
+    let mut min_rtt = f32::INFINITY;
+
+    // (start, last inflated sample, peak ratio)
+    let mut episode: Option<(f64, f64, f32)> = None;
+
+    let mut flush = |episode: (f64, f64, f32), min_rtt: f32| {
+        if episode.1 - episode.0 >= thresholds.rtt_inflation_duration {
+            findings.push(Finding {
+                kind: AnomalyKind::RttInflation,
+                start: episode.0,
+                end: episode.1,
+                description: format!(
+                    "Latest RTT up to {:.1}x the minimum RTT of {:.1} ms",
+                    episode.2, min_rtt
+                ),
+            });
+        }
+    };
+
+    for &(time, rtt) in &ds.local_latest_rtt {
+        // Use the logged minimum RTT when there is one, otherwise track it.
+        while let Some((_, min)) = min_rtts.next_if(|(t, _)| *t <= time) {
+            min_rtt = *min;
+        }
+
+        if ds.local_min_rtt.is_empty() {
+            min_rtt = min_rtt.min(rtt);
+        }
+
+        if !(min_rtt > 0.0 && min_rtt.is_finite()) {
+            continue;
+        }
+
+        let ratio = rtt / min_rtt;
+
+        episode = match episode {
+            _ if ratio < thresholds.rtt_inflation_ratio => {
+                if let Some(episode) = episode {
+                    flush(episode, min_rtt);
+                }
+
+                None
+            },
+
+            Some((start, _, peak)) => Some((start, time, peak.max(ratio))),
+
+            None => Some((time, time, ratio)),
+        };
+    }
+
+    if let Some(episode) = episode {
+        flush(episode, min_rtt);
+    }
+}
+
+fn idle_gaps(
+    ds: &Datastore, thresholds: &AnomalyThresholds, findings: &mut Vec<Finding>,
+) {
+    let mut times: Vec<f64> = ds
+        .packet_sent
+        .values()
+        .chain(ds.packet_received.values())
+        .flat_map(|space| space.values().map(|pkt| pkt.created_time))
+        .collect();
+
+    times.sort_by(f64::total_cmp);
+
+    for pair in times.windows(2) {
+        let gap = pair[1] - pair[0];
+
+        if gap >= thresholds.idle_gap {
+            findings.push(Finding {
+                kind: AnomalyKind::IdleGap,
+                start: pair[0],
+                end: pair[1],
+                description: format!(
+                    "No packets sent or received for {:.1} ms",
+                    gap
+                ),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datastore::LostPacket;
+    use crate::datastore::PacketInfoStub;
+    use qlog::events::RawInfo;
+
+    fn packet(number: u64, time: f64, size: u64, acked: bool) -> PacketInfoStub {
+        PacketInfoStub {
+            acked: acked.then_some(true),
+            raw: Some(RawInfo {
+                length: Some(size),
+                payload_length: None,
+                data: None,
+            }),
+            created_time: time,
+            send_at_time: None,
+            ty: PacketType::OneRtt,
+            number,
+        }
+    }
+
+    fn lost(number: u64, time: f64) -> LostPacket {
+        LostPacket {
+            time,
+            ty: PacketType::OneRtt,
+            number,
+            mtu_probe: false,
+        }
+    }
+
+    fn kinds(findings: &[Finding]) -> Vec<AnomalyKind> {
+        findings.iter().map(|f| f.kind).collect()
+    }
+
+    #[test]
+    fn spurious_and_black_hole() {
+        let mut ds = Datastore {
+            last_event_time: 1000.0,
+            ..Default::default()
+        };
+
+        // Packet 0 was declared lost but acked later. Packets 2 to 6 are
+        // large and lost, while the small packet 4 got through.
+        let space = ds.packet_sent.entry(PacketType::OneRtt).or_default();
+        space.insert(0, packet(0, 0.0, 1200, true));
+        space.insert(1, packet(1, 10.0, 1200, true));
+        for pn in [2, 3, 5, 6, 7] {
+            space.insert(pn, packet(pn, pn as f64 * 10.0, 1400, false));
+        }
+        space.insert(4, packet(4, 40.0, 100, true));
+
+        ds.lost_packets = [0, 2, 3, 5, 6, 7]
+            .into_iter()
+            .map(|pn| lost(pn, 100.0 + pn as f64))
+            .collect();
+
+        let findings = detect(&ds, &AnomalyThresholds::default());
+        assert_eq!(kinds(&findings), [
+            AnomalyKind::PmtuBlackHole,
+            AnomalyKind::SpuriousRetransmission,
+        ]);
+
+        assert_eq!((findings[0].start, findings[0].end), (20.0, 70.0));
+        assert_eq!((findings[1].start, findings[1].end), (100.0, 100.0));
+    }
+
+    #[test]
+    fn flow_control_stall() {
+        let mut ds = Datastore {
+            last_event_time: 1000.0,
+            sent_data_blocked: vec![(100.0, 1000), (150.0, 1000), (500.0, 2000)],
+            received_max_data: vec![(0.0, 1000), (300.0, 2000), (520.0, 3000)],
+            ..Default::default()
+        };
+
+        ds.sent_stream_data_blocked.insert(4, vec![(600.0, 500)]);
+
+        let findings = detect(&ds, &AnomalyThresholds::default());
+        assert_eq!(kinds(&findings), [
+            AnomalyKind::FlowControlStall,
+            AnomalyKind::FlowControlStall,
+        ]);
+
+        assert_eq!((findings[0].start, findings[0].end), (100.0, 300.0));
+        assert_eq!((findings[1].start, findings[1].end), (600.0, 1000.0));
+        assert!(findings[1].description.starts_with("Stream 4"));
+    }
+
+    #[test]
+    fn recovery() {
+        let ds = Datastore {
+            last_event_time: 5000.0,
+            local_cwnd: vec![
+                (0.0, 2000),
+                (10.0, 12000),
+                (2000.0, 2700),
+                (2100.0, 2700),
+                (2500.0, 5400),
+            ],
+            local_pto_count: vec![(100.0, 1), (200.0, 2), (400.0, 3), (800.0, 0)],
+            local_min_rtt: vec![(0.0, 10.0)],
+            local_latest_rtt: vec![
+                (0.0, 10.0),
+                (1000.0, 40.0),
+                (1600.0, 50.0),
+                (1700.0, 12.0),
+            ],
+            ..Default::default()
+        };
+
+        let findings = detect(&ds, &AnomalyThresholds::default());
+        assert_eq!(kinds(&findings), [
+            AnomalyKind::PtoStorm,
+            AnomalyKind::RttInflation,
+            AnomalyKind::PersistentCongestion,
+        ]);
+
+        assert_eq!((findings[0].start, findings[0].end), (100.0, 800.0));
+        assert_eq!((findings[1].start, findings[1].end), (1000.0, 1600.0));
+        assert_eq!(
+            findings[1].description,
+            "Latest RTT up to 5.0x the minimum RTT of 10.0 ms"
+        );
+        assert_eq!((findings[2].start, findings[2].end), (2000.0, 2500.0));
+    }
+
+    #[test]
+    fn idle_gap() {
+        let mut ds = Datastore::default();
+
+        let space = ds.packet_sent.entry(PacketType::OneRtt).or_default();
+        space.insert(0, packet(0, 0.0, 1200, true));
+        space.insert(1, packet(1, 3000.0, 1200, true));
+
+        let space = ds.packet_received.entry(PacketType::OneRtt).or_default();
+        space.insert(0, packet(0, 500.0, 1200, false));
+
+        let findings = detect(&ds, &AnomalyThresholds::default());
+        assert_eq!(kinds(&findings), [AnomalyKind::IdleGap]);
+        assert_eq!((findings[0].start, findings[0].end), (500.0, 3000.0));
+    }
+}
@@ -26,6 +26,8 @@
 
 //! Reporting (tables etc.)
 
+use std::collections::BTreeMap;
+
 use table_to_html::html::Attribute;
 use table_to_html::html::HtmlElement;
 use table_to_html::html::HtmlValue;
@@ -33,9 +35,19 @@ use table_to_html::html::HtmlVisitorMut;
 use table_to_html::HtmlTable;
 use tabled::Table;
 
+use crate::batch::ConnectionSummary;
 use crate::create_file_recursive;
+use crate::plots::chart_filepath;
+use crate::plots::waterfall::waterfall_rows;
+use crate::plots::waterfall::waterfall_svg;
+use crate::plots::waterfall::WaterfallPlotParams;
+use crate::plots::ChartId;
+use crate::plots::ChartOutputType;
+use crate::plots::ClampParams;
+use crate::reports::anomalies::detect;
 use crate::reports::events::sqlog_event_list;
 use crate::reports::text::request_timing_table;
+use crate::request_stub::NaOption;
 use crate::AppConfig;
 use crate::LogFileParseResult;
 use std::io::Write;
@@ -171,8 +183,10 @@ pub fn overview(log_file: &LogFileParseResult, config: &AppConfig) {
             that were present in a log file. It is possible to filter only specific
             SNIs for analysis using the qlog-dancer `--netlog-filter` option.</p>
             <p class ="center">Analysed session detailed information is presented in
-            <a href="closures.html">session terminations</a>
-            and <a href="requests.html">requests breakdown</a>.</p>
+            <a href="closures.html">session terminations</a>,
+            <a href="requests.html">requests breakdown</a>,
+            <a href="waterfall.html">request waterfall</a>
+            and <a href="anomalies.html">anomalies</a>.</p>
             "#.as_bytes()).unwrap();
 
     let all_table = HtmlTable::with_header(Vec::<Vec<String>>::from(
@@ -191,6 +205,34 @@ pub fn overview(log_file: &LogFileParseResult, config: &AppConfig) {
     )
     .unwrap();
 
+    if !log_file.details.protocol_selections.is_empty() {
+        file.write_all(
+            r#"
+            <h2 class="center">Protocol Selection</h2>
+            <p class="center">The jobs raced to connect each request, and why
+            the request did or didn't use HTTP/3.</p>
+            "#
+            .as_bytes(),
+        )
+        .unwrap();
+
+        let selection_table = HtmlTable::with_header(Vec::<Vec<String>>::from(
+            Table::builder(&log_file.details.protocol_selections),
+        ));
+        file.write_all(
+            inject_table_id_class(
+                &selection_table,
+                Some("protocol_selection".to_string()),
+                Some(
+                    "log-dancer-table cell-border hover compact order-column"
+                        .to_string(),
+                ),
+            )
+            .as_bytes(),
+        )
+        .unwrap();
+    }
+
     file.write_all(
         r#"
         </div>
@@ -406,6 +448,197 @@ pub fn event_list_html_from_sqlog(events: &[qlog::reader::Event]) -> String {
     )
 }
 
+pub fn waterfall(log_file: &LogFileParseResult, config: &AppConfig) {
+    let filename = format!("{}-reports/waterfall.html", config.filename);
+    let mut file = create_file_recursive(&filename).unwrap();
+
+    file.write_all(SESSIONS_STYLES.as_bytes()).unwrap();
+    file.write_all(
+        r#"<html>
+    <head><head>
+    <body>
+        <div>
+            <h1 class="center">Request Waterfall</h1>
+            <p class="center">This page shows the timeline of each request
+            in a session, similar to browser developer tools. Requests are
+            queued from discovery until they are sent, then wait for the first
+            response byte, and then transfer until the last response byte.
+            Priority updates and stream resets are marked on each request.</p>
+            "#
+        .as_bytes(),
+    )
+    .unwrap();
+
+    let params = WaterfallPlotParams {
+        width: 1600,
+        colors: AppConfig::colors(config.dark_mode),
+        display_chart_title: true,
+        display_legend: true,
+        ..Default::default()
+    };
+
+    for data in &log_file.data {
+        let rows = waterfall_rows(&data.datastore);
+        if rows.is_empty() {
+            continue;
+        }
+
+        let title = format!(
+            "Session {} {}",
+            NaOption::new(data.datastore.session_id),
+            data.datastore.host.as_deref().unwrap_or_default()
+        );
+
+        writeln!(file, r#"<div class="center">"#).unwrap();
+        file.write_all(waterfall_svg(&params, &title, &rows).as_bytes())
+            .unwrap();
+        writeln!(file, "</div>").unwrap();
+    }
+
+    file.write_all(
+        r#"
+        </div>
+    </body>
+    <html>"#
+            .as_bytes(),
+    )
+    .unwrap();
+}
+
+/// Time around a finding that is included in the linked chart, in ms.
+const ANOMALY_CHART_MARGIN: f64 = 100.0;
+
+pub fn anomalies(log_file: &LogFileParseResult, config: &AppConfig) {
+    let filename = format!("{}-reports/anomalies.html", config.filename);
+    let mut file = create_file_recursive(&filename).unwrap();
+
+    file.write_all(HTML_INCLUDES.as_bytes()).unwrap();
+    file.write_all(TABLE_INIT_SCRIPT.as_bytes()).unwrap();
+    file.write_all(SESSIONS_STYLES.as_bytes()).unwrap();
+    file.write_all(
+        r#"<html>
+    <head><head>
+    <body>
+        <div id="loading" class="center">Loading...</div>
+        <div id="tables" style="visibility: hidden">
+            <h1 class="center">Anomalies</h1>
+            <p class="center">This page lists conditions detected in each
+            session that are often worth a closer look, such as spurious
+            retransmissions, flow control stalls or idle periods. Detection is
+            based on heuristics, so findings are hints rather than proof of a
+            problem.</p>
+            <p class="center">Each finding links to the chart that best shows
+            it, if that chart was generated along with this report. Charts are
+            not generated by default. To zoom in on the surrounding time range,
+            run qlog-dancer with the <code>--charts</code>, <code>-s</code> and
+            <code>-e</code> values shown.</p>
+            "#
+        .as_bytes(),
+    )
+    .unwrap();
+
+    let ty = ChartOutputType::Png {
+        output_dir: config.charts_dir.clone(),
+        cwnd_y_max: config.cwnd_y_max,
+        stream_y_max: config.stream_y_max,
+    };
+
+    // The charts generated in this run cover the configured time range.
+    let chart_clamp = ClampParams {
+        start: config.start,
+        end: config.end,
+        stream_y_max: config.stream_y_max,
+    };
+
+    for (i, data) in log_file.data.iter().enumerate() {
+        let ds = &data.datastore;
+        let findings = detect(ds, &config.anomaly_thresholds);
+
+        writeln!(
+            file,
+            r#"<h2 class="center">Session ID: {}, {:?}, {}</h2>"#,
+            NaOption::new(ds.session_id),
+            ds.application_proto,
+            ds.host.as_deref().unwrap_or_default()
+        )
+        .unwrap();
+
+        if findings.is_empty() {
+            writeln!(file, r#"<p class="center">No anomalies found.</p>"#)
+                .unwrap();
+            continue;
+        }
+
+        writeln!(
+            file,
+            "<table id='anomalies{i}' class='log-dancer-table cell-border hover compact order-column'>
+            <thead><tr><th>Anomaly</th><th>Start (ms)</th><th>End (ms)</th><th>Description</th><th>Chart</th></tr></thead>
+            <tbody>"
+        )
+        .unwrap();
+
+        for finding in &findings {
+            let clamp = ClampParams {
+                start: Some(
+                    (finding.start - ANOMALY_CHART_MARGIN).floor().max(0.0),
+                ),
+                end: Some((finding.end + ANOMALY_CHART_MARGIN).ceil()),
+                stream_y_max: config.stream_y_max,
+            };
+
+            let chart = finding.kind.chart();
+
+            let (charts, generated) = match chart {
+                ChartId::ConnOverview => ("overview", config.plot_conn_overview),
+                ChartId::FlowControl =>
+                    ("conn-flow", config.plot_conn_flow_control),
+                ChartId::PacketSent => ("overview", config.plot_pkt_sent),
+            };
+
+            let link = if generated {
+                format!(
+                    r#"<a href="../{}">{}</a> "#,
+                    chart_filepath(
+                        chart,
+                        &chart_clamp,
+                        &config.filename,
+                        ds,
+                        &ty
+                    ),
+                    chart.title()
+                )
+            } else {
+                String::new()
+            };
+
+            writeln!(
+                file,
+                r#"<tr><td>{}</td><td>{:.3}</td><td>{:.3}</td><td>{}</td><td>{}<code>--charts {} -s {} -e {}</code></td></tr>"#,
+                finding.kind,
+                finding.start,
+                finding.end,
+                finding.description,
+                link,
+                charts,
+                clamp.start.unwrap_or_default(),
+                clamp.end.unwrap_or_default(),
+            )
+            .unwrap();
+        }
+
+        writeln!(file, "</tbody></table>").unwrap();
+    }
+
+    file.write_all(
+        r#"
+        </div>
+    </body>
+    <html>"#
+            .as_bytes(),
+    )
+    .unwrap();
+}
+
 pub fn event_list(log_file: &LogFileParseResult, config: &AppConfig) {
     let filename = format!("{}-reports/event-list.html", config.filename);
     let mut file = create_file_recursive(&filename).unwrap();
@@ -657,3 +890,97 @@ impl HtmlVisitorMut for RequestTableDecorator {
         true
     }
 }
+
+/// Writes the summary of a batch run, including a table of all connections,
+/// to `filename`.
+pub fn batch_summary(
+    summaries: &[ConnectionSummary], failed: usize, histograms: &[String],
+    filename: &str,
+) -> std::io::Result<()> {
+    let mut close_reasons: BTreeMap<&str, usize> = BTreeMap::new();
+    for summary in summaries {
+        *close_reasons
+            .entry(summary.close_reason.as_deref().unwrap_or("n/a"))
+            .or_default() += 1;
+    }
+
+    let mut file = create_file_recursive(filename)?;
+
+    file.write_all(HTML_INCLUDES.as_bytes())?;
+    file.write_all(TABLE_INIT_SCRIPT.as_bytes())?;
+    file.write_all(SESSIONS_STYLES.as_bytes())?;
+    write!(
+        file,
+        r#"<html>
+    <head><head>
+    <body>
+        <div id="loading" class="center">Loading...</div>
+        <div id="tables" style="visibility: hidden">
+            <h1 class="center">Batch Summary</h1>
+            <p class="center">{} connections were analysed. {} files could not
+            be parsed and were skipped.</p>
+            <div class="center">"#,
+        summaries.len(),
+        failed
+    )?;
+
+    for histogram in histograms {
+        file.write_all(histogram.as_bytes())?;
+    }
+
+    file.write_all(
+        r#"
+            </div>
+            <h2 class="center">Close Reasons</h2>"#
+            .as_bytes(),
+    )?;
+
+    let mut reasons_table = vec![vec!["Reason".to_string(), "Count".to_string()]];
+    reasons_table.extend(
+        close_reasons
+            .iter()
+            .map(|(reason, count)| vec![reason.to_string(), count.to_string()]),
+    );
+
+    file.write_all(
+        inject_table_id_class(
+            &HtmlTable::with_header(reasons_table),
+            Some("close_reasons".to_string()),
+            Some(
+                "log-dancer-table cell-border hover compact order-column"
+                    .to_string(),
+            ),
+        )
+        .as_bytes(),
+    )?;
+
+    file.write_all(
+        r#"
+            <h2 class="center">Connections</h2>"#
+            .as_bytes(),
+    )?;
+
+    let mut connections_table =
+        vec![ConnectionSummary::COLUMNS.map(String::from).to_vec()];
+    connections_table.extend(summaries.iter().map(ConnectionSummary::row));
+
+    file.write_all(
+        inject_table_id_class(
+            &HtmlTable::with_header(connections_table),
+            Some("connections".to_string()),
+            Some(
+                "log-dancer-table cell-border hover compact order-column"
+                    .to_string(),
+            ),
+        )
+        .as_bytes(),
+    )?;
+
+    file.write_all(
+        r#"
+        </div>
+    </body>
+    <html>"#
+            .as_bytes(),
+    )
+}
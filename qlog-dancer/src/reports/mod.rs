@@ -28,12 +28,21 @@
 
 use events::sqlog_event_list;
 use tabled::settings::Style;
+use tabled::Table;
 
 use crate::config::AppConfig;
 use crate::LogFileParseResult;
 
 pub fn report(log_file: &LogFileParseResult, config: &AppConfig) {
     if config.report_text {
+        if !log_file.details.protocol_selections.is_empty() {
+            let mut table = Table::new(&log_file.details.protocol_selections);
+            table.with(Style::sharp());
+            println!("Protocol selection");
+            println!("{}", table);
+            println!();
+        }
+
         for data in &log_file.data {
             if let Some(table) = text::request_timing_table(data, config) {
                 println!(
@@ -52,6 +61,16 @@ pub fn report(log_file: &LogFileParseResult, config: &AppConfig) {
             text::print_stats(&data.datastore, &config.stats_config);
             println!();
 
+            let findings =
+                anomalies::detect(&data.datastore, &config.anomaly_thresholds);
+            if !findings.is_empty() {
+                let mut table = Table::new(findings);
+                table.with(Style::sharp());
+                println!("Anomalies");
+                println!("{}", table);
+                println!();
+            }
+
             match &data.raw {
                 crate::RawLogEvents::QlogJson { events: _ } => todo!(),
                 crate::RawLogEvents::QlogJsonSeq { events } => {
@@ -73,10 +92,13 @@ pub fn report(log_file: &LogFileParseResult, config: &AppConfig) {
         html::overview(log_file, config);
         html::closures(log_file, config);
         html::requests(log_file, config);
+        html::waterfall(log_file, config);
+        html::anomalies(log_file, config);
         html::event_list(log_file, config);
     }
 }
 
+pub mod anomalies;
 mod events;
 pub mod html;
 mod text;
@@ -84,6 +84,7 @@ pub struct HttpRequestStub {
     pub client_pri_hdr: NaOption<String>,
     pub server_pri_hdr: NaOption<String>,
     pub priority_updates: Vec<String>,
+    pub time_priority_updates: Vec<f64>,
 
     pub time_discovery: Option<f64>,
     pub time_first_headers_rx: Option<f64>,
@@ -97,6 +98,9 @@ pub struct HttpRequestStub {
 
     pub time_fin_rx: Option<f64>,
 
+    /// Time of the first stream reset, in either direction.
+    pub time_reset: Option<f64>,
+
     // TODO, Option<u64>
     pub time_data_rx_set: Vec<(f64, u64)>,
     pub time_data_tx_set: Vec<(f64, u64)>,
@@ -52,6 +52,7 @@ pub struct SeriesStore {
     pub onertt_packet_sent: Vec<QlogPointu64>,
     pub onertt_packet_sent_aggregate_count: Vec<QlogPointu64>,
     pub onertt_packet_lost_hacky: Vec<QlogPointu64>,
+    pub onertt_packet_lost: Vec<QlogPointu64>,
     pub onertt_packet_lost_aggregate_count: Vec<QlogPointu64>,
     pub onertt_packet_delivered_aggregate_count: Vec<QlogPointu64>,
 
@@ -59,6 +60,8 @@ pub struct SeriesStore {
 
     pub netlog_missing_packets: Vec<f64>,
 
+    pub connection_events: Vec<(f64, String)>,
+
     // this one is a little different, delta as a function of packet number
     pub onertt_packet_created_sent_delta: Vec<(u64, f64)>,
 
@@ -365,6 +368,19 @@ impl SeriesStore {
         }
     }
 
+    fn packet_lost(&mut self, data_store: &Datastore) {
+        for lost in &data_store.lost_packets {
+            if lost.ty == crate::PacketType::OneRtt {
+                self.onertt_packet_lost.push((lost.time, lost.number));
+            }
+        }
+    }
+
+    fn connection_events(&mut self, data_store: &Datastore) {
+        self.connection_events
+            .clone_from(&data_store.connection_events);
+    }
+
     fn sent_stream_max_data(&mut self, data_store: &Datastore) {
         for (stream, points) in
             &data_store.sent_stream_max_data_tracker.per_stream
@@ -742,6 +758,8 @@ impl SeriesStore {
         self.packet_sent(data_store);
         self.packet_recv(data_store);
         self.missing_packets(data_store);
+        self.packet_lost(data_store);
+        self.connection_events(data_store);
 
         self.sent_stream_max_data(data_store);
         self.received_stream_max_data(data_store);
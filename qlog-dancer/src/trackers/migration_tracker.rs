@@ -0,0 +1,232 @@
+// Copyright (C) 2026, Cloudflare, Inc.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are
+// met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//
+//     * Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in the
+//       documentation and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS
+// IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO,
+// THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR
+// PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+
+use netlog::quic;
+use netlog::quic::TransportParameters;
+use netlog::Event;
+use netlog::EventHeader;
+
+/// Links connection migrations to the QUIC session they migrate. Chrome logs
+/// migrations against a QUIC_CONNECTION_MIGRATION source of their own, and
+/// only the result of a migration names the connection ID of the session.
+#[derive(Debug, Default)]
+pub struct MigrationTracker {
+    /// Session source of each connection ID used by the server.
+    session_cids: HashMap<String, i64>,
+    /// Triggers of migrations without a result yet, per migration source.
+    triggers: BTreeMap<i64, Vec<(EventHeader, Event)>>,
+}
+
+impl MigrationTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the session that an event belongs to, with the events to
+    /// consume for it. Migration triggers are held until the result of the
+    /// migration is known, other events belong to their own source.
+    pub fn link(
+        &mut self, ev_hdr: EventHeader, event: Event,
+    ) -> Option<(i64, Vec<(EventHeader, Event)>)> {
+        let id = ev_hdr.source.id;
+
+        let cid = match &event {
+            Event::Quic(quic::Event::QuicSession(e)) =>
+                Some(e.params.connection_id.clone()),
+
+            Event::Quic(quic::Event::QuicSessionTransportParametersReceived(
+                e,
+            )) =>
+                TransportParameters::from(
+                    e.params.quic_transport_parameters.clone(),
+                )
+                .initial_source_connection_id,
+
+            Event::Quic(
+                quic::Event::QuicSessionNewConnectionIdFrameReceived(e),
+            ) => Some(e.params.connection_id.clone()),
+
+            Event::Quic(quic::Event::QuicConnectionMigrationTriggered(_)) => {
+                self.triggers.entry(id).or_default().push((ev_hdr, event));
+                return None;
+            },
+
+            Event::Quic(quic::Event::QuicConnectionMigrationSuccess(e)) => {
+                let session_id = self.session_cids.get(&e.params.connection_id);
+                return self.link_result(session_id.copied(), ev_hdr, event);
+            },
+
+            Event::Quic(quic::Event::QuicConnectionMigrationFailure(e)) => {
+                let session_id = self.session_cids.get(&e.params.connection_id);
+                return self.link_result(session_id.copied(), ev_hdr, event);
+            },
+
+            _ => None,
+        };
+
+        if let Some(cid) = cid {
+            self.session_cids.insert(cid, id);
+        }
+
+        Some((id, vec![(ev_hdr, event)]))
+    }
+
+    fn link_result(
+        &mut self, session_id: Option<i64>, ev_hdr: EventHeader, event: Event,
+    ) -> Option<(i64, Vec<(EventHeader, Event)>)> {
+        let mut events =
+            self.triggers.remove(&ev_hdr.source.id).unwrap_or_default();
+
+        // Without a known session, the migration can't be shown anywhere.
+        let session_id = session_id?;
+
+        events.push((ev_hdr, event));
+
+        Some((session_id, events))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SESSION_ID: i64 = 1;
+    const MIGRATION_ID: i64 = 2;
+
+    fn hdr(id: i64, time: u64) -> EventHeader {
+        let mut hdr = EventHeader::default();
+        hdr.source.id = id;
+        hdr.time_num = time;
+        hdr
+    }
+
+    fn session(tracker: &mut MigrationTracker) {
+        let mut ev = quic::QuicSessionEvent::default();
+        ev.params.connection_id = "0102030405060708".to_string();
+
+        let linked = tracker.link(
+            hdr(SESSION_ID, 0),
+            Event::Quic(quic::Event::QuicSession(ev)),
+        );
+        assert_eq!(linked.map(|(id, events)| (id, events.len())), Some((1, 1)));
+    }
+
+    fn trigger(tracker: &mut MigrationTracker, time: u64) {
+        let mut ev = quic::QuicConnectionMigrationTriggeredEvent::default();
+        ev.params.trigger = "OnNetworkDisconnected".to_string();
+
+        let linked = tracker.link(
+            hdr(MIGRATION_ID, time),
+            Event::Quic(quic::Event::QuicConnectionMigrationTriggered(ev)),
+        );
+        assert!(linked.is_none());
+    }
+
+    fn success(
+        tracker: &mut MigrationTracker, time: u64, cid: &str,
+    ) -> Option<(i64, Vec<u64>)> {
+        let mut ev = quic::QuicConnectionMigrationSuccessEvent::default();
+        ev.params.connection_id = cid.to_string();
+
+        tracker
+            .link(
+                hdr(MIGRATION_ID, time),
+                Event::Quic(quic::Event::QuicConnectionMigrationSuccess(ev)),
+            )
+            .map(|(id, events)| {
+                (id, events.iter().map(|(hdr, _)| hdr.time_num).collect())
+            })
+    }
+
+    #[test]
+    fn migration_of_initial_connection_id() {
+        let mut tracker = MigrationTracker::new();
+        session(&mut tracker);
+        trigger(&mut tracker, 10);
+
+        assert_eq!(
+            success(&mut tracker, 20, "0102030405060708"),
+            Some((SESSION_ID, vec![10, 20]))
+        );
+    }
+
+    #[test]
+    fn migration_of_issued_connection_id() {
+        let mut tracker = MigrationTracker::new();
+        session(&mut tracker);
+
+        let mut ev = quic::QuicSessionTransportParametersReceivedEvent::default();
+        ev.params.quic_transport_parameters = "[Server legacy[version 00000001] \
+             [chosen_version 00000001 other_versions 00000001] \
+             initial_source_connection_id c0ffee0011223344 \
+             max_idle_timeout 30000]"
+            .to_string();
+        tracker.link(
+            hdr(SESSION_ID, 1),
+            Event::Quic(quic::Event::QuicSessionTransportParametersReceived(ev)),
+        );
+
+        let mut ev =
+            quic::QuicSessionNewConnectionIdFrameReceivedEvent::default();
+        ev.params.connection_id = "a1a2a3a4a5a6a7a8".to_string();
+        ev.params.sequence_number = 1;
+        tracker.link(
+            hdr(SESSION_ID, 2),
+            Event::Quic(quic::Event::QuicSessionNewConnectionIdFrameReceived(ev)),
+        );
+
+        trigger(&mut tracker, 10);
+        assert_eq!(
+            success(&mut tracker, 20, "c0ffee0011223344"),
+            Some((SESSION_ID, vec![10, 20]))
+        );
+
+        trigger(&mut tracker, 30);
+        assert_eq!(
+            success(&mut tracker, 40, "a1a2a3a4a5a6a7a8"),
+            Some((SESSION_ID, vec![30, 40]))
+        );
+    }
+
+    #[test]
+    fn migration_of_unknown_session() {
+        let mut tracker = MigrationTracker::new();
+        session(&mut tracker);
+        trigger(&mut tracker, 10);
+
+        assert_eq!(success(&mut tracker, 20, "ffff"), None);
+
+        // The triggers of the unknown migration are dropped.
+        trigger(&mut tracker, 30);
+        assert_eq!(
+            success(&mut tracker, 40, "0102030405060708"),
+            Some((SESSION_ID, vec![30, 40]))
+        );
+    }
+}
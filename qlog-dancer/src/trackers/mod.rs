@@ -24,8 +24,13 @@
 // NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
 // SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+pub mod migration_tracker;
+pub mod protocol_selection_tracker;
 pub mod stream_buffer_tracker;
 pub mod stream_max_tracker;
 
+pub use migration_tracker::MigrationTracker;
+pub use protocol_selection_tracker::ProtocolSelection;
+pub use protocol_selection_tracker::ProtocolSelectionTracker;
 pub use stream_buffer_tracker::StreamBufferTracker;
 pub use stream_max_tracker::StreamMaxTracker;
@@ -0,0 +1,440 @@
+// Copyright (C) 2026, Cloudflare, Inc.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are
+// met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//
+//     * Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in the
+//       documentation and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS
+// IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO,
+// THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR
+// PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+
+use netlog::http;
+use netlog::socket;
+use netlog::Event;
+use netlog::EventHeader;
+use tabled::Tabled;
+
+/// A stream job, which connects a request to a server over a single protocol.
+#[derive(Debug, Default)]
+struct StreamJob {
+    ty: Option<String>,
+    using_quic: bool,
+    delay: Option<u64>,
+    orphaned: bool,
+    bound_to_request: bool,
+    net_error: Option<i64>,
+    socket_id: Option<i64>,
+}
+
+/// A stream job controller, which races the jobs of a request.
+#[derive(Debug, Default)]
+struct JobController {
+    url: Option<String>,
+    alt_svcs: Vec<(String, bool)>,
+    jobs: Vec<i64>,
+}
+
+/// The protocol that a request was sent over, and why.
+#[derive(Clone, Debug, PartialEq, Tabled)]
+pub struct ProtocolSelection {
+    #[tabled(rename = "ID")]
+    pub controller_id: i64,
+    #[tabled(rename = "URL")]
+    pub url: String,
+    #[tabled(rename = "Alt-Svc")]
+    pub alt_svc: String,
+    #[tabled(rename = "Jobs")]
+    pub jobs: String,
+    #[tabled(rename = "Protocol")]
+    pub protocol: String,
+    #[tabled(rename = "Reason")]
+    pub reason: String,
+}
+
+/// Tracks the stream jobs that Chrome races for each request, to explain why
+/// a request didn't use HTTP/3. Jobs and sockets are netlog sources of their
+/// own, so they are linked to controllers by source dependencies.
+#[derive(Debug, Default)]
+pub struct ProtocolSelectionTracker {
+    controllers: BTreeMap<i64, JobController>,
+    jobs: BTreeMap<i64, StreamJob>,
+    /// Negotiated ALPN protocol per socket source.
+    next_protos: BTreeMap<i64, String>,
+}
+
+impl ProtocolSelectionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_event(&mut self, ev_hdr: &EventHeader, event: &Event) {
+        let id = ev_hdr.source.id;
+
+        match event {
+            Event::Http(http::Event::HttpStreamJobController(e)) => {
+                self.controllers.entry(id).or_default().url =
+                    e.params.url.clone();
+            },
+
+            Event::Http(http::Event::HttpStreamJobControllerAltSvcFound(e)) => {
+                self.controllers
+                    .entry(id)
+                    .or_default()
+                    .alt_svcs
+                    .push((e.params.alt_svc.clone(), e.params.is_broken));
+            },
+
+            Event::Http(http::Event::HttpStreamRequestStartedJob(e)) => {
+                self.link_job(id, e.params.source_dependency.id);
+            },
+
+            Event::Http(http::Event::HttpStreamJob(e)) => {
+                let job = self.jobs.entry(id).or_default();
+                job.ty = e.params.ty.clone();
+                job.using_quic = e.params.using_quic.unwrap_or_default();
+
+                if let Some(controller) = &e.params.source_dependency {
+                    self.link_job(controller.id, id);
+                }
+            },
+
+            Event::Http(http::Event::HttpStreamJobDelayed(e)) => {
+                self.jobs.entry(id).or_default().delay = e.params.delay;
+            },
+
+            Event::Http(http::Event::HttpStreamJobOrphaned) => {
+                self.jobs.entry(id).or_default().orphaned = true;
+            },
+
+            Event::Http(http::Event::HttpStreamJobBoundToRequest(_)) => {
+                self.jobs.entry(id).or_default().bound_to_request = true;
+            },
+
+            Event::Http(http::Event::HttpStreamJobInitConnectionEnd(e)) =>
+                if let Some(job) = self.jobs.get_mut(&id) {
+                    job.net_error = e.params.net_error;
+                },
+
+            Event::Socket(socket::Event::SocketPoolBoundToSocket(e)) =>
+                if let Some(job) = self.jobs.get_mut(&id) {
+                    job.socket_id = Some(e.params.source_dependency.id);
+                },
+
+            Event::Socket(socket::Event::SslConnectEnd(e)) => {
+                if let Some(proto) = &e.params.next_proto {
+                    self.next_protos.insert(id, proto.clone());
+                }
+            },
+
+            _ => (),
+        }
+    }
+
+    fn link_job(&mut self, controller_id: i64, job_id: i64) {
+        let controller = self.controllers.entry(controller_id).or_default();
+
+        if !controller.jobs.contains(&job_id) {
+            controller.jobs.push(job_id);
+        }
+    }
+
+    /// Returns the protocol selection of each controller that started a job.
+    /// Net errors are named using `net_errors`, the netlog constants keyed by
+    /// error code.
+    pub fn selections(
+        &self, net_errors: &HashMap<i64, String>,
+    ) -> Vec<ProtocolSelection> {
+        let net_error =
+            |e: i64| net_errors.get(&e).cloned().unwrap_or_else(|| e.to_string());
+
+        let mut ret = vec![];
+
+        for (controller_id, controller) in &self.controllers {
+            let jobs: Vec<&StreamJob> = controller
+                .jobs
+                .iter()
+                .filter_map(|id| self.jobs.get(id))
+                .collect();
+
+            if jobs.is_empty() {
+                continue;
+            }
+
+            let alt_svc = controller
+                .alt_svcs
+                .iter()
+                .map(|(alt_svc, is_broken)| {
+                    if *is_broken {
+                        format!("{} (broken)", alt_svc)
+                    } else {
+                        alt_svc.clone()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            let job_descriptions = jobs
+                .iter()
+                .map(|job| {
+                    let mut details = vec![];
+
+                    if job.using_quic {
+                        details.push("QUIC".to_string());
+                    }
+
+                    if let Some(delay) = job.delay {
+                        details.push(format!("delayed {} ms", delay));
+                    }
+
+                    if let Some(e) = job.net_error {
+                        details.push(format!("failed {}", net_error(e)));
+                    }
+
+                    if job.orphaned {
+                        details.push("orphaned".to_string());
+                    }
+
+                    let ty = job.ty.as_deref().unwrap_or("job");
+
+                    if details.is_empty() {
+                        ty.to_string()
+                    } else {
+                        format!("{} ({})", ty, details.join(", "))
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            let winner = jobs.iter().find(|job| job.bound_to_request);
+
+            let protocol = match winner {
+                Some(job) if job.using_quic => "HTTP/3".to_string(),
+
+                Some(job) => match job
+                    .socket_id
+                    .and_then(|id| self.next_protos.get(&id))
+                    .map(String::as_str)
+                {
+                    Some("h2") => "HTTP/2".to_string(),
+                    Some("http/1.1") => "HTTP/1.1".to_string(),
+                    Some(other) => other.to_string(),
+                    None => "TCP".to_string(),
+                },
+
+                None => "-".to_string(),
+            };
+
+            let quic_jobs: Vec<&&StreamJob> =
+                jobs.iter().filter(|job| job.using_quic).collect();
+
+            let reason = if winner.is_some_and(|job| job.using_quic) {
+                "QUIC job succeeded".to_string()
+            } else if let Some(e) = quic_jobs.iter().find_map(|job| job.net_error)
+            {
+                format!("QUIC job failed with {}", net_error(e))
+            } else if !quic_jobs.is_empty() && winner.is_some() {
+                "TCP job won the race".to_string()
+            } else if !quic_jobs.is_empty() {
+                "no job completed".to_string()
+            } else if controller.alt_svcs.is_empty() {
+                "no Alt-Svc known for origin".to_string()
+            } else if controller.alt_svcs.iter().all(|(_, broken)| *broken) {
+                "QUIC marked broken for origin".to_string()
+            } else {
+                "Alt-Svc not used".to_string()
+            };
+
+            ret.push(ProtocolSelection {
+                controller_id: *controller_id,
+                url: controller.url.clone().unwrap_or_default(),
+                alt_svc,
+                jobs: job_descriptions,
+                protocol,
+                reason,
+            });
+        }
+
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hdr(id: i64) -> EventHeader {
+        let mut hdr = EventHeader::default();
+        hdr.source.id = id;
+        hdr
+    }
+
+    fn controller(tracker: &mut ProtocolSelectionTracker, id: i64) {
+        let mut ev = http::HttpStreamJobControllerEvent::default();
+        ev.params.url = Some("https://example.com/".to_string());
+
+        tracker.add_event(
+            &hdr(id),
+            &Event::Http(http::Event::HttpStreamJobController(ev)),
+        );
+    }
+
+    fn alt_svc(tracker: &mut ProtocolSelectionTracker, id: i64, broken: bool) {
+        let mut ev = http::HttpStreamJobControllerAltSvcFoundEvent::default();
+        ev.params.alt_svc = "h3 example.com:443".to_string();
+        ev.params.is_broken = broken;
+
+        tracker.add_event(
+            &hdr(id),
+            &Event::Http(http::Event::HttpStreamJobControllerAltSvcFound(ev)),
+        );
+    }
+
+    fn job(
+        tracker: &mut ProtocolSelectionTracker, controller_id: i64, id: i64,
+        ty: &str, using_quic: bool,
+    ) {
+        let mut ev = http::HttpStreamRequestStartedJobEvent::default();
+        ev.params.source_dependency.id = id;
+
+        tracker.add_event(
+            &hdr(controller_id),
+            &Event::Http(http::Event::HttpStreamRequestStartedJob(ev)),
+        );
+
+        let mut ev = http::HttpStreamJobEvent::default();
+        ev.params.ty = Some(ty.to_string());
+        ev.params.using_quic = Some(using_quic);
+
+        tracker.add_event(&hdr(id), &Event::Http(http::Event::HttpStreamJob(ev)));
+    }
+
+    fn bind(tracker: &mut ProtocolSelectionTracker, id: i64) {
+        tracker.add_event(
+            &hdr(id),
+            &Event::Http(http::Event::HttpStreamJobBoundToRequest(
+                http::HttpStreamJobBoundToRequestEvent::default(),
+            )),
+        );
+    }
+
+    fn alpn(
+        tracker: &mut ProtocolSelectionTracker, job_id: i64, socket_id: i64,
+        proto: &str,
+    ) {
+        let mut ev = socket::SocketPoolBoundToSocketEvent::default();
+        ev.params.source_dependency.id = socket_id;
+
+        tracker.add_event(
+            &hdr(job_id),
+            &Event::Socket(socket::Event::SocketPoolBoundToSocket(ev)),
+        );
+
+        let mut ev = socket::SslConnectEndEvent::default();
+        ev.params.next_proto = Some(proto.to_string());
+
+        tracker.add_event(
+            &hdr(socket_id),
+            &Event::Socket(socket::Event::SslConnectEnd(ev)),
+        );
+    }
+
+    #[test]
+    fn quic_job_won() {
+        let mut tracker = ProtocolSelectionTracker::new();
+        controller(&mut tracker, 1);
+        alt_svc(&mut tracker, 1, false);
+        job(&mut tracker, 1, 2, "main", false);
+        job(&mut tracker, 1, 3, "alternative", true);
+        bind(&mut tracker, 3);
+
+        let selections = tracker.selections(&HashMap::new());
+        assert_eq!(selections.len(), 1);
+        assert_eq!(selections[0].url, "https://example.com/");
+        assert_eq!(selections[0].jobs, "main, alternative (QUIC)");
+        assert_eq!(selections[0].protocol, "HTTP/3");
+        assert_eq!(selections[0].reason, "QUIC job succeeded");
+    }
+
+    #[test]
+    fn quic_job_failed() {
+        let mut tracker = ProtocolSelectionTracker::new();
+        controller(&mut tracker, 1);
+        alt_svc(&mut tracker, 1, false);
+        job(&mut tracker, 1, 2, "main", false);
+        job(&mut tracker, 1, 3, "alternative", true);
+
+        let mut ev = http::HttpStreamJobInitConnectionEndEvent::default();
+        ev.params.net_error = Some(-356);
+        tracker.add_event(
+            &hdr(3),
+            &Event::Http(http::Event::HttpStreamJobInitConnectionEnd(ev)),
+        );
+
+        bind(&mut tracker, 2);
+        alpn(&mut tracker, 2, 4, "h2");
+
+        let net_errors =
+            HashMap::from([(-356, "ERR_QUIC_PROTOCOL_ERROR".to_string())]);
+
+        let selections = tracker.selections(&net_errors);
+        assert_eq!(
+            selections[0].jobs,
+            "main, alternative (QUIC, failed ERR_QUIC_PROTOCOL_ERROR)"
+        );
+        assert_eq!(selections[0].protocol, "HTTP/2");
+        assert_eq!(
+            selections[0].reason,
+            "QUIC job failed with ERR_QUIC_PROTOCOL_ERROR"
+        );
+    }
+
+    #[test]
+    fn quic_broken() {
+        let mut tracker = ProtocolSelectionTracker::new();
+        controller(&mut tracker, 1);
+        alt_svc(&mut tracker, 1, true);
+        job(&mut tracker, 1, 2, "main", false);
+        bind(&mut tracker, 2);
+        alpn(&mut tracker, 2, 4, "http/1.1");
+
+        let selections = tracker.selections(&HashMap::new());
+        assert_eq!(selections[0].alt_svc, "h3 example.com:443 (broken)");
+        assert_eq!(selections[0].protocol, "HTTP/1.1");
+        assert_eq!(selections[0].reason, "QUIC marked broken for origin");
+    }
+
+    #[test]
+    fn no_alt_svc() {
+        let mut tracker = ProtocolSelectionTracker::new();
+        controller(&mut tracker, 1);
+        job(&mut tracker, 1, 2, "main", false);
+        bind(&mut tracker, 2);
+
+        // Controllers that didn't start a job are skipped.
+        controller(&mut tracker, 5);
+
+        let selections = tracker.selections(&HashMap::new());
+        assert_eq!(selections.len(), 1);
+        assert_eq!(selections[0].protocol, "TCP");
+        assert_eq!(selections[0].reason, "no Alt-Svc known for origin");
+    }
+}
@@ -25,6 +25,7 @@
 // SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::config::AppConfig;
+use crate::export;
 use crate::plots::congestion_control::plot_cc_plot;
 use crate::plots::conn_flow_control::plot_conn_flow_control_canvas;
 use crate::plots::conn_overview::plot_main_plot;
@@ -40,6 +41,9 @@ use crate::plots::stream_multiplex::plot_stream_multiplexing;
 use crate::plots::stream_multiplex::MultiplexPlotsParams;
 use crate::plots::stream_sparks::plot_sparks;
 use crate::plots::stream_sparks::SparkPlotsParams;
+use crate::plots::waterfall;
+use crate::plots::waterfall::plot_waterfall_canvas;
+use crate::plots::waterfall::WaterfallPlotParams;
 use crate::plots::AreaMargin;
 use crate::plots::ChartMargin;
 use crate::plots::ChartOutputType;
@@ -654,6 +658,37 @@ impl QlogDancerWeb {
         }
     }
 
+    #[wasm_bindgen]
+    /// Returns the height in pixels needed to draw the request waterfall.
+    pub fn waterfall_height(&self) -> u32 {
+        waterfall::chart_height(&waterfall::waterfall_rows(&self.ds))
+    }
+
+    #[wasm_bindgen]
+    /// Draws the request waterfall into the provided canvas_id.
+    pub fn draw_waterfall(
+        &mut self, canvas_id: &str, display_legend: bool, x_start: Option<f32>,
+        x_end: Option<f32>,
+    ) {
+        let params = WaterfallPlotParams {
+            clamp: ClampParams {
+                start: x_start.map(f64::from),
+                end: x_end.map(f64::from),
+                stream_y_max: None,
+            },
+            width: 1042,
+            colors: AppConfig::colors(false),
+            display_chart_title: false,
+            display_legend,
+        };
+
+        plot_waterfall_canvas(
+            &params,
+            &waterfall::waterfall_rows(&self.ds),
+            canvas_id,
+        );
+    }
+
     #[wasm_bindgen]
     /// Draws the packet sent plot into the provided canvas_id.
     pub fn draw_packet_sent_plot(
@@ -842,6 +877,25 @@ impl QlogDancerWeb {
         target_div.set_inner_html(&event_table);
     }
 
+    #[wasm_bindgen]
+    /// Returns all plot series as JSON, or as a Vega-Lite specification if
+    /// `vega_lite` is set.
+    pub fn export_series(&self, vega_lite: bool) -> String {
+        let Some(ss) = &self.ss else {
+            return "{}".to_string();
+        };
+
+        let title = export::title(&self.ds);
+
+        let value = if vega_lite {
+            export::vega_lite(&title, ss)
+        } else {
+            export::series_json(&title, ss)
+        };
+
+        value.to_string()
+    }
+
     #[wasm_bindgen]
     /// TODO: example of accessing richer data from a parsed log
     pub fn packet_sent(&self) -> Vec<Pointu64> {
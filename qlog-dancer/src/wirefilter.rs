@@ -24,147 +24,621 @@
 // NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
 // SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+//! Wireshark-like filtering of qlog and netlog events.
+//!
+//! Events of both formats are mapped to the same set of fields, so that one
+//! expression can be used regardless of the log format:
+//!
+//! * `category` and `name`: the event category and type. Netlog event types are
+//!   lowercased, e.g. `quic_session_packet_sent`.
+//! * `time`: milliseconds since the start of the trace, or of the session for
+//!   netlogs.
+//! * `stream_id`, `packet_number`, `frame_type` and `error_code`: the values
+//!   carried by an event or its frames.
+//! * `header_name` and `header_value`: the HTTP header fields of an event.
+
+use qlog::events::http3::Http3Frame;
+use qlog::events::http3::HttpHeader;
 use qlog::events::quic::QuicFrame;
 use qlog::events::EventData;
 use qlog::reader::Event;
-use std::iter::FromIterator;
-use std::vec;
 use wirefilter::ExecutionContext;
+use wirefilter::Filter;
 use wirefilter::Scheme;
 use wirefilter::TypedArray;
 
 use crate::category_and_type_from_event;
+use crate::category_and_type_from_name;
+
+/// The values of an event that filter expressions can match on.
+#[derive(Debug, Default)]
+pub struct EventFields {
+    pub category: String,
+    pub name: String,
+    pub time: i64,
+    pub stream_id: Vec<i64>,
+    pub packet_number: Vec<i64>,
+    pub frame_type: Vec<String>,
+    pub error_code: Vec<i64>,
+    pub header_name: Vec<String>,
+    pub header_value: Vec<String>,
+}
+
+impl EventFields {
+    fn add_quic_frame(&mut self, frame: &QuicFrame) {
+        self.frame_type.push(quic_frame_type(frame).to_string());
+
+        match frame {
+            QuicFrame::ResetStream {
+                stream_id,
+                error_code,
+                ..
+            } |
+            QuicFrame::StopSending {
+                stream_id,
+                error_code,
+                ..
+            } => {
+                self.stream_id.push(*stream_id as i64);
+                self.error_code.extend(error_code.map(|e| e as i64));
+            },
+
+            QuicFrame::Stream { stream_id, .. } |
+            QuicFrame::MaxStreamData { stream_id, .. } |
+            QuicFrame::StreamDataBlocked { stream_id, .. } =>
+                self.stream_id.push(*stream_id as i64),
+
+            QuicFrame::ConnectionClose { error_code, .. } =>
+                self.error_code.extend(error_code.map(|e| e as i64)),
+
+            // other frames are not related to streams
+            _ => (),
+        }
+    }
+
+    fn add_quic_frames(&mut self, frames: &Option<Vec<QuicFrame>>) {
+        for frame in frames.iter().flatten() {
+            self.add_quic_frame(frame);
+        }
+    }
 
-fn stream_ids(event: &Event) -> TypedArray<'_, i64> {
-    let mut ids: TypedArray<i64> = TypedArray::new();
+    fn add_http3_frame(&mut self, stream_id: u64, frame: &Http3Frame) {
+        self.stream_id.push(stream_id as i64);
 
-    match event {
-        Event::Qlog(event) => match &event.data {
+        self.frame_type.push(http3_frame_type(frame).to_string());
+
+        if let Http3Frame::Headers { headers, .. } |
+        Http3Frame::PushPromise { headers, .. } = frame
+        {
+            for HttpHeader { name, value, .. } in headers {
+                self.header_name.push(name.clone().unwrap_or_default());
+                self.header_value.push(value.clone().unwrap_or_default());
+            }
+        }
+    }
+
+    /// Adds netlog headers, which are formatted as `name: value`.
+    fn add_netlog_headers(&mut self, headers: &[String]) {
+        for header in headers {
+            let (name, value) = header.split_once(": ").unwrap_or((header, ""));
+            self.header_name.push(name.to_string());
+            self.header_value.push(value.to_string());
+        }
+    }
+
+    pub fn from_qlog(event: &qlog::events::Event) -> Self {
+        let (category, name) = category_and_type_from_event(event);
+
+        let mut fields = EventFields {
+            category,
+            name,
+            time: event.time as i64,
+            ..Default::default()
+        };
+
+        match &event.data {
             EventData::QuicStreamDataMoved(v) =>
-                if let Some(id) = v.stream_id {
-                    ids.push(id as i64);
-                },
+                fields.stream_id.extend(v.stream_id.map(|id| id as i64)),
+
             EventData::QuicPacketSent(v) => {
-                if let Some(frames) = &v.frames {
-                    for frame in frames {
-                        match frame {
-                            QuicFrame::ResetStream { stream_id, .. } =>
-                                ids.push(*stream_id as i64),
-                            QuicFrame::StopSending { stream_id, .. } =>
-                                ids.push(*stream_id as i64),
-                            QuicFrame::Stream { stream_id, .. } =>
-                                ids.push(*stream_id as i64),
-                            QuicFrame::MaxStreamData { stream_id, .. } =>
-                                ids.push(*stream_id as i64),
-                            QuicFrame::StreamDataBlocked {
-                                stream_id, ..
-                            } => ids.push(*stream_id as i64),
-
-                            // other frames are not related to streams
-                            _ => (),
-                        }
-                    }
-                }
+                fields
+                    .packet_number
+                    .extend(v.header.packet_number.map(|pn| pn as i64));
+                fields.add_quic_frames(&v.frames);
+            },
+
+            EventData::QuicPacketReceived(v) => {
+                fields
+                    .packet_number
+                    .extend(v.header.packet_number.map(|pn| pn as i64));
+                fields.add_quic_frames(&v.frames);
             },
+
+            EventData::QuicPacketLost(v) => {
+                fields.packet_number.extend(
+                    v.header
+                        .as_ref()
+                        .and_then(|hdr| hdr.packet_number)
+                        .map(|pn| pn as i64),
+                );
+                fields.add_quic_frames(&v.frames);
+            },
+
+            EventData::QuicConnectionClosed(v) =>
+                fields.error_code.extend(v.error_code.map(|e| e as i64)),
+
             EventData::Http3StreamTypeSet(v) => {
-                ids.push(v.stream_id as i64);
+                fields.stream_id.push(v.stream_id as i64);
             },
             EventData::Http3FrameCreated(v) => {
-                ids.push(v.stream_id as i64);
+                fields.add_http3_frame(v.stream_id, &v.frame);
             },
             EventData::Http3FrameParsed(v) => {
-                ids.push(v.stream_id as i64);
+                fields.add_http3_frame(v.stream_id, &v.frame);
             },
 
-            // other events are not related to streams
+            // other events don't carry any of the fields
             _ => (),
-        },
+        }
+
+        fields
+    }
+
+    pub fn from_sqlog(event: &Event) -> Self {
+        match event {
+            Event::Qlog(event) => Self::from_qlog(event),
+
+            Event::Json(event) => {
+                let (category, name) = category_and_type_from_name(&event.name);
+
+                let mut fields = EventFields {
+                    category,
+                    name,
+                    time: event.time as i64,
+                    ..Default::default()
+                };
+
+                // Extended events have no schema, but commonly name a stream.
+                fields.stream_id.extend(event.data["stream_id"].as_i64());
+
+                fields
+            },
+        }
+    }
+
+    /// Returns the fields of a netlog event. Times are relative to
+    /// `session_start_time`.
+    pub fn from_netlog(
+        ev_hdr: &netlog::EventHeader, event: &netlog::Event,
+        session_start_time: u64,
+    ) -> Self {
+        let category = match event {
+            netlog::Event::Http(_) => "http",
+            netlog::Event::H2(_) => "http2",
+            netlog::Event::H3(_) => "http3",
+            netlog::Event::Quic(_) => "quic",
+            netlog::Event::Socket(_) => "socket",
+            netlog::Event::Dns(_) => "dns",
+        };
+
+        let mut fields = EventFields {
+            category: category.to_string(),
+            name: ev_hdr.ty_string.to_lowercase(),
+            time: ev_hdr.time_num.saturating_sub(session_start_time) as i64,
+            ..Default::default()
+        };
+
+        match event {
+            netlog::Event::Quic(ev) => fields.add_netlog_quic(ev),
+            netlog::Event::H2(ev) => fields.add_netlog_h2(ev),
+            netlog::Event::H3(ev) => fields.add_netlog_h3(ev),
+            netlog::Event::Http(ev) => fields.add_netlog_http(ev),
+            netlog::Event::Socket(ev) => fields.add_netlog_socket(ev),
+            netlog::Event::Dns(ev) => fields.add_netlog_dns(ev),
+        }
 
-        // TODO: try and fuzzy extract stream id
-        Event::Json(_event) => {},
+        fields
     }
 
-    ids
+    fn add_netlog_quic(&mut self, ev: &netlog::quic::Event) {
+        use netlog::quic::Event::*;
+
+        let frame_type = match ev {
+            QuicSessionAckFrameSent(_) | QuicSessionAckFrameReceived(_) =>
+                Some("ack"),
+            QuicSessionStreamFrameSent(_) | QuicSessionStreamFrameReceived(_) =>
+                Some("stream"),
+            QuicSessionStopSendingFrameSent(_) => Some("stop_sending"),
+            QuicSessionRstStreamFrameSent(_) |
+            QuicSessionRstStreamFrameReceived(_) => Some("reset_stream"),
+            QuicSessionBlockedFrameReceived(_) => Some("stream_data_blocked"),
+            QuicSessionWindowUpdateFrameSent(_) => Some("max_stream_data"),
+            QuicSessionCryptoFrameSent(_) | QuicSessionCryptoFrameReceived(_) =>
+                Some("crypto"),
+            QuicSessionHandshakeDoneFrameReceived(_) => Some("handshake_done"),
+            QuicSessionPathChallengeFrameSent(_) |
+            QuicSessionPathChallengeFrameReceived(_) => Some("path_challenge"),
+            QuicSessionPathResponseFrameSent(_) |
+            QuicSessionPathResponseFrameReceived(_) => Some("path_response"),
+            QuicSessionNewConnectionIdFrameReceived(_) =>
+                Some("new_connection_id"),
+            _ => None,
+        };
+
+        self.frame_type.extend(frame_type.map(str::to_string));
+
+        match ev {
+            QuicSessionUnauthenticatedPacketHeaderReceived(e) =>
+                self.packet_number.push(e.params.packet_number as i64),
+            QuicSessionPacketSent(e) =>
+                self.packet_number.push(e.params.packet_number as i64),
+            QuicSessionPacketLost(e) =>
+                self.packet_number.push(e.params.packet_number as i64),
+            QuicSessionPacketRetransmitted(e) => {
+                self.packet_number.push(e.params.old_packet_number as i64);
+                self.packet_number.push(e.params.new_packet_number as i64);
+            },
+
+            QuicSessionStreamFrameSent(e) =>
+                self.stream_id.push(e.params.stream_id as i64),
+            QuicSessionStreamFrameReceived(e) =>
+                self.stream_id.push(e.params.stream_id as i64),
+            QuicSessionBlockedFrameReceived(e) =>
+                self.stream_id.push(e.params.stream_id),
+            QuicSessionWindowUpdateFrameSent(e) =>
+                self.stream_id.push(e.params.stream_id),
+
+            QuicSessionStopSendingFrameSent(e) => {
+                self.stream_id.push(e.params.stream_id as i64);
+                self.error_code.push(e.params.quic_rst_stream_error as i64);
+            },
+            QuicSessionRstStreamFrameSent(e) => {
+                self.stream_id.push(e.params.stream_id as i64);
+                self.error_code.push(e.params.quic_rst_stream_error as i64);
+            },
+            QuicSessionRstStreamFrameReceived(e) => {
+                self.stream_id.push(e.params.stream_id as i64);
+                self.error_code.push(e.params.quic_rst_stream_error as i64);
+            },
+
+            QuicSessionClosed(e) => self.error_code.push(e.params.quic_error),
+
+            _ => (),
+        }
+    }
+
+    fn add_netlog_h2(&mut self, ev: &netlog::h2::Event) {
+        use netlog::h2::Event::*;
+
+        // HTTP/2 error codes are formatted as `code (NAME)`.
+        let error_code = |e: &str| {
+            e.split_whitespace()
+                .next()
+                .and_then(|code| code.parse::<i64>().ok())
+        };
+
+        match ev {
+            Http2SessionSendHeaders(e) => {
+                self.stream_id.push(e.params.stream_id.into());
+                self.frame_type.push("headers".to_string());
+                self.add_netlog_headers(&e.params.headers);
+            },
+            Http2SessionRecvHeaders(e) => {
+                self.stream_id.push(e.params.stream_id.into());
+                self.frame_type.push("headers".to_string());
+                self.add_netlog_headers(&e.params.headers);
+            },
+            Http2SessionSendData(e) => {
+                self.stream_id.push(e.params.stream_id.into());
+                self.frame_type.push("data".to_string());
+            },
+            Http2SessionRecvData(e) => {
+                self.stream_id.push(e.params.stream_id.into());
+                self.frame_type.push("data".to_string());
+            },
+            Http2SessionSendWindowUpdate(e) => {
+                self.stream_id.push(e.params.stream_id.into());
+                self.frame_type.push("window_update".to_string());
+            },
+            Http2SessionRecvWindowUpdate(e) => {
+                self.stream_id.push(e.params.stream_id.into());
+                self.frame_type.push("window_update".to_string());
+            },
+            Http2StreamUpdateSendWindow(e) =>
+                self.stream_id.push(e.params.stream_id.into()),
+            Http2StreamUpdateRecvWindow(e) =>
+                self.stream_id.push(e.params.stream_id.into()),
+            Http2StreamStalledByStreamSendWindow(e) =>
+                self.stream_id.push(e.params.stream_id.into()),
+            Http2SessionSendSettings(_) | Http2SessionRecvSetting(_) =>
+                self.frame_type.push("settings".to_string()),
+            Http2SessionPing(_) => self.frame_type.push("ping".to_string()),
+            Http2SessionSendRstStream(e) => {
+                self.stream_id.push(e.params.stream_id.into());
+                self.frame_type.push("rst_stream".to_string());
+                self.error_code.extend(error_code(&e.params.error_code));
+            },
+            Http2SessionRecvRstStream(e) => {
+                self.stream_id.push(e.params.stream_id.into());
+                self.frame_type.push("rst_stream".to_string());
+                self.error_code.extend(error_code(&e.params.error_code));
+            },
+            Http2SessionRecvGoaway(e) => {
+                self.frame_type.push("goaway".to_string());
+                self.error_code.extend(error_code(&e.params.error_code));
+            },
+
+            _ => (),
+        }
+    }
+
+    fn add_netlog_h3(&mut self, ev: &netlog::h3::Event) {
+        use netlog::h3::Event::*;
+
+        match ev {
+            Http3PriorityUpdateSent(e) => {
+                self.stream_id.push(e.params.prioritized_element_id as i64);
+                self.frame_type.push("priority_update".to_string());
+            },
+            Http3HeadersSent(e) => {
+                self.stream_id.push(e.params.stream_id as i64);
+                self.frame_type.push("headers".to_string());
+                self.add_netlog_headers(&e.params.headers);
+            },
+            Http3HeadersReceived(e) => {
+                self.stream_id.push(e.params.stream_id as i64);
+                self.frame_type.push("headers".to_string());
+            },
+            Http3HeadersDecoded(e) => {
+                self.stream_id.push(e.params.stream_id as i64);
+                self.add_netlog_headers(&e.params.headers);
+            },
+            Http3DataSent(e) => {
+                self.stream_id.push(e.params.stream_id as i64);
+                self.frame_type.push("data".to_string());
+            },
+            Http3DataFrameReceived(e) => {
+                self.stream_id.push(e.params.stream_id as i64);
+                self.frame_type.push("data".to_string());
+            },
+        }
+    }
+
+    fn add_netlog_http(&mut self, ev: &netlog::http::Event) {
+        use netlog::http::Event::*;
+
+        match ev {
+            HttpTransactionSendRequestHeaders(e) =>
+                self.add_netlog_headers(&e.params.headers),
+            HttpTransactionHttp2SendRequestHeaders(e) =>
+                self.add_netlog_headers(&e.params.headers),
+            HttpTransactionQuicSendRequestHeaders(e) => {
+                self.stream_id.push(e.params.quic_stream_id as i64);
+                self.add_netlog_headers(&e.params.headers);
+            },
+            HttpTransactionReadResponseHeaders(e) =>
+                self.add_netlog_headers(&e.params.headers),
+            HttpStreamJobInitConnectionEnd(e) =>
+                self.error_code.extend(e.params.net_error),
+
+            _ => (),
+        }
+    }
+
+    fn add_netlog_socket(&mut self, ev: &netlog::socket::Event) {
+        use netlog::socket::Event::*;
+
+        let net_error = match ev {
+            TcpConnectEnd(e) => e.params.net_error,
+            TcpConnectAttemptEnd(e) => e.params.net_error,
+            SslConnectEnd(e) => e.params.net_error,
+            _ => None,
+        };
+
+        self.error_code.extend(net_error);
+    }
+
+    fn add_netlog_dns(&mut self, ev: &netlog::dns::Event) {
+        use netlog::dns::Event::*;
+
+        let net_error = match ev {
+            HostResolverRequestEnd(e) => e.params.net_error,
+            ProxyResolutionServiceEnd(e) => e.params.net_error,
+            _ => None,
+        };
+
+        self.error_code.extend(net_error);
+    }
 }
 
-pub fn filter_sqlog_events(mut events: Vec<Event>, filter: &str) -> Vec<Event> {
-    let mut ret = vec![];
+/// Returns the qlog name of a QUIC frame, e.g. `max_stream_data`.
+fn quic_frame_type(frame: &QuicFrame) -> &'static str {
+    match frame {
+        QuicFrame::Padding { .. } => "padding",
+        QuicFrame::Ping { .. } => "ping",
+        QuicFrame::Ack { .. } => "ack",
+        QuicFrame::ResetStream { .. } => "reset_stream",
+        QuicFrame::StopSending { .. } => "stop_sending",
+        QuicFrame::Crypto { .. } => "crypto",
+        QuicFrame::NewToken { .. } => "new_token",
+        QuicFrame::Stream { .. } => "stream",
+        QuicFrame::MaxData { .. } => "max_data",
+        QuicFrame::MaxStreamData { .. } => "max_stream_data",
+        QuicFrame::MaxStreams { .. } => "max_streams",
+        QuicFrame::DataBlocked { .. } => "data_blocked",
+        QuicFrame::StreamDataBlocked { .. } => "stream_data_blocked",
+        QuicFrame::StreamsBlocked { .. } => "streams_blocked",
+        QuicFrame::NewConnectionId { .. } => "new_connection_id",
+        QuicFrame::RetireConnectionId { .. } => "retire_connection_id",
+        QuicFrame::PathChallenge { .. } => "path_challenge",
+        QuicFrame::PathResponse { .. } => "path_response",
+        QuicFrame::ConnectionClose { .. } => "connection_close",
+        QuicFrame::HandshakeDone { .. } => "handshake_done",
+        QuicFrame::Datagram { .. } => "datagram",
+        QuicFrame::Unknown { .. } => "unknown",
+    }
+}
+
+/// Returns the qlog name of an HTTP/3 frame, e.g. `headers`.
+fn http3_frame_type(frame: &Http3Frame) -> &'static str {
+    match frame {
+        Http3Frame::Data { .. } => "data",
+        Http3Frame::Headers { .. } => "headers",
+        Http3Frame::CancelPush { .. } => "cancel_push",
+        Http3Frame::Settings { .. } => "settings",
+        Http3Frame::PushPromise { .. } => "push_promise",
+        Http3Frame::Goaway { .. } => "goaway",
+        Http3Frame::MaxPushId { .. } => "max_push_id",
+        Http3Frame::PriorityUpdate { .. } => "priority_update",
+        Http3Frame::Reserved { .. } => "reserved",
+        Http3Frame::Unknown { .. } => "unknown",
+    }
+}
+
+fn int_array<'a>(values: &[i64]) -> TypedArray<'a, i64> {
+    let mut array = TypedArray::new();
+
+    for v in values {
+        array.push(*v);
+    }
+
+    array
+}
+
+fn bytes_array<'a>(values: &[String]) -> TypedArray<'a, String> {
+    let mut array = TypedArray::new();
+
+    for v in values {
+        array.push(v.clone());
+    }
+
+    array
+}
 
+fn scheme() -> Scheme {
     let mut builder = Scheme! {
         category: Bytes,
         name: Bytes,
+        time: Int,
         stream_id: Array(Int),
+        packet_number: Array(Int),
+        frame_type: Array(Bytes),
+        error_code: Array(Int),
+        header_name: Array(Bytes),
+        header_value: Array(Bytes),
     };
 
     builder
         .add_function("any", wirefilter::AnyFunction {})
         .unwrap();
 
-    let scheme = builder.build();
-    let ast = scheme.parse(filter).unwrap();
-    let filter = ast.compile();
-
-    // TODO: smarter filtering rather then drain / recreate
-    for event in events.drain(..) {
-        // Recreate context each time to appease borrow checker
-        let mut ctx = ExecutionContext::new(&scheme);
-
-        let filter_match = match &event {
-            Event::Qlog(ev) => {
-                let (cat, ty) = category_and_type_from_event(&ev);
-
-                ctx.set_field_value(
-                    scheme.get_field("category").unwrap(),
-                    cat.clone(),
-                )
-                .unwrap();
-                ctx.set_field_value(
-                    scheme.get_field("name").unwrap(),
-                    ty.clone(),
-                )
-                .unwrap();
-
-                ctx.set_field_value(
-                    scheme.get_field("stream_id").unwrap(),
-                    stream_ids(&event),
-                )
-                .unwrap();
-
-                filter.execute(&ctx).unwrap()
-            },
-            Event::Json(ev) => {
-                let (cat, ty) = category_and_type_from_event(&ev);
-                ctx.set_field_value(
-                    scheme.get_field("category").unwrap(),
-                    cat.clone(),
-                )
-                .unwrap();
-                ctx.set_field_value(
-                    scheme.get_field("name").unwrap(),
-                    ty.clone(),
-                )
-                .unwrap();
-
-                ctx.set_field_value(
-                    scheme.get_field("stream_id").unwrap(),
-                    stream_ids(&event),
-                )
-                .unwrap();
-                filter.execute(&ctx).unwrap()
-            },
-        };
+    builder.build()
+}
 
-        if filter_match {
-            ret.push(event);
-        }
+fn matches(scheme: &Scheme, filter: &Filter, fields: &EventFields) -> bool {
+    let mut ctx = ExecutionContext::new(scheme);
+
+    ctx.set_field_value(
+        scheme.get_field("category").unwrap(),
+        fields.category.clone(),
+    )
+    .unwrap();
+    ctx.set_field_value(scheme.get_field("name").unwrap(), fields.name.clone())
+        .unwrap();
+    ctx.set_field_value(scheme.get_field("time").unwrap(), fields.time)
+        .unwrap();
+    ctx.set_field_value(
+        scheme.get_field("stream_id").unwrap(),
+        int_array(&fields.stream_id),
+    )
+    .unwrap();
+    ctx.set_field_value(
+        scheme.get_field("packet_number").unwrap(),
+        int_array(&fields.packet_number),
+    )
+    .unwrap();
+    ctx.set_field_value(
+        scheme.get_field("frame_type").unwrap(),
+        bytes_array(&fields.frame_type),
+    )
+    .unwrap();
+    ctx.set_field_value(
+        scheme.get_field("error_code").unwrap(),
+        int_array(&fields.error_code),
+    )
+    .unwrap();
+    ctx.set_field_value(
+        scheme.get_field("header_name").unwrap(),
+        bytes_array(&fields.header_name),
+    )
+    .unwrap();
+    ctx.set_field_value(
+        scheme.get_field("header_value").unwrap(),
+        bytes_array(&fields.header_value),
+    )
+    .unwrap();
+
+    filter.execute(&ctx).unwrap()
+}
+
+/// A compiled filter expression, for checking events one at a time as they are
+/// read.
+pub struct EventFilter {
+    scheme: Scheme,
+    filter: Filter,
+}
+
+impl EventFilter {
+    /// Compiles `filter`, returning a description of the problem if it isn't a
+    /// valid expression.
+    pub fn new(filter: &str) -> Result<Self, String> {
+        let scheme = scheme();
+        let filter = scheme
+            .parse(filter)
+            .map_err(|e| format!("Invalid wirefilter expression: {e}"))?
+            .compile();
+
+        Ok(EventFilter { scheme, filter })
+    }
+
+    fn matches(&self, fields: &EventFields) -> bool {
+        matches(&self.scheme, &self.filter, fields)
+    }
+
+    /// Checks an event of a netlog session that started at
+    /// `session_start_time`.
+    pub fn matches_netlog(
+        &self, ev_hdr: &netlog::EventHeader, event: &netlog::Event,
+        session_start_time: u64,
+    ) -> bool {
+        self.matches(&EventFields::from_netlog(ev_hdr, event, session_start_time))
     }
+}
+
+fn filter_events<T>(
+    events: Vec<T>, filter: &EventFilter, fields: impl Fn(&T) -> EventFields,
+) -> Vec<T> {
+    events
+        .into_iter()
+        .filter(|event| filter.matches(&fields(event)))
+        .collect()
+}
+
+pub fn filter_sqlog_events(
+    events: Vec<Event>, filter: &EventFilter,
+) -> Vec<Event> {
+    filter_events(events, filter, EventFields::from_sqlog)
+}
 
-    ret
+pub fn filter_qlog_events(
+    events: Vec<qlog::events::Event>, filter: &EventFilter,
+) -> Vec<qlog::events::Event> {
+    filter_events(events, filter, EventFields::from_qlog)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::wirefilter::filter_sqlog_events;
+    use crate::wirefilter::EventFilter;
     use qlog::events::quic::PacketHeader;
     use qlog::events::quic::PacketSent;
     use qlog::events::quic::PacketType::Initial;
@@ -173,6 +647,11 @@ mod tests {
     use qlog::events::RawInfo;
     use qlog::reader::Event;
 
+    fn filter_sqlog_events(events: Vec<Event>, filter: &str) -> Vec<Event> {
+        let filter = EventFilter::new(filter).unwrap();
+        crate::wirefilter::filter_sqlog_events(events, &filter)
+    }
+
     fn stream_frame(stream_id: u64) -> QuicFrame {
         QuicFrame::Stream {
             stream_id,
@@ -456,4 +935,140 @@ mod tests {
             Event::Json(_json_event) => panic!("unexpected type"),
         }
     }
+
+    #[test]
+    fn test_frame_type_filter() {
+        let filtered_events =
+            filter_sqlog_events(events(), r#"any(frame_type[*] == "crypto")"#);
+        assert_eq!(filtered_events.len(), 1);
+
+        let filtered_events =
+            filter_sqlog_events(events(), r#"any(frame_type[*] == "ack")"#);
+        assert!(filtered_events.is_empty());
+    }
+
+    #[test]
+    fn test_packet_number_and_time_filter() {
+        let filtered_events =
+            filter_sqlog_events(events(), "any(packet_number[*] == 0)");
+        assert_eq!(filtered_events.len(), 3);
+
+        let filtered_events = filter_sqlog_events(events(), "time > 0");
+        assert!(filtered_events.is_empty());
+    }
+
+    fn netlog_event(
+        time: u64, ty: &str, event: netlog::Event,
+    ) -> (netlog::EventHeader, netlog::Event) {
+        let ev_hdr = netlog::EventHeader {
+            ty_string: ty.to_string(),
+            time_num: time,
+            ..Default::default()
+        };
+
+        (ev_hdr, event)
+    }
+
+    fn filter_netlog_events(
+        events: Vec<(netlog::EventHeader, netlog::Event)>, filter: &str,
+        session_start_time: u64,
+    ) -> Vec<(netlog::EventHeader, netlog::Event)> {
+        let filter = EventFilter::new(filter).unwrap();
+
+        events
+            .into_iter()
+            .filter(|(ev_hdr, event)| {
+                filter.matches_netlog(ev_hdr, event, session_start_time)
+            })
+            .collect()
+    }
+
+    fn netlog_events() -> Vec<(netlog::EventHeader, netlog::Event)> {
+        let mut headers = netlog::h2::Http2SessionSendHeadersEvent::default();
+        headers.params.stream_id = 1;
+        headers.params.headers =
+            vec![":method: GET".to_string(), ":path: /slow".to_string()];
+
+        let mut rst = netlog::h2::Http2SessionRecvRstStreamEvent::default();
+        rst.params.stream_id = 1;
+        rst.params.error_code = "8 (CANCEL)".to_string();
+
+        let mut closed = netlog::quic::QuicSessionClosedEvent::default();
+        closed.params.quic_error = 25;
+
+        vec![
+            netlog_event(
+                1100,
+                "HTTP2_SESSION_SEND_HEADERS",
+                netlog::Event::H2(netlog::h2::Event::Http2SessionSendHeaders(
+                    headers,
+                )),
+            ),
+            netlog_event(
+                1500,
+                "HTTP2_SESSION_RECV_RST_STREAM",
+                netlog::Event::H2(netlog::h2::Event::Http2SessionRecvRstStream(
+                    rst,
+                )),
+            ),
+            netlog_event(
+                3000,
+                "QUIC_SESSION_CLOSED",
+                netlog::Event::Quic(netlog::quic::Event::QuicSessionClosed(
+                    closed,
+                )),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_netlog_header_filter() {
+        let filtered_events = filter_netlog_events(
+            netlog_events(),
+            r#"any(header_value[*] == "/slow")"#,
+            1000,
+        );
+        assert_eq!(filtered_events.len(), 1);
+        assert_eq!(filtered_events[0].0.ty_string, "HTTP2_SESSION_SEND_HEADERS");
+    }
+
+    #[test]
+    fn test_netlog_stream_and_error_filter() {
+        let filtered_events = filter_netlog_events(
+            netlog_events(),
+            r#"any(stream_id[*] == 1) && any(frame_type[*] == "rst_stream")"#,
+            1000,
+        );
+        assert_eq!(filtered_events.len(), 1);
+
+        let filtered_events = filter_netlog_events(
+            netlog_events(),
+            "any(error_code[*] in {8 25})",
+            1000,
+        );
+        assert_eq!(filtered_events.len(), 2);
+    }
+
+    #[test]
+    fn test_netlog_category_and_time_filter() {
+        let filtered_events = filter_netlog_events(
+            netlog_events(),
+            r#"category == "http2" && time < 200"#,
+            1000,
+        );
+        assert_eq!(filtered_events.len(), 1);
+
+        let filtered_events = filter_netlog_events(
+            netlog_events(),
+            r#"name == "quic_session_closed""#,
+            1000,
+        );
+        assert_eq!(filtered_events.len(), 1);
+    }
+
+    #[test]
+    fn test_invalid_filter() {
+        assert!(EventFilter::new("any(stream_id[*]==1)").is_ok());
+        assert!(EventFilter::new("stream_identifier == 1").is_err());
+    }
 }
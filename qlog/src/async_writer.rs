@@ -0,0 +1,651 @@
+// Copyright (C) 2025, Cloudflare, Inc.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are
+// met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//
+//     * Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in the
+//       documentation and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS
+// IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO,
+// THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR
+// PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Non-blocking qlog output.
+//!
+//! By default a [`QlogStreamer`] writes each record synchronously, so a slow
+//! writer stalls whoever is logging events.
+//! [`QlogStreamer::set_async_writer()`] instead hands the writer to a
+//! [`QlogWriterPool`], whose background threads perform all IO. Serialized
+//! records are queued in a bounded per-log buffer and the [`OverflowPolicy`]
+//! decides what happens when it is full.
+//!
+//! A single pool can serve any number of logs, so applications with many
+//! connections don't need a thread per log. The pool's threads take turns
+//! between logs, writing the records queued so far before moving on to the
+//! next log, so a busy log can't starve the others. A writer that blocks, e.g.
+//! a socket whose reader is slow, only holds up one of the pool's threads.
+//!
+//! Events discarded because of the overflow policy are counted. The streamer
+//! reports the count in the trace itself, using a `loglevel:warning` event
+//! written once space is available again, and exposes it through
+//! [`QlogStreamer::dropped_events()`].
+//!
+//! [`QlogStreamer`]: crate::streamer::QlogStreamer
+//! [`QlogStreamer::set_async_writer()`]: crate::streamer::QlogStreamer::set_async_writer
+//! [`QlogStreamer::dropped_events()`]: crate::streamer::QlogStreamer::dropped_events
+
+use std::collections::VecDeque;
+use std::io;
+use std::io::Write;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::sync::Condvar;
+use std::sync::Mutex;
+use std::sync::Weak;
+
+use crate::events::EventImportance;
+
+/// Decides what happens to a record when a log's buffer is full.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    serde::Deserialize,
+    serde::Serialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum OverflowPolicy {
+    /// Discard the oldest buffered event to make room.
+    #[default]
+    DropOldest,
+
+    /// Discard incoming events of the given importance or any less important
+    /// one. More important events wait for space.
+    ///
+    /// For example, `DropEventClass(EventImportance::Extra)` sheds `Extra`
+    /// events but never loses `Core` or `Base` ones.
+    DropEventClass(EventImportance),
+
+    /// Wait for space. This never loses events, but can stall the caller in
+    /// the same way as a synchronous writer once the buffer fills up.
+    Block,
+}
+
+/// Configuration of the buffer used for a log written by a
+/// [`QlogWriterPool`].
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct AsyncWriterConfig {
+    /// Maximum number of records buffered for the log.
+    pub capacity: usize,
+
+    /// What to do with records that don't fit in the buffer.
+    pub overflow_policy: OverflowPolicy,
+}
+
+impl Default for AsyncWriterConfig {
+    fn default() -> Self {
+        AsyncWriterConfig {
+            capacity: 4096,
+            overflow_policy: OverflowPolicy::default(),
+        }
+    }
+}
+
+#[cfg(feature = "foundations")]
+impl foundations::settings::Settings for AsyncWriterConfig {}
+
+#[cfg(feature = "foundations")]
+impl foundations::settings::Settings for OverflowPolicy {}
+
+/// The number of threads spawned by [`QlogWriterPool::new()`].
+pub const DEFAULT_WORKERS: usize = 4;
+
+/// Background threads writing qlog records on behalf of any number of
+/// [`QlogStreamer`]s.
+///
+/// Cloning a pool returns a handle to the same threads. The threads exit once
+/// all handles, and all logs registered with them, have been dropped.
+///
+/// [`QlogStreamer`]: crate::streamer::QlogStreamer
+#[derive(Clone)]
+pub struct QlogWriterPool {
+    tx: Arc<mpsc::Sender<Arc<Queue>>>,
+}
+
+impl QlogWriterPool {
+    /// Spawns a pool of [`DEFAULT_WORKERS`] threads.
+    pub fn new() -> io::Result<Self> {
+        Self::with_workers(DEFAULT_WORKERS)
+    }
+
+    /// Spawns a pool of `workers` threads, or one if `workers` is zero.
+    pub fn with_workers(workers: usize) -> io::Result<Self> {
+        let (tx, rx) = mpsc::channel::<Arc<Queue>>();
+
+        let tx = Arc::new(tx);
+        let rx = Arc::new(Mutex::new(rx));
+
+        for _ in 0..workers.max(1) {
+            let tx = Arc::downgrade(&tx);
+            let rx = Arc::clone(&rx);
+
+            std::thread::Builder::new()
+                .name("qlog-writer".to_string())
+                .spawn(move || work(&rx, &tx))?;
+        }
+
+        Ok(QlogWriterPool { tx })
+    }
+
+    /// Returns whether both handles refer to the same pool.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.tx, &other.tx)
+    }
+
+    pub(crate) fn register(
+        &self, writer: Box<dyn Write + Send + Sync>, config: AsyncWriterConfig,
+    ) -> AsyncWriter {
+        let queue = Queue {
+            state: Mutex::new(QueueState {
+                records: VecDeque::new(),
+                scheduled: false,
+                closed: false,
+                finished: false,
+                error: None,
+            }),
+            changed: Condvar::new(),
+            writer: Mutex::new(Some(writer)),
+            dropped: AtomicU64::new(0),
+        };
+
+        AsyncWriter {
+            queue: Arc::new(queue),
+            config,
+            pool: self.clone(),
+        }
+    }
+}
+
+/// Runs a pool thread, giving each scheduled log a turn until the pool and all
+/// its logs are dropped.
+///
+/// Workers only hold a weak reference to the sender, so that they don't keep
+/// the channel open.
+fn work(
+    rx: &Mutex<mpsc::Receiver<Arc<Queue>>>, tx: &Weak<mpsc::Sender<Arc<Queue>>>,
+) {
+    loop {
+        let Ok(queue) = rx.lock().unwrap().recv() else {
+            return;
+        };
+
+        while queue.drain() {
+            // Let the other logs have a turn first. If all handles to the pool
+            // were dropped, the remaining records are written right away.
+            if let Some(tx) = tx.upgrade() {
+                if tx.send(Arc::clone(&queue)).is_ok() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for QlogWriterPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("QlogWriterPool").finish_non_exhaustive()
+    }
+}
+
+/// The producer side of a log registered with a [`QlogWriterPool`].
+pub(crate) struct AsyncWriter {
+    queue: Arc<Queue>,
+    config: AsyncWriterConfig,
+    pool: QlogWriterPool,
+}
+
+impl AsyncWriter {
+    /// Queues a serialized record.
+    ///
+    /// Records without an `importance` are never dropped, e.g. the log
+    /// header. Returns false if the record was dropped.
+    pub(crate) fn push(
+        &self, bytes: Vec<u8>, importance: Option<EventImportance>,
+    ) -> bool {
+        let mut state = self.queue.state.lock().unwrap();
+
+        while state.records.len() >= self.config.capacity.max(1) {
+            let droppable = match (self.config.overflow_policy, importance) {
+                (_, None) | (OverflowPolicy::Block, _) => None,
+
+                (OverflowPolicy::DropOldest, Some(_)) => state
+                    .records
+                    .iter()
+                    .position(|r| r.importance.is_some())
+                    .map(Some),
+
+                (OverflowPolicy::DropEventClass(class), Some(importance)) =>
+                    class.is_contained_in(&importance).then_some(None),
+            };
+
+            match droppable {
+                // Evict a buffered record.
+                Some(Some(pos)) => {
+                    state.records.remove(pos);
+                },
+
+                // Drop the incoming record.
+                Some(None) => {
+                    self.queue.dropped.fetch_add(1, Ordering::Relaxed);
+                    return false;
+                },
+
+                None => {
+                    state = self.queue.changed.wait(state).unwrap();
+                    continue;
+                },
+            }
+
+            self.queue.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+
+        if state.closed || state.error.is_some() {
+            self.queue.dropped.fetch_add(1, Ordering::Relaxed);
+            return false;
+        }
+
+        state.records.push_back(Record { bytes, importance });
+
+        self.schedule(&mut state);
+
+        true
+    }
+
+    /// Returns the number of records dropped so far.
+    pub(crate) fn dropped(&self) -> u64 {
+        self.queue.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Stops accepting records. The remaining records are written, then the
+    /// writer is flushed and dropped.
+    ///
+    /// If `wait` is true, this blocks until that has happened and returns
+    /// the first error hit by the writer.
+    pub(crate) fn close(&self, wait: bool) -> io::Result<()> {
+        let mut state = self.queue.state.lock().unwrap();
+
+        if !state.closed {
+            state.closed = true;
+            self.schedule(&mut state);
+        }
+
+        if !wait {
+            return Ok(());
+        }
+
+        while !state.finished {
+            state = self.queue.changed.wait(state).unwrap();
+        }
+
+        match state.error.take() {
+            Some(e) => Err(e),
+
+            None => Ok(()),
+        }
+    }
+
+    /// Hands the queue to the pool unless it is already waiting there.
+    fn schedule(&self, state: &mut QueueState) {
+        if !state.scheduled {
+            state.scheduled = true;
+
+            if self.pool.tx.send(Arc::clone(&self.queue)).is_err() {
+                // The pool thread is gone, so nothing will ever be written.
+                state.scheduled = false;
+                state.finished = true;
+                state.error.get_or_insert_with(|| {
+                    io::Error::other("qlog writer thread exited")
+                });
+            }
+        }
+    }
+}
+
+struct Record {
+    bytes: Vec<u8>,
+    importance: Option<EventImportance>,
+}
+
+struct QueueState {
+    records: VecDeque<Record>,
+
+    /// Whether the queue was handed to the pool and is yet to be drained.
+    scheduled: bool,
+
+    closed: bool,
+
+    /// Whether the writer was flushed and dropped after closing.
+    finished: bool,
+
+    error: Option<io::Error>,
+}
+
+struct Queue {
+    state: Mutex<QueueState>,
+
+    /// Signaled when records are taken from the queue and when it finishes.
+    changed: Condvar,
+
+    /// Only used by the pool thread draining the queue.
+    writer: Mutex<Option<Box<dyn Write + Send + Sync>>>,
+
+    dropped: AtomicU64,
+}
+
+impl Queue {
+    /// Writes out the records queued so far, or finishes the log once it is
+    /// closed and empty.
+    ///
+    /// Returns whether the queue needs another turn, in which case it is still
+    /// marked as scheduled.
+    fn drain(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+
+        if state.records.is_empty() {
+            if state.closed && !state.finished {
+                drop(state);
+
+                let res = self.finish();
+
+                state = self.state.lock().unwrap();
+                state.finished = true;
+
+                if let Err(e) = res {
+                    state.error.get_or_insert(e);
+                }
+            }
+
+            state.scheduled = false;
+            self.changed.notify_all();
+
+            return false;
+        }
+
+        let records = std::mem::take(&mut state.records);
+        let failed = state.error.is_some();
+        self.changed.notify_all();
+        drop(state);
+
+        if failed {
+            self.dropped
+                .fetch_add(records.len() as u64, Ordering::Relaxed);
+        } else if let Err(e) = self.write(records) {
+            self.state.lock().unwrap().error.get_or_insert(e);
+        }
+
+        true
+    }
+
+    fn write(&self, records: VecDeque<Record>) -> io::Result<()> {
+        let mut writer = self.writer.lock().unwrap();
+
+        let Some(writer) = writer.as_mut() else {
+            return Ok(());
+        };
+
+        let count = records.len();
+
+        for (i, record) in records.into_iter().enumerate() {
+            if let Err(e) = writer.write_all(&record.bytes) {
+                self.dropped
+                    .fetch_add((count - i) as u64, Ordering::Relaxed);
+                return Err(e);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn finish(&self) -> io::Result<()> {
+        // Dropping the writer lets compressing writers emit their trailer.
+        match self.writer.lock().unwrap().take() {
+            Some(mut writer) => writer.flush(),
+
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A writer that blocks until `open` is set, and then takes `delay` for
+    /// each write.
+    #[derive(Clone, Default)]
+    struct Gate {
+        open: Arc<(Mutex<bool>, Condvar)>,
+        out: Arc<Mutex<Vec<u8>>>,
+        delay: std::time::Duration,
+    }
+
+    impl Gate {
+        fn open(&self) {
+            *self.open.0.lock().unwrap() = true;
+            self.open.1.notify_all();
+        }
+    }
+
+    impl Write for Gate {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let mut open = self.open.0.lock().unwrap();
+            while !*open {
+                open = self.open.1.wait(open).unwrap();
+            }
+
+            std::thread::sleep(self.delay);
+
+            self.out.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn register(gate: &Gate, overflow_policy: OverflowPolicy) -> AsyncWriter {
+        let pool = QlogWriterPool::with_workers(1).unwrap();
+
+        pool.register(Box::new(gate.clone()), AsyncWriterConfig {
+            capacity: 2,
+            overflow_policy,
+        })
+    }
+
+    /// Pushes a record the pool thread picks up and then blocks on, so the
+    /// queue is empty but nothing can be drained until the gate opens.
+    fn stall(writer: &AsyncWriter) {
+        writer.push(b"0".to_vec(), None);
+
+        while !writer.queue.state.lock().unwrap().records.is_empty() {
+            std::thread::yield_now();
+        }
+    }
+
+    #[test]
+    fn drop_oldest() {
+        let gate = Gate::default();
+        let writer = register(&gate, OverflowPolicy::DropOldest);
+        stall(&writer);
+
+        for b in [b"1", b"2", b"3", b"4"] {
+            assert!(writer.push(b.to_vec(), Some(EventImportance::Core)));
+        }
+
+        assert_eq!(writer.dropped(), 2);
+
+        gate.open();
+        writer.close(true).unwrap();
+
+        assert_eq!(*gate.out.lock().unwrap(), b"034");
+    }
+
+    #[test]
+    fn drop_event_class() {
+        let gate = Gate::default();
+        let writer = register(
+            &gate,
+            OverflowPolicy::DropEventClass(EventImportance::Base),
+        );
+        stall(&writer);
+
+        assert!(writer.push(b"1".to_vec(), Some(EventImportance::Extra)));
+        assert!(writer.push(b"2".to_vec(), Some(EventImportance::Core)));
+        assert!(!writer.push(b"3".to_vec(), Some(EventImportance::Base)));
+        assert!(!writer.push(b"4".to_vec(), Some(EventImportance::Extra)));
+
+        assert_eq!(writer.dropped(), 2);
+
+        // A Core event waits for space rather than being dropped.
+        let writer = Arc::new(writer);
+        let core = std::thread::spawn({
+            let writer = Arc::clone(&writer);
+            move || writer.push(b"5".to_vec(), Some(EventImportance::Core))
+        });
+
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        assert!(!core.is_finished());
+        gate.open();
+
+        assert!(core.join().unwrap());
+        writer.close(true).unwrap();
+
+        assert_eq!(*gate.out.lock().unwrap(), b"0125");
+    }
+
+    #[test]
+    fn gated_writer_does_not_stall_other_logs() {
+        let pool = QlogWriterPool::with_workers(2).unwrap();
+        assert!(pool.ptr_eq(&pool.clone()));
+        assert!(!pool.ptr_eq(&QlogWriterPool::with_workers(1).unwrap()));
+
+        let gate = Gate::default();
+        let gated = pool.register(Box::new(gate.clone()), AsyncWriterConfig {
+            capacity: 2,
+            overflow_policy: OverflowPolicy::Block,
+        });
+        stall(&gated);
+
+        let out = Gate::default();
+        out.open();
+        let writer =
+            pool.register(Box::new(out.clone()), AsyncWriterConfig::default());
+
+        for b in [b"1", b"2", b"3"] {
+            assert!(writer.push(b.to_vec(), Some(EventImportance::Core)));
+        }
+        writer.close(true).unwrap();
+
+        assert_eq!(*out.out.lock().unwrap(), b"123");
+        assert!(gate.out.lock().unwrap().is_empty());
+
+        gate.open();
+        gated.close(true).unwrap();
+
+        assert_eq!(*gate.out.lock().unwrap(), b"0");
+    }
+
+    #[test]
+    fn busy_log_does_not_starve_other_logs() {
+        let pool = QlogWriterPool::with_workers(1).unwrap();
+
+        let gate = Gate {
+            delay: std::time::Duration::from_millis(1),
+            ..Default::default()
+        };
+        let busy =
+            Arc::new(pool.register(Box::new(gate.clone()), AsyncWriterConfig {
+                capacity: 2,
+                overflow_policy: OverflowPolicy::Block,
+            }));
+        stall(&busy);
+
+        // Keep the busy log's queue full. Its writer is slow, so there is
+        // always more to write by the time a batch has been written.
+        let producer = std::thread::spawn({
+            let busy = Arc::clone(&busy);
+            move || {
+                for _ in 0..500 {
+                    busy.push(b"b".to_vec(), Some(EventImportance::Core));
+                }
+            }
+        });
+
+        while busy.queue.state.lock().unwrap().records.len() < 2 {
+            std::thread::yield_now();
+        }
+
+        let out = Gate::default();
+        out.open();
+        let writer =
+            pool.register(Box::new(out.clone()), AsyncWriterConfig::default());
+        assert!(writer.push(b"1".to_vec(), Some(EventImportance::Core)));
+
+        gate.open();
+        writer.close(true).unwrap();
+
+        assert_eq!(*out.out.lock().unwrap(), b"1");
+        assert!(!producer.is_finished());
+
+        producer.join().unwrap();
+        busy.close(true).unwrap();
+
+        assert_eq!(gate.out.lock().unwrap().len(), 501);
+    }
+
+    #[test]
+    fn write_error() {
+        struct Broken;
+
+        impl Write for Broken {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Err(io::Error::other("broken"))
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let pool = QlogWriterPool::new().unwrap();
+        let writer =
+            pool.register(Box::new(Broken), AsyncWriterConfig::default());
+
+        writer.push(b"1".to_vec(), Some(EventImportance::Core));
+        writer.push(b"2".to_vec(), Some(EventImportance::Core));
+
+        assert!(writer.close(true).is_err());
+        assert_eq!(writer.dropped(), 2);
+    }
+}
@@ -25,6 +25,7 @@
 // SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::Bytes;
+use crate::TimeFormat;
 use crate::Token;
 use http3::*;
 use quic::*;
@@ -54,7 +55,24 @@ pub enum EventType {
     None,
 }
 
-use crate::TimeFormat;
+impl EventType {
+    /// Returns the name used for events of this type, e.g.
+    /// `quic:packet_sent`.
+    pub fn name(&self) -> Option<&'static str> {
+        EVENT_TYPE_NAMES
+            .iter()
+            .find(|(ty, _)| ty == self)
+            .map(|(_, name)| *name)
+    }
+
+    /// Returns the event type with the given name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        EVENT_TYPE_NAMES
+            .iter()
+            .find(|(_, n)| *n == name)
+            .map(|(ty, _)| *ty)
+    }
+}
 
 #[serde_with::skip_serializing_none]
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -116,6 +134,10 @@ impl Eventable for Event {
         self.ty.into()
     }
 
+    fn event_type(&self) -> EventType {
+        self.ty
+    }
+
     fn set_time(&mut self, time: f64) {
         self.time = time;
     }
@@ -153,7 +175,8 @@ impl Eventable for JsonEvent {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum EventImportance {
     #[default]
     Core,
@@ -287,126 +310,12 @@ impl From<EventType> for EventImportance {
 pub trait Eventable {
     fn importance(&self) -> EventImportance;
 
-    fn set_time(&mut self, time: f64);
-}
-
-impl From<&EventData> for EventType {
-    fn from(event_data: &EventData) -> Self {
-        match event_data {
-            EventData::QuicServerListening { .. } =>
-                EventType::QuicEventType(QuicEventType::ServerListening),
-            EventData::QuicConnectionStarted { .. } =>
-                EventType::QuicEventType(QuicEventType::ConnectionStarted),
-            EventData::QuicConnectionClosed { .. } =>
-                EventType::QuicEventType(QuicEventType::ConnectionClosed),
-            EventData::QuicConnectionIdUpdated { .. } =>
-                EventType::QuicEventType(QuicEventType::ConnectionIdUpdated),
-            EventData::QuicSpinBitUpdated { .. } =>
-                EventType::QuicEventType(QuicEventType::SpinBitUpdated),
-            EventData::QuicConnectionStateUpdated { .. } =>
-                EventType::QuicEventType(QuicEventType::ConnectionStateUpdated),
-            EventData::QuicTupleAssigned { .. } =>
-                EventType::QuicEventType(QuicEventType::TupleAssigned),
-            EventData::QuicMtuUpdated { .. } =>
-                EventType::QuicEventType(QuicEventType::MtuUpdated),
-
-            EventData::QuicVersionInformation { .. } =>
-                EventType::QuicEventType(QuicEventType::VersionInformation),
-            EventData::QuicAlpnInformation { .. } =>
-                EventType::QuicEventType(QuicEventType::AlpnInformation),
-            EventData::QuicParametersSet { .. } =>
-                EventType::QuicEventType(QuicEventType::ParametersSet),
-            EventData::QuicParametersRestored { .. } =>
-                EventType::QuicEventType(QuicEventType::ParametersRestored),
-            EventData::QuicPacketSent { .. } =>
-                EventType::QuicEventType(QuicEventType::PacketSent),
-            EventData::QuicPacketReceived { .. } =>
-                EventType::QuicEventType(QuicEventType::PacketReceived),
-            EventData::QuicPacketDropped { .. } =>
-                EventType::QuicEventType(QuicEventType::PacketDropped),
-            EventData::QuicPacketBuffered { .. } =>
-                EventType::QuicEventType(QuicEventType::PacketBuffered),
-            EventData::QuicPacketsAcked { .. } =>
-                EventType::QuicEventType(QuicEventType::PacketsAcked),
-            EventData::QuicUdpDatagramsSent { .. } =>
-                EventType::QuicEventType(QuicEventType::UdpDatagramsSent),
-            EventData::QuicUdpDatagramsReceived { .. } =>
-                EventType::QuicEventType(QuicEventType::UdpDatagramsReceived),
-            EventData::QuicUdpDatagramDropped { .. } =>
-                EventType::QuicEventType(QuicEventType::UdpDatagramDropped),
-            EventData::QuicStreamStateUpdated { .. } =>
-                EventType::QuicEventType(QuicEventType::StreamStateUpdated),
-            EventData::QuicFramesProcessed { .. } =>
-                EventType::QuicEventType(QuicEventType::FramesProcessed),
-            EventData::QuicStreamDataMoved { .. } =>
-                EventType::QuicEventType(QuicEventType::StreamDataMoved),
-            EventData::QuicDatagramDataMoved { .. } =>
-                EventType::QuicEventType(QuicEventType::DatagramDataMoved),
-            EventData::QuicConnectionDataBlockedUpdated { .. } =>
-                EventType::QuicEventType(
-                    QuicEventType::ConnectionDataBlockedUpdated,
-                ),
-            EventData::QuicStreamDataBlockedUpdated { .. } =>
-                EventType::QuicEventType(QuicEventType::StreamDataBlockedUpdated),
-            EventData::QuicDatagramDataBlockedUpdated { .. } =>
-                EventType::QuicEventType(
-                    QuicEventType::DatagramDataBlockedUpdated,
-                ),
-            EventData::QuicMigrationStateUpdated { .. } =>
-                EventType::QuicEventType(QuicEventType::MigrationStateUpdated),
-
-            EventData::QuicKeyUpdated { .. } =>
-                EventType::QuicEventType(QuicEventType::KeyUpdated),
-            EventData::QuicKeyDiscarded { .. } =>
-                EventType::QuicEventType(QuicEventType::KeyDiscarded),
-
-            EventData::QuicRecoveryParametersSet { .. } =>
-                EventType::QuicEventType(QuicEventType::RecoveryParametersSet),
-            EventData::QuicMetricsUpdated { .. } =>
-                EventType::QuicEventType(QuicEventType::RecoveryMetricsUpdated),
-            EventData::QuicCongestionStateUpdated { .. } =>
-                EventType::QuicEventType(QuicEventType::CongestionStateUpdated),
-            EventData::QuicTimerUpdated { .. } =>
-                EventType::QuicEventType(QuicEventType::TimerUpdated),
-            EventData::QuicPacketLost { .. } =>
-                EventType::QuicEventType(QuicEventType::PacketLost),
-            EventData::QuicMarkedForRetransmit { .. } =>
-                EventType::QuicEventType(QuicEventType::MarkedForRetransmit),
-            EventData::QuicEcnStateUpdated { .. } =>
-                EventType::QuicEventType(QuicEventType::EcnStateUpdated),
-
-            EventData::Http3ParametersSet { .. } =>
-                EventType::Http3EventType(Http3EventType::ParametersSet),
-            EventData::Http3ParametersRestored { .. } =>
-                EventType::Http3EventType(Http3EventType::ParametersRestored),
-            EventData::Http3StreamTypeSet { .. } =>
-                EventType::Http3EventType(Http3EventType::StreamTypeSet),
-            EventData::Http3PriorityUpdated { .. } =>
-                EventType::Http3EventType(Http3EventType::PriorityUpdated),
-            EventData::Http3FrameCreated { .. } =>
-                EventType::Http3EventType(Http3EventType::FrameCreated),
-            EventData::Http3FrameParsed { .. } =>
-                EventType::Http3EventType(Http3EventType::FrameParsed),
-            EventData::Http3DatagramCreated { .. } =>
-                EventType::Http3EventType(Http3EventType::DatagramCreated),
-            EventData::Http3DatagramParsed { .. } =>
-                EventType::Http3EventType(Http3EventType::DatagramParsed),
-            EventData::Http3PushResolved { .. } =>
-                EventType::Http3EventType(Http3EventType::PushResolved),
-
-            EventData::LogLevelError { .. } =>
-                EventType::LogLevelEventType(LogLevelEventType::Error),
-            EventData::LogLevelWarning { .. } =>
-                EventType::LogLevelEventType(LogLevelEventType::Warning),
-            EventData::LogLevelInfo { .. } =>
-                EventType::LogLevelEventType(LogLevelEventType::Info),
-            EventData::LogLevelDebug { .. } =>
-                EventType::LogLevelEventType(LogLevelEventType::Debug),
-            EventData::LogLevelVerbose { .. } =>
-                EventType::LogLevelEventType(LogLevelEventType::Verbose),
-            //_ => EventType::None,
-        }
+    /// Returns the type of the event, used to filter events by type.
+    fn event_type(&self) -> EventType {
+        EventType::None
     }
+
+    fn set_time(&mut self, time: f64);
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
@@ -428,181 +337,260 @@ pub struct RawInfo {
     pub data: Option<Box<Bytes>>,
 }
 
-#[serde_with::skip_serializing_none]
-#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
-#[serde(tag = "name", content = "data")]
-#[allow(clippy::large_enum_variant)]
-pub enum EventData {
+/// Defines [`EventData`] along with the wire name and [`EventType`] of each of
+/// its variants, so that the names used by serde and by
+/// [`EventType::from_name()`] can't drift apart.
+macro_rules! event_data {
+    ($(
+        $name:literal => $variant:ident $fields:tt as $cat:ident($ty:expr),
+    )*) => {
+        #[serde_with::skip_serializing_none]
+        #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+        #[serde(tag = "name", content = "data")]
+        #[allow(clippy::large_enum_variant)]
+        pub enum EventData {
+            $(
+                #[serde(rename = $name)]
+                $variant $fields,
+            )*
+        }
+
+        impl From<&EventData> for EventType {
+            fn from(event_data: &EventData) -> Self {
+                match event_data {
+                    $(EventData::$variant { .. } => EventType::$cat($ty),)*
+                }
+            }
+        }
+
+        /// Wire names of all event types.
+        const EVENT_TYPE_NAMES: &[(EventType, &str)] =
+            &[$((EventType::$cat($ty), $name),)*];
+    };
+}
+
+event_data! {
     // QUIC
-    #[serde(rename = "quic:server_listening")]
-    QuicServerListening(quic::ServerListening),
+    "quic:server_listening" =>
+        QuicServerListening(quic::ServerListening)
+        as QuicEventType(QuicEventType::ServerListening),
 
-    #[serde(rename = "quic:connection_started")]
-    QuicConnectionStarted(quic::ConnectionStarted),
+    "quic:connection_started" =>
+        QuicConnectionStarted(quic::ConnectionStarted)
+        as QuicEventType(QuicEventType::ConnectionStarted),
 
-    #[serde(rename = "quic:connection_closed")]
-    QuicConnectionClosed(quic::ConnectionClosed),
+    "quic:connection_closed" =>
+        QuicConnectionClosed(quic::ConnectionClosed)
+        as QuicEventType(QuicEventType::ConnectionClosed),
 
-    #[serde(rename = "quic:connection_id_updated")]
-    QuicConnectionIdUpdated(quic::ConnectionIdUpdated),
+    "quic:connection_id_updated" =>
+        QuicConnectionIdUpdated(quic::ConnectionIdUpdated)
+        as QuicEventType(QuicEventType::ConnectionIdUpdated),
 
-    #[serde(rename = "quic:spin_bit_updated")]
-    QuicSpinBitUpdated(quic::SpinBitUpdated),
+    "quic:spin_bit_updated" =>
+        QuicSpinBitUpdated(quic::SpinBitUpdated)
+        as QuicEventType(QuicEventType::SpinBitUpdated),
 
-    #[serde(rename = "quic:connection_state_updated")]
-    QuicConnectionStateUpdated(quic::ConnectionStateUpdated),
+    "quic:connection_state_updated" =>
+        QuicConnectionStateUpdated(quic::ConnectionStateUpdated)
+        as QuicEventType(QuicEventType::ConnectionStateUpdated),
 
-    #[serde(rename = "quic:tuple_assigned")]
-    QuicTupleAssigned(quic::TupleAssigned),
+    "quic:tuple_assigned" =>
+        QuicTupleAssigned(quic::TupleAssigned)
+        as QuicEventType(QuicEventType::TupleAssigned),
 
-    #[serde(rename = "quic:mtu_updated")]
-    QuicMtuUpdated(quic::MtuUpdated),
+    "quic:mtu_updated" =>
+        QuicMtuUpdated(quic::MtuUpdated)
+        as QuicEventType(QuicEventType::MtuUpdated),
 
-    #[serde(rename = "quic:version_information")]
-    QuicVersionInformation(quic::QuicVersionInformation),
+    "quic:version_information" =>
+        QuicVersionInformation(quic::QuicVersionInformation)
+        as QuicEventType(QuicEventType::VersionInformation),
 
-    #[serde(rename = "quic:alpn_information")]
-    QuicAlpnInformation(quic::AlpnInformation),
+    "quic:alpn_information" =>
+        QuicAlpnInformation(quic::AlpnInformation)
+        as QuicEventType(QuicEventType::AlpnInformation),
 
-    #[serde(rename = "quic:parameters_set")]
-    QuicParametersSet(Box<quic::ParametersSet>),
+    "quic:parameters_set" =>
+        QuicParametersSet(Box<quic::ParametersSet>)
+        as QuicEventType(QuicEventType::ParametersSet),
 
-    #[serde(rename = "quic:parameters_restored")]
-    QuicParametersRestored(quic::ParametersRestored),
+    "quic:parameters_restored" =>
+        QuicParametersRestored(quic::ParametersRestored)
+        as QuicEventType(QuicEventType::ParametersRestored),
 
-    #[serde(rename = "quic:packet_sent")]
-    QuicPacketSent(quic::PacketSent),
+    "quic:packet_sent" =>
+        QuicPacketSent(quic::PacketSent)
+        as QuicEventType(QuicEventType::PacketSent),
 
-    #[serde(rename = "quic:packet_received")]
-    QuicPacketReceived(quic::PacketReceived),
+    "quic:packet_received" =>
+        QuicPacketReceived(quic::PacketReceived)
+        as QuicEventType(QuicEventType::PacketReceived),
 
-    #[serde(rename = "quic:packet_dropped")]
-    QuicPacketDropped(quic::PacketDropped),
+    "quic:packet_dropped" =>
+        QuicPacketDropped(quic::PacketDropped)
+        as QuicEventType(QuicEventType::PacketDropped),
 
-    #[serde(rename = "quic:packet_buffered")]
-    QuicPacketBuffered(quic::PacketBuffered),
+    "quic:packet_buffered" =>
+        QuicPacketBuffered(quic::PacketBuffered)
+        as QuicEventType(QuicEventType::PacketBuffered),
 
-    #[serde(rename = "quic:packets_acked")]
-    QuicPacketsAcked(quic::PacketsAcked),
+    "quic:packets_acked" =>
+        QuicPacketsAcked(quic::PacketsAcked)
+        as QuicEventType(QuicEventType::PacketsAcked),
 
-    #[serde(rename = "quic:datagrams_sent")]
-    QuicUdpDatagramsSent(quic::UdpDatagramsSent),
+    "quic:datagrams_sent" =>
+        QuicUdpDatagramsSent(quic::UdpDatagramsSent)
+        as QuicEventType(QuicEventType::UdpDatagramsSent),
 
-    #[serde(rename = "quic:datagrams_received")]
-    QuicUdpDatagramsReceived(quic::UdpDatagramsReceived),
+    "quic:datagrams_received" =>
+        QuicUdpDatagramsReceived(quic::UdpDatagramsReceived)
+        as QuicEventType(QuicEventType::UdpDatagramsReceived),
 
-    #[serde(rename = "quic:datagram_dropped")]
-    QuicUdpDatagramDropped(quic::UdpDatagramDropped),
+    "quic:datagram_dropped" =>
+        QuicUdpDatagramDropped(quic::UdpDatagramDropped)
+        as QuicEventType(QuicEventType::UdpDatagramDropped),
 
-    #[serde(rename = "quic:stream_state_updated")]
-    QuicStreamStateUpdated(quic::StreamStateUpdated),
+    "quic:stream_state_updated" =>
+        QuicStreamStateUpdated(quic::StreamStateUpdated)
+        as QuicEventType(QuicEventType::StreamStateUpdated),
 
-    #[serde(rename = "quic:frames_processed")]
-    QuicFramesProcessed(quic::FramesProcessed),
+    "quic:frames_processed" =>
+        QuicFramesProcessed(quic::FramesProcessed)
+        as QuicEventType(QuicEventType::FramesProcessed),
 
-    #[serde(rename = "quic:stream_data_moved")]
-    QuicStreamDataMoved(quic::StreamDataMoved),
+    "quic:stream_data_moved" =>
+        QuicStreamDataMoved(quic::StreamDataMoved)
+        as QuicEventType(QuicEventType::StreamDataMoved),
 
-    #[serde(rename = "quic:datagram_data_moved")]
-    QuicDatagramDataMoved(quic::DatagramDataMoved),
+    "quic:datagram_data_moved" =>
+        QuicDatagramDataMoved(quic::DatagramDataMoved)
+        as QuicEventType(QuicEventType::DatagramDataMoved),
 
-    #[serde(rename = "quic:connection_data_blocked_updated")]
-    QuicConnectionDataBlockedUpdated(quic::ConnectionDataBlockedUpdated),
+    "quic:connection_data_blocked_updated" =>
+        QuicConnectionDataBlockedUpdated(quic::ConnectionDataBlockedUpdated)
+        as QuicEventType(QuicEventType::ConnectionDataBlockedUpdated),
 
-    #[serde(rename = "quic:stream_data_blocked_updated")]
-    QuicStreamDataBlockedUpdated(quic::StreamDataBlockedUpdated),
+    "quic:stream_data_blocked_updated" =>
+        QuicStreamDataBlockedUpdated(quic::StreamDataBlockedUpdated)
+        as QuicEventType(QuicEventType::StreamDataBlockedUpdated),
 
-    #[serde(rename = "quic:datagram_data_blocked_updated")]
-    QuicDatagramDataBlockedUpdated(quic::DatagramDataBlockedUpdated),
+    "quic:datagram_data_blocked_updated" =>
+        QuicDatagramDataBlockedUpdated(quic::DatagramDataBlockedUpdated)
+        as QuicEventType(QuicEventType::DatagramDataBlockedUpdated),
 
-    #[serde(rename = "quic:migration_state_updated")]
-    QuicMigrationStateUpdated(quic::MigrationStateUpdated),
+    "quic:migration_state_updated" =>
+        QuicMigrationStateUpdated(quic::MigrationStateUpdated)
+        as QuicEventType(QuicEventType::MigrationStateUpdated),
 
-    #[serde(rename = "quic:key_updated")]
-    QuicKeyUpdated(quic::KeyUpdated),
+    "quic:key_updated" =>
+        QuicKeyUpdated(quic::KeyUpdated)
+        as QuicEventType(QuicEventType::KeyUpdated),
 
-    #[serde(rename = "quic:key_retired")]
-    QuicKeyDiscarded(quic::KeyDiscarded),
+    "quic:key_retired" =>
+        QuicKeyDiscarded(quic::KeyDiscarded)
+        as QuicEventType(QuicEventType::KeyDiscarded),
 
-    #[serde(rename = "quic:recovery_parameters_set")]
-    QuicRecoveryParametersSet(quic::RecoveryParametersSet),
+    "quic:recovery_parameters_set" =>
+        QuicRecoveryParametersSet(quic::RecoveryParametersSet)
+        as QuicEventType(QuicEventType::RecoveryParametersSet),
 
-    #[serde(rename = "quic:recovery_metrics_updated")]
-    QuicMetricsUpdated(quic::RecoveryMetricsUpdated),
+    "quic:recovery_metrics_updated" =>
+        QuicMetricsUpdated(quic::RecoveryMetricsUpdated)
+        as QuicEventType(QuicEventType::RecoveryMetricsUpdated),
 
-    #[serde(rename = "quic:congestion_state_updated")]
-    QuicCongestionStateUpdated(quic::CongestionStateUpdated),
+    "quic:congestion_state_updated" =>
+        QuicCongestionStateUpdated(quic::CongestionStateUpdated)
+        as QuicEventType(QuicEventType::CongestionStateUpdated),
 
-    #[serde(rename = "quic:timer_updated")]
-    QuicTimerUpdated(quic::TimerUpdated),
+    "quic:timer_updated" =>
+        QuicTimerUpdated(quic::TimerUpdated)
+        as QuicEventType(QuicEventType::TimerUpdated),
 
-    #[serde(rename = "quic:packet_lost")]
-    QuicPacketLost(quic::PacketLost),
+    "quic:packet_lost" =>
+        QuicPacketLost(quic::PacketLost)
+        as QuicEventType(QuicEventType::PacketLost),
 
-    #[serde(rename = "quic:marked_for_retransmit")]
-    QuicMarkedForRetransmit(quic::MarkedForRetransmit),
+    "quic:marked_for_retransmit" =>
+        QuicMarkedForRetransmit(quic::MarkedForRetransmit)
+        as QuicEventType(QuicEventType::MarkedForRetransmit),
 
-    #[serde(rename = "quic:ecn_state_updated")]
-    QuicEcnStateUpdated(quic::EcnStateUpdated),
+    "quic:ecn_state_updated" =>
+        QuicEcnStateUpdated(quic::EcnStateUpdated)
+        as QuicEventType(QuicEventType::EcnStateUpdated),
 
     // HTTP/3
-    #[serde(rename = "http3:parameters_set")]
-    Http3ParametersSet(http3::ParametersSet),
+    "http3:parameters_set" =>
+        Http3ParametersSet(http3::ParametersSet)
+        as Http3EventType(Http3EventType::ParametersSet),
 
-    #[serde(rename = "http3:parameters_restored")]
-    Http3ParametersRestored(http3::ParametersRestored),
+    "http3:parameters_restored" =>
+        Http3ParametersRestored(http3::ParametersRestored)
+        as Http3EventType(Http3EventType::ParametersRestored),
 
-    #[serde(rename = "http3:stream_type_set")]
-    Http3StreamTypeSet(http3::StreamTypeSet),
+    "http3:stream_type_set" =>
+        Http3StreamTypeSet(http3::StreamTypeSet)
+        as Http3EventType(Http3EventType::StreamTypeSet),
 
-    #[serde(rename = "http3:priority_updated")]
-    Http3PriorityUpdated(http3::PriorityUpdated),
+    "http3:priority_updated" =>
+        Http3PriorityUpdated(http3::PriorityUpdated)
+        as Http3EventType(Http3EventType::PriorityUpdated),
 
-    #[serde(rename = "http3:frame_created")]
-    Http3FrameCreated(http3::FrameCreated),
+    "http3:frame_created" =>
+        Http3FrameCreated(http3::FrameCreated)
+        as Http3EventType(Http3EventType::FrameCreated),
 
-    #[serde(rename = "http3:frame_parsed")]
-    Http3FrameParsed(http3::FrameParsed),
+    "http3:frame_parsed" =>
+        Http3FrameParsed(http3::FrameParsed)
+        as Http3EventType(Http3EventType::FrameParsed),
 
-    #[serde(rename = "http3:datagram_created")]
-    Http3DatagramCreated(http3::DatagramCreated),
+    "http3:datagram_created" =>
+        Http3DatagramCreated(http3::DatagramCreated)
+        as Http3EventType(Http3EventType::DatagramCreated),
 
-    #[serde(rename = "http3:datagram_parsed")]
-    Http3DatagramParsed(http3::DatagramParsed),
+    "http3:datagram_parsed" =>
+        Http3DatagramParsed(http3::DatagramParsed)
+        as Http3EventType(Http3EventType::DatagramParsed),
 
-    #[serde(rename = "http3:push_resolved")]
-    Http3PushResolved(http3::PushResolved),
+    "http3:push_resolved" =>
+        Http3PushResolved(http3::PushResolved)
+        as Http3EventType(Http3EventType::PushResolved),
 
     // LogLevel
-    #[serde(rename = "loglevel:error")]
-    LogLevelError {
-        code: Option<u64>,
-        message: Option<String>,
-    },
-
-    #[serde(rename = "loglevel:warning")]
-    LogLevelWarning {
-        code: Option<u64>,
-        message: Option<String>,
-    },
-
-    #[serde(rename = "loglevel:info")]
-    LogLevelInfo {
-        code: Option<u64>,
-        message: Option<String>,
-    },
-
-    #[serde(rename = "loglevel:debug")]
-    LogLevelDebug {
-        code: Option<u64>,
-        message: Option<String>,
-    },
-
-    #[serde(rename = "loglevel:verbose")]
-    LogLevelVerbose {
-        code: Option<u64>,
-        message: Option<String>,
-    },
+    "loglevel:error" =>
+        LogLevelError {
+            code: Option<u64>,
+            message: Option<String>,
+        }
+        as LogLevelEventType(LogLevelEventType::Error),
+
+    "loglevel:warning" =>
+        LogLevelWarning {
+            code: Option<u64>,
+            message: Option<String>,
+        }
+        as LogLevelEventType(LogLevelEventType::Warning),
+
+    "loglevel:info" =>
+        LogLevelInfo {
+            code: Option<u64>,
+            message: Option<String>,
+        }
+        as LogLevelEventType(LogLevelEventType::Info),
+
+    "loglevel:debug" =>
+        LogLevelDebug {
+            code: Option<u64>,
+            message: Option<String>,
+        }
+        as LogLevelEventType(LogLevelEventType::Debug),
+
+    "loglevel:verbose" =>
+        LogLevelVerbose {
+            code: Option<u64>,
+            message: Option<String>,
+        }
+        as LogLevelEventType(LogLevelEventType::Verbose),
 }
 
 impl EventData {
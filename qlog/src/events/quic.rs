@@ -1056,6 +1056,13 @@ pub struct RecoveryMetricsUpdated {
 #[serde_with::skip_serializing_none]
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug, Default)]
 pub struct CongestionStateUpdated {
+    /// Extension data for non-standard fields, such as details of the
+    /// congestion controller state. `flatten` causes these fields to be
+    /// serialized into the `data` field of a qlog event. On deserialize,
+    /// unknown fields are collected into `ex_data`.
+    #[serde(flatten)]
+    pub ex_data: ExData,
+
     pub old: Option<String>,
     pub new: String,
 
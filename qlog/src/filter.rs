@@ -0,0 +1,368 @@
+// Copyright (C) 2025, Cloudflare, Inc.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are
+// met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//
+//     * Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in the
+//       documentation and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS
+// IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO,
+// THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR
+// PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Per-event-type filtering and sampling.
+//!
+//! The log level of a [`QlogStreamer`] selects events by their importance.
+//! An [`EventFilter`] refines that per event type: types can be enabled
+//! regardless of the level, disabled entirely, or sampled so that only one in
+//! every N events of the type is written.
+//!
+//! Connection closure and loss events, listed in [`ALWAYS_ON`], are logged
+//! regardless of the level unless they are disabled.
+//!
+//! For example, the following logs the details of frames at any level, and
+//! only logs one in ten sent and received packets:
+//!
+//! ```
+//! let filter = qlog::filter::EventFilter {
+//!     enable: vec!["quic:frames_processed".to_string()],
+//!     sample: [
+//!         ("quic:packet_sent".to_string(), 10),
+//!         ("quic:packet_received".to_string(), 10),
+//!     ]
+//!     .into(),
+//!     ..Default::default()
+//! };
+//!
+//! assert!(filter.validate().is_ok());
+//! ```
+//!
+//! [`QlogStreamer`]: crate::streamer::QlogStreamer
+
+use std::collections::BTreeMap;
+
+use crate::events::quic::QuicEventType;
+use crate::events::EventImportance;
+use crate::events::EventType;
+
+/// Events that are logged regardless of the log level, unless they are
+/// disabled by an [`EventFilter`].
+pub const ALWAYS_ON: &[EventType] = &[
+    EventType::QuicEventType(QuicEventType::ConnectionClosed),
+    EventType::QuicEventType(QuicEventType::PacketLost),
+];
+
+/// Event filtering configuration.
+///
+/// Events are referred to by name, e.g. `quic:packet_sent`.
+#[derive(
+    Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize,
+)]
+#[serde(default)]
+pub struct EventFilter {
+    /// Events that are logged regardless of the log level, in addition to
+    /// [`ALWAYS_ON`].
+    pub enable: Vec<String>,
+
+    /// Events that are never logged. This takes precedence over `enable` and
+    /// [`ALWAYS_ON`].
+    pub disable: Vec<String>,
+
+    /// Events of which only one in every N is logged, keyed by name.
+    ///
+    /// The first event of each type is always logged. A rate of 0 or 1 logs
+    /// every event.
+    pub sample: BTreeMap<String, u64>,
+}
+
+impl EventFilter {
+    /// Checks that all event names in the filter are known.
+    pub fn validate(&self) -> Result<(), UnknownEventName> {
+        self.compile().map(|_| ())
+    }
+
+    pub(crate) fn compile(&self) -> Result<CompiledFilter, UnknownEventName> {
+        let event_type = |name: &String| {
+            EventType::from_name(name)
+                .ok_or_else(|| UnknownEventName(name.clone()))
+        };
+
+        Ok(CompiledFilter {
+            enable: self
+                .enable
+                .iter()
+                .map(event_type)
+                .collect::<Result<_, _>>()?,
+
+            disable: self
+                .disable
+                .iter()
+                .map(event_type)
+                .collect::<Result<_, _>>()?,
+
+            sample: self
+                .sample
+                .iter()
+                .filter(|(_, every)| **every > 1)
+                .map(|(name, every)| {
+                    Ok(Sampler {
+                        ty: event_type(name)?,
+                        every: *every,
+                        seen: 0,
+                        presampled: false,
+                    })
+                })
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+#[cfg(feature = "foundations")]
+impl foundations::settings::Settings for EventFilter {}
+
+/// An event name in an [`EventFilter`] that doesn't match any known event.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownEventName(pub String);
+
+impl std::fmt::Display for UnknownEventName {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "unknown qlog event name {:?}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownEventName {}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CompiledFilter {
+    enable: Vec<EventType>,
+    disable: Vec<EventType>,
+    sample: Vec<Sampler>,
+}
+
+#[derive(Clone, Debug)]
+struct Sampler {
+    ty: EventType,
+    every: u64,
+    seen: u64,
+
+    /// Whether the next event was already sampled, before it was built.
+    presampled: bool,
+}
+
+impl Sampler {
+    fn next(&mut self) -> bool {
+        let sampled = self.seen.is_multiple_of(self.every);
+        self.seen += 1;
+
+        sampled
+    }
+}
+
+impl CompiledFilter {
+    /// Returns whether events of type `ty` may be logged at `level`.
+    ///
+    /// This doesn't account for sampling.
+    pub(crate) fn is_enabled(
+        &self, ty: EventType, importance: EventImportance,
+        level: &EventImportance,
+    ) -> bool {
+        if self.disable.contains(&ty) {
+            return false;
+        }
+
+        ALWAYS_ON.contains(&ty) ||
+            self.enable.contains(&ty) ||
+            importance.is_contained_in(level)
+    }
+
+    /// Counts an event of type `ty`, and returns whether it was sampled.
+    ///
+    /// An event that was already counted by [`presample()`] is not counted
+    /// again.
+    ///
+    /// [`presample()`]: CompiledFilter::presample
+    pub(crate) fn sample(&mut self, ty: EventType) -> bool {
+        let Some(sampler) = self.sampler(ty) else {
+            return true;
+        };
+
+        if std::mem::take(&mut sampler.presampled) {
+            return true;
+        }
+
+        sampler.next()
+    }
+
+    /// Counts an event of type `ty` before it is built, and returns whether it
+    /// was sampled.
+    ///
+    /// If so, the next event of type `ty` passed to [`sample()`] is accepted
+    /// without being counted again.
+    ///
+    /// [`sample()`]: CompiledFilter::sample
+    pub(crate) fn presample(&mut self, ty: EventType) -> bool {
+        let Some(sampler) = self.sampler(ty) else {
+            return true;
+        };
+
+        sampler.presampled = sampler.next();
+
+        sampler.presampled
+    }
+
+    fn sampler(&mut self, ty: EventType) -> Option<&mut Sampler> {
+        self.sample.iter_mut().find(|s| s.ty == ty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PACKET_SENT: EventType =
+        EventType::QuicEventType(QuicEventType::PacketSent);
+
+    const CONNECTION_CLOSED: EventType =
+        EventType::QuicEventType(QuicEventType::ConnectionClosed);
+
+    const FRAMES_PROCESSED: EventType =
+        EventType::QuicEventType(QuicEventType::FramesProcessed);
+
+    #[test]
+    fn enable_and_disable() {
+        let filter = EventFilter {
+            enable: vec!["quic:connection_closed".to_string()],
+            disable: vec!["quic:packet_sent".to_string()],
+            ..Default::default()
+        }
+        .compile()
+        .unwrap();
+
+        let level = EventImportance::Core;
+
+        assert!(!filter.is_enabled(PACKET_SENT, PACKET_SENT.into(), &level));
+        assert!(filter.is_enabled(
+            CONNECTION_CLOSED,
+            CONNECTION_CLOSED.into(),
+            &level
+        ));
+        assert!(!filter.is_enabled(
+            FRAMES_PROCESSED,
+            FRAMES_PROCESSED.into(),
+            &level
+        ));
+        assert!(filter.is_enabled(
+            FRAMES_PROCESSED,
+            FRAMES_PROCESSED.into(),
+            &EventImportance::Extra
+        ));
+    }
+
+    #[test]
+    fn always_on() {
+        const PACKET_LOST: EventType =
+            EventType::QuicEventType(QuicEventType::PacketLost);
+
+        let level = EventImportance::Core;
+
+        let filter = CompiledFilter::default();
+        assert!(filter.is_enabled(
+            CONNECTION_CLOSED,
+            CONNECTION_CLOSED.into(),
+            &level
+        ));
+        assert!(filter.is_enabled(PACKET_LOST, PACKET_LOST.into(), &level));
+
+        let filter = EventFilter {
+            disable: vec!["quic:connection_closed".to_string()],
+            ..Default::default()
+        }
+        .compile()
+        .unwrap();
+        assert!(!filter.is_enabled(
+            CONNECTION_CLOSED,
+            CONNECTION_CLOSED.into(),
+            &level
+        ));
+        assert!(filter.is_enabled(PACKET_LOST, PACKET_LOST.into(), &level));
+    }
+
+    #[test]
+    fn sampling() {
+        let mut filter = EventFilter {
+            sample: [("quic:packet_sent".to_string(), 3)].into(),
+            ..Default::default()
+        }
+        .compile()
+        .unwrap();
+
+        let sampled: Vec<bool> =
+            (0..7).map(|_| filter.sample(PACKET_SENT)).collect();
+        assert_eq!(sampled, [true, false, false, true, false, false, true]);
+
+        assert!(filter.sample(CONNECTION_CLOSED));
+    }
+
+    #[test]
+    fn presampling() {
+        let mut filter = EventFilter {
+            sample: [("quic:packet_sent".to_string(), 2)].into(),
+            ..Default::default()
+        }
+        .compile()
+        .unwrap();
+
+        // An event sampled before it was built is not counted again when it
+        // is added.
+        assert!(filter.presample(PACKET_SENT));
+        assert!(filter.sample(PACKET_SENT));
+
+        assert!(!filter.presample(PACKET_SENT));
+        assert!(filter.presample(PACKET_SENT));
+        assert!(filter.sample(PACKET_SENT));
+
+        // Events added without being presampled are counted as usual.
+        assert!(!filter.sample(PACKET_SENT));
+        assert!(filter.sample(PACKET_SENT));
+
+        assert!(filter.presample(CONNECTION_CLOSED));
+        assert!(filter.sample(CONNECTION_CLOSED));
+    }
+
+    #[test]
+    fn unknown_name() {
+        let filter = EventFilter {
+            enable: vec!["quic:packet_sent".to_string()],
+            disable: vec!["transport:packet_sent".to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            filter.validate(),
+            Err(UnknownEventName("transport:packet_sent".to_string()))
+        );
+    }
+
+    #[test]
+    fn names_roundtrip() {
+        for ty in [PACKET_SENT, CONNECTION_CLOSED, FRAMES_PROCESSED] {
+            assert_eq!(EventType::from_name(ty.name().unwrap()), Some(ty));
+        }
+
+        assert_eq!(EventType::None.name(), None);
+    }
+}
@@ -377,6 +377,28 @@
 //! Serialization to JSON occurs as methods on the [`QlogStreamer`]
 //! are called. No additional steps are required.
 //!
+//! ### Compact encoding
+//!
+//! With the `cbor` feature, a [`QlogStreamer`] can encode records as CBOR
+//! instead of JSON text by calling [`with_serialization()`]. This is cheaper
+//! to produce and more compact, which helps when logging a large share of
+//! connections. [`QlogSeqReader`] reads either encoding, and its
+//! [`write_json_seq()`] method converts a log back to JSON-SEQ.
+//!
+//! ## Legacy schema versions
+//!
+//! Traces using the pre-IETF 0.3 and 0.4 schemas can be read with
+//! [`QlogSeqReader`] or [`version::qlog_from_value()`], which upgrade them to
+//! the current data model. A [`QlogStreamer`] can write those versions using
+//! [`with_version()`]. See the [`version`] module for details.
+//!
+//! ## Merging traces
+//!
+//! Client and server traces of the same connection can be combined into a
+//! single multi-trace [`Qlog`] using [`merge::merge()`], which estimates the
+//! clock offset between the vantage points from matching sent and received
+//! packets and aligns all events to a shared reference time.
+//!
 //! [`Trace`]: struct.Trace.html
 //! [`TraceSeq`]: struct.TraceSeq.html
 //! [`VantagePoint`]: struct.VantagePoint.html
@@ -392,6 +414,10 @@
 //! [`add_event_data_with_instant()`]: streamer/struct.QlogStreamer.html#method.add_event_data_with_instant
 //! [`add_event_data_now()`]: streamer/struct.QlogStreamer.html#method.add_event_data_now
 //! [`finish_log()`]: streamer/struct.QlogStreamer.html#method.finish_log
+//! [`QlogSeqReader`]: reader/struct.QlogSeqReader.html
+//! [`with_version()`]: streamer/struct.QlogStreamer.html#method.with_version
+//! [`with_serialization()`]: streamer/struct.QlogStreamer.html#method.with_serialization
+//! [`write_json_seq()`]: reader/struct.QlogSeqReader.html#method.write_json_seq
 
 use std::time::SystemTime;
 
@@ -669,11 +695,15 @@ impl std::fmt::Display for HexSlice<'_> {
     }
 }
 
+pub mod async_writer;
 pub mod events;
+pub mod filter;
+pub mod merge;
 pub mod reader;
 pub mod streamer;
 #[doc(hidden)]
 pub mod testing;
+pub mod version;
 pub mod writer;
 
 #[cfg(test)]
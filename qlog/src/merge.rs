@@ -0,0 +1,572 @@
+// Copyright (C) 2025, Cloudflare, Inc.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are
+// met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//
+//     * Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in the
+//       documentation and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS
+// IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO,
+// THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR
+// PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Merging traces of one connection from multiple vantage points.
+//!
+//! Client and server usually log the same connection to separate files, each
+//! with its own clock. [`merge()`] combines such traces into a single
+//! [`Qlog`] with one trace per vantage point, with all event times relative
+//! to a shared reference time.
+//!
+//! The clock offset between two traces is estimated from packets that one
+//! side logged as sent and the other as received. Assuming the minimum delay
+//! is the same in both directions, the offset is half the difference between
+//! the minimum observed delays of each direction.
+//!
+//! Traces belonging to the same connection can be found with
+//! [`group_by_odcid()`].
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use crate::events::quic::PacketHeader;
+use crate::events::quic::PacketType;
+use crate::events::Event;
+use crate::events::EventData;
+use crate::reader;
+use crate::reader::QlogSeqReader;
+use crate::CommonFields;
+use crate::Qlog;
+use crate::QlogSeq;
+use crate::Trace;
+use crate::VantagePointType;
+use crate::QLOGFILE_URI;
+
+/// A fully read qlog trace.
+#[derive(Clone, Debug)]
+pub struct TraceLog {
+    pub header: QlogSeq,
+    pub events: Vec<Event>,
+}
+
+impl TraceLog {
+    /// Reads all events from `reader`.
+    ///
+    /// Events that are not native qlog events are skipped.
+    pub fn from_reader(reader: QlogSeqReader) -> Self {
+        let header = reader.qlog.clone();
+
+        let events = reader
+            .filter_map(|event| match event {
+                reader::Event::Qlog(event) => Some(event),
+
+                reader::Event::Json(_) => None,
+            })
+            .collect();
+
+        TraceLog { header, events }
+    }
+
+    /// Returns the vantage point type of the trace.
+    pub fn vantage_point(&self) -> VantagePointType {
+        self.header
+            .trace
+            .vantage_point
+            .as_ref()
+            .map(|vp| vp.ty.clone())
+            .unwrap_or_default()
+    }
+
+    /// Returns the original destination connection ID of the connection.
+    ///
+    /// This is taken from the transport parameters if they carry it, and
+    /// otherwise from the first Initial packet sent by the client. The
+    /// trace's `group_id` is used as a last resort.
+    pub fn odcid(&self) -> Option<String> {
+        let from_params = self.events.iter().find_map(|ev| match &ev.data {
+            EventData::QuicParametersSet(params) =>
+                params.original_destination_connection_id.clone(),
+
+            _ => None,
+        });
+
+        if from_params.is_some() {
+            return from_params;
+        }
+
+        let first_initial = |hdr: &PacketHeader| {
+            (hdr.packet_type == PacketType::Initial)
+                .then(|| hdr.dcid.clone())
+                .flatten()
+        };
+
+        let from_initial = match self.vantage_point() {
+            VantagePointType::Client =>
+                self.events.iter().find_map(|ev| match &ev.data {
+                    EventData::QuicPacketSent(pkt) => first_initial(&pkt.header),
+
+                    _ => None,
+                }),
+
+            VantagePointType::Server =>
+                self.events.iter().find_map(|ev| match &ev.data {
+                    EventData::QuicPacketReceived(pkt) =>
+                        first_initial(&pkt.header),
+
+                    _ => None,
+                }),
+
+            _ => None,
+        };
+
+        from_initial.or_else(|| {
+            self.header
+                .trace
+                .common_fields
+                .as_ref()
+                .and_then(|c| c.group_id.clone())
+        })
+    }
+
+    /// Returns the reference time of the trace in milliseconds since the
+    /// UNIX epoch, or 0 if it is unknown.
+    ///
+    /// System clocks use the `epoch`, monotonic clocks the optional
+    /// `wall_clock_time`.
+    fn reference_time_ms(&self) -> f64 {
+        self.header
+            .trace
+            .common_fields
+            .as_ref()
+            .and_then(|c| match c.reference_time.clock_type.as_str() {
+                "system" => Some(&c.reference_time.epoch),
+
+                _ => c.reference_time.wall_clock_time.as_ref(),
+            })
+            .and_then(|t| humantime::parse_rfc3339(t).ok())
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs_f64() * 1000.0)
+            .unwrap_or(0.0)
+    }
+
+    /// Returns the absolute times of sent and received packets, keyed by
+    /// packet number space and packet number.
+    fn packet_times(&self) -> (PacketTimes, PacketTimes) {
+        let base = self.reference_time_ms();
+
+        let mut sent = PacketTimes::new();
+        let mut received = PacketTimes::new();
+
+        for ev in &self.events {
+            let (times, hdr) = match &ev.data {
+                EventData::QuicPacketSent(pkt) => (&mut sent, &pkt.header),
+
+                EventData::QuicPacketReceived(pkt) =>
+                    (&mut received, &pkt.header),
+
+                _ => continue,
+            };
+
+            let (Some(space), Some(pn)) =
+                (packet_number_space(&hdr.packet_type), hdr.packet_number)
+            else {
+                continue;
+            };
+
+            // Keep the first occurrence, later ones are most likely
+            // duplicates.
+            times.entry((space, pn)).or_insert(base + ev.time);
+        }
+
+        (sent, received)
+    }
+}
+
+type PacketTimes = HashMap<(u8, u64), f64>;
+
+fn packet_number_space(ty: &PacketType) -> Option<u8> {
+    match ty {
+        PacketType::Initial => Some(0),
+
+        PacketType::Handshake => Some(1),
+
+        PacketType::ZeroRtt | PacketType::OneRtt => Some(2),
+
+        _ => None,
+    }
+}
+
+/// The estimated clock offset between two traces.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClockOffset {
+    /// Milliseconds to subtract from the other trace's times to get the
+    /// reference trace's clock.
+    pub offset: f64,
+
+    /// The estimated minimum one-way delay between the two vantage points, in
+    /// milliseconds.
+    pub one_way_delay: f64,
+
+    /// The number of packets that were matched in both traces.
+    pub samples: usize,
+}
+
+/// Estimates the clock offset of `other` relative to `reference`.
+///
+/// Returns `None` unless packets were matched in both directions.
+pub fn estimate_clock_offset(
+    reference: &TraceLog, other: &TraceLog,
+) -> Option<ClockOffset> {
+    let (ref_sent, ref_received) = reference.packet_times();
+    let (other_sent, other_received) = other.packet_times();
+
+    let min_delay = |sent: &PacketTimes, received: &PacketTimes| {
+        sent.iter()
+            .filter_map(|(pkt, sent)| received.get(pkt).map(|rx| rx - sent))
+            .fold((f64::INFINITY, 0), |(min, n), d| (min.min(d), n + 1))
+    };
+
+    // Both delays include the clock offset, with opposite signs.
+    let (forward, forward_samples) = min_delay(&ref_sent, &other_received);
+    let (backward, backward_samples) = min_delay(&other_sent, &ref_received);
+
+    if forward_samples == 0 || backward_samples == 0 {
+        return None;
+    }
+
+    Some(ClockOffset {
+        offset: (forward - backward) / 2.0,
+        one_way_delay: (forward + backward) / 2.0,
+        samples: forward_samples + backward_samples,
+    })
+}
+
+/// Groups traces by their original destination connection ID.
+///
+/// Traces where none could be found are grouped under `None`.
+pub fn group_by_odcid(
+    traces: Vec<TraceLog>,
+) -> BTreeMap<Option<String>, Vec<TraceLog>> {
+    let mut groups: BTreeMap<_, Vec<_>> = BTreeMap::new();
+
+    for trace in traces {
+        groups.entry(trace.odcid()).or_default().push(trace);
+    }
+
+    groups
+}
+
+/// The result of merging traces.
+pub struct MergedTraces {
+    /// The merged qlog, with one trace per input.
+    pub qlog: Qlog,
+
+    /// The clock offset applied to each input trace. The first trace is the
+    /// reference, so its offset is always zero. `None` means that the offset
+    /// couldn't be estimated and the trace's clock was used as-is.
+    pub offsets: Vec<Option<ClockOffset>>,
+}
+
+/// Merges traces into a single multi-trace qlog.
+///
+/// The first trace is used as the clock reference. All event times in the
+/// output are relative to its reference time, after correcting for the
+/// estimated clock offset of each trace.
+pub fn merge(traces: Vec<TraceLog>) -> crate::Result<MergedTraces> {
+    let reference = traces.first().ok_or(crate::Error::InvalidState)?;
+
+    let reference_common = reference
+        .header
+        .trace
+        .common_fields
+        .clone()
+        .unwrap_or_default();
+    let reference_time = reference.reference_time_ms();
+    let group_id = reference.odcid();
+    let title = reference.header.title.clone();
+
+    let offsets: Vec<_> = traces
+        .iter()
+        .enumerate()
+        .map(|(i, trace)| {
+            if i == 0 {
+                return Some(ClockOffset {
+                    offset: 0.0,
+                    one_way_delay: 0.0,
+                    samples: 0,
+                });
+            }
+
+            estimate_clock_offset(reference, trace)
+        })
+        .collect();
+
+    let traces = traces
+        .into_iter()
+        .zip(&offsets)
+        .map(|(log, offset)| {
+            let shift = log.reference_time_ms() -
+                reference_time -
+                offset.map(|o| o.offset).unwrap_or_default();
+
+            let header = log.header.trace;
+
+            let common_fields = CommonFields {
+                group_id: group_id.clone(),
+                reference_time: reference_common.reference_time.clone(),
+                ..header.common_fields.unwrap_or_default()
+            };
+
+            let mut trace = Trace::new(
+                header.title,
+                header.description,
+                Some(common_fields),
+                header.vantage_point,
+                header.event_schemas,
+            );
+
+            trace.events = log
+                .events
+                .into_iter()
+                .map(|mut ev| {
+                    ev.time += shift;
+                    ev
+                })
+                .collect();
+
+            trace
+        })
+        .collect();
+
+    let qlog = Qlog {
+        file_schema: QLOGFILE_URI.to_string(),
+        serialization_format: "JSON".to_string(),
+        title,
+        description: Some(format!(
+            "merged at {}",
+            humantime::format_rfc3339_seconds(SystemTime::now())
+        )),
+        traces,
+    };
+
+    Ok(MergedTraces { qlog, offsets })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::quic::PacketReceived;
+    use crate::events::quic::PacketSent;
+    use crate::events::quic::ParametersSet;
+    use crate::events::quic::TransportInitiator;
+    use crate::ReferenceTime;
+    use crate::TraceSeq;
+    use crate::VantagePoint;
+
+    fn header(pn: u64, ty: PacketType) -> PacketHeader {
+        PacketHeader {
+            packet_type: ty,
+            packet_number: Some(pn),
+            ..Default::default()
+        }
+    }
+
+    fn sent(time: f64, pn: u64) -> Event {
+        Event::with_time(
+            time,
+            EventData::QuicPacketSent(PacketSent {
+                header: header(pn, PacketType::OneRtt),
+                ..Default::default()
+            }),
+        )
+    }
+
+    fn received(time: f64, pn: u64) -> Event {
+        Event::with_time(
+            time,
+            EventData::QuicPacketReceived(PacketReceived {
+                header: header(pn, PacketType::OneRtt),
+                ..Default::default()
+            }),
+        )
+    }
+
+    fn trace(
+        ty: VantagePointType, wall_clock_time: &str, events: Vec<Event>,
+    ) -> TraceLog {
+        let common_fields = CommonFields {
+            reference_time: ReferenceTime {
+                wall_clock_time: Some(wall_clock_time.to_string()),
+                ..ReferenceTime::new_monotonic(None)
+            },
+            ..Default::default()
+        };
+
+        TraceLog {
+            header: QlogSeq {
+                file_schema: crate::QLOGFILESEQ_URI.to_string(),
+                serialization_format: "JSON-SEQ".to_string(),
+                title: None,
+                description: None,
+                trace: TraceSeq::new(
+                    None,
+                    None,
+                    Some(common_fields),
+                    Some(VantagePoint {
+                        name: None,
+                        ty,
+                        flow: None,
+                    }),
+                    vec![],
+                ),
+            },
+            events,
+        }
+    }
+
+    // The server clock is 100ms ahead of the client clock and the one-way
+    // delay is 10ms, plus queueing delay on some packets.
+    fn client_and_server() -> (TraceLog, TraceLog) {
+        let client =
+            trace(VantagePointType::Client, "2025-01-01T00:00:00Z", vec![
+                sent(0.0, 0),
+                sent(1.0, 1),
+                received(25.0, 0),
+                sent(30.0, 2),
+            ]);
+
+        let server =
+            trace(VantagePointType::Server, "2025-01-01T00:00:00.050Z", vec![
+                received(60.0, 0),
+                received(65.0, 1),
+                sent(65.0, 0),
+                received(95.0, 2),
+            ]);
+
+        (client, server)
+    }
+
+    #[test]
+    fn clock_offset() {
+        let (client, server) = client_and_server();
+
+        let offset = estimate_clock_offset(&client, &server).unwrap();
+        assert_eq!(offset.offset, 100.0);
+        assert_eq!(offset.one_way_delay, 10.0);
+        assert_eq!(offset.samples, 4);
+
+        let offset = estimate_clock_offset(&server, &client).unwrap();
+        assert_eq!(offset.offset, -100.0);
+    }
+
+    #[test]
+    fn clock_offset_one_direction() {
+        let (mut client, server) = client_and_server();
+        client
+            .events
+            .retain(|ev| !matches!(ev.data, EventData::QuicPacketReceived(_)));
+
+        assert_eq!(estimate_clock_offset(&client, &server), None);
+    }
+
+    #[test]
+    fn merge_traces() {
+        let (client, server) = client_and_server();
+
+        let merged = merge(vec![client, server]).unwrap();
+        assert_eq!(merged.qlog.traces.len(), 2);
+        assert_eq!(merged.offsets[1].unwrap().offset, 100.0);
+
+        let client = &merged.qlog.traces[0];
+        let server = &merged.qlog.traces[1];
+
+        assert_eq!(client.common_fields, server.common_fields);
+
+        let times: Vec<f64> = server.events.iter().map(|ev| ev.time).collect();
+        assert_eq!(times, [10.0, 15.0, 15.0, 45.0]);
+    }
+
+    #[test]
+    fn merge_system_clock() {
+        let (mut client, mut server) = client_and_server();
+
+        // Without received packets on the client there's no clock offset
+        // estimate, so the traces are merged by reference time only.
+        client
+            .events
+            .retain(|ev| !matches!(ev.data, EventData::QuicPacketReceived(_)));
+
+        for (log, epoch) in [
+            (&mut client, "2025-01-01T00:00:00Z"),
+            (&mut server, "2025-01-01T00:00:00.050Z"),
+        ] {
+            log.header.trace.common_fields.as_mut().unwrap().reference_time =
+                ReferenceTime {
+                    clock_type: "system".to_string(),
+                    epoch: epoch.to_string(),
+                    wall_clock_time: None,
+                };
+        }
+
+        let merged = merge(vec![client, server]).unwrap();
+        assert_eq!(merged.offsets[1], None);
+
+        let server = &merged.qlog.traces[1];
+        let times: Vec<f64> = server.events.iter().map(|ev| ev.time).collect();
+        assert_eq!(times, [110.0, 115.0, 115.0, 145.0]);
+    }
+
+    #[test]
+    fn merge_nothing() {
+        assert!(merge(vec![]).is_err());
+    }
+
+    #[test]
+    fn group() {
+        let (client, mut server) = client_and_server();
+
+        server.events.push(Event::with_time(
+            0.0,
+            EventData::QuicParametersSet(Box::new(ParametersSet {
+                initiator: Some(TransportInitiator::Local),
+                original_destination_connection_id: Some("abcd".to_string()),
+                ..Default::default()
+            })),
+        ));
+
+        let other =
+            trace(VantagePointType::Client, "2025-01-01T00:00:00Z", vec![
+                Event::with_time(
+                    0.0,
+                    EventData::QuicPacketSent(PacketSent {
+                        header: PacketHeader {
+                            packet_type: PacketType::Initial,
+                            dcid: Some("abcd".to_string()),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    }),
+                ),
+            ]);
+
+        let groups = group_by_odcid(vec![client, server, other]);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[&None].len(), 1);
+        assert_eq!(groups[&Some("abcd".to_string())].len(), 2);
+    }
+}
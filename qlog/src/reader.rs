@@ -28,6 +28,8 @@ use std::fs::File;
 use std::io::BufReader;
 use std::path::Path;
 
+use crate::streamer::QlogSerialization;
+use crate::version::QlogVersion;
 use crate::QlogSeq;
 use crate::SQLOG_EXT;
 use crate::SQLOG_GZ_EXT;
@@ -47,9 +49,19 @@ pub enum Event {
 /// A helper object specialized for reading JSON-SEQ qlog from a [`BufRead`]
 /// trait.
 ///
+/// Legacy 0.3 and 0.4 files are upgraded to the current schema as they are
+/// read, see the [`version`] module. The version found in the file header is
+/// available in `version`.
+///
+/// Both JSON-SEQ and, with the `cbor` feature, CBOR-SEQ logs are supported.
+/// The encoding is detected from the first byte of the stream.
+///
 /// [`BufRead`]: https://doc.rust-lang.org/std/io/trait.BufRead.html
+/// [`version`]: crate::version
 pub struct QlogSeqReader<'a> {
     pub qlog: QlogSeq,
+    pub version: QlogVersion,
+    pub serialization: QlogSerialization,
     reader: Box<dyn std::io::BufRead + Send + Sync + 'a>,
 }
 
@@ -57,20 +69,41 @@ impl<'a> QlogSeqReader<'a> {
     pub fn new(
         mut reader: Box<dyn std::io::BufRead + Send + Sync + 'a>,
     ) -> Result<Self, Box<dyn std::error::Error>> {
-        // "null record" skip it
-        Self::read_record(reader.as_mut());
+        let serialization = Self::detect_serialization(reader.as_mut())?;
 
-        let header = Self::read_record(reader.as_mut()).ok_or_else(|| {
-            std::io::Error::other("error reading file header bytes")
-        })?;
+        let header: serde_json::Value = match serialization {
+            QlogSerialization::JsonSeq => {
+                // "null record" skip it
+                Self::read_record(reader.as_mut());
 
-        let res: Result<QlogSeq, serde_json::Error> =
-            serde_json::from_slice(&header);
-        match res {
-            Ok(qlog) => Ok(Self { qlog, reader }),
+                let header =
+                    Self::read_record(reader.as_mut()).ok_or_else(|| {
+                        std::io::Error::other("error reading file header bytes")
+                    })?;
 
-            Err(e) => Err(e.into()),
-        }
+                serde_json::from_slice(&header)?
+            },
+
+            #[cfg(feature = "cbor")]
+            QlogSerialization::CborSeq => ciborium::from_reader(reader.as_mut())?,
+        };
+
+        let version = QlogVersion::detect(&header).unwrap_or_default();
+
+        let header = if version.is_legacy() {
+            crate::version::upgrade_seq_header(header)
+        } else {
+            header
+        };
+
+        let qlog: QlogSeq = serde_json::from_value(header)?;
+
+        Ok(Self {
+            qlog,
+            version,
+            serialization,
+            reader,
+        })
     }
 
     /// Convenience constructor that opens `path` and picks a streaming
@@ -159,6 +192,78 @@ impl<'a> QlogSeqReader<'a> {
         .into())
     }
 
+    /// Writes the header and all remaining events as JSON-SEQ.
+    ///
+    /// This converts logs of any supported encoding and schema version to
+    /// JSON-SEQ following the current main schema, e.g. so CBOR-SEQ logs can
+    /// be loaded in tools that only understand JSON.
+    pub fn write_json_seq<W: std::io::Write>(
+        &mut self, mut writer: W,
+    ) -> std::io::Result<()> {
+        let mut qlog = self.qlog.clone();
+        qlog.serialization_format = QlogSerialization::JsonSeq
+            .serialization_format()
+            .to_string();
+
+        writer.write_all(b"\x1e")?;
+        serde_json::to_writer(&mut writer, &qlog)?;
+        writer.write_all(b"\n")?;
+
+        for event in self.by_ref() {
+            writer.write_all(b"\x1e")?;
+            match event {
+                Event::Qlog(event) => serde_json::to_writer(&mut writer, &event)?,
+
+                Event::Json(event) => serde_json::to_writer(&mut writer, &event)?,
+            }
+            writer.write_all(b"\n")?;
+        }
+
+        writer.flush()
+    }
+
+    /// JSON-SEQ logs start with an RS character, anything else is assumed to
+    /// be CBOR-SEQ.
+    fn detect_serialization(
+        reader: &mut (dyn std::io::BufRead + Send + Sync),
+    ) -> std::io::Result<QlogSerialization> {
+        match reader.fill_buf()?.first() {
+            None | Some(b'\x1e') => Ok(QlogSerialization::JsonSeq),
+
+            #[cfg(feature = "cbor")]
+            Some(_) => Ok(QlogSerialization::CborSeq),
+
+            #[cfg(not(feature = "cbor"))]
+            Some(_) => Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "qlog stream is not JSON-SEQ; CBOR-SEQ requires the `cbor` \
+                 feature on the qlog crate to decode",
+            )),
+        }
+    }
+
+    /// Converts a JSON value into an event, upgrading it from a legacy schema
+    /// version first if needed.
+    fn event_from_value(&self, value: serde_json::Value) -> Option<Event> {
+        let value = if self.version.is_legacy() {
+            crate::version::upgrade_event(value)
+        } else {
+            value
+        };
+
+        let r: serde_json::Result<crate::events::Event> =
+            serde_json::from_value(value.clone());
+
+        if let Ok(event) = r {
+            return Some(Event::Qlog(event));
+        }
+
+        let r: serde_json::Result<crate::events::JsonEvent> =
+            serde_json::from_value(value);
+
+        r.ok().map(Event::Json)
+    }
+
     fn read_record(
         reader: &mut (dyn std::io::BufRead + Send + Sync),
     ) -> Option<Vec<u8>> {
@@ -181,22 +286,46 @@ impl Iterator for QlogSeqReader<'_> {
     fn next(&mut self) -> Option<Self::Item> {
         // Attempt to deserialize events but skip them if that fails for any
         // reason, ensuring we always read all bytes in the reader.
-        while let Some(bytes) = Self::read_record(&mut self.reader) {
-            let r: serde_json::Result<crate::events::Event> =
-                serde_json::from_slice(&bytes);
+        loop {
+            let value = match self.serialization {
+                QlogSerialization::JsonSeq => {
+                    let bytes = Self::read_record(&mut self.reader)?;
 
-            if let Ok(event) = r {
-                return Some(Event::Qlog(event));
-            }
+                    if !self.version.is_legacy() {
+                        let r: serde_json::Result<crate::events::Event> =
+                            serde_json::from_slice(&bytes);
+
+                        if let Ok(event) = r {
+                            return Some(Event::Qlog(event));
+                        }
+
+                        let r: serde_json::Result<crate::events::JsonEvent> =
+                            serde_json::from_slice(&bytes);
+
+                        if let Ok(event) = r {
+                            return Some(Event::Json(event));
+                        }
 
-            let r: serde_json::Result<crate::events::JsonEvent> =
-                serde_json::from_slice(&bytes);
+                        continue;
+                    }
 
-            if let Ok(event) = r {
-                return Some(Event::Json(event));
+                    match serde_json::from_slice(&bytes) {
+                        Ok(value) => value,
+
+                        Err(_) => continue,
+                    }
+                },
+
+                // CBOR items aren't delimited, so there's no way to resync
+                // after a decoding error.
+                #[cfg(feature = "cbor")]
+                QlogSerialization::CborSeq =>
+                    ciborium::from_reader(&mut self.reader).ok()?,
+            };
+
+            if let Some(event) = self.event_from_value(value) {
+                return Some(event);
             }
         }
-
-        None
     }
 }
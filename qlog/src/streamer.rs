@@ -24,11 +24,18 @@
 // NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
 // SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use crate::async_writer::AsyncWriter;
+use crate::async_writer::AsyncWriterConfig;
+use crate::async_writer::QlogWriterPool;
 use crate::events::EventData;
 use crate::events::EventImportance;
 use crate::events::EventType;
 use crate::events::Eventable;
 use crate::events::ExData;
+use crate::filter::CompiledFilter;
+use crate::filter::EventFilter;
+use crate::filter::UnknownEventName;
+use crate::version::QlogVersion;
 
 /// Controls the time precisions of events.
 ///
@@ -45,6 +52,76 @@ pub enum EventTimePrecision {
     NanoSeconds,
 }
 
+/// Controls the encoding of records written by a [`QlogStreamer`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum QlogSerialization {
+    /// Records are JSON texts separated by the RS character, as defined in
+    /// [RFC 7464].
+    ///
+    /// [RFC 7464]: https://www.rfc-editor.org/rfc/rfc7464
+    #[default]
+    JsonSeq,
+
+    /// Records are CBOR data items, written back to back as defined in
+    /// [RFC 8742].
+    ///
+    /// The data model is the same as for JSON-SEQ, but encoding is
+    /// considerably cheaper and the output more compact. Logs can be read
+    /// back with [`QlogSeqReader`] and converted to JSON-SEQ using
+    /// [`QlogSeqReader::write_json_seq()`].
+    ///
+    /// Requires the `cbor` feature.
+    ///
+    /// [RFC 8742]: https://www.rfc-editor.org/rfc/rfc8742
+    /// [`QlogSeqReader`]: crate::reader::QlogSeqReader
+    /// [`QlogSeqReader::write_json_seq()`]: crate::reader::QlogSeqReader::write_json_seq
+    #[cfg(feature = "cbor")]
+    CborSeq,
+}
+
+impl QlogSerialization {
+    /// Returns the value of the `serialization_format` header field.
+    pub fn serialization_format(&self) -> &'static str {
+        match self {
+            QlogSerialization::JsonSeq => "JSON-SEQ",
+            #[cfg(feature = "cbor")]
+            QlogSerialization::CborSeq => "CBOR-SEQ",
+        }
+    }
+}
+
+/// Writes a single record using `serialization`.
+///
+/// `pretty` only applies to JSON-SEQ.
+fn write_record<T: Serialize + ?Sized>(
+    writer: &mut (dyn std::io::Write + Send + Sync),
+    serialization: QlogSerialization, record: &T, pretty: bool,
+) -> Result<()> {
+    match serialization {
+        QlogSerialization::JsonSeq => {
+            writer.write_all(b"\x1e")?;
+            if pretty {
+                serde_json::to_writer_pretty(&mut *writer, record)
+                    .map_err(|_| Error::Done)?;
+            } else {
+                serde_json::to_writer(&mut *writer, record)
+                    .map_err(|_| Error::Done)?;
+            }
+            writer.write_all(b"\n")?;
+        },
+
+        #[cfg(feature = "cbor")]
+        QlogSerialization::CborSeq => {
+            ciborium::into_writer(record, writer).map_err(|e| match e {
+                ciborium::ser::Error::Io(e) => Error::IoError(e),
+                ciborium::ser::Error::Value(_) => Error::Done,
+            })?;
+        },
+    }
+
+    Ok(())
+}
+
 /// Converts a [`Duration`] to milliseconds as `f64` using the requested
 /// precision variant.
 fn duration_to_millis(
@@ -97,6 +174,11 @@ pub struct QlogStreamer {
     state: StreamerState,
     log_level: EventImportance,
     time_precision: EventTimePrecision,
+    version: QlogVersion,
+    serialization: QlogSerialization,
+    async_writer: Option<AsyncWriter>,
+    reported_drops: u64,
+    filter: CompiledFilter,
 }
 
 impl QlogStreamer {
@@ -131,7 +213,109 @@ impl QlogStreamer {
             state: StreamerState::Initial,
             log_level,
             time_precision,
+            version: QlogVersion::MainSchema,
+            serialization: QlogSerialization::JsonSeq,
+            async_writer: None,
+            reported_drops: 0,
+            filter: CompiledFilter::default(),
+        }
+    }
+
+    /// Sets the encoding of the written log.
+    ///
+    /// Defaults to [`QlogSerialization::JsonSeq`]. This must be called before
+    /// [`start_log()`], as it also updates the `serialization_format` of the
+    /// header.
+    ///
+    /// [`start_log()`]: #method.start_log
+    pub fn with_serialization(
+        mut self, serialization: QlogSerialization,
+    ) -> Self {
+        self.serialization = serialization;
+        self.qlog.serialization_format =
+            serialization.serialization_format().to_string();
+        self
+    }
+
+    /// Sets the schema version of the written log.
+    ///
+    /// Defaults to [`QlogVersion::MainSchema`]. Selecting a legacy version
+    /// downgrades the header and each event as they are written, so the
+    /// output can be read by tooling that only understands that version.
+    /// Events with no legacy equivalent are written unchanged.
+    pub fn with_version(mut self, version: QlogVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Sets a per-event-type filter, refining the log level.
+    ///
+    /// See the [`filter`] module for details. Sampling state is reset.
+    ///
+    /// [`filter`]: crate::filter
+    pub fn set_filter(
+        &mut self, filter: &EventFilter,
+    ) -> std::result::Result<(), UnknownEventName> {
+        self.filter = filter.compile()?;
+
+        Ok(())
+    }
+
+    /// Returns whether events of type `ty` can be logged, according to the log
+    /// level and filter.
+    ///
+    /// Events of sampled types may still be discarded when they are added.
+    /// Callers can use [`sample_event()`] instead to also avoid building events
+    /// that would be sampled out.
+    ///
+    /// [`sample_event()`]: #method.sample_event
+    pub fn is_enabled(&self, ty: EventType) -> bool {
+        self.filter.is_enabled(ty, ty.into(), &self.log_level)
+    }
+
+    /// Returns whether an event of type `ty` is to be logged, according to the
+    /// log level and filter, making the sampling decision for it.
+    ///
+    /// This is meant to be called before building an event that may be
+    /// expensive to build. If it returns true, the next event of type `ty`
+    /// that is added is not sampled again.
+    pub fn sample_event(&mut self, ty: EventType) -> bool {
+        self.is_enabled(ty) && self.filter.presample(ty)
+    }
+
+    /// Moves the writer to a background thread of `pool`.
+    ///
+    /// From then on, records are serialized by the caller but queued in a
+    /// bounded buffer rather than written directly, so a slow writer can't
+    /// stall the caller. See the [`async_writer`] module for details.
+    ///
+    /// This can be called at any time before [`finish_log()`]. Afterwards,
+    /// [`writer()`] no longer returns the original writer.
+    ///
+    /// [`async_writer`]: crate::async_writer
+    /// [`finish_log()`]: #method.finish_log
+    /// [`writer()`]: #method.writer
+    pub fn set_async_writer(
+        &mut self, pool: &QlogWriterPool, config: AsyncWriterConfig,
+    ) -> Result<()> {
+        if self.async_writer.is_some() || self.state == StreamerState::Finished {
+            return Err(Error::InvalidState);
         }
+
+        let writer =
+            std::mem::replace(&mut self.writer, Box::new(std::io::sink()));
+        self.async_writer = Some(pool.register(writer, config));
+
+        Ok(())
+    }
+
+    /// Returns the number of events dropped by the async writer.
+    ///
+    /// This is always 0 unless [`set_async_writer()`] was called.
+    ///
+    /// [`set_async_writer()`]: #method.set_async_writer
+    pub fn dropped_events(&self) -> u64 {
+        self.async_writer.as_ref().map_or(0, |w| w.dropped())
     }
 
     /// Starts qlog streaming serialization.
@@ -151,10 +335,14 @@ impl QlogStreamer {
             return Err(Error::Done);
         }
 
-        self.writer.as_mut().write_all(b"")?;
-        serde_json::to_writer(self.writer.as_mut(), &self.qlog)
-            .map_err(|_| Error::Done)?;
-        self.writer.as_mut().write_all(b"\n")?;
+        let mut header =
+            serde_json::to_value(&self.qlog).map_err(|_| Error::Done)?;
+
+        if self.version.is_legacy() {
+            header = crate::version::downgrade_seq_header(header, self.version);
+        }
+
+        self.write_record(&header, None, false)?;
 
         self.state = StreamerState::Ready;
 
@@ -164,16 +352,29 @@ impl QlogStreamer {
     /// Finishes qlog streaming serialization.
     ///
     /// After this is called, no more serialization will occur.
+    ///
+    /// With an async writer, this waits until all buffered records have been
+    /// written out.
     pub fn finish_log(&mut self) -> Result<()> {
+        self.finish(true)
+    }
+
+    fn finish(&mut self, wait: bool) -> Result<()> {
         if self.state == StreamerState::Initial ||
             self.state == StreamerState::Finished
         {
             return Err(Error::InvalidState);
         }
 
+        self.report_drops()?;
+
         self.state = StreamerState::Finished;
 
-        self.writer.as_mut().flush()?;
+        match &self.async_writer {
+            Some(w) => w.close(wait)?,
+
+            None => self.writer.as_mut().flush()?,
+        }
 
         Ok(())
     }
@@ -221,7 +422,11 @@ impl QlogStreamer {
             return Err(Error::InvalidState);
         }
 
-        if !event.importance().is_contained_in(&self.log_level) {
+        if !self.filter.is_enabled(
+            event.event_type(),
+            event.importance(),
+            &self.log_level,
+        ) {
             return Err(Error::Done);
         }
 
@@ -319,7 +524,7 @@ impl QlogStreamer {
         }
 
         let ty = EventType::from(&event_data);
-        if !EventImportance::from(ty).is_contained_in(&self.log_level) {
+        if !self.is_enabled(ty) {
             return Err(Error::Done);
         }
 
@@ -359,21 +564,78 @@ impl QlogStreamer {
             return Err(Error::InvalidState);
         }
 
-        if !event.importance().is_contained_in(&self.log_level) {
+        let ty = event.event_type();
+        if !self
+            .filter
+            .is_enabled(ty, event.importance(), &self.log_level) ||
+            !self.filter.sample(ty)
+        {
             return Err(Error::Done);
         }
 
-        self.writer.as_mut().write_all(b"")?;
-        if pretty {
-            serde_json::to_writer_pretty(self.writer.as_mut(), &event)
-                .map_err(|_| Error::Done)?;
+        self.report_drops()?;
+
+        self.write_event_record(&event, Some(event.importance()), pretty)
+    }
+
+    /// Logs a warning with the number of events dropped by the async writer,
+    /// if it changed since the last report.
+    fn report_drops(&mut self) -> Result<()> {
+        let dropped = self.dropped_events();
+
+        if dropped == self.reported_drops {
+            return Ok(());
+        }
+
+        self.reported_drops = dropped;
+
+        let time = elapsed_millis(
+            self.start_time,
+            std::time::Instant::now(),
+            &self.time_precision,
+        );
+
+        let event = Event::with_time(time, EventData::LogLevelWarning {
+            code: None,
+            message: Some(format!(
+                "{dropped} qlog events dropped due to writer backpressure"
+            )),
+        });
+
+        self.write_event_record(&event, None, false)
+    }
+
+    fn write_event_record<E: Serialize>(
+        &mut self, event: &E, importance: Option<EventImportance>, pretty: bool,
+    ) -> Result<()> {
+        if self.version.is_legacy() {
+            let event = serde_json::to_value(event).map_err(|_| Error::Done)?;
+            let event = crate::version::downgrade_event(event, self.version);
+            self.write_record(&event, importance, pretty)
         } else {
-            serde_json::to_writer(self.writer.as_mut(), &event)
-                .map_err(|_| Error::Done)?;
+            self.write_record(event, importance, pretty)
         }
-        self.writer.as_mut().write_all(b"\n")?;
+    }
 
-        Ok(())
+    fn write_record<T: Serialize + ?Sized>(
+        &mut self, record: &T, importance: Option<EventImportance>, pretty: bool,
+    ) -> Result<()> {
+        match &self.async_writer {
+            Some(w) => {
+                let mut buf = Vec::new();
+                write_record(&mut buf, self.serialization, record, pretty)?;
+                w.push(buf, importance);
+
+                Ok(())
+            },
+
+            None => write_record(
+                self.writer.as_mut(),
+                self.serialization,
+                record,
+                pretty,
+            ),
+        }
     }
 
     /// Returns the writer.
@@ -389,7 +651,8 @@ impl QlogStreamer {
 
 impl Drop for QlogStreamer {
     fn drop(&mut self) {
-        let _ = self.finish_log();
+        // Don't wait for an async writer, it finishes in the background.
+        let _ = self.finish(false);
     }
 }
 
@@ -570,6 +833,66 @@ mod tests {
         pretty_assertions::assert_eq!(log_string, written_string);
     }
 
+    #[test]
+    fn stream_json_event_async() {
+        #[derive(Clone, Default)]
+        struct SharedBuf(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+        impl std::io::Write for SharedBuf {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().write(buf)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let data = json!({"foo": "Bar", "hello": 123});
+        let ev = events::JsonEvent {
+            time: 0.0,
+            importance: events::EventImportance::Core,
+            name: "jsonevent:sample".into(),
+            data,
+        };
+
+        let buf = SharedBuf::default();
+
+        let mut s = streamer::QlogStreamer::new(
+            Some("title".to_string()),
+            Some("description".to_string()),
+            std::time::Instant::now(),
+            make_trace_seq(),
+            EventImportance::Base,
+            EventTimePrecision::NanoSeconds,
+            Box::new(buf.clone()),
+        );
+
+        let pool = QlogWriterPool::new().unwrap();
+        assert!(matches!(
+            s.set_async_writer(&pool, AsyncWriterConfig::default()),
+            Ok(())
+        ));
+        assert!(matches!(
+            s.set_async_writer(&pool, AsyncWriterConfig::default()),
+            Err(Error::InvalidState)
+        ));
+
+        assert!(matches!(s.start_log(), Ok(())));
+        assert!(matches!(s.add_event(ev), Ok(())));
+        assert!(matches!(s.finish_log(), Ok(())));
+        assert_eq!(s.dropped_events(), 0);
+
+        let log_string = r#"{"file_schema":"urn:ietf:params:qlog:file:sequential","serialization_format":"JSON-SEQ","title":"title","description":"description","trace":{"title":"Quiche qlog trace","description":"Quiche qlog trace description","vantage_point":{"type":"server"},"event_schemas":[]}}
+{"time":0.0,"name":"jsonevent:sample","data":{"foo":"Bar","hello":123}}
+"#;
+
+        let written = buf.0.lock().unwrap();
+        let written_string = std::str::from_utf8(&written).unwrap();
+
+        pretty_assertions::assert_eq!(log_string, written_string);
+    }
+
     #[test]
     fn stream_data_ex() {
         let v: Vec<u8> = Vec::new();
@@ -658,6 +981,45 @@ mod tests {
         pretty_assertions::assert_eq!(log_string, written_string);
     }
 
+    #[test]
+    fn sample_event() {
+        let mut s = streamer::QlogStreamer::new(
+            Some("title".to_string()),
+            Some("description".to_string()),
+            std::time::Instant::now(),
+            make_trace_seq(),
+            EventImportance::Base,
+            EventTimePrecision::MilliSeconds,
+            Box::new(std::io::sink()),
+        );
+
+        s.set_filter(&EventFilter {
+            sample: [("quic:packet_sent".to_string(), 2)].into(),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(matches!(s.start_log(), Ok(())));
+
+        let ty = EventType::QuicEventType(quic::QuicEventType::PacketSent);
+        let packet_sent = || {
+            Event::with_time(
+                0.0,
+                EventData::QuicPacketSent(quic::PacketSent::default()),
+            )
+        };
+
+        // The decision made before building an event holds when it is added.
+        assert!(s.sample_event(ty));
+        assert!(matches!(s.add_event(packet_sent()), Ok(())));
+        assert!(!s.sample_event(ty));
+        assert!(s.sample_event(ty));
+        assert!(matches!(s.add_event(packet_sent()), Ok(())));
+
+        // Events added without a prior decision are sampled as they are added.
+        assert!(matches!(s.add_event(packet_sent()), Err(Error::Done)));
+        assert!(matches!(s.add_event(packet_sent()), Ok(())));
+    }
+
     #[test]
     fn elapsed_millis_precision() {
         let dur = std::time::Duration::from_nanos(1_234_567);
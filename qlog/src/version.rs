@@ -0,0 +1,911 @@
+// Copyright (C) 2025, Cloudflare, Inc.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are
+// met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//
+//     * Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in the
+//       documentation and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS
+// IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO,
+// THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR
+// PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Conversion between qlog schema versions.
+//!
+//! The crate's data model follows the IETF [main logging schema] drafts. Older
+//! tooling still emits the pre-IETF schema, identified by a top-level
+//! `qlog_version` of `"0.3"` or `"0.4"`. These versions use the
+//! `category:event` naming (e.g. `transport:packet_sent`), a numeric
+//! `reference_time` and different shapes for some event data.
+//!
+//! Conversion happens on the JSON representation. Legacy records are upgraded
+//! to the current schema before being deserialized into the data model, and
+//! records serialized from the data model are downgraded when a legacy version
+//! is requested. [`QlogSeqReader`] and [`QlogStreamer`] do this
+//! automatically, based on the trace header and
+//! [`QlogStreamer::with_version()`] respectively.
+//!
+//! [main logging schema]: https://datatracker.ietf.org/doc/html/draft-ietf-quic-qlog-main-schema
+//! [`QlogSeqReader`]: crate::reader::QlogSeqReader
+//! [`QlogStreamer`]: crate::streamer::QlogStreamer
+//! [`QlogStreamer::with_version()`]: crate::streamer::QlogStreamer::with_version
+
+use std::time::Duration;
+use std::time::UNIX_EPOCH;
+
+use serde_json::json;
+use serde_json::Map;
+use serde_json::Value;
+
+use crate::events::HTTP3_URI;
+use crate::events::QUIC_URI;
+use crate::Qlog;
+use crate::QLOGFILESEQ_URI;
+use crate::QLOGFILE_URI;
+
+/// A qlog schema version.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum QlogVersion {
+    /// qlog 0.3, draft-ietf-quic-qlog-main-schema-02 and earlier.
+    V0_3,
+
+    /// qlog 0.4, which shares the event naming of 0.3.
+    V0_4,
+
+    /// The IETF main schema, identified by a `file_schema` URI.
+    #[default]
+    MainSchema,
+}
+
+impl QlogVersion {
+    /// Detects the version of a serialized qlog file header.
+    ///
+    /// Returns `None` if the header doesn't look like any known version.
+    pub fn detect(header: &Value) -> Option<Self> {
+        if header.get("file_schema").is_some() {
+            return Some(QlogVersion::MainSchema);
+        }
+
+        match header.get("qlog_version")?.as_str()? {
+            "0.3" => Some(QlogVersion::V0_3),
+            "0.4" => Some(QlogVersion::V0_4),
+            _ => None,
+        }
+    }
+
+    /// Returns true for the pre-IETF schema versions.
+    pub fn is_legacy(&self) -> bool {
+        !matches!(self, QlogVersion::MainSchema)
+    }
+
+    /// Returns the `qlog_version` string of a legacy version.
+    pub fn qlog_version(&self) -> Option<&'static str> {
+        match self {
+            QlogVersion::V0_3 => Some("0.3"),
+            QlogVersion::V0_4 => Some("0.4"),
+            QlogVersion::MainSchema => None,
+        }
+    }
+}
+
+impl std::fmt::Display for QlogVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.qlog_version() {
+            Some(v) => write!(f, "{v}"),
+            None => write!(f, "main-schema"),
+        }
+    }
+}
+
+/// The last second that can be formatted as an RFC 3339 timestamp, at the end
+/// of year 9999.
+const MAX_RFC3339_SECS: u64 = 253_402_300_799;
+
+/// Legacy and current names of events that exist in both schemas.
+const EVENT_NAMES: &[(&str, &str)] = &[
+    ("connectivity:server_listening", "quic:server_listening"),
+    ("connectivity:connection_started", "quic:connection_started"),
+    ("connectivity:connection_closed", "quic:connection_closed"),
+    (
+        "connectivity:connection_id_updated",
+        "quic:connection_id_updated",
+    ),
+    ("connectivity:spin_bit_updated", "quic:spin_bit_updated"),
+    (
+        "connectivity:connection_state_updated",
+        "quic:connection_state_updated",
+    ),
+    ("connectivity:mtu_updated", "quic:mtu_updated"),
+    ("transport:version_information", "quic:version_information"),
+    ("transport:alpn_information", "quic:alpn_information"),
+    ("transport:parameters_set", "quic:parameters_set"),
+    ("transport:parameters_restored", "quic:parameters_restored"),
+    ("transport:packet_sent", "quic:packet_sent"),
+    ("transport:packet_received", "quic:packet_received"),
+    ("transport:packet_dropped", "quic:packet_dropped"),
+    ("transport:packet_buffered", "quic:packet_buffered"),
+    ("transport:packets_acked", "quic:packets_acked"),
+    ("transport:datagrams_sent", "quic:datagrams_sent"),
+    ("transport:datagrams_received", "quic:datagrams_received"),
+    ("transport:datagram_dropped", "quic:datagram_dropped"),
+    (
+        "transport:stream_state_updated",
+        "quic:stream_state_updated",
+    ),
+    ("transport:frames_processed", "quic:frames_processed"),
+    ("transport:data_moved", "quic:stream_data_moved"),
+    ("security:key_updated", "quic:key_updated"),
+    ("security:key_retired", "quic:key_retired"),
+    ("security:key_discarded", "quic:key_retired"),
+    ("recovery:parameters_set", "quic:recovery_parameters_set"),
+    ("recovery:metrics_updated", "quic:recovery_metrics_updated"),
+    (
+        "recovery:congestion_state_updated",
+        "quic:congestion_state_updated",
+    ),
+    ("recovery:loss_timer_updated", "quic:timer_updated"),
+    ("recovery:packet_lost", "quic:packet_lost"),
+    (
+        "recovery:marked_for_retransmit",
+        "quic:marked_for_retransmit",
+    ),
+    ("http:parameters_set", "http3:parameters_set"),
+    ("http:parameters_restored", "http3:parameters_restored"),
+    ("http:stream_type_set", "http3:stream_type_set"),
+    ("http:frame_created", "http3:frame_created"),
+    ("http:frame_parsed", "http3:frame_parsed"),
+    ("http:push_resolved", "http3:push_resolved"),
+    ("generic:error", "loglevel:error"),
+    ("generic:warning", "loglevel:warning"),
+    ("generic:info", "loglevel:info"),
+    ("generic:debug", "loglevel:debug"),
+    ("generic:verbose", "loglevel:verbose"),
+];
+
+/// Returns the current name of a legacy event name, if it has one.
+pub fn upgrade_event_name(name: &str) -> Option<&'static str> {
+    EVENT_NAMES
+        .iter()
+        .find(|(legacy, _)| *legacy == name)
+        .map(|(_, current)| *current)
+}
+
+/// Returns the legacy name of a current event name, if it has one.
+pub fn downgrade_event_name(name: &str) -> Option<&'static str> {
+    EVENT_NAMES
+        .iter()
+        .find(|(_, current)| *current == name)
+        .map(|(legacy, _)| *legacy)
+}
+
+/// Converts a serialized legacy JSON-SEQ header into the current schema.
+///
+/// The result can be deserialized into a [`QlogSeq`].
+///
+/// [`QlogSeq`]: crate::QlogSeq
+pub fn upgrade_seq_header(header: Value) -> Value {
+    let Value::Object(mut header) = header else {
+        return header;
+    };
+
+    let trace = header.remove("trace").unwrap_or_else(|| json!({}));
+
+    let mut out = upgrade_file_fields(&mut header);
+    out.insert("trace".to_string(), upgrade_trace(trace));
+
+    Value::Object(out)
+}
+
+/// Converts a serialized JSON-SEQ header into the legacy `version` schema.
+///
+/// Headers are returned unchanged if `version` isn't a legacy one.
+pub fn downgrade_seq_header(header: Value, version: QlogVersion) -> Value {
+    let Some(qlog_version) = version.qlog_version() else {
+        return header;
+    };
+
+    let Value::Object(mut header) = header else {
+        return header;
+    };
+
+    let trace = header.remove("trace").unwrap_or_else(|| json!({}));
+
+    let mut out = downgrade_file_fields(&mut header, qlog_version);
+    out.insert("trace".to_string(), downgrade_trace(trace));
+
+    Value::Object(out)
+}
+
+/// Deserializes a buffered JSON qlog of any known version.
+pub fn qlog_from_value(value: Value) -> serde_json::Result<Qlog> {
+    let legacy = QlogVersion::detect(&value).is_some_and(|v| v.is_legacy());
+
+    let value = match value {
+        Value::Object(mut qlog) if legacy => {
+            let traces = match qlog.remove("traces") {
+                Some(Value::Array(traces)) => traces,
+                _ => Vec::new(),
+            };
+
+            let mut out = upgrade_file_fields(&mut qlog);
+            out.insert(
+                "traces".to_string(),
+                traces.into_iter().map(upgrade_trace).collect(),
+            );
+
+            Value::Object(out)
+        },
+
+        value => value,
+    };
+
+    serde_json::from_value(value)
+}
+
+/// Converts a serialized legacy event into the current schema.
+///
+/// Events without a current equivalent are returned with their original
+/// name, so they can still be read as [`JsonEvent`]s.
+///
+/// [`JsonEvent`]: crate::events::JsonEvent
+pub fn upgrade_event(event: Value) -> Value {
+    let Value::Object(mut event) = event else {
+        return event;
+    };
+
+    let name = event.get("name").and_then(Value::as_str).unwrap_or("");
+
+    let Some(name) = upgrade_event_name(name) else {
+        return Value::Object(event);
+    };
+
+    event.insert("name".to_string(), name.into());
+
+    if let Some(time_format) = event.get_mut("time_format") {
+        *time_format = upgrade_time_format(time_format);
+    }
+
+    if let Some(Value::Object(data)) = event.get_mut("data") {
+        match name {
+            "quic:connection_started" => upgrade_connection_started(data),
+
+            "quic:connection_closed" => upgrade_connection_closed(data),
+
+            "quic:stream_data_moved" => move_length_to_raw(data, "length"),
+
+            _ => (),
+        }
+
+        if let Some(Value::Array(frames)) = data.get_mut("frames") {
+            for frame in frames.iter_mut().filter_map(Value::as_object_mut) {
+                move_length_to_raw(frame, "payload_length");
+            }
+        }
+    }
+
+    Value::Object(event)
+}
+
+/// Converts a serialized event into the legacy `version` schema.
+///
+/// Events are returned unchanged if `version` isn't a legacy one, or if they
+/// have no legacy equivalent.
+pub fn downgrade_event(event: Value, version: QlogVersion) -> Value {
+    if !version.is_legacy() {
+        return event;
+    }
+
+    let Value::Object(mut event) = event else {
+        return event;
+    };
+
+    let name = event.get("name").and_then(Value::as_str).unwrap_or("");
+
+    let Some(legacy_name) = downgrade_event_name(name) else {
+        return Value::Object(event);
+    };
+
+    let name = name.to_string();
+    event.insert("name".to_string(), legacy_name.into());
+
+    if let Some(time_format) = event.get_mut("time_format") {
+        *time_format = downgrade_time_format(time_format);
+    }
+
+    if let Some(Value::Object(data)) = event.get_mut("data") {
+        match name.as_str() {
+            "quic:connection_started" => downgrade_connection_started(data),
+
+            "quic:connection_closed" => downgrade_connection_closed(data),
+
+            "quic:stream_data_moved" => copy_raw_to_length(data, "length"),
+
+            _ => (),
+        }
+
+        if let Some(Value::Array(frames)) = data.get_mut("frames") {
+            for frame in frames.iter_mut().filter_map(Value::as_object_mut) {
+                copy_raw_to_length(frame, "payload_length");
+            }
+        }
+    }
+
+    Value::Object(event)
+}
+
+fn upgrade_file_fields(qlog: &mut Map<String, Value>) -> Map<String, Value> {
+    let format = qlog
+        .remove("qlog_format")
+        .and_then(|f| f.as_str().map(str::to_string))
+        .unwrap_or_else(|| "JSON".to_string());
+
+    let file_schema = if format == "JSON-SEQ" {
+        QLOGFILESEQ_URI
+    } else {
+        QLOGFILE_URI
+    };
+
+    let mut out = Map::new();
+    out.insert("file_schema".to_string(), file_schema.into());
+    out.insert("serialization_format".to_string(), format.into());
+    move_field(qlog, &mut out, "title", "title");
+    move_field(qlog, &mut out, "description", "description");
+
+    out
+}
+
+fn downgrade_file_fields(
+    qlog: &mut Map<String, Value>, qlog_version: &str,
+) -> Map<String, Value> {
+    let mut out = Map::new();
+    out.insert("qlog_version".to_string(), qlog_version.into());
+    move_field(qlog, &mut out, "serialization_format", "qlog_format");
+    move_field(qlog, &mut out, "title", "title");
+    move_field(qlog, &mut out, "description", "description");
+
+    out
+}
+
+fn upgrade_trace(trace: Value) -> Value {
+    let Value::Object(mut trace) = trace else {
+        return trace;
+    };
+
+    let mut out = Map::new();
+    move_field(&mut trace, &mut out, "title", "title");
+    move_field(&mut trace, &mut out, "description", "description");
+    move_field(&mut trace, &mut out, "vantage_point", "vantage_point");
+
+    let mut event_schemas: Vec<Value> = Vec::new();
+
+    if let Some(Value::Object(mut legacy)) = trace.remove("common_fields") {
+        let mut common = Map::new();
+
+        move_field(&mut legacy, &mut common, "group_id", "group_id");
+        move_field(&mut legacy, &mut common, "ODCID", "group_id");
+
+        if let Some(Value::Array(protocols)) = legacy.remove("protocol_type") {
+            for protocol in protocols.iter().filter_map(Value::as_str) {
+                match protocol {
+                    "QUIC" => event_schemas.push(QUIC_URI.into()),
+                    "HTTP3" => event_schemas.push(HTTP3_URI.into()),
+                    _ => (),
+                }
+            }
+
+            common.insert("protocol_types".to_string(), protocols.into());
+        }
+
+        // Absolute event times are already relative to the Unix epoch, so
+        // the legacy reference time must not be applied on top of them.
+        let absolute =
+            legacy.get("time_format").and_then(Value::as_str) == Some("absolute");
+
+        let epoch = legacy
+            .remove("reference_time")
+            .filter(|_| !absolute)
+            .and_then(|t| t.as_f64())
+            .and_then(|ms| Duration::try_from_secs_f64(ms / 1000.0).ok())
+            .filter(|d| d.as_secs() <= MAX_RFC3339_SECS)
+            .and_then(|d| UNIX_EPOCH.checked_add(d))
+            .map(|t| humantime::format_rfc3339_millis(t).to_string())
+            .unwrap_or_else(|| "1970-01-01T00:00:00.000Z".to_string());
+
+        common.insert(
+            "reference_time".to_string(),
+            json!({ "clock_type": "system", "epoch": epoch }),
+        );
+
+        if let Some(time_format) = legacy.get("time_format") {
+            common.insert(
+                "time_format".to_string(),
+                upgrade_time_format(time_format),
+            );
+        }
+
+        out.insert("common_fields".to_string(), common.into());
+    }
+
+    out.insert("event_schemas".to_string(), event_schemas.into());
+
+    if let Some(Value::Array(events)) = trace.remove("events") {
+        out.insert(
+            "events".to_string(),
+            events.into_iter().map(upgrade_event).collect(),
+        );
+    }
+
+    Value::Object(out)
+}
+
+fn downgrade_trace(trace: Value) -> Value {
+    let Value::Object(mut trace) = trace else {
+        return trace;
+    };
+
+    let mut out = Map::new();
+    move_field(&mut trace, &mut out, "title", "title");
+    move_field(&mut trace, &mut out, "description", "description");
+    move_field(&mut trace, &mut out, "vantage_point", "vantage_point");
+
+    let mut legacy = Map::new();
+
+    if let Some(Value::Object(mut common)) = trace.remove("common_fields") {
+        move_field(&mut common, &mut legacy, "group_id", "group_id");
+        move_field(&mut common, &mut legacy, "protocol_types", "protocol_type");
+
+        let reference_time = common
+            .get("reference_time")
+            .and_then(|t| t.get("epoch"))
+            .and_then(Value::as_str)
+            .and_then(|epoch| humantime::parse_rfc3339(epoch).ok())
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok());
+
+        if let Some(reference_time) = reference_time {
+            legacy.insert(
+                "reference_time".to_string(),
+                (reference_time.as_secs_f64() * 1000.0).into(),
+            );
+        }
+
+        if let Some(time_format) = common.get("time_format") {
+            legacy.insert(
+                "time_format".to_string(),
+                downgrade_time_format(time_format),
+            );
+        }
+    }
+
+    if !legacy.contains_key("protocol_type") {
+        let protocols: Vec<Value> = trace
+            .get("event_schemas")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|schema| match schema.as_str() {
+                Some(QUIC_URI) => Some("QUIC".into()),
+                Some(HTTP3_URI) => Some("HTTP3".into()),
+                _ => None,
+            })
+            .collect();
+
+        if !protocols.is_empty() {
+            legacy.insert("protocol_type".to_string(), protocols.into());
+        }
+    }
+
+    if !legacy.is_empty() {
+        out.insert("common_fields".to_string(), legacy.into());
+    }
+
+    Value::Object(out)
+}
+
+fn upgrade_time_format(time_format: &Value) -> Value {
+    match time_format.as_str() {
+        Some("delta") => "relative_to_previous_event".into(),
+        Some("relative") | Some("absolute") => "relative_to_epoch".into(),
+        _ => time_format.clone(),
+    }
+}
+
+fn downgrade_time_format(time_format: &Value) -> Value {
+    match time_format.as_str() {
+        Some("relative_to_previous_event") => "delta".into(),
+        Some("relative_to_epoch") => "relative".into(),
+        _ => time_format.clone(),
+    }
+}
+
+/// The 0.3 `connection_started` event describes the connection using source
+/// and destination addresses. These are mapped to the local and remote
+/// endpoints respectively.
+fn upgrade_connection_started(data: &mut Map<String, Value>) {
+    let ipv6 = match data.remove("ip_version") {
+        Some(v) => v.as_str() == Some("ipv6"),
+        None => data
+            .get("src_ip")
+            .and_then(Value::as_str)
+            .is_some_and(|ip| ip.contains(':')),
+    };
+
+    for (prefix, endpoint) in [("src", "local"), ("dst", "remote")] {
+        let mut info = Map::new();
+
+        let (ip, port) = if ipv6 {
+            ("ip_v6", "port_v6")
+        } else {
+            ("ip_v4", "port_v4")
+        };
+
+        move_field(data, &mut info, &format!("{prefix}_ip"), ip);
+        move_field(data, &mut info, &format!("{prefix}_port"), port);
+
+        if let Some(cid) = data.remove(&format!("{prefix}_cid")) {
+            info.insert("connection_ids".to_string(), json!([cid]));
+        }
+
+        data.insert(endpoint.to_string(), info.into());
+    }
+
+    data.remove("protocol");
+}
+
+fn downgrade_connection_started(data: &mut Map<String, Value>) {
+    let mut ipv6 = false;
+
+    for (endpoint, prefix) in [("local", "src"), ("remote", "dst")] {
+        let Some(Value::Object(mut info)) = data.remove(endpoint) else {
+            continue;
+        };
+
+        if info.contains_key("ip_v6") && !info.contains_key("ip_v4") {
+            ipv6 = true;
+            move_field(&mut info, data, "ip_v6", &format!("{prefix}_ip"));
+            move_field(&mut info, data, "port_v6", &format!("{prefix}_port"));
+        } else {
+            move_field(&mut info, data, "ip_v4", &format!("{prefix}_ip"));
+            move_field(&mut info, data, "port_v4", &format!("{prefix}_port"));
+        }
+
+        if let Some(Value::Array(cids)) = info.remove("connection_ids") {
+            if let Some(cid) = cids.into_iter().next() {
+                data.insert(format!("{prefix}_cid"), cid);
+            }
+        }
+    }
+
+    let ip_version = if ipv6 { "ipv6" } else { "ipv4" };
+    data.insert("ip_version".to_string(), ip_version.into());
+    data.insert("protocol".to_string(), "QUIC".into());
+}
+
+/// In 0.3, `connection_closed` carries either a numeric code or the name of a
+/// known error in its `connection_code` and `application_code` fields. A
+/// numeric code becomes `error_code` with an `unknown` error name, so that the
+/// space it came from survives a round-trip.
+fn upgrade_connection_closed(data: &mut Map<String, Value>) {
+    if let Some(owner) = data.remove("owner") {
+        data.insert("initiator".to_string(), owner);
+    }
+
+    for (code, error) in [
+        ("connection_code", "connection_error"),
+        ("application_code", "application_error"),
+    ] {
+        match data.remove(code) {
+            Some(Value::Number(n)) => {
+                data.insert("error_code".to_string(), Value::Number(n));
+                data.insert(error.to_string(), "unknown".into());
+            },
+
+            Some(v @ Value::String(_)) => {
+                data.insert(error.to_string(), v);
+            },
+
+            _ => (),
+        }
+    }
+}
+
+fn downgrade_connection_closed(data: &mut Map<String, Value>) {
+    if let Some(initiator) = data.remove("initiator") {
+        data.insert("owner".to_string(), initiator);
+    }
+
+    let mut error_code = data.remove("error_code");
+
+    for (code, error) in [
+        ("application_code", "application_error"),
+        ("connection_code", "connection_error"),
+    ] {
+        let Some(name) = data.remove(error) else {
+            continue;
+        };
+
+        let value = match (name.as_str(), error_code.take()) {
+            (Some("unknown"), Some(code)) => code,
+
+            (_, code) => {
+                error_code = code;
+                name
+            },
+        };
+
+        data.insert(code.to_string(), value);
+    }
+
+    // Without an error name, the code is assumed to be a transport error.
+    if let Some(code) = error_code {
+        if !data.contains_key("connection_code") {
+            data.insert("connection_code".to_string(), code);
+        }
+    }
+}
+
+/// Moves a legacy top-level `length` field into `raw.<raw_field>`.
+fn move_length_to_raw(data: &mut Map<String, Value>, raw_field: &str) {
+    let Some(length) = data.remove("length") else {
+        return;
+    };
+
+    let raw = data
+        .entry("raw")
+        .or_insert_with(|| Value::Object(Map::new()));
+
+    if let Value::Object(raw) = raw {
+        raw.entry(raw_field).or_insert(length);
+    }
+}
+
+/// Copies `raw.<raw_field>` into the legacy top-level `length` field.
+fn copy_raw_to_length(data: &mut Map<String, Value>, raw_field: &str) {
+    let length = data.get("raw").and_then(|raw| raw.get(raw_field)).cloned();
+
+    if let Some(length) = length {
+        data.entry("length").or_insert(length);
+    }
+}
+
+fn move_field(
+    from: &mut Map<String, Value>, to: &mut Map<String, Value>, old: &str,
+    new: &str,
+) {
+    if let Some(v) = from.remove(old) {
+        to.entry(new).or_insert(v);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::quic::PacketType;
+    use crate::events::EventData;
+    use crate::QlogSeq;
+
+    const LEGACY_HEADER: &str = r#"{"qlog_version":"0.3","qlog_format":"JSON-SEQ","title":"title","trace":{"vantage_point":{"type":"client"},"common_fields":{"ODCID":"abcd","protocol_type":["QUIC","HTTP3"],"reference_time":1705314600000.0,"time_format":"relative"}}}"#;
+
+    #[test]
+    fn detect() {
+        let header: Value = serde_json::from_str(LEGACY_HEADER).unwrap();
+        assert_eq!(QlogVersion::detect(&header), Some(QlogVersion::V0_3));
+
+        let header = json!({ "file_schema": QLOGFILESEQ_URI });
+        assert_eq!(QlogVersion::detect(&header), Some(QlogVersion::MainSchema));
+
+        let header = json!({ "qlog_version": "0.1" });
+        assert_eq!(QlogVersion::detect(&header), None);
+    }
+
+    #[test]
+    fn header_roundtrip() {
+        let header: Value = serde_json::from_str(LEGACY_HEADER).unwrap();
+
+        let qlog: QlogSeq =
+            serde_json::from_value(upgrade_seq_header(header)).unwrap();
+        assert_eq!(qlog.file_schema, QLOGFILESEQ_URI);
+        assert_eq!(qlog.serialization_format, "JSON-SEQ");
+        assert_eq!(qlog.trace.event_schemas, vec![QUIC_URI, HTTP3_URI]);
+
+        let common = qlog.trace.common_fields.clone().unwrap();
+        assert_eq!(common.group_id.as_deref(), Some("abcd"));
+        assert_eq!(common.reference_time.epoch, "2024-01-15T10:30:00.000Z");
+        assert_eq!(common.time_format, Some(crate::TimeFormat::RelativeToEpoch));
+
+        let legacy = downgrade_seq_header(
+            serde_json::to_value(&qlog).unwrap(),
+            QlogVersion::V0_3,
+        );
+        assert_eq!(legacy["qlog_version"], "0.3");
+        assert_eq!(legacy["qlog_format"], "JSON-SEQ");
+        assert_eq!(legacy["trace"]["common_fields"]["group_id"], "abcd");
+        assert_eq!(
+            legacy["trace"]["common_fields"]["reference_time"],
+            1705314600000.0
+        );
+        assert_eq!(legacy["trace"]["common_fields"]["time_format"], "relative");
+    }
+
+    #[test]
+    fn packet_sent_roundtrip() {
+        let legacy = json!({
+            "time": 1.5,
+            "name": "transport:packet_sent",
+            "data": {
+                "header": { "packet_type": "initial", "packet_number": 0 },
+                "frames": [
+                    { "frame_type": "crypto", "offset": 0, "length": 300 }
+                ]
+            }
+        });
+
+        let event: crate::events::Event =
+            serde_json::from_value(upgrade_event(legacy.clone())).unwrap();
+
+        let EventData::QuicPacketSent(pkt) = &event.data else {
+            panic!("unexpected event {event:?}");
+        };
+        assert_eq!(pkt.header.packet_type, PacketType::Initial);
+
+        let downgraded = downgrade_event(
+            serde_json::to_value(&event).unwrap(),
+            QlogVersion::V0_3,
+        );
+        assert_eq!(downgraded["name"], "transport:packet_sent");
+        assert_eq!(downgraded["data"]["frames"][0]["length"], 300);
+    }
+
+    #[test]
+    fn connection_started_roundtrip() {
+        let legacy = json!({
+            "time": 0.0,
+            "name": "connectivity:connection_started",
+            "data": {
+                "ip_version": "ipv4",
+                "src_ip": "192.0.2.1",
+                "dst_ip": "192.0.2.2",
+                "protocol": "QUIC",
+                "src_port": 4433,
+                "dst_port": 443,
+                "src_cid": "aa",
+                "dst_cid": "bb"
+            }
+        });
+
+        let event: crate::events::Event =
+            serde_json::from_value(upgrade_event(legacy.clone())).unwrap();
+
+        let EventData::QuicConnectionStarted(started) = &event.data else {
+            panic!("unexpected event {event:?}");
+        };
+        assert_eq!(started.local.ip_v4.as_deref(), Some("192.0.2.1"));
+        assert_eq!(started.remote.port_v4, Some(443));
+
+        let downgraded = downgrade_event(
+            serde_json::to_value(&event).unwrap(),
+            QlogVersion::V0_3,
+        );
+        assert_eq!(downgraded, legacy);
+    }
+
+    #[test]
+    fn connection_closed() {
+        let legacy = json!({
+            "time": 0.0,
+            "name": "connectivity:connection_closed",
+            "data": { "owner": "remote", "application_code": 256 }
+        });
+
+        let upgraded = upgrade_event(legacy.clone());
+        assert_eq!(upgraded["name"], "quic:connection_closed");
+        assert_eq!(upgraded["data"]["initiator"], "remote");
+        assert_eq!(upgraded["data"]["error_code"], 256);
+
+        assert_eq!(upgraded["data"]["application_error"], "unknown");
+
+        let downgraded = downgrade_event(upgraded, QlogVersion::V0_3);
+        assert_eq!(downgraded, legacy);
+    }
+
+    #[test]
+    fn connection_closed_named_errors() {
+        let legacy = json!({
+            "time": 0.0,
+            "name": "connectivity:connection_closed",
+            "data": {
+                "owner": "local",
+                "connection_code": "protocol_violation",
+                "application_code": "http_internal_error"
+            }
+        });
+
+        let event: crate::events::Event =
+            serde_json::from_value(upgrade_event(legacy.clone())).unwrap();
+
+        let EventData::QuicConnectionClosed(closed) = &event.data else {
+            panic!("unexpected event {event:?}");
+        };
+        assert_eq!(
+            closed.application_error,
+            Some(crate::events::ApplicationError::HttpInternalError)
+        );
+        assert_eq!(closed.error_code, None);
+
+        let downgraded = downgrade_event(
+            serde_json::to_value(&event).unwrap(),
+            QlogVersion::V0_3,
+        );
+        assert_eq!(downgraded, legacy);
+    }
+
+    #[test]
+    fn header_invalid_reference_time() {
+        for reference_time in [json!(-1.0), json!(1e300), json!(f64::MAX)] {
+            let mut header: Value = serde_json::from_str(LEGACY_HEADER).unwrap();
+            header["trace"]["common_fields"]["reference_time"] = reference_time;
+
+            let qlog: QlogSeq =
+                serde_json::from_value(upgrade_seq_header(header)).unwrap();
+
+            let common = qlog.trace.common_fields.unwrap();
+            assert_eq!(common.reference_time.epoch, "1970-01-01T00:00:00.000Z");
+        }
+    }
+
+    #[test]
+    fn header_absolute_time_format() {
+        let mut header: Value = serde_json::from_str(LEGACY_HEADER).unwrap();
+        header["trace"]["common_fields"]["time_format"] = json!("absolute");
+
+        let qlog: QlogSeq =
+            serde_json::from_value(upgrade_seq_header(header)).unwrap();
+
+        let common = qlog.trace.common_fields.unwrap();
+        assert_eq!(common.reference_time.epoch, "1970-01-01T00:00:00.000Z");
+        assert_eq!(common.time_format, Some(crate::TimeFormat::RelativeToEpoch));
+    }
+
+    #[test]
+    fn unknown_events_untouched() {
+        let event = json!({ "time": 0.0, "name": "custom:thing", "data": {} });
+        assert_eq!(upgrade_event(event.clone()), event);
+        assert_eq!(downgrade_event(event.clone(), QlogVersion::V0_4), event);
+    }
+
+    #[test]
+    fn buffered_legacy_qlog() {
+        let qlog = json!({
+            "qlog_version": "0.3",
+            "qlog_format": "JSON",
+            "traces": [{
+                "vantage_point": { "type": "server" },
+                "events": [{
+                    "time": 0.0,
+                    "name": "recovery:metrics_updated",
+                    "data": { "smoothed_rtt": 10.0 }
+                }]
+            }]
+        });
+
+        let qlog = qlog_from_value(qlog).unwrap();
+        assert_eq!(qlog.file_schema, QLOGFILE_URI);
+        assert!(matches!(
+            qlog.traces[0].events[0].data,
+            EventData::QuicMetricsUpdated(_)
+        ));
+    }
+}
@@ -49,6 +49,7 @@ use qlog::reader::QlogSeqReader;
 use qlog::streamer::EventTimePrecision;
 use qlog::streamer::QlogStreamer;
 use qlog::testing;
+use qlog::version::QlogVersion;
 use qlog::writer::make_qlog_writer_from_path;
 use qlog::writer::qlog_file_name;
 use qlog::writer::QlogCompression;
@@ -175,6 +176,104 @@ fn roundtrip_none() {
     assert_roundtrip(&path);
 }
 
+#[test]
+fn roundtrip_legacy_version() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("legacy.sqlog");
+    let writer = make_qlog_writer_from_path(&path, QlogCompression::None)
+        .expect("make_qlog_writer_from_path");
+
+    let mut streamer = QlogStreamer::new(
+        Some("legacy test".to_string()),
+        None,
+        Instant::now(),
+        testing::make_trace_seq(),
+        EventImportance::Base,
+        EventTimePrecision::NanoSeconds,
+        writer,
+    )
+    .with_version(QlogVersion::V0_3);
+
+    streamer.start_log().expect("start_log");
+    streamer
+        .add_event_data_now(make_event())
+        .expect("add_event_data_now");
+    streamer.finish_log().expect("finish_log");
+    drop(streamer);
+
+    let contents = std::fs::read_to_string(&path).expect("read qlog");
+    assert!(contents.contains(r#""qlog_version":"0.3""#));
+    assert!(contents.contains(r#""name":"transport:packet_sent""#));
+    assert!(contents.contains(r#""length":100"#));
+
+    let mut reader =
+        QlogSeqReader::with_file(&path).expect("QlogSeqReader::with_file");
+    assert_eq!(reader.version, QlogVersion::V0_3);
+    assert_eq!(reader.qlog.serialization_format, "JSON-SEQ");
+
+    let events: Vec<Event> = (&mut reader).collect();
+    assert_eq!(events.len(), 1);
+
+    let Event::Qlog(event) = &events[0] else {
+        panic!("expected a native qlog event, got {:?}", events[0]);
+    };
+    assert_eq!(event.data, make_event());
+}
+
+#[cfg(feature = "cbor")]
+#[test]
+fn roundtrip_cbor() {
+    use qlog::streamer::QlogSerialization;
+
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir
+        .path()
+        .join(qlog_file_name("cbor", QlogCompression::None));
+    let writer = make_qlog_writer_from_path(&path, QlogCompression::None)
+        .expect("make_qlog_writer_from_path");
+
+    let mut streamer = QlogStreamer::new(
+        Some("cbor test".to_string()),
+        None,
+        Instant::now(),
+        testing::make_trace_seq(),
+        EventImportance::Base,
+        EventTimePrecision::NanoSeconds,
+        writer,
+    )
+    .with_serialization(QlogSerialization::CborSeq);
+
+    streamer.start_log().expect("start_log");
+    streamer
+        .add_event_data_now(make_event())
+        .expect("add_event_data_now");
+    streamer.finish_log().expect("finish_log");
+    drop(streamer);
+
+    let mut reader =
+        QlogSeqReader::with_file(&path).expect("QlogSeqReader::with_file");
+    assert_eq!(reader.serialization, QlogSerialization::CborSeq);
+    assert_eq!(reader.qlog.serialization_format, "CBOR-SEQ");
+    assert_eq!(reader.qlog.title.as_deref(), Some("cbor test"));
+
+    // Convert to JSON-SEQ and read that back too.
+    let mut json = Vec::new();
+    reader.write_json_seq(&mut json).expect("write_json_seq");
+
+    let mut reader = QlogSeqReader::new(Box::new(std::io::Cursor::new(json)))
+        .expect("QlogSeqReader::new");
+    assert_eq!(reader.serialization, QlogSerialization::JsonSeq);
+    assert_eq!(reader.qlog.serialization_format, "JSON-SEQ");
+
+    let events: Vec<Event> = (&mut reader).collect();
+    assert_eq!(events.len(), 1);
+
+    let Event::Qlog(event) = &events[0] else {
+        panic!("expected a native qlog event, got {:?}", events[0]);
+    };
+    assert_eq!(event.data, make_event());
+}
+
 #[cfg(feature = "gzip")]
 #[test]
 fn roundtrip_gzip() {
@@ -238,6 +238,8 @@ enum quiche_cc_algorithm {
     QUICHE_CC_RENO = 0,
     QUICHE_CC_CUBIC = 1,
     QUICHE_CC_BBR2_GCONGESTION = 4,
+    QUICHE_CC_BBR3_GCONGESTION = 5,
+    QUICHE_CC_LEDBAT = 6,
 };
 
 // Sets the congestion control algorithm used.
@@ -605,6 +605,10 @@ pub struct Config {
     track_unknown_transport_params: Option<usize>,
 
     initial_rtt: Duration,
+
+    clock: ConnectionClock,
+
+    rng: ConnectionRng,
 }
 
 // See https://quicwg.org/base-drafts/rfc9000.html#section-15
@@ -683,6 +687,9 @@ impl Config {
 
             track_unknown_transport_params: None,
             initial_rtt: DEFAULT_INITIAL_RTT,
+
+            clock: ConnectionClock::default(),
+            rng: ConnectionRng::default(),
         })
     }
 
@@ -901,6 +908,32 @@ impl Config {
         self.initial_rtt = v;
     }
 
+    /// Sets the source of the current time of connections.
+    ///
+    /// This API is experimental, it is meant to run connections on simulated
+    /// time, e.g. in network simulations.
+    ///
+    /// The default is to use the system clock.
+    #[cfg(feature = "internal")]
+    #[doc(hidden)]
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = ConnectionClock { clock: Some(clock) };
+    }
+
+    /// Sets the source of the random numbers used by connections.
+    ///
+    /// This API is experimental, it is meant to make connections reproducible,
+    /// e.g. in network simulations. The random numbers are used for things
+    /// like connection IDs, PATH_CHALLENGE data and congestion control, but
+    /// not by the TLS stack.
+    ///
+    /// The default is to use the TLS library's random number generator.
+    #[cfg(feature = "internal")]
+    #[doc(hidden)]
+    pub fn set_rng(&mut self, rng: Arc<dyn Rng>) {
+        self.rng = ConnectionRng { rng: Some(rng) };
+    }
+
     /// Sets the `max_idle_timeout` transport parameter, in milliseconds.
     ///
     /// The default value is infinite, that is, no timeout is used.
@@ -1087,7 +1120,9 @@ impl Config {
     /// This API is experimental and will be removed in the future.
     ///
     /// Currently this only applies if cc_algorithm is
-    /// `CongestionControlAlgorithm::Bbr2Gcongestion` is set.
+    /// `CongestionControlAlgorithm::Bbr2Gcongestion` or
+    /// `CongestionControlAlgorithm::Bbr3Gcongestion`. Settings override the
+    /// defaults of the selected BBR version.
     ///
     /// The default value is `None`.
     #[cfg(feature = "internal")]
@@ -1532,6 +1567,12 @@ where
     #[cfg(feature = "qlog")]
     qlog: QlogInfo,
 
+    /// Source of the current time.
+    clock: ConnectionClock,
+
+    /// Source of random numbers.
+    rng: ConnectionRng,
+
     /// DATAGRAM queues.
     dgram_recv_queue: dgram::DatagramQueue<F>,
     dgram_send_queue: dgram::DatagramQueue<F>,
@@ -1901,13 +1942,13 @@ macro_rules! push_frame_to_pkt {
 
 /// Executes the provided body if the qlog feature is enabled, quiche has been
 /// configured with a log writer, the event's importance is within the
-/// configured level.
+/// configured level and the event is not sampled out.
 macro_rules! qlog_with_type {
     ($ty:expr, $qlog:expr, $qlog_streamer_ref:ident, $body:block) => {{
         #[cfg(feature = "qlog")]
         {
-            if EventImportance::from($ty).is_contained_in(&$qlog.level) {
-                if let Some($qlog_streamer_ref) = &mut $qlog.streamer {
+            if let Some($qlog_streamer_ref) = &mut $qlog.streamer {
+                if $qlog_streamer_ref.sample_event($ty) {
                     $body
                 }
             }
@@ -1927,6 +1968,10 @@ const QLOG_PACKET_RX: EventType =
 const QLOG_PACKET_TX: EventType =
     EventType::QuicEventType(QuicEventType::PacketSent);
 
+#[cfg(feature = "qlog")]
+const QLOG_KEY_UPDATED: EventType =
+    EventType::QuicEventType(QuicEventType::KeyUpdated);
+
 #[cfg(feature = "qlog")]
 const QLOG_DATA_MV: EventType =
     EventType::QuicEventType(QuicEventType::StreamDataMoved);
@@ -1939,6 +1984,102 @@ const QLOG_METRICS: EventType =
 const QLOG_CONNECTION_CLOSED: EventType =
     EventType::QuicEventType(QuicEventType::ConnectionClosed);
 
+/// A source of the current time.
+///
+/// Connections use the system clock unless another source is configured
+/// using [`Config::set_clock()`].
+#[cfg(feature = "internal")]
+#[doc(hidden)]
+pub trait Clock: Send + Sync {
+    /// Returns the current time.
+    fn now(&self) -> Instant;
+}
+
+/// The source of the current time of a connection.
+#[derive(Clone, Default)]
+struct ConnectionClock {
+    #[cfg(feature = "internal")]
+    clock: Option<Arc<dyn Clock>>,
+}
+
+impl ConnectionClock {
+    #[inline]
+    fn now(&self) -> Instant {
+        #[cfg(feature = "internal")]
+        if let Some(clock) = &self.clock {
+            return clock.now();
+        }
+
+        Instant::now()
+    }
+}
+
+/// A source of random numbers.
+///
+/// Connections use the TLS library's random number generator unless another
+/// source is configured using [`Config::set_rng()`].
+#[cfg(feature = "internal")]
+#[doc(hidden)]
+pub trait Rng: Send + Sync {
+    /// Fills `buf` with random bytes.
+    fn fill_bytes(&self, buf: &mut [u8]);
+}
+
+/// The source of random numbers of a connection.
+#[derive(Clone, Default)]
+struct ConnectionRng {
+    #[cfg(feature = "internal")]
+    rng: Option<Arc<dyn Rng>>,
+}
+
+impl ConnectionRng {
+    fn rand_bytes(&self, buf: &mut [u8]) {
+        #[cfg(feature = "internal")]
+        if let Some(rng) = &self.rng {
+            rng.fill_bytes(buf);
+            return;
+        }
+
+        rand::rand_bytes(buf)
+    }
+
+    fn rand_u64(&self) -> u64 {
+        let mut buf = [0; 8];
+
+        self.rand_bytes(&mut buf);
+
+        u64::from_ne_bytes(buf)
+    }
+
+    fn rand_u64_uniform(&self, max: u64) -> u64 {
+        rand::uniform(max, || self.rand_u64())
+    }
+}
+
+// Sources of random numbers can't be compared, so connections are only
+// considered to use the same one if they share it.
+impl PartialEq for ConnectionRng {
+    #[cfg(feature = "internal")]
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.rng, &other.rng) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    #[cfg(not(feature = "internal"))]
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl std::fmt::Debug for ConnectionRng {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("ConnectionRng").finish_non_exhaustive()
+    }
+}
+
 #[cfg(feature = "qlog")]
 struct QlogInfo {
     streamer: Option<qlog::streamer::QlogStreamer>,
@@ -2013,6 +2154,8 @@ impl<F: BufFactory> Connection<F> {
 
         let recovery_config = recovery::RecoveryConfig::from_config(config);
 
+        let now = config.clock.now();
+
         let mut path = path::Path::new(
             local,
             peer,
@@ -2020,6 +2163,7 @@ impl<F: BufFactory> Connection<F> {
             config.path_challenge_recv_max_queue_len,
             true,
             Some(config),
+            now,
         );
 
         // If we sent a Retry assume the peer's address is verified.
@@ -2065,7 +2209,7 @@ impl<F: BufFactory> Connection<F> {
 
             next_pkt_num: 0,
 
-            pkt_num_manager: packet::PktNumManager::new(),
+            pkt_num_manager: packet::PktNumManager::new(config.rng.clone()),
 
             peer_transport_params: TransportParams::default(),
 
@@ -2183,6 +2327,10 @@ impl<F: BufFactory> Connection<F> {
             #[cfg(feature = "qlog")]
             qlog: Default::default(),
 
+            clock: config.clock.clone(),
+
+            rng: config.rng.clone(),
+
             dgram_recv_queue: dgram::DatagramQueue::new(
                 config.dgram_recv_max_queue_len,
             ),
@@ -2245,7 +2393,7 @@ impl<F: BufFactory> Connection<F> {
                 // because we already generated the random
                 // destination connection ID.
                 let mut dcid = [0; 16];
-                rand::rand_bytes(&mut dcid[..]);
+                conn.rng.rand_bytes(&mut dcid[..]);
                 dcid.to_vec()
             };
 
@@ -2306,10 +2454,15 @@ impl<F: BufFactory> Connection<F> {
     /// Only qlog events included in the specified `QlogLevel` are written. The
     /// serialization format is JSON-SEQ.
     ///
+    /// Events can be filtered further by type, or sampled, using
+    /// [`QlogStreamer::set_filter()`] on the [`qlog_streamer()`].
+    ///
     /// This needs to be called as soon as the connection is created, to avoid
     /// missing some early logs.
     ///
     /// [`Writer`]: https://doc.rust-lang.org/std/io/trait.Write.html
+    /// [`QlogStreamer::set_filter()`]: qlog::streamer::QlogStreamer::set_filter
+    /// [`qlog_streamer()`]: Connection::qlog_streamer
     #[cfg(feature = "qlog")]
     #[cfg_attr(docsrs, doc(cfg(feature = "qlog")))]
     pub fn set_qlog_with_level(
@@ -2340,7 +2493,7 @@ impl<F: BufFactory> Connection<F> {
 
         // Best effort to get Instant::now() and SystemTime::now() as closely
         // together as possible.
-        let now = Instant::now();
+        let now = self.clock.now();
         let now_wall_clock = std::time::SystemTime::now();
         let common_fields = CommonFields {
             reference_time: ReferenceTime::new_monotonic(Some(now_wall_clock)),
@@ -2459,7 +2612,9 @@ impl<F: BufFactory> Connection<F> {
     /// This API is experimental and will be removed in the future.
     ///
     /// Currently this only applies if cc_algorithm is
-    /// `CongestionControlAlgorithm::Bbr2Gcongestion` is set.
+    /// `CongestionControlAlgorithm::Bbr2Gcongestion` or
+    /// `CongestionControlAlgorithm::Bbr3Gcongestion`. Settings override the
+    /// defaults of the selected BBR version.
     ///
     /// This function can only be called inside one of BoringSSL's handshake
     /// callbacks, before any packet has been sent. Calling this function any
@@ -2960,7 +3115,7 @@ impl<F: BufFactory> Connection<F> {
     fn recv_single(
         &mut self, buf: &mut [u8], info: &RecvInfo, recv_pid: Option<usize>,
     ) -> Result<usize> {
-        let now = Instant::now();
+        let now = self.clock.now();
 
         if buf.is_empty() {
             return Err(Error::Done);
@@ -3268,9 +3423,6 @@ impl<F: BufFactory> Connection<F> {
             AddrTupleFmt(info.from, info.to)
         );
 
-        #[cfg(feature = "qlog")]
-        let mut qlog_frames = vec![];
-
         // Check for key update.
         let mut aead_next = None;
 
@@ -3372,7 +3524,7 @@ impl<F: BufFactory> Connection<F> {
 
             self.key_phase = !self.key_phase;
 
-            qlog_with_type!(QLOG_PACKET_RX, self.qlog, q, {
+            qlog_with_type!(QLOG_KEY_UPDATED, self.qlog, q, {
                 let trigger = Some(
                     qlog::events::quic::KeyUpdateOrRetiredTrigger::RemoteUpdate,
                 );
@@ -3440,13 +3592,25 @@ impl<F: BufFactory> Connection<F> {
         // whether this is a non-probing packet.
         let mut probing = true;
 
+        // Whether the packet is logged is decided before collecting its frames,
+        // so that packets that are sampled out are cheap to process. Packets
+        // dropped before this point are not counted.
+        #[cfg(feature = "qlog")]
+        let mut qlog_frames = self
+            .qlog
+            .streamer
+            .as_mut()
+            .is_some_and(|q| q.sample_event(QLOG_PACKET_RX))
+            .then(Vec::new);
+
         // Process packet payload.
         while payload.cap() > 0 {
             let frame = frame::Frame::from_bytes(&mut payload, hdr.ty)?;
 
-            qlog_with_type!(QLOG_PACKET_RX, self.qlog, _q, {
+            #[cfg(feature = "qlog")]
+            if let Some(qlog_frames) = &mut qlog_frames {
                 qlog_frames.push(frame.to_qlog());
-            });
+            }
 
             if frame.ack_eliciting() {
                 ack_elicited = true;
@@ -3463,7 +3627,10 @@ impl<F: BufFactory> Connection<F> {
             }
         }
 
-        qlog_with_type!(QLOG_PACKET_RX, self.qlog, q, {
+        #[cfg(feature = "qlog")]
+        if let (Some(q), Some(qlog_frames)) =
+            (&mut self.qlog.streamer, qlog_frames)
+        {
             let packet_size = b.len();
 
             let qlog_pkt_hdr = qlog::events::quic::PacketHeader::with_type(
@@ -3490,7 +3657,7 @@ impl<F: BufFactory> Connection<F> {
             );
 
             q.add_event_data_with_instant(ev_data, now).ok();
-        });
+        }
 
         qlog_with_type!(QLOG_METRICS, self.qlog, q, {
             let recv_path = self.paths.get_mut(recv_pid)?;
@@ -3965,7 +4132,7 @@ impl<F: BufFactory> Connection<F> {
             return Err(Error::Done);
         }
 
-        let now = Instant::now();
+        let now = self.clock.now();
 
         if self.local_error.is_none() {
             self.do_handshake(now)?;
@@ -4667,7 +4834,7 @@ impl<F: BufFactory> Connection<F> {
             // Create PATH_CHALLENGE frame if needed.
             if path.validation_requested() {
                 // TODO: ensure that data is unique over paths.
-                let data = rand::rand_u64().to_be_bytes();
+                let data = self.rng.rand_u64().to_be_bytes();
 
                 let frame = frame::Frame::PathChallenge { data };
 
@@ -5325,6 +5492,17 @@ impl<F: BufFactory> Connection<F> {
             return Err(Error::Done);
         }
 
+        // Whether the packet is logged is only decided once it is known to be
+        // sent, so that sampling counts sent packets and packets that are
+        // sampled out don't collect their frames.
+        #[cfg(feature = "qlog")]
+        let qlog_pkt_hdr = qlog_pkt_hdr.filter(|_| {
+            self.qlog
+                .streamer
+                .as_mut()
+                .is_some_and(|q| q.sample_event(QLOG_PACKET_TX))
+        });
+
         // When coalescing a 1-RTT packet, we can't add padding in the UDP
         // datagram, so use PADDING frames instead.
         //
@@ -5380,44 +5558,44 @@ impl<F: BufFactory> Connection<F> {
 
         #[cfg(feature = "qlog")]
         let mut qlog_frames: Vec<qlog::events::quic::QuicFrame> =
-            Vec::with_capacity(frames.len());
+            Vec::with_capacity(qlog_pkt_hdr.as_ref().map_or(0, |_| frames.len()));
 
         for frame in &mut frames {
             trace!("{} tx frm {:?}", self.trace_id, frame);
 
-            qlog_with_type!(QLOG_PACKET_TX, self.qlog, _q, {
+            #[cfg(feature = "qlog")]
+            if qlog_pkt_hdr.is_some() {
                 qlog_frames.push(frame.to_qlog());
-            });
+            }
         }
 
-        qlog_with_type!(QLOG_PACKET_TX, self.qlog, q, {
-            if let Some(header) = qlog_pkt_hdr {
-                // Qlog packet raw info described at
-                // https://datatracker.ietf.org/doc/html/draft-ietf-quic-qlog-main-schema-00#section-5.1
-                //
-                // `length` includes packet headers and trailers (AEAD tag).
-                let length = payload_len + payload_offset + crypto_overhead;
-                let qlog_raw_info = RawInfo {
-                    length: Some(length as u64),
-                    payload_length: Some(payload_len as u64),
-                    data: None,
-                };
+        #[cfg(feature = "qlog")]
+        if let (Some(q), Some(header)) = (&mut self.qlog.streamer, qlog_pkt_hdr) {
+            // Qlog packet raw info described at
+            // https://datatracker.ietf.org/doc/html/draft-ietf-quic-qlog-main-schema-00#section-5.1
+            //
+            // `length` includes packet headers and trailers (AEAD tag).
+            let length = payload_len + payload_offset + crypto_overhead;
+            let qlog_raw_info = RawInfo {
+                length: Some(length as u64),
+                payload_length: Some(payload_len as u64),
+                data: None,
+            };
 
-                let send_at_time =
-                    now.duration_since(q.start_time()).as_secs_f64() * 1000.0;
+            let send_at_time =
+                now.duration_since(q.start_time()).as_secs_f64() * 1000.0;
 
-                let ev_data =
-                    EventData::QuicPacketSent(qlog::events::quic::PacketSent {
-                        header,
-                        frames: Some(qlog_frames),
-                        raw: Some(qlog_raw_info),
-                        send_at_time: Some(send_at_time),
-                        ..Default::default()
-                    });
+            let ev_data =
+                EventData::QuicPacketSent(qlog::events::quic::PacketSent {
+                    header,
+                    frames: Some(qlog_frames),
+                    raw: Some(qlog_raw_info),
+                    send_at_time: Some(send_at_time),
+                    ..Default::default()
+                });
 
-                q.add_event_data_with_instant(ev_data, now).ok();
-            }
-        });
+            q.add_event_data_with_instant(ev_data, now).ok();
+        }
 
         let aead = match crypto_ctx.crypto_seal {
             Some(ref mut v) => v,
@@ -5847,7 +6025,7 @@ impl<F: BufFactory> Connection<F> {
                 },
             );
 
-            let now = Instant::now();
+            let now = self.clock.now();
             q.add_event_data_with_instant(ev_data, now).ok();
         });
 
@@ -6130,7 +6308,7 @@ impl<F: BufFactory> Connection<F> {
                 },
             );
 
-            let now = Instant::now();
+            let now = self.clock.now();
             q.add_event_data_with_instant(ev_data, now).ok();
         });
 
@@ -6292,7 +6470,8 @@ impl<F: BufFactory> Connection<F> {
                         },
                     );
 
-                    q.add_event_data_with_instant(ev_data, Instant::now()).ok();
+                    q.add_event_data_with_instant(ev_data, self.clock.now())
+                        .ok();
                 });
 
                 // Update send capacity.
@@ -7055,7 +7234,7 @@ impl<F: BufFactory> Connection<F> {
     /// [`on_timeout()`]: struct.Connection.html#method.on_timeout
     pub fn timeout(&self) -> Option<Duration> {
         self.timeout_instant().map(|timeout| {
-            let now = Instant::now();
+            let now = self.clock.now();
 
             if timeout <= now {
                 Duration::ZERO
@@ -7069,7 +7248,7 @@ impl<F: BufFactory> Connection<F> {
     ///
     /// If no timeout has occurred it does nothing.
     pub fn on_timeout(&mut self) {
-        let now = Instant::now();
+        let now = self.clock.now();
 
         if let Some(draining_timer) = self.draining_timer {
             if draining_timer <= now {
@@ -7284,7 +7463,8 @@ impl<F: BufFactory> Connection<F> {
         };
 
         // Change the active path.
-        self.set_active_path(pid, Instant::now())?;
+        let now = self.clock.now();
+        self.set_active_path(pid, now)?;
 
         Ok(dcid_seq)
     }
@@ -8025,7 +8205,7 @@ impl<F: BufFactory> Connection<F> {
 
             local_transport_params: self.local_transport_params.clone(),
 
-            recovery_config: self.recovery_config,
+            recovery_config: self.recovery_config.clone(),
 
             tx_cap_factor: self.tx_cap_factor,
 
@@ -8053,7 +8233,7 @@ impl<F: BufFactory> Connection<F> {
                     if ex_data.recovery_config != self.recovery_config {
                         if let Ok(path) = self.paths.get_active_mut() {
                             self.recovery_config = ex_data.recovery_config;
-                            path.reinit_recovery(&self.recovery_config);
+                            path.reinit_recovery(&self.recovery_config, now);
                         }
                     }
 
@@ -9050,6 +9230,7 @@ impl<F: BufFactory> Connection<F> {
             self.path_challenge_recv_max_queue_len,
             false,
             None,
+            self.clock.now(),
         );
 
         path.max_send_bytes = buf_len * self.max_amplification_factor;
@@ -9201,6 +9382,7 @@ impl<F: BufFactory> Connection<F> {
             self.path_challenge_recv_max_queue_len,
             false,
             None,
+            self.clock.now(),
         );
         path.active_dcid_seq = Some(dcid_seq);
 
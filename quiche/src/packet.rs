@@ -32,6 +32,7 @@ use std::ops::RangeInclusive;
 
 use std::time::Instant;
 
+use crate::ConnectionRng;
 use crate::Error;
 use crate::Result;
 use crate::DEFAULT_INITIAL_CONGESTION_WINDOW_PACKETS;
@@ -1023,13 +1024,17 @@ pub struct PktNumManager {
     /// None indicates the counter is not armed while Some(0) indicates that the
     /// counter has expired.
     pub skip_pn_counter: Option<u64>,
+
+    /// Source of randomness for the skip counter.
+    rng: ConnectionRng,
 }
 
 impl PktNumManager {
-    pub fn new() -> Self {
+    pub fn new(rng: ConnectionRng) -> Self {
         PktNumManager {
             skip_pn: None,
             skip_pn_counter: None,
+            rng,
         }
     }
 
@@ -1097,7 +1102,7 @@ impl PktNumManager {
         let upper = packets_per_cwnd * 2;
         // rand_u64_uniform requires a non-zero value so add 1
         let skip_range = upper - lower + 1;
-        let rand_skip_value = rand::rand_u64_uniform(skip_range);
+        let rand_skip_value = self.rng.rand_u64_uniform(skip_range);
 
         // Skip calculation:
         // skip_counter = min_skip
@@ -2217,7 +2222,7 @@ mod tests {
 
     #[test]
     fn skip_pn() {
-        let mut skip_manager = PktNumManager::new();
+        let mut skip_manager = PktNumManager::new(ConnectionRng::default());
         let cwnd = 1000;
         let handshake_completed = true;
         let mut next_pn = 0;
@@ -2262,7 +2267,7 @@ mod tests {
 
     #[test]
     fn arm_skip_counter_only_after_verifying_prev_skip_pn() {
-        let mut skip_manager = PktNumManager::new();
+        let mut skip_manager = PktNumManager::new(ConnectionRng::default());
         let cwnd = 1000;
         let handshake_completed = true;
 
@@ -2295,7 +2300,7 @@ mod tests {
 
     #[test]
     fn arm_skip_counter_only_after_handshake_complete() {
-        let mut skip_manager = PktNumManager::new();
+        let mut skip_manager = PktNumManager::new(ConnectionRng::default());
         let cwnd = 1000;
         skip_manager.skip_pn_counter = None;
 
@@ -2320,7 +2325,7 @@ mod tests {
 
     #[test]
     fn only_skip_after_handshake_complete() {
-        let mut skip_manager = PktNumManager::new();
+        let mut skip_manager = PktNumManager::new(ConnectionRng::default());
         skip_manager.skip_pn_counter = Some(0);
 
         let mut handshake_completed = false;
@@ -232,7 +232,7 @@ impl Path {
         local_addr: SocketAddr, peer_addr: SocketAddr,
         recovery_config: &recovery::RecoveryConfig,
         path_challenge_recv_max_queue_len: usize, is_initial: bool,
-        config: Option<&Config>,
+        config: Option<&Config>, now: Instant,
     ) -> Self {
         let (state, active_scid_seq, active_dcid_seq) = if is_initial {
             (PathState::Validated, Some(0), Some(0))
@@ -264,7 +264,7 @@ impl Path {
             active_dcid_seq,
             state,
             active: false,
-            recovery: recovery::Recovery::new_with_config(recovery_config),
+            recovery: recovery::Recovery::new_with_config(recovery_config, now),
             pmtud,
             in_flight_challenges: VecDeque::new(),
             max_challenge_size: 0,
@@ -539,9 +539,9 @@ impl Path {
     }
 
     pub fn reinit_recovery(
-        &mut self, recovery_config: &recovery::RecoveryConfig,
+        &mut self, recovery_config: &recovery::RecoveryConfig, now: Instant,
     ) {
-        self.recovery = recovery::Recovery::new_with_config(recovery_config)
+        self.recovery = recovery::Recovery::new_with_config(recovery_config, now)
     }
 
     pub fn stats(&self) -> PathStats {
@@ -1090,6 +1090,7 @@ mod tests {
             config.path_challenge_recv_max_queue_len,
             true,
             None,
+            Instant::now(),
         );
         let mut path_mgr = PathMap::new(path, 2, false);
 
@@ -1100,6 +1101,7 @@ mod tests {
             config.path_challenge_recv_max_queue_len,
             false,
             None,
+            Instant::now(),
         );
         path_mgr.insert_path(probed_path, false).unwrap();
 
@@ -1177,6 +1179,7 @@ mod tests {
             config.path_challenge_recv_max_queue_len,
             true,
             None,
+            Instant::now(),
         );
         let mut client_path_mgr = PathMap::new(path, 2, false);
         let mut server_path = Path::new(
@@ -1186,6 +1189,7 @@ mod tests {
             config.path_challenge_recv_max_queue_len,
             false,
             None,
+            Instant::now(),
         );
 
         let client_pid = client_path_mgr
@@ -1261,6 +1265,7 @@ mod tests {
             config.path_challenge_recv_max_queue_len,
             true,
             None,
+            Instant::now(),
         );
         let mut client_path_mgr = PathMap::new(path, 2, false);
         let mut server_path = Path::new(
@@ -1270,6 +1275,7 @@ mod tests {
             config.path_challenge_recv_max_queue_len,
             false,
             None,
+            Instant::now(),
         );
 
         let client_pid = client_path_mgr
@@ -47,13 +47,18 @@ pub fn rand_u64() -> u64 {
 }
 
 pub fn rand_u64_uniform(max: u64) -> u64 {
+    uniform(max, rand_u64)
+}
+
+/// Maps the values returned by `next` to a uniform value in `[0, max)`.
+pub fn uniform(max: u64, mut next: impl FnMut() -> u64) -> u64 {
     let chunk_size = u64::MAX / max;
     let end_of_last_chunk = chunk_size * max;
 
-    let mut r = rand_u64();
+    let mut r = next();
 
     while r >= end_of_last_chunk {
-        r = rand_u64();
+        r = next();
     }
 
     r / chunk_size
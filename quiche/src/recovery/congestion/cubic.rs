@@ -709,6 +709,8 @@ mod tests {
         // Now we are in CSS.
         assert!(sender.hystart.css_start_time().is_some());
         assert_eq!(sender.congestion_window(), cwnd_prev + size);
+        #[cfg(feature = "qlog")]
+        assert_eq!(state_str(&sender, sender.time), "conservative_slow_start");
 
         // Run 5 (CSS_ROUNDS) in CSS, to exit to congestion avoidance.
         let rtt_css = Duration::from_millis(100);
@@ -729,6 +731,8 @@ mod tests {
         }
         // Now we are in congestion avoidance.
         assert_eq!(sender.congestion_window(), sender.ssthresh.get());
+        #[cfg(feature = "qlog")]
+        assert_eq!(state_str(&sender, sender.time), "congestion_avoidance");
     }
 
     #[test]
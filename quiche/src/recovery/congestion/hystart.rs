@@ -36,6 +36,9 @@ use std::time::Instant;
 
 use super::Acked;
 
+#[cfg(feature = "qlog")]
+use crate::recovery::CfExData;
+
 /// Constants from I-D.
 const MIN_RTT_THRESH: Duration = Duration::from_millis(4);
 
@@ -197,6 +200,29 @@ impl Hystart {
         self.window_end = None;
         self.css_start_time = None;
     }
+
+    // Add the RTTs driving HyStart++ transitions to a qlog event.
+    #[cfg(feature = "qlog")]
+    pub(super) fn add_qlog_state_data(&self, ex_data: &mut CfExData) {
+        if !self.enabled {
+            return;
+        }
+
+        let rtts = [
+            ("cf_hystart_last_round_min_rtt", self.last_round_min_rtt),
+            (
+                "cf_hystart_current_round_min_rtt",
+                self.current_round_min_rtt,
+            ),
+            ("cf_hystart_css_baseline_min_rtt", self.css_baseline_min_rtt),
+        ];
+
+        for (name, rtt) in rtts {
+            if rtt != Duration::MAX {
+                ex_data.insert(name, rtt.as_secs_f32() * 1000.0);
+            }
+        }
+    }
 }
 
 #[cfg(test)]
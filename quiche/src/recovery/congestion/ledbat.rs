@@ -0,0 +1,552 @@
+// Copyright (C) 2026, Cloudflare, Inc.
+// All rights reserved.
+//
+// Redistribution and use in source and binary forms, with or without
+// modification, are permitted provided that the following conditions are
+// met:
+//
+//     * Redistributions of source code must retain the above copyright notice,
+//       this list of conditions and the following disclaimer.
+//
+//     * Redistributions in binary form must reproduce the above copyright
+//       notice, this list of conditions and the following disclaimer in the
+//       documentation and/or other materials provided with the distribution.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS
+// IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO,
+// THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR
+// PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR
+// CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL,
+// EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO,
+// PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR
+// PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF
+// LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING
+// NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS
+// SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! LEDBAT++ Congestion Control
+//!
+//! A less-than-best-effort congestion controller for background transfers,
+//! which backs off as soon as it sees queueing delay so that it yields to
+//! competing flows.
+//!
+//! This implementation is based on the following draft:
+//! <https://datatracker.ietf.org/doc/html/draft-irtf-iccrg-ledbat-plus-plus>
+//!
+//! Queueing delay is measured as the difference between the filtered latest
+//! RTT and the minimum RTT, rather than using one-way delays.
+
+use std::cmp;
+use std::collections::VecDeque;
+use std::time::Duration;
+use std::time::Instant;
+
+use super::reno;
+use super::rtt::RttStats;
+use super::Acked;
+use super::Sent;
+
+use super::Congestion;
+use super::CongestionControlOps;
+use crate::recovery::LOSS_REDUCTION_FACTOR;
+use crate::recovery::MINIMUM_WINDOW_PACKETS;
+use crate::StartupExitReason;
+
+pub(crate) static LEDBAT: CongestionControlOps = CongestionControlOps {
+    on_init,
+    on_packet_sent,
+    on_packets_acked,
+    congestion_event,
+    checkpoint,
+    rollback,
+    #[cfg(feature = "qlog")]
+    state_str,
+    debug_fmt,
+};
+
+/// LEDBAT++ Constants.
+///
+/// These are recommended values in the draft.
+const TARGET: Duration = Duration::from_millis(60);
+
+/// Upper bound of the inverse of the window increase gain.
+const MAX_GAIN_DIVISOR: f64 = 16.0;
+
+/// Multiplicative decrease factor applied when above the target delay.
+const CONSTANT: f64 = 1.0;
+
+/// Slow start is exited once queueing delay exceeds this fraction of the
+/// target.
+const SLOW_START_EXIT_FACTOR: f64 = 0.75;
+
+/// Number of RTT samples the current delay filter keeps.
+const CURRENT_DELAY_FILTER: usize = 4;
+
+/// Number of RTTs the window is kept at its minimum during a slowdown.
+const SLOWDOWN_RTTS: u32 = 2;
+
+/// Time between slowdowns, as a multiple of the last slowdown's duration.
+const SLOWDOWN_INTERVAL_FACTOR: u32 = 9;
+
+/// LEDBAT++ State Variables.
+#[derive(Debug, Default)]
+pub struct State {
+    // The most recent RTT samples, used to filter out noise from the
+    // current delay.
+    rtt_samples: VecDeque<Duration>,
+
+    // Fractional window increase (or decrease) carried across ACKs.
+    cwnd_inc: f64,
+
+    slowdown: Slowdown,
+}
+
+/// Periodic slowdowns drain the queue built by this connection, so that the
+/// minimum RTT keeps tracking the base delay of the path.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Slowdown {
+    // The initial slow start has not ended yet.
+    #[default]
+    Inactive,
+
+    // The next slowdown starts at the given time.
+    Scheduled(Instant),
+
+    // The window is held at its minimum until `until`.
+    Frozen {
+        start: Instant,
+        until: Instant,
+    },
+
+    // Slow start back to the window preceding the slowdown.
+    Recovering {
+        start: Instant,
+    },
+}
+
+impl State {
+    fn on_rtt_sample(&mut self, rtt: Duration) {
+        if self.rtt_samples.len() == CURRENT_DELAY_FILTER {
+            self.rtt_samples.pop_front();
+        }
+
+        self.rtt_samples.push_back(rtt);
+    }
+
+    fn current_delay(&self) -> Option<Duration> {
+        self.rtt_samples.iter().min().copied()
+    }
+}
+
+// GAIN = 1 / min(16, ceil(2 * TARGET / base_delay))
+fn gain(base_delay: Duration) -> f64 {
+    if base_delay.is_zero() {
+        return 1.0 / MAX_GAIN_DIVISOR;
+    }
+
+    let divisor = (2.0 * TARGET.as_secs_f64() / base_delay.as_secs_f64()).ceil();
+
+    1.0 / divisor.clamp(1.0, MAX_GAIN_DIVISOR)
+}
+
+fn min_window(r: &Congestion) -> usize {
+    r.max_datagram_size * MINIMUM_WINDOW_PACKETS
+}
+
+fn on_init(_r: &mut Congestion) {}
+
+fn on_packet_sent(
+    r: &mut Congestion, sent_bytes: usize, bytes_in_flight: usize, now: Instant,
+) {
+    reno::on_packet_sent(r, sent_bytes, bytes_in_flight, now);
+}
+
+fn on_packets_acked(
+    r: &mut Congestion, _bytes_in_flight: usize, packets: &mut Vec<Acked>,
+    now: Instant, rtt_stats: &RttStats,
+) {
+    r.ledbat_state.on_rtt_sample(rtt_stats.latest_rtt());
+
+    update_slowdown(r, now, rtt_stats.rtt());
+
+    for pkt in packets.drain(..) {
+        on_packet_acked(r, &pkt, rtt_stats);
+    }
+
+    maybe_schedule_slowdown(r, now, rtt_stats.rtt());
+}
+
+fn on_packet_acked(r: &mut Congestion, packet: &Acked, rtt_stats: &RttStats) {
+    if r.in_congestion_recovery(packet.time_sent) {
+        return;
+    }
+
+    if matches!(r.ledbat_state.slowdown, Slowdown::Frozen { .. }) {
+        return;
+    }
+
+    let (base_delay, current_delay) =
+        match (rtt_stats.min_rtt(), r.ledbat_state.current_delay()) {
+            (Some(base_delay), Some(current_delay)) =>
+                (base_delay, current_delay),
+
+            _ => return,
+        };
+
+    let queue_delay = current_delay.saturating_sub(base_delay);
+    let gain = gain(base_delay);
+
+    if r.congestion_window < r.ssthresh.get() {
+        // Slow start, exited early when queueing delay builds up.
+        if queue_delay.as_secs_f64() >
+            TARGET.as_secs_f64() * SLOW_START_EXIT_FACTOR
+        {
+            r.ssthresh.update_with_reason(
+                r.congestion_window,
+                StartupExitReason::PersistentQueue,
+            );
+
+            return;
+        }
+
+        if r.app_limited {
+            return;
+        }
+
+        r.ledbat_state.cwnd_inc += gain * packet.size as f64;
+    } else {
+        // Congestion avoidance.
+        let cwnd_packets =
+            r.congestion_window as f64 / r.max_datagram_size as f64;
+        let acked_packets = packet.size as f64 / r.max_datagram_size as f64;
+
+        let delta = if queue_delay <= TARGET {
+            if r.app_limited {
+                return;
+            }
+
+            gain
+        } else {
+            // F = GAIN - CONSTANT * W * (delay / TARGET - 1), reducing the
+            // window by at most half of it per RTT.
+            let ratio = queue_delay.as_secs_f64() / TARGET.as_secs_f64();
+
+            f64::max(
+                gain - CONSTANT * cwnd_packets * (ratio - 1.0),
+                -cwnd_packets / 2.0,
+            )
+        };
+
+        r.ledbat_state.cwnd_inc +=
+            delta * acked_packets / cwnd_packets * r.max_datagram_size as f64;
+    }
+
+    apply_cwnd_inc(r);
+}
+
+fn apply_cwnd_inc(r: &mut Congestion) {
+    let inc = r.ledbat_state.cwnd_inc.trunc();
+    r.ledbat_state.cwnd_inc -= inc;
+
+    let cwnd = (r.congestion_window as f64 + inc).max(0.0) as usize;
+
+    r.congestion_window = cmp::max(cwnd, min_window(r));
+}
+
+// Starts and ends slowdowns as their deadlines are reached.
+fn update_slowdown(r: &mut Congestion, now: Instant, rtt: Duration) {
+    match r.ledbat_state.slowdown {
+        Slowdown::Scheduled(start) if now >= start => {
+            r.ssthresh.update(r.congestion_window, false);
+            r.congestion_window = min_window(r);
+            r.ledbat_state.cwnd_inc = 0.0;
+
+            r.ledbat_state.slowdown = Slowdown::Frozen {
+                start: now,
+                until: now + rtt * SLOWDOWN_RTTS,
+            };
+        },
+
+        Slowdown::Frozen { start, until } if now >= until => {
+            r.ledbat_state.slowdown = Slowdown::Recovering { start };
+        },
+
+        _ => (),
+    }
+}
+
+// Schedules the next slowdown once slow start ends.
+fn maybe_schedule_slowdown(r: &mut Congestion, now: Instant, rtt: Duration) {
+    if r.congestion_window < r.ssthresh.get() {
+        return;
+    }
+
+    match r.ledbat_state.slowdown {
+        // The first slowdown happens two RTTs after the initial slow start.
+        Slowdown::Inactive => {
+            r.ledbat_state.slowdown =
+                Slowdown::Scheduled(now + rtt * SLOWDOWN_RTTS);
+        },
+
+        Slowdown::Recovering { start } => {
+            let duration = now.saturating_duration_since(start);
+
+            r.ledbat_state.slowdown =
+                Slowdown::Scheduled(now + duration * SLOWDOWN_INTERVAL_FACTOR);
+        },
+
+        _ => (),
+    }
+}
+
+fn congestion_event(
+    r: &mut Congestion, _bytes_in_flight: usize, _lost_bytes: usize,
+    largest_lost_pkt: &Sent, now: Instant,
+) {
+    // Start a new congestion event if packet was sent after the
+    // start of the previous congestion recovery period.
+    let time_sent = largest_lost_pkt.time_sent;
+
+    if !r.in_congestion_recovery(time_sent) {
+        r.congestion_recovery_start_time = Some(now);
+
+        r.congestion_window =
+            (r.congestion_window as f64 * LOSS_REDUCTION_FACTOR) as usize;
+
+        r.congestion_window = cmp::max(r.congestion_window, min_window(r));
+
+        r.ledbat_state.cwnd_inc = 0.0;
+
+        // Slow start ends on loss, the next slowdown is scheduled on the
+        // following ACK.
+        r.ssthresh.update(r.congestion_window, false);
+    }
+}
+
+fn checkpoint(_r: &mut Congestion) {}
+
+fn rollback(_r: &mut Congestion) -> bool {
+    true
+}
+
+#[cfg(feature = "qlog")]
+fn state_str(r: &Congestion, now: Instant) -> &'static str {
+    if matches!(r.ledbat_state.slowdown, Slowdown::Frozen { .. }) {
+        return "periodic_slowdown";
+    }
+
+    reno::state_str(r, now)
+}
+
+fn debug_fmt(r: &Congestion, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(
+        f,
+        "ledbat={{ current_delay={:?} slowdown={:?} }} ",
+        r.ledbat_state.current_delay(),
+        r.ledbat_state.slowdown
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::CongestionControlAlgorithm;
+
+    use crate::recovery::congestion::recovery::LegacyRecovery;
+    use crate::recovery::congestion::test_sender::TestSender;
+    use crate::recovery::RecoveryOps;
+
+    fn test_sender() -> TestSender {
+        TestSender::new(CongestionControlAlgorithm::Ledbat, false)
+    }
+
+    // Sends and acknowledges a window worth of packets, one RTT later.
+    fn send_and_ack_round(sender: &mut TestSender, rtt: Duration) {
+        let size = sender.max_datagram_size;
+        let packets = sender.congestion_window / size;
+
+        for _ in 0..packets {
+            sender.send_packet(size);
+        }
+
+        sender.advance_time(rtt);
+        sender.update_rtt(rtt);
+        sender.ack_n_packets(packets, size);
+    }
+
+    #[test]
+    fn ledbat_init() {
+        let mut cfg = crate::Config::new(crate::PROTOCOL_VERSION).unwrap();
+        cfg.set_cc_algorithm(CongestionControlAlgorithm::Ledbat);
+
+        let r = LegacyRecovery::new(&cfg);
+
+        assert!(r.cwnd() > 0);
+        assert_eq!(r.bytes_in_flight(), 0);
+    }
+
+    #[test]
+    fn ledbat_gain() {
+        assert_eq!(gain(Duration::from_millis(120)), 1.0);
+        assert_eq!(gain(Duration::from_millis(60)), 0.5);
+        assert_eq!(gain(Duration::from_millis(50)), 1.0 / 3.0);
+        assert_eq!(gain(Duration::from_millis(1)), 1.0 / 16.0);
+        assert_eq!(gain(Duration::ZERO), 1.0 / 16.0);
+    }
+
+    #[test]
+    fn ledbat_slow_start() {
+        let mut sender = test_sender();
+        let rtt = Duration::from_millis(120);
+
+        let prev_cwnd = sender.congestion_window;
+
+        send_and_ack_round(&mut sender, rtt);
+
+        // No queueing delay, and a gain of 1 for a 120ms base delay: the
+        // window doubles like in regular slow start.
+        assert_eq!(sender.congestion_window, 2 * prev_cwnd);
+        assert_eq!(sender.ssthresh.get(), usize::MAX);
+    }
+
+    #[test]
+    fn ledbat_slow_start_exit_on_delay() {
+        let mut sender = test_sender();
+        let size = sender.max_datagram_size;
+        let base_rtt = Duration::from_millis(120);
+
+        send_and_ack_round(&mut sender, base_rtt);
+
+        // Enough samples with 50ms of queueing delay for the filter to
+        // report it.
+        let rtt = base_rtt + Duration::from_millis(50);
+        for _ in 0..CURRENT_DELAY_FILTER {
+            sender.send_packet(size);
+            sender.advance_time(rtt);
+            sender.update_rtt(rtt);
+            sender.ack_n_packets(1, size);
+        }
+
+        assert_eq!(sender.ssthresh.get(), sender.congestion_window);
+        assert_eq!(
+            sender.ssthresh.startup_exit().map(|e| e.reason),
+            Some(StartupExitReason::PersistentQueue)
+        );
+        assert!(matches!(
+            sender.ledbat_state.slowdown,
+            Slowdown::Scheduled(_)
+        ));
+    }
+
+    #[test]
+    fn ledbat_congestion_avoidance_decrease() {
+        let mut sender = test_sender();
+        let size = sender.max_datagram_size;
+        let base_rtt = Duration::from_millis(120);
+
+        // Leave slow start.
+        send_and_ack_round(&mut sender, base_rtt);
+        let cwnd = sender.congestion_window;
+        sender.ssthresh.update(cwnd, false);
+
+        let prev_cwnd = sender.congestion_window;
+
+        // Queueing delay twice the target shrinks the window, by at most
+        // half of it per RTT.
+        let rtt = base_rtt + 2 * TARGET;
+        for _ in 0..CURRENT_DELAY_FILTER {
+            sender.ledbat_state.on_rtt_sample(rtt);
+        }
+
+        send_and_ack_round(&mut sender, rtt);
+
+        assert!(sender.congestion_window < prev_cwnd);
+        assert!(sender.congestion_window >= prev_cwnd / 2 - size);
+    }
+
+    #[test]
+    fn ledbat_congestion_avoidance_increase() {
+        let mut sender = test_sender();
+        let size = sender.max_datagram_size;
+        let base_rtt = Duration::from_millis(120);
+
+        // Leave slow start.
+        send_and_ack_round(&mut sender, base_rtt);
+        let cwnd = sender.congestion_window;
+        sender.ssthresh.update(cwnd, false);
+
+        let prev_cwnd = sender.congestion_window;
+
+        send_and_ack_round(&mut sender, base_rtt);
+
+        // Below the target delay, the window grows by about GAIN packets
+        // per RTT.
+        assert!(sender.congestion_window >= prev_cwnd + size * 9 / 10);
+        assert!(sender.congestion_window <= prev_cwnd + size);
+    }
+
+    #[test]
+    fn ledbat_congestion_event() {
+        let mut sender = test_sender();
+        let size = sender.max_datagram_size;
+
+        let prev_cwnd = sender.congestion_window;
+
+        sender.send_packet(size);
+        sender.lose_n_packets(1, size, None);
+
+        assert_eq!(prev_cwnd / 2, sender.congestion_window);
+        assert_eq!(sender.ssthresh.get(), sender.congestion_window);
+    }
+
+    #[test]
+    fn ledbat_periodic_slowdown() {
+        let mut sender = test_sender();
+        let size = sender.max_datagram_size;
+        let rtt = Duration::from_millis(120);
+
+        // Leave the initial slow start.
+        send_and_ack_round(&mut sender, rtt);
+        let cwnd = sender.congestion_window;
+        sender.ssthresh.update(cwnd, false);
+        send_and_ack_round(&mut sender, rtt);
+
+        let cwnd = sender.congestion_window;
+
+        // The first slowdown starts two RTTs after slow start ends.
+        assert!(matches!(
+            sender.ledbat_state.slowdown,
+            Slowdown::Scheduled(_)
+        ));
+
+        send_and_ack_round(&mut sender, rtt);
+        send_and_ack_round(&mut sender, rtt);
+
+        assert!(matches!(
+            sender.ledbat_state.slowdown,
+            Slowdown::Frozen { .. }
+        ));
+        assert_eq!(sender.congestion_window, size * MINIMUM_WINDOW_PACKETS);
+        assert!(sender.ssthresh.get() >= cwnd);
+
+        // The window stays at its minimum for two RTTs.
+        send_and_ack_round(&mut sender, rtt);
+        assert_eq!(sender.congestion_window, size * MINIMUM_WINDOW_PACKETS);
+
+        // Then slow start back to the previous window, and schedule the
+        // next slowdown.
+        let mut rounds = 0;
+        while sender.congestion_window < sender.ssthresh.get() {
+            send_and_ack_round(&mut sender, rtt);
+
+            rounds += 1;
+            assert!(rounds < 10);
+        }
+
+        assert!(matches!(
+            sender.ledbat_state.slowdown,
+            Slowdown::Scheduled(_)
+        ));
+    }
+}
@@ -65,15 +65,19 @@ impl SsThresh {
     }
 
     fn update(&mut self, ssthresh: usize, in_css: bool) {
+        let reason = if in_css {
+            // Exit happened in conservative slow start, attribute
+            // the exit to CSS.
+            StartupExitReason::ConservativeSlowStartRounds
+        } else {
+            // In normal slow start, attribute the exit to loss.
+            StartupExitReason::Loss
+        };
+        self.update_with_reason(ssthresh, reason);
+    }
+
+    fn update_with_reason(&mut self, ssthresh: usize, reason: StartupExitReason) {
         if self.startup_exit.is_none() {
-            let reason = if in_css {
-                // Exit happened in conservative slow start, attribute
-                // the exit to CSS.
-                StartupExitReason::ConservativeSlowStartRounds
-            } else {
-                // In normal slow start, attribute the exit to loss.
-                StartupExitReason::Loss
-            };
             self.startup_exit = Some(StartupExit::new(ssthresh, None, reason));
         }
         self.ssthresh = ssthresh;
@@ -86,6 +90,8 @@ pub struct Congestion {
 
     cubic_state: cubic::State,
 
+    ledbat_state: ledbat::State,
+
     // HyStart++.
     pub(crate) hystart: hystart::Hystart,
 
@@ -140,6 +146,8 @@ impl Congestion {
 
             cubic_state: cubic::State::default(),
 
+            ledbat_state: ledbat::State::default(),
+
             app_limited: false,
 
             lost_count: 0,
@@ -288,11 +296,13 @@ impl From<CongestionControlAlgorithm> for &'static CongestionControlOps {
         match algo {
             CongestionControlAlgorithm::Reno => &reno::RENO,
             CongestionControlAlgorithm::CUBIC => &cubic::CUBIC,
-            // Bbr2Gcongestion is routed to the congestion implementation in
-            // the gcongestion directory by Recovery::new_with_config;
-            // LegacyRecovery never gets a RecoveryConfig with the
-            // Bbr2Gcongestion algorithm.
-            CongestionControlAlgorithm::Bbr2Gcongestion => unreachable!(),
+            CongestionControlAlgorithm::Ledbat => &ledbat::LEDBAT,
+            // Bbr2Gcongestion and Bbr3Gcongestion are routed to the
+            // congestion implementation in the gcongestion directory by
+            // Recovery::new_with_config; LegacyRecovery never gets a
+            // RecoveryConfig with either algorithm.
+            CongestionControlAlgorithm::Bbr2Gcongestion |
+            CongestionControlAlgorithm::Bbr3Gcongestion => unreachable!(),
         }
     }
 }
@@ -353,6 +363,7 @@ mod tests {
 mod cubic;
 mod delivery_rate;
 mod hystart;
+mod ledbat;
 mod prr;
 pub(crate) mod recovery;
 mod reno;
@@ -44,6 +44,10 @@ use crate::recovery::StartupExit;
 use crate::Error;
 use crate::Result;
 
+#[cfg(feature = "qlog")]
+use crate::recovery::qlog_cc_state_updated;
+#[cfg(feature = "qlog")]
+use crate::recovery::CfExData;
 #[cfg(feature = "qlog")]
 use crate::recovery::QlogMetrics;
 
@@ -1067,16 +1071,18 @@ impl RecoveryOps for LegacyRecovery {
     }
 
     #[cfg(feature = "qlog")]
-    fn get_updated_qlog_cc_state(
-        &mut self, now: Instant,
-    ) -> Option<&'static str> {
+    fn get_updated_qlog_cc_state(&mut self, now: Instant) -> Option<EventData> {
         let cc_state = self.state_str(now);
-        if cc_state != self.qlog_prev_cc_state {
-            self.qlog_prev_cc_state = cc_state;
-            Some(cc_state)
-        } else {
-            None
+        if cc_state == self.qlog_prev_cc_state {
+            return None;
         }
+
+        let mut ex_data = CfExData::new();
+        self.congestion.hystart.add_qlog_state_data(&mut ex_data);
+
+        let old = std::mem::replace(&mut self.qlog_prev_cc_state, cc_state);
+
+        Some(qlog_cc_state_updated(old, cc_state, ex_data))
     }
 
     fn send_quantum(&self) -> usize {
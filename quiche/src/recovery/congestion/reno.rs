@@ -144,10 +144,14 @@ fn rollback(_r: &mut Congestion) -> bool {
 
 #[cfg(feature = "qlog")]
 pub fn state_str(r: &Congestion, now: Instant) -> &'static str {
-    if r.hystart.in_css() {
-        "conservative_slow_start"
-    } else if r.congestion_window < r.ssthresh.get() {
-        "slow_start"
+    // HyStart++ keeps its CSS start time after exiting to congestion
+    // avoidance, so only report CSS while still in slow start.
+    if r.congestion_window < r.ssthresh.get() {
+        if r.hystart.in_css() {
+            "conservative_slow_start"
+        } else {
+            "slow_start"
+        }
     } else if r.in_congestion_recovery(now) {
         "recovery"
     } else {
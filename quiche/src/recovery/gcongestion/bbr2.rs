@@ -42,7 +42,10 @@ use std::time::Instant;
 use network_model::BBRv2NetworkModel;
 
 use crate::recovery::gcongestion::Bandwidth;
+#[cfg(feature = "qlog")]
+use crate::recovery::CfExData;
 use crate::recovery::RecoveryStats;
+use crate::ConnectionRng;
 
 use self::mode::Mode;
 use self::mode::ModeImpl;
@@ -66,6 +69,10 @@ struct Params {
 
     startup_pacing_gain: f32,
 
+    /// The gain applied to the initial congestion window over the initial RTT
+    /// to compute the pacing rate used before the first RTT sample.
+    initial_pacing_gain: f32,
+
     /// STARTUP or PROBE_UP are exited if the total bandwidth growth is less
     /// than `full_bw_threshold` in the last `startup_full_bw_rounds`` round
     /// trips.
@@ -101,6 +108,11 @@ struct Params {
     /// Minimum duration for BBR-native probes.
     probe_bw_probe_base_duration: Duration,
 
+    /// Maximum random duration added to `probe_bw_probe_base_duration`, so
+    /// flows sharing a bottleneck don't probe in lockstep. If zero, the time
+    /// between probes is fixed.
+    probe_bw_probe_max_rand_duration: Duration,
+
     /// The minimum number of loss marking events to exit the PROBE_UP phase.
     probe_bw_full_loss_count: usize,
 
@@ -258,7 +270,7 @@ impl Params {
         apply_override!(time_sent_set_to_now);
         apply_optional_override!(initial_pacing_rate_bytes_per_second);
 
-        #[cfg(feature = "internal")]
+        #[cfg(any(test, feature = "internal"))]
         {
             if let Some(custom_value) = custom_bbr_settings.rtt_jump_detector {
                 self.rtt_jump_detector = custom_value;
@@ -278,6 +290,8 @@ const DEFAULT_PARAMS: Params = Params {
 
     startup_pacing_gain: 2.773,
 
+    initial_pacing_gain: 2.885,
+
     full_bw_threshold: 1.25,
 
     startup_full_bw_rounds: 3,
@@ -298,6 +312,8 @@ const DEFAULT_PARAMS: Params = Params {
 
     probe_bw_probe_base_duration: Duration::from_millis(2000),
 
+    probe_bw_probe_max_rand_duration: Duration::ZERO,
+
     probe_bw_full_loss_count: 2,
 
     probe_bw_probe_up_pacing_gain: 1.25,
@@ -363,6 +379,58 @@ const DEFAULT_PARAMS: Params = Params {
     rtt_jump_detector: BbrRttJumpDetector::Disabled,
 };
 
+/// The parameters of BBRv3, as described in draft-ietf-ccwg-bbr.
+///
+/// BBRv3 shares the state machine and network model of BBRv2, and differs in:
+///
+/// * STARTUP pacing: both the initial and the STARTUP pacing gains are 4 *
+///   ln(2), the smallest gain that doubles the sending rate every round.
+///
+/// * Loss response: STARTUP exits after 6 loss events in a round, and PROBE_UP
+///   stops at the first loss event, once the loss rate of the round exceeds 2%.
+///
+/// * PROBE_BW cycle: the time between bandwidth probes is picked at random
+///   between 2 and 3 seconds, and PROBE_DOWN lasts until inflight has drained
+///   to the estimated BDP.
+///
+/// BBRv3 also responds to ECN marks, but quiche does not mark packets as
+/// ECN-capable so that response is not implemented.
+const BBR3_PARAMS: Params = Params {
+    startup_pacing_gain: 2.77,
+
+    initial_pacing_gain: 2.77,
+
+    decrease_startup_pacing_at_end_of_round: false,
+
+    startup_full_loss_count: 6,
+
+    probe_bw_full_loss_count: 1,
+
+    loss_threshold: 0.02,
+
+    probe_bw_probe_max_rand_duration: Duration::from_millis(1000),
+
+    disable_probe_down_early_exit: true,
+
+    ..DEFAULT_PARAMS
+};
+
+/// The version of the BBR algorithm implemented by [`BBRv2`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BbrVersion {
+    V2,
+    V3,
+}
+
+impl BbrVersion {
+    fn default_params(self) -> Params {
+        match self {
+            BbrVersion::V2 => DEFAULT_PARAMS,
+            BbrVersion::V3 => BBR3_PARAMS,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 enum BwLoMode {
     /// Mode that implements the BBRAdaptLowerBoundsFromCongestion()
@@ -433,7 +501,8 @@ fn initial_pacing_rate(
         return Bandwidth::from_bytes_per_second(pacing_rate);
     }
 
-    Bandwidth::from_bytes_and_time_delta(cwnd_in_bytes, smoothed_rtt) * 2.885
+    Bandwidth::from_bytes_and_time_delta(cwnd_in_bytes, smoothed_rtt) *
+        params.initial_pacing_gain
 }
 
 #[derive(Debug)]
@@ -511,21 +580,28 @@ impl BBRv2CongestionEvent {
 }
 
 impl BBRv2 {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
-        initial_congestion_window: usize, max_congestion_window: usize,
-        max_segment_size: usize, smoothed_rtt: Duration,
-        custom_bbr_params: Option<&BbrParams>,
+        version: BbrVersion, initial_congestion_window: usize,
+        max_congestion_window: usize, max_segment_size: usize,
+        smoothed_rtt: Duration, custom_bbr_params: Option<&BbrParams>,
+        now: Instant, rng: &ConnectionRng,
     ) -> Self {
         let cwnd = initial_congestion_window * max_segment_size;
 
         let params = if let Some(custom_bbr_settings) = custom_bbr_params {
-            DEFAULT_PARAMS.with_overrides(custom_bbr_settings)
+            version.default_params().with_overrides(custom_bbr_settings)
         } else {
-            DEFAULT_PARAMS
+            version.default_params()
         };
 
         BBRv2 {
-            mode: Mode::startup(BBRv2NetworkModel::new(&params, smoothed_rtt)),
+            mode: Mode::startup(BBRv2NetworkModel::new(
+                &params,
+                smoothed_rtt,
+                now,
+                rng,
+            )),
             cwnd,
             pacing_rate: initial_pacing_rate(cwnd, smoothed_rtt, &params),
             cwnd_limits: Limits {
@@ -657,6 +733,27 @@ impl BBRv2 {
     pub(crate) fn rtt_persistent_jump_count(&self) -> u64 {
         self.mode.network_model().rtt_persistent_jump_count()
     }
+
+    /// Adds the details of the most recently confirmed persistent RTT jump to
+    /// `ex_data`.
+    #[cfg(feature = "qlog")]
+    pub(crate) fn add_qlog_rtt_jump_data(
+        &self, now: Instant, ex_data: &mut CfExData,
+    ) {
+        let network_model = self.mode.network_model();
+
+        ex_data.insert(
+            "cf_rtt_jump_count",
+            network_model.rtt_persistent_jump_count(),
+        );
+
+        if let Some(start) = network_model.last_persistent_jump_time() {
+            ex_data.insert(
+                "cf_rtt_jump_duration",
+                now.saturating_duration_since(start).as_secs_f32() * 1000.0,
+            );
+        }
+    }
 }
 
 impl CongestionControl for BBRv2 {
@@ -665,6 +762,33 @@ impl CongestionControl for BBRv2 {
         self.mode.state_str()
     }
 
+    #[cfg(feature = "qlog")]
+    fn add_qlog_state_data(&self, ex_data: &mut CfExData) {
+        let network_model = self.mode.network_model();
+
+        ex_data.insert("cf_pacing_gain", network_model.pacing_gain());
+        ex_data.insert("cf_cwnd_gain", network_model.cwnd_gain());
+        ex_data.insert(
+            "cf_bandwidth_estimate",
+            network_model.bandwidth_estimate().to_bytes_per_second(),
+        );
+        ex_data.insert(
+            "cf_max_bandwidth",
+            network_model.max_bandwidth().to_bytes_per_second(),
+        );
+        ex_data
+            .insert("cf_min_rtt", network_model.min_rtt().as_secs_f32() * 1000.0);
+
+        // Unset bounds are omitted.
+        if network_model.inflight_hi() != network_model.inflight_hi_default() {
+            ex_data.insert("cf_inflight_hi", network_model.inflight_hi());
+        }
+
+        if network_model.inflight_lo() != usize::MAX {
+            ex_data.insert("cf_inflight_lo", network_model.inflight_lo());
+        }
+    }
+
     fn get_congestion_window(&self) -> usize {
         self.cwnd
     }
@@ -840,11 +964,14 @@ mod tests {
         const NEW_MAX_CWND: usize = MAX_WINDOW_PACKETS * NEW_PACKET_SIZE;
 
         let mut bbr2 = BBRv2::new(
+            BbrVersion::V2,
             INIT_WINDOW_PACKETS,
             MAX_WINDOW_PACKETS,
             INIT_PACKET_SIZE,
             initial_rtt,
             Some(bbr_params),
+            Instant::now(),
+            &Default::default(),
         );
 
         assert_eq!(bbr2.cwnd_limits.lo, INIT_CWND);
@@ -870,4 +997,52 @@ mod tests {
             (2.88499 * pacing_cwnd as f64) as u64
         );
     }
+
+    #[test]
+    fn bbr3_params() {
+        const INIT_PACKET_SIZE: usize = 1200;
+        const INIT_WINDOW_PACKETS: usize = 10;
+        const INIT_CWND: usize = INIT_WINDOW_PACKETS * INIT_PACKET_SIZE;
+        let initial_rtt = Duration::from_millis(333);
+
+        let bbr3 = BBRv2::new(
+            BbrVersion::V3,
+            INIT_WINDOW_PACKETS,
+            10000,
+            INIT_PACKET_SIZE,
+            initial_rtt,
+            None,
+            Instant::now(),
+            &Default::default(),
+        );
+
+        assert_eq!(bbr3.params.startup_pacing_gain, 2.77);
+        assert_eq!(bbr3.mode.network_model().pacing_gain(), 2.77);
+        assert_eq!(bbr3.params.loss_threshold, 0.02);
+        assert_eq!(bbr3.params.startup_full_loss_count, 6);
+        assert_eq!(bbr3.params.probe_bw_full_loss_count, 1);
+        assert!(bbr3.params.disable_probe_down_early_exit);
+        assert_eq!(
+            bbr3.pacing_rate.to_bytes_per_period(initial_rtt),
+            (2.76999 * INIT_CWND as f64) as u64
+        );
+
+        // Custom settings apply on top of the BBRv3 defaults.
+        let bbr3 = BBRv2::new(
+            BbrVersion::V3,
+            INIT_WINDOW_PACKETS,
+            10000,
+            INIT_PACKET_SIZE,
+            initial_rtt,
+            Some(&BbrParams {
+                startup_pacing_gain: Some(2.5),
+                ..Default::default()
+            }),
+            Instant::now(),
+            &Default::default(),
+        );
+
+        assert_eq!(bbr3.params.startup_pacing_gain, 2.5);
+        assert_eq!(bbr3.params.loss_threshold, 0.02);
+    }
 }
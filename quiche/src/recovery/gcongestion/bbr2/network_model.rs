@@ -36,6 +36,7 @@ use crate::recovery::gcongestion::bbr::BandwidthSampler;
 use crate::recovery::gcongestion::bbr2::Params;
 use crate::recovery::gcongestion::Bandwidth;
 use crate::recovery::gcongestion::Lost;
+use crate::ConnectionRng;
 
 use super::rtt_jump_detector::RttJumpDetector;
 use super::Acked;
@@ -195,10 +196,15 @@ pub(super) struct BBRv2NetworkModel {
 
     /// Detector for persistent RTT jump episodes.
     rtt_jump_detector: RttJumpDetector,
+
+    /// Source of randomness for the probe wait time.
+    rng: ConnectionRng,
 }
 
 impl BBRv2NetworkModel {
-    pub(super) fn new(params: &Params, initial_rtt: Duration) -> Self {
+    pub(super) fn new(
+        params: &Params, initial_rtt: Duration, now: Instant, rng: &ConnectionRng,
+    ) -> Self {
         BBRv2NetworkModel {
             min_bytes_in_flight_in_round: usize::MAX,
             inflight_hi_limited_in_round: false,
@@ -214,7 +220,7 @@ impl BBRv2NetworkModel {
             },
             min_rtt_filter: MinRttFilter {
                 min_rtt: initial_rtt,
-                min_rtt_timestamp: Instant::now(),
+                min_rtt_timestamp: now,
             },
             max_bandwidth_filter: MaxBandwidthFilter {
                 max_bandwidth: [Bandwidth::zero(), Bandwidth::zero()],
@@ -244,9 +250,15 @@ impl BBRv2NetworkModel {
             latest_ack_rate: None,
 
             rtt_jump_detector: RttJumpDetector::new(params.rtt_jump_detector),
+
+            rng: rng.clone(),
         }
     }
 
+    pub(super) fn rng(&self) -> &ConnectionRng {
+        &self.rng
+    }
+
     #[cfg(feature = "qlog")]
     pub(super) fn send_rate(&self) -> Option<Bandwidth> {
         self.latest_send_rate
@@ -718,7 +730,7 @@ impl BBRv2NetworkModel {
 
     /// The start time of the most recently confirmed persistent RTT jump
     /// episode, if any.
-    #[cfg(test)]
+    #[cfg(any(test, feature = "qlog"))]
     pub(super) fn last_persistent_jump_time(&self) -> Option<Instant> {
         self.rtt_jump_detector.last_persistent_jump_time()
     }
@@ -892,8 +904,9 @@ mod tests {
     #[test]
     fn rtt_jump_detector_is_disabled_by_default() {
         let params = &DEFAULT_PARAMS;
-        let mut model = BBRv2NetworkModel::new(params, RTT);
         let base = Instant::now();
+        let mut model =
+            BBRv2NetworkModel::new(params, RTT, base, &Default::default());
 
         for pkt in 1..5 {
             ack_with_rtt(&mut model, params, pkt, base, ms(pkt * 10), RTT);
@@ -913,8 +926,9 @@ mod tests {
     #[test]
     fn global_min_detector_can_be_enabled() {
         let params = &rtt_jump_params(BbrRttJumpDetector::GlobalMin);
-        let mut model = BBRv2NetworkModel::new(params, RTT);
         let base = Instant::now();
+        let mut model =
+            BBRv2NetworkModel::new(params, RTT, base, &Default::default());
 
         for pkt in 1..5 {
             ack_with_rtt(&mut model, params, pkt, base, ms(pkt * 10), RTT);
@@ -933,8 +947,9 @@ mod tests {
     #[test]
     fn hmm_detector_can_be_enabled() {
         let params = &rtt_jump_params(BbrRttJumpDetector::Hmm);
-        let mut model = BBRv2NetworkModel::new(params, RTT);
         let base = Instant::now();
+        let mut model =
+            BBRv2NetworkModel::new(params, RTT, base, &Default::default());
 
         for pkt in 1..9u64 {
             ack_with_rtt(&mut model, params, pkt, base, ms(pkt * 10), RTT);
@@ -955,8 +970,9 @@ mod tests {
     #[test]
     fn global_min_detector_sustained_step_becomes_persistent() {
         let params = &rtt_jump_params(BbrRttJumpDetector::GlobalMin);
-        let mut model = BBRv2NetworkModel::new(params, RTT);
         let base = Instant::now();
+        let mut model =
+            BBRv2NetworkModel::new(params, RTT, base, &Default::default());
 
         for pkt in 1..5 {
             ack_with_rtt(&mut model, params, pkt, base, ms(pkt * 10), RTT);
@@ -981,13 +997,15 @@ mod tests {
         let params = &rtt_jump_params(BbrRttJumpDetector::GlobalMin);
         let base = Instant::now();
 
-        let mut at_edge = BBRv2NetworkModel::new(params, RTT);
+        let mut at_edge =
+            BBRv2NetworkModel::new(params, RTT, base, &Default::default());
         ack_with_rtt(&mut at_edge, params, 1, base, ms(10), RTT);
         at_edge.set_full_bandwidth_reached();
         ack_with_rtt(&mut at_edge, params, 2, base, ms(20), RTT_3X);
         assert!(!at_edge.is_rtt_jump_active());
 
-        let mut just_above = BBRv2NetworkModel::new(params, RTT);
+        let mut just_above =
+            BBRv2NetworkModel::new(params, RTT, base, &Default::default());
         ack_with_rtt(&mut just_above, params, 1, base, ms(10), RTT);
         just_above.set_full_bandwidth_reached();
         ack_with_rtt(&mut just_above, params, 2, base, ms(20), RTT_JUMP);
@@ -997,8 +1015,9 @@ mod tests {
     #[test]
     fn global_min_detector_tracks_downward_baseline() {
         let params = &rtt_jump_params(BbrRttJumpDetector::GlobalMin);
-        let mut model = BBRv2NetworkModel::new(params, RTT);
         let base = Instant::now();
+        let mut model =
+            BBRv2NetworkModel::new(params, RTT, base, &Default::default());
 
         ack_with_rtt(&mut model, params, 1, base, ms(100), ms(100));
         ack_with_rtt(&mut model, params, 2, base, ms(200), ms(299));
@@ -1015,8 +1034,9 @@ mod tests {
     #[test]
     fn global_min_detector_ignores_startup_rtt_jump_until_full_bandwidth() {
         let params = &rtt_jump_params(BbrRttJumpDetector::GlobalMin);
-        let mut model = BBRv2NetworkModel::new(params, RTT);
         let base = Instant::now();
+        let mut model =
+            BBRv2NetworkModel::new(params, RTT, base, &Default::default());
 
         ack_with_rtt(&mut model, params, 1, base, ms(10), RTT);
         ack_with_rtt(&mut model, params, 2, base, ms(20), RTT_JUMP);
@@ -1038,8 +1058,9 @@ mod tests {
     #[test]
     fn hmm_detector_ignores_startup_rtt_jump_until_full_bandwidth() {
         let params = &rtt_jump_params(BbrRttJumpDetector::Hmm);
-        let mut model = BBRv2NetworkModel::new(params, RTT);
         let base = Instant::now();
+        let mut model =
+            BBRv2NetworkModel::new(params, RTT, base, &Default::default());
 
         for pkt in 1..9u64 {
             ack_with_rtt(&mut model, params, pkt, base, ms(pkt * 10), RTT);
@@ -220,11 +220,27 @@ impl ProbeBW {
         }
 
         // Pick probe wait time.
-        // TODO(vlad): actually pick time
-        cycle.rounds_since_probe = 0;
-        cycle.probe_wait_time = Some(
-            params.probe_bw_probe_base_duration + Duration::from_micros(500),
-        );
+        let max_rand_micros =
+            params.probe_bw_probe_max_rand_duration.as_micros() as u64;
+
+        if max_rand_micros > 0 {
+            // Randomize the probe wait time, and start the count of rounds
+            // towards the Reno-coexistence probe at 0 or 1 round.
+            let rng = self.model.rng();
+            cycle.rounds_since_probe = rng.rand_u64_uniform(2) as usize;
+            cycle.probe_wait_time = Some(
+                params.probe_bw_probe_base_duration +
+                    Duration::from_micros(
+                        rng.rand_u64_uniform(max_rand_micros),
+                    ),
+            );
+        } else {
+            // TODO(vlad): actually pick time
+            cycle.rounds_since_probe = 0;
+            cycle.probe_wait_time = Some(
+                params.probe_bw_probe_base_duration + Duration::from_micros(500),
+            );
+        }
 
         cycle.probe_up_bytes = None;
         cycle.probe_up_app_limited_since_inflight_hi_limited = false;
@@ -650,6 +666,7 @@ mod tests {
 
     use super::*;
     use crate::recovery::gcongestion::bbr2::SendTimeState;
+    use crate::recovery::gcongestion::bbr2::BBR3_PARAMS;
     use crate::recovery::gcongestion::bbr2::DEFAULT_PARAMS;
 
     #[rstest]
@@ -690,7 +707,12 @@ mod tests {
         };
 
         let params = &DEFAULT_PARAMS;
-        let model = BBRv2NetworkModel::new(params, Duration::from_millis(333));
+        let model = BBRv2NetworkModel::new(
+            params,
+            Duration::from_millis(333),
+            Instant::now(),
+            &Default::default(),
+        );
         let cycle = Cycle::default();
         let mut probe_bw = ProbeBW { model, cycle };
         probe_bw.model.set_inflight_hi(100_000);
@@ -726,4 +748,40 @@ mod tests {
         // End inflight_hi should be independent of step size.
         assert_eq!(probe_bw.model.inflight_hi(), 174_100);
     }
+
+    #[test]
+    fn probe_wait_time() {
+        let now = Instant::now();
+
+        let params = &DEFAULT_PARAMS;
+        let model = BBRv2NetworkModel::new(
+            params,
+            Duration::from_millis(100),
+            now,
+            &Default::default(),
+        );
+        let mut probe_bw = ProbeBW {
+            model,
+            cycle: Cycle::default(),
+        };
+
+        probe_bw.enter_probe_down(false, false, now, params);
+        assert_eq!(probe_bw.cycle.phase, CyclePhase::Down);
+        assert_eq!(probe_bw.cycle.rounds_since_probe, 0);
+        assert_eq!(
+            probe_bw.cycle.probe_wait_time,
+            Some(Duration::from_micros(2_000_500))
+        );
+
+        // BBRv3 waits between 2 and 3 seconds before probing.
+        let params = &BBR3_PARAMS;
+        for _ in 0..100 {
+            probe_bw.enter_probe_down(false, false, now, params);
+
+            let wait = probe_bw.cycle.probe_wait_time.unwrap();
+            assert!(wait >= Duration::from_secs(2));
+            assert!(wait < Duration::from_secs(3));
+            assert!(probe_bw.cycle.rounds_since_probe <= 1);
+        }
+    }
 }
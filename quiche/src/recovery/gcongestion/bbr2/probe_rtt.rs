@@ -166,9 +166,15 @@ mod tests {
         };
         let params = &DEFAULT_PARAMS.with_overrides(&custom_bbr_settings);
 
-        let model = BBRv2NetworkModel::new(params, Duration::from_millis(333));
+        let now = Instant::now();
+        let model = BBRv2NetworkModel::new(
+            params,
+            Duration::from_millis(333),
+            now,
+            &Default::default(),
+        );
         let mut probe_rtt = ProbeRTT::new(model, Cycle::default());
-        probe_rtt.enter(Instant::now(), None, params);
+        probe_rtt.enter(now, None, params);
         assert_eq!(probe_rtt.model.pacing_gain(), 0.8);
         assert_eq!(probe_rtt.model.cwnd_gain(), 0.5);
     }
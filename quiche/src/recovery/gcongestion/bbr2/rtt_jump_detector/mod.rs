@@ -115,7 +115,7 @@ impl RttJumpDetector {
 
     /// The start time of the most recently confirmed persistent RTT jump
     /// episode, if any.
-    #[cfg(test)]
+    #[cfg(any(test, feature = "qlog"))]
     pub(super) fn last_persistent_jump_time(&self) -> Option<Instant> {
         self.last_persistent_jump_time
     }
@@ -37,6 +37,8 @@ pub use self::recovery::GRecovery;
 use crate::recovery::bandwidth::Bandwidth;
 
 use crate::recovery::rtt::RttStats;
+#[cfg(feature = "qlog")]
+use crate::recovery::CfExData;
 use crate::recovery::RecoveryStats;
 
 #[derive(Debug)]
@@ -129,6 +131,11 @@ pub(super) trait CongestionControl: Debug {
     fn ssthresh(&self) -> Option<u64> {
         None
     }
+
+    /// Adds details of the current state of the congestion control state
+    /// machine to the qlog event logged after state transitions.
+    #[cfg(feature = "qlog")]
+    fn add_qlog_state_data(&self, _ex_data: &mut CfExData) {}
 }
 
 /// BBR settings used to customize the algorithm's behavior.
@@ -235,7 +242,7 @@ pub struct BbrParams {
     pub time_sent_set_to_now: Option<bool>,
 
     /// Selects the RTT jump detector implementation.
-    #[cfg(feature = "internal")]
+    #[cfg(any(test, feature = "internal"))]
     pub rtt_jump_detector: Option<BbrRttJumpDetector>,
 }
 
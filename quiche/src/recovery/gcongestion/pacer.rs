@@ -34,6 +34,8 @@ use crate::recovery::gcongestion::bbr2::BBRv2;
 use crate::recovery::gcongestion::Bandwidth;
 use crate::recovery::gcongestion::CongestionControl;
 use crate::recovery::rtt::RttStats;
+#[cfg(feature = "qlog")]
+use crate::recovery::CfExData;
 use crate::recovery::RecoveryStats;
 use crate::recovery::ReleaseDecision;
 use crate::recovery::ReleaseTime;
@@ -118,6 +120,11 @@ impl Pacer {
         self.sender.state_str()
     }
 
+    #[cfg(feature = "qlog")]
+    pub fn add_qlog_state_data(&self, ex_data: &mut CfExData) {
+        self.sender.add_qlog_state_data(ex_data)
+    }
+
     pub fn get_congestion_window(&self) -> usize {
         self.sender.get_congestion_window()
     }
@@ -262,6 +269,11 @@ impl Pacer {
         self.sender.rtt_persistent_jump_count()
     }
 
+    #[cfg(feature = "qlog")]
+    pub fn add_qlog_rtt_jump_data(&self, now: Instant, ex_data: &mut CfExData) {
+        self.sender.add_qlog_rtt_jump_data(now, ex_data)
+    }
+
     #[cfg(feature = "qlog")]
     pub fn send_rate(&self) -> Option<Bandwidth> {
         self.sender.send_rate()
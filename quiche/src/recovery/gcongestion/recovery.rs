@@ -14,6 +14,10 @@ use smallvec::SmallVec;
 #[cfg(feature = "qlog")]
 use qlog::events::EventData;
 
+#[cfg(feature = "qlog")]
+use crate::recovery::qlog_cc_state_updated;
+#[cfg(feature = "qlog")]
+use crate::recovery::CfExData;
 #[cfg(feature = "qlog")]
 use crate::recovery::QlogMetrics;
 
@@ -42,6 +46,7 @@ use crate::recovery::MAX_PTO_PROBES_COUNT;
 use crate::recovery::PACKET_REORDER_TIME_THRESHOLD;
 
 use super::bbr2::BBRv2;
+use super::bbr2::BbrVersion;
 use super::pacer::Pacer;
 use super::Acked;
 use super::Lost;
@@ -487,6 +492,9 @@ pub struct GRecovery {
     #[cfg(feature = "qlog")]
     qlog_prev_cc_state: &'static str,
 
+    #[cfg(feature = "qlog")]
+    qlog_prev_rtt_jump_count: u64,
+
     /// How many non-ack-eliciting packets have been sent.
     outstanding_non_ack_eliciting: usize,
 
@@ -511,18 +519,24 @@ impl GRecovery {
         self.pacer.ack_rate().unwrap_or(Bandwidth::zero())
     }
 
-    pub fn new(recovery_config: &RecoveryConfig) -> Option<Self> {
-        let cc = match recovery_config.cc_algorithm {
-            CongestionControlAlgorithm::Bbr2Gcongestion => BBRv2::new(
-                recovery_config.initial_congestion_window_packets,
-                MAX_WINDOW_PACKETS,
-                recovery_config.max_send_udp_payload_size,
-                recovery_config.initial_rtt,
-                recovery_config.custom_bbr_params.as_ref(),
-            ),
+    pub fn new(recovery_config: &RecoveryConfig, now: Instant) -> Option<Self> {
+        let version = match recovery_config.cc_algorithm {
+            CongestionControlAlgorithm::Bbr2Gcongestion => BbrVersion::V2,
+            CongestionControlAlgorithm::Bbr3Gcongestion => BbrVersion::V3,
             _ => return None,
         };
 
+        let cc = BBRv2::new(
+            version,
+            recovery_config.initial_congestion_window_packets,
+            MAX_WINDOW_PACKETS,
+            recovery_config.max_send_udp_payload_size,
+            recovery_config.initial_rtt,
+            recovery_config.custom_bbr_params.as_ref(),
+            now,
+            &recovery_config.rng,
+        );
+
         Some(Self {
             epochs: Default::default(),
             rtt_stats: RttStats::new(
@@ -550,6 +564,9 @@ impl GRecovery {
             #[cfg(feature = "qlog")]
             qlog_prev_cc_state: "",
 
+            #[cfg(feature = "qlog")]
+            qlog_prev_rtt_jump_count: 0,
+
             outstanding_non_ack_eliciting: 0,
 
             pacer: Pacer::new(
@@ -1213,22 +1230,40 @@ impl RecoveryOps for GRecovery {
             lost_packets: Some(self.lost_count as u64),
             lost_bytes: Some(self.bytes_lost),
             pto_count: Some(self.pto_count),
+            rtt_persistent_jumps: Some(self.pacer.rtt_persistent_jump_count())
+                .filter(|&count| count > 0),
         };
 
         self.qlog_metrics.maybe_update(qlog_metrics)
     }
 
     #[cfg(feature = "qlog")]
-    fn get_updated_qlog_cc_state(
-        &mut self, now: Instant,
-    ) -> Option<&'static str> {
+    fn get_updated_qlog_cc_state(&mut self, now: Instant) -> Option<EventData> {
         let cc_state = self.state_str(now);
-        if cc_state != self.qlog_prev_cc_state {
-            self.qlog_prev_cc_state = cc_state;
-            Some(cc_state)
-        } else {
-            None
+        let rtt_jump_count = self.pacer.rtt_persistent_jump_count();
+        let rtt_jumped = rtt_jump_count != self.qlog_prev_rtt_jump_count;
+        if cc_state == self.qlog_prev_cc_state && !rtt_jumped {
+            return None;
+        }
+
+        let mut ex_data = CfExData::new();
+        self.pacer.add_qlog_state_data(&mut ex_data);
+
+        // Each newly confirmed RTT jump gets its own event, even when the
+        // congestion control state is unchanged.
+        if rtt_jumped {
+            self.qlog_prev_rtt_jump_count = rtt_jump_count;
+
+            ex_data.insert(
+                "cf_latest_rtt",
+                self.rtt_stats.latest_rtt().as_secs_f32() * 1000.0,
+            );
+            self.pacer.add_qlog_rtt_jump_data(now, &mut ex_data);
         }
+
+        let old = std::mem::replace(&mut self.qlog_prev_cc_state, cc_state);
+
+        Some(qlog_cc_state_updated(old, cc_state, ex_data))
     }
 
     fn send_quantum(&self) -> usize {
@@ -1370,7 +1405,8 @@ mod tests {
         let mut config = Config::new(crate::PROTOCOL_VERSION).unwrap();
         config.set_cc_algorithm(CongestionControlAlgorithm::Bbr2Gcongestion);
         let recovery_config = RecoveryConfig::from_config(&config);
-        let mut r = GRecovery::new(&recovery_config).unwrap();
+        let now = Instant::now();
+        let mut r = GRecovery::new(&recovery_config, now).unwrap();
 
         r.pto_count = 99999;
 
@@ -1379,7 +1415,6 @@ mod tests {
             has_handshake_keys: true,
             peer_verified_address: true,
         };
-        let now = Instant::now();
 
         let _ = r.pto_time_and_space(handshake_status, now);
     }
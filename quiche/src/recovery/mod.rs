@@ -33,6 +33,7 @@ use crate::packet;
 use crate::ranges::RangeSet;
 pub(crate) use crate::recovery::bandwidth::Bandwidth;
 use crate::Config;
+use crate::ConnectionRng;
 use crate::Result;
 
 #[cfg(feature = "qlog")]
@@ -128,7 +129,7 @@ impl std::fmt::Debug for LossDetectionTimer {
     }
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, PartialEq)]
 pub struct RecoveryConfig {
     pub initial_rtt: Duration,
     pub max_send_udp_payload_size: usize,
@@ -141,6 +142,7 @@ pub struct RecoveryConfig {
     pub initial_congestion_window_packets: usize,
     pub enable_relaxed_loss_threshold: bool,
     pub enable_cubic_idle_restart_fix: bool,
+    pub rng: ConnectionRng,
 }
 
 impl RecoveryConfig {
@@ -158,6 +160,7 @@ impl RecoveryConfig {
                 .initial_congestion_window_packets,
             enable_relaxed_loss_threshold: config.enable_relaxed_loss_threshold,
             enable_cubic_idle_restart_fix: config.enable_cubic_idle_restart_fix,
+            rng: config.rng.clone(),
         }
     }
 }
@@ -318,8 +321,7 @@ pub trait RecoveryOps {
     fn get_updated_qlog_event_data(&mut self) -> Option<EventData>;
 
     #[cfg(feature = "qlog")]
-    fn get_updated_qlog_cc_state(&mut self, now: Instant)
-        -> Option<&'static str>;
+    fn get_updated_qlog_cc_state(&mut self, now: Instant) -> Option<EventData>;
 
     fn send_quantum(&self) -> usize;
 
@@ -329,8 +331,10 @@ pub trait RecoveryOps {
 }
 
 impl Recovery {
-    pub fn new_with_config(recovery_config: &RecoveryConfig) -> Self {
-        let grecovery = GRecovery::new(recovery_config);
+    pub fn new_with_config(
+        recovery_config: &RecoveryConfig, now: Instant,
+    ) -> Self {
+        let grecovery = GRecovery::new(recovery_config, now);
         if let Some(grecovery) = grecovery {
             Recovery::from(grecovery)
         } else {
@@ -346,22 +350,17 @@ impl Recovery {
             qlog.add_event_data_with_instant(ev_data, now).ok();
         }
 
-        if let Some(cc_state) = self.get_updated_qlog_cc_state(now) {
-            let ev_data = EventData::QuicCongestionStateUpdated(
-                qlog::events::quic::CongestionStateUpdated {
-                    old: None,
-                    new: cc_state.to_string(),
-                    trigger: None,
-                },
-            );
-
+        if let Some(ev_data) = self.get_updated_qlog_cc_state(now) {
             qlog.add_event_data_with_instant(ev_data, now).ok();
         }
     }
 
     #[cfg(test)]
     pub fn new(config: &Config) -> Self {
-        Self::new_with_config(&RecoveryConfig::from_config(config))
+        Self::new_with_config(
+            &RecoveryConfig::from_config(config),
+            Instant::now(),
+        )
     }
 }
 
@@ -379,6 +378,12 @@ pub enum CongestionControlAlgorithm {
     /// BBRv2 congestion control algorithm implementation from gcongestion
     /// branch. `bbr2_gcongestion` in a string form.
     Bbr2Gcongestion = 4,
+    /// BBRv3 congestion control algorithm, a variant of the gcongestion BBRv2
+    /// implementation. `bbr3_gcongestion` in a string form.
+    Bbr3Gcongestion = 5,
+    /// LEDBAT++ delay-based congestion control algorithm, for background
+    /// transfers that yield to competing traffic. `ledbat` in a string form.
+    Ledbat          = 6,
 }
 
 impl FromStr for CongestionControlAlgorithm {
@@ -394,6 +399,9 @@ impl FromStr for CongestionControlAlgorithm {
             "bbr" => Ok(CongestionControlAlgorithm::Bbr2Gcongestion),
             "bbr2" => Ok(CongestionControlAlgorithm::Bbr2Gcongestion),
             "bbr2_gcongestion" => Ok(CongestionControlAlgorithm::Bbr2Gcongestion),
+            "bbr3" => Ok(CongestionControlAlgorithm::Bbr3Gcongestion),
+            "bbr3_gcongestion" => Ok(CongestionControlAlgorithm::Bbr3Gcongestion),
+            "ledbat" => Ok(CongestionControlAlgorithm::Ledbat),
             _ => Err(crate::Error::CongestionControl),
         }
     }
@@ -495,6 +503,7 @@ struct QlogMetrics {
     lost_packets: Option<u64>,
     lost_bytes: Option<u64>,
     pto_count: Option<u32>,
+    rtt_persistent_jumps: Option<u64>,
 }
 
 #[cfg(feature = "qlog")]
@@ -555,6 +564,23 @@ impl CfExData {
     }
 }
 
+// Makes a qlog CongestionStateUpdated event for a transition from the `old`
+// to the `new` congestion control state. An empty `old` state means there was
+// no previous state.
+#[cfg(feature = "qlog")]
+fn qlog_cc_state_updated(
+    old: &'static str, new: &'static str, ex_data: CfExData,
+) -> EventData {
+    EventData::QuicCongestionStateUpdated(
+        qlog::events::quic::CongestionStateUpdated {
+            old: (!old.is_empty()).then(|| old.to_string()),
+            new: new.to_string(),
+            trigger: None,
+            ex_data: ex_data.into_inner(),
+        },
+    )
+}
+
 #[cfg(feature = "qlog")]
 impl QlogMetrics {
     // Make a qlog event if the latest instance of QlogMetrics is different.
@@ -683,6 +709,16 @@ impl QlogMetrics {
                 self.lost_bytes = latest.lost_bytes;
             }
         }
+        if self.rtt_persistent_jumps != latest.rtt_persistent_jumps {
+            if let Some(val) = latest.rtt_persistent_jumps {
+                emit_event = true;
+                ex_data.insert("cf_rtt_persistent_jumps", TotalAndDelta {
+                    total: latest.rtt_persistent_jumps,
+                    delta: Some(val - self.rtt_persistent_jumps.unwrap_or(0)),
+                });
+                self.rtt_persistent_jumps = latest.rtt_persistent_jumps;
+            }
+        }
 
         if emit_event {
             return Some(EventData::QuicMetricsUpdated(
@@ -821,7 +857,7 @@ pub enum StartupExitReason {
     /// Exit BBR startup due to bandwidth plateau.
     BandwidthPlateau,
 
-    /// Exit BBR startup due to persistent queue.
+    /// Exit BBR startup or LEDBAT++ slow start due to persistent queue.
     PersistentQueue,
 
     /// Exit HyStart++ conservative slow start after the max rounds allowed.
@@ -868,6 +904,19 @@ mod tests {
             CongestionControlAlgorithm::from_str("bbr2_gcongestion").unwrap();
         assert_eq!(algo, CongestionControlAlgorithm::Bbr2Gcongestion);
         assert!(recovery_for_alg(algo).gcongestion_enabled());
+
+        let algo = CongestionControlAlgorithm::from_str("bbr3").unwrap();
+        assert_eq!(algo, CongestionControlAlgorithm::Bbr3Gcongestion);
+        assert!(recovery_for_alg(algo).gcongestion_enabled());
+
+        let algo =
+            CongestionControlAlgorithm::from_str("bbr3_gcongestion").unwrap();
+        assert_eq!(algo, CongestionControlAlgorithm::Bbr3Gcongestion);
+        assert!(recovery_for_alg(algo).gcongestion_enabled());
+
+        let algo = CongestionControlAlgorithm::from_str("ledbat").unwrap();
+        assert_eq!(algo, CongestionControlAlgorithm::Ledbat);
+        assert!(!recovery_for_alg(algo).gcongestion_enabled());
     }
 
     #[test]
@@ -3077,6 +3126,185 @@ mod tests {
             "No packets should be marked as lost - PTO only retransmits"
         );
     }
+
+    // Sends `pkt_nums` at `now` and acks them all after `rtt`, returning the
+    // time of the ack.
+    #[cfg(feature = "qlog")]
+    fn send_and_ack(
+        r: &mut Recovery, pkt_nums: std::ops::Range<u64>, now: Instant,
+        rtt: Duration,
+    ) -> Instant {
+        for pkt_num in pkt_nums.clone() {
+            let p = Sent {
+                pkt_num,
+                frames: smallvec![],
+                time_sent: now,
+                time_acked: None,
+                time_lost: None,
+                size: 1200,
+                ack_eliciting: true,
+                in_flight: true,
+                delivered: 0,
+                delivered_time: now,
+                first_sent_time: now,
+                is_app_limited: false,
+                tx_in_flight: 0,
+                lost: 0,
+                has_data: true,
+                is_pmtud_probe: false,
+            };
+
+            r.on_packet_sent(
+                p,
+                packet::Epoch::Application,
+                HandshakeStatus::default(),
+                now,
+                "",
+            );
+        }
+        r.update_app_limited(false);
+
+        let now = now + rtt;
+
+        let mut acked = RangeSet::default();
+        acked.insert(pkt_nums);
+
+        r.on_ack_received(
+            &acked,
+            0,
+            packet::Epoch::Application,
+            HandshakeStatus::default(),
+            now,
+            None,
+            "",
+        )
+        .unwrap();
+
+        now
+    }
+
+    #[cfg(feature = "qlog")]
+    fn cc_state_updated(
+        ev_data: Option<EventData>,
+    ) -> qlog::events::quic::CongestionStateUpdated {
+        match ev_data {
+            Some(EventData::QuicCongestionStateUpdated(ev)) => ev,
+
+            ev_data => panic!("unexpected event data {ev_data:?}"),
+        }
+    }
+
+    #[cfg(feature = "qlog")]
+    fn ms_value(d: Duration) -> serde_json::Value {
+        serde_json::json!(d.as_secs_f32() * 1000.0)
+    }
+
+    #[cfg(feature = "qlog")]
+    #[test]
+    fn qlog_cc_state_hystart() {
+        let mut cfg = Config::new(crate::PROTOCOL_VERSION).unwrap();
+        cfg.set_cc_algorithm(CongestionControlAlgorithm::CUBIC);
+        cfg.enable_hystart(true);
+
+        let mut r = Recovery::new(&cfg);
+        let mut now = Instant::now();
+
+        // No HyStart++ round has completed yet.
+        let ev = cc_state_updated(r.get_updated_qlog_cc_state(now));
+        assert_eq!(ev.old, None);
+        assert_eq!(ev.new, "slow_start");
+        assert!(ev.ex_data.is_empty());
+
+        let rtt_1st = Duration::from_millis(50);
+        now = send_and_ack(&mut r, 0..10, now, rtt_1st);
+        assert!(r.get_updated_qlog_cc_state(now).is_none());
+
+        // An RTT increase over the 1st round exits to CSS.
+        let rtt_2nd = Duration::from_millis(100);
+        now = send_and_ack(&mut r, 10..20, now, rtt_2nd);
+
+        let ev = cc_state_updated(r.get_updated_qlog_cc_state(now));
+        assert_eq!(ev.old.as_deref(), Some("slow_start"));
+        assert_eq!(ev.new, "conservative_slow_start");
+        assert_eq!(
+            ev.ex_data.get("cf_hystart_last_round_min_rtt"),
+            Some(&ms_value(rtt_1st))
+        );
+        assert_eq!(
+            ev.ex_data.get("cf_hystart_current_round_min_rtt"),
+            Some(&ms_value(rtt_2nd))
+        );
+        assert_eq!(
+            ev.ex_data.get("cf_hystart_css_baseline_min_rtt"),
+            Some(&ms_value(rtt_2nd))
+        );
+    }
+
+    #[cfg(feature = "qlog")]
+    #[test]
+    fn qlog_cc_state_bbr_rtt_jump() {
+        use gcongestion::BbrRttJumpDetector;
+
+        let mut cfg = Config::new(crate::PROTOCOL_VERSION).unwrap();
+        cfg.set_cc_algorithm(CongestionControlAlgorithm::Bbr2Gcongestion);
+
+        let mut recovery_config = RecoveryConfig::from_config(&cfg);
+        recovery_config.custom_bbr_params = Some(BbrParams {
+            rtt_jump_detector: Some(BbrRttJumpDetector::GlobalMin),
+            ..Default::default()
+        });
+
+        let mut now = Instant::now();
+        let mut r = Recovery::new_with_config(&recovery_config, now);
+
+        let ev = cc_state_updated(r.get_updated_qlog_cc_state(now));
+        assert_eq!(ev.old, None);
+        assert_eq!(ev.new, "bbr_startup");
+        for key in [
+            "cf_pacing_gain",
+            "cf_cwnd_gain",
+            "cf_bandwidth_estimate",
+            "cf_max_bandwidth",
+            "cf_min_rtt",
+        ] {
+            assert!(ev.ex_data.contains_key(key), "missing {key}");
+        }
+        assert!(!ev.ex_data.contains_key("cf_rtt_jump_count"));
+
+        // A steady RTT lets startup reach full bandwidth.
+        let rtt = Duration::from_millis(50);
+        for pkt_num in 0..20 {
+            now = send_and_ack(&mut r, pkt_num..pkt_num + 1, now, rtt);
+            if let Some(ev_data) = r.get_updated_qlog_cc_state(now) {
+                let ev = cc_state_updated(Some(ev_data));
+                assert!(!ev.ex_data.contains_key("cf_rtt_jump_count"));
+            }
+        }
+        assert_eq!(r.rtt_persistent_jump_count(), 0);
+
+        // A sustained RTT of more than 3x the baseline is a persistent jump,
+        // confirmed on the 3rd elevated sample.
+        let rtt_jump = Duration::from_millis(200);
+        let mut jump_events = Vec::new();
+        for pkt_num in 20..30 {
+            now = send_and_ack(&mut r, pkt_num..pkt_num + 1, now, rtt_jump);
+            if let Some(ev_data) = r.get_updated_qlog_cc_state(now) {
+                let ev = cc_state_updated(Some(ev_data));
+                if ev.ex_data.contains_key("cf_rtt_jump_count") {
+                    jump_events.push(ev);
+                }
+            }
+        }
+        assert_eq!(r.rtt_persistent_jump_count(), 1);
+        assert_eq!(jump_events.len(), 1);
+
+        let ev = &jump_events[0];
+        assert_eq!(ev.ex_data["cf_rtt_jump_count"], serde_json::json!(1));
+        assert_eq!(ev.ex_data["cf_rtt_jump_duration"], ms_value(rtt_jump * 2));
+        assert_eq!(ev.ex_data["cf_latest_rtt"], ms_value(rtt_jump));
+        assert!(ev.ex_data.contains_key("cf_pacing_gain"));
+        assert!(ev.ex_data.contains_key("cf_min_rtt"));
+    }
 }
 
 mod bandwidth;
@@ -12931,3 +12931,268 @@ fn server_qlog() {
         panic!("expected Qlog event");
     }
 }
+
+#[cfg(feature = "internal")]
+#[test]
+fn injected_clock() {
+    use std::sync::atomic::AtomicU64;
+    use std::sync::atomic::Ordering;
+
+    struct TestClock {
+        base: Instant,
+        elapsed_ms: AtomicU64,
+    }
+
+    impl Clock for TestClock {
+        fn now(&self) -> Instant {
+            self.base +
+                Duration::from_millis(self.elapsed_ms.load(Ordering::Relaxed))
+        }
+    }
+
+    let clock = std::sync::Arc::new(TestClock {
+        base: Instant::now(),
+        elapsed_ms: AtomicU64::new(0),
+    });
+
+    let mut config = test_utils::Pipe::default_config("cubic").unwrap();
+    config.set_max_idle_timeout(1_000);
+    config.set_clock(clock.clone());
+
+    let mut pipe = test_utils::Pipe::with_config(&mut config).unwrap();
+    assert_eq!(pipe.handshake(), Ok(()));
+
+    // The injected clock has not moved, so no timer has expired yet.
+    pipe.client.on_timeout();
+    assert!(!pipe.client.is_closed());
+
+    // Move the injected clock past the idle timeout.
+    clock.elapsed_ms.store(60_000, Ordering::Relaxed);
+    assert_eq!(pipe.client.timeout(), Some(Duration::ZERO));
+
+    pipe.client.on_timeout();
+    assert!(pipe.client.is_closed());
+    assert!(pipe.client.is_timed_out());
+}
+
+#[cfg(feature = "internal")]
+#[test]
+fn injected_rng() {
+    struct TestRng;
+
+    impl Rng for TestRng {
+        fn fill_bytes(&self, buf: &mut [u8]) {
+            buf.fill(0xab);
+        }
+    }
+
+    let mut buf = [0; 65535];
+
+    let mut config = test_utils::Pipe::default_config("cubic").unwrap();
+    config.set_rng(std::sync::Arc::new(TestRng));
+
+    let mut pipe = test_utils::Pipe::with_client_config(&mut config).unwrap();
+
+    // The client picks the destination connection ID of its first Initial
+    // from the injected random number source.
+    let (len, _) = pipe.client.send(&mut buf).unwrap();
+
+    let hdr = Header::from_slice(&mut buf[..len], MAX_CONN_ID_LEN).unwrap();
+    assert_eq!(hdr.dcid.as_ref(), &[0xab; 16]);
+}
+
+#[cfg(feature = "qlog")]
+#[test]
+fn server_qlog_sampled() {
+    use qlog::reader::QlogSeqReader;
+
+    let mut pipe = test_utils::Pipe::new("cubic").unwrap();
+
+    pipe.server.set_qlog(
+        Box::new(std::io::Cursor::new(Vec::<u8>::new())),
+        "test qlog".to_string(),
+        "test qlog description".to_string(),
+    );
+
+    let filter = qlog::filter::EventFilter {
+        sample: [("quic:packet_sent".to_string(), 2)].into(),
+        ..Default::default()
+    };
+    pipe.server
+        .qlog_streamer()
+        .unwrap()
+        .set_filter(&filter)
+        .unwrap();
+
+    assert_eq!(pipe.handshake(), Ok(()));
+
+    pipe.server.qlog_streamer().unwrap().finish_log().unwrap();
+
+    let raw = pipe.server.qlog_streamer().unwrap().writer();
+    #[allow(clippy::borrowed_box)]
+    let w: &Box<std::io::Cursor<Vec<u8>>> = unsafe { std::mem::transmute(raw) };
+    let bytes = w.get_ref().clone();
+
+    let reader = QlogSeqReader::new(Box::new(std::io::BufReader::new(
+        std::io::Cursor::new(bytes),
+    )))
+    .unwrap();
+
+    let packets_sent = reader
+        .filter(|ev| {
+            matches!(
+                ev,
+                qlog::reader::Event::Qlog(Event {
+                    data: EventData::QuicPacketSent(_),
+                    ..
+                })
+            )
+        })
+        .count();
+
+    // One in two sent packets is logged, starting with the first.
+    assert_eq!(packets_sent, pipe.server.stats().sent.div_ceil(2));
+}
+
+#[cfg(feature = "qlog")]
+#[test]
+fn server_qlog_sampled_send_until_done() {
+    use qlog::reader::QlogSeqReader;
+
+    let mut buf = [0; 65535];
+
+    let mut pipe = test_utils::Pipe::new("cubic").unwrap();
+
+    pipe.server.set_qlog(
+        Box::new(std::io::Cursor::new(Vec::<u8>::new())),
+        "test qlog".to_string(),
+        "test qlog description".to_string(),
+    );
+
+    let filter = qlog::filter::EventFilter {
+        sample: [("quic:packet_sent".to_string(), 3)].into(),
+        ..Default::default()
+    };
+    pipe.server
+        .qlog_streamer()
+        .unwrap()
+        .set_filter(&filter)
+        .unwrap();
+
+    assert_eq!(pipe.handshake(), Ok(()));
+
+    // Every call to `send()` ends with an attempt that returns `Done`, which
+    // must not count towards sampling.
+    for _ in 0..7 {
+        assert_eq!(pipe.server.stream_send(1, b"a", false), Ok(1));
+
+        loop {
+            match pipe.server.send(&mut buf) {
+                Ok(_) => (),
+
+                Err(Error::Done) => break,
+
+                Err(e) => panic!("unexpected error {e:?}"),
+            }
+        }
+    }
+
+    pipe.server.qlog_streamer().unwrap().finish_log().unwrap();
+
+    let raw = pipe.server.qlog_streamer().unwrap().writer();
+    #[allow(clippy::borrowed_box)]
+    let w: &Box<std::io::Cursor<Vec<u8>>> = unsafe { std::mem::transmute(raw) };
+    let bytes = w.get_ref().clone();
+
+    let reader = QlogSeqReader::new(Box::new(std::io::BufReader::new(
+        std::io::Cursor::new(bytes),
+    )))
+    .unwrap();
+
+    let packets_sent = reader
+        .filter(|ev| {
+            matches!(
+                ev,
+                qlog::reader::Event::Qlog(Event {
+                    data: EventData::QuicPacketSent(_),
+                    ..
+                })
+            )
+        })
+        .count();
+
+    // One in three sent packets is logged, starting with the first.
+    assert_eq!(packets_sent, pipe.server.stats().sent.div_ceil(3));
+}
+
+#[cfg(feature = "qlog")]
+#[test]
+fn server_qlog_sampled_with_dropped_packets() {
+    use qlog::reader::QlogSeqReader;
+
+    let mut buf = [0; 65535];
+
+    let mut pipe = test_utils::Pipe::new("cubic").unwrap();
+
+    pipe.server.set_qlog(
+        Box::new(std::io::Cursor::new(Vec::<u8>::new())),
+        "test qlog".to_string(),
+        "test qlog description".to_string(),
+    );
+
+    let filter = qlog::filter::EventFilter {
+        sample: [("quic:packet_received".to_string(), 2)].into(),
+        ..Default::default()
+    };
+    pipe.server
+        .qlog_streamer()
+        .unwrap()
+        .set_filter(&filter)
+        .unwrap();
+
+    assert_eq!(pipe.handshake(), Ok(()));
+
+    // Deliver each packet twice, so that every processed packet is followed
+    // by a duplicate that is dropped.
+    for _ in 0..5 {
+        assert_eq!(pipe.client.stream_send(0, b"a", false), Ok(1));
+
+        let (len, _) = pipe.client.send(&mut buf).unwrap();
+        let mut dup = buf[..len].to_vec();
+
+        let recv_count = pipe.server.stats().recv;
+
+        assert_eq!(pipe.server_recv(&mut buf[..len]), Ok(len));
+        assert_eq!(pipe.server_recv(&mut dup), Ok(len));
+
+        assert_eq!(pipe.server.stats().recv, recv_count + 1);
+    }
+
+    pipe.server.qlog_streamer().unwrap().finish_log().unwrap();
+
+    let raw = pipe.server.qlog_streamer().unwrap().writer();
+    #[allow(clippy::borrowed_box)]
+    let w: &Box<std::io::Cursor<Vec<u8>>> = unsafe { std::mem::transmute(raw) };
+    let bytes = w.get_ref().clone();
+
+    let reader = QlogSeqReader::new(Box::new(std::io::BufReader::new(
+        std::io::Cursor::new(bytes),
+    )))
+    .unwrap();
+
+    let packets_received = reader
+        .filter(|ev| {
+            matches!(
+                ev,
+                qlog::reader::Event::Qlog(Event {
+                    data: EventData::QuicPacketReceived(_),
+                    ..
+                })
+            )
+        })
+        .count();
+
+    // Dropped packets don't count towards sampling, so one in two processed
+    // packets is logged, starting with the first.
+    assert_eq!(packets_received, pipe.server.stats().recv.div_ceil(2));
+}
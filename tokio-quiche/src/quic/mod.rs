@@ -88,6 +88,8 @@ use datagram_socket::DatagramSocketSend;
 use foundations::telemetry::log;
 use qlog::writer::make_qlog_writer_from_path;
 use qlog::writer::qlog_file_name;
+use qlog::writer::QlogCompression;
+use qlog::writer::QlogFileWriter;
 
 use crate::http3::settings::Http3Settings;
 use crate::metrics::DefaultMetrics;
@@ -230,19 +232,27 @@ where
 
     // Set the qlog writer here instead of in the `ClientConnector` to avoid
     // missing logs from early in the connection
-    if let Some(qlog_dir) = &client_config.qlog_dir {
-        log::info!("setting up qlogs"; "qlog_dir"=>qlog_dir);
-        let id = format!("{:?}", scid);
-        let path = std::path::Path::new(qlog_dir)
-            .join(qlog_file_name(&id, client_config.qlog_compression));
-        if let Ok(writer) =
-            make_qlog_writer_from_path(&path, client_config.qlog_compression)
-        {
-            quiche_conn.set_qlog(
-                writer,
-                "tokio-quiche qlog".to_string(),
-                format!("tokio-quiche qlog id={id}"),
-            );
+    let id = format!("{:?}", scid);
+    if let Some(writer) = make_conn_qlog_writer(
+        client_config.qlog_dir.as_deref(),
+        client_config.qlog_socket.as_deref(),
+        client_config.qlog_compression,
+        &id,
+    ) {
+        log::info!("setting up qlogs");
+        quiche_conn.set_qlog(
+            writer,
+            "tokio-quiche qlog".to_string(),
+            format!("tokio-quiche qlog id={id}"),
+        );
+
+        if let Some(streamer) = quiche_conn.qlog_streamer() {
+            // The filter was validated when loading the settings.
+            let _ = streamer.set_filter(&client_config.qlog_filter);
+
+            if let Some((pool, config)) = &client_config.qlog_async_writer {
+                let _ = streamer.set_async_writer(pool, config.clone());
+            }
         }
     }
 
@@ -307,7 +317,10 @@ where
         ConnectionAcceptorConfig {
             disable_client_ip_validation: config.disable_client_ip_validation,
             qlog_dir: config.qlog_dir.clone(),
+            qlog_socket: config.qlog_socket.clone(),
             qlog_compression: config.qlog_compression,
+            qlog_async_writer: config.qlog_async_writer.clone(),
+            qlog_filter: config.qlog_filter.clone(),
             keylog_file: config
                 .keylog_file
                 .as_ref()
@@ -346,3 +359,131 @@ where
     });
     Ok(QuicConnectionStream::new(accept_stream))
 }
+
+/// Creates the qlog writer of the connection identified by `id`.
+///
+/// Output is streamed to `qlog_socket` if set, or saved to a file in
+/// `qlog_dir` otherwise, or if the socket can't be connected to. Returns `None`
+/// if neither is set or the writer could not be created.
+pub(crate) fn make_conn_qlog_writer(
+    qlog_dir: Option<&str>, qlog_socket: Option<&str>,
+    compression: QlogCompression, id: &str,
+) -> Option<QlogFileWriter> {
+    #[cfg(unix)]
+    if let Some(qlog_socket) = qlog_socket {
+        match std::os::unix::net::UnixStream::connect(qlog_socket) {
+            Ok(stream) => return Some(Box::new(stream)),
+
+            Err(e) => {
+                log::warn!("failed to connect to qlog socket"; "error" => e);
+            },
+        }
+    }
+
+    #[cfg(not(unix))]
+    if qlog_socket.is_some() {
+        log::warn!("qlog_socket is only supported on UNIX platforms");
+    }
+
+    let path =
+        std::path::Path::new(qlog_dir?).join(qlog_file_name(id, compression));
+
+    make_qlog_writer_from_path(&path, compression).ok()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::io::Write;
+    use std::os::unix::net::UnixListener;
+
+    use crate::settings::Hooks;
+    use crate::settings::QuicSettings;
+    use crate::socket::SocketCapabilities;
+
+    #[test]
+    fn qlog_socket_writer() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket_path = dir.path().join("qlog.sock");
+        let listener = UnixListener::bind(&socket_path).unwrap();
+
+        let mut writer = make_conn_qlog_writer(
+            dir.path().to_str(),
+            socket_path.to_str(),
+            QlogCompression::None,
+            "abc",
+        )
+        .unwrap();
+
+        writer.write_all(b"qlog").unwrap();
+        drop(writer);
+
+        let mut output = String::new();
+        let (mut stream, _) = listener.accept().unwrap();
+        stream.read_to_string(&mut output).unwrap();
+
+        assert_eq!(output, "qlog");
+
+        // Nothing was written to the qlog directory.
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn qlog_socket_unreachable() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket_path = dir.path().join("missing.sock");
+
+        let writer = make_conn_qlog_writer(
+            dir.path().to_str(),
+            socket_path.to_str(),
+            QlogCompression::None,
+            "abc",
+        );
+        assert!(writer.is_some());
+        assert!(dir
+            .path()
+            .join(qlog_file_name("abc", QlogCompression::None))
+            .exists());
+
+        // Without a directory to fall back to, there is no qlog.
+        assert!(make_conn_qlog_writer(
+            None,
+            socket_path.to_str(),
+            QlogCompression::None,
+            "abc",
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn qlog_socket_uses_async_writer() {
+        let settings = QuicSettings {
+            qlog_socket: Some("/tmp/qlog.sock".to_string()),
+            ..Default::default()
+        };
+        let params =
+            ConnectionParams::new_client(settings, None, Hooks::default());
+        let config = Config::new(&params, SocketCapabilities::default()).unwrap();
+
+        let (pool, async_config) = config.qlog_async_writer.unwrap();
+        assert_eq!(
+            async_config,
+            qlog::async_writer::AsyncWriterConfig::default()
+        );
+
+        // Connections share the writer pool, rather than each spawning its
+        // own threads.
+        let other = Config::new(&params, SocketCapabilities::default()).unwrap();
+        assert!(other.qlog_async_writer.unwrap().0.ptr_eq(&pool));
+
+        let params = ConnectionParams::new_client(
+            QuicSettings::default(),
+            None,
+            Hooks::default(),
+        );
+        let config = Config::new(&params, SocketCapabilities::default()).unwrap();
+
+        assert!(config.qlog_async_writer.is_none());
+    }
+}
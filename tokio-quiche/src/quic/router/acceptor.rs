@@ -32,8 +32,9 @@ use std::time::Instant;
 use datagram_socket::DatagramSocketSend;
 use datagram_socket::DatagramSocketSendExt;
 use datagram_socket::MAX_DATAGRAM_SIZE;
-use qlog::writer::make_qlog_writer_from_path;
-use qlog::writer::qlog_file_name;
+use qlog::async_writer::AsyncWriterConfig;
+use qlog::async_writer::QlogWriterPool;
+use qlog::filter::EventFilter;
 use qlog::writer::QlogCompression;
 use quiche::ConnectionId;
 use quiche::Header;
@@ -45,6 +46,7 @@ use crate::metrics::labels;
 use crate::metrics::Metrics;
 use crate::quic::addr_validation_token::AddrValidationTokenManager;
 use crate::quic::connection::SharedConnectionIdGenerator;
+use crate::quic::make_conn_qlog_writer;
 use crate::quic::router::NewConnection;
 use crate::quic::Incoming;
 use crate::QuicResultExt;
@@ -64,7 +66,10 @@ pub(crate) struct ConnectionAcceptor<S, M> {
 pub(crate) struct ConnectionAcceptorConfig {
     pub(crate) disable_client_ip_validation: bool,
     pub(crate) qlog_dir: Option<String>,
+    pub(crate) qlog_socket: Option<String>,
     pub(crate) qlog_compression: QlogCompression,
+    pub(crate) qlog_async_writer: Option<(QlogWriterPool, AsyncWriterConfig)>,
+    pub(crate) qlog_filter: EventFilter,
     pub(crate) keylog_file: Option<File>,
     #[cfg(target_os = "linux")]
     pub(crate) with_pktinfo: bool,
@@ -115,18 +120,26 @@ where
         }
         .into_io()?;
 
-        if let Some(qlog_dir) = &self.config.qlog_dir {
-            let id = format!("{:?}", scid);
-            let path = std::path::Path::new(qlog_dir)
-                .join(qlog_file_name(&id, self.config.qlog_compression));
-            if let Ok(writer) =
-                make_qlog_writer_from_path(&path, self.config.qlog_compression)
-            {
-                conn.set_qlog(
-                    writer,
-                    "tokio-quiche qlog".to_string(),
-                    format!("tokio-quiche qlog id={id}"),
-                );
+        let id = format!("{:?}", scid);
+        if let Some(writer) = make_conn_qlog_writer(
+            self.config.qlog_dir.as_deref(),
+            self.config.qlog_socket.as_deref(),
+            self.config.qlog_compression,
+            &id,
+        ) {
+            conn.set_qlog(
+                writer,
+                "tokio-quiche qlog".to_string(),
+                format!("tokio-quiche qlog id={id}"),
+            );
+
+            if let Some(streamer) = conn.qlog_streamer() {
+                // The filter was validated when loading the settings.
+                let _ = streamer.set_filter(&self.config.qlog_filter);
+
+                if let Some((pool, config)) = &self.config.qlog_async_writer {
+                    let _ = streamer.set_async_writer(pool, config.clone());
+                }
             }
         }
 
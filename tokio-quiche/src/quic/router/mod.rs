@@ -965,7 +965,10 @@ mod tests {
             ConnectionAcceptorConfig {
                 disable_client_ip_validation: config.disable_client_ip_validation,
                 qlog_dir: config.qlog_dir.clone(),
+                qlog_socket: config.qlog_socket.clone(),
                 qlog_compression: config.qlog_compression,
+                qlog_async_writer: None,
+                qlog_filter: Default::default(),
                 keylog_file: config
                     .keylog_file
                     .as_ref()
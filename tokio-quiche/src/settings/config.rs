@@ -27,8 +27,12 @@
 use foundations::telemetry::log;
 use std::borrow::Cow;
 use std::fs::File;
+use std::sync::OnceLock;
 use std::time::Duration;
 
+use qlog::async_writer::AsyncWriterConfig;
+use qlog::async_writer::QlogWriterPool;
+use qlog::filter::EventFilter;
 use qlog::writer::QlogCompression;
 
 use crate::result::QuicResult;
@@ -47,7 +51,10 @@ pub(crate) struct Config {
     pub quiche_config: quiche::Config,
     pub disable_client_ip_validation: bool,
     pub qlog_dir: Option<String>,
+    pub qlog_socket: Option<String>,
     pub qlog_compression: QlogCompression,
+    pub qlog_async_writer: Option<(QlogWriterPool, AsyncWriterConfig)>,
+    pub qlog_filter: EventFilter,
     pub has_gso: bool,
     pub pacing_offload: bool,
     pub enable_expensive_packet_count_metrics: bool,
@@ -93,6 +100,20 @@ impl Config {
             ..
         } = socket_capabilities;
 
+        quic_settings.qlog_filter.validate()?;
+
+        // Writes to the qlog socket block until the reader catches up, so they
+        // are always moved off the IO workers.
+        let qlog_async_writer = match &quic_settings.qlog_async_writer {
+            Some(config)
+                if quic_settings.qlog_dir.is_some() ||
+                    quic_settings.qlog_socket.is_some() =>
+                Some((qlog_writer_pool()?, config.clone())),
+            None if quic_settings.qlog_socket.is_some() =>
+                Some((qlog_writer_pool()?, AsyncWriterConfig::default())),
+            _ => None,
+        };
+
         #[cfg(feature = "gcongestion")]
         let pacing_offload = quic_settings.enable_pacing && pacing_offload;
 
@@ -101,7 +122,10 @@ impl Config {
             disable_client_ip_validation: quic_settings
                 .disable_client_ip_validation,
             qlog_dir: quic_settings.qlog_dir.clone(),
+            qlog_socket: quic_settings.qlog_socket.clone(),
             qlog_compression: quic_settings.qlog_compression,
+            qlog_async_writer,
+            qlog_filter: quic_settings.qlog_filter.clone(),
             has_gso,
             pacing_offload,
             enable_expensive_packet_count_metrics: quic_settings
@@ -116,6 +140,22 @@ impl Config {
     }
 }
 
+/// Returns the qlog writer pool shared by all connections, spawning it on
+/// first use.
+fn qlog_writer_pool() -> std::io::Result<QlogWriterPool> {
+    static POOL: OnceLock<QlogWriterPool> = OnceLock::new();
+
+    if let Some(pool) = POOL.get() {
+        return Ok(pool.clone());
+    }
+
+    // If several connections race to spawn the pool, the losers' pools are
+    // dropped and their threads exit.
+    let pool = QlogWriterPool::new()?;
+
+    Ok(POOL.get_or_init(|| pool).clone())
+}
+
 fn make_quiche_config(
     params: &ConnectionParams, should_log_keys: bool,
 ) -> QuicResult<quiche::Config> {
@@ -29,6 +29,9 @@ use serde_with::serde_as;
 use serde_with::DurationMilliSeconds;
 use std::time::Duration;
 
+pub use qlog::async_writer::AsyncWriterConfig as QlogAsyncWriterConfig;
+pub use qlog::async_writer::OverflowPolicy as QlogOverflowPolicy;
+pub use qlog::filter::EventFilter as QlogEventFilter;
 pub use qlog::writer::QlogCompression;
 
 /// QUIC configuration parameters.
@@ -154,6 +157,20 @@ pub struct QuicSettings {
     /// Path to a directory where QLOG files will be saved.
     pub qlog_dir: Option<String>,
 
+    /// Path to a UNIX socket that QLOG output is streamed to instead of
+    /// being saved to `qlog_dir`, e.g. for live analysis with
+    /// `qlog-dancer live --socket`.
+    ///
+    /// Each connection opens its own stream on the socket. Output is never
+    /// compressed. Since writes block until the reader catches up, output is
+    /// always written from a background thread, using the default
+    /// `qlog_async_writer` configuration if none is set. Connections fall
+    /// back to `qlog_dir` if the socket can't be connected to.
+    ///
+    /// Defaults to `None`. Only supported on UNIX platforms.
+    #[serde(default)]
+    pub qlog_socket: Option<String>,
+
     /// Compression applied to QLOG output files.
     ///
     /// Defaults to [`QlogCompression::None`], preserving the historical
@@ -164,6 +181,30 @@ pub struct QuicSettings {
     #[serde(default)]
     pub qlog_compression: QlogCompression,
 
+    /// Write QLOG output from a background thread instead of the IO worker.
+    ///
+    /// All connections share a small pool of writer threads. Records are
+    /// buffered per connection up to the configured capacity, and the
+    /// overflow policy decides what to drop once it is full. The number of
+    /// dropped events is logged in the trace.
+    ///
+    /// Defaults to `None`, which writes QLOG output synchronously.
+    #[serde(default)]
+    pub qlog_async_writer: Option<QlogAsyncWriterConfig>,
+
+    /// Per-event-type filtering and sampling of QLOG output.
+    ///
+    /// Event types can be enabled regardless of the QLOG level, disabled, or
+    /// sampled so that only one in every N events is written, e.g. to sample
+    /// `quic:packet_sent`. Unknown event names are rejected.
+    ///
+    /// Defaults to no filtering, which keeps the [`ALWAYS_ON`] events such as
+    /// `quic:connection_closed` and `quic:packet_lost` at every QLOG level.
+    ///
+    /// [`ALWAYS_ON`]: qlog::filter::ALWAYS_ON
+    #[serde(default)]
+    pub qlog_filter: QlogEventFilter,
+
     /// Congestion control algorithm to use.
     ///
     /// For available values, see